@@ -2,16 +2,9 @@ fn main() {
     use std::io::Write;
 
     fn gen_pthread_mutex(out_file: &str) -> std::io::Result<()> {
-        // TODO: generate size and initial content automatically.
-        let (mutex_size, mutex_init) = if cfg!(feature = "multitask") {
-            if cfg!(feature = "smp") {
-                (6, "{0, 0, 8, 0, 0, 0}") // core::mem::transmute::<_, [usize; 6]>(axsync::Mutex::new(()))
-            } else {
-                (5, "{0, 8, 0, 0, 0}") // core::mem::transmute::<_, [usize; 5]>(axsync::Mutex::new(()))
-            }
-        } else {
-            (1, "{0}")
-        };
+        // The mutex state is allocated on first use and its address kept in
+        // the first word, see `src/imp/pthread/mutex.rs`.
+        let (mutex_size, mutex_init) = (1, "{0}");
 
         let mut output = Vec::new();
         writeln!(
@@ -48,6 +41,14 @@ typedef struct {{
             "pthread_attr_t",
             "pthread_mutex_t",
             "pthread_mutexattr_t",
+            "pthread_cond_t",
+            "pthread_condattr_t",
+            "pthread_rwlock_t",
+            "pthread_rwlockattr_t",
+            "pthread_barrier_t",
+            "pthread_barrierattr_t",
+            "pthread_key_t",
+            "pthread_once_t",
             "epoll_event",
             "iovec",
             "clockid_t",
@@ -68,6 +69,7 @@ typedef struct {{
             "RLIMIT_.*",
             "EAI_.*",
             "MAXADDRS",
+            "PTHREAD_.*",
         ];
 
         #[derive(Debug)]
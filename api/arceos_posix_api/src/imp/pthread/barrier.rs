@@ -0,0 +1,100 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::LinuxError;
+use axtask::WaitQueue;
+use spin::Mutex;
+
+use core::ffi::{c_int, c_uint};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::{lazy_state, take_state};
+
+/// The state of a `pthread_barrier_t`, allocated by `pthread_barrier_init`.
+struct PthreadBarrier {
+    count: u32,
+    /// Threads that have arrived in the current round.
+    arrived: Mutex<u32>,
+    /// Bumped each time the barrier opens.
+    generation: AtomicU32,
+    wq: WaitQueue,
+}
+
+impl PthreadBarrier {
+    const fn new(count: u32) -> Self {
+        Self {
+            count,
+            arrived: Mutex::new(0),
+            generation: AtomicU32::new(0),
+            wq: WaitQueue::new(),
+        }
+    }
+
+    /// Returns `true` for the thread that opens the barrier.
+    fn wait(&self) -> bool {
+        let mut arrived = self.arrived.lock();
+        *arrived += 1;
+        let generation = self.generation.load(Ordering::Acquire);
+        if *arrived == self.count {
+            *arrived = 0;
+            self.generation.fetch_add(1, Ordering::Release);
+            drop(arrived);
+            self.wq.notify_all(true);
+            return true;
+        }
+        drop(arrived);
+        self.wq
+            .wait_until(|| self.generation.load(Ordering::Acquire) != generation);
+        false
+    }
+}
+
+/// Initialize a barrier that opens once `count` threads are waiting on it.
+pub fn sys_pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    _attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    debug!(
+        "sys_pthread_barrier_init <= {:#x}, {}",
+        barrier as usize, count
+    );
+    syscall_body!(sys_pthread_barrier_init, {
+        check_null_mut_ptr(barrier)?;
+        if count == 0 {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe { barrier.write(core::mem::zeroed()) };
+        lazy_state(barrier.cast(), || PthreadBarrier::new(count));
+        Ok(0)
+    })
+}
+
+/// Destroy a barrier.
+pub fn sys_pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    debug!("sys_pthread_barrier_destroy <= {:#x}", barrier as usize);
+    syscall_body!(sys_pthread_barrier_destroy, {
+        check_null_mut_ptr(barrier)?;
+        drop(take_state::<PthreadBarrier>(barrier.cast()));
+        Ok(0)
+    })
+}
+
+/// Wait until enough threads have reached the barrier.
+///
+/// Returns `PTHREAD_BARRIER_SERIAL_THREAD` to exactly one of the threads
+/// and 0 to the others.
+pub fn sys_pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    debug!("sys_pthread_barrier_wait <= {:#x}", barrier as usize);
+    syscall_body!(sys_pthread_barrier_wait, {
+        check_null_mut_ptr(barrier)?;
+        let state = unsafe { *barrier.cast::<*const PthreadBarrier>() };
+        if state.is_null() {
+            return Err(LinuxError::EINVAL);
+        }
+        if unsafe { (*state).wait() } {
+            Ok(ctypes::PTHREAD_BARRIER_SERIAL_THREAD)
+        } else {
+            Ok(0)
+        }
+    })
+}
@@ -0,0 +1,136 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axtask::WaitQueue;
+
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::time::Duration;
+
+use super::mutex::PthreadMutex;
+use super::{abs_deadline, lazy_state, take_state, wait_deadline};
+
+/// The state of a `pthread_cond_t`, allocated on first use.
+struct PthreadCond {
+    /// Bumped by every signal or broadcast, so that waiters can tell whether
+    /// they have been woken for a reason.
+    seq: AtomicU32,
+    wq: WaitQueue,
+}
+
+impl PthreadCond {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicU32::new(0),
+            wq: WaitQueue::new(),
+        }
+    }
+
+    fn get(cond: *mut ctypes::pthread_cond_t) -> &'static Self {
+        lazy_state(cond.cast(), Self::new)
+    }
+
+    fn wait(&self, mutex: &PthreadMutex, deadline: Option<Duration>) -> LinuxResult {
+        let seq = self.seq.load(Ordering::Acquire);
+        mutex.unlock()?;
+        let timeout = wait_deadline(&self.wq, deadline, || {
+            self.seq.load(Ordering::Acquire) != seq
+        });
+        mutex.lock()?;
+        if timeout {
+            Err(LinuxError::ETIMEDOUT)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn signal(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        self.wq.notify_one(true);
+    }
+
+    fn broadcast(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        self.wq.notify_all(true);
+    }
+}
+
+/// Initialize a condition variable.
+pub fn sys_pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    _attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    debug!("sys_pthread_cond_init <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_init, {
+        check_null_mut_ptr(cond)?;
+        unsafe { cond.write(core::mem::zeroed()) };
+        Ok(0)
+    })
+}
+
+/// Destroy a condition variable.
+pub fn sys_pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_destroy <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_destroy, {
+        check_null_mut_ptr(cond)?;
+        drop(take_state::<PthreadCond>(cond.cast()));
+        Ok(0)
+    })
+}
+
+/// Release `mutex` and wait on the condition variable, then lock `mutex`
+/// again.
+pub fn sys_pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_wait <= {:#x}, {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_wait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        PthreadCond::get(cond).wait(PthreadMutex::get(mutex), None)?;
+        Ok(0)
+    })
+}
+
+/// Like [`sys_pthread_cond_wait`], but gives up at the absolute time `abstime`.
+pub fn sys_pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!(
+        "sys_pthread_cond_timedwait <= {:#x}, {:#x}",
+        cond as usize, mutex as usize
+    );
+    syscall_body!(sys_pthread_cond_timedwait, {
+        check_null_mut_ptr(cond)?;
+        check_null_mut_ptr(mutex)?;
+        let deadline = abs_deadline(abstime)?;
+        PthreadCond::get(cond).wait(PthreadMutex::get(mutex), Some(deadline))?;
+        Ok(0)
+    })
+}
+
+/// Wake up one thread waiting on the condition variable.
+pub fn sys_pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_signal <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_signal, {
+        check_null_mut_ptr(cond)?;
+        PthreadCond::get(cond).signal();
+        Ok(0)
+    })
+}
+
+/// Wake up all threads waiting on the condition variable.
+pub fn sys_pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    debug!("sys_pthread_cond_broadcast <= {:#x}", cond as usize);
+    syscall_body!(sys_pthread_cond_broadcast, {
+        check_null_mut_ptr(cond)?;
+        PthreadCond::get(cond).broadcast();
+        Ok(0)
+    })
+}
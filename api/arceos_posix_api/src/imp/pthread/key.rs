@@ -0,0 +1,138 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::LinuxError;
+use spin::Mutex;
+
+use core::ffi::{c_int, c_void};
+use core::ptr::null_mut;
+
+use super::Pthread;
+
+/// Maximum number of thread-specific data keys.
+pub const PTHREAD_KEYS_MAX: usize = 128;
+/// How many times destructors are retried while values are still being set.
+const PTHREAD_DESTRUCTOR_ITERATIONS: usize = 4;
+
+type Destructor = Option<extern "C" fn(*mut c_void)>;
+
+/// A key slot. `generation` is bumped each time the slot is allocated, so that
+/// values bound to a deleted key are not seen through a new key at the same
+/// index.
+#[derive(Clone, Copy)]
+struct KeySlot {
+    /// The destructor of the allocated key, `None` for a free slot.
+    dtor: Option<Destructor>,
+    generation: usize,
+}
+
+/// A value bound to a key in a thread, with the generation of the key.
+#[derive(Clone, Copy)]
+pub(super) struct Specific {
+    generation: usize,
+    value: *mut c_void,
+}
+
+impl Specific {
+    /// No value bound.
+    pub(super) const NULL: Self = Self {
+        generation: 0,
+        value: null_mut(),
+    };
+}
+
+/// All key slots, indexed by `pthread_key_t`.
+static KEYS: Mutex<[KeySlot; PTHREAD_KEYS_MAX]> = Mutex::new(
+    [KeySlot {
+        dtor: None,
+        generation: 0,
+    }; PTHREAD_KEYS_MAX],
+);
+
+/// Index and generation of the allocated `key`.
+fn key_slot(key: ctypes::pthread_key_t) -> Option<(usize, usize)> {
+    let key = key as usize;
+    let slot = KEYS.lock().get(key).copied()?;
+    slot.dtor.map(|_| (key, slot.generation))
+}
+
+/// Calls the destructors of the non-null values in `tsd`, as a thread exits.
+pub(super) fn run_destructors(tsd: &mut [Specific; PTHREAD_KEYS_MAX]) {
+    for _ in 0..PTHREAD_DESTRUCTOR_ITERATIONS {
+        let mut called = false;
+        for (key, specific) in tsd.iter_mut().enumerate() {
+            if specific.value.is_null() {
+                continue;
+            }
+            let slot = KEYS.lock()[key];
+            let Some(Some(dtor)) = slot.dtor.filter(|_| slot.generation == specific.generation)
+            else {
+                continue;
+            };
+            let arg = core::mem::replace(&mut specific.value, null_mut());
+            dtor(arg);
+            called = true;
+        }
+        if !called {
+            break;
+        }
+    }
+}
+
+/// Create a thread-specific data key, visible to all threads.
+pub fn sys_pthread_key_create(key: *mut ctypes::pthread_key_t, destructor: Destructor) -> c_int {
+    debug!("sys_pthread_key_create <= {:#x}", key as usize);
+    syscall_body!(sys_pthread_key_create, {
+        check_null_mut_ptr(key)?;
+        let mut keys = KEYS.lock();
+        let index = keys
+            .iter()
+            .position(|slot| slot.dtor.is_none())
+            .ok_or(LinuxError::EAGAIN)?;
+        keys[index].dtor = Some(destructor);
+        keys[index].generation += 1;
+        unsafe { key.write(index as _) };
+        Ok(0)
+    })
+}
+
+/// Delete a thread-specific data key.
+///
+/// The values bound to it are not destructed, and are not seen through keys
+/// created later.
+pub fn sys_pthread_key_delete(key: ctypes::pthread_key_t) -> c_int {
+    debug!("sys_pthread_key_delete <= {}", key);
+    syscall_body!(sys_pthread_key_delete, {
+        let (index, _) = key_slot(key).ok_or(LinuxError::EINVAL)?;
+        KEYS.lock()[index].dtor = None;
+        Ok(0)
+    })
+}
+
+/// Returns the value bound to `key` in the current thread, or null.
+pub fn sys_pthread_getspecific(key: ctypes::pthread_key_t) -> *mut c_void {
+    let (Some((index, generation)), Some(thread)) = (key_slot(key), Pthread::current()) else {
+        return null_mut();
+    };
+    let specific = unsafe { (*thread.tsd.get())[index] };
+    if specific.generation == generation {
+        specific.value
+    } else {
+        null_mut()
+    }
+}
+
+/// Bind `value` to `key` in the current thread.
+pub fn sys_pthread_setspecific(key: ctypes::pthread_key_t, value: *const c_void) -> c_int {
+    debug!("sys_pthread_setspecific <= {}, {:#x}", key, value as usize);
+    syscall_body!(sys_pthread_setspecific, {
+        let (index, generation) = key_slot(key).ok_or(LinuxError::EINVAL)?;
+        let thread = Pthread::current().ok_or(LinuxError::EINVAL)?;
+        unsafe {
+            (*thread.tsd.get())[index] = Specific {
+                generation,
+                value: value as *mut c_void,
+            }
+        };
+        Ok(0)
+    })
+}
@@ -1,32 +1,55 @@
 use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
 use core::cell::UnsafeCell;
 use core::ffi::{c_int, c_void};
+use core::mem::size_of;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicI32, AtomicPtr, AtomicU8, Ordering};
+use core::time::Duration;
 
 use axerrno::{LinuxError, LinuxResult};
-use axtask::AxTaskRef;
+use axtask::{AxTaskRef, WaitQueue};
 use spin::RwLock;
 
 use crate::ctypes;
+use crate::utils::{check_null_mut_ptr, check_null_ptr};
 
+pub mod barrier;
+pub mod cond;
+pub mod key;
 pub mod mutex;
+pub mod rwlock;
+
+#[cfg(test)]
+mod tests;
 
 lazy_static::lazy_static! {
     static ref TID_TO_PTHREAD: RwLock<BTreeMap<u64, ForceSendSync<ctypes::pthread_t>>> = {
         let mut map = BTreeMap::new();
         let main_task = axtask::current();
         let main_tid = main_task.id().as_u64();
-        let main_thread = Pthread {
-            inner: main_task.as_task_ref().clone(),
-            retval: Arc::new(Packet {
-                result: UnsafeCell::new(core::ptr::null_mut()),
+        let main_thread = Pthread::new(
+            main_task.as_task_ref().clone(),
+            Arc::new(Packet {
+                result: UnsafeCell::new(null_mut()),
             }),
-        };
+            DT_JOINABLE,
+        );
         let ptr = Box::into_raw(Box::new(main_thread)) as *mut c_void;
         map.insert(main_tid, ForceSendSync(ptr));
         RwLock::new(map)
     };
 }
 
+/// Nobody has joined or detached the thread yet.
+const DT_JOINABLE: u8 = 0;
+/// The thread is detached and releases itself when it exits.
+const DT_DETACHED: u8 = 1;
+/// The thread has exited and is waiting to be joined.
+const DT_EXITED: u8 = 2;
+
+/// Minimum stack size accepted by `pthread_attr_setstacksize`.
+const PTHREAD_STACK_MIN: usize = 16384;
+
 struct Packet<T> {
     result: UnsafeCell<T>,
 }
@@ -37,18 +60,45 @@ unsafe impl<T> Sync for Packet<T> {}
 pub struct Pthread {
     inner: AxTaskRef,
     retval: Arc<Packet<*mut c_void>>,
+    detach_state: AtomicU8,
+    /// Thread-specific data, indexed by `pthread_key_t`.
+    tsd: UnsafeCell<[key::Specific; key::PTHREAD_KEYS_MAX]>,
 }
 
 impl Pthread {
+    fn new(inner: AxTaskRef, retval: Arc<Packet<*mut c_void>>, detach_state: u8) -> Self {
+        Self {
+            inner,
+            retval,
+            detach_state: AtomicU8::new(detach_state),
+            tsd: UnsafeCell::new([key::Specific::NULL; key::PTHREAD_KEYS_MAX]),
+        }
+    }
+
     fn create(
-        _attr: *const ctypes::pthread_attr_t,
+        attr: *const ctypes::pthread_attr_t,
         start_routine: extern "C" fn(arg: *mut c_void) -> *mut c_void,
         arg: *mut c_void,
     ) -> LinuxResult<ctypes::pthread_t> {
+        let attr = if attr.is_null() {
+            PthreadAttr::default()
+        } else {
+            unsafe { attr.cast::<PthreadAttr>().read() }
+        };
+        let detach_state = if attr.detach_state == ctypes::PTHREAD_CREATE_DETACHED as c_int {
+            DT_DETACHED
+        } else {
+            DT_JOINABLE
+        };
+        // An attributes object that was zeroed instead of initialized.
+        let stack_size = match attr.stack_size {
+            0 => axconfig::TASK_STACK_SIZE,
+            size => size,
+        };
         let arg_wrapper = ForceSendSync(arg);
 
         let my_packet: Arc<Packet<*mut c_void>> = Arc::new(Packet {
-            result: UnsafeCell::new(core::ptr::null_mut()),
+            result: UnsafeCell::new(null_mut()),
         });
         let their_packet = my_packet.clone();
 
@@ -57,16 +107,17 @@ impl Pthread {
             let ret = start_routine(arg.0);
             unsafe { *their_packet.result.get() = ret };
             drop(their_packet);
+            Self::finish_current();
         };
 
-        let task_inner = axtask::spawn(main);
+        // Hold the map until the new thread is registered, so that it can
+        // always find itself, even if it runs before we return.
+        let mut map = TID_TO_PTHREAD.write();
+        let task_inner = axtask::spawn_raw(main, "".into(), stack_size);
         let tid = task_inner.id().as_u64();
-        let thread = Pthread {
-            inner: task_inner,
-            retval: my_packet,
-        };
+        let thread = Pthread::new(task_inner, my_packet, detach_state);
         let ptr = Box::into_raw(Box::new(thread)) as *mut c_void;
-        TID_TO_PTHREAD.write().insert(tid, ForceSendSync(ptr));
+        map.insert(tid, ForceSendSync(ptr));
         Ok(ptr)
     }
 
@@ -85,13 +136,33 @@ impl Pthread {
     fn exit_current(retval: *mut c_void) -> ! {
         let thread = Self::current().expect("fail to get current thread");
         unsafe { *thread.retval.result.get() = retval };
+        Self::finish_current();
         axtask::exit(0);
     }
 
+    /// Runs the thread-specific data destructors of the current thread, and
+    /// releases its `struct __pthread` if it has been detached.
+    fn finish_current() {
+        let Some(thread) = Self::current() else {
+            return;
+        };
+        key::run_destructors(unsafe { &mut *thread.tsd.get() });
+        if thread.detach_state.swap(DT_EXITED, Ordering::AcqRel) == DT_DETACHED {
+            let tid = thread.inner.id().as_u64();
+            if let Some(ptr) = TID_TO_PTHREAD.write().remove(&tid) {
+                drop(unsafe { Box::from_raw(ptr.0 as *mut Pthread) });
+            }
+        }
+    }
+
     fn join(ptr: ctypes::pthread_t) -> LinuxResult<*mut c_void> {
         if core::ptr::eq(ptr, Self::current_ptr() as _) {
             return Err(LinuxError::EDEADLK);
         }
+        let state = unsafe { &(*(ptr as *const Pthread)).detach_state };
+        if state.load(Ordering::Acquire) == DT_DETACHED {
+            return Err(LinuxError::EINVAL);
+        }
 
         let thread = unsafe { Box::from_raw(ptr as *mut Pthread) };
         thread.inner.join();
@@ -101,6 +172,113 @@ impl Pthread {
         drop(thread);
         Ok(retval)
     }
+
+    fn detach(ptr: ctypes::pthread_t) -> LinuxResult {
+        let state = unsafe { &(*(ptr as *const Pthread)).detach_state };
+        match state.compare_exchange(
+            DT_JOINABLE,
+            DT_DETACHED,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => Ok(()),
+            // Already exited: nobody else will reap it.
+            Err(DT_EXITED) => Self::join(ptr).map(|_| ()),
+            Err(_) => Err(LinuxError::EINVAL),
+        }
+    }
+}
+
+static_assertions::const_assert!(size_of::<PthreadAttr>() <= size_of::<ctypes::pthread_attr_t>());
+
+/// The fields of `pthread_attr_t` that we support, in the same layout as the
+/// `_a_*` accessors in `pthread.h`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct PthreadAttr {
+    stack_size: usize,
+    _guard_size: usize,
+    _stack_addr: usize,
+    detach_state: c_int,
+}
+
+impl Default for PthreadAttr {
+    fn default() -> Self {
+        Self {
+            stack_size: axconfig::TASK_STACK_SIZE,
+            _guard_size: 0,
+            _stack_addr: 0,
+            detach_state: ctypes::PTHREAD_CREATE_JOINABLE as c_int,
+        }
+    }
+}
+
+/// Returns the state object whose address is kept in the first word of a
+/// `pthread_*_t`, allocating it on first use so that zeroed static
+/// initializers work.
+fn lazy_state<T>(obj: *mut c_void, init: impl FnOnce() -> T) -> &'static T {
+    let slot = unsafe { AtomicPtr::<T>::from_ptr(obj.cast()) };
+    let mut ptr = slot.load(Ordering::Acquire);
+    if ptr.is_null() {
+        let new = Box::into_raw(Box::new(init()));
+        match slot.compare_exchange(null_mut(), new, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => ptr = new,
+            Err(cur) => {
+                drop(unsafe { Box::from_raw(new) });
+                ptr = cur;
+            }
+        }
+    }
+    unsafe { &*ptr }
+}
+
+/// Detaches the state object installed by [`lazy_state`], if any.
+fn take_state<T>(obj: *mut c_void) -> Option<Box<T>> {
+    let slot = unsafe { AtomicPtr::<T>::from_ptr(obj.cast()) };
+    let ptr = slot.swap(null_mut(), Ordering::AcqRel);
+    (!ptr.is_null()).then(|| unsafe { Box::from_raw(ptr) })
+}
+
+/// Converts an absolute `CLOCK_REALTIME` timeout to a deadline.
+fn abs_deadline(abstime: *const ctypes::timespec) -> LinuxResult<Duration> {
+    check_null_ptr(abstime)?;
+    let ts = unsafe { *abstime };
+    if ts.tv_nsec < 0 || ts.tv_nsec >= 1_000_000_000 {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(ts.into())
+}
+
+/// Blocks on `wq` until `condition` returns true, or until `deadline` (on the
+/// wall clock) has passed.
+///
+/// Returns `true` if it has timed out.
+fn wait_deadline<F>(wq: &WaitQueue, deadline: Option<Duration>, condition: F) -> bool
+where
+    F: Fn() -> bool,
+{
+    let Some(deadline) = deadline else {
+        wq.wait_until(condition);
+        return false;
+    };
+    #[cfg(feature = "irq")]
+    {
+        let now = axhal::time::wall_time();
+        if now >= deadline {
+            return !condition();
+        }
+        return wq.wait_timeout_until(deadline - now, condition);
+    }
+    #[cfg(not(feature = "irq"))]
+    loop {
+        if condition() {
+            return false;
+        }
+        if axhal::time::wall_time() >= deadline {
+            return true;
+        }
+        axtask::yield_now();
+    }
 }
 
 /// Returns the `pthread` struct of current thread.
@@ -147,6 +325,132 @@ pub unsafe fn sys_pthread_join(thread: ctypes::pthread_t, retval: *mut *mut c_vo
     })
 }
 
+/// Detaches the given thread, so that its resources are released as soon as
+/// it exits.
+pub fn sys_pthread_detach(thread: ctypes::pthread_t) -> c_int {
+    debug!("sys_pthread_detach <= {:#x}", thread as usize);
+    syscall_body!(sys_pthread_detach, {
+        Pthread::detach(thread)?;
+        Ok(0)
+    })
+}
+
+/// Threads waiting for the `init_routine` of some `pthread_once_t` to return.
+///
+/// A `pthread_once_t` is a single `int`, so all of them share this queue.
+static ONCE_WQ: WaitQueue = WaitQueue::new();
+
+/// Calls `init_routine` exactly once for the given `once_control`.
+pub fn sys_pthread_once(
+    once_control: *mut ctypes::pthread_once_t,
+    init_routine: extern "C" fn(),
+) -> c_int {
+    debug!("sys_pthread_once <= {:#x}", once_control as usize);
+    syscall_body!(sys_pthread_once, {
+        const INCOMPLETE: c_int = 0;
+        const RUNNING: c_int = 1;
+        const COMPLETE: c_int = 2;
+        check_null_mut_ptr(once_control)?;
+        let state = unsafe { AtomicI32::from_ptr(once_control) };
+        match state.compare_exchange(INCOMPLETE, RUNNING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {
+                init_routine();
+                state.store(COMPLETE, Ordering::Release);
+                ONCE_WQ.notify_all(true);
+            }
+            Err(_) => ONCE_WQ.wait_until(|| state.load(Ordering::Acquire) == COMPLETE),
+        }
+        Ok(0)
+    })
+}
+
+/// Initializes a thread attributes object with the default attributes.
+pub fn sys_pthread_attr_init(attr: *mut ctypes::pthread_attr_t) -> c_int {
+    debug!("sys_pthread_attr_init <= {:#x}", attr as usize);
+    syscall_body!(sys_pthread_attr_init, {
+        check_null_mut_ptr(attr)?;
+        unsafe {
+            attr.write(core::mem::zeroed());
+            attr.cast::<PthreadAttr>().write(PthreadAttr::default());
+        }
+        Ok(0)
+    })
+}
+
+/// Destroys a thread attributes object.
+pub fn sys_pthread_attr_destroy(attr: *mut ctypes::pthread_attr_t) -> c_int {
+    debug!("sys_pthread_attr_destroy <= {:#x}", attr as usize);
+    syscall_body!(sys_pthread_attr_destroy, {
+        check_null_mut_ptr(attr)?;
+        Ok(0)
+    })
+}
+
+/// Gets the stack size attribute.
+pub fn sys_pthread_attr_getstacksize(
+    attr: *const ctypes::pthread_attr_t,
+    stacksize: *mut usize,
+) -> c_int {
+    debug!("sys_pthread_attr_getstacksize <= {:#x}", attr as usize);
+    syscall_body!(sys_pthread_attr_getstacksize, {
+        check_null_ptr(attr)?;
+        check_null_mut_ptr(stacksize)?;
+        unsafe { *stacksize = (*attr.cast::<PthreadAttr>()).stack_size };
+        Ok(0)
+    })
+}
+
+/// Sets the stack size of threads created with the attributes object.
+pub fn sys_pthread_attr_setstacksize(attr: *mut ctypes::pthread_attr_t, stacksize: usize) -> c_int {
+    debug!(
+        "sys_pthread_attr_setstacksize <= {:#x}, {:#x}",
+        attr as usize, stacksize
+    );
+    syscall_body!(sys_pthread_attr_setstacksize, {
+        check_null_mut_ptr(attr)?;
+        if stacksize < PTHREAD_STACK_MIN {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe { (*attr.cast::<PthreadAttr>()).stack_size = stacksize };
+        Ok(0)
+    })
+}
+
+/// Gets the detach state attribute.
+pub fn sys_pthread_attr_getdetachstate(
+    attr: *const ctypes::pthread_attr_t,
+    detachstate: *mut c_int,
+) -> c_int {
+    debug!("sys_pthread_attr_getdetachstate <= {:#x}", attr as usize);
+    syscall_body!(sys_pthread_attr_getdetachstate, {
+        check_null_ptr(attr)?;
+        check_null_mut_ptr(detachstate)?;
+        unsafe { *detachstate = (*attr.cast::<PthreadAttr>()).detach_state };
+        Ok(0)
+    })
+}
+
+/// Sets whether threads created with the attributes object start detached.
+pub fn sys_pthread_attr_setdetachstate(
+    attr: *mut ctypes::pthread_attr_t,
+    detachstate: c_int,
+) -> c_int {
+    debug!(
+        "sys_pthread_attr_setdetachstate <= {:#x}, {}",
+        attr as usize, detachstate
+    );
+    syscall_body!(sys_pthread_attr_setdetachstate, {
+        check_null_mut_ptr(attr)?;
+        if detachstate != ctypes::PTHREAD_CREATE_JOINABLE as c_int
+            && detachstate != ctypes::PTHREAD_CREATE_DETACHED as c_int
+        {
+            return Err(LinuxError::EINVAL);
+        }
+        unsafe { (*attr.cast::<PthreadAttr>()).detach_state = detachstate };
+        Ok(0)
+    })
+}
+
 #[derive(Clone, Copy)]
 struct ForceSendSync<T>(T);
 
@@ -1,33 +1,87 @@
 use crate::{ctypes, utils::check_null_mut_ptr};
 
-use axerrno::LinuxResult;
-use axsync::Mutex;
+use axerrno::{LinuxError, LinuxResult};
+use axtask::{WaitQueue, current};
 
 use core::ffi::c_int;
-use core::mem::{ManuallyDrop, size_of};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
 
-static_assertions::const_assert_eq!(
-    size_of::<ctypes::pthread_mutex_t>(),
-    size_of::<PthreadMutex>()
-);
+use super::{abs_deadline, lazy_state, take_state, wait_deadline};
 
-#[repr(C)]
-pub struct PthreadMutex(Mutex<()>);
+/// The state of a `pthread_mutex_t`, allocated on first use, so that a
+/// zeroed `PTHREAD_MUTEX_INITIALIZER` works.
+///
+/// It is a sleeping mutex whose waiters can give up at a deadline.
+pub struct PthreadMutex {
+    wq: WaitQueue,
+    /// ID of the task holding the mutex, `0` if it is unlocked.
+    owner_id: AtomicU64,
+}
 
 impl PthreadMutex {
     const fn new() -> Self {
-        Self(Mutex::new(()))
+        Self {
+            wq: WaitQueue::new(),
+            owner_id: AtomicU64::new(0),
+        }
     }
 
-    fn lock(&self) -> LinuxResult {
-        let _guard = ManuallyDrop::new(self.0.lock());
-        Ok(())
+    pub(super) fn get(mutex: *mut ctypes::pthread_mutex_t) -> &'static Self {
+        lazy_state(mutex.cast(), Self::new)
+    }
+
+    fn try_acquire(&self) -> bool {
+        let current_id = current().id().as_u64();
+        self.owner_id
+            .compare_exchange(0, current_id, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    /// Locks the mutex, giving up at `deadline` on the wall clock.
+    fn lock_until(&self, deadline: Option<Duration>) -> LinuxResult {
+        if self.owner_id.load(Ordering::Relaxed) == current().id().as_u64() {
+            return Err(LinuxError::EDEADLK);
+        }
+        if self.try_acquire() || !wait_deadline(&self.wq, deadline, || self.try_acquire()) {
+            return Ok(());
+        }
+        // The wakeup of an unlock may have chosen this waiter as it timed out,
+        // pass it on to the next one.
+        if !self.is_locked() {
+            self.wq.notify_one(true);
+        }
+        Err(LinuxError::ETIMEDOUT)
     }
 
-    fn unlock(&self) -> LinuxResult {
-        unsafe { self.0.force_unlock() };
+    pub(super) fn lock(&self) -> LinuxResult {
+        self.lock_until(None)
+    }
+
+    fn try_lock(&self) -> LinuxResult {
+        if self.try_acquire() {
+            Ok(())
+        } else {
+            Err(LinuxError::EBUSY)
+        }
+    }
+
+    pub(super) fn unlock(&self) -> LinuxResult {
+        let current_id = current().id().as_u64();
+        if self
+            .owner_id
+            .compare_exchange(current_id, 0, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(LinuxError::EPERM);
+        }
+        self.wq.notify_one(true);
         Ok(())
     }
+
+    fn is_locked(&self) -> bool {
+        self.owner_id.load(Ordering::Relaxed) != 0
+    }
 }
 
 /// Initialize a mutex.
@@ -38,9 +92,7 @@ pub fn sys_pthread_mutex_init(
     debug!("sys_pthread_mutex_init <= {:#x}", mutex as usize);
     syscall_body!(sys_pthread_mutex_init, {
         check_null_mut_ptr(mutex)?;
-        unsafe {
-            mutex.cast::<PthreadMutex>().write(PthreadMutex::new());
-        }
+        unsafe { mutex.write(core::mem::zeroed()) };
         Ok(0)
     })
 }
@@ -50,9 +102,7 @@ pub fn sys_pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     debug!("sys_pthread_mutex_lock <= {:#x}", mutex as usize);
     syscall_body!(sys_pthread_mutex_lock, {
         check_null_mut_ptr(mutex)?;
-        unsafe {
-            (*mutex.cast::<PthreadMutex>()).lock()?;
-        }
+        PthreadMutex::get(mutex).lock()?;
         Ok(0)
     })
 }
@@ -62,9 +112,47 @@ pub fn sys_pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     debug!("sys_pthread_mutex_unlock <= {:#x}", mutex as usize);
     syscall_body!(sys_pthread_mutex_unlock, {
         check_null_mut_ptr(mutex)?;
-        unsafe {
-            (*mutex.cast::<PthreadMutex>()).unlock()?;
+        PthreadMutex::get(mutex).unlock()?;
+        Ok(0)
+    })
+}
+
+/// Lock the given mutex if it is not held by another thread.
+pub fn sys_pthread_mutex_trylock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    debug!("sys_pthread_mutex_trylock <= {:#x}", mutex as usize);
+    syscall_body!(sys_pthread_mutex_trylock, {
+        check_null_mut_ptr(mutex)?;
+        PthreadMutex::get(mutex).try_lock()?;
+        Ok(0)
+    })
+}
+
+/// Lock the given mutex, giving up at the absolute time `abstime`.
+pub fn sys_pthread_mutex_timedlock(
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!("sys_pthread_mutex_timedlock <= {:#x}", mutex as usize);
+    syscall_body!(sys_pthread_mutex_timedlock, {
+        check_null_mut_ptr(mutex)?;
+        let mutex = PthreadMutex::get(mutex);
+        if mutex.try_lock().is_ok() {
+            return Ok(0);
+        }
+        mutex.lock_until(Some(abs_deadline(abstime)?))?;
+        Ok(0)
+    })
+}
+
+/// Destroy the given mutex, which must not be locked.
+pub fn sys_pthread_mutex_destroy(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    debug!("sys_pthread_mutex_destroy <= {:#x}", mutex as usize);
+    syscall_body!(sys_pthread_mutex_destroy, {
+        check_null_mut_ptr(mutex)?;
+        if PthreadMutex::get(mutex).is_locked() {
+            return Err(LinuxError::EBUSY);
         }
+        drop(take_state::<PthreadMutex>(mutex.cast()));
         Ok(0)
     })
 }
@@ -0,0 +1,194 @@
+use crate::{ctypes, utils::check_null_mut_ptr};
+
+use axerrno::{LinuxError, LinuxResult};
+use axtask::WaitQueue;
+
+use core::ffi::c_int;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+
+use super::{abs_deadline, lazy_state, take_state, wait_deadline};
+
+/// Set in `state` while a writer holds the lock; otherwise `state` counts the
+/// readers.
+const WRITER: usize = usize::MAX;
+
+/// The state of a `pthread_rwlock_t`, allocated on first use.
+struct PthreadRwLock {
+    state: AtomicUsize,
+    wq: WaitQueue,
+}
+
+impl PthreadRwLock {
+    const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(0),
+            wq: WaitQueue::new(),
+        }
+    }
+
+    fn get(rwlock: *mut ctypes::pthread_rwlock_t) -> &'static Self {
+        lazy_state(rwlock.cast(), Self::new)
+    }
+
+    fn try_read(&self) -> bool {
+        let mut state = self.state.load(Ordering::Relaxed);
+        while state < WRITER - 1 {
+            match self.state.compare_exchange_weak(
+                state,
+                state + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(s) => state = s,
+            }
+        }
+        false
+    }
+
+    fn try_write(&self) -> bool {
+        self.state
+            .compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    fn read(&self, deadline: Option<Duration>) -> LinuxResult {
+        if self.try_read() || !wait_deadline(&self.wq, deadline, || self.try_read()) {
+            Ok(())
+        } else {
+            Err(LinuxError::ETIMEDOUT)
+        }
+    }
+
+    fn write(&self, deadline: Option<Duration>) -> LinuxResult {
+        if self.try_write() || !wait_deadline(&self.wq, deadline, || self.try_write()) {
+            Ok(())
+        } else {
+            Err(LinuxError::ETIMEDOUT)
+        }
+    }
+
+    fn unlock(&self) -> LinuxResult {
+        let state = self.state.load(Ordering::Relaxed);
+        if state == 0 {
+            return Err(LinuxError::EPERM);
+        }
+        if state == WRITER {
+            self.state.store(0, Ordering::Release);
+        } else if self.state.fetch_sub(1, Ordering::Release) != 1 {
+            // Other readers remain, nobody can make progress yet.
+            return Ok(());
+        }
+        self.wq.notify_all(true);
+        Ok(())
+    }
+}
+
+/// Initialize a read-write lock.
+pub fn sys_pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    _attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    debug!("sys_pthread_rwlock_init <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_init, {
+        check_null_mut_ptr(rwlock)?;
+        unsafe { rwlock.write(core::mem::zeroed()) };
+        Ok(0)
+    })
+}
+
+/// Destroy a read-write lock, which must not be locked.
+pub fn sys_pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_destroy <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_destroy, {
+        check_null_mut_ptr(rwlock)?;
+        if PthreadRwLock::get(rwlock).state.load(Ordering::Acquire) != 0 {
+            return Err(LinuxError::EBUSY);
+        }
+        drop(take_state::<PthreadRwLock>(rwlock.cast()));
+        Ok(0)
+    })
+}
+
+/// Lock a read-write lock for reading.
+pub fn sys_pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_rdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_rdlock, {
+        check_null_mut_ptr(rwlock)?;
+        PthreadRwLock::get(rwlock).read(None)?;
+        Ok(0)
+    })
+}
+
+/// Lock a read-write lock for reading if no writer holds it.
+pub fn sys_pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_tryrdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_tryrdlock, {
+        check_null_mut_ptr(rwlock)?;
+        if !PthreadRwLock::get(rwlock).try_read() {
+            return Err(LinuxError::EBUSY);
+        }
+        Ok(0)
+    })
+}
+
+/// Lock a read-write lock for reading, giving up at the absolute time `abstime`.
+pub fn sys_pthread_rwlock_timedrdlock(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!("sys_pthread_rwlock_timedrdlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_timedrdlock, {
+        check_null_mut_ptr(rwlock)?;
+        let deadline = abs_deadline(abstime)?;
+        PthreadRwLock::get(rwlock).read(Some(deadline))?;
+        Ok(0)
+    })
+}
+
+/// Lock a read-write lock for writing.
+pub fn sys_pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_wrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_wrlock, {
+        check_null_mut_ptr(rwlock)?;
+        PthreadRwLock::get(rwlock).write(None)?;
+        Ok(0)
+    })
+}
+
+/// Lock a read-write lock for writing if nobody holds it.
+pub fn sys_pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_trywrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_trywrlock, {
+        check_null_mut_ptr(rwlock)?;
+        if !PthreadRwLock::get(rwlock).try_write() {
+            return Err(LinuxError::EBUSY);
+        }
+        Ok(0)
+    })
+}
+
+/// Lock a read-write lock for writing, giving up at the absolute time `abstime`.
+pub fn sys_pthread_rwlock_timedwrlock(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    debug!("sys_pthread_rwlock_timedwrlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_timedwrlock, {
+        check_null_mut_ptr(rwlock)?;
+        let deadline = abs_deadline(abstime)?;
+        PthreadRwLock::get(rwlock).write(Some(deadline))?;
+        Ok(0)
+    })
+}
+
+/// Unlock a read-write lock held for either reading or writing.
+pub fn sys_pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    debug!("sys_pthread_rwlock_unlock <= {:#x}", rwlock as usize);
+    syscall_body!(sys_pthread_rwlock_unlock, {
+        check_null_mut_ptr(rwlock)?;
+        PthreadRwLock::get(rwlock).unlock()?;
+        Ok(0)
+    })
+}
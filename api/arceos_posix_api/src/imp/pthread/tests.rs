@@ -0,0 +1,222 @@
+use core::ffi::c_void;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicI32, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+
+use super::barrier::{
+    sys_pthread_barrier_destroy, sys_pthread_barrier_init, sys_pthread_barrier_wait,
+};
+use super::key::{
+    sys_pthread_getspecific, sys_pthread_key_create, sys_pthread_key_delete,
+    sys_pthread_setspecific,
+};
+use super::mutex::{
+    sys_pthread_mutex_destroy, sys_pthread_mutex_lock, sys_pthread_mutex_trylock,
+    sys_pthread_mutex_unlock,
+};
+use super::{sys_pthread_create, sys_pthread_join, sys_pthread_once};
+use crate::ctypes;
+use axerrno::LinuxError;
+
+static INIT: Once = Once::new();
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn spawn(
+    start_routine: extern "C" fn(*mut c_void) -> *mut c_void,
+    arg: *mut c_void,
+) -> ctypes::pthread_t {
+    let mut thread: ctypes::pthread_t = null_mut();
+    assert_eq!(
+        unsafe { sys_pthread_create(&mut thread, core::ptr::null(), start_routine, arg) },
+        0
+    );
+    thread
+}
+
+fn join(thread: ctypes::pthread_t) -> *mut c_void {
+    let mut retval = null_mut();
+    assert_eq!(unsafe { sys_pthread_join(thread, &mut retval) }, 0);
+    retval
+}
+
+#[test]
+fn test_barrier() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_THREADS: usize = 5;
+    const ROUNDS: usize = 3;
+    static ARRIVED: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn worker(barrier: *mut c_void) -> *mut c_void {
+        let mut serial = 0usize;
+        for round in 0..ROUNDS {
+            ARRIVED.fetch_add(1, Ordering::Relaxed);
+            axtask::yield_now();
+            let ret = sys_pthread_barrier_wait(barrier.cast());
+            // nobody leaves before all have arrived in this round
+            assert!(ARRIVED.load(Ordering::Relaxed) >= (round + 1) * (NUM_THREADS + 1));
+            match ret {
+                ctypes::PTHREAD_BARRIER_SERIAL_THREAD => serial += 1,
+                0 => {}
+                ret => panic!("barrier_wait returned {}", ret),
+            }
+            // the next round does not start until the serial thread is back
+            sys_pthread_barrier_wait(barrier.cast());
+        }
+        serial as *mut c_void
+    }
+
+    let mut barrier: ctypes::pthread_barrier_t = unsafe { core::mem::zeroed() };
+    let count = NUM_THREADS as u32 + 1;
+    assert_eq!(
+        sys_pthread_barrier_init(&mut barrier, core::ptr::null(), count),
+        0
+    );
+    let arg = &mut barrier as *mut ctypes::pthread_barrier_t as *mut c_void;
+    let threads: Vec<_> = (0..NUM_THREADS).map(|_| spawn(worker, arg)).collect();
+    let serial =
+        worker(arg) as usize + threads.into_iter().map(|t| join(t) as usize).sum::<usize>();
+    println!("barrier: {} serial threads in {} rounds", serial, ROUNDS);
+    assert_eq!(serial, ROUNDS);
+    assert_eq!(sys_pthread_barrier_destroy(&mut barrier), 0);
+
+    assert_eq!(
+        sys_pthread_barrier_init(&mut barrier, core::ptr::null(), 0),
+        -LinuxError::EINVAL.code()
+    );
+}
+
+#[test]
+fn test_static_mutex() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_THREADS: usize = 5;
+    const ROUNDS: usize = 100;
+    // what `PTHREAD_MUTEX_INITIALIZER` leaves in the mutex
+    static mut MUTEX: ctypes::pthread_mutex_t = unsafe { core::mem::zeroed() };
+    static mut COUNTER: usize = 0;
+
+    extern "C" fn worker(_arg: *mut c_void) -> *mut c_void {
+        let mutex = &raw mut MUTEX;
+        for _ in 0..ROUNDS {
+            assert_eq!(sys_pthread_mutex_lock(mutex), 0);
+            let value = unsafe { COUNTER };
+            // let the others find it locked
+            axtask::yield_now();
+            unsafe { COUNTER = value + 1 };
+            assert_eq!(sys_pthread_mutex_unlock(mutex), 0);
+        }
+        null_mut()
+    }
+
+    let threads: Vec<_> = (0..NUM_THREADS)
+        .map(|_| spawn(worker, null_mut()))
+        .collect();
+    for thread in threads {
+        join(thread);
+    }
+    assert_eq!(unsafe { COUNTER }, NUM_THREADS * ROUNDS);
+
+    let mutex = &raw mut MUTEX;
+    assert_eq!(sys_pthread_mutex_trylock(mutex), 0);
+    assert_eq!(sys_pthread_mutex_destroy(mutex), -LinuxError::EBUSY.code());
+    assert_eq!(sys_pthread_mutex_unlock(mutex), 0);
+    assert_eq!(sys_pthread_mutex_destroy(mutex), 0);
+}
+
+#[test]
+fn test_once() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_THREADS: usize = 10;
+    static ONCE: AtomicI32 = AtomicI32::new(0);
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn init_routine() {
+        // let the other threads find it running
+        axtask::yield_now();
+        CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    extern "C" fn worker(_arg: *mut c_void) -> *mut c_void {
+        assert_eq!(sys_pthread_once(ONCE.as_ptr(), init_routine), 0);
+        // nobody returns before the routine has finished
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+        null_mut()
+    }
+
+    let threads: Vec<_> = (0..NUM_THREADS)
+        .map(|_| spawn(worker, null_mut()))
+        .collect();
+    for thread in threads {
+        join(thread);
+    }
+    worker(null_mut());
+    println!(
+        "once: init_routine called {} times",
+        CALLS.load(Ordering::Relaxed)
+    );
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_key_destructor() {
+    let _lock = SERIAL.lock();
+    INIT.call_once(axtask::init_scheduler);
+
+    const NUM_THREADS: usize = 5;
+    static DESTRUCTED: AtomicUsize = AtomicUsize::new(0);
+    static KEY: AtomicU32 = AtomicU32::new(0);
+
+    extern "C" fn destructor(value: *mut c_void) {
+        // called once with the value of each thread, which is already unbound
+        assert!(sys_pthread_getspecific(KEY.load(Ordering::Relaxed)).is_null());
+        DESTRUCTED.fetch_add(value as usize, Ordering::Relaxed);
+    }
+
+    extern "C" fn worker(arg: *mut c_void) -> *mut c_void {
+        let key = KEY.load(Ordering::Relaxed);
+        assert!(sys_pthread_getspecific(key).is_null());
+        assert_eq!(sys_pthread_setspecific(key, arg), 0);
+        axtask::yield_now();
+        // each thread sees its own value
+        assert_eq!(sys_pthread_getspecific(key), arg);
+        null_mut()
+    }
+
+    let mut key = 0;
+    assert_eq!(sys_pthread_key_create(&mut key, Some(destructor)), 0);
+    KEY.store(key, Ordering::Relaxed);
+    let threads: Vec<_> = (1..=NUM_THREADS)
+        .map(|i| spawn(worker, i as *mut c_void))
+        .collect();
+    for thread in threads {
+        join(thread);
+    }
+    // 1 + 2 + ... + NUM_THREADS
+    println!(
+        "key_destructor: sum of destructed values {}",
+        DESTRUCTED.load(Ordering::Relaxed)
+    );
+    assert_eq!(
+        DESTRUCTED.load(Ordering::Relaxed),
+        NUM_THREADS * (NUM_THREADS + 1) / 2
+    );
+    assert!(sys_pthread_getspecific(key).is_null());
+
+    // values bound to a deleted key are not destructed
+    extern "C" fn bind_and_delete(_arg: *mut c_void) -> *mut c_void {
+        let key = KEY.load(Ordering::Relaxed);
+        assert_eq!(sys_pthread_setspecific(key, 100 as *const c_void), 0);
+        assert_eq!(sys_pthread_key_delete(key), 0);
+        null_mut()
+    }
+    join(spawn(bind_and_delete, null_mut()));
+    assert_eq!(
+        DESTRUCTED.load(Ordering::Relaxed),
+        NUM_THREADS * (NUM_THREADS + 1) / 2
+    );
+}
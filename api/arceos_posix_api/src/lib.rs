@@ -53,8 +53,34 @@ pub use imp::net::{
 #[cfg(feature = "pipe")]
 pub use imp::pipe::sys_pipe;
 #[cfg(feature = "multitask")]
+pub use imp::pthread::barrier::{
+    sys_pthread_barrier_destroy, sys_pthread_barrier_init, sys_pthread_barrier_wait,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::cond::{
+    sys_pthread_cond_broadcast, sys_pthread_cond_destroy, sys_pthread_cond_init,
+    sys_pthread_cond_signal, sys_pthread_cond_timedwait, sys_pthread_cond_wait,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::key::{
+    sys_pthread_getspecific, sys_pthread_key_create, sys_pthread_key_delete,
+    sys_pthread_setspecific,
+};
+#[cfg(feature = "multitask")]
 pub use imp::pthread::mutex::{
-    sys_pthread_mutex_init, sys_pthread_mutex_lock, sys_pthread_mutex_unlock,
+    sys_pthread_mutex_destroy, sys_pthread_mutex_init, sys_pthread_mutex_lock,
+    sys_pthread_mutex_timedlock, sys_pthread_mutex_trylock, sys_pthread_mutex_unlock,
+};
+#[cfg(feature = "multitask")]
+pub use imp::pthread::rwlock::{
+    sys_pthread_rwlock_destroy, sys_pthread_rwlock_init, sys_pthread_rwlock_rdlock,
+    sys_pthread_rwlock_timedrdlock, sys_pthread_rwlock_timedwrlock, sys_pthread_rwlock_tryrdlock,
+    sys_pthread_rwlock_trywrlock, sys_pthread_rwlock_unlock, sys_pthread_rwlock_wrlock,
 };
 #[cfg(feature = "multitask")]
-pub use imp::pthread::{sys_pthread_create, sys_pthread_exit, sys_pthread_join, sys_pthread_self};
+pub use imp::pthread::{
+    sys_pthread_attr_destroy, sys_pthread_attr_getdetachstate, sys_pthread_attr_getstacksize,
+    sys_pthread_attr_init, sys_pthread_attr_setdetachstate, sys_pthread_attr_setstacksize,
+    sys_pthread_create, sys_pthread_detach, sys_pthread_exit, sys_pthread_join, sys_pthread_once,
+    sys_pthread_self,
+};
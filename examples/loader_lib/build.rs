@@ -0,0 +1,173 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+/// ABI 表的魔数，内存中依次为`ABIT`四个字节
+const ABI_MAGIC: u32 = u32::from_le_bytes(*b"ABIT");
+
+/// `table.def`中的一项
+struct AbiDef {
+    num: usize,
+    name: String,
+    handler: Option<String>,
+}
+
+fn parse_table(text: &str) -> (u32, Vec<AbiDef>) {
+    let mut version = None;
+    let mut defs: Vec<AbiDef> = Vec::new();
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            ["version", v] => version = Some(v.parse().expect("Invalid ABI table version")),
+            [num, name, handler] => {
+                let num: usize = num
+                    .parse()
+                    .unwrap_or_else(|_| panic!("table.def:{}: invalid number", lineno + 1));
+                if let Some(prev) = defs.iter().find(|d| d.num == num || d.name == name) {
+                    panic!(
+                        "table.def:{}: {} {} conflicts with {} {}",
+                        lineno + 1,
+                        num,
+                        name,
+                        prev.num,
+                        prev.name
+                    );
+                }
+                defs.push(AbiDef {
+                    num,
+                    name: name.into(),
+                    handler: (handler != "-").then(|| handler.into()),
+                });
+            }
+            _ => panic!("table.def:{}: malformed line", lineno + 1),
+        }
+    }
+    (version.expect("table.def: missing version"), defs)
+}
+
+fn gen_rust(version: u32, count: usize, defs: &[AbiDef]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// 由`build.rs`根据`src/abi/table.def`生成，不要手动修改"
+    )
+    .unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "/// ABI 表的魔数").unwrap();
+    writeln!(out, "pub const ABI_MAGIC: u32 = {:#x};", ABI_MAGIC).unwrap();
+    writeln!(out, "/// ABI 表的版本号").unwrap();
+    writeln!(out, "pub const ABI_VERSION: u32 = {};", version).unwrap();
+    writeln!(out, "/// ABI 表的项数").unwrap();
+    writeln!(out, "pub const ABI_COUNT: usize = {};", count).unwrap();
+    writeln!(out).unwrap();
+    for def in defs {
+        let name = def.name.to_uppercase();
+        writeln!(out, "pub const ABI_{}: usize = {};", name, def.num).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "/// 各项的名称，没有分配的编号为空串").unwrap();
+    writeln!(out, "pub const ABI_NAMES: [&str; ABI_COUNT] = [").unwrap();
+    for num in 0..count {
+        let name = defs.iter().find(|d| d.num == num).map_or("", |d| &d.name);
+        writeln!(out, "    {:?},", name).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "/// 每个编号默认指向的陷阱").unwrap();
+    writeln!(
+        out,
+        "pub const ABI_TRAPS: [extern \"C\" fn() -> !; ABI_COUNT] = ["
+    )
+    .unwrap();
+    for num in 0..count {
+        writeln!(out, "    abi_unbound::<{}>,", num).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+    let impls: Vec<&AbiDef> = defs.iter().filter(|d| d.handler.is_some()).collect();
+    writeln!(out, "/// 已实现的项：编号和实现函数的地址").unwrap();
+    writeln!(
+        out,
+        "pub fn abi_impls() -> [(usize, usize); {}] {{",
+        impls.len()
+    )
+    .unwrap();
+    writeln!(out, "    [").unwrap();
+    for def in impls {
+        let handler = def.handler.as_deref().unwrap();
+        writeln!(out, "        ({}, {} as usize),", def.num, handler).unwrap();
+    }
+    writeln!(out, "    ]").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn gen_c_header(version: u32, count: usize, defs: &[AbiDef]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// 由 examples/loader_lib/build.rs 根据 examples/loader_lib/src/abi/table.def 生成，不要手动修改"
+    )
+    .unwrap();
+    writeln!(out, "#ifndef _ABI_TABLE_H").unwrap();
+    writeln!(out, "#define _ABI_TABLE_H").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#define ABI_MAGIC   {:#x}", ABI_MAGIC).unwrap();
+    writeln!(out, "#define ABI_VERSION {}", version).unwrap();
+    writeln!(out, "#define ABI_COUNT   {}", count).unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "// 装载器传给程序入口的 ABI 表，表头之后是各项实现函数的地址"
+    )
+    .unwrap();
+    writeln!(out, "struct abi_table {{").unwrap();
+    writeln!(out, "    unsigned int magic;").unwrap();
+    writeln!(out, "    unsigned int version;").unwrap();
+    writeln!(out, "    unsigned long count;").unwrap();
+    writeln!(out, "    unsigned long entries[];").unwrap();
+    writeln!(out, "}};").unwrap();
+    writeln!(out).unwrap();
+    for def in defs {
+        let name = format!("ABI_{}", def.name.to_uppercase());
+        writeln!(out, "#define {:<26} {}", name, def.num).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "#endif").unwrap();
+    out
+}
+
+/// mocklibc 使用的头文件，随源码提交
+const C_HEADER: &str = "../../ulib/mocklibc_lib/include/abi_table.h";
+
+fn main() {
+    let table = "src/abi/table.def";
+    println!("cargo:rerun-if-changed={table}");
+    println!("cargo:rerun-if-changed={C_HEADER}");
+
+    let text = fs::read_to_string(table).expect("Failed to read ABI table");
+    let (version, defs) = parse_table(&text);
+    let count = defs.iter().map(|d| d.num + 1).max().unwrap_or(0);
+
+    let out_dir = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
+    fs::write(
+        out_dir.join("abi_table.rs"),
+        gen_rust(version, count, &defs),
+    )
+    .expect("Failed to write abi_table.rs");
+
+    // 构建过程不改动源码树，只检查提交的头文件是否与`table.def`一致
+    let header = gen_c_header(version, count, &defs);
+    let generated = out_dir.join("abi_table.h");
+    fs::write(&generated, &header).expect("Failed to write abi_table.h");
+    if fs::read_to_string(C_HEADER).ok().as_deref() != Some(header.as_str()) {
+        panic!(
+            "{} is out of date with {}, replace it with {}",
+            C_HEADER,
+            table,
+            generated.display()
+        );
+    }
+}
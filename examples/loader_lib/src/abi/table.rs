@@ -0,0 +1,6 @@
+//! ABI 编号、名称和实现函数，由`build.rs`根据`table.def`生成
+#![allow(dead_code)]
+
+use super::*;
+
+include!(concat!(env!("OUT_DIR"), "/abi_table.rs"));
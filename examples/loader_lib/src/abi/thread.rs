@@ -1,13 +1,61 @@
-use api::{
-    sys_pthread_create, sys_pthread_exit, sys_pthread_join, sys_pthread_mutex_init,
-    sys_pthread_mutex_lock, sys_pthread_mutex_unlock, sys_pthread_self,
-};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
 use arceos_posix_api::{self as api, ctypes};
 use axlog::{debug, info};
 use axstd::thread::sleep;
-use core::ffi::{c_int, c_void};
+use axsync::Mutex;
+use axtask::current;
+use core::ffi::{c_int, c_uint, c_void};
 use core::time::Duration;
 
+use crate::{
+    linux_env::process_ext::{
+        api::{current_process, leave_current},
+        process::Process,
+    },
+    tls::{alloc_tls_block, dealloc_tls_block},
+};
+
+/// `arceos_posix_api`以负数返回错误码，pthread 函数则直接返回正的错误码
+fn pthread_ret(ret: c_int) -> c_int {
+    if ret < 0 { -ret } else { ret }
+}
+
+/// 线程号到`abi_pthread_create`为它分配的 TLS 块，线程退出时释放
+static THREAD_TLS: Mutex<BTreeMap<u64, usize>> = Mutex::new(BTreeMap::new());
+
+/// 释放当前线程的 TLS 块，主线程的 TLS 块不在这里登记
+fn release_current_tls() {
+    if let Some(tp) = THREAD_TLS.lock().remove(&current().id().as_u64()) {
+        dealloc_tls_block(tp);
+    }
+}
+
+/// 传给新线程入口跳板的参数
+struct ThreadStart {
+    /// 新线程所属的进程，即创建者所在的进程
+    process: Arc<Process>,
+    start_routine: extern "C" fn(arg: *mut c_void) -> *mut c_void,
+    arg: *mut c_void,
+    /// 新线程独占的 TLS 块
+    tp: usize,
+}
+
+/// 新线程的实际入口：先加入创建者的进程并切换到自己的 TLS 块，再进入客户程序的
+/// `start_routine`。
+///
+/// 通过 `pthread_exit` 退出的线程不会回到这里，它的 TLS 块由`abi_pthread_exit`释放。
+extern "C" fn thread_trampoline(start: *mut c_void) -> *mut c_void {
+    let start = unsafe { Box::from_raw(start as *mut ThreadStart) };
+    start.process.attach_current();
+    THREAD_TLS.lock().insert(current().id().as_u64(), start.tp);
+    unsafe { axhal::arch::write_thread_pointer(start.tp) };
+    let ret = (start.start_routine)(start.arg);
+    drop(start);
+    leave_current(0);
+    release_current_tls();
+    ret
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn abi_pthread_create(
     res: *mut ctypes::pthread_t,
@@ -22,25 +70,40 @@ pub extern "C" fn abi_pthread_create(
     info!("start_routine: {:p}", start_routine);
     info!("arg: {:p}", arg);
 
-    unsafe { sys_pthread_create(res, attr, start_routine, arg) }
+    let start = Box::into_raw(Box::new(ThreadStart {
+        process: current_process(),
+        start_routine,
+        arg,
+        tp: alloc_tls_block(),
+    }));
+    let ret =
+        unsafe { api::sys_pthread_create(res, attr, thread_trampoline, start as *mut c_void) };
+    if ret != 0 {
+        let start = unsafe { Box::from_raw(start) };
+        dealloc_tls_block(start.tp);
+    }
+    pthread_ret(ret)
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn abi_pthread_join(thread: ctypes::pthread_t, retval: *mut *mut c_void) -> i32 {
     info!("[ABI:Thread] Wait for the given thread to exit!");
-    unsafe { sys_pthread_join(thread, retval) }
+    pthread_ret(unsafe { api::sys_pthread_join(thread, retval) })
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn abi_pthread_exit(retval: *mut c_void) -> ! {
     info!("[ABI:Thread] Exit the current thread!");
-    sys_pthread_exit(retval);
+    // 线程运行在自己的内核栈上，可以直接离开进程
+    leave_current(0);
+    release_current_tls();
+    api::sys_pthread_exit(retval);
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn abi_pthread_self() -> ctypes::pthread_t {
     info!("[ABI:Thread] Get the `pthread` struct of current thread!");
-    sys_pthread_self()
+    api::sys_pthread_self()
 }
 
 #[unsafe(no_mangle)]
@@ -50,27 +113,271 @@ pub extern "C" fn abi_sleep(seconds: u32) {
 }
 
 #[unsafe(no_mangle)]
-pub fn abi_pthread_mutex_init(
+pub extern "C" fn abi_pthread_detach(thread: ctypes::pthread_t) -> c_int {
+    info!("[ABI:Thread] Detach the given thread!");
+    pthread_ret(api::sys_pthread_detach(thread))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_once(
+    once_control: *mut ctypes::pthread_once_t,
+    init_routine: extern "C" fn(),
+) -> c_int {
+    info!("[ABI:Thread] Run the initialization routine once!");
+    pthread_ret(api::sys_pthread_once(once_control, init_routine))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_attr_init(attr: *mut ctypes::pthread_attr_t) -> c_int {
+    info!("[ABI:Thread] Initialize thread attributes!");
+    pthread_ret(api::sys_pthread_attr_init(attr))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_attr_destroy(attr: *mut ctypes::pthread_attr_t) -> c_int {
+    info!("[ABI:Thread] Destroy thread attributes!");
+    pthread_ret(api::sys_pthread_attr_destroy(attr))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_attr_getstacksize(
+    attr: *const ctypes::pthread_attr_t,
+    stacksize: *mut usize,
+) -> c_int {
+    info!("[ABI:Thread] Get the stack size attribute!");
+    pthread_ret(api::sys_pthread_attr_getstacksize(attr, stacksize))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_attr_setstacksize(
+    attr: *mut ctypes::pthread_attr_t,
+    stacksize: usize,
+) -> c_int {
+    info!("[ABI:Thread] Set the stack size attribute!");
+    pthread_ret(api::sys_pthread_attr_setstacksize(attr, stacksize))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_attr_getdetachstate(
+    attr: *const ctypes::pthread_attr_t,
+    detachstate: *mut c_int,
+) -> c_int {
+    info!("[ABI:Thread] Get the detach state attribute!");
+    pthread_ret(api::sys_pthread_attr_getdetachstate(attr, detachstate))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_attr_setdetachstate(
+    attr: *mut ctypes::pthread_attr_t,
+    detachstate: c_int,
+) -> c_int {
+    info!("[ABI:Thread] Set the detach state attribute!");
+    pthread_ret(api::sys_pthread_attr_setdetachstate(attr, detachstate))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_mutex_init(
     mutex: *mut ctypes::pthread_mutex_t,
-    _attr: *const ctypes::pthread_mutexattr_t,
+    attr: *const ctypes::pthread_mutexattr_t,
 ) -> c_int {
     info!("[ABI:Thread] Initialize a mutex!");
-    sys_pthread_mutex_init(mutex, _attr)
+    pthread_ret(api::sys_pthread_mutex_init(mutex, attr))
 }
 
 #[unsafe(no_mangle)]
-pub fn abi_pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+pub extern "C" fn abi_pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     info!("[ABI:Thread] Lock the given mutex!");
-    sys_pthread_mutex_lock(mutex)
+    pthread_ret(api::sys_pthread_mutex_lock(mutex))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_mutex_trylock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    info!("[ABI:Thread] Try to lock the given mutex!");
+    pthread_ret(api::sys_pthread_mutex_trylock(mutex))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_mutex_timedlock(
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    info!("[ABI:Thread] Lock the given mutex before a deadline!");
+    pthread_ret(api::sys_pthread_mutex_timedlock(mutex, abstime))
 }
 
 #[unsafe(no_mangle)]
-pub fn abi_pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+pub extern "C" fn abi_pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     info!("[ABI:Thread] Unlock the given mutex!");
-    sys_pthread_mutex_unlock(mutex)
+    pthread_ret(api::sys_pthread_mutex_unlock(mutex))
 }
 
 #[unsafe(no_mangle)]
-pub fn abi_pthread_mutex_destroy() {
+pub extern "C" fn abi_pthread_mutex_destroy(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     info!("[ABI:Thread] Destroy the given mutex!");
+    pthread_ret(api::sys_pthread_mutex_destroy(mutex))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    info!("[ABI:Thread] Initialize a condition variable!");
+    pthread_ret(api::sys_pthread_cond_init(cond, attr))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    info!("[ABI:Thread] Destroy the given condition variable!");
+    pthread_ret(api::sys_pthread_cond_destroy(cond))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    info!("[ABI:Thread] Wait on the given condition variable!");
+    pthread_ret(api::sys_pthread_cond_wait(cond, mutex))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    info!("[ABI:Thread] Wait on the given condition variable before a deadline!");
+    pthread_ret(api::sys_pthread_cond_timedwait(cond, mutex, abstime))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    info!("[ABI:Thread] Wake up one waiter of the given condition variable!");
+    pthread_ret(api::sys_pthread_cond_signal(cond))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    info!("[ABI:Thread] Wake up all waiters of the given condition variable!");
+    pthread_ret(api::sys_pthread_cond_broadcast(cond))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    info!("[ABI:Thread] Initialize a read-write lock!");
+    pthread_ret(api::sys_pthread_rwlock_init(rwlock, attr))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    info!("[ABI:Thread] Destroy the given read-write lock!");
+    pthread_ret(api::sys_pthread_rwlock_destroy(rwlock))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    info!("[ABI:Thread] Lock the given read-write lock for reading!");
+    pthread_ret(api::sys_pthread_rwlock_rdlock(rwlock))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    info!("[ABI:Thread] Try to lock the given read-write lock for reading!");
+    pthread_ret(api::sys_pthread_rwlock_tryrdlock(rwlock))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_timedrdlock(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    info!("[ABI:Thread] Lock the given read-write lock for reading before a deadline!");
+    pthread_ret(api::sys_pthread_rwlock_timedrdlock(rwlock, abstime))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    info!("[ABI:Thread] Lock the given read-write lock for writing!");
+    pthread_ret(api::sys_pthread_rwlock_wrlock(rwlock))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    info!("[ABI:Thread] Try to lock the given read-write lock for writing!");
+    pthread_ret(api::sys_pthread_rwlock_trywrlock(rwlock))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_timedwrlock(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    info!("[ABI:Thread] Lock the given read-write lock for writing before a deadline!");
+    pthread_ret(api::sys_pthread_rwlock_timedwrlock(rwlock, abstime))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    info!("[ABI:Thread] Unlock the given read-write lock!");
+    pthread_ret(api::sys_pthread_rwlock_unlock(rwlock))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    info!("[ABI:Thread] Initialize a barrier for {} threads!", count);
+    pthread_ret(api::sys_pthread_barrier_init(barrier, attr, count))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    info!("[ABI:Thread] Destroy the given barrier!");
+    pthread_ret(api::sys_pthread_barrier_destroy(barrier))
+}
+
+/// 恰好一个线程得到`PTHREAD_BARRIER_SERIAL_THREAD`，它不是错误码
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    info!("[ABI:Thread] Wait on the given barrier!");
+    match api::sys_pthread_barrier_wait(barrier) {
+        ctypes::PTHREAD_BARRIER_SERIAL_THREAD => ctypes::PTHREAD_BARRIER_SERIAL_THREAD,
+        ret => pthread_ret(ret),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_key_create(
+    key: *mut ctypes::pthread_key_t,
+    destructor: Option<extern "C" fn(*mut c_void)>,
+) -> c_int {
+    info!("[ABI:Thread] Create a thread-specific data key!");
+    pthread_ret(api::sys_pthread_key_create(key, destructor))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_key_delete(key: ctypes::pthread_key_t) -> c_int {
+    info!("[ABI:Thread] Delete the given thread-specific data key!");
+    pthread_ret(api::sys_pthread_key_delete(key))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_getspecific(key: ctypes::pthread_key_t) -> *mut c_void {
+    debug!("[ABI:Thread] Get the thread-specific data of key {}", key);
+    api::sys_pthread_getspecific(key)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn abi_pthread_setspecific(
+    key: ctypes::pthread_key_t,
+    value: *const c_void,
+) -> c_int {
+    debug!("[ABI:Thread] Set the thread-specific data of key {}", key);
+    pthread_ret(api::sys_pthread_setspecific(key, value))
 }
@@ -0,0 +1,188 @@
+//! aarch64：Arm 64-bit ELF psABI
+
+use core::arch::naked_asm;
+
+use elf::abi::EM_AARCH64;
+
+use super::RelocKind;
+use crate::abi::ABI_TABLE;
+
+/// ELF 文件头中的`e_machine`
+pub const EM_ARCH: u16 = EM_AARCH64;
+/// 体系结构名称，用于日志
+pub const ARCH_NAME: &str = "aarch64";
+
+/// `bin`所在闪存的物理地址（QEMU virt 的第二块 pflash，`apps.bin`要补齐到 64 MiB）
+pub const PAYLOAD_PADDR: Option<usize> = Some(0x0400_0000);
+/// 静态链接的客户程序的链接地址：内核镜像之后 4 MiB
+pub const APP_START: usize = 0xffff_0000_4048_0000;
+
+// 重定位类型，编号见 psABI
+const R_AARCH64_NONE: u32 = 0;
+const R_AARCH64_ABS64: u32 = 257;
+const R_AARCH64_ABS32: u32 = 258;
+const R_AARCH64_COPY: u32 = 1024;
+const R_AARCH64_GLOB_DAT: u32 = 1025;
+const R_AARCH64_JUMP_SLOT: u32 = 1026;
+const R_AARCH64_RELATIVE: u32 = 1027;
+const R_AARCH64_TLS_DTPMOD: u32 = 1028;
+const R_AARCH64_TLS_DTPREL: u32 = 1029;
+const R_AARCH64_TLS_TPREL: u32 = 1030;
+const R_AARCH64_IRELATIVE: u32 = 1032;
+
+/// TLS variant I：`tp`（`TPIDR_EL0`）指向线程控制块，TLS 块在它之上
+pub const TLS_ABOVE_TP: bool = true;
+/// `tp`与第一个 TLS 块之间为线程控制块保留的 16 字节
+pub const TLS_GAP_ABOVE_TP: usize = 16;
+/// `tp`处线程控制块的大小，只用于 variant II
+pub const TLS_TCB_SIZE: usize = 0;
+/// `DTPREL`就是相对 TLS 块起始处的偏移
+pub const TLS_DTV_OFFSET: usize = 0;
+
+/// 还没有`svc`的系统调用入口，不支持以用户态运行程序
+pub const USER_MODE: bool = false;
+/// 信号处理函数返回用的跳板：`mov x8, 139`（`rt_sigreturn`）和`svc 0`
+pub const SIGRETURN_CODE: &[u8] = &[0x68, 0x11, 0x80, 0xd2, 0x01, 0x00, 0x00, 0xd4];
+
+/// 不支持`R_AARCH64_TLSDESC`：它需要装载器提供描述符的解析函数
+pub fn reloc_kind(r_type: u32) -> Option<RelocKind> {
+    Some(match r_type {
+        R_AARCH64_NONE => RelocKind::None,
+        R_AARCH64_ABS32 => RelocKind::Abs32,
+        // GOT 和 PLT 表项同样是`S + A`
+        R_AARCH64_ABS64 | R_AARCH64_GLOB_DAT | R_AARCH64_JUMP_SLOT => RelocKind::Abs64,
+        R_AARCH64_RELATIVE => RelocKind::Relative,
+        R_AARCH64_COPY => RelocKind::Copy,
+        R_AARCH64_TLS_DTPMOD => RelocKind::TlsModule,
+        R_AARCH64_TLS_DTPREL => RelocKind::TlsDtpOffset,
+        R_AARCH64_TLS_TPREL => RelocKind::TlsTpOffset,
+        R_AARCH64_IRELATIVE => RelocKind::IRelative,
+        _ => return None,
+    })
+}
+
+/// 调用号本来就是分发用的通用调用号（asm-generic），不需要换算
+pub fn generic_syscall(id: usize, args: [usize; 6]) -> Option<(usize, [usize; 6])> {
+    Some((id, args))
+}
+
+/// 以`tp`为线程指针、`sp`为栈顶调用客户程序的入口`entry`，程序返回后才返回
+///
+/// `x0`同样指向`argc`，兼容`_start(long *p)`形式的入口；`x1`是 ABI 表的地址。
+pub unsafe fn run_entry(entry: usize, sp: usize, tp: usize) {
+    let kernel_tp = axhal::arch::read_thread_pointer();
+    unsafe {
+        axhal::arch::write_thread_pointer(tp);
+        core::arch::asm!("
+            // 切换到用户栈(x10)
+            mov     x20, sp
+            mov     sp, x10
+            mov     x0, sp
+            adrp    x1, {abi_table}
+            add     x1, x1, :lo12:{abi_table}
+            blr     x9
+            mov     sp, x20",
+            abi_table = sym ABI_TABLE,
+            in("x9") entry,
+            in("x10") sp,
+            out("x20") _,
+            clobber_abi("C"),
+        );
+        axhal::arch::write_thread_pointer(kernel_tp);
+    }
+}
+
+/// 当前的栈指针
+#[inline(always)]
+pub fn stack_pointer() -> usize {
+    let sp: usize;
+    unsafe { core::arch::asm!("mov {}, sp", out(reg) sp) };
+    sp
+}
+
+/// 让刚写入的代码对取指可见
+pub fn flush_icache() {
+    axhal::arch::flush_icache_all();
+}
+
+/// 在当前栈上保存`x19`～`x30`，切换到`stack_top`调用`f(arg, ctx)`，其中`ctx`
+/// 是保存位置。之后回到原来的栈并返回`f`的返回值。
+///
+/// # Safety
+///
+/// `stack_top`必须是一个足够大的空闲栈的顶端，按 16 字节对齐。
+#[naked]
+pub unsafe extern "C" fn fork_snapshot(
+    _arg: usize,
+    _f: extern "C" fn(usize, usize) -> isize,
+    _stack_top: usize,
+) -> isize {
+    unsafe {
+        naked_asm!(
+            "
+            sub     sp, sp, 96
+            stp     x29, x30, [sp]
+            stp     x19, x20, [sp, 16]
+            stp     x21, x22, [sp, 32]
+            stp     x23, x24, [sp, 48]
+            stp     x25, x26, [sp, 64]
+            stp     x27, x28, [sp, 80]
+
+            mov     x9, x1
+            mov     x1, sp
+            mov     x19, sp
+            mov     sp, x2
+            blr     x9
+            mov     sp, x19
+
+            ldp     x19, x20, [sp, 16]
+            ldp     x29, x30, [sp]
+            add     sp, sp, 96
+            ret",
+        )
+    }
+}
+
+/// 从[`fork_snapshot`]保存的`ctx`恢复寄存器，让`fork_snapshot`返回 0
+///
+/// # Safety
+///
+/// `ctx`必须在当前地址空间中有效，且保存它的调用帧仍然存在。
+#[naked]
+pub unsafe extern "C" fn fork_return(_ctx: usize) -> ! {
+    unsafe {
+        naked_asm!(
+            "
+            mov     sp, x0
+            ldp     x19, x20, [sp, 16]
+            ldp     x21, x22, [sp, 32]
+            ldp     x23, x24, [sp, 48]
+            ldp     x25, x26, [sp, 64]
+            ldp     x27, x28, [sp, 80]
+            ldp     x29, x30, [sp]
+            add     sp, sp, 96
+            mov     x0, 0
+            ret",
+        )
+    }
+}
+
+/// 切换到`stack_top`并调用`f(arg)`，不再返回
+///
+/// # Safety
+///
+/// `stack_top`必须是一个空闲栈的顶端，按 16 字节对齐。
+#[naked]
+pub unsafe extern "C" fn call_on_stack(
+    _arg: usize,
+    _f: extern "C" fn(usize) -> !,
+    _stack_top: usize,
+) -> ! {
+    unsafe {
+        naked_asm!(
+            "
+            mov     sp, x2
+            br      x1",
+        )
+    }
+}
@@ -0,0 +1,46 @@
+//! 与体系结构相关的部分：ELF 机器类型、进入客户程序的约定、系统调用号、重定位类型、
+//! TLS 布局，以及`fork`和切换栈用到的汇编
+//!
+//! 支持 riscv64、x86_64 和 aarch64，其余代码只通过这里的接口使用它们。以用户态运行
+//! 没有链接 mocklibc 的普通 Linux 程序目前只支持 riscv64。
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64;
+#[cfg(target_arch = "riscv64")]
+mod riscv64;
+#[cfg(target_arch = "x86_64")]
+mod x86_64;
+
+#[cfg(target_arch = "aarch64")]
+pub use aarch64::*;
+#[cfg(target_arch = "riscv64")]
+pub use riscv64::*;
+#[cfg(target_arch = "x86_64")]
+pub use x86_64::*;
+
+/// 动态重定位的计算方式，由各体系结构的重定位类型换算而来
+///
+/// `A`为加数，`B`为对象的装载基址，`S`为符号的值，`TLSMODULE`为符号所在对象的 TLS
+/// 模块编号，`TLSOFFSET`为该模块的 TLS 块相对`tp`的偏移。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelocKind {
+    None,
+    /// 32 位的`S + A`
+    Abs32,
+    /// `S + A`
+    Abs64,
+    /// `B + A`
+    Relative,
+    /// 把符号的内容从定义它的对象复制过来，只出现在主程序中
+    Copy,
+    /// `S`，用于 GOT 和 PLT 表项
+    Symbol,
+    /// `TLSMODULE`
+    TlsModule,
+    /// `S + A - TLS_DTV_OFFSET`
+    TlsDtpOffset,
+    /// `S + A + TLSOFFSET`
+    TlsTpOffset,
+    /// `ifunc_resolver(B + A)`
+    IRelative,
+}
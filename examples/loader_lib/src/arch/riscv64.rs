@@ -0,0 +1,243 @@
+//! riscv64：RISC-V Linux psABI
+
+use core::arch::naked_asm;
+
+use elf::abi::{
+    EM_RISCV, R_RISCV_32, R_RISCV_64, R_RISCV_COPY, R_RISCV_JUMP_SLOT, R_RISCV_NONE,
+    R_RISCV_RELATIVE, R_RISCV_TLS_DTPMOD64, R_RISCV_TLS_DTPREL64, R_RISCV_TLS_TPREL64,
+};
+
+use super::RelocKind;
+use crate::abi::ABI_TABLE;
+
+/// ELF 文件头中的`e_machine`
+pub const EM_ARCH: u16 = EM_RISCV;
+/// 体系结构名称，用于日志
+pub const ARCH_NAME: &str = "riscv64";
+
+/// `bin`所在闪存的物理地址（QEMU virt 的第二块 pflash）
+pub const PAYLOAD_PADDR: Option<usize> = Some(0x2200_0000);
+/// 静态链接的客户程序的链接地址：内核镜像之后 4 MiB
+pub const APP_START: usize = 0xffff_ffc0_8060_0000;
+
+/// `R_RISCV_IRELATIVE`，`elf` crate 的常量表中没有收录
+const R_RISCV_IRELATIVE: u32 = 58;
+
+/// TLS variant I：`tp`指向第一个 TLS 块
+pub const TLS_ABOVE_TP: bool = true;
+/// `tp`与第一个 TLS 块之间的空隙
+pub const TLS_GAP_ABOVE_TP: usize = 0;
+/// `tp`处线程控制块的大小，只用于 variant II
+pub const TLS_TCB_SIZE: usize = 0;
+/// RISC-V psABI 规定 `DTPREL` 相对 TLS 块起始处偏移 0x800，便于 12 位立即数寻址
+pub const TLS_DTV_OFFSET: usize = 0x800;
+
+/// 支持以用户态运行没有链接 mocklibc 的普通 Linux 程序
+pub const USER_MODE: bool = true;
+/// 用户态程序的信号处理函数返回到这里：`li a7, 139`（`rt_sigreturn`）和`ecall`
+pub const SIGRETURN_CODE: &[u8] = &[0x93, 0x08, 0xb0, 0x08, 0x73, 0x00, 0x00, 0x00];
+
+pub fn reloc_kind(r_type: u32) -> Option<RelocKind> {
+    Some(match r_type {
+        R_RISCV_NONE => RelocKind::None,
+        R_RISCV_32 => RelocKind::Abs32,
+        R_RISCV_64 => RelocKind::Abs64,
+        R_RISCV_RELATIVE => RelocKind::Relative,
+        R_RISCV_COPY => RelocKind::Copy,
+        R_RISCV_JUMP_SLOT => RelocKind::Symbol,
+        R_RISCV_TLS_DTPMOD64 => RelocKind::TlsModule,
+        R_RISCV_TLS_DTPREL64 => RelocKind::TlsDtpOffset,
+        R_RISCV_TLS_TPREL64 => RelocKind::TlsTpOffset,
+        R_RISCV_IRELATIVE => RelocKind::IRelative,
+        _ => return None,
+    })
+}
+
+/// 调用号本来就是分发用的通用调用号（asm-generic），不需要换算
+pub fn generic_syscall(id: usize, args: [usize; 6]) -> Option<(usize, [usize; 6])> {
+    Some((id, args))
+}
+
+/// 以`tp`为线程指针、`sp`为栈顶调用客户程序的入口`entry`，程序返回后才返回
+///
+/// `a0`同样指向`argc`，兼容`_start(long *p)`形式的入口；`a7`是 ABI 表的地址。
+pub unsafe fn run_entry(entry: usize, sp: usize, tp: usize) {
+    unsafe {
+        core::arch::asm!("
+            // 保存更多上下文信息
+            addi    sp, sp, -144 // 增加栈空间以存储额外的寄存器
+            // 保存CPU相关的寄存器
+            sd      tp, 0(sp)       // 保存CPU_ID
+            mv      tp, {tp}        // 切换到主线程的TLS块
+            csrr    t0, sstatus     // 保存系统状态
+            sd      t0, 8(sp)
+
+            // 保存通用寄存器
+            sd      ra, 16(sp)
+            sd      a7, 24(sp)
+            sd      a6, 32(sp)
+            sd      a5, 40(sp)
+            sd      a4, 48(sp)
+            sd      a3, 56(sp)
+            sd      a2, 64(sp)
+            sd      a1, 72(sp)
+            sd      a0, 80(sp)
+            sd      t6, 88(sp)
+            sd      t5, 96(sp)
+            sd      t4, 104(sp)
+            sd      t3, 112(sp)
+            sd      t2, 120(sp)
+            sd      t1, 128(sp)
+            sd      t0, 136(sp)
+
+            // 切换到用户栈(t4)，`a0`同样指向`argc`，兼容`_start(long *p)`形式的入口(t3)
+            mv      s2, sp
+            mv      sp, t4
+            mv      a0, sp
+            la      a7, {abi_table}
+            jalr    t3
+            mv      sp, s2
+
+            // 恢复所有寄存器
+            ld      t0, 0(sp)       // 恢复CPU ID
+            mv      tp, t0
+            ld      t0, 8(sp)       // 恢复系统状态
+            csrw    sstatus, t0
+
+            ld      ra, 16(sp)
+            ld      a7, 24(sp)
+            ld      a6, 32(sp)
+            ld      a5, 40(sp)
+            ld      a4, 48(sp)
+            ld      a3, 56(sp)
+            ld      a2, 64(sp)
+            ld      a1, 72(sp)
+            ld      a0, 80(sp)
+            ld      t6, 88(sp)
+            ld      t5, 96(sp)
+            ld      t4, 104(sp)
+            ld      t3, 112(sp)
+            ld      t2, 120(sp)
+            ld      t1, 128(sp)
+            ld      t0, 136(sp)
+
+            addi    sp, sp, 144
+            ",
+            abi_table = sym ABI_TABLE,
+            tp = in(reg) tp,
+            in("t3") entry,
+            in("t4") sp,
+            out("s2") _,
+            options(nostack)
+        )
+    }
+}
+
+/// 当前的栈指针
+#[inline(always)]
+pub fn stack_pointer() -> usize {
+    let sp: usize;
+    unsafe { core::arch::asm!("mv {}, sp", out(reg) sp) };
+    sp
+}
+
+/// 让刚写入的代码对取指可见
+pub fn flush_icache() {
+    unsafe { core::arch::asm!("fence.i") };
+}
+
+/// 在当前栈上保存`ra`和`s0`～`s11`，切换到`stack_top`调用`f(arg, ctx)`，其中`ctx`
+/// 是保存位置。之后回到原来的栈并返回`f`的返回值。
+///
+/// # Safety
+///
+/// `stack_top`必须是一个足够大的空闲栈的顶端。
+#[naked]
+pub unsafe extern "C" fn fork_snapshot(
+    _arg: usize,
+    _f: extern "C" fn(usize, usize) -> isize,
+    _stack_top: usize,
+) -> isize {
+    unsafe {
+        naked_asm!(
+            "
+            addi    sp, sp, -112
+            sd      ra, 0(sp)
+            sd      s0, 8(sp)
+            sd      s1, 16(sp)
+            sd      s2, 24(sp)
+            sd      s3, 32(sp)
+            sd      s4, 40(sp)
+            sd      s5, 48(sp)
+            sd      s6, 56(sp)
+            sd      s7, 64(sp)
+            sd      s8, 72(sp)
+            sd      s9, 80(sp)
+            sd      s10, 88(sp)
+            sd      s11, 96(sp)
+
+            mv      t0, a1
+            mv      a1, sp
+            mv      s0, sp
+            mv      sp, a2
+            jalr    t0
+            mv      sp, s0
+
+            ld      ra, 0(sp)
+            ld      s0, 8(sp)
+            addi    sp, sp, 112
+            ret",
+        )
+    }
+}
+
+/// 从[`fork_snapshot`]保存的`ctx`恢复寄存器，让`fork_snapshot`返回 0
+///
+/// # Safety
+///
+/// `ctx`必须在当前地址空间中有效，且保存它的调用帧仍然存在。
+#[naked]
+pub unsafe extern "C" fn fork_return(_ctx: usize) -> ! {
+    unsafe {
+        naked_asm!(
+            "
+            mv      sp, a0
+            ld      ra, 0(sp)
+            ld      s0, 8(sp)
+            ld      s1, 16(sp)
+            ld      s2, 24(sp)
+            ld      s3, 32(sp)
+            ld      s4, 40(sp)
+            ld      s5, 48(sp)
+            ld      s6, 56(sp)
+            ld      s7, 64(sp)
+            ld      s8, 72(sp)
+            ld      s9, 80(sp)
+            ld      s10, 88(sp)
+            ld      s11, 96(sp)
+            addi    sp, sp, 112
+            li      a0, 0
+            ret",
+        )
+    }
+}
+
+/// 切换到`stack_top`并调用`f(arg)`，不再返回
+///
+/// # Safety
+///
+/// `stack_top`必须是一个空闲栈的顶端。
+#[naked]
+pub unsafe extern "C" fn call_on_stack(
+    _arg: usize,
+    _f: extern "C" fn(usize) -> !,
+    _stack_top: usize,
+) -> ! {
+    unsafe {
+        naked_asm!(
+            "
+            mv      sp, a2
+            jr      a1",
+        )
+    }
+}
@@ -0,0 +1,329 @@
+//! x86_64：System V AMD64 psABI
+
+use core::arch::naked_asm;
+
+use elf::abi::EM_X86_64;
+
+use super::RelocKind;
+use crate::abi::ABI_TABLE;
+
+/// ELF 文件头中的`e_machine`
+pub const EM_ARCH: u16 = EM_X86_64;
+/// 体系结构名称，用于日志
+pub const ARCH_NAME: &str = "x86_64";
+
+/// `bin`的物理地址：q35 的闪存用来放固件，QEMU 用`-device loader`把`apps.bin`放进
+/// 内核管理的 128 MiB 内存之后（见`scripts/make/qemu.mk`）
+pub const PAYLOAD_PADDR: Option<usize> = Some(0x800_0000);
+/// 静态链接的客户程序的链接地址：内核镜像之后 4 MiB
+pub const APP_START: usize = 0xffff_8000_0060_0000;
+
+// 重定位类型，编号见 psABI
+const R_X86_64_NONE: u32 = 0;
+const R_X86_64_64: u32 = 1;
+const R_X86_64_COPY: u32 = 5;
+const R_X86_64_GLOB_DAT: u32 = 6;
+const R_X86_64_JUMP_SLOT: u32 = 7;
+const R_X86_64_RELATIVE: u32 = 8;
+const R_X86_64_32: u32 = 10;
+const R_X86_64_DTPMOD64: u32 = 16;
+const R_X86_64_DTPOFF64: u32 = 17;
+const R_X86_64_TPOFF64: u32 = 18;
+const R_X86_64_IRELATIVE: u32 = 37;
+
+/// TLS variant II：TLS 块在`tp`之下，`tp`指向线程控制块
+pub const TLS_ABOVE_TP: bool = false;
+/// `tp`与第一个 TLS 块之间的空隙，只用于 variant I
+pub const TLS_GAP_ABOVE_TP: usize = 0;
+/// `tp`处线程控制块的大小：第一个字是指向自身的指针，`%fs:0`读出的就是`tp`
+pub const TLS_TCB_SIZE: usize = 8;
+/// `DTPOFF`就是相对 TLS 块起始处的偏移
+pub const TLS_DTV_OFFSET: usize = 0;
+
+/// 还没有`syscall`指令的入口，不支持以用户态运行程序
+pub const USER_MODE: bool = false;
+/// 信号处理函数返回用的跳板：`mov eax, 15`（`rt_sigreturn`）和`syscall`
+pub const SIGRETURN_CODE: &[u8] = &[0xb8, 0x0f, 0x00, 0x00, 0x00, 0x0f, 0x05];
+
+pub fn reloc_kind(r_type: u32) -> Option<RelocKind> {
+    Some(match r_type {
+        R_X86_64_NONE => RelocKind::None,
+        R_X86_64_32 => RelocKind::Abs32,
+        R_X86_64_64 => RelocKind::Abs64,
+        R_X86_64_RELATIVE => RelocKind::Relative,
+        R_X86_64_COPY => RelocKind::Copy,
+        R_X86_64_GLOB_DAT | R_X86_64_JUMP_SLOT => RelocKind::Symbol,
+        R_X86_64_DTPMOD64 => RelocKind::TlsModule,
+        R_X86_64_DTPOFF64 => RelocKind::TlsDtpOffset,
+        R_X86_64_TPOFF64 => RelocKind::TlsTpOffset,
+        R_X86_64_IRELATIVE => RelocKind::IRelative,
+        _ => return None,
+    })
+}
+
+/// 把 x86_64 的系统调用号换算成分发用的通用调用号（asm-generic，riscv64 和 aarch64
+/// 直接使用），参数按通用调用的顺序排列
+///
+/// 只收录加载器实现了的调用。`open`、`stat`这类只有 x86_64 才有的旧调用和其他没有
+/// 收录的调用号一样返回`None`，按未知调用处理；mocklibc 不会发起它们。
+pub fn generic_syscall(id: usize, mut args: [usize; 6]) -> Option<(usize, [usize; 6])> {
+    let generic = match id {
+        0 => 63,   // read
+        1 => 64,   // write
+        3 => 57,   // close
+        5 => 80,   // fstat
+        8 => 62,   // lseek
+        9 => 222,  // mmap
+        10 => 226, // mprotect
+        11 => 215, // munmap
+        12 => 214, // brk
+        13 => 134, // rt_sigaction
+        14 => 135, // rt_sigprocmask
+        15 => 139, // rt_sigreturn
+        16 => 29,  // ioctl
+        17 => 67,  // pread64
+        18 => 68,  // pwrite64
+        19 => 65,  // readv
+        20 => 66,  // writev
+        24 => 124, // sched_yield
+        25 => 216, // mremap
+        26 => 227, // msync
+        28 => 233, // madvise
+        29 => 194, // shmget
+        30 => 196, // shmat
+        31 => 195, // shmctl
+        32 => 23,  // dup
+        35 => 101, // nanosleep
+        36 => 102, // getitimer
+        38 => 103, // setitimer
+        39 => 172, // getpid
+        40 => 71,  // sendfile
+        41 => 198, // socket
+        42 => 203, // connect
+        43 => 202, // accept
+        44 => 206, // sendto
+        45 => 207, // recvfrom
+        48 => 210, // shutdown
+        49 => 200, // bind
+        50 => 201, // listen
+        51 => 204, // getsockname
+        52 => 205, // getpeername
+        54 => 208, // setsockopt
+        55 => 209, // getsockopt
+        56 => {
+            // clone：x86_64 的`ctid`在`tls`之前
+            args.swap(3, 4);
+            220
+        }
+        59 => 221,  // execve
+        60 => 93,   // exit
+        61 => 260,  // wait4
+        62 => 129,  // kill
+        63 => 160,  // uname
+        72 => 25,   // fcntl
+        74 => 82,   // fsync
+        75 => 83,   // fdatasync
+        77 => 46,   // ftruncate
+        79 => 17,   // getcwd
+        80 => 49,   // chdir
+        91 => 52,   // fchmod
+        93 => 55,   // fchown
+        95 => 166,  // umask
+        96 => 169,  // gettimeofday
+        98 => 165,  // getrusage
+        99 => 179,  // sysinfo
+        100 => 153, // times
+        102 => 174, // getuid
+        103 => 116, // syslog
+        104 => 176, // getgid
+        105 => 146, // setuid
+        106 => 144, // setgid
+        107 => 175, // geteuid
+        108 => 177, // getegid
+        109 => 154, // setpgid
+        110 => 173, // getppid
+        112 => 157, // setsid
+        113 => 145, // setreuid
+        114 => 143, // setregid
+        115 => 158, // getgroups
+        116 => 159, // setgroups
+        117 => 147, // setresuid
+        118 => 148, // getresuid
+        119 => 149, // setresgid
+        120 => 150, // getresgid
+        121 => 155, // getpgid
+        128 => 137, // rt_sigtimedwait
+        130 => 133, // rt_sigsuspend
+        137 => 43,  // statfs
+        144 => 119, // sched_setscheduler
+        145 => 120, // sched_getscheduler
+        157 => 167, // prctl
+        162 => 81,  // sync
+        165 => 40,  // mount
+        166 => 39,  // umount2
+        186 => 178, // gettid
+        200 => 130, // tkill
+        202 => 98,  // futex
+        203 => 122, // sched_setaffinity
+        204 => 123, // sched_getaffinity
+        217 => 61,  // getdents64
+        218 => 96,  // set_tid_address
+        228 => 113, // clock_gettime
+        229 => 114, // clock_getres
+        230 => 115, // clock_nanosleep
+        231 => 94,  // exit_group
+        233 => 21,  // epoll_ctl
+        234 => 131, // tgkill
+        239 => 236, // get_mempolicy
+        257 => 56,  // openat
+        258 => 34,  // mkdirat
+        260 => 54,  // fchownat
+        262 => 79,  // newfstatat
+        263 => 35,  // unlinkat
+        264 => 38,  // renameat
+        265 => 37,  // linkat
+        266 => 36,  // symlinkat
+        267 => 78,  // readlinkat
+        268 => 53,  // fchmodat
+        269 => 48,  // faccessat
+        270 => 72,  // pselect6
+        271 => 73,  // ppoll
+        273 => 99,  // set_robust_list
+        274 => 100, // get_robust_list
+        280 => 88,  // utimensat
+        281 => 22,  // epoll_pwait
+        288 => 242, // accept4
+        290 => 19,  // eventfd2
+        291 => 20,  // epoll_create1
+        292 => 24,  // dup3
+        293 => 59,  // pipe2
+        302 => 261, // prlimit64
+        316 => 276, // renameat2
+        318 => 278, // getrandom
+        324 => 283, // membarrier
+        326 => 285, // copy_file_range
+        435 => 435, // clone3
+        _ => return None,
+    };
+    Some((generic, args))
+}
+
+/// 以`tp`为线程指针（`fs`）、`sp`为栈顶调用客户程序的入口`entry`，程序返回后才返回
+///
+/// `rdi`同样指向`argc`，兼容`_start(long *p)`形式的入口；`rsi`是 ABI 表的地址。
+pub unsafe fn run_entry(entry: usize, sp: usize, tp: usize) {
+    let kernel_tp = axhal::arch::read_thread_pointer();
+    unsafe {
+        axhal::arch::write_thread_pointer(tp);
+        core::arch::asm!("
+            // 切换到用户栈(rdx)，`call`之前按 16 字节对齐
+            mov     r12, rsp
+            mov     rsp, rdx
+            mov     rdi, rsp
+            lea     rsi, [rip + {abi_table}]
+            call    rax
+            mov     rsp, r12",
+            abi_table = sym ABI_TABLE,
+            in("rax") entry,
+            in("rdx") sp,
+            out("r12") _,
+            clobber_abi("C"),
+        );
+        axhal::arch::write_thread_pointer(kernel_tp);
+    }
+}
+
+/// 当前的栈指针
+#[inline(always)]
+pub fn stack_pointer() -> usize {
+    let sp: usize;
+    unsafe { core::arch::asm!("mov {}, rsp", out(reg) sp) };
+    sp
+}
+
+/// 指令缓存与数据缓存一致，无需处理
+pub fn flush_icache() {}
+
+/// 在当前栈上保存`rbp`、`rbx`和`r12`～`r15`，切换到`stack_top`调用`f(arg, ctx)`，
+/// 其中`ctx`是保存位置。之后回到原来的栈并返回`f`的返回值。
+///
+/// # Safety
+///
+/// `stack_top`必须是一个足够大的空闲栈的顶端，按 16 字节对齐。
+#[naked]
+pub unsafe extern "C" fn fork_snapshot(
+    _arg: usize,
+    _f: extern "C" fn(usize, usize) -> isize,
+    _stack_top: usize,
+) -> isize {
+    unsafe {
+        naked_asm!(
+            "
+            push    rbp
+            push    rbx
+            push    r12
+            push    r13
+            push    r14
+            push    r15
+
+            mov     rax, rsi
+            mov     rsi, rsp
+            mov     rbx, rsp
+            mov     rsp, rdx
+            call    rax
+            mov     rsp, rbx
+
+            pop     r15
+            pop     r14
+            pop     r13
+            pop     r12
+            pop     rbx
+            pop     rbp
+            ret",
+        )
+    }
+}
+
+/// 从[`fork_snapshot`]保存的`ctx`恢复寄存器，让`fork_snapshot`返回 0
+///
+/// # Safety
+///
+/// `ctx`必须在当前地址空间中有效，且保存它的调用帧仍然存在。
+#[naked]
+pub unsafe extern "C" fn fork_return(_ctx: usize) -> ! {
+    unsafe {
+        naked_asm!(
+            "
+            mov     rsp, rdi
+            pop     r15
+            pop     r14
+            pop     r13
+            pop     r12
+            pop     rbx
+            pop     rbp
+            xor     eax, eax
+            ret",
+        )
+    }
+}
+
+/// 切换到`stack_top`并调用`f(arg)`，不再返回
+///
+/// # Safety
+///
+/// `stack_top`必须是一个空闲栈的顶端，按 16 字节对齐。
+#[naked]
+pub unsafe extern "C" fn call_on_stack(
+    _arg: usize,
+    _f: extern "C" fn(usize) -> !,
+    _stack_top: usize,
+) -> ! {
+    unsafe {
+        naked_asm!(
+            "
+            mov     rsp, rdx
+            call    rsi
+            ud2",
+        )
+    }
+}
@@ -1,2 +1,58 @@
 /// Stack size of each task.
 pub const TASK_STACK_SIZE: usize = 0x40000;
+
+/// Stack size of the loaded program's main thread.
+///
+/// Unlike the heap and `mmap` areas, the stack is populated when mapped:
+/// traps taken by the program push their frames onto it.
+pub const USER_STACK_SIZE: usize = 0x40000;
+
+/// Top of the loaded program's main thread stack (outside the `mmap` window).
+pub const USER_STACK_TOP: usize = 0xffff_fff0_0000_0000;
+
+/// Start of the window where program images and shared objects are loaded.
+pub const EXEC_START: usize = 0xffff_ffe0_0000_0000;
+/// End of the program image window.
+pub const EXEC_END: usize = 0xffff_ffe8_0000_0000;
+
+/// 以用户态运行的程序（没有链接 mocklibc 的普通 Linux 静态程序）镜像所在的范围，
+/// 即 Sv39 的低半部分。只有这个进程能访问它，内核地址空间中没有对应的映射。
+pub const USER_IMAGE_BASE: usize = 0x1000;
+/// End of the user mode program image range.
+pub const USER_IMAGE_END: usize = 0x40_0000_0000;
+
+/// 用户态程序的信号处理函数返回到这里，调用`rt_sigreturn`
+pub const SIGRETURN_TRAMPOLINE: usize = EXEC_END;
+
+/// 每个进程私有的地址范围：堆、`mmap`窗口、程序镜像和栈都在其中，
+/// 其余部分与内核地址空间共享。两端按 1 GiB（Sv39 顶级页表项）对齐。
+#[cfg(target_arch = "riscv64")]
+pub const USER_SPACE_BASE: usize = HEAP_START;
+/// End of the per-process address range.
+#[cfg(target_arch = "riscv64")]
+pub const USER_SPACE_END: usize = USER_STACK_TOP;
+
+/// x86_64 和 aarch64 的四级页表中一个顶级页表项覆盖 512 GiB，私有范围是内核地址空间
+/// 的最后一项，上面的各个窗口都在其中。
+#[cfg(not(target_arch = "riscv64"))]
+pub const USER_SPACE_BASE: usize = 0xffff_ff80_0000_0000;
+/// End of the per-process address range, i.e. the end of the kernel address space.
+#[cfg(not(target_arch = "riscv64"))]
+pub const USER_SPACE_END: usize = 0xffff_ffff_ffff_f000;
+
+/// 描述要运行的程序及其 `argv`/`envp` 的文件，不存在时运行 payload 中的程序
+pub const LOADER_CMDLINE_PATH: &str = "/etc/loader.conf";
+
+/// 查找共享对象的目录，按顺序搜索
+pub const LIB_SEARCH_PATHS: &[&str] = &["/lib", "/usr/lib"];
+
+/// Start of the window used by `mmap` (outside the kernel linear mapping).
+pub const MMAP_START: usize = 0xffff_ffd0_0000_0000;
+/// End of the window used by `mmap`.
+pub const MMAP_END: usize = 0xffff_ffe0_0000_0000;
+
+/// Base address of the program heap managed by `brk`.
+pub const HEAP_START: usize = 0xffff_ffc8_0000_0000;
+
+/// Maximum size of the program heap. Pages are allocated on first touch.
+pub const MAX_HEAP_SIZE: usize = 0x1000_0000;
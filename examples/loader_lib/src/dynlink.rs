@@ -0,0 +1,607 @@
+//! 动态链接器
+//!
+//! 从主程序开始按广度优先顺序遍历 `DT_NEEDED`，把所需的共享对象装载到各自分配的基址，
+//! 然后按全局查找顺序（主程序在前，其余按装载顺序）解析符号并完成重定位。
+//!
+//! 主程序和共享对象都装载在当前进程的镜像窗口中，`fork` 后以写时复制的方式共享。
+
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+use core::slice::{from_raw_parts, from_raw_parts_mut};
+
+use axhal::{
+    mem::{PAGE_SIZE_4K, VirtAddr},
+    paging::MappingFlags,
+};
+use axlog::{debug, warn};
+use elf::{
+    ElfBytes,
+    abi::{
+        DT_JMPREL, DT_NEEDED, DT_PLTRELSZ, DT_RELA, DT_RELASZ, DT_SONAME, PT_LOAD, PT_TLS,
+        SHN_UNDEF, STB_GLOBAL, STB_LOCAL, STB_WEAK,
+    },
+    endian::LittleEndian,
+    file::Class,
+    relocation::{Rela, RelaIterator},
+    segment::ProgramHeader,
+    string_table::StringTable,
+    symbol::{Symbol, SymbolTable},
+};
+
+use crate::{
+    arch::{EM_ARCH, RelocKind, TLS_DTV_OFFSET, reloc_kind},
+    config::{EXEC_END, EXEC_START},
+    elf::{ELFMAG0, ELFMAG1, ELFMAG2, ELFMAG3, LoadError, verify_segments},
+    linux_env::process_ext::api::current_process,
+    tls::{TlsLayout, TlsTemplate},
+};
+
+/// 对象在静态 TLS 布局中的位置
+#[derive(Clone, Copy, Debug)]
+pub struct TlsModule {
+    /// 模块编号，从 1 开始
+    pub id: usize,
+    /// 本模块 TLS 块相对 `tp` 的偏移，在 `tp` 之下时为负数的补码
+    pub offset: usize,
+}
+
+/// 一个已装载到内存中的 ELF 对象（主程序或共享库）
+///
+/// 文件内容只在装载和重定位期间需要，随对象一起释放。
+pub struct LoadedObject<'a> {
+    /// 用来装载的`DT_NEEDED`名称，主程序为空串
+    pub name: String,
+    /// 整个 ELF 文件
+    data: Cow<'a, [u8]>,
+    /// 装载基址：运行地址 = `base` + 链接地址
+    pub base: usize,
+    /// 含有 `PT_TLS` 段的对象在静态 TLS 布局中的位置
+    pub tls: Option<TlsModule>,
+    /// `DT_NEEDED` 列表，保持文件中的顺序
+    needed: Vec<String>,
+}
+
+impl LoadedObject<'_> {
+    /// 文件在装载时已经解析过一次，不会失败
+    pub fn elf(&self) -> ElfBytes<'_, LittleEndian> {
+        parse(&self.data).expect("Failed to parse loaded object")
+    }
+
+    fn dynamic_symbols(&self) -> Option<(SymbolTable<'_, LittleEndian>, StringTable<'_>)> {
+        self.elf()
+            .dynamic_symbol_table()
+            .expect("Failed to parse dynamic symbol table")
+    }
+
+    /// 在本对象中查找名为 `name` 的已定义全局（或弱）符号
+    fn find_definition(&self, name: &str) -> Option<Symbol> {
+        let (dynsym, dynstr) = self.dynamic_symbols()?;
+        dynsym.iter().find(|sym| {
+            sym.st_shndx != SHN_UNDEF
+                && sym.st_bind() != STB_LOCAL
+                && dynstr.get(sym.st_name as usize).unwrap_or("") == name
+        })
+    }
+
+    /// 读取位于已装载镜像中的一张重定位表（`DT_RELA` 或 `DT_JMPREL`）
+    fn relas(&self, addr_tag: i64, size_tag: i64) -> Option<RelaIterator<'static, LittleEndian>> {
+        let elf = self.elf();
+        let addr = dynamic_value(&elf, addr_tag)?;
+        let size = dynamic_value(&elf, size_tag)?;
+        let data =
+            unsafe { from_raw_parts((self.base + addr as usize) as *const u8, size as usize) };
+        Some(RelaIterator::new(LittleEndian, Class::ELF64, data))
+    }
+}
+
+/// 全局符号作用域：`objects[0]` 是主程序，其后按装载顺序排列共享库
+pub struct DynamicLinker<'a> {
+    objects: Vec<LoadedObject<'a>>,
+    /// 静态 TLS 区域的布局
+    tls: TlsLayout,
+}
+
+/// 按 `DT_NEEDED` 名称提供共享对象的 ELF 镜像
+pub trait LibraryProvider {
+    fn find(&mut self, name: &str) -> Option<Cow<'static, [u8]>>;
+}
+
+/// 打包在 payload 中的共享对象。
+///
+/// 每个共享对象前可以有一条记录其文件名的镜像（不以 ELF 魔数开头）。`DT_NEEDED` 优先与
+/// 各共享对象的 `DT_SONAME` 匹配，其次与文件名匹配；都不匹配的依赖视为缺失。
+pub struct PayloadLibraries {
+    /// `(soname, 文件名, 镜像)`
+    pending: Vec<(&'static str, &'static str, &'static [u8])>,
+}
+
+impl PayloadLibraries {
+    pub fn new(images: &[&'static [u8]]) -> Result<Self, LoadError> {
+        let mut pending = Vec::new();
+        let mut file_name = "";
+        for &slice in images {
+            if !slice.starts_with(&[ELFMAG0, ELFMAG1, ELFMAG2, ELFMAG3]) {
+                file_name = core::str::from_utf8(slice).map_err(|_| LoadError::InvalidMagic)?;
+                continue;
+            }
+            let soname = dynamic_string(&parse(slice)?, DT_SONAME).unwrap_or("");
+            debug!(
+                "[DL] Payload object {:?} ({:?}), 0x{:x} bytes",
+                soname,
+                file_name,
+                slice.len()
+            );
+            pending.push((soname, core::mem::take(&mut file_name), slice));
+        }
+        Ok(Self { pending })
+    }
+}
+
+impl LibraryProvider for PayloadLibraries {
+    fn find(&mut self, name: &str) -> Option<Cow<'static, [u8]>> {
+        let file_name = name.rsplit('/').next().unwrap_or(name);
+        let pos = self
+            .pending
+            .iter()
+            .position(|(soname, _, _)| *soname == name)
+            .or_else(|| {
+                self.pending
+                    .iter()
+                    .position(|(_, file, _)| !file.is_empty() && *file == file_name)
+            })?;
+        Some(Cow::Borrowed(self.pending.remove(pos).2))
+    }
+}
+
+impl Drop for PayloadLibraries {
+    fn drop(&mut self) {
+        for (soname, file_name, _) in &self.pending {
+            debug!(
+                "[DL] Skip unreferenced shared object {:?} ({:?})",
+                soname, file_name
+            );
+        }
+    }
+}
+
+/// 从文件系统中按搜索路径查找共享对象，`DT_NEEDED` 中带 `/` 的名称直接作为路径打开。
+///
+/// 读入的镜像由 [`DynamicLinker`] 持有，重定位完成后随它一起释放。
+pub struct FileLibraries {
+    search_paths: &'static [&'static str],
+}
+
+impl FileLibraries {
+    pub const fn new(search_paths: &'static [&'static str]) -> Self {
+        Self { search_paths }
+    }
+
+    /// 按搜索规则找到`name`对应的文件路径
+    fn resolve(&self, name: &str) -> Option<String> {
+        if name.contains('/') {
+            return Some(name.into());
+        }
+        self.search_paths
+            .iter()
+            .map(|dir| format!("{}/{}", dir.trim_end_matches('/'), name))
+            .find(|path| axfs::api::metadata(path).is_ok_and(|meta| meta.is_file()))
+    }
+
+    /// 检查`app`依赖的所有共享对象都能找到并且可以装载
+    ///
+    /// `execve`在清空地址空间之前调用，之后[`DynamicLinker::new`]不会因为缺少或损坏的
+    /// 共享对象而失败。读入的文件检查完就释放。
+    pub fn check(&self, app: &ElfBytes<LittleEndian>) -> Result<(), LoadError> {
+        let mut queue: Vec<String> = dynamic_strings(app, DT_NEEDED)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut index = 0;
+        while index < queue.len() {
+            let data = self
+                .resolve(&queue[index])
+                .and_then(|path| axfs::api::read(&path).ok())
+                .ok_or_else(|| {
+                    warn!("[DL] Missing dependency: {}", queue[index]);
+                    LoadError::MissingDependency
+                })?;
+            let elf = ElfBytes::<LittleEndian>::minimal_parse(&data)
+                .map_err(|_| LoadError::InvalidMagic)?;
+            if elf.ehdr.e_machine != EM_ARCH {
+                return Err(LoadError::WrongArchitecture);
+            }
+            verify_segments(&elf, data.len())?;
+            elf.dynamic_symbol_table()
+                .map_err(|_| LoadError::UndefinedSymbol)?;
+            for need in dynamic_strings(&elf, DT_NEEDED) {
+                if !queue.iter().any(|name| name == need) {
+                    queue.push(need.into());
+                }
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+}
+
+impl LibraryProvider for FileLibraries {
+    fn find(&mut self, name: &str) -> Option<Cow<'static, [u8]>> {
+        read_image(&self.resolve(name)?).map(Cow::Owned)
+    }
+}
+
+/// 把整个 ELF 文件读入内存
+pub fn read_image(path: &str) -> Option<Vec<u8>> {
+    let data = axfs::api::read(path).ok()?;
+    debug!("[DL] Read {} (0x{:x} bytes)", path, data.len());
+    Some(data)
+}
+
+impl<'a> DynamicLinker<'a> {
+    /// 装载主程序，再由 `libs` 按 `DT_NEEDED` 提供并装载它依赖的共享对象。
+    pub fn new(app_slice: &'a [u8], libs: &mut dyn LibraryProvider) -> Result<Self, LoadError> {
+        let mut objects = Vec::new();
+        objects.push(load_object(Cow::Borrowed(app_slice))?);
+
+        // 广度优先：`objects` 本身就是队列
+        let mut index = 0;
+        while index < objects.len() {
+            for need in objects[index].needed.clone() {
+                if objects.iter().any(|obj: &LoadedObject| obj.name == need) {
+                    continue;
+                }
+                let Some(data) = libs.find(&need) else {
+                    warn!("[DL] Missing dependency: {}", need);
+                    return Err(LoadError::MissingDependency);
+                };
+                let mut obj = load_object(data)?;
+                debug!("[DL] Load {} at 0x{:x}", need, obj.base);
+                obj.name = need;
+                objects.push(obj);
+            }
+            index += 1;
+        }
+
+        // 按装载顺序为带 `PT_TLS` 的对象分配模块编号和静态 TLS 偏移
+        let mut tls = TlsLayout::new();
+        let mut next_id = 1;
+        for obj in objects.iter_mut() {
+            let Some(ph) = tls_segment(&obj.elf()) else {
+                continue;
+            };
+            let offset = tls.add(ph.p_memsz as usize, ph.p_align as usize);
+            obj.tls = Some(TlsModule {
+                id: next_id,
+                offset,
+            });
+            debug!(
+                "[DL] TLS module {} {:?} offset 0x{:x}",
+                next_id, obj.name, offset
+            );
+            next_id += 1;
+        }
+
+        Ok(Self { objects, tls })
+    }
+
+    /// 对所有对象执行重定位。共享库先于主程序处理，与常见的 `ld.so` 顺序一致。
+    pub fn relocate(&self) -> Result<(), LoadError> {
+        for obj in self.objects.iter().rev() {
+            debug!("[DL] Relocate {:?} @0x{:x}", obj.name, obj.base);
+            if let Some(relas) = obj.relas(DT_RELA, DT_RELASZ) {
+                for rela in relas {
+                    self.relocate_one(obj, &rela)?;
+                }
+            }
+            if let Some(relas) = obj.relas(DT_JMPREL, DT_PLTRELSZ) {
+                for rela in relas {
+                    self.relocate_one(obj, &rela)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 用重定位后各模块的 `.tdata` 生成静态 TLS 模板
+    pub fn tls_template(&self) -> TlsTemplate {
+        let mut template = self.tls.template();
+        for obj in &self.objects {
+            let (Some(module), Some(ph)) = (obj.tls, tls_segment(&obj.elf())) else {
+                continue;
+            };
+            let init = unsafe {
+                from_raw_parts(
+                    (obj.base + ph.p_vaddr as usize) as *const u8,
+                    ph.p_filesz as usize,
+                )
+            };
+            template.copy_module(module.offset, init);
+        }
+        template
+    }
+
+    /// 提供程序入口的对象：装载顺序中第一个带入口地址的共享库（即运行时库的 `_start`），
+    /// 找不到时退回主程序自身。
+    fn entry_object(&self) -> &LoadedObject<'a> {
+        self.objects[1..]
+            .iter()
+            .find(|obj| obj.elf().ehdr.e_entry != 0)
+            .unwrap_or(&self.objects[0])
+    }
+
+    /// 主程序的装载基址
+    pub fn app_base(&self) -> usize {
+        self.objects[0].base
+    }
+
+    /// 程序入口地址
+    pub fn entry(&self) -> usize {
+        let obj = self.entry_object();
+        obj.base + obj.elf().ehdr.e_entry as usize
+    }
+
+    /// 提供入口的运行时库的装载基址（`AT_BASE`），入口在主程序中时为 0
+    pub fn interp_base(&self) -> usize {
+        let obj = self.entry_object();
+        if core::ptr::eq(obj, &self.objects[0]) {
+            0
+        } else {
+            obj.base
+        }
+    }
+
+    /// 按全局查找顺序解析符号：第一个强定义立即生效，否则取第一个弱定义
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        self.resolve(name, None)
+            .map(|(obj, sym)| obj.base + sym.st_value as usize)
+    }
+
+    /// 在全局作用域中查找 `name` 的定义，`exclude` 指定的对象不参与查找
+    /// （`COPY` 重定位需要跳过发起复制的主程序自身）
+    fn resolve(
+        &self,
+        name: &str,
+        exclude: Option<&LoadedObject>,
+    ) -> Option<(&LoadedObject<'a>, Symbol)> {
+        let mut weak = None;
+        for obj in &self.objects {
+            if exclude.is_some_and(|ex| core::ptr::eq(ex, obj)) {
+                continue;
+            }
+            let Some(sym) = obj.find_definition(name) else {
+                continue;
+            };
+            match sym.st_bind() {
+                STB_GLOBAL => return Some((obj, sym)),
+                STB_WEAK if weak.is_none() => weak = Some((obj, sym)),
+                _ => {}
+            }
+        }
+        weak
+    }
+
+    /// 解析重定位项引用的符号，返回定义它的对象和符号本身。
+    ///
+    /// `r_sym` 为 0 时表示不引用符号，视为本对象中值为 0 的定义；
+    /// 未定义的弱引用返回 `None`，由调用者按 0 处理。
+    fn resolve_rela<'s>(
+        &'s self,
+        obj: &'s LoadedObject<'a>,
+        r_sym: u32,
+        exclude: Option<&LoadedObject>,
+    ) -> Result<Option<(&'s LoadedObject<'a>, Symbol)>, LoadError> {
+        let (dynsym, dynstr) = obj.dynamic_symbols().ok_or(LoadError::RelocationError)?;
+        let sym = dynsym
+            .get(r_sym as usize)
+            .map_err(|_| LoadError::RelocationError)?;
+        if r_sym == 0 || sym.st_bind() == STB_LOCAL {
+            return Ok(Some((obj, sym)));
+        }
+        let name = dynstr.get(sym.st_name as usize).unwrap_or("");
+        match self.resolve(name, exclude) {
+            Some(def) => Ok(Some(def)),
+            None if sym.st_bind() == STB_WEAK => Ok(None),
+            None => {
+                warn!("[DL] Undefined symbol: {}", name);
+                Err(LoadError::UndefinedSymbol)
+            }
+        }
+    }
+
+    /// 求重定位项引用的符号地址 `S`；未定义的弱引用解析为 0
+    fn symbol_value(&self, obj: &LoadedObject, r_sym: u32) -> Result<usize, LoadError> {
+        Ok(self
+            .resolve_rela(obj, r_sym, None)?
+            .map_or(0, |(def, sym)| def.base + sym.st_value as usize))
+    }
+
+    /// 求 TLS 符号所在模块及其在模块 TLS 块内的偏移
+    fn tls_symbol(&self, obj: &LoadedObject, r_sym: u32) -> Result<(TlsModule, usize), LoadError> {
+        let (def, sym) = self
+            .resolve_rela(obj, r_sym, None)?
+            .ok_or(LoadError::UndefinedSymbol)?;
+        let module = def.tls.ok_or_else(|| {
+            warn!("[DL] TLS relocation against {:?} without PT_TLS", def.name);
+            LoadError::RelocationError
+        })?;
+        Ok((module, sym.st_value as usize))
+    }
+
+    fn relocate_one(&self, obj: &LoadedObject, rela: &Rela) -> Result<(), LoadError> {
+        // 各体系结构的重定位类型先换算成 [`RelocKind`]
+        //
+        // `A` Addend field in the relocation entry associated with the symbol.
+        // `B` Base address of a shared object loaded into memory.
+        // `P` Position of the relocation.
+        // `S` Value of the symbol in the symbol table.
+        // `TLSMODULE` TLS module index for the object containing the symbol.
+        // `TLSOFFSET` TLS static block offset (relative to `tp`) for the object containing the symbol.
+        let place = obj.base + rela.r_offset as usize;
+        let addend = rela.r_addend as usize;
+        let Some(kind) = reloc_kind(rela.r_type) else {
+            warn!("[DL] Unknown relocation type: {}", rela.r_type);
+            return Err(LoadError::RelocationError);
+        };
+        let value = match kind {
+            RelocKind::None => return Ok(()),
+            // 32-bit relocation: `S + A`.
+            RelocKind::Abs32 => {
+                let value = self.symbol_value(obj, rela.r_sym)?.wrapping_add(addend);
+                if u32::try_from(value).is_err() {
+                    warn!("[DL] 32-bit relocation value 0x{:x} truncated", value);
+                }
+                debug!(
+                    "[DL] rela type {} @0x{:x}=0x{:x}",
+                    rela.r_type, place, value
+                );
+                unsafe { (place as *mut u32).write_unaligned(value as u32) };
+                return Ok(());
+            }
+            // 64-bit relocation: `S + A`.
+            RelocKind::Abs64 => self.symbol_value(obj, rela.r_sym)?.wrapping_add(addend),
+            // Adjust a link address (A) to its load address: `B + A`.
+            RelocKind::Relative => obj.base.wrapping_add(addend),
+            // Must be in executable; not allowed in shared library.
+            RelocKind::Copy => {
+                let Some((def, sym)) = self.resolve_rela(obj, rela.r_sym, Some(obj))? else {
+                    return Err(LoadError::UndefinedSymbol);
+                };
+                let src = def.base + sym.st_value as usize;
+                debug!(
+                    "[DL] COPY @0x{:x} <- 0x{:x} size 0x{:x}",
+                    place, src, sym.st_size
+                );
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        src as *const u8,
+                        place as *mut u8,
+                        sym.st_size as usize,
+                    )
+                };
+                return Ok(());
+            }
+            // Indicates the symbol associated with a `PLT` entry: `S`.
+            RelocKind::Symbol => self.symbol_value(obj, rela.r_sym)?,
+            // `TLSMODULE`
+            RelocKind::TlsModule => self.tls_symbol(obj, rela.r_sym)?.0.id,
+            // `S + A - TLS_DTV_OFFSET`
+            RelocKind::TlsDtpOffset => {
+                let (_, offset) = self.tls_symbol(obj, rela.r_sym)?;
+                offset.wrapping_add(addend).wrapping_sub(TLS_DTV_OFFSET)
+            }
+            // `S + A + TLSOFFSET`
+            RelocKind::TlsTpOffset => {
+                let (module, offset) = self.tls_symbol(obj, rela.r_sym)?;
+                offset.wrapping_add(addend).wrapping_add(module.offset)
+            }
+            // `ifunc_resolver(B + A)`
+            RelocKind::IRelative => {
+                let resolver: extern "C" fn() -> usize =
+                    unsafe { core::mem::transmute(obj.base.wrapping_add(addend)) };
+                resolver()
+            }
+        };
+        debug!(
+            "[DL] rela type {} @0x{:x}=0x{:x}",
+            rela.r_type, place, value
+        );
+        unsafe { (place as *mut usize).write_unaligned(value) };
+        Ok(())
+    }
+}
+
+fn parse(slice: &[u8]) -> Result<ElfBytes<'_, LittleEndian>, LoadError> {
+    ElfBytes::<LittleEndian>::minimal_parse(slice).map_err(|_| LoadError::InvalidMagic)
+}
+
+/// 把文件内容为`data`的对象装载到新分配的基址
+fn load_object(data: Cow<'_, [u8]>) -> Result<LoadedObject<'_>, LoadError> {
+    let elf = parse(&data)?;
+    let base = alloc_image(image_span(&elf))?;
+    load_segments(&elf, &data, base);
+    let needed = dynamic_strings(&elf, DT_NEEDED)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    Ok(LoadedObject {
+        name: String::new(),
+        data,
+        base,
+        tls: None,
+        needed,
+    })
+}
+
+pub fn tls_segment(elf: &ElfBytes<LittleEndian>) -> Option<ProgramHeader> {
+    elf.segments()?.iter().find(|ph| ph.p_type == PT_TLS)
+}
+
+fn dynamic_value(elf: &ElfBytes<LittleEndian>, tag: i64) -> Option<u64> {
+    elf.dynamic()
+        .ok()??
+        .iter()
+        .find(|d| d.d_tag == tag)
+        .map(|d| d.d_val())
+}
+
+fn dynamic_string<'a>(elf: &ElfBytes<'a, LittleEndian>, tag: i64) -> Option<&'a str> {
+    dynamic_strings(elf, tag).into_iter().next()
+}
+
+/// 取出所有标签为 `tag` 的动态项，并把其值当作 `.dynstr` 中的偏移解析为字符串
+fn dynamic_strings<'a>(elf: &ElfBytes<'a, LittleEndian>, tag: i64) -> Vec<&'a str> {
+    let (Ok(Some(dynamic)), Ok(Some((_, dynstr)))) = (elf.dynamic(), elf.dynamic_symbol_table())
+    else {
+        return Vec::new();
+    };
+    dynamic
+        .iter()
+        .filter(|d| d.d_tag == tag)
+        .filter_map(|d| dynstr.get(d.d_val() as usize).ok())
+        .collect()
+}
+
+/// 所有 `PT_LOAD` 段覆盖的范围（从链接地址 0 算起），按页对齐
+fn image_span(elf: &ElfBytes<LittleEndian>) -> usize {
+    let end = elf
+        .segments()
+        .iter()
+        .flat_map(|phdrs| phdrs.iter())
+        .filter(|ph| ph.p_type == PT_LOAD)
+        .map(|ph| (ph.p_vaddr + ph.p_memsz) as usize)
+        .max()
+        .unwrap_or(0);
+    (end + PAGE_SIZE_4K - 1) & !(PAGE_SIZE_4K - 1)
+}
+
+/// 在当前进程的镜像窗口中映射一段可读写执行的匿名区域作为装载基址
+///
+/// 页帧在首次访问时分配并清零。窗口中放不下时返回错误。
+fn alloc_image(span: usize) -> Result<usize, LoadError> {
+    current_process()
+        .memory_set
+        .lock()
+        .map_anywhere(
+            span.max(PAGE_SIZE_4K),
+            MappingFlags::READ | MappingFlags::WRITE | MappingFlags::EXECUTE,
+            (VirtAddr::from(EXEC_START), VirtAddr::from(EXEC_END)),
+        )
+        .map(VirtAddr::as_usize)
+        .map_err(|_| LoadError::SegmentOutOfBounds)
+}
+
+/// 把所有 `PT_LOAD` 段复制到 `base` + `p_vaddr`，`memsz` 超出 `filesz` 的部分清零
+fn load_segments(elf: &ElfBytes<LittleEndian>, elf_slice: &[u8], base: usize) {
+    let phdrs = elf.segments().expect("Failed to parse program headers");
+    for ph in phdrs.iter().filter(|ph| ph.p_type == PT_LOAD) {
+        let offset = ph.p_offset as usize;
+        let filesz = ph.p_filesz as usize;
+        let memsz = ph.p_memsz as usize;
+        debug!(
+            "Load Segment vaddr 0x{:x} offset 0x{:x} filesz 0x{:x} memsz 0x{:x} base 0x{:x}",
+            ph.p_vaddr, offset, filesz, memsz, base
+        );
+        let dest = unsafe { from_raw_parts_mut((base + ph.p_vaddr as usize) as *mut u8, memsz) };
+        dest[..filesz].copy_from_slice(&elf_slice[offset..offset + filesz]);
+        dest[filesz..].fill(0);
+    }
+}
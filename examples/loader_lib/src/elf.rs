@@ -1,17 +1,19 @@
 use core::fmt;
 
 use axlog::debug;
-use elf::{ElfBytes, abi::EM_RISCV, endian::LittleEndian};
+use elf::{ElfBytes, abi::PT_LOAD, endian::LittleEndian};
+
+use crate::arch::{ARCH_NAME, EM_ARCH};
 
 pub fn verify_elf_header(elf: &ElfBytes<LittleEndian>) -> Result<(), LoadError> {
     let header: elf::file::FileHeader<LittleEndian> = elf.ehdr;
     debug!("ELF header: {:?}", header);
 
     // 1. 验证目标架构
-    if header.e_machine != EM_RISCV {
+    if header.e_machine != EM_ARCH {
         debug!(
-            "Wrong architecture: expected RISC-V, got {:?}",
-            header.e_machine
+            "Wrong architecture: expected {}, got {:?}",
+            ARCH_NAME, header.e_machine
         );
         return Err(LoadError::WrongArchitecture);
     }
@@ -43,6 +45,27 @@ pub fn verify_elf_header(elf: &ElfBytes<LittleEndian>) -> Result<(), LoadError>
     Ok(())
 }
 
+/// 检查程序头表可以解析，至少有一个`PT_LOAD`段，且每个段在文件中的内容不越界、
+/// `filesz`不超过`memsz`
+pub fn verify_segments(elf: &ElfBytes<LittleEndian>, file_size: usize) -> Result<(), LoadError> {
+    let phdrs = elf.segments().ok_or(LoadError::NoSegments)?;
+    let mut loads = phdrs.iter().filter(|ph| ph.p_type == PT_LOAD).peekable();
+    if loads.peek().is_none() {
+        return Err(LoadError::NoSegments);
+    }
+    for ph in loads {
+        let file_end = ph.p_offset.checked_add(ph.p_filesz);
+        if ph.p_filesz > ph.p_memsz
+            || file_end.is_none_or(|end| end > file_size as u64)
+            || ph.p_vaddr.checked_add(ph.p_memsz).is_none()
+        {
+            debug!("Segment out of bounds: {:?}", ph);
+            return Err(LoadError::SegmentOutOfBounds);
+        }
+    }
+    Ok(())
+}
+
 // 扩展错误类型
 #[derive(Debug)]
 pub enum LoadError {
@@ -56,6 +79,8 @@ pub enum LoadError {
     SegmentOutOfBounds,
     BadAlignment,
     RelocationError,
+    MissingDependency,
+    UndefinedSymbol,
 }
 
 impl fmt::Display for LoadError {
@@ -71,13 +96,15 @@ impl fmt::Display for LoadError {
             Self::SegmentOutOfBounds => write!(f, "Segment out of bounds"),
             Self::BadAlignment => write!(f, "Bad segment alignment"),
             Self::RelocationError => write!(f, "Relocation error"),
+            Self::MissingDependency => write!(f, "Required shared object not found"),
+            Self::UndefinedSymbol => write!(f, "Undefined symbol"),
         }
     }
 }
 
 // ELF 常量定义（如果需要）
-const ELFMAG0: u8 = 0x7f;
-const ELFMAG1: u8 = b'E';
-const ELFMAG2: u8 = b'L';
-const ELFMAG3: u8 = b'F';
+pub(crate) const ELFMAG0: u8 = 0x7f;
+pub(crate) const ELFMAG1: u8 = b'E';
+pub(crate) const ELFMAG2: u8 = b'L';
+pub(crate) const ELFMAG3: u8 = b'F';
 const EV_CURRENT: u32 = 1;
@@ -4,13 +4,15 @@ use axsync::Mutex;
 use crate::linux_env::{
     axfs_ext::api::OpenFlags,
     linux_fs::{
-        fd_manager::{FD_LIMIT_ORIGIN, FDM, FdManager},
+        fd_manager::{FD_LIMIT_ORIGIN, FdManager},
         stdio::{Stderr, Stdin, Stdout},
     },
+    process_ext::process::init_process,
 };
 
+/// 创建 init 进程，它最初只打开了标准输入输出
 pub fn init_all() {
-    FDM.init_once(FdManager::new(
+    init_process(FdManager::new(
         vec![
             // 标准输入
             Some(Arc::new(Stdin {
@@ -4,7 +4,10 @@ use axio::{Read, Seek, SeekFrom, Write};
 use axlog::debug;
 use core::any::Any;
 
-/// 文件系统信息
+use crate::linux_env::linux_fs::poll_queue::PollQueue;
+
+/// 文件系统信息，即`fstat`写给程序的`struct stat`（asm-generic 的布局）
+#[cfg(not(target_arch = "x86_64"))]
 #[derive(Debug, Clone, Copy, Default)]
 #[repr(C)]
 pub struct Kstat {
@@ -46,6 +49,48 @@ pub struct Kstat {
     pub st_ctime_nsec: isize,
 }
 
+/// 文件系统信息，即`fstat`写给程序的`struct stat`（x86_64 的布局：`st_nlink`
+/// 和`st_blksize`是 64 位的，`st_nlink`在`st_mode`之前）
+#[cfg(target_arch = "x86_64")]
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub struct Kstat {
+    /// 设备
+    pub st_dev: u64,
+    /// `inode`编号
+    pub st_ino: u64,
+    /// 硬链接数
+    pub st_nlink: u64,
+    /// 文件类型
+    pub st_mode: u32,
+    /// 用户id
+    pub st_uid: u32,
+    /// 用户组id
+    pub st_gid: u32,
+    /// `padding`
+    pub _pad0: u32,
+    /// 设备号
+    pub st_rdev: u64,
+    /// 文件大小
+    pub st_size: u64,
+    /// 块大小
+    pub st_blksize: u64,
+    /// 块个数
+    pub st_blocks: u64,
+    /// 最后一次访问时间(秒)
+    pub st_atime_sec: isize,
+    /// 最后一次访问时间(纳秒)
+    pub st_atime_nsec: isize,
+    /// 最后一次修改时间(秒)
+    pub st_mtime_sec: isize,
+    /// 最后一次修改时间(纳秒)
+    pub st_mtime_nsec: isize,
+    /// 最后一次改变状态时间(秒)
+    pub st_ctime_sec: isize,
+    /// 最后一次改变状态时间(纳秒)
+    pub st_ctime_nsec: isize,
+}
+
 use bitflags::*;
 
 bitflags! {
@@ -66,6 +111,8 @@ bitflags! {
         const NOCTTY = 1 << 8;
         /// 同上，在不同的库中可能会用到这个或者上一个
         const EXCL = 1 << 9;
+        /// 每次写入前把读写指针移到文件末尾
+        const APPEND = 1 << 10;
         /// 非阻塞读写?(虽然不知道为什么但`date.lua`也要)
         /// 在 socket 中使用得较多
         const NON_BLOCK = 1 << 11;
@@ -174,6 +221,16 @@ pub trait FileIO: AsAny + Send + Sync {
         Err(AxError::Unsupported) // 如果没有实现, 则返回Unsupported
     }
 
+    /// 在`offset`处读取，不改变读写指针
+    fn read_at(&self, _offset: u64, _buf: &mut [u8]) -> AxResult<usize> {
+        Err(AxError::Unsupported) // 如果没有实现, 则返回Unsupported
+    }
+
+    /// 在`offset`处写入，不改变读写指针
+    fn write_at(&self, _offset: u64, _buf: &[u8]) -> AxResult<usize> {
+        Err(AxError::Unsupported) // 如果没有实现, 则返回Unsupported
+    }
+
     /// Whether the file is readable
     fn readable(&self) -> bool;
 
@@ -196,6 +253,11 @@ pub trait FileIO: AsAny + Send + Sync {
         Err(AxError::Unsupported) // 如果没有实现get_stat, 则返回Unsupported
     }
 
+    /// 把数据和元数据写回存储设备，即`fsync`。不支持的对象（管道等）返回`InvalidInput`
+    fn sync(&self) -> AxResult<()> {
+        Err(AxError::InvalidInput)
+    }
+
     /// 截断文件到指定长度
     fn truncate(&self, _len: usize) -> AxResult<()> {
         debug!("Function truncate not implemented");
@@ -246,6 +308,11 @@ pub trait FileIO: AsAny + Send + Sync {
         false
     }
 
+    /// 状态改变时会通知的等待队列，没有时等待者定时重新检查
+    fn poll_queue(&self) -> Option<&PollQueue> {
+        None
+    }
+
     /// To control the file descriptor
     fn ioctl(&self, _request: usize, _arg1: usize) -> AxResult<isize> {
         Err(AxError::Unsupported)
@@ -366,21 +433,24 @@ impl From<usize> for OpenFlags {
 #[allow(missing_docs)]
 pub const TCGETS: usize = 0x5401;
 #[allow(missing_docs)]
+pub const TCSETS: usize = 0x5402;
+/// 等输出写完再设置，输出总是立即写出，同`TCSETS`
+pub const TCSETSW: usize = 0x5403;
+/// 丢弃未读的输入再设置
+pub const TCSETSF: usize = 0x5404;
+#[allow(missing_docs)]
+pub const TCFLSH: usize = 0x540B;
+#[allow(missing_docs)]
 pub const TIOCGPGRP: usize = 0x540F;
 #[allow(missing_docs)]
 pub const TIOCSPGRP: usize = 0x5410;
 #[allow(missing_docs)]
 pub const TIOCGWINSZ: usize = 0x5413;
 #[allow(missing_docs)]
+pub const TIOCSWINSZ: usize = 0x5414;
+/// 可以读取的字节数
+pub const FIONREAD: usize = 0x541B;
+#[allow(missing_docs)]
 pub const FIONBIO: usize = 0x5421;
 #[allow(missing_docs)]
 pub const FIOCLEX: usize = 0x5451;
-#[repr(C)]
-#[derive(Clone, Copy, Default)]
-/// the size of the console window
-pub struct ConsoleWinSize {
-    ws_row: u16,
-    ws_col: u16,
-    ws_xpixel: u16,
-    ws_ypixel: u16,
-}
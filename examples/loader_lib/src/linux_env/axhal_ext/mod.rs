@@ -0,0 +1,31 @@
+//! 运行在用户态的程序：进入用户态，以及陷入时保存的寄存器
+//!
+//! 没有链接 mocklibc 的普通 Linux 程序运行在用户态，通过`ecall`陷入内核进行系统调用
+//! （见`syscall::syscall`）。陷入帧保存在当前任务内核栈的栈顶。
+use axhal::{
+    arch::{TrapFrame, UspaceContext},
+    mem::VirtAddr,
+};
+
+use crate::linux_env::process_ext::api::kernel_stack_top;
+
+/// 以用户态从`entry`开始运行，栈顶为`sp`，不再返回
+///
+/// 与 S 模式的程序一样，`a0`也指向`argc`。
+pub fn enter_user(entry: usize, sp: usize) -> ! {
+    let ctx = UspaceContext::new(entry, VirtAddr::from(sp), sp);
+    enter_user_context(&ctx)
+}
+
+/// 以`ctx`中的寄存器回到用户态，不再返回
+pub fn enter_user_context(ctx: &UspaceContext) -> ! {
+    unsafe { ctx.enter_uspace(VirtAddr::from(kernel_stack_top())) }
+}
+
+/// 当前线程从用户态陷入时保存的寄存器
+///
+/// 只能在用户态程序的线程处理系统调用时使用。
+pub fn user_trap_frame() -> &'static TrapFrame {
+    let addr = kernel_stack_top() - core::mem::size_of::<TrapFrame>();
+    unsafe { &*(addr as *const TrapFrame) }
+}
@@ -1,48 +1,54 @@
 //!`todo`重构fd_table, fd_allocator
+//!
+//! 文件描述符表和文件系统上下文（工作目录、`umask`）属于各个进程，`clone`时按
+//! `CLONE_FILES`/`CLONE_FS`决定与子进程共享还是复制一份。
 extern crate alloc;
 use crate::linux_env::{
     axfs_ext::api::{FileIO, OpenFlags},
     linux_fs::stdio::{Stdin, Stdout},
+    process_ext::api::{current_process, try_current_process},
+};
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
 };
-use alloc::{string::String, sync::Arc, vec::Vec};
 use axerrno::{AxError, AxResult};
 use axlog::debug;
 use axlog::info;
 use axsync::Mutex;
 use core::sync::atomic::{AtomicI32, AtomicU64};
-use lazyinit::LazyInit;
 
 pub const FD_LIMIT_ORIGIN: usize = 1025;
-/// 通常情况下，一个`FdManager`足以满足单进程多线程的需求。
-pub static FDM: LazyInit<FdManager> = LazyInit::new();
 
 pub struct FdManager {
     /// 保存文件描述符的数组
     pub fd_table: Mutex<Vec<Option<Arc<dyn FileIO>>>>,
-    /// 保存文件描述符的数组的最大长度
+    /// 保存文件描述符的数组的最大长度，即`RLIMIT_NOFILE`
     pub limit: AtomicU64,
+}
+
+/// 文件系统相关的进程状态，`CLONE_FS`时在进程间共享
+pub struct FsContext {
     /// 创建文件时的`mode`的掩码
     umask: AtomicI32,
-    pub cwd: Mutex<String>,
+    /// 工作目录，总是以`/`结尾
+    cwd: Mutex<String>,
 }
 
-pub fn alloc_fd(fd_table: &mut Vec<Option<Arc<dyn FileIO>>>) -> AxResult<usize> {
-    for (i, fd) in fd_table.iter().enumerate() {
-        if fd.is_none() {
-            return Ok(i);
-        }
-    }
-    if fd_table.len() >= FDM.get_limit() as usize {
-        debug!("fd table is full");
-        return Err(AxError::StorageFull);
-    }
-    fd_table.push(None);
-    Ok(fd_table.len() - 1)
+/// 当前进程的文件描述符表
+pub fn current_fd_manager() -> Arc<FdManager> {
+    current_process().fd_manager()
 }
 
-/// 获取当前进程的工作目录
+/// 获取当前进程的工作目录，以`/`结尾
+///
+/// 还没有进程时（如启动阶段）为根目录。
 pub fn get_cwd() -> String {
-    FDM.cwd.lock().clone()
+    match try_current_process() {
+        Some(process) => process.fs.cwd(),
+        None => String::from("/"),
+    }
 }
 
 impl FdManager {
@@ -50,8 +56,14 @@ impl FdManager {
         Self {
             fd_table: Mutex::new(fd_table),
             limit: AtomicU64::new(limit as u64),
-            umask: AtomicI32::new(0o022),
-            cwd: Mutex::new(String::from("/")),
+        }
+    }
+
+    /// 为不带`CLONE_FILES`的子进程复制一份文件描述符表，打开的文件本身是共享的
+    pub fn fork(&self) -> Self {
+        Self {
+            fd_table: Mutex::new(self.fd_table.lock().clone()),
+            limit: AtomicU64::new(self.get_limit()),
         }
     }
 
@@ -64,16 +76,19 @@ impl FdManager {
             .store(new_limit, core::sync::atomic::Ordering::Release)
     }
 
-    pub fn get_mask(&self) -> i32 {
-        self.umask.load(core::sync::atomic::Ordering::Acquire)
-    }
-
-    /// 设置新的`mask`，返回旧的`mask`
-    pub fn set_mask(&self, new_mask: i32) -> i32 {
-        let old_mask = self.umask.load(core::sync::atomic::Ordering::Acquire);
-        self.umask
-            .store(new_mask, core::sync::atomic::Ordering::Release);
-        old_mask
+    /// 在`fd_table`中分配最小的空闲文件描述符，`fd_table`是已经上锁的`self.fd_table`
+    pub fn alloc_fd(&self, fd_table: &mut Vec<Option<Arc<dyn FileIO>>>) -> AxResult<usize> {
+        for (i, fd) in fd_table.iter().enumerate() {
+            if fd.is_none() {
+                return Ok(i);
+            }
+        }
+        if fd_table.len() >= self.get_limit() as usize {
+            debug!("fd table is full");
+            return Err(AxError::StorageFull);
+        }
+        fd_table.push(None);
+        Ok(fd_table.len() - 1)
     }
 
     /// 在执行 `exec()` 时关闭标记为 `CLOEXEC` 的文件
@@ -99,3 +114,51 @@ impl FdManager {
         }
     }
 }
+
+impl Default for FsContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsContext {
+    pub fn new() -> Self {
+        Self {
+            umask: AtomicI32::new(0o022),
+            cwd: Mutex::new(String::from("/")),
+        }
+    }
+
+    /// 为不带`CLONE_FS`的子进程复制一份
+    pub fn fork(&self) -> Self {
+        Self {
+            umask: AtomicI32::new(self.get_mask()),
+            cwd: Mutex::new(self.cwd()),
+        }
+    }
+
+    pub fn cwd(&self) -> String {
+        self.cwd.lock().clone()
+    }
+
+    /// 切换工作目录，`path`应当是已经规范化的绝对路径
+    pub fn set_cwd(&self, path: &str) {
+        let mut cwd = path.to_string();
+        if !cwd.ends_with('/') {
+            cwd.push('/');
+        }
+        *self.cwd.lock() = cwd;
+    }
+
+    pub fn get_mask(&self) -> i32 {
+        self.umask.load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    /// 设置新的`mask`，返回旧的`mask`
+    pub fn set_mask(&self, new_mask: i32) -> i32 {
+        let old_mask = self.umask.load(core::sync::atomic::Ordering::Acquire);
+        self.umask
+            .store(new_mask, core::sync::atomic::Ordering::Release);
+        old_mask
+    }
+}
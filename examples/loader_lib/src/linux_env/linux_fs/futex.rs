@@ -1,22 +1,56 @@
 //! 实现与`futex`相关的系统调用
-use alloc::collections::{BTreeMap, VecDeque};
-use axhal::mem::{MemoryAddr, VirtAddr};
+//!
+//! 等待者按 [`FutexKey`] 排队。每个等待者有自己的 [`PollQueue`]，唤醒者在持有
+//! [`FUTEX_WAIT_TASK`] 的锁时把它移出队列、设置`woken`再通知它；超时由定时器唤醒，
+//! 不需要轮询。有需要处理的信号时等待者也会醒来，离开队列并返回`EINTR`。
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use axerrno::{LinuxError, LinuxResult};
+use axhal::{
+    mem::{MemoryAddr, VirtAddr},
+    paging::MappingFlags,
+    time::TimeValue,
+};
 use axsync::Mutex;
-use axtask::{AxTaskRef, WaitQueue};
+use axtask::current;
+
+use crate::{
+    linux_env::{
+        linux_fs::poll_queue::{PollQueue, PollWait, wait_on},
+        process_ext::{api::current_process, process::TID2PC},
+    },
+    syscall::{FUTEX_BITSET_MATCH_ANY, FUTEX_PRIVATE_FLAG},
+};
 
 extern crate alloc;
 
-/// `vec`中的元素分别是任务指针,对应存储时的`futex`变量的值
-pub static FUTEX_WAIT_TASK: Mutex<BTreeMap<FutexKey, VecDeque<(AxTaskRef, u32)>>> =
+/// futex 变量中的标志：有线程在等待这个锁
+pub const FUTEX_WAITERS: u32 = 0x8000_0000;
+/// futex 变量中的标志：锁的持有者退出时没有释放它
+pub const FUTEX_OWNER_DIED: u32 = 0x4000_0000;
+/// futex 变量中保存持有者线程号的位
+pub const FUTEX_TID_MASK: u32 = 0x3fff_ffff;
+
+/// 退出时最多处理的 robust list 节点数，防止用户构造的环使内核无法退出
+const ROBUST_LIST_LIMIT: usize = 2048;
+
+/// 各个 futex 变量上的等待者，按等待的先后排列
+pub static FUTEX_WAIT_TASK: Mutex<BTreeMap<FutexKey, VecDeque<Arc<FutexWaiter>>>> =
     Mutex::new(BTreeMap::new());
 
-/// Waiting queue which stores tasks waiting for `futex` variable
-pub static WAIT_FOR_FUTEX: Mutex<WaitQueue> = Mutex::new(WaitQueue::new());
+/// 线程号到它用`set_robust_list`登记的 robust list
+pub static ROBUST_LISTS: Mutex<BTreeMap<u64, FutexRobustList>> = Mutex::new(BTreeMap::new());
 
 /// `Futexes` are matched on equal values of this key.
 ///
 /// The key type depends on whether it's a shared or private mapping.
-/// Use `pid` to replace the mm_struct pointer
+/// Private futexes use `pid` to replace the mm_struct pointer and the virtual page in
+/// `word`; shared futexes have `ptr == 0` and the physical page in `word`, so that
+/// processes mapping the same page at different addresses can wake each other.
 #[derive(Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq)]
 pub struct FutexKey {
     ptr: u64,
@@ -31,12 +65,346 @@ impl FutexKey {
 }
 
 /// 获取`futex`变量的key
-/// TODO: shared `futex` and error handling
-pub fn get_futex_key(uaddr: VirtAddr, _flags: i32) -> FutexKey {
-    let ptr = 0;
+///
+/// 带`FUTEX_PRIVATE_FLAG`或者不在`MAP_SHARED`区域中的变量按进程和虚拟地址匹配：
+/// 私有的页是写时复制的，写入时会换成新的页帧。其余的按物理页匹配，所以要先模拟
+/// 一次写访问，让按需分配和写时复制的页在等待之前就固定到本进程可写的页帧上。
+pub fn get_futex_key(uaddr: VirtAddr, flags: i32) -> LinuxResult<FutexKey> {
+    let word = futex_word(uaddr)?;
     let offset = uaddr.align_offset_4k() as u32;
-    let word = uaddr.align_down_4k().as_usize();
-    FutexKey::new(ptr, word, offset)
+    let process = current_process();
+    if flags & FUTEX_PRIVATE_FLAG != 0 || !process.memory_set.lock().is_shared(uaddr) {
+        return Ok(FutexKey::new(
+            process.pid(),
+            uaddr.align_down_4k().as_usize(),
+            offset,
+        ));
+    }
+    word.load(Ordering::Relaxed);
+    let mut aspace = process.aspace().lock();
+    aspace.handle_page_fault(uaddr, MappingFlags::WRITE);
+    let (paddr, _, _) = aspace
+        .page_table()
+        .query(uaddr)
+        .map_err(|_| LinuxError::EFAULT)?;
+    Ok(FutexKey::new(0, paddr.align_down_4k().as_usize(), offset))
+}
+
+/// `uaddr`处的 futex 变量，它必须按 4 字节对齐
+///
+/// 未映射的地址返回`EFAULT`，否则访问它会在内核中缺页。
+pub fn futex_word(uaddr: VirtAddr) -> LinuxResult<&'static AtomicU32> {
+    if uaddr.as_usize() == 0 {
+        return Err(LinuxError::EFAULT);
+    }
+    if uaddr.as_usize() % 4 != 0 {
+        return Err(LinuxError::EINVAL);
+    }
+    if !is_accessible(uaddr) {
+        return Err(LinuxError::EFAULT);
+    }
+    Ok(unsafe { &*(uaddr.as_usize() as *const AtomicU32) })
+}
+
+/// `addr`处的 4 字节能否访问：落在进程的某个区域中，或者已经映射在页表中
+///
+/// 后者是与内核共享的部分，例如装载在固定地址的程序镜像和内核堆。
+fn is_accessible(addr: VirtAddr) -> bool {
+    let process = current_process();
+    if process.memory_set.lock().is_mapped(addr, 4) {
+        return true;
+    }
+    process.aspace().lock().page_table().query(addr).is_ok()
+}
+
+/// 在某个 futex 变量上等待的线程
+pub struct FutexWaiter {
+    tid: u64,
+    /// `FUTEX_WAKE_BITSET`只唤醒位集有交集的等待者
+    bitset: u32,
+    /// 唤醒者已经把它移出队列
+    woken: AtomicBool,
+    queue: PollQueue,
+}
+
+impl FutexWaiter {
+    fn new(bitset: u32) -> Arc<Self> {
+        Arc::new(Self {
+            tid: current().id().as_u64(),
+            bitset,
+            woken: AtomicBool::new(false),
+            queue: PollQueue::new(),
+        })
+    }
+
+    /// 调用者已经把它移出了队列
+    fn wake(&self) {
+        self.woken.store(true, Ordering::Release);
+        self.queue.notify();
+    }
+
+    /// 阻塞到被唤醒、到达`monotonic_time`下的截止时间或者有需要处理的信号
+    ///
+    /// 超时或被信号打断的等待者自己离开队列。它可能在这同时被唤醒，此时仍然算作被唤醒，
+    /// 否则这次唤醒就丢失了。
+    fn block(self: &Arc<Self>, deadline: Option<TimeValue>) -> PollWait {
+        let woken = || self.woken.load(Ordering::Acquire);
+        let wait = wait_on(&[&self.queue], false, deadline, woken);
+        if wait == PollWait::Ready {
+            return wait;
+        }
+        let mut queues = FUTEX_WAIT_TASK.lock();
+        if woken() {
+            return PollWait::Ready;
+        }
+        // 可能已经被`requeue`到别的变量上
+        queues.retain(|_, waiters| {
+            waiters.retain(|waiter| !Arc::ptr_eq(waiter, self));
+            !waiters.is_empty()
+        });
+        wait
+    }
+}
+
+/// 唤醒`key`上至多`max`个位集与`bitset`有交集的等待者，返回唤醒的个数
+fn wake_locked(
+    queues: &mut BTreeMap<FutexKey, VecDeque<Arc<FutexWaiter>>>,
+    key: FutexKey,
+    max: usize,
+    bitset: u32,
+) -> usize {
+    let Some(waiters) = queues.get_mut(&key) else {
+        return 0;
+    };
+    let mut woken = 0;
+    waiters.retain(|waiter| {
+        if woken < max && waiter.bitset & bitset != 0 {
+            waiter.wake();
+            woken += 1;
+            false
+        } else {
+            true
+        }
+    });
+    if waiters.is_empty() {
+        queues.remove(&key);
+    }
+    woken
+}
+
+/// 若`uaddr`处的值仍是`val`，就在`key`上等待，直到被位集与`bitset`有交集的唤醒者
+/// 唤醒或到达截止时间
+pub fn futex_wait(
+    uaddr: VirtAddr,
+    key: FutexKey,
+    val: u32,
+    bitset: u32,
+    deadline: Option<TimeValue>,
+) -> LinuxResult {
+    let word = futex_word(uaddr)?;
+    let waiter = FutexWaiter::new(bitset);
+    {
+        // 唤醒者修改变量后要先取得这个锁，在锁中检查不会错过唤醒
+        let mut queues = FUTEX_WAIT_TASK.lock();
+        if word.load(Ordering::SeqCst) != val {
+            return Err(LinuxError::EAGAIN);
+        }
+        queues.entry(key).or_default().push_back(waiter.clone());
+    }
+    match waiter.block(deadline) {
+        PollWait::Ready => Ok(()),
+        PollWait::Timeout => Err(LinuxError::ETIMEDOUT),
+        PollWait::Interrupted => Err(LinuxError::EINTR),
+    }
+}
+
+/// 唤醒`key`上至多`max`个位集与`bitset`有交集的等待者，返回唤醒的个数
+pub fn futex_wake(key: FutexKey, max: usize, bitset: u32) -> usize {
+    wake_locked(&mut FUTEX_WAIT_TASK.lock(), key, max, bitset)
+}
+
+/// `Futex requeue`操作
+///
+/// 首先唤醒`src`上至多`wake_num`个任务，再把剩下的任务中至多`move_num`个移动到`dst`的
+/// 等待队列中，返回两者之和。
+///
+/// `expected`不为`None`时（`FUTEX_CMP_REQUEUE`），先检查`uaddr`处的值仍是`val`。
+pub fn futex_requeue(
+    src: FutexKey,
+    dst: FutexKey,
+    wake_num: usize,
+    move_num: usize,
+    expected: Option<(VirtAddr, u32)>,
+) -> LinuxResult<usize> {
+    let mut queues = FUTEX_WAIT_TASK.lock();
+    if let Some((uaddr, val)) = expected {
+        if futex_word(uaddr)?.load(Ordering::SeqCst) != val {
+            return Err(LinuxError::EAGAIN);
+        }
+    }
+    let woken = wake_locked(&mut queues, src, wake_num, FUTEX_BITSET_MATCH_ANY);
+    if src == dst {
+        return Ok(woken);
+    }
+    let Some(src_waiters) = queues.get_mut(&src) else {
+        return Ok(woken);
+    };
+    let move_num = move_num.min(src_waiters.len());
+    let mut moved = src_waiters.drain(..move_num).collect::<VecDeque<_>>();
+    if src_waiters.is_empty() {
+        queues.remove(&src);
+    }
+    if !moved.is_empty() {
+        queues.entry(dst).or_default().append(&mut moved);
+    }
+    Ok(woken + move_num)
+}
+
+/// `FUTEX_WAKE_OP`：按`op`修改`uaddr2`处的值，唤醒`key`上至多`wake_num`个等待者；若修改前
+/// 的值满足`op`中的比较，再唤醒`key2`上至多`wake_num2`个等待者
+///
+/// `op`的编码与 Linux 相同：`op:4 cmp:4 oparg:12 cmparg:12`。
+pub fn futex_wake_op(
+    key: FutexKey,
+    key2: FutexKey,
+    uaddr2: VirtAddr,
+    wake_num: usize,
+    wake_num2: usize,
+    op: u32,
+) -> LinuxResult<usize> {
+    let shift = op & (8 << 28) != 0;
+    let opcode = (op >> 28) & 7;
+    let cmp = (op >> 24) & 15;
+    // 12 位有符号数
+    let mut oparg = ((op << 8) as i32 >> 20) as u32;
+    let cmparg = ((op << 20) as i32) >> 20;
+    if shift {
+        // 与 Linux 相同，移位数只取低 5 位
+        oparg = 1 << (oparg & 31);
+    }
+    let update = |old: u32| match opcode {
+        0 => Some(oparg),
+        1 => Some(old.wrapping_add(oparg)),
+        2 => Some(old | oparg),
+        3 => Some(old & !oparg),
+        4 => Some(old ^ oparg),
+        _ => None,
+    };
+    update(0).ok_or(LinuxError::ENOSYS)?;
+    if cmp > 5 {
+        return Err(LinuxError::ENOSYS);
+    }
+
+    let word2 = futex_word(uaddr2)?;
+    let mut queues = FUTEX_WAIT_TASK.lock();
+    let old = word2
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, update)
+        .unwrap() as i32;
+    let mut woken = wake_locked(&mut queues, key, wake_num, FUTEX_BITSET_MATCH_ANY);
+    let matched = match cmp {
+        0 => old == cmparg,
+        1 => old != cmparg,
+        2 => old < cmparg,
+        3 => old <= cmparg,
+        4 => old > cmparg,
+        _ => old >= cmparg,
+    };
+    if matched {
+        woken += wake_locked(&mut queues, key2, wake_num2, FUTEX_BITSET_MATCH_ANY);
+    }
+    Ok(woken)
+}
+
+/// 获取`uaddr`处的优先级继承锁：变量的低 30 位是持有者的线程号，为 0 时锁空闲
+///
+/// 释放者把锁直接交给最先等待的线程，等待者醒来时锁已经属于它。调度器没有可以
+/// 继承的优先级，这里只实现锁的交接和`FUTEX_OWNER_DIED`的语义。
+/// `trylock`为真时不等待，锁被占用时返回`EAGAIN`。
+pub fn futex_lock_pi(
+    uaddr: VirtAddr,
+    key: FutexKey,
+    deadline: Option<TimeValue>,
+    trylock: bool,
+) -> LinuxResult {
+    let word = futex_word(uaddr)?;
+    let tid = current().id().as_u64() as u32;
+    loop {
+        let waiter = {
+            let mut queues = FUTEX_WAIT_TASK.lock();
+            let val = word.load(Ordering::SeqCst);
+            let owner = val & FUTEX_TID_MASK;
+            if owner == tid {
+                return Err(LinuxError::EDEADLK);
+            }
+            if owner == 0 {
+                // 锁空闲或持有者已经退出：接手它，保留`FUTEX_OWNER_DIED`让用户程序知道
+                let waiters = if queues.contains_key(&key) {
+                    FUTEX_WAITERS
+                } else {
+                    0
+                };
+                let new = tid | waiters | (val & FUTEX_OWNER_DIED);
+                if word
+                    .compare_exchange(val, new, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+                {
+                    return Ok(());
+                }
+                continue;
+            }
+            if val & FUTEX_OWNER_DIED == 0 && !TID2PC.lock().contains_key(&(owner as u64)) {
+                return Err(LinuxError::ESRCH);
+            }
+            if trylock {
+                return Err(LinuxError::EAGAIN);
+            }
+            // 持有者看到这个标志才会进入内核释放锁
+            if val & FUTEX_WAITERS == 0
+                && word
+                    .compare_exchange(val, val | FUTEX_WAITERS, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+            {
+                continue;
+            }
+            let waiter = FutexWaiter::new(FUTEX_BITSET_MATCH_ANY);
+            queues.entry(key).or_default().push_back(waiter.clone());
+            waiter
+        };
+        let wait = waiter.block(deadline);
+        if word.load(Ordering::SeqCst) & FUTEX_TID_MASK == tid {
+            return Ok(());
+        }
+        match wait {
+            PollWait::Timeout => return Err(LinuxError::ETIMEDOUT),
+            PollWait::Interrupted => return Err(LinuxError::EINTR),
+            // 被持有者退出时的清理唤醒，重新争抢
+            PollWait::Ready => {}
+        }
+    }
+}
+
+/// 释放当前线程持有的优先级继承锁，有等待者时直接交给最先等待的线程
+pub fn futex_unlock_pi(uaddr: VirtAddr, key: FutexKey) -> LinuxResult {
+    let word = futex_word(uaddr)?;
+    let tid = current().id().as_u64() as u32;
+    let mut queues = FUTEX_WAIT_TASK.lock();
+    if word.load(Ordering::SeqCst) & FUTEX_TID_MASK != tid {
+        return Err(LinuxError::EPERM);
+    }
+    let Some(waiters) = queues.get_mut(&key) else {
+        word.store(0, Ordering::SeqCst);
+        return Ok(());
+    };
+    let next = waiters.pop_front().unwrap();
+    let waiters = if waiters.is_empty() {
+        queues.remove(&key);
+        0
+    } else {
+        FUTEX_WAITERS
+    };
+    word.store(next.tid as u32 | waiters, Ordering::SeqCst);
+    next.wake();
+    Ok(())
 }
 
 #[derive(Default)]
@@ -55,29 +423,88 @@ impl FutexRobustList {
     }
 }
 
-//```
-// /// 退出的时候清空指针
-// ///
-// /// 若当前线程是主线程，代表进程退出，此时传入的id是进程id，要清除所有进程下的线程
-// ///
-// /// 否则传入的id是线程id
-// pub fn clear_wait(id: u64, leader: bool) {
-//     let mut futex_wait_task = FUTEX_WAIT_TASK.lock();
-//
-//     if leader {
-//         // 清空所有所属进程为指定进程的线程
-//         futex_wait_task.iter_mut().for_each(|(_, tasks)| {
-//             // tasks.drain_filter(|task| task.get_process_id() == id);
-//             tasks.retain(|(task, _)| task.get_process_id() != id);
-//         });
-//     } else {
-//         futex_wait_task.iter_mut().for_each(|(_, tasks)| {
-//             // tasks.drain_filter(|task| task.id().as_u64() == id);
-//             tasks.retain(|(task, _)| task.id().as_u64() != id)
-//         });
-//     }
-//
-//     // 如果一个共享变量不会被线程所使用了，那么直接把他移除
-//     // info!("clean pre keys: {:?}", futex_wait_task.keys());
-//     futex_wait_task.retain(|_, tasks| !tasks.is_empty());
-// }
+/// 用户空间中 robust list 的表头，与 Linux 的`struct robust_list_head`相同
+#[repr(C)]
+pub struct RobustListHead {
+    /// 第一个节点，链表为空时指向表头自己；节点的最低位表示它是优先级继承锁
+    list: usize,
+    /// 节点地址加上它得到节点对应的 futex 变量
+    futex_offset: isize,
+    /// 正在加入或移出链表的节点
+    list_op_pending: usize,
+}
+
+/// 退出的线程还持有`uaddr`处的 robust 锁：标记`FUTEX_OWNER_DIED`并唤醒一个等待者
+///
+/// 等待者可能使用私有或者共享的 key，两种都唤醒；多出的唤醒对 futex 的使用者无害。
+fn handle_futex_death(uaddr: usize, tid: u32, readable: &dyn Fn(usize, usize) -> bool) {
+    if !readable(uaddr, 4) {
+        return;
+    }
+    let Ok(word) = futex_word(uaddr.into()) else {
+        return;
+    };
+    let mut val = word.load(Ordering::SeqCst);
+    while val & FUTEX_TID_MASK == tid {
+        let new = (val & FUTEX_WAITERS) | FUTEX_OWNER_DIED;
+        match word.compare_exchange(val, new, Ordering::SeqCst, Ordering::SeqCst) {
+            Ok(_) => break,
+            Err(actual) => val = actual,
+        }
+    }
+    if val & FUTEX_TID_MASK != tid || val & FUTEX_WAITERS == 0 {
+        return;
+    }
+    for flags in [FUTEX_PRIVATE_FLAG, 0] {
+        if let Ok(key) = get_futex_key(uaddr.into(), flags) {
+            futex_wake(key, 1, FUTEX_BITSET_MATCH_ANY);
+        }
+    }
+}
+
+/// 线程`tid`退出或`execve`：释放它登记的 robust list 中还持有的锁
+///
+/// 必须在还能访问所属进程的地址空间时调用。链表在用户空间中，不可访问的节点
+/// 结束遍历。
+pub fn exit_robust_list(tid: u64) {
+    let Some(list) = ROBUST_LISTS.lock().remove(&tid) else {
+        return;
+    };
+    let process = current_process();
+    let readable =
+        |addr: usize, size: usize| process.memory_set.lock().is_mapped(addr.into(), size);
+    if list.len != core::mem::size_of::<RobustListHead>()
+        || list.head % core::mem::align_of::<RobustListHead>() != 0
+        || !readable(list.head, list.len)
+    {
+        return;
+    }
+    let head = unsafe { (list.head as *const RobustListHead).read() };
+    let pending = head.list_op_pending & !1;
+    let mut entry = head.list & !1;
+    for _ in 0..ROBUST_LIST_LIMIT {
+        if entry == list.head
+            || entry % core::mem::align_of::<usize>() != 0
+            || !readable(entry, core::mem::size_of::<usize>())
+        {
+            break;
+        }
+        // 先取出下一个节点，释放锁之后节点可能被其他线程修改
+        let next = unsafe { *(entry as *const usize) } & !1;
+        if entry != pending {
+            handle_futex_death(
+                entry.wrapping_add_signed(head.futex_offset),
+                tid as u32,
+                &readable,
+            );
+        }
+        entry = next;
+    }
+    if pending != 0 {
+        handle_futex_death(
+            pending.wrapping_add_signed(head.futex_offset),
+            tid as u32,
+            &readable,
+        );
+    }
+}
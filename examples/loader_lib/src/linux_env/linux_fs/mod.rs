@@ -3,5 +3,6 @@ pub mod config;
 pub mod fd_manager;
 pub mod futex;
 pub mod link;
+pub mod poll_queue;
 pub mod stdio;
 pub mod utils;
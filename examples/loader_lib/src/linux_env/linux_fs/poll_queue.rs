@@ -0,0 +1,132 @@
+//! 等待文件就绪
+//!
+//! 管道、`eventfd`等文件各有一个 [`PollQueue`]，状态改变时通知其中登记的等待者。等待者在
+//! 它关心的每个文件的队列中登记，醒来后重新检查这些文件。socket、标准输入等不会主动通知的
+//! 文件靠定时重新检查。
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use axhal::time::{TimeValue, monotonic_time};
+use axtask::WaitQueue;
+use kspin::SpinNoIrq;
+
+use crate::linux_env::{
+    axfs_ext::api::FileIO, process_ext::api::try_current_process,
+    signal_ext::api::has_pending_signal,
+};
+
+/// 有不会通知的文件时重新检查的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// `wait_ready`的结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PollWait {
+    /// 条件已经满足
+    Ready,
+    /// 到达了截止时间
+    Timeout,
+    /// 有需要处理的信号
+    Interrupted,
+}
+
+/// 一次`wait_ready`中的等待者
+struct Poller {
+    /// 每次通知加一，等待者在检查之前记下它，睡眠时只要它变了就醒来
+    seq: AtomicU64,
+    wq: WaitQueue,
+}
+
+/// 在某个文件上等待的任务
+pub struct PollQueue {
+    pollers: SpinNoIrq<Vec<Arc<Poller>>>,
+}
+
+impl PollQueue {
+    pub const fn new() -> Self {
+        Self {
+            pollers: SpinNoIrq::new(Vec::new()),
+        }
+    }
+
+    /// 文件状态改变，唤醒所有登记的等待者重新检查
+    ///
+    /// 调用时不能持有等待者在检查时需要的锁。
+    pub fn notify(&self) {
+        for poller in self.pollers.lock().iter() {
+            poller.seq.fetch_add(1, Ordering::Release);
+            poller.wq.notify_one(false);
+        }
+    }
+
+    fn register(&self, poller: &Arc<Poller>) {
+        self.pollers.lock().push(poller.clone());
+    }
+
+    fn unregister(&self, poller: &Arc<Poller>) {
+        self.pollers.lock().retain(|p| !Arc::ptr_eq(p, poller));
+    }
+}
+
+/// 阻塞直到`ready`返回`true`、到达截止时间`deadline`或者当前线程有需要处理的信号
+///
+/// `files`是`ready`检查的文件，它们的通知唤醒等待者；其中有不会通知的文件时再定时
+/// 重新检查。`deadline`是`monotonic_time`下的绝对时间，为`None`时不会超时。
+pub fn wait_ready<F>(files: &[&dyn FileIO], deadline: Option<TimeValue>, ready: F) -> PollWait
+where
+    F: FnMut() -> bool,
+{
+    let queues: Vec<&PollQueue> = files.iter().filter_map(|file| file.poll_queue()).collect();
+    let timed = queues.len() < files.len();
+    wait_on(&queues, timed, deadline, ready)
+}
+
+/// 同[`wait_ready`]，但直接给出等待的队列。`timed`为真时还要定时重新检查。
+pub fn wait_on<F>(
+    queues: &[&PollQueue],
+    timed: bool,
+    deadline: Option<TimeValue>,
+    mut ready: F,
+) -> PollWait
+where
+    F: FnMut() -> bool,
+{
+    let poller = Arc::new(Poller {
+        seq: AtomicU64::new(0),
+        wq: WaitQueue::new(),
+    });
+    // 信号也会唤醒等待者
+    let process = try_current_process();
+    let signal_queue = process.as_ref().map(|process| process.signal_poll());
+    let all_queues = || queues.iter().copied().chain(signal_queue);
+    all_queues().for_each(|queue| queue.register(&poller));
+    let result = loop {
+        let seq = poller.seq.load(Ordering::Acquire);
+        if ready() {
+            break PollWait::Ready;
+        }
+        if has_pending_signal() {
+            break PollWait::Interrupted;
+        }
+        let mut dur = timed.then_some(POLL_INTERVAL);
+        if let Some(deadline) = deadline {
+            let now = monotonic_time();
+            if now >= deadline {
+                break PollWait::Timeout;
+            }
+            dur = Some(dur.map_or(deadline - now, |dur| dur.min(deadline - now)));
+        }
+        // 检查之后到来的通知改变了`seq`，不会丢失
+        let notified = || poller.seq.load(Ordering::Acquire) != seq;
+        match dur {
+            Some(dur) => {
+                poller.wq.wait_timeout_until(dur, notified);
+            }
+            None => poller.wq.wait_until(notified),
+        }
+    };
+    all_queues().for_each(|queue| queue.unregister(&poller));
+    result
+}
@@ -1,14 +1,24 @@
-use crate::linux_env::axfs_ext::api::{
-    ConsoleWinSize, FIOCLEX, FileExt, FileIO, FileIOType, OpenFlags, TCGETS, TIOCGPGRP, TIOCGWINSZ,
-    TIOCSPGRP,
+//! 控制台的标准输入输出
+//!
+//! 三者共用一个[`LineDiscipline`]：规范模式下按行读取，支持退格、`^W`、`^U`编辑和回显；
+//! 程序可以用`TCSETS`切换到原始模式。`^C`/`^\`/`^Z`产生的信号发给前台进程
+//! （`TIOCSPGRP`设置，未设置时为当前进程）。
+use crate::linux_env::{
+    axfs_ext::api::{
+        FIOCLEX, FIONREAD, FileExt, FileIO, FileIOType, OpenFlags, TCFLSH, TCGETS, TCSETS, TCSETSF,
+        TCSETSW, TIOCGPGRP, TIOCGWINSZ, TIOCSPGRP, TIOCSWINSZ,
+    },
+    process_ext::api::current_process,
+    signal_ext::api::{has_pending_signal, send_signal_to_current, send_signal_to_process},
 };
-use alloc::string::String;
 use axerrno::{AxError, AxResult};
 use axhal::console::{read_bytes, write_bytes};
 use axio::{Read, Seek, SeekFrom, Write};
-use axlog::warn;
 use axsync::Mutex;
 use axtask::yield_now;
+use axtty::{LineDiscipline, Termios, TtySignal, WinSize};
+use core::sync::atomic::{AtomicU64, Ordering};
+
 /// `stdin` file for getting chars from console
 pub struct Stdin {
     pub flags: Mutex<OpenFlags>,
@@ -24,75 +34,116 @@ pub struct Stderr {
     pub flags: Mutex<OpenFlags>,
 }
 
-pub const LF: u8 = 0x0au8;
-pub const CR: u8 = 0x0du8;
-pub const DL: u8 = 0x7fu8;
-pub const BS: u8 = 0x08u8;
-
-pub const SPACE: u8 = 0x20u8;
-
-pub const BACKSPACE: [u8; 3] = [BS, SPACE, BS];
-
-fn stdin_read(buf: &mut [u8]) -> AxResult<usize> {
-    // `busybox`
-    if buf.len() == 1 {
-        let mut ch = [0u8; 1];
-        loop {
-            let read_len = read_bytes(&mut ch);
-            if read_len > 0 {
-                break;
-            }
-            yield_now();
+/// 控制台的行规程
+static TTY: Mutex<LineDiscipline> = Mutex::new(LineDiscipline::new());
+
+/// 前台进程号，为 0 时表示当前进程
+static FOREGROUND: AtomicU64 = AtomicU64::new(0);
+
+/// `TCFLSH`的参数：丢弃未读的输入
+const TCIFLUSH: usize = 0;
+/// `TCFLSH`的参数：同时丢弃输入和未写出的输出，输出总是立即写出
+const TCIOFLUSH: usize = 2;
+
+/// 把控制台收到的字节交给行规程处理，并发送其中产生的信号
+fn poll_console() {
+    let mut ch = [0u8; 1];
+    while read_bytes(&mut ch) > 0 {
+        let signal = TTY.lock().input(ch[0], write_bytes);
+        if let Some(signal) = signal {
+            send_tty_signal(signal);
         }
-        unsafe {
-            buf.as_mut_ptr().write_volatile(ch[0]);
+    }
+}
+
+fn send_tty_signal(signal: TtySignal) {
+    let pid = FOREGROUND.load(Ordering::Acquire);
+    if pid == 0 || send_signal_to_process(pid, signal.signo()).is_err() {
+        send_signal_to_current(signal.signo());
+    }
+}
+
+/// 等到行规程中有可读的输入。有待处理的信号或非阻塞时返回`WouldBlock`
+fn stdin_read(buf: &mut [u8], nonblock: bool) -> AxResult<usize> {
+    loop {
+        poll_console();
+        let mut tty = TTY.lock();
+        if tty.can_read(buf.len()) {
+            return Ok(tty.read(buf));
         }
-        Ok(1)
-    } else {
-        // User application
-        let mut line = String::new();
-        let mut ch = [0u8; 1];
-        loop {
-            let read_len = read_bytes(&mut ch);
-            if read_len > 0 {
-                match ch[0] {
-                    LF | CR => {
-                        // Convert '\r' to '\n'
-                        line.push('\n');
-                        write_bytes(b"\n");
-                        break;
-                    }
-                    BS | DL => {
-                        if !line.is_empty() {
-                            write_bytes(&BACKSPACE);
-                            line.pop();
-                        }
-                    }
-                    _ => {
-                        // Echo
-                        write_bytes(&[ch[0]]);
-                        line.push(ch[0] as char);
-                    }
-                }
-            } else {
-                yield_now();
-                continue;
-            }
+        drop(tty);
+        if nonblock || has_pending_signal() {
+            return Err(AxError::WouldBlock);
         }
-        let len = line.len();
-        buf[..len].copy_from_slice(line.as_bytes());
-        Ok(len)
+        yield_now();
     }
 }
 
+fn stdin_ready() -> bool {
+    poll_console();
+    TTY.lock().can_read(1)
+}
+
 fn stdout_write(buf: &[u8]) -> AxResult<usize> {
-    write_bytes(buf);
+    TTY.lock().output(buf, write_bytes);
     Ok(buf.len())
 }
 
+fn user_ptr<T>(data: usize) -> AxResult<*mut T> {
+    match data {
+        0 => Err(AxError::BadAddress),
+        ptr => Ok(ptr as *mut T),
+    }
+}
+
+/// 三个标准文件共用的终端`ioctl`
+fn tty_ioctl(request: usize, data: usize) -> AxResult<isize> {
+    match request {
+        TCGETS => unsafe { *user_ptr::<Termios>(data)? = TTY.lock().termios() },
+        TCSETS | TCSETSW => {
+            let termios = unsafe { *user_ptr::<Termios>(data)? };
+            TTY.lock().set_termios(termios);
+        }
+        TCSETSF => {
+            let termios = unsafe { *user_ptr::<Termios>(data)? };
+            let mut tty = TTY.lock();
+            tty.flush_input();
+            tty.set_termios(termios);
+        }
+        TIOCGWINSZ => unsafe { *user_ptr::<WinSize>(data)? = TTY.lock().winsize() },
+        TIOCSWINSZ => {
+            let winsize = unsafe { *user_ptr::<WinSize>(data)? };
+            TTY.lock().set_winsize(winsize);
+        }
+        TIOCGPGRP => {
+            let pid = match FOREGROUND.load(Ordering::Acquire) {
+                0 => current_process().pid(),
+                pid => pid,
+            };
+            unsafe { *user_ptr::<u32>(data)? = pid as u32 };
+        }
+        TIOCSPGRP => {
+            let pid = unsafe { *user_ptr::<u32>(data)? };
+            FOREGROUND.store(pid as u64, Ordering::Release);
+        }
+        FIONREAD => {
+            poll_console();
+            unsafe { *user_ptr::<i32>(data)? = TTY.lock().available() as i32 };
+        }
+        TCFLSH => match data {
+            TCIFLUSH | TCIOFLUSH => TTY.lock().flush_input(),
+            1 => {}
+            _ => return Err(AxError::InvalidInput),
+        },
+        FIOCLEX => {}
+        _ => return Err(AxError::Unsupported),
+    }
+    Ok(0)
+}
+
 impl Read for Stdin {
     fn read(&mut self, buf: &mut [u8]) -> AxResult<usize> {
-        stdin_read(buf)
+        stdin_read(buf, false)
     }
 }
 
@@ -125,7 +176,7 @@ impl FileExt for Stdin {
 
 impl FileIO for Stdin {
     fn read(&self, buf: &mut [u8]) -> AxResult<usize> {
-        stdin_read(buf)
+        stdin_read(buf, self.get_status().contains(OpenFlags::NON_BLOCK))
     }
 
     fn get_type(&self) -> FileIOType {
@@ -133,7 +184,7 @@ impl FileIO for Stdin {
     }
 
     fn ready_to_read(&self) -> bool {
-        true
+        stdin_ready()
     }
 
     fn ready_to_write(&self) -> bool {
@@ -153,29 +204,7 @@ impl FileIO for Stdin {
     }
 
     fn ioctl(&self, request: usize, data: usize) -> AxResult<isize> {
-        match request {
-            TIOCGWINSZ => {
-                let winsize = data as *mut ConsoleWinSize;
-                unsafe {
-                    *winsize = ConsoleWinSize::default();
-                }
-                Ok(0)
-            }
-            TCGETS | TIOCSPGRP => {
-                warn!("stdin TCGETS | TIOCSPGRP, pretend to be tty.");
-                // pretend to be tty
-                Ok(0)
-            }
-            TIOCGPGRP => {
-                warn!("stdin TIOCGPGRP, pretend to be have a tty process group.");
-                unsafe {
-                    *(data as *mut u32) = 0;
-                }
-                Ok(0)
-            }
-            FIOCLEX => Ok(0),
-            _ => Err(AxError::Unsupported),
-        }
+        tty_ioctl(request, data)
     }
 
     fn set_status(&self, flags: OpenFlags) -> bool {
@@ -210,8 +239,7 @@ impl Read for Stdout {
 
 impl Write for Stdout {
     fn write(&mut self, buf: &[u8]) -> AxResult<usize> {
-        write_bytes(buf);
-        Ok(buf.len())
+        stdout_write(buf)
     }
     fn flush(&mut self) -> AxResult {
         // `stdout` is always flushed
@@ -294,29 +322,7 @@ impl FileIO for Stdout {
     }
 
     fn ioctl(&self, request: usize, data: usize) -> AxResult<isize> {
-        match request {
-            TIOCGWINSZ => {
-                let winsize = data as *mut ConsoleWinSize;
-                unsafe {
-                    *winsize = ConsoleWinSize::default();
-                }
-                Ok(0)
-            }
-            TCGETS | TIOCSPGRP => {
-                warn!("stdout TCGETS | TIOCSPGRP, pretend to be tty.");
-                // pretend to be tty
-                Ok(0)
-            }
-            TIOCGPGRP => {
-                warn!("stdout TIOCGPGRP, pretend to be have a tty process group.");
-                unsafe {
-                    *(data as *mut u32) = 0;
-                }
-                Ok(0)
-            }
-            FIOCLEX => Ok(0),
-            _ => Err(AxError::Unsupported),
-        }
+        tty_ioctl(request, data)
     }
 }
 
@@ -328,8 +334,7 @@ impl Read for Stderr {
 
 impl Write for Stderr {
     fn write(&mut self, buf: &[u8]) -> AxResult<usize> {
-        write_bytes(buf);
-        Ok(buf.len())
+        stdout_write(buf)
     }
 
     /// Stderr is always flushed
@@ -358,8 +363,7 @@ impl FileExt for Stderr {
 
 impl FileIO for Stderr {
     fn write(&self, buf: &[u8]) -> AxResult<usize> {
-        write_bytes(buf);
-        Ok(buf.len())
+        stdout_write(buf)
     }
 
     /// Stderr is always flushed
@@ -392,29 +396,6 @@ impl FileIO for Stderr {
     }
 
     fn ioctl(&self, request: usize, data: usize) -> AxResult<isize> {
-        match request {
-            TIOCGWINSZ => {
-                let winsize = data as *mut ConsoleWinSize;
-                unsafe {
-                    *winsize = ConsoleWinSize::default();
-                }
-                Ok(0)
-            }
-            TCGETS | TIOCSPGRP => {
-                warn!("stderr TCGETS | TIOCSPGRP, pretend to be tty.");
-                // pretend to be tty
-                Ok(0)
-            }
-
-            TIOCGPGRP => {
-                warn!("stderr TIOCGPGRP, pretend to be have a tty process group.");
-                unsafe {
-                    *(data as *mut u32) = 0;
-                }
-                Ok(0)
-            }
-            FIOCLEX => Ok(0),
-            _ => Err(AxError::Unsupported),
-        }
+        tty_ioctl(request, data)
     }
 }
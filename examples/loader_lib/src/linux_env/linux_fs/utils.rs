@@ -1,12 +1,17 @@
-use crate::linux_env::{
-    axfs_ext::api::FileIOType,
-    linux_fs::{
-        fd_manager::FDM,
-        link::{AT_FDCWD, FilePath, raw_ptr_to_ref_str},
+use crate::{
+    linux_env::{
+        axfs_ext::api::FileIOType,
+        linux_fs::{
+            fd_manager::current_fd_manager,
+            link::{AT_FDCWD, FilePath, raw_ptr_to_ref_str},
+        },
+        process_ext::api::current_process,
     },
+    syscall::SyscallError,
 };
 use alloc::{format, string::ToString};
-use axfs::api::Permissions;
+use axerrno::AxError;
+use axfs::api::{Permissions, check_access, file_access};
 use axlog::debug;
 
 /// The error type used by `utils`.
@@ -73,7 +78,7 @@ pub fn deal_path(
             return Err(UtilsError::NULL);
         }
         const START_ADDR: usize = 0xffffffc080100000;
-        const END_ADDR: usize = 0xffffffc0ffffffff;
+        const END_ADDR: usize = crate::config::USER_SPACE_END;
         // FIX: 检查指针是否被分配，检查是不是在范围内，并返回错误
         if (path_addr as usize).lt(&START_ADDR) || (path_addr as usize).ge(&END_ADDR) {
             return Err(UtilsError::CannotAcce);
@@ -87,7 +92,8 @@ pub fn deal_path(
         return Err(UtilsError::StrTooLong);
     } else if !path.starts_with('/') && dir_fd != AT_FDCWD && dir_fd as u32 != AT_FDCWD as u32 {
         // 如果不是绝对路径, 且dir_fd不是AT_FDCWD, 则需要将dir_fd和path拼接起来
-        let fd_table = FDM.fd_table.lock();
+        let fd_manager = current_fd_manager();
+        let fd_table = fd_manager.fd_table.lock();
         if dir_fd >= fd_table.len() {
             debug!(
                 "dir_fd out of the fd_table bound.(pathname is relative but dirfd is neither AT_FDCWD nor a valid file descriptor)return EBADF"
@@ -143,3 +149,29 @@ pub fn has_permission(mode: Permissions, perm: Permissions) -> bool {
     }
     true
 }
+
+/// 检查当前进程能否在绝对路径`path`所在的目录中创建或删除项，需要对目录有写和搜索权限。
+///
+/// `remove`时还检查粘滞位：目录设置了粘滞位时，非 root 用户只能删除属于自己的项，
+/// 或者自己的目录中的项
+pub fn check_parent_writable(path: &str, remove: bool) -> Result<(), SyscallError> {
+    let path = path.trim_end_matches('/');
+    let parent = match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(pos) => &path[..pos],
+    };
+    let cred = current_process().cred();
+    match check_access(parent, &cred.fs_credentials(), 0o3) {
+        Ok(()) => {}
+        Err(AxError::PermissionDenied) => return Err(SyscallError::EACCES),
+        Err(_) => return Err(SyscallError::ENOENT),
+    }
+    if remove && !cred.is_root() {
+        let dir = file_access(parent).map_err(|_| SyscallError::ENOENT)?;
+        let owner = file_access(path).map_or(cred.euid, |access| access.uid);
+        if dir.mode & 0o1000 != 0 && dir.uid != cred.euid && owner != cred.euid {
+            return Err(SyscallError::EPERM);
+        }
+    }
+    Ok(())
+}
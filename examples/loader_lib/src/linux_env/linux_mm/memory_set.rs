@@ -0,0 +1,557 @@
+//! 客户程序通过`mmap`/`brk`获得的内存区域
+//!
+//! 每个进程有自己的`axmm`地址空间，`mmap`区域、堆、程序镜像和栈都放在线性映射之外的
+//! 私有窗口中（见`config.rs`），其余部分与内核共享。所有区域都由`axmm`的分配映射支撑：
+//! 匿名区域首次访问某页时才在缺页处理中分配清零的页帧，文件映射则在建立时读入内容。
+//!
+//! 链接了 mocklibc 的客户程序运行在 S 模式，陷入时陷入帧直接压在当前栈上，所以栈所在的页
+//! 绝不能缺页：栈用[`MemorySet::map_eager`]映射，页帧在映射时分配，`fork`时立即复制。
+//! 普通的 Linux 程序运行在用户态（见[`MemorySet::set_user`]），镜像装载在低半部分的
+//! [`USER_IMAGE_BASE`]～[`USER_IMAGE_END`]中，所有区域都带有`USER`权限。
+use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use axerrno::{AxError, AxResult, ax_err};
+use axhal::{
+    arch::flush_tlb,
+    mem::{MemoryAddr, PAGE_SIZE_4K, PhysAddr, VirtAddr, phys_to_virt},
+    paging::MappingFlags,
+};
+use axlog::{debug, warn};
+use axmm::AddrSpace;
+use kspin::SpinNoIrq;
+
+use crate::{
+    config::{USER_IMAGE_BASE, USER_IMAGE_END, USER_SPACE_BASE, USER_SPACE_END},
+    linux_env::axfs_ext::api::{FileIO, SeekFrom},
+};
+
+/// 文件映射的后端
+#[derive(Clone)]
+pub struct FileBackend {
+    pub file: Arc<dyn FileIO>,
+    /// 区域起始处对应的文件偏移
+    pub offset: usize,
+}
+
+impl FileBackend {
+    /// 在不改变文件当前偏移的前提下，从`offset`处读入
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> AxResult<usize> {
+        let old = self.file.seek(SeekFrom::Current(0))?;
+        let ret = self
+            .file
+            .seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| self.file.read(buf));
+        self.file.seek(SeekFrom::Start(old))?;
+        ret
+    }
+
+    /// 在不改变文件当前偏移的前提下，写到`offset`处
+    fn write_at(&self, offset: usize, buf: &[u8]) -> AxResult<usize> {
+        let old = self.file.seek(SeekFrom::Current(0))?;
+        let ret = self
+            .file
+            .seek(SeekFrom::Start(offset as u64))
+            .and_then(|_| self.file.write(buf));
+        self.file.seek(SeekFrom::Start(old))?;
+        ret
+    }
+}
+
+/// 一段连续的映射区域
+#[derive(Clone)]
+pub struct MapArea {
+    pub start: VirtAddr,
+    size: usize,
+    pub flags: MappingFlags,
+    /// `MAP_SHARED`：修改需要写回文件
+    pub shared: bool,
+    /// 页帧在映射时分配，`fork`时立即复制而不是写时复制
+    eager: bool,
+    pub backend: Option<FileBackend>,
+}
+
+impl MapArea {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn end(&self) -> VirtAddr {
+        self.start + self.size()
+    }
+
+    /// 把区域从`addr`处一分为二，返回后半部分
+    fn split_off(&mut self, addr: VirtAddr) -> MapArea {
+        let offset = addr.as_usize() - self.start.as_usize();
+        let backend = self.backend.clone().map(|mut b| {
+            b.offset += offset;
+            b
+        });
+        let tail = MapArea {
+            start: addr,
+            size: self.size - offset,
+            flags: self.flags,
+            shared: self.shared,
+            eager: self.eager,
+            backend,
+        };
+        self.size = offset;
+        tail
+    }
+
+    /// 把共享文件映射中`[start, end)`部分的内容写回文件
+    fn sync(&self, aspace: &SpinNoIrq<AddrSpace>, start: VirtAddr, end: VirtAddr) -> AxResult {
+        let Some(backend) = self.backend.as_ref().filter(|_| self.shared) else {
+            return Ok(());
+        };
+        let mut page = vec![0; PAGE_SIZE_4K];
+        let mut vaddr = start.align_down_4k();
+        while vaddr < end {
+            // 写文件时可能再次访问地址空间，不能一直持有锁
+            aspace.lock().read(vaddr, &mut page)?;
+            let offset = vaddr.as_usize() - self.start.as_usize();
+            backend.write_at(backend.offset + offset, &page)?;
+            vaddr += PAGE_SIZE_4K;
+        }
+        backend.file.flush()
+    }
+}
+
+/// 区域按起始地址排列，互不重叠
+pub struct MemorySet {
+    aspace: Arc<SpinNoIrq<AddrSpace>>,
+    areas: BTreeMap<VirtAddr, MapArea>,
+    /// 堆（`brk`）的当前顶端，0 表示还未初始化
+    heap_top: usize,
+    /// 程序运行在用户态，区域都要能从用户态访问
+    user: bool,
+    /// 程序镜像按链接地址装载在内核地址空间中，不属于这个地址空间，无法为子进程复制
+    fixed_image: bool,
+}
+
+impl MemorySet {
+    /// 创建一个只含内核映射的新地址空间
+    pub fn new() -> AxResult<Self> {
+        let mut aspace = axmm::new_user_aspace(
+            VirtAddr::from(USER_SPACE_BASE),
+            USER_SPACE_END - USER_SPACE_BASE,
+        )?;
+        aspace.set_user_range(
+            VirtAddr::from(USER_IMAGE_BASE),
+            USER_IMAGE_END - USER_IMAGE_BASE,
+        )?;
+        Ok(Self {
+            aspace: Arc::new(SpinNoIrq::new(aspace)),
+            areas: BTreeMap::new(),
+            heap_top: 0,
+            user: false,
+            fixed_image: false,
+        })
+    }
+
+    /// 程序将在用户态运行：之后建立的区域都带有`USER`权限。要在建立任何区域之前调用。
+    pub fn set_user(&mut self) {
+        self.user = true;
+    }
+
+    /// 程序是否运行在用户态
+    pub fn is_user(&self) -> bool {
+        self.user
+    }
+
+    /// 程序镜像装载在内核地址空间中的固定位置（链接了 mocklibc 的静态程序）
+    pub fn set_fixed_image(&mut self) {
+        self.fixed_image = true;
+    }
+
+    /// 程序镜像不在这个地址空间中，`fork`后父子进程会共享它的可写数据，所以不能`fork`
+    pub fn has_fixed_image(&self) -> bool {
+        self.fixed_image
+    }
+
+    /// 用户态程序的区域加上`USER`权限
+    fn area_flags(&self, flags: MappingFlags) -> MappingFlags {
+        match self.user {
+            true => flags | MappingFlags::USER,
+            false => flags,
+        }
+    }
+
+    /// 区域所在的地址空间
+    pub fn aspace(&self) -> &Arc<SpinNoIrq<AddrSpace>> {
+        &self.aspace
+    }
+
+    /// 页表根的物理地址
+    pub fn page_table_root(&self) -> PhysAddr {
+        self.aspace.lock().page_table_root()
+    }
+
+    /// 为子进程复制一份：所有已分配的页以写时复制的方式共享
+    ///
+    /// 共享文件映射在子进程中同样变为写时复制，不再与父进程共享修改。
+    pub fn fork(&self) -> AxResult<Self> {
+        if self.fixed_image {
+            return ax_err!(Unsupported);
+        }
+        let child = Self::new()?;
+        self.aspace.lock().share_cow(&mut child.aspace.lock())?;
+        for area in self.areas.values().filter(|area| area.eager) {
+            for offset in (0..area.size()).step_by(PAGE_SIZE_4K) {
+                let vaddr = area.start + offset;
+                // 模拟一次写访问，双方各自得到私有的页帧
+                child
+                    .aspace
+                    .lock()
+                    .handle_page_fault(vaddr, MappingFlags::WRITE);
+                self.aspace
+                    .lock()
+                    .handle_page_fault(vaddr, MappingFlags::WRITE);
+            }
+        }
+        Ok(Self {
+            areas: self.areas.clone(),
+            heap_top: self.heap_top,
+            user: self.user,
+            fixed_image: false,
+            ..child
+        })
+    }
+
+    /// 在`limit`范围内找一段大小为`size`、不与已有区域重叠的空闲空间，`hint`可用时优先使用
+    pub fn find_free_area(
+        &self,
+        hint: VirtAddr,
+        size: usize,
+        limit: (VirtAddr, VirtAddr),
+    ) -> Option<VirtAddr> {
+        let (base, end) = limit;
+        if hint >= base && hint + size <= end && self.is_free(hint, size) {
+            return Some(hint);
+        }
+        let mut cursor = base;
+        for area in self.areas.values() {
+            if area.end() <= cursor {
+                continue;
+            }
+            if area.start >= cursor + size {
+                break;
+            }
+            cursor = area.end();
+        }
+        (cursor + size <= end).then_some(cursor)
+    }
+
+    fn is_free(&self, start: VirtAddr, size: usize) -> bool {
+        let end = start + size;
+        !self
+            .areas
+            .values()
+            .any(|area| area.start < end && start < area.end())
+    }
+
+    /// 检查`[start, start + size)`是否完全被已有区域覆盖
+    pub fn is_mapped(&self, start: VirtAddr, size: usize) -> bool {
+        let end = start + size;
+        let mut cursor = start;
+        for area in self.areas.values() {
+            if area.end() <= cursor || area.start >= end {
+                continue;
+            }
+            if area.start > cursor {
+                return false;
+            }
+            cursor = area.end();
+        }
+        cursor >= end
+    }
+
+    /// `addr`是否落在`MAP_SHARED`区域中
+    pub fn is_shared(&self, addr: VirtAddr) -> bool {
+        self.areas
+            .range(..=addr)
+            .next_back()
+            .is_some_and(|(_, area)| area.shared && addr < area.end())
+    }
+
+    /// 若`addr`落在某个区域内部，就在`addr`处把它拆开
+    fn split_at(&mut self, addr: VirtAddr) {
+        let Some((_, area)) = self.areas.range_mut(..addr).next_back() else {
+            return;
+        };
+        if area.end() > addr {
+            let tail = area.split_off(addr);
+            self.areas.insert(addr, tail);
+        }
+    }
+
+    /// 新建一个区域
+    ///
+    /// 匿名区域只登记到地址空间中，访问时才分配页帧；文件映射则立即逐页分配物理页帧并
+    /// 填入文件内容。
+    pub fn map(
+        &mut self,
+        start: VirtAddr,
+        size: usize,
+        flags: MappingFlags,
+        shared: bool,
+        backend: Option<FileBackend>,
+    ) -> AxResult {
+        self.map_area(start, size, flags, shared, false, backend)
+    }
+
+    /// 新建一个匿名区域，立即分配所有页帧，用于栈和用户态程序的镜像
+    pub fn map_eager(&mut self, start: VirtAddr, size: usize, flags: MappingFlags) -> AxResult {
+        self.map_area(start, size, flags, false, true, None)
+    }
+
+    fn map_area(
+        &mut self,
+        start: VirtAddr,
+        size: usize,
+        flags: MappingFlags,
+        shared: bool,
+        eager: bool,
+        backend: Option<FileBackend>,
+    ) -> AxResult {
+        let flags = self.area_flags(flags);
+        debug!(
+            "[MM] map [{:#x}, {:#x}) {:?} shared {}",
+            start.as_usize(),
+            start.as_usize() + size,
+            flags,
+            shared
+        );
+        let populate = eager || backend.is_some();
+        self.aspace.lock().map_alloc(start, size, flags, populate)?;
+        if let Some(backend) = &backend {
+            let mut page = vec![0; PAGE_SIZE_4K];
+            let result = (0..size / PAGE_SIZE_4K).try_for_each(|i| {
+                // 超出文件末尾的部分保持为 0
+                page.fill(0);
+                backend.read_at(backend.offset + i * PAGE_SIZE_4K, &mut page)?;
+                self.aspace.lock().write(start + i * PAGE_SIZE_4K, &page)
+            });
+            if let Err(e) = result {
+                let _ = self.aspace.lock().unmap(start, size);
+                return Err(e);
+            }
+        }
+        self.areas.insert(start, MapArea {
+            start,
+            size,
+            flags,
+            shared,
+            eager,
+            backend,
+        });
+        Ok(())
+    }
+
+    /// 在`limit`范围内任选一段空闲空间建立匿名区域，返回其起始地址
+    pub fn map_anywhere(
+        &mut self,
+        size: usize,
+        flags: MappingFlags,
+        limit: (VirtAddr, VirtAddr),
+    ) -> AxResult<VirtAddr> {
+        let size = VirtAddr::from(size).align_up_4k().as_usize();
+        let start = self
+            .find_free_area(limit.0, size, limit)
+            .ok_or(AxError::NoMemory)?;
+        self.map(start, size, flags, false, None)?;
+        Ok(start)
+    }
+
+    /// 解除`[start, start + size)`中的所有映射，共享文件映射先写回
+    pub fn unmap(&mut self, start: VirtAddr, size: usize) -> AxResult {
+        let end = start + size;
+        self.split_at(start);
+        self.split_at(end);
+        let keys: Vec<VirtAddr> = self.areas.range(start..end).map(|(k, _)| *k).collect();
+        for key in keys {
+            let area = self.areas.remove(&key).unwrap();
+            if let Err(e) = area.sync(&self.aspace, area.start, area.end()) {
+                warn!(
+                    "[MM] write back {:#x} failed: {:?}",
+                    area.start.as_usize(),
+                    e
+                );
+            }
+            // 已分配的页帧由`axmm`释放
+            let _ = self.aspace.lock().unmap(area.start, area.size());
+        }
+        flush_tlb(None);
+        Ok(())
+    }
+
+    /// 解除所有区域，进程退出或`execve`时调用
+    pub fn clear(&mut self) {
+        let _ = self.unmap(
+            VirtAddr::from(USER_IMAGE_BASE),
+            USER_IMAGE_END - USER_IMAGE_BASE,
+        );
+        let _ = self.unmap(
+            VirtAddr::from(USER_SPACE_BASE),
+            USER_SPACE_END - USER_SPACE_BASE,
+        );
+        self.heap_top = 0;
+        self.user = false;
+        self.fixed_image = false;
+    }
+
+    /// 修改`[start, start + size)`的访问权限，范围必须已全部映射
+    pub fn protect(&mut self, start: VirtAddr, size: usize, flags: MappingFlags) -> AxResult {
+        if !self.is_mapped(start, size) {
+            return ax_err!(NoMemory);
+        }
+        let flags = self.area_flags(flags);
+        let end = start + size;
+        self.split_at(start);
+        self.split_at(end);
+        let mut aspace = self.aspace.lock();
+        for area in self.areas.range_mut(start..end).map(|(_, area)| area) {
+            area.flags = flags;
+            aspace.protect(area.start, area.size(), flags)?;
+        }
+        flush_tlb(None);
+        Ok(())
+    }
+
+    /// 把`[start, start + size)`中共享文件映射的内容写回文件
+    pub fn sync(&self, start: VirtAddr, size: usize) -> AxResult {
+        if !self.is_mapped(start, size) {
+            return ax_err!(NoMemory);
+        }
+        let end = start + size;
+        for area in self.areas.values() {
+            if area.start < end && start < area.end() {
+                area.sync(&self.aspace, start.max(area.start), end.min(area.end()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 调整`[old, old + old_size)`的大小。原区域必须是单独一个区域。
+    ///
+    /// 缩小时直接解除尾部；扩大时优先原地扩展，否则在`may_move`时把已分配的页整体搬到
+    /// `limit`内新的空闲位置。返回调整后的起始地址。
+    pub fn remap(
+        &mut self,
+        old: VirtAddr,
+        old_size: usize,
+        new_size: usize,
+        may_move: bool,
+        limit: (VirtAddr, VirtAddr),
+    ) -> AxResult<VirtAddr> {
+        if new_size <= old_size {
+            self.unmap(old + new_size, old_size - new_size)?;
+            return Ok(old);
+        }
+        self.split_at(old);
+        self.split_at(old + old_size);
+        if !self.areas.get(&old).is_some_and(|a| a.size() == old_size) {
+            return ax_err!(BadAddress);
+        }
+        let extra = new_size - old_size;
+        let new_start = if self.is_free(old + old_size, extra) && old + new_size <= limit.1 {
+            old
+        } else if may_move {
+            self.find_free_area(limit.0, new_size, limit)
+                .ok_or(AxError::NoMemory)?
+        } else {
+            return ax_err!(NoMemory);
+        };
+
+        let mut area = self.areas.remove(&old).unwrap();
+        if new_start != old {
+            self.move_area(&area, new_start)?;
+            area.start = new_start;
+        }
+        let tail_offset = area.size();
+        let tail = self.map(
+            area.start + tail_offset,
+            extra,
+            area.flags,
+            area.shared,
+            area.backend.clone().map(|mut b| {
+                b.offset += tail_offset;
+                b
+            }),
+        );
+        if let Err(e) = tail {
+            self.areas.insert(area.start, area);
+            return Err(e);
+        }
+        // 合并刚刚映射的尾部
+        let tail = self.areas.remove(&(area.start + tail_offset)).unwrap();
+        area.size += tail.size;
+        self.areas.insert(area.start, area);
+        Ok(new_start)
+    }
+
+    /// 把区域搬到`to`处，只复制已经分配了页帧的页
+    fn move_area(&self, area: &MapArea, to: VirtAddr) -> AxResult {
+        let populated: Vec<(usize, PhysAddr)> = {
+            let mut aspace = self.aspace.lock();
+            // 先以可写权限建立新区域，复制完成后再改为原来的权限
+            aspace.map_alloc(
+                to,
+                area.size(),
+                MappingFlags::READ | MappingFlags::WRITE,
+                false,
+            )?;
+            (0..area.size() / PAGE_SIZE_4K)
+                .filter_map(|i| {
+                    let (frame, _, _) = aspace
+                        .page_table()
+                        .query(area.start + i * PAGE_SIZE_4K)
+                        .ok()?;
+                    Some((i, frame))
+                })
+                .collect()
+        };
+        // 写入新区域会触发缺页，复制时不能持有地址空间的锁
+        for (i, frame) in populated {
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    phys_to_virt(frame).as_ptr(),
+                    (to + i * PAGE_SIZE_4K).as_mut_ptr(),
+                    PAGE_SIZE_4K,
+                )
+            };
+        }
+        let mut aspace = self.aspace.lock();
+        aspace.protect(to, area.size(), area.flags)?;
+        aspace.unmap(area.start, area.size())?;
+        flush_tlb(None);
+        Ok(())
+    }
+
+    /// 把堆顶设为`brk`，堆位于`[base, base + max_size)`。失败时保持不变，返回当前堆顶。
+    pub fn set_brk(&mut self, brk: usize, base: usize, max_size: usize) -> usize {
+        if self.heap_top == 0 {
+            self.heap_top = base;
+        }
+        if brk < base || brk > base + max_size {
+            return self.heap_top;
+        }
+        let old_end = VirtAddr::from(self.heap_top).align_up_4k();
+        let new_end = VirtAddr::from(brk).align_up_4k();
+        let result = if new_end > old_end {
+            self.map(
+                old_end,
+                new_end.as_usize() - old_end.as_usize(),
+                MappingFlags::READ | MappingFlags::WRITE,
+                false,
+                None,
+            )
+        } else if new_end < old_end {
+            self.unmap(new_end, old_end.as_usize() - new_end.as_usize())
+        } else {
+            Ok(())
+        };
+        if result.is_ok() {
+            self.heap_top = brk;
+        }
+        self.heap_top
+    }
+}
@@ -0,0 +1,77 @@
+//! 客户程序的内存管理
+use axhal::{
+    mem::{MemoryAddr, VirtAddr, virt_to_phys},
+    paging::MappingFlags,
+    trap::{PAGE_FAULT, register_trap_handler},
+};
+use axlog::{debug, warn};
+
+use crate::linux_env::{
+    process_ext::api::{kill_current, try_current_process},
+    signal_ext::signal_no::SignalNo,
+};
+
+pub mod memory_set;
+
+/// 缺页处理：先交给当前进程的地址空间（按需分配和写时复制），再交给内核地址空间
+///
+/// 用户态程序的非法访问以`SIGSEGV`结束当前进程。
+#[register_trap_handler(PAGE_FAULT)]
+fn handle_page_fault(vaddr: VirtAddr, access_flags: MappingFlags, is_user: bool) -> bool {
+    if let Some(process) = try_current_process() {
+        if process
+            .aspace()
+            .lock()
+            .handle_page_fault(vaddr, access_flags)
+        {
+            return true;
+        }
+        if is_user {
+            warn!(
+                "[MM] process {} segfault at {:#x} ({:?})",
+                process.pid(),
+                vaddr.as_usize(),
+                access_flags
+            );
+            drop(process);
+            axhal::arch::enable_irqs();
+            kill_current(SignalNo::SIGSEGV as usize, true);
+        }
+    }
+    axmm::kernel_aspace()
+        .lock()
+        .handle_page_fault(vaddr, access_flags)
+}
+
+/// 把`payload`所在的闪存窗口只读映射到内核地址空间
+///
+/// 它不在`axhal`的内存区域列表中，开启分页后默认没有映射。
+pub fn map_payload(start: usize, size: usize) {
+    let vaddr = VirtAddr::from(start);
+    axmm::kernel_aspace()
+        .lock()
+        .map_linear(vaddr, virt_to_phys(vaddr), size, MappingFlags::READ)
+        .expect("Failed to map payload");
+}
+
+/// 为装载到空闲内存中的程序段加上执行权限
+///
+/// 空闲内存默认只有读写权限，直接跳转过去会触发`PAGE_FAULT`。
+pub fn set_executable(start: usize, size: usize) {
+    let begin = VirtAddr::from(start).align_down_4k();
+    let end = VirtAddr::from(start + size).align_up_4k();
+    debug!(
+        "[MM] executable [{:#x}, {:#x})",
+        begin.as_usize(),
+        end.as_usize()
+    );
+    axmm::kernel_aspace()
+        .lock()
+        .protect(
+            begin,
+            end.as_usize() - begin.as_usize(),
+            MappingFlags::READ | MappingFlags::WRITE | MappingFlags::EXECUTE,
+        )
+        .expect("Failed to set executable");
+    axhal::arch::flush_tlb(None);
+}
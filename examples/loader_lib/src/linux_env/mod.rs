@@ -1,3 +1,7 @@
 pub mod axfs_ext;
+#[cfg(target_arch = "riscv64")]
 pub mod axhal_ext;
 pub mod linux_fs;
+pub mod linux_mm;
+pub mod process_ext;
+pub mod signal_ext;
@@ -1,123 +1,114 @@
-// extern crate alloc;
-// use alloc::{string::ToString, sync::Arc};
-// use axerrno::AxResult;
-// use axhal::{mem::VirtAddr, paging::MappingFlags};
-// use axlog::{debug, info};
-// use axmm::AddrSpace;
-// use axtask::{current, AxTaskRef, CurrentTask, TaskExtRef};
-// use crate::elf::{elf::load_elf, EXEC_ZONE_START};
-//
-// use super::{Process, PID2PC, TID2TASK};
-//
-// /// return the `Arc<Process>` of the current process
-// #[allow(unused)]
-// pub fn current_process() -> Arc<Process> {
-//     let current_task = current();
-//     let current_process = Arc::clone(PID2PC.lock().get(&current_task.task_ext().get_process_id()).unwrap());
-//
-//     current_process
-// }
-//
-// /// Load a user app.
-// ///
-// /// # Returns
-// /// - The first return value is the entry point of the user app.
-// /// - The second return value is the top of the user stack.
-// /// - The third return value is the address space of the user app.
-// pub fn load_user_app(
-//     memory_set: &mut AddrSpace,
-//     app_name: &str,
-//     elf_file: &'static [u8]
-// ) -> AxResult<(VirtAddr, VirtAddr)> {
-//
-//     let elf_info = load_elf(VirtAddr::from(EXEC_ZONE_START), elf_file);
-//     for segement in elf_info.segments {
-//         debug!(
-//             "Mapping ELF segment: [{:#x?}, {:#x?}) flags: {:#x?}",
-//             segement.start_vaddr,
-//             segement.start_vaddr + segement.size,
-//             segement.flags
-//         );
-//         memory_set.map_alloc(segement.start_vaddr, segement.size, segement.flags, true)?;
-//
-//         if segement.data.is_empty() {
-//             continue;
-//         }
-//
-//         memory_set.write(segement.start_vaddr + segement.offset, &segement.data)?;
-//     }
-//
-//     info!("Mapping user stack {:?}", memory_set);
-//
-//     // The user stack is divided into two parts:
-//     // `ustack_start` -> `ustack_pointer`: It is the stack space that users actually read and write.
-//     // `ustack_pointer` -> `ustack_end`: It is the space that contains the arguments, environment variables and auxv passed to the app.
-//     //  When the app starts running, the stack pointer points to `ustack_pointer`.
-//
-//     let ustack_end = VirtAddr::from_usize(EXEC_ZONE_START);
-//     let ustack_size = 0x10000;
-//     let ustack_start = ustack_end - ustack_size;
-//     debug!(
-//         "Mapping user stack: {:#x?} -> {:#x?}",
-//         ustack_start, ustack_end
-//     );
-//
-//     // user-heap-base = "0x3FA0_0000"
-//     // # The base address of the user stack. And the stack bottom is `user-stack-top + max-user-stack-size`.
-//     // user-stack-top = "0x3FE0_0000"
-//     // # The size of the user heap.
-//     // max-user-heap-size = "0x40_0000"
-//
-//     // FIXME: Add more arguments and environment variables
-//     let (stack_data, ustack_pointer) = kernel_elf_parser::get_app_stack_region(
-//         &[app_name.to_string()],
-//         &[],
-//         &elf_info.auxv,
-//         ustack_start,
-//         ustack_size,
-//     );
-//
-//     info!("Mapping user stack data: {:#x?}", ustack_pointer);
-//
-//     memory_set.map_alloc(
-//         ustack_start,
-//         ustack_size,
-//         MappingFlags::READ | MappingFlags::WRITE,
-//         true,
-//     )?;
-//
-//     info!("Writing user stack data");
-//
-//     memory_set.write(VirtAddr::from_usize(ustack_pointer), stack_data.as_slice())?;
-//     Ok((elf_info.entry, VirtAddr::from(ustack_pointer)))
-// }
-//
-// /// 以进程作为中转调用 task 的 yield
-// #[allow(unused)]
-// pub fn yield_now_task() {
-//     axtask::yield_now();
-// }
-//
-// /// 以进程作为中转调用 task 的 sleep
-// #[allow(unused)]
-// pub fn sleep_now_task(dur: core::time::Duration) {
-//     axtask::sleep(dur);
-// }
-//
-// /// current running task
-// #[allow(unused)]
-// pub fn current_task() -> CurrentTask {
-//     axtask::current()
-// }
-//
-// /// 设置当前任务的 clear_child_tid
-// #[allow(unused)]
-// pub fn set_child_tid(tid: usize) {
-//     todo!()
-// }
-//
-// /// Get the task reference by tid
-// #[allow(unused)]
-// pub fn get_task_ref(tid: u64) -> Option<AxTaskRef> {
-//     TID2TASK.lock().get(&tid).cloned()
-// }
+//! 进程相关的操作：查找当前进程、`execve`和退出
+use alloc::{boxed::Box, sync::Arc, vec};
+
+use axlog::debug;
+use axtask::current;
+use lazyinit::LazyInit;
+
+use super::process::{Process, TID2PC};
+use crate::{
+    arch::call_on_stack, config::TASK_STACK_SIZE, linux_env::linux_fs::futex::exit_robust_list,
+    load::Command, syscall::dump_unimplemented_syscalls,
+};
+
+/// 当前任务所属的进程
+pub fn current_process() -> Arc<Process> {
+    try_current_process().expect("Current task does not belong to a process")
+}
+
+/// 当前任务所属的进程，任务不属于任何进程时返回`None`
+pub fn try_current_process() -> Option<Arc<Process>> {
+    let task = axtask::current_may_uninit()?;
+    TID2PC.lock().get(&task.id().as_u64()).cloned()
+}
+
+/// 当前任务内核栈的栈顶
+///
+/// `execve`和退出时要丢弃进程中的栈，之后在这里继续运行。内核栈上原有的调用帧都不会
+/// 再返回，可以直接覆盖。启动任务没有单独分配的内核栈，第一次使用时为它分配一个。
+/// 用户态程序陷入时，寄存器保存在这个栈的顶端。
+pub fn kernel_stack_top() -> usize {
+    static BOOT_TASK_STACK: LazyInit<usize> = LazyInit::new();
+    if let Some(top) = current().kernel_stack_top() {
+        return top.as_usize();
+    }
+    if !BOOT_TASK_STACK.is_inited() {
+        let stack = vec![0u8; TASK_STACK_SIZE].leak();
+        BOOT_TASK_STACK.init_once((stack.as_ptr() as usize + TASK_STACK_SIZE) & !0xf);
+    }
+    *BOOT_TASK_STACK
+}
+
+/// 用`cmd`指定的程序替换当前进程的映像，不再返回
+///
+/// 调用者要先确认程序可以装载：原来的地址空间会先被清空。
+pub fn exec_current(cmd: Command) -> ! {
+    let cmd = Box::into_raw(Box::new(cmd)) as usize;
+    unsafe { call_on_stack(cmd, exec_main, kernel_stack_top()) }
+}
+
+extern "C" fn exec_main(cmd: usize) -> ! {
+    let cmd = unsafe { Box::from_raw(cmd as *mut Command) };
+    exit_robust_list(current().id().as_u64());
+    let process = current_process();
+    process.memory_set.lock().clear();
+    process.unshare_files();
+    process.fd_manager().close_on_exec();
+    process
+        .signals
+        .lock()
+        .reset_on_exec(current().id().as_u64());
+    drop(process);
+    crate::run_program(&cmd);
+    drop(cmd);
+    exit_current(0, true)
+}
+
+/// 结束当前线程；`group`为真时结束整个进程（`exit_group`）
+///
+/// 其他线程无法被强行终止，它们在下一次系统调用返回前退出；进程在最后一个线程退出后
+/// 才释放地址空间并通知父进程。
+/// init 进程退出时整个系统随之结束。
+pub fn exit_current(exit_code: i32, group: bool) -> ! {
+    let process = current_process();
+    if group {
+        process.set_group_exit((exit_code & 0xff) << 8);
+    }
+    if process.is_init() && (group || process.thread_count() == 1) {
+        dump_unimplemented_syscalls();
+        axstd::process::exit(exit_code);
+    }
+    drop(process);
+    unsafe { call_on_stack(exit_code as u32 as usize, exit_main, kernel_stack_top()) }
+}
+
+/// 当前进程被信号`signo`结束，`core`表示默认处理方式要求转储内存
+///
+/// init 进程被结束时整个系统以`128 + signo`退出。
+pub fn kill_current(signo: usize, core: bool) -> ! {
+    let process = current_process();
+    debug!("[Process] {} killed by signal {}", process.pid(), signo);
+    process.set_group_exit(signo as i32 | if core { 0x80 } else { 0 });
+    if process.is_init() {
+        dump_unimplemented_syscalls();
+        axstd::process::exit(128 + signo as i32);
+    }
+    drop(process);
+    exit_current(0, false)
+}
+
+extern "C" fn exit_main(exit_code: usize) -> ! {
+    leave_current(exit_code as i32);
+    axtask::exit(exit_code as i32)
+}
+
+/// 当前任务离开所属进程；它是最后一个线程时进程随之退出
+///
+/// 调用时必须已经不在进程中的栈上运行。线程持有的 robust 锁在切换回内核地址空间前释放。
+pub fn leave_current(exit_code: i32) {
+    exit_robust_list(current().id().as_u64());
+    let process = current_process();
+    if process.detach_current() {
+        process.exit(exit_code);
+    }
+}
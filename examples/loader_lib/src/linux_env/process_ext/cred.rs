@@ -0,0 +1,105 @@
+//! 进程的用户和组身份
+//!
+//! 与 Linux 一样分为实际、有效和保存的用户号/组号，以及附加组。文件的访问检查使用
+//! 有效用户号（见[`Cred::fs_credentials`]），`faccessat`使用实际用户号。init 进程以
+//! root（0）运行，`clone`时子进程复制父进程的身份。
+
+use alloc::vec::Vec;
+
+use axfs::api::Credentials;
+
+use crate::syscall::SyscallError;
+
+/// 附加组数目的上限，即 Linux 的`NGROUPS_MAX`
+pub const NGROUPS_MAX: usize = 65536;
+
+#[derive(Clone, Debug, Default)]
+pub struct Cred {
+    /// 实际用户号
+    pub ruid: u32,
+    /// 有效用户号，决定进程的权限
+    pub euid: u32,
+    /// 保存的用户号，`setuid`后可以切换回它
+    pub suid: u32,
+    pub rgid: u32,
+    pub egid: u32,
+    pub sgid: u32,
+    /// 附加组
+    pub groups: Vec<u32>,
+}
+
+impl Cred {
+    /// 是否有超级用户权限
+    pub fn is_root(&self) -> bool {
+        self.euid == 0
+    }
+
+    /// 是否属于组`gid`
+    pub fn in_group(&self, gid: u32) -> bool {
+        self.egid == gid || self.groups.contains(&gid)
+    }
+
+    /// 访问文件时使用的身份
+    pub fn fs_credentials(&self) -> Credentials {
+        Credentials {
+            uid: self.euid,
+            gid: self.egid,
+            groups: self.groups.clone(),
+        }
+    }
+
+    /// `faccessat`检查时使用的身份
+    pub fn real_credentials(&self) -> Credentials {
+        Credentials {
+            uid: self.ruid,
+            gid: self.rgid,
+            groups: self.groups.clone(),
+        }
+    }
+
+    /// `setresuid`，`None`表示不改变。非 root 只能设为实际、有效或保存的用户号之一。
+    ///
+    /// 与 Linux 相同，`setuid`/`setreuid`都归结到这里
+    pub fn set_uids(
+        &mut self,
+        ruid: Option<u32>,
+        euid: Option<u32>,
+        suid: Option<u32>,
+    ) -> Result<(), SyscallError> {
+        let allowed = |id: u32| id == self.ruid || id == self.euid || id == self.suid;
+        if !self.is_root()
+            && [ruid, euid, suid]
+                .into_iter()
+                .flatten()
+                .any(|id| !allowed(id))
+        {
+            return Err(SyscallError::EPERM);
+        }
+        self.ruid = ruid.unwrap_or(self.ruid);
+        self.euid = euid.unwrap_or(self.euid);
+        self.suid = suid.unwrap_or(self.suid);
+        Ok(())
+    }
+
+    /// `setresgid`，规则同[`Cred::set_uids`]
+    pub fn set_gids(
+        &mut self,
+        rgid: Option<u32>,
+        egid: Option<u32>,
+        sgid: Option<u32>,
+    ) -> Result<(), SyscallError> {
+        let allowed = |id: u32| id == self.rgid || id == self.egid || id == self.sgid;
+        if !self.is_root()
+            && [rgid, egid, sgid]
+                .into_iter()
+                .flatten()
+                .any(|id| !allowed(id))
+        {
+            return Err(SyscallError::EPERM);
+        }
+        self.rgid = rgid.unwrap_or(self.rgid);
+        self.egid = egid.unwrap_or(self.egid);
+        self.sgid = sgid.unwrap_or(self.sgid);
+        Ok(())
+    }
+}
@@ -1 +1,5 @@
-// pub mod flags;
+//! 客户程序的进程：独立的地址空间、父子关系，以及`fork`/`execve`/`wait4`/`exit`
+pub mod api;
+pub mod cred;
+pub mod flags;
+pub mod process;
@@ -1,333 +1,472 @@
-// //! 规定进程控制块内容
-// extern crate alloc;
-// use alloc::vec;
-// use alloc::{
-//     collections::BTreeMap,
-//     format,
-//     string::{String, ToString},
-//     sync::Arc,
-//     vec::Vec,
-// };
-// use axerrno::AxResult;
-// use axlog::{info, trace};
-// use axmm::{AddrSpace, Backend, new_kernel_aspace};
-// use axstd::println;
-// use axsync::Mutex;
-// use axtask::{AxTaskRef, TaskInner, current, spawn_task};
-// use core::sync::atomic::{AtomicU64, Ordering};
-// use memory_addr::{PAGE_SIZE_4K, PhysAddr, VirtAddr};
-//
-// use crate::config::TASK_STACK_SIZE;
-// use crate::process::load_user_app;
-// use crate::process::task_ext::TaskExt;
-// use crate::{FORK_WAIT, MAIN_WAIT_QUEUE, PROCESS_COUNT, UserContext};
-//
-// /// Map from task id to arc pointer of task
-// pub static TID2TASK: Mutex<BTreeMap<u64, AxTaskRef>> = Mutex::new(BTreeMap::new());
-//
-// /// Map from process id to arc pointer of process
-// pub static PID2PC: Mutex<BTreeMap<u64, Arc<Process>>> = Mutex::new(BTreeMap::new());
-//
-// #[allow(unused)]
-// /// The process control block
-// pub struct Process {
-//     /// 进程号
-//     pub pid: AtomicU64,
-//     /// 父进程号
-//     pub parent: AtomicU64,
-//     /// 子进程
-//     pub children: Mutex<Vec<Arc<Process>>>,
-//     /// 所管理的线程
-//     pub tasks: Mutex<Vec<AxTaskRef>>,
-//     /// 地址空间
-//     pub memory_set: Mutex<Arc<Mutex<AddrSpace>>>,
-//     /// the page table token of the process which the task belongs to
-//     pub page_table_token: AtomicU64,
-// }
-//
-// impl Process {
-//     /// 创建一个新的进程
-//     #[allow(unused)]
-//     pub fn new(parent: u64, memory_set: Mutex<Arc<Mutex<AddrSpace>>>) -> Self {
-//         let page_table_token = {
-//             let ms = memory_set.lock();
-//             let token = ms.as_ref().lock().page_table_root().as_usize();
-//             AtomicU64::new(token as u64)
-//         };
-//
-//         Self {
-//             pid: AtomicU64::new(0),
-//             parent: AtomicU64::new(parent),
-//             children: Mutex::new(Vec::new()),
-//             tasks: Mutex::new(Vec::new()),
-//             memory_set,
-//             page_table_token,
-//         }
-//     }
-//
-//     /// 根据给定参数创建一个新的进程
-//     #[allow(unused)]
-//     pub fn init(mut path: String, elf_file: &'static [u8]) {
-//         let mut memory_set = new_kernel_aspace().unwrap();
-//
-//         let page_table_token = memory_set.page_table_root();
-//
-//         info!("page_table_token: 0x{:x}", page_table_token);
-//
-//         let (entry, usp) = load_user_app(&mut memory_set, "fork", elf_file).unwrap();
-//
-//         let mut new_process = Arc::new(Self::new(
-//             current().id().as_u64(),
-//             Mutex::new(Arc::new(Mutex::new(memory_set))),
-//         ));
-//
-//         let mut task_inner = TaskInner::new(
-//             move || {
-//                 // 设置用户程序入口点
-//                 unsafe {
-//                     user_entry(entry.as_usize(), usp);
-//                 }
-//             },
-//             path.to_string(),
-//             TASK_STACK_SIZE,
-//         );
-//
-//         #[cfg(target_arch = "riscv64")]
-//         task_inner.ctx_mut().set_page_table_root(page_table_token);
-//
-//         let proc_id = task_inner.id().as_u64();
-//
-//         new_process.pid.store(proc_id, Ordering::Release);
-//
-//         let task_ext = TaskExt::init(proc_id, true);
-//
-//         task_inner.init_task_ext(task_ext);
-//
-//         let new_task = spawn_task(task_inner);
-//
-//         TID2TASK
-//             .lock()
-//             .insert(new_task.id().as_u64(), Arc::clone(&new_task));
-//         new_process.tasks.lock().push(Arc::clone(&new_task));
-//         PID2PC.lock().insert(proc_id, Arc::clone(&new_process));
-//
-//         // 添加到进程计数
-//         PROCESS_COUNT.fetch_add(1, Ordering::SeqCst);
-//
-//         MAIN_WAIT_QUEUE.wait();
-//     }
-//
-//     pub fn fork(
-//         &self,
-//         stack_data: &'static [u8],
-//         #[allow(unused)] user_ctx: UserContext,
-//     ) -> AxResult<u64> {
-//         // 创建新的地址空间
-//         let parent_memory_set = self.memory_set.lock();
-//         let parent_ms = parent_memory_set.lock();
-//
-//         let (start, end) = (parent_ms.base(), parent_ms.end());
-//
-//         let mut child_memory_set =
-//             AddrSpace::new_empty(start, end.as_usize() - start.as_usize()).unwrap();
-//
-//         // 遍历并复制所有内存区域
-//         for area in parent_ms.areas.iter() {
-//             let start = area.start();
-//             let size = area.size();
-//             let flags = area.flags();
-//             trace!(
-//                 "fork: start = {:#x}, size = {:#x}, flags = {:?}",
-//                 start, size, flags
-//             );
-//
-//             match area.backend() {
-//                 Backend::Linear { pa_va_offset } => {
-//                     let paddr = PhysAddr::from(start.as_usize() - pa_va_offset);
-//
-//                     child_memory_set.map_linear(start, paddr, size, flags)?;
-//                 }
-//                 Backend::Alloc { populate: _ } => {
-//                     child_memory_set.map_alloc(start, size, flags, true)?;
-//
-//                     let mut buffer = vec![0u8; PAGE_SIZE_4K];
-//                     for offset in (0..size).step_by(PAGE_SIZE_4K) {
-//                         let curr_size = PAGE_SIZE_4K.min(size - offset);
-//                         let curr_va = VirtAddr::from(start.as_usize() + offset);
-//                         parent_ms.read(curr_va, &mut buffer[..curr_size])?;
-//                         child_memory_set.write(curr_va, &buffer[..curr_size])?;
-//                     }
-//                 }
-//             }
-//         }
-//
-//         let page_table_root = child_memory_set.page_table_root();
-//         info!("page_table_root: 0x{:x}", page_table_root);
-//
-//         // 创建新进程控制块
-//         let child_process = Arc::new(Process::new(
-//             self.pid(),
-//             Mutex::new(Arc::new(Mutex::new(child_memory_set))),
-//         ));
-//
-//         // 建立父子关系
-//         self.children.lock().push(Arc::clone(&child_process));
-//
-//         // 创建子进程的任务
-//         let mut child_inner = TaskInner::new(
-//             move || {
-//                 info!("Jump to sub_task ...");
-//
-//                 // 在子进程中
-//                 FORK_WAIT.notify_one(true);
-//
-//                 #[cfg(target_arch = "riscv64")]
-//                 {
-//                     let current = current();
-//                     let sub_sp = current.as_task_ref().inner().ctx().sp;
-//                     let sbu_s0 = current.as_task_ref().inner().ctx().s0;
-//
-//                     unsafe {
-//                         let mut pc: usize;
-//                         core::arch::asm!(
-//                             // 获取当前PC
-//                             "auipc {}, 0",  // 将当前PC值加载到寄存器中
-//                             out(reg) pc,
-//                         );
-//
-//                         info!("Current PC: 0x{:x}", pc);
-//
-//                         info!("{:x?}", current.ctx());
-//                         fork_entry(sub_sp, sbu_s0, user_ctx.ra);
-//                     }
-//                 }
-//             },
-//             format!("child"),
-//             TASK_STACK_SIZE,
-//         );
-//
-//         let sub_kstack_top = child_inner.kernel_stack_top().unwrap().as_usize();
-//         let sub_sp = sub_kstack_top - stack_data.len();
-//         info!(
-//             "sub_sp : {:x?}, sub_kstack_top: {:x?}, stack_size : {:x?}",
-//             sub_sp,
-//             sub_kstack_top,
-//             sub_kstack_top - sub_sp
-//         );
-//
-//         // 复制栈内容
-//         unsafe {
-//             core::ptr::copy_nonoverlapping(
-//                 stack_data.as_ptr(),
-//                 sub_sp as *mut u8,
-//                 stack_data.len(),
-//             );
-//         }
-//
-//         // let mut pc: usize;
-//
-//         // unsafe {
-//         //     core::arch::asm!(
-//         //         // 获取当前PC
-//         //         "auipc {}, 0",  // 将当前PC值加载到寄存器中
-//         //         out(reg) pc,
-//         //     );
-//         // }
-//
-//         // info!("Current PC: 0x{:x}", pc);
-//
-//         #[cfg(target_arch = "riscv64")]
-//         {
-//             child_inner.ctx_mut().set_page_table_root(page_table_root);
-//             child_inner.ctx_mut().sp = sub_sp;
-//             child_inner.ctx_mut().s0 = sub_sp + 48;
-//             child_inner.ctx_mut().s1 = user_ctx.s1;
-//             child_inner.ctx_mut().s2 = user_ctx.s2;
-//             child_inner.ctx_mut().s3 = user_ctx.s3;
-//             child_inner.ctx_mut().s4 = user_ctx.s4;
-//             child_inner.ctx_mut().s5 = user_ctx.s5;
-//             child_inner.ctx_mut().s6 = user_ctx.s6;
-//             child_inner.ctx_mut().s7 = user_ctx.s7;
-//             child_inner.ctx_mut().s8 = user_ctx.s8;
-//             child_inner.ctx_mut().s9 = user_ctx.s9;
-//             child_inner.ctx_mut().s10 = user_ctx.s10;
-//             child_inner.ctx_mut().s11 = user_ctx.s11;
-//             child_inner.ctx_mut().tp = user_ctx.tp;
-//         }
-//
-//         info!("{:x?}", child_inner.ctx());
-//
-//         let child_pid = child_inner.id().as_u64();
-//
-//         // 设置任务扩展信息
-//         child_inner.init_task_ext(TaskExt::init(child_pid, true));
-//
-//         // 创建子进程任务
-//         let child_task = spawn_task(child_inner);
-//
-//         // 添加到全局表
-//         child_process.tasks.lock().push(Arc::clone(&child_task));
-//         TID2TASK
-//             .lock()
-//             .insert(child_task.id().as_u64(), Arc::clone(&child_task));
-//         PID2PC
-//             .lock()
-//             .insert(child_process.pid(), Arc::clone(&child_process));
-//
-//         // 添加到进程计数
-//         PROCESS_COUNT.fetch_add(1, Ordering::SeqCst);
-//
-//         info!("Fork success! Parent={}, Child={}", self.pid(), child_pid);
-//
-//         // 父进程等待子进程开始执行
-//         FORK_WAIT.wait();
-//
-//         Ok(child_pid)
-//     }
-// }
-//
-// impl Process {
-//     /// get the process ID
-//     pub fn pid(&self) -> u64 {
-//         self.pid.load(Ordering::Acquire)
-//     }
-//
-//     /// get the page table token
-//     #[allow(unused)]
-//     pub fn page_table_token(&self) -> u64 {
-//         self.page_table_token.load(Ordering::Acquire)
-//     }
-//
-//     /// set the page table token of the process
-//     #[allow(unused)]
-//     pub fn set_page_table_token(&self, token: u64) {
-//         self.page_table_token.store(token, Ordering::Release);
-//     }
-// }
-//
-// pub unsafe extern "C" fn user_entry(entry: usize, _usp: VirtAddr) -> () {
-//     info!("entry: 0x{:x}", entry);
-//     println!("Jump to task ...");
-//
-//     unsafe {
-//         core::arch::asm!("
-//             mv      t2, {run_start}
-//             jalr    ra, t2, 0",
-//             run_start = in(reg) entry,
-//             clobber_abi("C"),
-//         )
-//     }
-// }
-//
-// #[naked]
-// #[allow(unused)]
-// pub unsafe extern "C" fn fork_entry(sp: usize, s0: usize, ra: usize) {
-//     unsafe {
-//         core::arch::naked_asm!(
-//             "mv sp, a0", // 第一个参数作为 sp
-//             "mv s0, a1", // 第二个参数作为 s0
-//             "li a0, 0",  // 子进程返回值为 0
-//             "jr a2",     // 跳转到第三个参数（ra）
-//         );
-//     }
-// }
+//! 进程控制块
+//!
+//! 进程号等于创建它的任务的任务号。进程拥有自己的地址空间（见[`MemorySet`]），
+//! 属于它的任务在切换时会切换到它的页表。文件描述符表（[`FdManager`]）和工作目录、
+//! `umask`（[`FsContext`]）也属于进程，`clone`时按`CLONE_FILES`/`CLONE_FS`共享或复制。
+//! 用户和组身份（[`Cred`]）在`clone`时复制。
+//! 信号的处理方式和各线程的掩码见[`SignalModule`]。
+//!
+//! 链接 mocklibc 的客户程序运行在内核态，直接以函数调用的方式进入装载器，没有可以
+//! 复制的陷入帧。`fork`因此在客户程序的栈上保存被调用者保存的寄存器（见
+//! [`fork_snapshot`]），子进程从这份快照返回到同一个调用点。运行在用户态的程序（只支持
+//! riscv64）则复制陷入帧（见`fork_user`）。
+
+use alloc::{
+    alloc::{alloc, dealloc},
+    collections::BTreeMap,
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    alloc::Layout,
+    sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering},
+};
+
+use axerrno::{AxError, AxResult, LinuxError};
+#[cfg(target_arch = "riscv64")]
+use axhal::arch::{TrapFrame, UspaceContext};
+use axlog::{debug, warn};
+use axmm::AddrSpace;
+use axsync::Mutex;
+use axtask::{TaskInner, WaitQueue, current};
+use kspin::SpinNoIrq;
+use lazyinit::LazyInit;
+
+use super::{api::current_process, cred::Cred, flags::CloneFlags};
+#[cfg(target_arch = "riscv64")]
+use crate::linux_env::axhal_ext::enter_user_context;
+use crate::{
+    arch::{fork_return, fork_snapshot, stack_pointer},
+    config::{TASK_STACK_SIZE, USER_SPACE_BASE, USER_SPACE_END},
+    linux_env::{
+        linux_fs::{
+            fd_manager::{FdManager, FsContext},
+            poll_queue::PollQueue,
+        },
+        linux_mm::memory_set::MemorySet,
+        signal_ext::{SignalModule, signal_no::SignalNo},
+    },
+    tls::TlsTemplate,
+};
+
+/// 进程号到进程控制块。退出的进程在被`wait4`回收前一直留在这里。
+pub static PID2PC: Mutex<BTreeMap<u64, Arc<Process>>> = Mutex::new(BTreeMap::new());
+
+/// 任务号到所属的进程。缺页处理中也会查询，所以用关中断的自旋锁。
+pub static TID2PC: SpinNoIrq<BTreeMap<u64, Arc<Process>>> = SpinNoIrq::new(BTreeMap::new());
+
+/// 第一个进程，收养所有父进程已退出的进程
+static INIT_PROCESS: LazyInit<Arc<Process>> = LazyInit::new();
+
+pub struct Process {
+    pid: u64,
+    /// 父进程号，init 进程为 0
+    parent: AtomicU64,
+    /// `wait4`在等待队列的锁中检查它，所以用自旋锁
+    pub children: SpinNoIrq<Vec<Arc<Process>>>,
+    pub memory_set: Mutex<MemorySet>,
+    /// 与`memory_set`使用的地址空间相同，缺页处理不能等待`memory_set`的锁
+    aspace: Arc<SpinNoIrq<AddrSpace>>,
+    /// 文件描述符表，`CLONE_FILES`时与父进程共享
+    fd_manager: Mutex<Arc<FdManager>>,
+    /// 工作目录和`umask`，`CLONE_FS`时与父进程共享
+    pub fs: Arc<FsContext>,
+    /// 用户和组身份
+    cred: Mutex<Cred>,
+    /// 信号处理方式、待处理的信号和各线程的掩码。发送信号时也会使用，所以用自旋锁
+    pub signals: SpinNoIrq<SignalModule>,
+    /// 有信号到达时唤醒在`rt_sigsuspend`中等待的线程
+    signal_wait: WaitQueue,
+    /// 有信号到达时唤醒在`poll`、管道等处阻塞的线程
+    signal_poll: PollQueue,
+    /// 当前程序的静态 TLS 模板，创建线程时用它初始化 TLS 块
+    tls_template: Mutex<Arc<TlsTemplate>>,
+    /// 还没有退出的线程数
+    threads: AtomicUsize,
+    /// `wait4`报告的状态：正常退出时为`退出码 << 8`，被信号结束时为信号编号
+    exit_status: AtomicI32,
+    /// 已调用`exit_group`，之后退出的线程不再改变退出码
+    group_exiting: AtomicBool,
+    /// 所有线程都已退出，等待父进程回收
+    zombie: AtomicBool,
+    /// 子进程退出时唤醒在`wait4`中等待的线程
+    child_exit: WaitQueue,
+}
+
+impl Process {
+    fn new(
+        pid: u64,
+        parent: u64,
+        memory_set: MemorySet,
+        tls_template: Arc<TlsTemplate>,
+        fd_manager: Arc<FdManager>,
+        fs: Arc<FsContext>,
+        cred: Cred,
+        signals: SignalModule,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            pid,
+            parent: AtomicU64::new(parent),
+            children: SpinNoIrq::new(Vec::new()),
+            aspace: memory_set.aspace().clone(),
+            memory_set: Mutex::new(memory_set),
+            fd_manager: Mutex::new(fd_manager),
+            fs,
+            cred: Mutex::new(cred),
+            signals: SpinNoIrq::new(signals),
+            signal_wait: WaitQueue::new(),
+            signal_poll: PollQueue::new(),
+            tls_template: Mutex::new(tls_template),
+            threads: AtomicUsize::new(0),
+            exit_status: AtomicI32::new(0),
+            group_exiting: AtomicBool::new(false),
+            zombie: AtomicBool::new(false),
+            child_exit: WaitQueue::new(),
+        })
+    }
+
+    pub fn pid(&self) -> u64 {
+        self.pid
+    }
+
+    pub fn parent(&self) -> u64 {
+        self.parent.load(Ordering::Acquire)
+    }
+
+    pub fn is_init(&self) -> bool {
+        self.parent() == 0
+    }
+
+    pub fn aspace(&self) -> &Arc<SpinNoIrq<AddrSpace>> {
+        &self.aspace
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.threads.load(Ordering::Acquire)
+    }
+
+    pub fn tls_template(&self) -> Arc<TlsTemplate> {
+        self.tls_template.lock().clone()
+    }
+
+    /// 装载新程序后替换 TLS 模板
+    pub fn set_tls_template(&self, template: TlsTemplate) {
+        *self.tls_template.lock() = Arc::new(template);
+    }
+
+    pub fn cred(&self) -> Cred {
+        self.cred.lock().clone()
+    }
+
+    /// 在持有锁时检查并修改身份
+    pub fn update_cred<T>(&self, f: impl FnOnce(&mut Cred) -> T) -> T {
+        f(&mut self.cred.lock())
+    }
+
+    pub fn fd_manager(&self) -> Arc<FdManager> {
+        self.fd_manager.lock().clone()
+    }
+
+    /// `execve`前调用：文件描述符表与其他进程共享时换成一份私有的副本
+    pub fn unshare_files(&self) {
+        let mut fd_manager = self.fd_manager.lock();
+        if Arc::strong_count(&fd_manager) > 1 {
+            *fd_manager = Arc::new(fd_manager.fork());
+        }
+    }
+
+    pub fn is_zombie(&self) -> bool {
+        self.zombie.load(Ordering::Acquire)
+    }
+
+    pub fn exit_status(&self) -> i32 {
+        self.exit_status.load(Ordering::Acquire)
+    }
+
+    pub fn is_group_exiting(&self) -> bool {
+        self.group_exiting.load(Ordering::Acquire)
+    }
+
+    /// 整个进程将要退出（`exit_group`或被信号结束），记录`wait4`报告的状态，只有第一次
+    /// 调用生效。其他线程在下一次系统调用返回前退出。
+    pub fn set_group_exit(&self, status: i32) {
+        if self
+            .group_exiting
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            self.exit_status.store(status, Ordering::Release);
+            self.notify_signal();
+        }
+    }
+
+    /// 唤醒在`rt_sigsuspend`中等待的线程，以及在`poll`、管道等处阻塞的线程
+    pub fn notify_signal(&self) {
+        self.signal_wait.notify_all(false);
+        self.signal_poll.notify();
+    }
+
+    /// 等待文件就绪的线程登记在这里，以便被信号唤醒
+    pub fn signal_poll(&self) -> &PollQueue {
+        &self.signal_poll
+    }
+
+    /// 等待线程`tid`有可以处理的信号，或者进程将要退出
+    pub fn wait_signal(&self, tid: u64) {
+        self.signal_wait
+            .wait_until(|| self.is_group_exiting() || self.signals.lock().has_pending(tid));
+    }
+
+    /// 让当前任务成为本进程的一个线程，并切换到本进程的地址空间
+    pub fn attach_current(self: &Arc<Self>) {
+        let task = current();
+        let root = self.aspace.lock().page_table_root();
+        // 先登记再切换，之后在新地址空间中的缺页才能找到这个进程
+        TID2PC.lock().insert(task.id().as_u64(), self.clone());
+        self.signals.lock().attach(task.id().as_u64());
+        self.threads.fetch_add(1, Ordering::AcqRel);
+        unsafe {
+            (*task.ctx_mut_ptr()).set_page_table_root(root);
+            axhal::arch::write_page_table_root(root);
+        }
+    }
+
+    /// 当前任务离开本进程并切换回内核地址空间，返回它是否是最后一个线程
+    ///
+    /// 调用时不能再使用本进程中的栈。
+    pub fn detach_current(&self) -> bool {
+        let task = current();
+        let root = axmm::kernel_aspace().lock().page_table_root();
+        unsafe {
+            (*task.ctx_mut_ptr()).set_page_table_root(root);
+            axhal::arch::write_page_table_root(root);
+        }
+        self.signals.lock().detach(task.id().as_u64());
+        let _ = TID2PC.lock().remove(&task.id().as_u64());
+        self.threads.fetch_sub(1, Ordering::AcqRel) == 1
+    }
+
+    /// 最后一个线程已退出：释放地址空间和文件描述符表，把子进程交给 init，并通知父进程
+    pub fn exit(&self, exit_code: i32) {
+        debug!("[Process] {} exit with {}", self.pid, exit_code);
+        self.memory_set.lock().clear();
+        // 僵尸进程在被回收前一直存在，先关闭文件，管道的另一端才能看到
+        *self.fd_manager.lock() = Arc::new(FdManager::new(Vec::new(), 0));
+        if !self.group_exiting.load(Ordering::Acquire) {
+            self.exit_status
+                .store((exit_code & 0xff) << 8, Ordering::Release);
+        }
+
+        let children = core::mem::take(&mut *self.children.lock());
+        if !children.is_empty() {
+            let init = INIT_PROCESS.get().expect("No init process");
+            for child in children.iter() {
+                child.parent.store(init.pid, Ordering::Release);
+            }
+            init.children.lock().extend(children);
+            // 收养的进程可能已经退出
+            init.child_exit.notify_all(false);
+        }
+
+        self.zombie.store(true, Ordering::Release);
+        let parent = PID2PC.lock().get(&self.parent()).cloned();
+        if let Some(parent) = parent {
+            parent
+                .signals
+                .lock()
+                .send_to_process(SignalNo::SIGCHLD as usize);
+            parent.notify_signal();
+            parent.child_exit.notify_all(false);
+        }
+    }
+
+    /// 等待进程号为`pid`的子进程退出并回收它，`pid`不大于 0 时等待任意子进程
+    ///
+    /// 返回子进程号和`wait4`报告的状态；`nohang`时若还没有子进程退出则返回`None`。
+    pub fn wait_child(&self, pid: i64, nohang: bool) -> Result<Option<(u64, i32)>, LinuxError> {
+        let matches = |child: &Arc<Process>| pid <= 0 || child.pid == pid as u64;
+        loop {
+            let zombie = {
+                let mut children = self.children.lock();
+                if !children.iter().any(matches) {
+                    return Err(LinuxError::ECHILD);
+                }
+                children
+                    .iter()
+                    .position(|child| matches(child) && child.is_zombie())
+                    .map(|index| children.remove(index))
+            };
+            if let Some(child) = zombie {
+                let _ = PID2PC.lock().remove(&child.pid);
+                return Ok(Some((child.pid, child.exit_status())));
+            }
+            if nohang {
+                return Ok(None);
+            }
+            self.child_exit.wait_until(|| {
+                self.children
+                    .lock()
+                    .iter()
+                    .any(|child| matches(child) && child.is_zombie())
+            });
+        }
+    }
+
+    /// 创建子进程及其第一个任务，该任务在子进程中调用`resume`回到客户程序
+    fn spawn_child(
+        self: &Arc<Self>,
+        flags: CloneFlags,
+        ctid: usize,
+        resume: impl FnOnce() + Send + 'static,
+    ) -> AxResult<u64> {
+        let memory_set = self.memory_set.lock().fork()?;
+        let mut task = TaskInner::new(
+            move || {
+                if flags.contains(CloneFlags::CLONE_CHILD_SETTID) && ctid != 0 {
+                    unsafe { *(ctid as *mut i32) = current().id().as_u64() as i32 };
+                }
+                resume()
+            },
+            current().name().into(),
+            TASK_STACK_SIZE,
+        );
+        let pid = task.id().as_u64();
+        let fd_manager = if flags.contains(CloneFlags::CLONE_FILES) {
+            self.fd_manager()
+        } else {
+            Arc::new(self.fd_manager().fork())
+        };
+        let fs = if flags.contains(CloneFlags::CLONE_FS) {
+            self.fs.clone()
+        } else {
+            Arc::new(self.fs.fork())
+        };
+        let signals = self.signals.lock().fork(current().id().as_u64(), pid);
+        let child = Process::new(
+            pid,
+            self.pid,
+            memory_set,
+            self.tls_template(),
+            fd_manager,
+            fs,
+            self.cred(),
+            signals,
+        );
+        task.ctx_mut()
+            .set_page_table_root(child.aspace.lock().page_table_root());
+        child.threads.store(1, Ordering::Release);
+        TID2PC.lock().insert(pid, child.clone());
+        PID2PC.lock().insert(pid, child.clone());
+        self.children.lock().push(child);
+        axtask::spawn_task(task);
+        debug!("[Process] {} fork {}", self.pid, pid);
+        Ok(pid)
+    }
+}
+
+/// 复制当前进程。父进程中返回子进程号，子进程中返回 0。
+///
+/// 子进程从同一个调用帧返回，所以调用者此时不能持有锁或堆上的对象（包括当前进程的
+/// `Arc`）。只有运行在进程自己的栈上的线程（主线程）可以调用，其他线程的栈属于内核，
+/// 无法复制。
+/// 程序镜像装载在内核地址空间中的进程（见[`MemorySet::has_fixed_image`]）不能复制，
+/// 返回`Unsupported`（`ENOSYS`）。
+pub fn fork_current(flags: CloneFlags, ctid: usize) -> AxResult<u64> {
+    let sp = stack_pointer();
+    if !(USER_SPACE_BASE..USER_SPACE_END).contains(&sp) {
+        warn!("[Process] fork from a thread without its own stack");
+        return Err(AxError::InvalidInput);
+    }
+    if current_process().memory_set.lock().has_fixed_image() {
+        warn!("[Process] fork a program loaded at a fixed kernel address");
+        return Err(AxError::Unsupported);
+    }
+
+    let args = ForkArgs { flags, ctid };
+    // 复制期间父进程的栈是只读的，在单独的栈上运行。子进程不会释放它。
+    let layout = Layout::from_size_align(TASK_STACK_SIZE, 16).unwrap();
+    let stack = unsafe { alloc(layout) };
+    if stack.is_null() {
+        return Err(AxError::NoMemory);
+    }
+    let ret = unsafe {
+        fork_snapshot(
+            &args as *const ForkArgs as usize,
+            fork_inner,
+            stack as usize + TASK_STACK_SIZE,
+        )
+    };
+    if ret == 0 {
+        return Ok(0);
+    }
+    unsafe { dealloc(stack, layout) };
+    if ret < 0 {
+        return Err(AxError::NoMemory);
+    }
+    Ok(ret as u64)
+}
+
+/// 复制运行在用户态的当前进程。父进程中返回子进程号，子进程从陷入帧`tf`回到用户态，
+/// 系统调用返回 0。
+///
+/// `stack`不为 0 时子进程换用这个用户栈，`tls`不为`None`时作为子进程的`tp`。
+#[cfg(target_arch = "riscv64")]
+pub fn fork_user(
+    tf: &TrapFrame,
+    flags: CloneFlags,
+    ctid: usize,
+    stack: usize,
+    tls: Option<usize>,
+) -> AxResult<u64> {
+    let mut ctx = UspaceContext::from(tf);
+    ctx.set_retval(0);
+    if stack != 0 {
+        ctx.regs.sp = stack;
+    }
+    if let Some(tls) = tls {
+        ctx.regs.tp = tls;
+    }
+    current_process().spawn_child(flags, ctid, move || enter_user_context(&ctx))
+}
+
+/// 为启动任务创建 init 进程并切换到它的地址空间，`fd_manager`是它最初的文件描述符表
+pub fn init_process(fd_manager: FdManager) {
+    let pid = current().id().as_u64();
+    let memory_set = MemorySet::new().expect("Failed to create the init address space");
+    let process = Process::new(
+        pid,
+        0,
+        memory_set,
+        Arc::new(TlsTemplate::empty()),
+        Arc::new(fd_manager),
+        Arc::new(FsContext::new()),
+        Cred::default(),
+        SignalModule::new(),
+    );
+    PID2PC.lock().insert(pid, process.clone());
+    process.attach_current();
+    INIT_PROCESS.init_once(process);
+}
+
+/// 只含`Copy`的字段：子进程中这份数据也在栈上，但不会被使用或释放
+struct ForkArgs {
+    flags: CloneFlags,
+    ctid: usize,
+}
+
+/// 返回子进程号，失败时返回 -1
+extern "C" fn fork_inner(args: usize, ctx: usize) -> isize {
+    let args = unsafe { &*(args as *const ForkArgs) };
+    let tp = axhal::arch::read_thread_pointer();
+    let resume = move || {
+        unsafe { axhal::arch::write_thread_pointer(tp) };
+        unsafe { fork_return(ctx) }
+    };
+    match current_process().spawn_child(args.flags, args.ctid, resume) {
+        Ok(pid) => pid as isize,
+        Err(e) => {
+            warn!("[Process] fork failed: {:?}", e);
+            -1
+        }
+    }
+}
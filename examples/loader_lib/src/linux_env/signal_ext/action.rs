@@ -0,0 +1,51 @@
+//! `rt_sigaction`使用的信号处理方式
+
+use bitflags::*;
+
+/// 使用默认处理方式
+pub const SIG_DFL: usize = 0;
+/// 忽略信号
+pub const SIG_IGN: usize = 1;
+
+bitflags! {
+    /// `sa_flags`中的选项
+    #[derive(Debug, Clone, Copy)]
+    pub struct SigActionFlags: usize {
+        /// 子进程暂停时不发送`SIGCHLD`
+        const SA_NOCLDSTOP = 1;
+        /// 子进程退出后不成为僵尸进程
+        const SA_NOCLDWAIT = 2;
+        /// 处理函数接收`siginfo`和`ucontext`两个额外的参数
+        const SA_SIGINFO = 4;
+        /// 在备用信号栈上运行处理函数，目前未实现
+        const SA_ONSTACK = 0x0800_0000;
+        /// 被打断的系统调用自动重新开始
+        const SA_RESTART = 0x1000_0000;
+        /// 处理函数运行期间不阻塞该信号本身
+        const SA_NODEFER = 0x4000_0000;
+        /// 处理一次后恢复为默认处理方式
+        const SA_RESETHAND = 0x8000_0000;
+    }
+}
+
+/// 内核使用的`struct sigaction`。riscv64 上没有`sa_restorer`：处理函数直接返回到
+/// 递送它的地方。
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SigAction {
+    /// 处理函数地址，或[`SIG_DFL`]、[`SIG_IGN`]
+    pub handler: usize,
+    /// [`SigActionFlags`]
+    pub flags: usize,
+    /// x86_64 和 aarch64 的`sa_restorer`，只为保持布局，不会使用
+    #[cfg(not(target_arch = "riscv64"))]
+    pub restorer: usize,
+    /// 处理函数运行期间额外阻塞的信号
+    pub mask: u64,
+}
+
+impl SigAction {
+    pub fn flags(&self) -> SigActionFlags {
+        SigActionFlags::from_bits_truncate(self.flags)
+    }
+}
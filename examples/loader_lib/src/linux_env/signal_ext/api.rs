@@ -0,0 +1,237 @@
+//! 发送和递送信号
+
+use alloc::sync::Arc;
+
+use axerrno::LinuxError;
+use axhal::arch::TrapFrame;
+#[cfg(target_arch = "riscv64")]
+use axhal::mem::VirtAddr;
+use axlog::{debug, warn};
+use axtask::current;
+
+use super::{
+    action::{SIG_DFL, SIG_IGN},
+    signal_no::{DefaultAction, MAX_SIG, SignalNo, default_action},
+    ucontext::{SigInfo, UContext},
+};
+#[cfg(target_arch = "riscv64")]
+use crate::config::SIGRETURN_TRAMPOLINE;
+use crate::linux_env::process_ext::{
+    api::{current_process, exit_current, kill_current, try_current_process},
+    process::{PID2PC, Process, TID2PC},
+};
+
+/// 检查信号编号，`0`只用于检查目标是否存在
+pub fn check_signo(signo: usize) -> Result<(), LinuxError> {
+    if signo > MAX_SIG {
+        return Err(LinuxError::EINVAL);
+    }
+    Ok(())
+}
+
+/// 向进程`pid`发送信号`signo`，`signo`为 0 时只检查进程是否存在
+pub fn send_signal_to_process(pid: u64, signo: usize) -> Result<(), LinuxError> {
+    check_signo(signo)?;
+    let process = PID2PC.lock().get(&pid).cloned().ok_or(LinuxError::ESRCH)?;
+    if signo != 0 && !process.is_zombie() {
+        debug!("[Signal] send {} to process {}", signo, pid);
+        process.signals.lock().send_to_process(signo);
+        process.notify_signal();
+    }
+    Ok(())
+}
+
+/// 向线程`tid`发送信号`signo`；`tgid`不为`None`时线程必须属于该进程
+pub fn send_signal_to_thread(tgid: Option<u64>, tid: u64, signo: usize) -> Result<(), LinuxError> {
+    check_signo(signo)?;
+    let process = TID2PC.lock().get(&tid).cloned().ok_or(LinuxError::ESRCH)?;
+    if tgid.is_some_and(|tgid| tgid != process.pid()) {
+        return Err(LinuxError::ESRCH);
+    }
+    if signo != 0 {
+        debug!("[Signal] send {} to thread {}", signo, tid);
+        process.signals.lock().send_to_thread(tid, signo);
+        process.notify_signal();
+    }
+    Ok(())
+}
+
+/// 向当前线程发送信号，它会在当前系统调用返回前被处理
+pub fn send_signal_to_current(signo: usize) {
+    let tid = current().id().as_u64();
+    current_process().signals.lock().send_to_thread(tid, signo);
+}
+
+/// 在当前系统调用期间换用掩码`mask`，用于`ppoll`、`pselect6`和`epoll_pwait`
+///
+/// 系统调用返回前恢复原掩码；期间处理了信号时在处理函数返回后恢复。
+pub fn set_temporary_mask(mask: u64) {
+    let tid = current().id().as_u64();
+    current_process().signals.lock().suspend(tid, mask);
+}
+
+/// 当前线程是否有可以处理的信号，可以阻塞的系统调用据此返回`EINTR`
+pub fn has_pending_signal() -> bool {
+    let tid = current().id().as_u64();
+    try_current_process().is_some_and(|process| {
+        process.is_group_exiting() || process.signals.lock().has_pending(tid)
+    })
+}
+
+/// 在系统调用返回前处理当前线程所有可以处理的信号
+///
+/// 处理函数直接在当前栈上调用，返回后再恢复掩码。进程正在`exit_group`时当前线程
+/// 随之退出，不再返回。
+pub fn handle_pending_signals() {
+    handle_signals(None);
+}
+
+/// 在返回用户态前处理当前线程的信号，陷入帧为`tf`
+///
+/// 有处理函数时在用户栈上构造信号帧，修改`tf`使返回用户态时进入处理函数，一次只递送
+/// 一个信号，其余的在处理函数通过跳板调用`rt_sigreturn`后递送。
+#[cfg(target_arch = "riscv64")]
+pub fn handle_user_signals(tf: &mut TrapFrame) {
+    handle_signals(Some(tf));
+}
+
+/// 只有 riscv64 支持用户态程序，其他体系结构上`tf`总是`None`
+#[cfg_attr(not(target_arch = "riscv64"), allow(unused_mut, unused_variables))]
+fn handle_signals(mut tf: Option<&mut TrapFrame>) {
+    let tid = current().id().as_u64();
+    loop {
+        let Some(process) = try_current_process() else {
+            return;
+        };
+        if process.is_group_exiting() {
+            drop(process);
+            exit_current(0, false);
+        }
+        let (signo, code, handler) = {
+            let mut signals = process.signals.lock();
+            let Some((signo, code)) = signals.take_pending(tid) else {
+                signals.end_suspend(tid);
+                return;
+            };
+            (signo, code, signals.action(signo).handler)
+        };
+        match handler {
+            SIG_IGN => {}
+            SIG_DFL => match default_action(signo) {
+                DefaultAction::Terminate => {
+                    drop(process);
+                    kill_current(signo, false);
+                }
+                DefaultAction::CoreDump => {
+                    drop(process);
+                    kill_current(signo, true);
+                }
+                DefaultAction::Ignore => {}
+                DefaultAction::Stop | DefaultAction::Continue => {
+                    warn!("[Signal] job control is not supported, ignore {}", signo);
+                }
+            },
+            handler => {
+                #[cfg(target_arch = "riscv64")]
+                if let Some(tf) = tf.as_deref_mut() {
+                    return push_user_frame(process, tid, signo, code, handler, tf);
+                }
+                run_handler(process, tid, signo, code, handler)
+            }
+        }
+    }
+}
+
+/// 在用户栈上依次放置`siginfo`和`ucontext`，都按 16 字节对齐
+#[cfg(target_arch = "riscv64")]
+fn push_user_frame(
+    process: Arc<Process>,
+    tid: u64,
+    signo: usize,
+    code: i32,
+    handler: usize,
+    tf: &mut TrapFrame,
+) {
+    let sp = tf.regs.sp;
+    let info_addr = (sp - size_of::<SigInfo>()) & !0xf;
+    let frame = (info_addr - size_of::<UContext>()) & !0xf;
+    if !process
+        .memory_set
+        .lock()
+        .is_mapped(VirtAddr::from(frame), sp - frame)
+    {
+        warn!("[Signal] thread {} has no stack for signal {}", tid, signo);
+        drop(process);
+        kill_current(SignalNo::SIGSEGV as usize, true);
+    }
+    let info = info_addr as *mut SigInfo;
+    let uc = frame as *mut UContext;
+    unsafe {
+        info.write(SigInfo::new(signo, code));
+        uc.write(UContext::new(0));
+        (*uc).uc_mcontext.save(tf);
+        (*uc).uc_sigmask = process.signals.lock().enter_handler(tid, signo, frame);
+    }
+    debug!(
+        "[Signal] thread {} enter handler {:#x} for {} in user mode",
+        tid, handler, signo
+    );
+    tf.sepc = handler;
+    tf.regs.a0 = signo;
+    tf.regs.a1 = info_addr;
+    tf.regs.a2 = frame;
+    tf.regs.ra = SIGRETURN_TRAMPOLINE;
+    tf.regs.sp = frame;
+}
+
+fn run_handler(process: Arc<Process>, tid: u64, signo: usize, code: i32, handler: usize) {
+    let mut info = SigInfo::new(signo, code);
+    let mut uc = UContext::new(0);
+    let frame = &mut uc as *mut UContext as usize;
+    uc.uc_sigmask = process.signals.lock().enter_handler(tid, signo, frame);
+    // 处理函数可能不再返回（退出或`siglongjmp`），不能持有进程的引用
+    drop(process);
+    debug!(
+        "[Signal] thread {} run handler {:#x} for {}",
+        tid, handler, signo
+    );
+    let handler: extern "C" fn(i32, *mut SigInfo, *mut UContext) =
+        unsafe { core::mem::transmute(handler) };
+    handler(signo as i32, &mut info, &mut uc);
+    restore_frame(Some(frame));
+}
+
+/// 离开最内层的信号处理函数，恢复它的`ucontext`中的掩码
+///
+/// `expected`不为`None`时只在最内层的正是这个`ucontext`时才离开，处理函数自己调用过
+/// `rt_sigreturn`时不会重复恢复。没有正在运行的处理函数时返回`false`。
+pub fn restore_frame(expected: Option<usize>) -> bool {
+    let tid = current().id().as_u64();
+    let process = current_process();
+    let mut signals = process.signals.lock();
+    let Some(frame) = signals.current_frame(tid) else {
+        return false;
+    };
+    if expected.is_some_and(|expected| expected != frame) {
+        return false;
+    }
+    signals.leave_handler(tid);
+    let mask = unsafe { (*(frame as *const UContext)).uc_sigmask };
+    signals.set_blocked(tid, mask);
+    true
+}
+
+/// 用户态程序的`rt_sigreturn`：离开最内层的处理函数，从它的`ucontext`恢复掩码和寄存器
+///
+/// 没有正在运行的处理函数时返回`false`。
+#[cfg(target_arch = "riscv64")]
+pub fn sigreturn_user(tf: &mut TrapFrame) -> bool {
+    let tid = current().id().as_u64();
+    let Some(frame) = current_process().signals.lock().current_frame(tid) else {
+        return false;
+    };
+    restore_frame(Some(frame));
+    let uc = frame as *const UContext;
+    unsafe { (*uc).uc_mcontext.restore(tf) };
+    true
+}
@@ -0,0 +1,13 @@
+//! 客户程序的 POSIX 信号：处理函数、阻塞与待处理的信号集，以及信号的递送
+//!
+//! 客户程序以函数调用的方式进入装载器，信号只在系统调用返回前递送（见
+//! [`api::handle_pending_signals`]），一直不进行系统调用的线程收不到信号。
+//! 用户态程序同样在系统调用返回前收到信号，处理函数在用户态运行，返回到跳板后通过
+//! `rt_sigreturn`恢复被打断时的寄存器（见[`api::handle_user_signals`]）。
+pub mod action;
+pub mod api;
+pub mod signal_no;
+pub mod ucontext;
+
+mod module;
+pub use module::SignalModule;
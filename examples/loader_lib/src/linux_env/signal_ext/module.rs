@@ -0,0 +1,214 @@
+//! 一个进程的信号状态
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::{
+    action::{SIG_DFL, SIG_IGN, SigAction, SigActionFlags},
+    signal_no::{DefaultAction, MAX_SIG, UNMASKABLE, default_action, sig_bit},
+    ucontext::{SI_TKILL, SI_USER},
+};
+
+/// 一个线程自己的信号状态
+#[derive(Default)]
+struct ThreadSignals {
+    /// 发给这个线程的待处理信号
+    pending: u64,
+    /// 被阻塞的信号
+    blocked: u64,
+    /// `rt_sigsuspend`期间保存的原掩码，处理函数返回后恢复它
+    saved_mask: Option<u64>,
+    /// 正在运行的处理函数的`ucontext`地址，最内层的在最后
+    frames: Vec<usize>,
+}
+
+/// 一个进程的信号状态：各信号的处理方式由所有线程共享，掩码属于各个线程
+pub struct SignalModule {
+    actions: [SigAction; MAX_SIG],
+    /// 发给整个进程的待处理信号，由任意一个没有阻塞它的线程处理
+    pending: u64,
+    threads: BTreeMap<u64, ThreadSignals>,
+}
+
+impl Default for SignalModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SignalModule {
+    pub fn new() -> Self {
+        Self {
+            actions: [SigAction::default(); MAX_SIG],
+            pending: 0,
+            threads: BTreeMap::new(),
+        }
+    }
+
+    /// `fork`时为子进程复制一份：处理方式相同，唯一的线程`child_tid`继承线程`tid`的
+    /// 掩码，没有待处理的信号
+    pub fn fork(&self, tid: u64, child_tid: u64) -> Self {
+        let mut threads = BTreeMap::new();
+        threads.insert(child_tid, ThreadSignals {
+            blocked: self.blocked(tid),
+            ..Default::default()
+        });
+        Self {
+            actions: self.actions,
+            pending: 0,
+            threads,
+        }
+    }
+
+    /// `execve`后安装的处理函数都已失效，恢复为默认处理方式；忽略的信号仍然忽略
+    pub fn reset_on_exec(&mut self, tid: u64) {
+        for action in self.actions.iter_mut() {
+            if action.handler != SIG_IGN {
+                *action = SigAction::default();
+            }
+        }
+        if let Some(thread) = self.threads.get_mut(&tid) {
+            thread.frames.clear();
+            thread.saved_mask = None;
+        }
+    }
+
+    pub fn attach(&mut self, tid: u64) {
+        self.threads.entry(tid).or_default();
+    }
+
+    /// 线程离开进程，发给它但还没处理的信号随之丢弃
+    pub fn detach(&mut self, tid: u64) {
+        self.threads.remove(&tid);
+    }
+
+    pub fn action(&self, signo: usize) -> SigAction {
+        self.actions[signo - 1]
+    }
+
+    /// 设置处理方式。改为忽略时丢弃已经待处理的该信号。
+    pub fn set_action(&mut self, signo: usize, action: SigAction) {
+        self.actions[signo - 1] = action;
+        if self.is_ignored(signo) {
+            self.pending &= !sig_bit(signo);
+            for thread in self.threads.values_mut() {
+                thread.pending &= !sig_bit(signo);
+            }
+        }
+    }
+
+    fn is_ignored(&self, signo: usize) -> bool {
+        match self.action(signo).handler {
+            SIG_IGN => true,
+            SIG_DFL => default_action(signo) == DefaultAction::Ignore,
+            _ => false,
+        }
+    }
+
+    /// 发给整个进程；被忽略的信号直接丢弃
+    pub fn send_to_process(&mut self, signo: usize) {
+        if !self.is_ignored(signo) {
+            self.pending |= sig_bit(signo);
+        }
+    }
+
+    /// 发给线程`tid`，线程不属于本进程时返回`false`
+    pub fn send_to_thread(&mut self, tid: u64, signo: usize) -> bool {
+        let ignored = self.is_ignored(signo);
+        match self.threads.get_mut(&tid) {
+            Some(thread) => {
+                if !ignored {
+                    thread.pending |= sig_bit(signo);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn blocked(&self, tid: u64) -> u64 {
+        self.threads.get(&tid).map_or(0, |thread| thread.blocked)
+    }
+
+    /// 设置线程`tid`的掩码，`SIGKILL`和`SIGSTOP`不能被阻塞
+    pub fn set_blocked(&mut self, tid: u64, mask: u64) {
+        if let Some(thread) = self.threads.get_mut(&tid) {
+            thread.blocked = mask & !UNMASKABLE;
+        }
+    }
+
+    /// `rt_sigsuspend`：临时换用`mask`，直到有信号被处理
+    pub fn suspend(&mut self, tid: u64, mask: u64) {
+        if let Some(thread) = self.threads.get_mut(&tid) {
+            thread.saved_mask = Some(thread.blocked);
+            thread.blocked = mask & !UNMASKABLE;
+        }
+    }
+
+    /// 结束`rt_sigsuspend`：没有处理函数接管原掩码时在这里恢复
+    pub fn end_suspend(&mut self, tid: u64) {
+        if let Some(thread) = self.threads.get_mut(&tid) {
+            if let Some(mask) = thread.saved_mask.take() {
+                thread.blocked = mask;
+            }
+        }
+    }
+
+    /// 线程`tid`是否有可以处理的信号
+    pub fn has_pending(&self, tid: u64) -> bool {
+        self.threads
+            .get(&tid)
+            .is_some_and(|thread| (thread.pending | self.pending) & !thread.blocked != 0)
+    }
+
+    /// 取出线程`tid`可以处理的编号最小的信号，返回信号编号和`si_code`
+    pub fn take_pending(&mut self, tid: u64) -> Option<(usize, i32)> {
+        let thread = self.threads.get_mut(&tid)?;
+        let own = thread.pending & !thread.blocked;
+        let shared = self.pending & !thread.blocked;
+        if own != 0 {
+            let signo = own.trailing_zeros() as usize + 1;
+            thread.pending &= !sig_bit(signo);
+            Some((signo, SI_TKILL))
+        } else if shared != 0 {
+            let signo = shared.trailing_zeros() as usize + 1;
+            self.pending &= !sig_bit(signo);
+            Some((signo, SI_USER))
+        } else {
+            None
+        }
+    }
+
+    /// 开始运行信号`signo`的处理函数：记录`ucontext`的位置，返回处理函数返回后应恢复
+    /// 的掩码，并按`action`阻塞信号
+    ///
+    /// 地址低于`frame`的记录属于已经用`siglongjmp`离开的处理函数，一并丢弃。
+    pub fn enter_handler(&mut self, tid: u64, signo: usize, frame: usize) -> u64 {
+        let action = self.action(signo);
+        if action.flags().contains(SigActionFlags::SA_RESETHAND) {
+            self.actions[signo - 1] = SigAction::default();
+        }
+        let Some(thread) = self.threads.get_mut(&tid) else {
+            return 0;
+        };
+        let old_mask = thread.saved_mask.take().unwrap_or(thread.blocked);
+        let mut mask = thread.blocked | action.mask;
+        if !action.flags().contains(SigActionFlags::SA_NODEFER) {
+            mask |= sig_bit(signo);
+        }
+        thread.blocked = mask & !UNMASKABLE;
+        thread.frames.retain(|&addr| addr > frame);
+        thread.frames.push(frame);
+        old_mask
+    }
+
+    /// 离开最内层的处理函数，返回它的`ucontext`地址；没有正在运行的处理函数时返回
+    /// `None`
+    pub fn leave_handler(&mut self, tid: u64) -> Option<usize> {
+        self.threads.get_mut(&tid)?.frames.pop()
+    }
+
+    /// 最内层的处理函数的`ucontext`地址
+    pub fn current_frame(&self, tid: u64) -> Option<usize> {
+        self.threads.get(&tid)?.frames.last().copied()
+    }
+}
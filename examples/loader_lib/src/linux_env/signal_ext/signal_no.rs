@@ -0,0 +1,90 @@
+//! 信号编号及其默认处理方式
+
+/// 信号编号的上限（含），`32`及以上为实时信号
+pub const MAX_SIG: usize = 64;
+
+numeric_enum_macro::numeric_enum! {
+#[repr(usize)]
+#[allow(missing_docs)]
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum SignalNo {
+    SIGHUP = 1,
+    SIGINT = 2,
+    SIGQUIT = 3,
+    SIGILL = 4,
+    SIGTRAP = 5,
+    SIGABRT = 6,
+    SIGBUS = 7,
+    SIGFPE = 8,
+    SIGKILL = 9,
+    SIGUSR1 = 10,
+    SIGSEGV = 11,
+    SIGUSR2 = 12,
+    SIGPIPE = 13,
+    SIGALRM = 14,
+    SIGTERM = 15,
+    SIGSTKFLT = 16,
+    SIGCHLD = 17,
+    SIGCONT = 18,
+    SIGSTOP = 19,
+    SIGTSTP = 20,
+    SIGTTIN = 21,
+    SIGTTOU = 22,
+    SIGURG = 23,
+    SIGXCPU = 24,
+    SIGXFSZ = 25,
+    SIGVTALRM = 26,
+    SIGPROF = 27,
+    SIGWINCH = 28,
+    SIGIO = 29,
+    SIGPWR = 30,
+    SIGSYS = 31,
+}
+}
+
+/// 没有安装处理函数时信号的处理方式，见`man 7 signal`
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum DefaultAction {
+    /// 结束进程
+    Terminate,
+    /// 结束进程并转储内存（这里不生成 core 文件，只在退出状态中标记）
+    CoreDump,
+    /// 忽略
+    Ignore,
+    /// 暂停进程
+    Stop,
+    /// 恢复暂停的进程
+    Continue,
+}
+
+impl SignalNo {
+    /// 该信号的默认处理方式
+    pub fn default_action(self) -> DefaultAction {
+        use SignalNo::*;
+        match self {
+            SIGQUIT | SIGILL | SIGTRAP | SIGABRT | SIGBUS | SIGFPE | SIGSEGV | SIGXCPU
+            | SIGXFSZ | SIGSYS => DefaultAction::CoreDump,
+            SIGCHLD | SIGURG | SIGWINCH => DefaultAction::Ignore,
+            SIGSTOP | SIGTSTP | SIGTTIN | SIGTTOU => DefaultAction::Stop,
+            SIGCONT => DefaultAction::Continue,
+            _ => DefaultAction::Terminate,
+        }
+    }
+}
+
+/// 信号`signo`的默认处理方式，实时信号默认结束进程
+pub fn default_action(signo: usize) -> DefaultAction {
+    match SignalNo::try_from(signo) {
+        Ok(signal) => signal.default_action(),
+        Err(_) => DefaultAction::Terminate,
+    }
+}
+
+/// 信号在信号集中对应的位
+pub const fn sig_bit(signo: usize) -> u64 {
+    1 << (signo - 1)
+}
+
+/// 不能被阻塞、忽略或捕获的信号
+pub const UNMASKABLE: u64 =
+    sig_bit(SignalNo::SIGKILL as usize) | sig_bit(SignalNo::SIGSTOP as usize);
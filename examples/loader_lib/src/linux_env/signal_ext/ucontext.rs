@@ -0,0 +1,108 @@
+//! 传给`SA_SIGINFO`处理函数的`siginfo_t`和`ucontext_t`，布局与 riscv64 Linux 相同
+
+#[cfg(target_arch = "riscv64")]
+use axhal::arch::{GeneralRegisters, TrapFrame};
+
+/// `si_code`：由`kill`发送
+pub const SI_USER: i32 = 0;
+/// `si_code`：由`tkill`/`tgkill`发送
+pub const SI_TKILL: i32 = -6;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SigInfo {
+    pub si_signo: i32,
+    pub si_errno: i32,
+    pub si_code: i32,
+    _pad: i32,
+    /// 发送者的进程号
+    pub si_pid: i32,
+    /// 发送者的用户号
+    pub si_uid: u32,
+    _rest: [u64; 13],
+}
+
+impl SigInfo {
+    pub fn new(signo: usize, code: i32) -> Self {
+        Self {
+            si_signo: signo as i32,
+            si_errno: 0,
+            si_code: code,
+            _pad: 0,
+            si_pid: 0,
+            si_uid: 0,
+            _rest: [0; 13],
+        }
+    }
+}
+
+/// `stack_t`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SignalStack {
+    pub ss_sp: usize,
+    pub ss_flags: i32,
+    pub ss_size: usize,
+}
+
+/// `mcontext_t`：通用寄存器（`gregs[0]`为`pc`）和浮点状态
+///
+/// 以函数调用方式运行的程序在系统调用返回前收到信号，被打断的位置就是系统调用的
+/// 调用点，不填写寄存器；用户态程序的寄存器来自陷入帧，`rt_sigreturn`时恢复。
+/// 浮点状态不保存。
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+pub struct MContext {
+    pub gregs: [usize; 32],
+    pub fpregs: [u64; 66],
+}
+
+#[cfg(target_arch = "riscv64")]
+impl MContext {
+    /// 保存陷入帧中的`pc`和`x1`～`x31`
+    pub fn save(&mut self, tf: &TrapFrame) {
+        self.gregs[0] = tf.sepc;
+        self.gregs[1..].copy_from_slice(regs_as_slice(&tf.regs));
+    }
+
+    /// 把保存的寄存器写回陷入帧，处理函数可能修改过它们
+    pub fn restore(&self, tf: &mut TrapFrame) {
+        tf.sepc = self.gregs[0];
+        let regs = &mut tf.regs as *mut GeneralRegisters as *mut usize;
+        unsafe { core::slice::from_raw_parts_mut(regs, 31) }.copy_from_slice(&self.gregs[1..]);
+    }
+}
+
+/// `GeneralRegisters`按`x1`～`x31`的顺序排列
+#[cfg(target_arch = "riscv64")]
+fn regs_as_slice(regs: &GeneralRegisters) -> &[usize] {
+    unsafe { core::slice::from_raw_parts(regs as *const GeneralRegisters as *const usize, 31) }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UContext {
+    pub uc_flags: usize,
+    pub uc_link: usize,
+    pub uc_stack: SignalStack,
+    /// 处理函数返回后恢复的信号掩码，处理函数可以修改它
+    pub uc_sigmask: u64,
+    _unused: [u8; 120],
+    pub uc_mcontext: MContext,
+}
+
+impl UContext {
+    pub fn new(sigmask: u64) -> Self {
+        Self {
+            uc_flags: 0,
+            uc_link: 0,
+            uc_stack: SignalStack::default(),
+            uc_sigmask: sigmask,
+            _unused: [0; 120],
+            uc_mcontext: MContext {
+                gregs: [0; 32],
+                fpregs: [0; 66],
+            },
+        }
+    }
+}
@@ -1,42 +1,158 @@
-use core::{
-    cmp::min,
-    slice::{from_raw_parts, from_raw_parts_mut},
-};
+use alloc::{borrow::Cow, boxed::Box, string::String, vec, vec::Vec};
+use core::slice::{from_raw_parts, from_raw_parts_mut};
 
-use axstd::{format, println};
+use axstd::println;
 
-use axlog::{debug, warn};
+use axlog::debug;
 
+use axhal::{
+    mem::{MemoryAddr, PAGE_SIZE_4K, PhysAddr, VirtAddr, phys_to_virt},
+    paging::MappingFlags,
+};
 use elf::{
     ElfBytes,
-    abi::{
-        PT_LOAD, R_RISCV_64, R_RISCV_JUMP_SLOT, R_RISCV_RELATIVE, STB_GLOBAL, STB_LOCAL, STB_WEAK,
-        STT_FUNC, STT_OBJECT, STT_TLS,
-    },
+    abi::{ET_EXEC, PF_R, PF_W, PF_X, PT_INTERP, PT_LOAD, PT_PHDR},
     endian::LittleEndian,
 };
 
-use crate::elf::{LoadError, verify_elf_header};
+use crate::{
+    arch::{APP_START, ARCH_NAME, PAYLOAD_PADDR, SIGRETURN_CODE, USER_MODE, flush_icache},
+    config::{
+        LIB_SEARCH_PATHS, LOADER_CMDLINE_PATH, SIGRETURN_TRAMPOLINE, USER_IMAGE_BASE,
+        USER_IMAGE_END,
+    },
+    dynlink::{
+        DynamicLinker, FileLibraries, LibraryProvider, PayloadLibraries, read_image, tls_segment,
+    },
+    elf::{LoadError, verify_elf_header, verify_segments},
+    linux_env::{
+        linux_mm::{map_payload, set_executable},
+        process_ext::api::current_process,
+    },
+    stack::AuxInfo,
+    tls::{TlsLayout, TlsTemplate},
+};
 
-/// `bin`的开始位置
-const PLASH_START: usize = 0xffff_ffc0_2200_0000;
+/// `bin`区域的大小（与`mockc_apps/Makefile`生成的`apps.bin`一致）
+const PLASH_SIZE: usize = 0x200_0000;
 // STATIC
 
+/// 按链接地址装载到`APP_START`的静态程序可用的区域大小
 const MAX_APP_SIZE: usize = 0x20_0000;
-const APP_START: usize = 0xffff_ffc0_8060_0000;
-const MAX_LIB_SIZE: usize = 0x08_0000;
-const LIB_START: usize = 0xffff_ffc0_8010_0000;
-
-pub fn load_elf() -> u64 {
-    debug!("Load payload ...");
-    // Load X out file
-    let app_elf_size = unsafe { *(PLASH_START as *const usize) };
-    debug!("app_elf_size 0x{:x}", app_elf_size);
-    if app_elf_size >= MAX_APP_SIZE {
-        panic!("app elf size > MAP_APP_SIZE");
+
+/// 依次取出`bin`中以 8 字节长度为前缀的 ELF 镜像，遇到长度为 0 时结束。
+///
+/// 第一个镜像是应用程序，其后是任意数量的共享对象。
+fn payload_images() -> Vec<&'static [u8]> {
+    let Some(paddr) = PAYLOAD_PADDR else {
+        panic!(
+            "No payload on {}, specify the program in {}",
+            ARCH_NAME, LOADER_CMDLINE_PATH
+        );
+    };
+    let plash_start = phys_to_virt(PhysAddr::from(paddr)).as_usize();
+    map_payload(plash_start, PLASH_SIZE);
+    let mut images = Vec::new();
+    let mut offset = 0;
+    while offset + 0x8 <= PLASH_SIZE {
+        // 镜像的长度不一定是 8 的倍数，后面的长度字段可能不对齐
+        let size = unsafe { ((plash_start + offset) as *const usize).read_unaligned() };
+        if size == 0 {
+            break;
+        }
+        if size > PLASH_SIZE - offset - 0x8 {
+            panic!("payload image at 0x{:x} exceeds the payload area", offset);
+        }
+        images.push(unsafe { from_raw_parts((plash_start + offset + 0x8) as *const u8, size) });
+        offset += 0x8 + size;
     }
-    let app_elf_slice = unsafe { from_raw_parts((PLASH_START + 0x8) as *const u8, app_elf_size) };
-    let app_code = unsafe { from_raw_parts_mut((APP_START) as *mut u8, MAX_APP_SIZE) };
+    images
+}
+
+/// 要装载运行的程序及其参数
+pub struct Command {
+    /// 程序在文件系统中的路径，为空时运行 payload 中的程序
+    pub path: Option<String>,
+    pub argv: Vec<String>,
+    pub envp: Vec<String>,
+}
+
+impl Command {
+    /// 读取 [`LOADER_CMDLINE_PATH`]，文件不存在或没有命令行时运行 payload 中的程序。
+    ///
+    /// 文件格式：`#` 开头的行和空行被忽略；`NAME=value` 形式的行加入 `envp`；
+    /// 第一个其他行按空白分隔，第一项是程序路径，整行作为 `argv`。
+    /// 例如 `STRACE=%fs,!write` 打开系统调用跟踪，见 `syscall::strace`。
+    pub fn from_config() -> Self {
+        let parsed = axfs::api::read(LOADER_CMDLINE_PATH)
+            .ok()
+            .and_then(|data| String::from_utf8(data).ok())
+            .and_then(|text| Self::parse(&text));
+        parsed.unwrap_or_else(|| {
+            debug!("No {}, run app in payload", LOADER_CMDLINE_PATH);
+            Self {
+                path: None,
+                argv: vec!["app".into()],
+                envp: Vec::new(),
+            }
+        })
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut envp = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let first = line.split_whitespace().next()?;
+            if !first.starts_with('/') && first.contains('=') {
+                envp.push(line.into());
+                continue;
+            }
+            return Some(Self {
+                path: Some(first.into()),
+                argv: line.split_whitespace().map(String::from).collect(),
+                envp,
+            });
+        }
+        None
+    }
+}
+
+/// 装载完成、可以开始运行的程序
+pub struct LoadedProgram {
+    /// 跳转地址：动态程序为运行时库的入口
+    pub entry: usize,
+    pub aux: AuxInfo,
+    /// 静态 TLS 模板，由装载者交给进程
+    pub tls: TlsTemplate,
+    /// 程序在用户态运行，通过`ecall`进行系统调用，自己初始化 TLS（只支持 riscv64）
+    pub user: bool,
+}
+
+pub fn load_elf(cmd: &Command) -> LoadedProgram {
+    let (app_data, mut libs): (Cow<'static, [u8]>, Box<dyn LibraryProvider>) = match &cmd.path {
+        Some(path) => {
+            debug!("Load {} ...", path);
+            let app = read_image(path).unwrap_or_else(|| panic!("Failed to read {}", path));
+            (
+                Cow::Owned(app),
+                Box::new(FileLibraries::new(LIB_SEARCH_PATHS)),
+            )
+        }
+        None => {
+            debug!("Load payload ...");
+            let images = payload_images();
+            let Some((&app, lib_slices)) = images.split_first() else {
+                panic!("No app found in payload");
+            };
+            let libs = PayloadLibraries::new(lib_slices).expect("Failed to parse payload libs");
+            (Cow::Borrowed(app), Box::new(libs))
+        }
+    };
+    let app_elf_slice: &[u8] = &app_data;
+    let app_elf_size = app_elf_slice.len();
+    debug!("app_elf_size 0x{:x}", app_elf_size);
 
     let app_elf: ElfBytes<'_, LittleEndian> =
         ElfBytes::<LittleEndian>::minimal_parse(app_elf_slice).expect("Failed to parse ELF");
@@ -60,51 +176,254 @@ pub fn load_elf() -> u64 {
         is_need_interp
     );
 
-    let entry: u64 = {
-        if is_need_interp == false {
+    let user = !is_need_interp && is_user_image(&app_elf);
+    if user && !USER_MODE {
+        panic!(
+            "Running programs in user mode is not supported on {}",
+            ARCH_NAME
+        );
+    }
+    let (entry, aux, tls) = {
+        if user {
+            debug!("Static app linked in user space");
+            load_user_image(&app_elf, app_elf_slice);
+            let entry = app_elf.ehdr.e_entry as usize;
+            (entry, aux_info(&app_elf, 0, entry, 0), TlsTemplate::empty())
+        } else if is_need_interp == false {
             // Static and position independent executable
+            // 按链接地址装载到内核地址空间中的固定位置，不属于进程的地址空间，无法写时复制，
+            // 所以这样的进程不能`fork`
             debug!("Static and position independent app");
+            if !fits_app_window(&app_elf) {
+                panic!(
+                    "app segments exceed the {:#x} bytes at APP_START",
+                    MAX_APP_SIZE
+                );
+            }
+            current_process().memory_set.lock().set_fixed_image();
+            let app_code = unsafe { from_raw_parts_mut((APP_START) as *mut u8, MAX_APP_SIZE) };
             let _ = load_exec(&app_elf, app_elf_slice, app_code);
-            app_elf.ehdr.e_entry
+            flush_icache();
+            // 静态程序只有一个 TLS 模块，链接器已经按同样的布局算好了它相对`tp`的偏移
+            let template = match tls_segment(&app_elf) {
+                Some(ph) => {
+                    let mut layout = TlsLayout::new();
+                    let module = layout.add(ph.p_memsz as usize, ph.p_align as usize);
+                    let mut template = layout.template();
+                    let offset = ph.p_offset as usize;
+                    template.copy_module(
+                        module,
+                        &app_elf_slice[offset..offset + ph.p_filesz as usize],
+                    );
+                    template
+                }
+                None => TlsTemplate::empty(),
+            };
+            let entry = app_elf.ehdr.e_entry as usize;
+            (entry, aux_info(&app_elf, 0, entry, 0), template)
         } else {
             debug!("Dynamic link app");
-            let lib_elf_size = unsafe { *((PLASH_START + app_elf_size + 0x8) as *const usize) };
-            if lib_elf_size > MAX_LIB_SIZE {
-                panic!("lib elf size > MAP LIB SIZE");
-            }
-            let lib_elf_slice = unsafe {
-                from_raw_parts(
-                    (PLASH_START + app_elf_size + 0x10) as *const u8,
-                    lib_elf_size,
-                )
-            };
-            let lib_code = unsafe { from_raw_parts_mut((LIB_START) as *mut u8, MAX_LIB_SIZE) };
+            let linker = DynamicLinker::new(app_elf_slice, libs.as_mut())
+                .unwrap_or_else(|e| panic!("Failed to load shared objects: {}", e));
+            linker
+                .relocate()
+                .unwrap_or_else(|e| panic!("Failed to relocate: {}", e));
+            flush_icache();
+            let app_base = linker.app_base();
+            let app_entry = app_base + app_elf.ehdr.e_entry as usize;
+            (
+                linker.entry(),
+                aux_info(&app_elf, app_base, app_entry, linker.interp_base()),
+                linker.tls_template(),
+            )
+        }
+    };
 
-            let lib_elf: ElfBytes<'_, LittleEndian> =
-                ElfBytes::<LittleEndian>::minimal_parse(lib_elf_slice)
-                    .expect("Failed to parse ELF at LIB file");
+    println!("App elf size: 0x{:x}", app_elf_size);
+    LoadedProgram {
+        entry,
+        aux,
+        tls,
+        user,
+    }
+}
 
-            debug!(
-                "ELF Headers App: 0x{:x}, Lib: 0x{:x}",
-                app_elf.ehdr.e_ehsize, lib_elf.ehdr.e_ehsize
-            );
+/// 在`execve`清空当前地址空间之前检查文件内容为`data`的程序能否装载
+///
+/// 检查 ELF 头、各个段在文件中的范围和装载位置；动态链接的程序还要找到它依赖的全部
+/// 共享对象。通过检查的程序在[`load_elf`]中不会因为文件内容而失败。
+pub fn check_program(data: &[u8]) -> Result<(), LoadError> {
+    let elf = ElfBytes::<LittleEndian>::minimal_parse(data).map_err(|_| LoadError::InvalidMagic)?;
+    verify_elf_header(&elf)?;
+    verify_segments(&elf, data.len())?;
+    let phdrs = elf.segments().ok_or(LoadError::NoSegments)?;
+    if phdrs.iter().any(|ph| ph.p_type == PT_INTERP) {
+        elf.dynamic_symbol_table()
+            .map_err(|_| LoadError::UndefinedSymbol)?;
+        return FileLibraries::new(LIB_SEARCH_PATHS).check(&elf);
+    }
+    if is_user_image(&elf) {
+        return match USER_MODE {
+            true => Ok(()),
+            false => Err(LoadError::NotExecutable),
+        };
+    }
+    // 其余的静态程序按链接地址复制到`APP_START`开始的固定区域中
+    match fits_app_window(&elf) {
+        true => Ok(()),
+        false => Err(LoadError::SegmentOutOfBounds),
+    }
+}
 
-            debug!("Load lib to mem space");
-            load_dyn(&lib_elf, lib_elf_slice, lib_code, 0);
-            debug!("Load app to mem space");
-            load_dyn(&app_elf, app_elf_slice, app_code, 0);
+/// 所有`PT_LOAD`段都落在`APP_START`开始、大小为[`MAX_APP_SIZE`]的固定区域中
+fn fits_app_window(elf: &ElfBytes<LittleEndian>) -> bool {
+    let Some(phdrs) = elf.segments() else {
+        return false;
+    };
+    phdrs.iter().filter(|ph| ph.p_type == PT_LOAD).all(|ph| {
+        let start = ph.p_vaddr as usize;
+        start >= APP_START && start + ph.p_memsz as usize <= APP_START + MAX_APP_SIZE
+    })
+}
 
-            modify_plt_for_app(&app_elf, &lib_elf);
-            modify_plt_for_lib(&app_elf, &lib_elf);
+/// 链接在低地址（[`USER_IMAGE_BASE`]～[`USER_IMAGE_END`]）的静态程序
+///
+/// 链接了 mocklibc 的程序都链接在内核地址空间中；链接在低地址的是普通的 Linux 程序，
+/// 它们只能在用户态运行。
+fn is_user_image(elf: &ElfBytes<LittleEndian>) -> bool {
+    let Some(phdrs) = elf.segments() else {
+        return false;
+    };
+    let mut loads = phdrs.iter().filter(|ph| ph.p_type == PT_LOAD).peekable();
+    elf.ehdr.e_type == ET_EXEC
+        && loads.peek().is_some()
+        && loads.all(|ph| {
+            let start = ph.p_vaddr as usize;
+            start >= USER_IMAGE_BASE && start + ph.p_memsz as usize <= USER_IMAGE_END
+        })
+}
 
-            println!("Lib elf size: 0x{:x}", lib_elf_size);
-            LIB_START as u64 + lib_elf.ehdr.e_entry
-            // APP_START as u64 + app_elf.ehdr.e_entry
+/// 按链接地址把程序装载到当前进程中，之后的区域都可以从用户态访问
+///
+/// 与动态链接的程序一样，整个镜像是一段可读写执行的区域，页帧在首次访问时分配。
+/// 同时在[`SIGRETURN_TRAMPOLINE`]处映射信号处理函数返回用的跳板。
+fn load_user_image(app_elf: &ElfBytes<LittleEndian>, app_elf_slice: &[u8]) {
+    verify_elf_header(app_elf).expect("Failed to verify ELF header");
+    let phdrs = app_elf.segments().expect("Failed to parse program headers");
+    let loads = || phdrs.iter().filter(|ph| ph.p_type == PT_LOAD);
+    let start = loads().map(|ph| ph.p_vaddr as usize).min().unwrap();
+    let end = loads()
+        .map(|ph| (ph.p_vaddr + ph.p_memsz) as usize)
+        .max()
+        .unwrap();
+    let start = VirtAddr::from(start).align_down_4k();
+    let end = VirtAddr::from(end).align_up_4k();
+
+    // 每页的权限取覆盖它的所有段的并集，两个段共用一页时才会合并
+    let mut page_flags =
+        vec![MappingFlags::empty(); (end.as_usize() - start.as_usize()) / PAGE_SIZE_4K];
+    for ph in loads() {
+        let seg_start = VirtAddr::from(ph.p_vaddr as usize).align_down_4k();
+        let seg_end = VirtAddr::from((ph.p_vaddr + ph.p_memsz) as usize).align_up_4k();
+        let first = (seg_start.as_usize() - start.as_usize()) / PAGE_SIZE_4K;
+        let last = (seg_end.as_usize() - start.as_usize()) / PAGE_SIZE_4K;
+        for flags in &mut page_flags[first..last] {
+            *flags |= segment_flags(ph.p_flags);
         }
-    };
+    }
 
-    println!("App elf size: 0x{:x}", app_elf_size);
-    return entry;
+    let process = current_process();
+    let mut memory_set = process.memory_set.lock();
+    memory_set.set_user();
+    // 权限相同的连续页建成一个区域；段之间的空洞不映射
+    let mut page = 0;
+    while page < page_flags.len() {
+        let flags = page_flags[page];
+        let count = page_flags[page..]
+            .iter()
+            .take_while(|&&f| f == flags)
+            .count();
+        if !flags.is_empty() {
+            memory_set
+                .map_eager(start + page * PAGE_SIZE_4K, count * PAGE_SIZE_4K, flags)
+                .expect("Failed to map the program image");
+        }
+        page += count;
+    }
+    let trampoline = VirtAddr::from(SIGRETURN_TRAMPOLINE);
+    memory_set
+        .map_eager(
+            trampoline,
+            PAGE_SIZE_4K,
+            MappingFlags::READ | MappingFlags::EXECUTE,
+        )
+        .expect("Failed to map the sigreturn trampoline");
+
+    // 只读的段不能直接写，通过地址空间写入页帧；页帧分配时已清零，`.bss`无需再填
+    let aspace = memory_set.aspace().lock();
+    aspace
+        .write(trampoline, SIGRETURN_CODE)
+        .expect("Failed to write the sigreturn trampoline");
+    for ph in loads() {
+        let offset = ph.p_offset as usize;
+        let filesz = ph.p_filesz as usize;
+        debug!(
+            "Load Segment vaddr 0x{:x} offset 0x{:x} filesz 0x{:x} memsz 0x{:x} flags {:#x}",
+            ph.p_vaddr, offset, filesz, ph.p_memsz, ph.p_flags
+        );
+        aspace
+            .write(
+                VirtAddr::from(ph.p_vaddr as usize),
+                &app_elf_slice[offset..offset + filesz],
+            )
+            .expect("Failed to load the program image");
+    }
+    drop(aspace);
+    drop(memory_set);
+    flush_icache();
+}
+
+/// 段的`p_flags`对应的映射权限
+fn segment_flags(p_flags: u32) -> MappingFlags {
+    let mut flags = MappingFlags::empty();
+    if p_flags & PF_R != 0 {
+        flags |= MappingFlags::READ;
+    }
+    if p_flags & PF_W != 0 {
+        flags |= MappingFlags::WRITE;
+    }
+    if p_flags & PF_X != 0 {
+        flags |= MappingFlags::EXECUTE;
+    }
+    flags
+}
+
+/// 程序头表的运行地址：优先取 `PT_PHDR`，否则假定它位于文件偏移为 0 的 `PT_LOAD` 段中
+fn aux_info(
+    elf: &ElfBytes<LittleEndian>,
+    base: usize,
+    entry: usize,
+    interp_base: usize,
+) -> AuxInfo {
+    let phdrs = elf.segments().expect("Failed to parse program headers");
+    let phdr = phdrs
+        .iter()
+        .find(|ph| ph.p_type == PT_PHDR)
+        .map(|ph| ph.p_vaddr)
+        .or_else(|| {
+            phdrs
+                .iter()
+                .find(|ph| ph.p_type == PT_LOAD && ph.p_offset == 0)
+                .map(|ph| ph.p_vaddr + elf.ehdr.e_phoff)
+        })
+        .unwrap_or(elf.ehdr.e_phoff);
+    AuxInfo {
+        phdr: base + phdr as usize,
+        phent: elf.ehdr.e_phentsize as usize,
+        phnum: elf.ehdr.e_phnum as usize,
+        entry,
+        base: interp_base,
+    }
 }
 
 fn load_exec(
@@ -152,6 +471,7 @@ fn load_exec(
                 let dest = &mut app_code[dest_addr + filesz..dest_addr + memsz];
                 dest.fill(0);
             }
+            set_executable(vaddr, memsz);
         }
     }
     /* ```
@@ -167,349 +487,3 @@ fn load_exec(
     ```*/
     Ok(())
 }
-
-fn load_dyn(
-    elf: &ElfBytes<LittleEndian>,
-    elf_slice: &[u8],
-    run_code: &mut [u8],
-    address_bios: usize,
-) {
-    let phdrs = elf.segments().expect("Failed to parse program headers");
-    for phdr in phdrs {
-        if phdr.p_type != elf::abi::PT_LOAD {
-            continue;
-        }
-        debug!(
-            "Load Segment vaddr 0x{:x} offset 0x{:x} filesz 0x{:x} memsz 0x{:x} address_bios 0x{:x}",
-            phdr.p_vaddr as usize,
-            phdr.p_offset as usize,
-            phdr.p_offset as usize,
-            phdr.p_memsz as u64,
-            address_bios
-        );
-        load_segment(
-            run_code,
-            elf_slice,
-            phdr.p_vaddr as usize,
-            phdr.p_offset as usize,
-            phdr.p_filesz as usize,
-            phdr.p_memsz as usize,
-            address_bios,
-        );
-    }
-}
-
-fn load_segment(
-    run_code: &mut [u8],
-    elf_slice: &[u8],
-    p_vaddr: usize,
-    p_offset: usize,
-    p_filesz: usize,
-    p_memsz: usize,
-    address_bios: usize,
-) {
-    // Copy the segment into the executable zone
-    // If `memsz` is larger than `filesz`, zero out the rest
-    let run_code_offset = p_vaddr - address_bios;
-    run_code[run_code_offset..run_code_offset + p_filesz]
-        .copy_from_slice(&elf_slice[p_offset..p_offset + p_filesz]);
-    if p_memsz > p_filesz {
-        let zero_size = min(run_code.len() - p_filesz, p_memsz - p_filesz);
-        run_code[run_code_offset + p_filesz..run_code_offset + p_filesz + zero_size].fill(0);
-    }
-}
-
-fn modify_plt_for_lib(app_elf: &ElfBytes<LittleEndian>, lib_elf: &ElfBytes<LittleEndian>) {
-    let (lib_dynsym_table, lib_dynstr_table) = lib_elf
-        .dynamic_symbol_table()
-        .expect("Failed to parse dynamic symbol table")
-        .expect("ELF should have a dynamic symbol table");
-    let (app_dynsym_table, app_dynstr_table) = app_elf
-        .dynamic_symbol_table()
-        .expect("Failed to parse dynamic symbol table")
-        .expect("ELF should have a dynamic symbol table");
-
-    debug!("modify for LIB-RELA.PLT so lib");
-    let lib_rela_plt_shdr = lib_elf
-        .section_header_by_name(".rela.plt")
-        .expect("section table should be parseable")
-        .expect("elf should have a .rela.plt section");
-    let lib_rela_plts = lib_elf
-        .section_data_as_relas(&lib_rela_plt_shdr)
-        .expect("Failed to parse .rela.plt section");
-
-    let run_code_entry_name = "main";
-
-    for lib_rela_plt in lib_rela_plts {
-        let lib_sym = lib_dynsym_table
-            .get(lib_rela_plt.r_sym as usize)
-            .expect(&format!(
-                "Failed to get symbol for index: {}",
-                lib_rela_plt.r_sym
-            ));
-        let lib_rela_name = lib_dynstr_table
-            .get(lib_sym.st_name as usize)
-            .expect(&format!(
-                "Failed to get symbol name for index: {}",
-                lib_sym.st_name
-            ));
-        let lib_rela_type = lib_rela_plt.r_type;
-
-        match lib_rela_type {
-            // Indicates the symbol associated with a `PLT` entry: `S`
-            R_RISCV_JUMP_SLOT => {
-                if lib_rela_name == run_code_entry_name {
-                    // Find symbol main in APP ELF
-                    let app_sym = app_dynsym_table
-                        .iter()
-                        .find(|s| {
-                            let name = app_dynstr_table.get(s.st_name as usize).unwrap_or(&"");
-                            name == lib_rela_name
-                        })
-                        .expect("Failed to find symbol in APP dynamic symbol table");
-
-                    unsafe {
-                        *((LIB_START as u64 + lib_rela_plt.r_offset) as *mut usize) =
-                            APP_START + app_sym.st_value as usize;
-
-                        debug!(
-                            "[Lib-plt ENTRY] @0x{:x} value 0x{:x} type {} st_name {}",
-                            LIB_START as u64 + lib_rela_plt.r_offset,
-                            APP_START + app_sym.st_value as usize,
-                            lib_sym.st_symtype(),
-                            lib_rela_name,
-                        );
-                        if app_sym.st_value == 0 {
-                            panic!("Bad st_value");
-                        }
-                    }
-                } else {
-                    unsafe {
-                        *((LIB_START as u64 + lib_rela_plt.r_offset) as *mut usize) =
-                            LIB_START + lib_sym.st_value as usize;
-                        debug!(
-                            "[Lib-rela.plt R_RISCV_JUMP_SLOT] @0x{:x}=0x{:x} st_name {}",
-                            LIB_START as u64 + lib_rela_plt.r_offset,
-                            LIB_START + lib_sym.st_value as usize,
-                            lib_rela_name,
-                        );
-                        if lib_sym.st_value == 0 {
-                            panic!("Bad st_value");
-                        }
-                    }
-                }
-            }
-            _ => {
-                panic!("Unknown relocation type: {}", lib_rela_type);
-            }
-        }
-    }
-
-    debug!("modify for LIB-RELA.DYN so lib");
-    let lib_rela_dyn_shdr = lib_elf
-        .section_header_by_name(".rela.dyn")
-        .expect("section table should be parseable")
-        .expect("elf should have a .rela.dyn section");
-    let lib_rela_dyns = lib_elf
-        .section_data_as_relas(&lib_rela_dyn_shdr)
-        .expect("Failed to parse .rela.dyn section");
-
-    for lib_rela_dyn in lib_rela_dyns {
-        let lib_sym = lib_dynsym_table
-            .get(lib_rela_dyn.r_sym as usize)
-            .expect(&format!(
-                "Failed to get symbol for index: {}",
-                lib_rela_dyn.r_sym
-            ));
-        let lib_rela_name = lib_dynstr_table
-            .get(lib_sym.st_name as usize)
-            .expect(&format!(
-                "Failed to get symbol name for index: {}",
-                lib_sym.st_name
-            ));
-        let lib_rela_type = lib_rela_dyn.r_type;
-
-        match lib_rela_type {
-            R_RISCV_RELATIVE => {
-                // Adjust a link address (A) to its load address: `(B + A)`.
-                unsafe {
-                    *((LIB_START as u64 + lib_rela_dyn.r_offset) as *mut usize) =
-                        LIB_START + lib_rela_dyn.r_addend as usize; //  + lib_sym.st_value as usize;
-                }
-                debug!(
-                    "[Lib-rela.dyn R_RISCV_RELATIVE] @0x{:x}=0x{:x}",
-                    LIB_START as u64 + lib_rela_dyn.r_offset,
-                    LIB_START + lib_rela_dyn.r_addend as usize, // + lib_sym.st_value as usize,
-                );
-            }
-            R_RISCV_64 => {
-                // 64-bit relocation: `S + A`.
-                unsafe {
-                    *((LIB_START as u64 + lib_rela_dyn.r_offset) as *mut usize) =
-                        LIB_START + lib_sym.st_value as usize;
-                }
-                debug!(
-                    "[Lib-rela.dyn R_RISCV_64] @0x{:x}=0x{:x} name {}",
-                    LIB_START as u64 + lib_rela_dyn.r_offset,
-                    LIB_START + lib_sym.st_value as usize,
-                    lib_rela_name,
-                );
-                if lib_sym.st_value == 0 {
-                    panic!("Bad st_value");
-                }
-            }
-            _ => {
-                panic!("Unknown relocation type: {}", lib_rela_type);
-            }
-        }
-    }
-}
-
-fn modify_plt_for_app(app_elf: &ElfBytes<LittleEndian>, lib_elf: &ElfBytes<LittleEndian>) {
-    // RISC-V relocation types
-    //
-    // `A` Addend field in the relocation entry associated with the symbol.
-    // `B` Base address of a shared object loaded into memory.
-    // `G` Offset of the symbol into the GOT (Global Offset Table).
-    // `GOT` Address of the GOT (Global Offset Table).
-    // `P` Position of the relocation.
-    // `S` Value of the symbol in the symbol table.
-    // `V` Value at the position of the relocation.
-    // `GP` Value of __global_pointer$ symbol.
-    // `TLSMODULE` TLS module index for the object containing the symbol.
-    // `TLSOFFSET` TLS static block offset (relative to `tp`) for the object containing the symbol.
-
-    let (app_dynsym_table, app_dynstr_table) = app_elf
-        .dynamic_symbol_table()
-        .expect("Failed to parse dynamic symbol table")
-        .expect("ELF should have a dynamic symbol table");
-    let (lib_dynsym_table, lib_dynstr_table) = lib_elf
-        .dynamic_symbol_table()
-        .expect("Failed to parse LIB dynamic symbol table")
-        .expect("LIB ELF should have a dynamic symbol table");
-
-    debug!("modify for APP-RELA.PLT run code");
-    let app_rela_plt_shdr = app_elf
-        .section_header_by_name(".rela.plt")
-        .expect("section table should be parseable")
-        .expect("elf should have a .rela.plt section");
-    let app_rela_plts = app_elf
-        .section_data_as_relas(&app_rela_plt_shdr)
-        .expect("Failed to parse .rela.dyn section");
-
-    for app_rela_plt in app_rela_plts {
-        // Get the `r_sym'th` symbol from the dynamic symbol table
-        let app_sym = app_dynsym_table
-            .get(app_rela_plt.r_sym as usize)
-            .expect("Failed to get symbol");
-        let app_rela_name = app_dynstr_table
-            .get(app_sym.st_name as usize)
-            .expect("Failed to get symbol name");
-
-        // Find symbol in LIB ELF
-        let lib_sym = lib_dynsym_table
-            .iter()
-            .find(|s| {
-                let name = lib_dynstr_table.get(s.st_name as usize).unwrap_or(&"");
-                // 如果开始寻找`__libc_start_main`，就替换成我们的实现
-                name == app_rela_name
-            })
-            .expect("Failed to find symbol in LIB dynamic symbol table");
-
-        unsafe {
-            debug!(
-                "[App-plt] @0x{:x} value 0x{:x} app-type {} lib-type {} st_name {}",
-                APP_START as u64 + app_rela_plt.r_offset,
-                LIB_START + lib_sym.st_value as usize,
-                app_sym.st_symtype(),
-                lib_sym.st_symtype(),
-                app_rela_name,
-            );
-
-            *((APP_START as u64 + app_rela_plt.r_offset) as *mut usize) =
-                LIB_START + lib_sym.st_value as usize;
-            if lib_sym.st_value == 0 {
-                panic!("Bad st_value");
-            }
-        }
-    }
-
-    debug!("modify for APP-RELA.DYN run code");
-    let app_rela_dyn_shdr = app_elf
-        .section_header_by_name(".rela.dyn")
-        .expect("section table should be parseable")
-        .expect("elf should have a .rela.dyn section");
-    let app_rela_dyns = app_elf
-        .section_data_as_relas(&app_rela_dyn_shdr)
-        .expect("Failed to parse .rela.dyn section");
-
-    for app_rela_dyn in app_rela_dyns {
-        let app_sym = app_dynsym_table
-            .get(app_rela_dyn.r_sym as usize)
-            .expect(&format!(
-                "Failed to get symbol for index: {}",
-                app_rela_dyn.r_sym
-            ));
-        let app_rela_name = app_dynstr_table
-            .get(app_sym.st_name as usize)
-            .expect(&format!(
-                "Failed to get symbol name for index: {}",
-                app_sym.st_name
-            ));
-        let app_rela_type = app_rela_dyn.r_type;
-
-        match app_rela_type {
-            R_RISCV_RELATIVE => {
-                // Adjust a link address (A) to its load address: `(B + A)`.
-                unsafe {
-                    *((APP_START as u64 + app_rela_dyn.r_offset) as *mut usize) =
-                        APP_START + app_rela_dyn.r_addend as usize;
-                }
-                debug!(
-                    "[App-rela.dyn R_RISCV_RELATIVE] @0x{:x}=0x{:x}",
-                    APP_START as u64 + app_rela_dyn.r_offset,
-                    APP_START + app_rela_dyn.r_addend as usize,
-                );
-            }
-            R_RISCV_64 => {
-                // 64-bit relocation: `S + A`.
-                unsafe {
-                    *((APP_START as u64 + app_rela_dyn.r_offset) as *mut usize) =
-                        APP_START + app_sym.st_value as usize;
-                }
-                debug!(
-                    "[App-rela.dyn R_RISCV_64] @0x{:x}=0x{:x} name {}",
-                    APP_START as u64 + app_rela_dyn.r_offset,
-                    APP_START + app_sym.st_value as usize,
-                    app_rela_name,
-                );
-                if app_sym.st_value == 0 {
-                    // Find symbol in LIB ELF
-                    let lib_sym = lib_dynsym_table
-                        .iter()
-                        .find(|s| {
-                            let name = lib_dynstr_table.get(s.st_name as usize).unwrap_or(&"");
-                            name == app_rela_name
-                        })
-                        .expect("Failed to find symbol in LIB dynamic symbol table");
-
-                    unsafe {
-                        *((APP_START as u64 + app_rela_dyn.r_offset) as *mut usize) =
-                            LIB_START + lib_sym.st_value as usize;
-                    }
-                    debug!(
-                        "[App-rela.dyn GLOBAL | WEAK](re) @0x{:x}=0x{:x} name {}",
-                        APP_START as u64 + app_rela_dyn.r_offset,
-                        LIB_START + lib_sym.st_value as usize,
-                        app_rela_name,
-                    );
-
-                    warn!("Bad st_value");
-                }
-            }
-            _ => {
-                panic!("Unknown relocation type: {}", app_rela_type);
-            }
-        }
-    }
-}
@@ -0,0 +1,149 @@
+//! 按 Linux 约定为客户程序构造初始栈
+//!
+//! 从低地址到高地址依次为：`argc`、`argv[]`、`NULL`、`envp[]`、`NULL`、auxv 键值对
+//! （以 `AT_NULL` 结尾），之后是 `AT_RANDOM` 的 16 字节随机数和各字符串本身。
+
+use alloc::{string::String, vec::Vec};
+
+use axhal::{
+    mem::{PAGE_SIZE_4K, VirtAddr},
+    paging::MappingFlags,
+};
+use axlog::debug;
+
+use crate::{
+    config::{USER_STACK_SIZE, USER_STACK_TOP},
+    linux_env::process_ext::api::current_process,
+};
+
+pub const AT_NULL: usize = 0;
+pub const AT_PHDR: usize = 3;
+pub const AT_PHENT: usize = 4;
+pub const AT_PHNUM: usize = 5;
+pub const AT_PAGESZ: usize = 6;
+pub const AT_BASE: usize = 7;
+pub const AT_ENTRY: usize = 9;
+pub const AT_UID: usize = 11;
+pub const AT_EUID: usize = 12;
+pub const AT_GID: usize = 13;
+pub const AT_EGID: usize = 14;
+pub const AT_SECURE: usize = 23;
+pub const AT_RANDOM: usize = 25;
+
+/// 生成辅助向量所需的程序信息
+pub struct AuxInfo {
+    /// 主程序程序头表在内存中的地址
+    pub phdr: usize,
+    pub phent: usize,
+    pub phnum: usize,
+    /// 主程序自身的入口（不是运行时库的入口）
+    pub entry: usize,
+    /// 解释器（运行时库）的装载基址，静态程序为 0
+    pub base: usize,
+}
+
+/// 自顶向下填充的栈
+struct StackWriter {
+    sp: usize,
+    bottom: usize,
+}
+
+impl StackWriter {
+    fn push_bytes(&mut self, bytes: &[u8]) -> usize {
+        self.sp -= bytes.len();
+        assert!(self.sp >= self.bottom, "user stack overflow");
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), self.sp as *mut u8, bytes.len()) };
+        self.sp
+    }
+
+    fn push_str(&mut self, s: &str) -> usize {
+        self.push_bytes(&[0]);
+        self.push_bytes(s.as_bytes())
+    }
+
+    fn push_usize(&mut self, value: usize) {
+        self.push_bytes(&value.to_ne_bytes());
+    }
+}
+
+/// 映射一个用户栈并写入 `argv`、`envp` 和辅助向量，返回程序入口处应使用的 `sp`
+///
+/// 栈映射在当前进程的地址空间中，页帧在映射时就全部分配好。
+pub fn init_stack(argv: &[String], envp: &[String], aux: &AuxInfo) -> usize {
+    let bottom = USER_STACK_TOP - USER_STACK_SIZE;
+    current_process()
+        .memory_set
+        .lock()
+        .map_eager(
+            VirtAddr::from(bottom),
+            USER_STACK_SIZE,
+            MappingFlags::READ | MappingFlags::WRITE,
+        )
+        .expect("Failed to map user stack");
+    let mut stack = StackWriter {
+        sp: bottom + USER_STACK_SIZE,
+        bottom,
+    };
+
+    let random = stack.push_bytes(&random_bytes());
+    let envp_ptrs: Vec<usize> = envp.iter().map(|s| stack.push_str(s)).collect();
+    let argv_ptrs: Vec<usize> = argv.iter().map(|s| stack.push_str(s)).collect();
+
+    let auxv = [
+        (AT_PHDR, aux.phdr),
+        (AT_PHENT, aux.phent),
+        (AT_PHNUM, aux.phnum),
+        (AT_PAGESZ, PAGE_SIZE_4K),
+        (AT_BASE, aux.base),
+        (AT_ENTRY, aux.entry),
+        (AT_UID, 0),
+        (AT_EUID, 0),
+        (AT_GID, 0),
+        (AT_EGID, 0),
+        (AT_SECURE, 0),
+        (AT_RANDOM, random),
+        (AT_NULL, 0),
+    ];
+
+    // 入口处 `sp` 必须 16 字节对齐
+    let words = 1 + argv_ptrs.len() + 1 + envp_ptrs.len() + 1 + auxv.len() * 2;
+    stack.sp = (stack.sp - words * core::mem::size_of::<usize>()) & !0xf;
+    stack.sp += words * core::mem::size_of::<usize>();
+
+    for &(key, value) in auxv.iter().rev() {
+        stack.push_usize(value);
+        stack.push_usize(key);
+    }
+    stack.push_usize(0);
+    envp_ptrs
+        .iter()
+        .rev()
+        .for_each(|&ptr| stack.push_usize(ptr));
+    stack.push_usize(0);
+    argv_ptrs
+        .iter()
+        .rev()
+        .for_each(|&ptr| stack.push_usize(ptr));
+    stack.push_usize(argv.len());
+
+    debug!(
+        "[Stack] user stack [0x{:x}, 0x{:x}), sp 0x{:x}",
+        bottom,
+        bottom + USER_STACK_SIZE,
+        stack.sp
+    );
+    stack.sp
+}
+
+/// `AT_RANDOM` 指向的 16 字节。没有硬件随机源，用启动以来的纳秒数做 xorshift 种子。
+fn random_bytes() -> [u8; 16] {
+    let mut seed = axhal::time::monotonic_time_nanos() | 1;
+    let mut bytes = [0; 16];
+    for chunk in bytes.chunks_mut(8) {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        chunk.copy_from_slice(&seed.to_ne_bytes());
+    }
+    bytes
+}
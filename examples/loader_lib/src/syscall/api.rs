@@ -1,4 +1,4 @@
-use crate::linux_env::axfs_ext::api::OpenFlags;
+use crate::linux_env::{axfs_ext::api::OpenFlags, process_ext::api::current_process};
 use alloc::{string::String, sync::Arc};
 use axerrno::AxResult;
 use axfs::api::File;
@@ -22,3 +22,17 @@ pub fn new_file(path: &str, flags: &OpenFlags) -> AxResult<File> {
     file.create_new(flags.new_creatable());
     file.open(path)
 }
+
+/// 以当前进程的有效用户身份打开文件，没有权限时返回`PermissionDenied`。
+/// 新建的文件属于该用户，权限为`mode`去掉`umask`后的部分
+pub fn new_user_file(path: &str, flags: &OpenFlags, mode: u32) -> AxResult<File> {
+    let process = current_process();
+    let mut file = File::options();
+    file.read(flags.readable());
+    file.write(flags.writable());
+    file.create(flags.creatable());
+    file.create_new(flags.new_creatable());
+    file.mode(mode & !(process.fs.get_mask() as u32));
+    file.credentials(process.cred().fs_credentials());
+    file.open(path)
+}
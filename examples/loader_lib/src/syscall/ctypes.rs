@@ -35,6 +35,63 @@ pub const NSEC_PER_SEC: usize = 1_000_000_000;
 //     pub tms_cstime: usize,
 // }
 
+bitflags! {
+    /// `sys_mmap`/`sys_mprotect` 中指定的页面权限
+    #[derive(Debug, Clone, Copy)]
+    pub struct MMAPPROT: u32 {
+        /// 可读
+        const PROT_READ = 1 << 0;
+        /// 可写
+        const PROT_WRITE = 1 << 1;
+        /// 可执行
+        const PROT_EXEC = 1 << 2;
+    }
+}
+
+impl From<MMAPPROT> for MappingFlags {
+    fn from(prot: MMAPPROT) -> Self {
+        let mut flags = MappingFlags::empty();
+        if prot.contains(MMAPPROT::PROT_READ) {
+            flags |= MappingFlags::READ;
+        }
+        if prot.contains(MMAPPROT::PROT_WRITE) {
+            flags |= MappingFlags::WRITE;
+        }
+        if prot.contains(MMAPPROT::PROT_EXEC) {
+            flags |= MappingFlags::EXECUTE;
+        }
+        flags
+    }
+}
+
+bitflags! {
+    /// `sys_mmap` 中指定的映射方式
+    #[derive(Debug, Clone, Copy)]
+    pub struct MMAPFlags: u32 {
+        /// 对这段内存的修改是共享的，会写回文件
+        const MAP_SHARED = 1 << 0;
+        /// 对这段内存的修改是私有的
+        const MAP_PRIVATE = 1 << 1;
+        /// 必须映射到指定的地址，已有的映射会被替换
+        const MAP_FIXED = 1 << 4;
+        /// 不映射文件，内容全部为 0
+        const MAP_ANONYMOUS = 1 << 5;
+        /// 和`MAP_FIXED`相同，但不替换已有的映射
+        const MAP_FIXED_NOREPLACE = 1 << 20;
+    }
+}
+
+bitflags! {
+    /// `sys_mremap` 中指定的选项
+    #[derive(Debug, Clone, Copy)]
+    pub struct MREMAPFlags: u32 {
+        /// 原地无法扩展时允许移动到新的地址
+        const MREMAP_MAYMOVE = 1 << 0;
+        /// 移动到`new_addr`指定的地址
+        const MREMAP_FIXED = 1 << 1;
+    }
+}
+
 /// `sys_gettimeofday` 中指定的类型
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -170,6 +227,52 @@ bitflags! {
     }
 }
 
+/// `sys_prlimit64`使用的资源限制
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RLimit {
+    /// 软上限
+    pub rlim_cur: u64,
+    /// 硬上限
+    pub rlim_max: u64,
+}
+
+/// 栈的最大字节数
+pub const RLIMIT_STACK: i32 = 3;
+/// 最多能打开的文件描述符数
+pub const RLIMIT_NOFILE: i32 = 7;
+/// 地址空间的最大字节数
+pub const RLIMIT_AS: i32 = 9;
+
+/// `sys_statfs`返回的文件系统信息，即`struct statfs`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FsStat {
+    /// 文件系统的类型（magic number）
+    pub f_type: i64,
+    /// 块大小
+    pub f_bsize: i64,
+    /// 总块数
+    pub f_blocks: u64,
+    /// 空闲块数
+    pub f_bfree: u64,
+    /// 非特权用户可用的空闲块数
+    pub f_bavail: u64,
+    /// 总`inode`数
+    pub f_files: u64,
+    /// 空闲`inode`数
+    pub f_ffree: u64,
+    /// 文件系统 id
+    pub f_fsid: [i32; 2],
+    /// 文件名的最大长度
+    pub f_namelen: i64,
+    /// 片段大小
+    pub f_frsize: i64,
+    /// 挂载选项
+    pub f_flags: i64,
+    pub f_spare: [i64; 4],
+}
+
 /// readv/writev使用的结构体
 #[repr(C)]
 pub struct IoVec {
@@ -188,6 +291,12 @@ bitflags! {
         const S_IFDIR = 1 << 14;
         /// character device
         const S_IFCHR = 1 << 13;
+        /// symbolic link
+        const S_IFLNK = (1 << 15) | (1 << 13);
+        /// socket
+        const S_IFSOCK = (1 << 15) | (1 << 14);
+        /// FIFO
+        const S_IFIFO = 1 << 12;
         /// 是否设置 uid/gid/sticky
         //const S_ISUID = 1 << 14;
         //const S_ISGID = 1 << 13;
@@ -222,6 +331,13 @@ pub fn normal_file_mode(file_type: StMode) -> StMode {
     file_type | StMode::S_IWUSR | StMode::S_IRUSR | StMode::S_IRGRP | StMode::S_IROTH
 }
 
+/// `futex_op`中的标志：futex 变量只在本进程中使用，按虚拟地址匹配
+pub const FUTEX_PRIVATE_FLAG: i32 = 128;
+/// `futex_op`中的标志：`FUTEX_WAIT_BITSET`的超时按`CLOCK_REALTIME`计算
+pub const FUTEX_CLOCK_REALTIME: i32 = 256;
+/// 与任何等待者匹配的位集，`FUTEX_WAIT`和`FUTEX_WAKE`使用它
+pub const FUTEX_BITSET_MATCH_ANY: u32 = u32::MAX;
+
 /// 对 futex 的操作
 pub enum FutexFlags {
     /// 检查用户地址 uaddr 处的值。如果不是要求的值则等待 wake
@@ -230,6 +346,20 @@ pub enum FutexFlags {
     Wake,
     /// 将等待 uaddr 的线程移动到 uaddr2
     Requeue,
+    /// 先检查 uaddr 处的值等于 val3，再做`Requeue`
+    CmpRequeue,
+    /// 修改 uaddr2 处的值，唤醒 uaddr 上的线程，并按修改前的值决定是否唤醒 uaddr2 上的线程
+    WakeOp,
+    /// 获取优先级继承锁，futex 变量中保存持有者的线程号
+    LockPi,
+    /// 释放优先级继承锁
+    UnlockPi,
+    /// 不等待地获取优先级继承锁
+    TrylockPi,
+    /// 同`Wait`，但只能被位集有交集的`WakeBitset`唤醒，超时为绝对时间
+    WaitBitset,
+    /// 同`Wake`，只唤醒位集有交集的线程
+    WakeBitset,
     /// 不支持的操作
     Unsupported,
 }
@@ -241,7 +371,22 @@ impl FutexFlags {
             0 => FutexFlags::Wait,
             1 => FutexFlags::Wake,
             3 => FutexFlags::Requeue,
+            4 => FutexFlags::CmpRequeue,
+            5 => FutexFlags::WakeOp,
+            6 => FutexFlags::LockPi,
+            7 => FutexFlags::UnlockPi,
+            8 => FutexFlags::TrylockPi,
+            9 => FutexFlags::WaitBitset,
+            10 => FutexFlags::WakeBitset,
             _ => FutexFlags::Unsupported,
         }
     }
+
+    /// 第四个参数是否是指向超时时间的指针，其他操作把它当作数值`val2`
+    pub fn has_timeout(&self) -> bool {
+        matches!(
+            self,
+            FutexFlags::Wait | FutexFlags::WaitBitset | FutexFlags::LockPi
+        )
+    }
 }
@@ -1,13 +1,19 @@
 mod api;
 mod ctypes;
+mod policy;
+mod strace;
 pub mod syscall;
 mod syscall_fs;
 mod syscall_mem;
+#[cfg(feature = "net")]
+mod syscall_net;
 mod syscall_task;
 
 pub use api::*;
 use axerrno::{self, LinuxError};
 pub use ctypes::*;
+pub use policy::{dump_unimplemented_syscalls, unimplemented_syscall, unknown_syscall};
+pub use strace::configure_from_env;
 
 /// Accept the result of a syscall, and return the `isize` to the user
 pub(crate) fn deal_result(result: SyscallResult) -> isize {
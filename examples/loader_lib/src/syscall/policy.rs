@@ -0,0 +1,45 @@
+//! 未知或者未实现的系统调用的处理方式
+//!
+//! 默认返回`ENOSYS`，让客户程序自己决定如何处理；打开`strict-syscall`时直接 panic，
+//! 便于调试。每个系统调用被拒绝的次数都会记录下来，系统结束时输出，作为完善系统调用的
+//! 依据。
+use alloc::{collections::BTreeMap, string::String};
+
+use axlog::warn;
+use axsync::Mutex;
+
+use super::{SyscallError, SyscallResult};
+
+/// 各个未实现的系统调用被调用的次数，以系统调用的名字（未知的以`#编号`）为键
+static UNIMPLEMENTED_HITS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+fn reject(name: String) -> SyscallResult {
+    if cfg!(feature = "strict-syscall") {
+        panic!("unimplemented syscall: {}", name);
+    }
+    warn!("[syscall] {} is not implemented, return ENOSYS", name);
+    *UNIMPLEMENTED_HITS.lock().entry(name).or_insert(0) += 1;
+    Err(SyscallError::ENOSYS)
+}
+
+/// 系统调用`name`已经识别，但还没有实现
+pub fn unimplemented_syscall(name: &str) -> SyscallResult {
+    reject(String::from(name))
+}
+
+/// 不认识的系统调用编号
+pub fn unknown_syscall(syscall_id: usize) -> SyscallResult {
+    reject(alloc::format!("#{}", syscall_id))
+}
+
+/// 输出未实现的系统调用被调用的次数，在系统结束时调用
+pub fn dump_unimplemented_syscalls() {
+    let hits = UNIMPLEMENTED_HITS.lock();
+    if hits.is_empty() {
+        return;
+    }
+    warn!("[syscall] unimplemented syscalls:");
+    for (name, count) in hits.iter() {
+        warn!("[syscall]     {:<24} {}", name, count);
+    }
+}
@@ -0,0 +1,316 @@
+//! 把系统调用的参数和返回值格式化为可读的形式
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ffi::CStr;
+
+use super::table::SyscallDesc;
+use crate::{
+    linux_env::{
+        axfs_ext::api::{Kstat, OpenFlags as OpenFlagBits},
+        signal_ext::signal_no::SignalNo,
+    },
+    syscall::{
+        MMAPFlags, MMAPPROT, SyscallResult, TimeSecs,
+        syscall_fs::ctype::epoll::{EpollEvent as Event, EpollEventType},
+    },
+};
+
+/// 字符串最多显示的字节数
+const MAX_STR_LEN: usize = 64;
+/// 缓冲区最多显示的字节数
+const MAX_BUF_LEN: usize = 32;
+/// `argv`/`envp`最多显示的项数
+const MAX_ARRAY_LEN: usize = 8;
+/// `epoll_pwait`最多显示的事件数
+const MAX_EVENTS: usize = 8;
+
+const AT_FDCWD: isize = -100;
+
+/// 参数的类型，决定参数如何显示
+#[derive(Clone, Copy)]
+pub enum Arg {
+    /// 有符号整数
+    Int,
+    /// 无符号整数
+    Uint,
+    Hex,
+    Oct,
+    /// 指针，为 0 时显示`NULL`
+    Ptr,
+    Fd,
+    /// `*at`系列的目录`fd`，可以是`AT_FDCWD`
+    DirFd,
+    /// 以`\0`结尾的字符串
+    Str,
+    /// 成功时写入的以`\0`结尾的字符串
+    OutStr,
+    /// 以空指针结尾的字符串数组，如`argv`
+    StrArray,
+    /// 输入的缓冲区，长度在第`n`个参数
+    InBuf(usize),
+    /// 输出的缓冲区，长度为返回值
+    OutBuf,
+    OpenFlags,
+    /// `mmap`的`prot`
+    Prot,
+    /// `mmap`的`flags`
+    MapFlags,
+    /// 信号编号
+    Signal,
+    /// `*const EpollEvent`
+    EpollEvent,
+    /// `epoll_pwait`输出的事件数组，长度为返回值
+    EpollEvents,
+    /// 成功时写入的`Kstat`
+    Stat,
+    /// `pipe2`写入的两个`fd`
+    Pipe,
+    /// `*const TimeSecs`
+    Timespec,
+    /// `struct sockaddr`，目前只解析 IPv4
+    SockAddr,
+}
+
+/// 把名字用`|`连接起来，不认识的位以十六进制附在最后
+macro_rules! flag_names {
+    ($ty:ty, $bits:expr, $zero:expr) => {{
+        let bits = $bits as u32;
+        let mut names: Vec<String> = <$ty>::from_bits_truncate(bits)
+            .iter_names()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let rest = bits & !<$ty>::all().bits();
+        if rest != 0 {
+            names.push(format!("{:#x}", rest));
+        }
+        if names.is_empty() {
+            String::from($zero)
+        } else {
+            names.join("|")
+        }
+    }};
+}
+
+/// 格式化第`index`个参数。`result`为`None`时系统调用还没有返回，输出参数只显示地址。
+pub fn format_arg(
+    arg: Arg,
+    index: usize,
+    args: &[usize; 6],
+    result: Option<&SyscallResult>,
+) -> String {
+    let value = args[index];
+    let ret = match result {
+        Some(Ok(ret)) => Some(*ret as usize),
+        _ => None,
+    };
+    match arg {
+        Arg::Int => format!("{}", value as isize),
+        Arg::Uint => format!("{}", value),
+        Arg::Hex => format!("{:#x}", value),
+        Arg::Oct => format!("{:#o}", value),
+        Arg::Ptr => format_ptr(value),
+        Arg::Fd => format!("{}", value as i32),
+        Arg::DirFd if value as isize == AT_FDCWD => String::from("AT_FDCWD"),
+        Arg::DirFd => format!("{}", value as i32),
+        Arg::Str => format_str(value),
+        Arg::OutStr => match ret {
+            Some(_) => format_str(value),
+            None => format_ptr(value),
+        },
+        Arg::StrArray => format_str_array(value),
+        Arg::InBuf(len) => format_buf(value, args[len]),
+        Arg::OutBuf => match ret {
+            Some(len) => format_buf(value, len),
+            None => format_ptr(value),
+        },
+        Arg::OpenFlags => format_open_flags(value as u32),
+        Arg::Prot => flag_names!(MMAPPROT, value, "PROT_NONE"),
+        Arg::MapFlags => flag_names!(MMAPFlags, value, "0"),
+        Arg::Signal => match SignalNo::try_from(value) {
+            Ok(signo) => format!("{:?}", signo),
+            Err(_) => format!("{}", value),
+        },
+        Arg::EpollEvent if value == 0 => format_ptr(value),
+        Arg::EpollEvent => format_epoll_event(unsafe { &*(value as *const Event) }),
+        Arg::EpollEvents => match ret {
+            Some(count) if value != 0 => {
+                let events = unsafe {
+                    core::slice::from_raw_parts(value as *const Event, count.min(MAX_EVENTS))
+                };
+                let mut list: Vec<String> = events.iter().map(format_epoll_event).collect();
+                if count > MAX_EVENTS {
+                    list.push(String::from("..."));
+                }
+                format!("[{}]", list.join(", "))
+            }
+            _ => format_ptr(value),
+        },
+        Arg::Stat => match ret {
+            Some(_) if value != 0 => format_stat(unsafe { &*(value as *const Kstat) }),
+            _ => format_ptr(value),
+        },
+        Arg::Pipe => match ret {
+            Some(_) if value != 0 => {
+                let fds = unsafe { *(value as *const [u32; 2]) };
+                format!("[{}, {}]", fds[0], fds[1])
+            }
+            _ => format_ptr(value),
+        },
+        Arg::Timespec if value == 0 => format_ptr(value),
+        Arg::Timespec => {
+            let time = unsafe { &*(value as *const TimeSecs) };
+            format!("{{tv_sec={}, tv_nsec={}}}", time.tv_sec, time.tv_nsec)
+        }
+        Arg::SockAddr => format_sockaddr(value),
+    }
+}
+
+/// 格式化成功的返回值：返回地址的系统调用显示为十六进制
+pub fn format_return(desc: Option<&SyscallDesc>, value: isize) -> String {
+    match desc.map(|desc| desc.name) {
+        Some("mmap" | "mremap" | "brk" | "shmat") => format!("{:#x}", value),
+        _ => format!("{}", value),
+    }
+}
+
+fn format_ptr(value: usize) -> String {
+    if value == 0 {
+        String::from("NULL")
+    } else {
+        format!("{:#x}", value)
+    }
+}
+
+/// 转义不可打印的字符，`truncated`时在引号后加上`...`
+fn quote(bytes: &[u8], truncated: bool) -> String {
+    let mut s = String::from("\"");
+    for &byte in bytes {
+        match byte {
+            b'"' => s.push_str("\\\""),
+            b'\\' => s.push_str("\\\\"),
+            b'\n' => s.push_str("\\n"),
+            b'\t' => s.push_str("\\t"),
+            b'\r' => s.push_str("\\r"),
+            0x20..=0x7e => s.push(byte as char),
+            _ => s.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    s.push('"');
+    if truncated {
+        s.push_str("...");
+    }
+    s
+}
+
+fn format_str(addr: usize) -> String {
+    if addr == 0 {
+        return format_ptr(addr);
+    }
+    let bytes = unsafe { CStr::from_ptr(addr as *const core::ffi::c_char) }.to_bytes();
+    quote(
+        &bytes[..bytes.len().min(MAX_STR_LEN)],
+        bytes.len() > MAX_STR_LEN,
+    )
+}
+
+fn format_str_array(addr: usize) -> String {
+    if addr == 0 {
+        return format_ptr(addr);
+    }
+    let mut items = Vec::new();
+    let mut ptr = addr as *const usize;
+    loop {
+        let item = unsafe { *ptr };
+        if item == 0 {
+            break;
+        }
+        if items.len() == MAX_ARRAY_LEN {
+            items.push(String::from("..."));
+            break;
+        }
+        items.push(format_str(item));
+        ptr = unsafe { ptr.add(1) };
+    }
+    format!("[{}]", items.join(", "))
+}
+
+fn format_buf(addr: usize, len: usize) -> String {
+    if addr == 0 {
+        return format_ptr(addr);
+    }
+    let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, len.min(MAX_BUF_LEN)) };
+    quote(bytes, len > MAX_BUF_LEN)
+}
+
+fn format_open_flags(flags: u32) -> String {
+    let access = match flags & 0b11 {
+        0 => "RDONLY",
+        1 => "WRONLY",
+        _ => "RDWR",
+    };
+    let rest = flags & !0b11;
+    if rest == 0 {
+        String::from(access)
+    } else {
+        format!("{}|{}", access, flag_names!(OpenFlagBits, rest, ""))
+    }
+}
+
+fn format_epoll_event(event: &Event) -> String {
+    // x86_64 上结构体是紧凑的，字段要先复制出来
+    let Event { event_type, data } = *event;
+    format!(
+        "{{{}, data={:#x}}}",
+        flag_names!(EpollEventType, event_type.bits(), "0"),
+        data
+    )
+}
+
+/// `st_mode`的文件类型
+fn file_type(mode: u32) -> &'static str {
+    match mode & 0o170000 {
+        0o140000 => "S_IFSOCK",
+        0o120000 => "S_IFLNK",
+        0o100000 => "S_IFREG",
+        0o060000 => "S_IFBLK",
+        0o040000 => "S_IFDIR",
+        0o020000 => "S_IFCHR",
+        0o010000 => "S_IFIFO",
+        _ => "0",
+    }
+}
+
+fn format_stat(stat: &Kstat) -> String {
+    format!(
+        "{{st_dev={}, st_ino={}, st_mode={}|{:#o}, st_nlink={}, st_size={}, st_blocks={}}}",
+        stat.st_dev,
+        stat.st_ino,
+        file_type(stat.st_mode),
+        stat.st_mode & 0o7777,
+        stat.st_nlink,
+        stat.st_size,
+        stat.st_blocks
+    )
+}
+
+fn format_sockaddr(addr: usize) -> String {
+    if addr == 0 {
+        return format_ptr(addr);
+    }
+    let family = unsafe { *(addr as *const u16) };
+    if family != 2 {
+        return format!("{{sa_family={}}}", family);
+    }
+    let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, 8) };
+    format!(
+        "{{sa_family=AF_INET, {}.{}.{}.{}:{}}}",
+        bytes[4],
+        bytes[5],
+        bytes[6],
+        bytes[7],
+        u16::from_be_bytes([bytes[2], bytes[3]])
+    )
+}
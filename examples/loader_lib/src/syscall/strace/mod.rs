@@ -0,0 +1,235 @@
+//! 类似`strace`的系统调用跟踪
+//!
+//! 打开后每个被选中的系统调用在返回时输出一行：
+//!
+//! ```text
+//! [1:3] openat(AT_FDCWD, "/etc/passwd", RDONLY|CLOEXEC, 0) = 3 <12us>
+//! [1:3] read(3, "root:x:0:0:"..., 4096) = 512 <40us>
+//! [1:3] openat(AT_FDCWD, "/nope", RDONLY, 0) = -2 ENOENT <8us>
+//! ```
+//!
+//! 行首是`[pid:tid]`，最后是调用耗时。不会返回的`exit`、`exit_group`和`execve`在进入时
+//! 输出。输出不受日志等级影响。
+//!
+//! 跟踪在运行时用过滤表达式选择，见[`set_trace_filter`]。程序（包括`execve`的新程序）
+//! 启动时，若环境变量中有`STRACE=表达式`则按它重新设置。
+mod decode;
+mod table;
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use axhal::time::{TimeValue, monotonic_time};
+use axlog::ax_println;
+use axsync::Mutex;
+use axtask::current;
+
+use self::table::{SyscallClass, SyscallDesc, lookup};
+use super::SyscallResult;
+use crate::linux_env::process_ext::api::try_current_process;
+
+/// 设置过滤表达式的环境变量
+const STRACE_ENV: &str = "STRACE";
+
+/// 是否有被选中的系统调用，关闭时系统调用入口只需读一次这个值
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static FILTER: Mutex<TraceFilter> = Mutex::new(TraceFilter::new());
+
+/// 过滤表达式中的一项
+enum Term {
+    All,
+    Name(String),
+    Id(usize),
+    Class(SyscallClass),
+}
+
+impl Term {
+    fn matches(&self, id: usize, desc: Option<&SyscallDesc>) -> bool {
+        match self {
+            Term::All => true,
+            Term::Name(name) => desc.is_some_and(|desc| desc.name == name),
+            Term::Id(term_id) => *term_id == id,
+            Term::Class(class) => desc.is_some_and(|desc| desc.class == *class),
+        }
+    }
+}
+
+/// 解析后的过滤表达式
+struct TraceFilter {
+    include: Vec<Term>,
+    exclude: Vec<Term>,
+    /// 只输出失败的调用
+    failed_only: bool,
+}
+
+impl TraceFilter {
+    const fn new() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            failed_only: false,
+        }
+    }
+
+    /// 解析过滤表达式：以逗号分隔的若干项，每项是
+    ///
+    /// * `all`：所有系统调用
+    /// * 系统调用名，如`openat`
+    /// * `#编号`，如`#98`，可以选中不认识的系统调用
+    /// * `%类别`：`%fs`、`%mem`、`%task`或`%net`
+    /// * `failed`：只输出返回错误的调用
+    ///
+    /// 以`!`开头的项表示排除。只有排除项时视为先选中了`all`。`none`或者空表达式关闭跟踪。
+    fn parse(expr: &str) -> Result<Self, String> {
+        let mut filter = Self::new();
+        for item in expr
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            if item == "none" {
+                return Ok(Self::new());
+            }
+            if item == "failed" {
+                filter.failed_only = true;
+                continue;
+            }
+            let (negated, item) = match item.strip_prefix('!') {
+                Some(item) => (true, item),
+                None => (false, item),
+            };
+            let term = if item == "all" {
+                Term::All
+            } else if let Some(class) = item.strip_prefix('%') {
+                Term::Class(SyscallClass::from_name(class).ok_or_else(|| item.to_string())?)
+            } else if let Some(id) = item.strip_prefix('#') {
+                Term::Id(id.parse().map_err(|_| item.to_string())?)
+            } else if table::lookup_name(item).is_some() {
+                Term::Name(item.to_string())
+            } else {
+                return Err(item.to_string());
+            };
+            if negated {
+                filter.exclude.push(term);
+            } else {
+                filter.include.push(term);
+            }
+        }
+        if filter.include.is_empty() && (!filter.exclude.is_empty() || filter.failed_only) {
+            filter.include.push(Term::All);
+        }
+        Ok(filter)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.include.is_empty()
+    }
+
+    fn matches(&self, id: usize, desc: Option<&SyscallDesc>) -> bool {
+        self.include.iter().any(|term| term.matches(id, desc))
+            && !self.exclude.iter().any(|term| term.matches(id, desc))
+    }
+}
+
+/// 设置过滤表达式，语法见`TraceFilter::parse`。表达式有误时保持原来的设置，返回出错的项。
+pub fn set_trace_filter(expr: &str) -> Result<(), String> {
+    let filter = TraceFilter::parse(expr)?;
+    ENABLED.store(!filter.is_empty(), Ordering::Release);
+    *FILTER.lock() = filter;
+    Ok(())
+}
+
+/// 程序启动时按环境变量`STRACE`设置过滤表达式，没有这个变量时保持原来的设置
+pub fn configure_from_env(envp: &[String]) {
+    let Some(expr) = envp.iter().find_map(|env| {
+        env.strip_prefix(STRACE_ENV)
+            .and_then(|rest| rest.strip_prefix('='))
+    }) else {
+        return;
+    };
+    if let Err(item) = set_trace_filter(expr) {
+        ax_println!("[strace] invalid filter item `{}` in {:?}", item, expr);
+    }
+}
+
+/// 一次被跟踪的系统调用
+pub struct Trace {
+    id: usize,
+    args: [usize; 6],
+    desc: Option<&'static SyscallDesc>,
+    start: TimeValue,
+    failed_only: bool,
+}
+
+/// 进入系统调用时调用，没有被选中时返回`None`
+pub fn trace_enter(id: usize, args: [usize; 6]) -> Option<Trace> {
+    if !ENABLED.load(Ordering::Acquire) {
+        return None;
+    }
+    let desc = lookup(id);
+    let failed_only = {
+        let filter = FILTER.lock();
+        if !filter.matches(id, desc) {
+            return None;
+        }
+        filter.failed_only
+    };
+    let trace = Trace {
+        id,
+        args,
+        desc,
+        start: monotonic_time(),
+        failed_only,
+    };
+    if desc.is_some_and(|desc| desc.no_return) && !failed_only {
+        ax_println!("{} {} ...", prefix(), trace.call(None));
+    }
+    Some(trace)
+}
+
+/// 系统调用返回前调用，输出这次调用
+pub fn trace_exit(trace: Trace, result: &SyscallResult) {
+    if trace.failed_only && result.is_ok() {
+        return;
+    }
+    let elapsed = monotonic_time().saturating_sub(trace.start);
+    let ret = match result {
+        Ok(value) => decode::format_return(trace.desc, *value),
+        Err(err) => alloc::format!("-{} {:?}", err.code(), err),
+    };
+    ax_println!(
+        "{} {} = {} <{}us>",
+        prefix(),
+        trace.call(Some(result)),
+        ret,
+        elapsed.as_micros()
+    );
+}
+
+impl Trace {
+    /// `name(arg0, arg1, ...)`，`result`为`None`时还没有返回，不解析输出参数
+    fn call(&self, result: Option<&SyscallResult>) -> String {
+        let Some(desc) = self.desc else {
+            return alloc::format!("syscall_{}({:#x?})", self.id, self.args);
+        };
+        let args: Vec<String> = desc
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| decode::format_arg(*arg, i, &self.args, result))
+            .collect();
+        alloc::format!("{}({})", desc.name, args.join(", "))
+    }
+}
+
+fn prefix() -> String {
+    let tid = current().id().as_u64();
+    match try_current_process() {
+        Some(process) => alloc::format!("[{}:{}]", process.pid(), tid),
+        None => alloc::format!("[-:{}]", tid),
+    }
+}
@@ -0,0 +1,219 @@
+//! 各个系统调用的名字和参数类型
+use super::decode::Arg::{self, *};
+
+/// 系统调用的类别，对应`syscall`下的各个模块
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SyscallClass {
+    Fs,
+    Mem,
+    Task,
+    Net,
+}
+
+impl SyscallClass {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fs" | "file" => Some(Self::Fs),
+            "mem" | "memory" => Some(Self::Mem),
+            "task" | "process" => Some(Self::Task),
+            "net" | "network" => Some(Self::Net),
+            _ => None,
+        }
+    }
+}
+
+/// 一个系统调用的描述
+pub struct SyscallDesc {
+    pub id: usize,
+    /// Linux 中的名字
+    pub name: &'static str,
+    pub class: SyscallClass,
+    pub args: &'static [Arg],
+    /// 成功时不返回
+    pub no_return: bool,
+}
+
+const fn desc(
+    id: usize,
+    name: &'static str,
+    class: SyscallClass,
+    args: &'static [Arg],
+) -> SyscallDesc {
+    SyscallDesc {
+        id,
+        name,
+        class,
+        args,
+        no_return: false,
+    }
+}
+
+const fn fs(id: usize, name: &'static str, args: &'static [Arg]) -> SyscallDesc {
+    desc(id, name, SyscallClass::Fs, args)
+}
+
+const fn mem(id: usize, name: &'static str, args: &'static [Arg]) -> SyscallDesc {
+    desc(id, name, SyscallClass::Mem, args)
+}
+
+const fn task(id: usize, name: &'static str, args: &'static [Arg]) -> SyscallDesc {
+    desc(id, name, SyscallClass::Task, args)
+}
+
+const fn net(id: usize, name: &'static str, args: &'static [Arg]) -> SyscallDesc {
+    desc(id, name, SyscallClass::Net, args)
+}
+
+const fn no_return(desc: SyscallDesc) -> SyscallDesc {
+    SyscallDesc {
+        no_return: true,
+        ..desc
+    }
+}
+
+/// 编号与`syscall_fs`、`syscall_mem`、`syscall_task`和`syscall_net`中的一致
+static SYSCALLS: &[SyscallDesc] = &[
+    // 文件系统
+    fs(17, "getcwd", &[OutStr, Uint]),
+    fs(19, "eventfd2", &[Uint, Hex]),
+    fs(20, "epoll_create1", &[Hex]),
+    fs(21, "epoll_ctl", &[Fd, Int, Fd, EpollEvent]),
+    fs(22, "epoll_pwait", &[Fd, EpollEvents, Int, Int, Ptr, Uint]),
+    fs(23, "dup", &[Fd]),
+    fs(24, "dup3", &[Fd, Fd, OpenFlags]),
+    fs(25, "fcntl", &[Fd, Int, Hex]),
+    fs(29, "ioctl", &[Fd, Hex, Hex]),
+    fs(34, "mkdirat", &[DirFd, Str, Oct]),
+    fs(35, "unlinkat", &[DirFd, Str, Hex]),
+    fs(36, "symlinkat", &[Str, DirFd, Str]),
+    fs(37, "linkat", &[DirFd, Str, DirFd, Str, Hex]),
+    fs(38, "renameat", &[DirFd, Str, DirFd, Str]),
+    fs(39, "umount2", &[Str, Hex]),
+    fs(40, "mount", &[Str, Str, Str, Hex, Ptr]),
+    fs(43, "statfs", &[Str, Ptr]),
+    fs(46, "ftruncate", &[Fd, Uint]),
+    fs(48, "faccessat", &[DirFd, Str, Oct, Hex]),
+    fs(49, "chdir", &[Str]),
+    fs(52, "fchmod", &[Fd, Oct]),
+    fs(53, "fchmodat", &[DirFd, Str, Oct]),
+    fs(54, "fchownat", &[DirFd, Str, Int, Int, Hex]),
+    fs(55, "fchown", &[Fd, Int, Int]),
+    fs(56, "openat", &[DirFd, Str, OpenFlags, Oct]),
+    fs(57, "close", &[Fd]),
+    fs(59, "pipe2", &[Pipe, OpenFlags]),
+    fs(61, "getdents64", &[Fd, Ptr, Uint]),
+    fs(62, "lseek", &[Fd, Int, Int]),
+    fs(63, "read", &[Fd, OutBuf, Uint]),
+    fs(64, "write", &[Fd, InBuf(2), Uint]),
+    fs(65, "readv", &[Fd, Ptr, Uint]),
+    fs(66, "writev", &[Fd, Ptr, Uint]),
+    fs(67, "pread64", &[Fd, OutBuf, Uint, Int]),
+    fs(68, "pwrite64", &[Fd, InBuf(2), Uint, Int]),
+    fs(71, "sendfile", &[Fd, Fd, Ptr, Uint]),
+    fs(72, "pselect6", &[Int, Ptr, Ptr, Ptr, Timespec, Ptr]),
+    fs(73, "ppoll", &[Ptr, Uint, Timespec, Ptr, Uint]),
+    fs(78, "readlinkat", &[DirFd, Str, OutBuf, Uint]),
+    fs(79, "newfstatat", &[DirFd, Str, Stat, Hex]),
+    fs(80, "fstat", &[Fd, Stat]),
+    fs(81, "sync", &[]),
+    fs(82, "fsync", &[Fd]),
+    fs(83, "fdatasync", &[Fd]),
+    fs(88, "utimensat", &[DirFd, Str, Ptr, Hex]),
+    fs(276, "renameat2", &[DirFd, Str, DirFd, Str, Hex]),
+    fs(285, "copy_file_range", &[Fd, Ptr, Fd, Ptr, Uint, Hex]),
+    // 内存
+    mem(194, "shmget", &[Int, Uint, Hex]),
+    mem(195, "shmctl", &[Int, Int, Ptr]),
+    mem(196, "shmat", &[Int, Ptr, Hex]),
+    mem(214, "brk", &[Ptr]),
+    mem(215, "munmap", &[Ptr, Uint]),
+    mem(216, "mremap", &[Ptr, Uint, Uint, Hex, Ptr]),
+    mem(222, "mmap", &[Ptr, Uint, Prot, MapFlags, Fd, Hex]),
+    mem(226, "mprotect", &[Ptr, Uint, Prot]),
+    mem(227, "msync", &[Ptr, Uint, Hex]),
+    mem(283, "membarrier", &[Int, Hex]),
+    // 进程与线程
+    no_return(task(93, "exit", &[Int])),
+    no_return(task(94, "exit_group", &[Int])),
+    task(96, "set_tid_address", &[Ptr]),
+    task(98, "futex", &[Ptr, Int, Uint, Ptr, Ptr, Uint]),
+    task(99, "set_robust_list", &[Ptr, Uint]),
+    task(100, "get_robust_list", &[Int, Ptr, Ptr]),
+    task(101, "nanosleep", &[Timespec, Ptr]),
+    task(102, "getitimer", &[Int, Ptr]),
+    task(103, "setitimer", &[Int, Ptr, Ptr]),
+    task(113, "clock_gettime", &[Int, Ptr]),
+    task(114, "clock_getres", &[Int, Ptr]),
+    task(115, "clock_nanosleep", &[Int, Hex, Timespec, Ptr]),
+    task(116, "syslog", &[Int, Ptr, Int]),
+    task(119, "sched_setscheduler", &[Int, Int, Ptr]),
+    task(120, "sched_getscheduler", &[Int]),
+    task(122, "sched_setaffinity", &[Int, Uint, Ptr]),
+    task(123, "sched_getaffinity", &[Int, Uint, Ptr]),
+    task(124, "sched_yield", &[]),
+    task(129, "kill", &[Int, Signal]),
+    task(130, "tkill", &[Int, Signal]),
+    task(131, "tgkill", &[Int, Int, Signal]),
+    task(133, "rt_sigsuspend", &[Ptr, Uint]),
+    task(134, "rt_sigaction", &[Signal, Ptr, Ptr, Uint]),
+    task(135, "rt_sigprocmask", &[Int, Ptr, Ptr, Uint]),
+    task(137, "rt_sigtimedwait", &[Ptr, Ptr, Timespec, Uint]),
+    task(139, "rt_sigreturn", &[]),
+    task(143, "setregid", &[Int, Int]),
+    task(144, "setgid", &[Int]),
+    task(145, "setreuid", &[Int, Int]),
+    task(146, "setuid", &[Int]),
+    task(147, "setresuid", &[Int, Int, Int]),
+    task(148, "getresuid", &[Ptr, Ptr, Ptr]),
+    task(149, "setresgid", &[Int, Int, Int]),
+    task(150, "getresgid", &[Ptr, Ptr, Ptr]),
+    task(153, "times", &[Ptr]),
+    task(154, "setpgid", &[Int, Int]),
+    task(155, "getpgid", &[Int]),
+    task(157, "setsid", &[]),
+    task(158, "getgroups", &[Int, Ptr]),
+    task(159, "setgroups", &[Int, Ptr]),
+    task(160, "uname", &[Ptr]),
+    task(165, "getrusage", &[Int, Ptr]),
+    task(166, "umask", &[Oct]),
+    task(167, "prctl", &[Int, Hex, Hex, Hex, Hex]),
+    task(169, "gettimeofday", &[Ptr, Ptr]),
+    task(172, "getpid", &[]),
+    task(173, "getppid", &[]),
+    task(174, "getuid", &[]),
+    task(175, "geteuid", &[]),
+    task(176, "getgid", &[]),
+    task(177, "getegid", &[]),
+    task(178, "gettid", &[]),
+    task(179, "sysinfo", &[Ptr]),
+    task(220, "clone", &[Hex, Ptr, Ptr, Ptr, Ptr]),
+    no_return(task(221, "execve", &[Str, StrArray, StrArray])),
+    task(233, "madvise", &[Ptr, Uint, Int]),
+    task(236, "get_mempolicy", &[Ptr, Ptr, Uint, Ptr, Hex]),
+    task(260, "wait4", &[Int, Ptr, Hex, Ptr]),
+    task(261, "prlimit64", &[Int, Int, Ptr, Ptr]),
+    task(278, "getrandom", &[Ptr, Uint, Hex]),
+    task(435, "clone3", &[Ptr, Uint]),
+    // 网络
+    net(198, "socket", &[Int, Hex, Int]),
+    net(200, "bind", &[Fd, SockAddr, Uint]),
+    net(201, "listen", &[Fd, Int]),
+    net(202, "accept", &[Fd, Ptr, Ptr]),
+    net(203, "connect", &[Fd, SockAddr, Uint]),
+    net(204, "getsockname", &[Fd, Ptr, Ptr]),
+    net(205, "getpeername", &[Fd, Ptr, Ptr]),
+    net(206, "sendto", &[Fd, InBuf(2), Uint, Hex, SockAddr, Uint]),
+    net(207, "recvfrom", &[Fd, OutBuf, Uint, Hex, Ptr, Ptr]),
+    net(208, "setsockopt", &[Fd, Int, Int, Ptr, Uint]),
+    net(209, "getsockopt", &[Fd, Int, Int, Ptr, Ptr]),
+    net(210, "shutdown", &[Fd, Int]),
+    net(242, "accept4", &[Fd, Ptr, Ptr, Hex]),
+];
+
+pub fn lookup(id: usize) -> Option<&'static SyscallDesc> {
+    SYSCALLS.iter().find(|desc| desc.id == id)
+}
+
+pub fn lookup_name(name: &str) -> Option<&'static SyscallDesc> {
+    SYSCALLS.iter().find(|desc| desc.name == name)
+}
@@ -3,51 +3,80 @@ use super::{
     syscall_mem::{MemSyscallId, mem_syscall},
     syscall_task::{TaskSyscallId, task_syscall},
 };
-use axlog::info;
 
-use super::{SyscallResult, deal_result};
+#[cfg(feature = "net")]
+use super::syscall_net::{NetSyscallId, net_syscall};
 
+#[cfg(target_arch = "riscv64")]
+use axhal::{
+    arch::TrapFrame,
+    trap::{SYSCALL, register_trap_handler},
+};
+
+use super::{SyscallResult, deal_result, strace, unknown_syscall};
+use crate::arch::generic_syscall;
+use crate::linux_env::signal_ext::api::handle_pending_signals;
+#[cfg(target_arch = "riscv64")]
+use crate::linux_env::signal_ext::api::{handle_user_signals, sigreturn_user};
+
+/// `rt_sigreturn`的系统调用号
+#[cfg(target_arch = "riscv64")]
+const SYSCALL_SIGRETURN: usize = 139;
+
+/// 客户程序通过 ABI 表发起的系统调用，调用号是所在体系结构的 Linux 调用号
 #[unsafe(no_mangle)]
 pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
+    let ans = match generic_syscall(syscall_id, args) {
+        Some((generic_id, args)) => dispatch(generic_id, args),
+        None => deal_result(unknown_syscall(syscall_id)),
+    };
+    handle_pending_signals();
+    ans
+}
+
+/// 用户态程序通过`ecall`发起的系统调用，参数和返回值都在陷入帧中
+///
+/// `rt_sigreturn`直接恢复陷入帧，返回值就是被信号打断时`a0`的值。
+#[cfg(target_arch = "riscv64")]
+#[register_trap_handler(SYSCALL)]
+fn handle_ecall(tf: &mut TrapFrame) {
+    let syscall_id = tf.sysno();
+    if syscall_id != SYSCALL_SIGRETURN || !sigreturn_user(tf) {
+        let ans = dispatch(syscall_id, tf.args());
+        tf.set_retval(ans as usize);
+    }
+    handle_user_signals(tf);
+}
+
+fn dispatch(syscall_id: usize, args: [usize; 6]) -> isize {
     #[allow(unused_mut, unused_assignments)]
     let mut ans: Option<SyscallResult> = None;
+    let trace = strace::trace_enter(syscall_id, args);
 
     if let Ok(mem_syscall_id) = MemSyscallId::try_from(syscall_id) {
-        info!(
-            "[syscall] id = {:#?}, args = {:?}, entry",
-            mem_syscall_id, args
-        );
         #[allow(unused_assignments)]
         ans = Some(mem_syscall(mem_syscall_id, args));
     }
 
     if let Ok(fs_syscall_id) = FsSyscallId::try_from(syscall_id) {
-        info!(
-            "[syscall] id = {:#?}, args = {:?}, entry",
-            fs_syscall_id, args
-        );
         #[allow(unused_assignments)]
         ans = Some(fs_syscall(fs_syscall_id, args));
     }
 
     if let Ok(task_syscall_id) = TaskSyscallId::try_from(syscall_id) {
-        info!(
-            "[syscall] id = {:#?}, args = {:?}, entry",
-            task_syscall_id, args
-        );
         #[allow(unused_assignments)]
         ans = Some(task_syscall(task_syscall_id, args));
     }
 
-    if ans.is_none() {
-        panic!("unknown syscall id: {}", syscall_id);
+    #[cfg(feature = "net")]
+    if let Ok(net_syscall_id) = NetSyscallId::try_from(syscall_id) {
+        #[allow(unused_assignments)]
+        ans = Some(net_syscall(net_syscall_id, args));
     }
-    let ans = deal_result(ans.unwrap());
-    if syscall_id != 96 && syscall_id != 98 {
-        info!(
-            "[syscall] id = {}, args = {:?}, return {}",
-            syscall_id, args, ans
-        );
+
+    let result = ans.unwrap_or_else(|| unknown_syscall(syscall_id));
+    if let Some(trace) = trace {
+        strace::trace_exit(trace, &result);
     }
-    ans
+    deal_result(result)
 }
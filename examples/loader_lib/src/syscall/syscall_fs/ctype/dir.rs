@@ -1,23 +1,114 @@
 // use crate::{normal_file_mode, StMode};
 // extern crate alloc;
-use crate::linux_env::axfs_ext::api::{self, FileIO, FileIOType, Kstat, OpenFlags, SeekFrom};
+use crate::{
+    linux_env::axfs_ext::api::{self, FileIO, FileIOType, Kstat, OpenFlags, SeekFrom},
+    syscall::{
+        StMode,
+        syscall_fs::ctype::file::{fill_access, inode_of},
+    },
+};
 use alloc::string::{String, ToString};
 use axerrno::{AxError, AxResult};
 // use axfs::api::{self, FileIO, FileIOType, Kstat, OpenFlags, SeekFrom};
-use axfs::api::{create_dir, metadata};
+use axfs::{
+    BLOCK_SIZE,
+    api::{create_dir, metadata},
+    fops::{DirEntry, Directory, FileType, OpenOptions},
+};
 use axlog::debug;
+use axsync::Mutex;
 
 /// 目录描述符
 pub struct DirDesc {
     /// 目录
     pub dir_path: String,
+    /// `getdents64`的读取位置，第一次读取时才打开目录
+    cursor: Mutex<Option<DirCursor>>,
+}
+
+/// 目录的读取位置。`.`和`..`总是最前面的两项，不论文件系统本身是否返回它们
+struct DirCursor {
+    dir: Directory,
+    /// 已经读过的项数，也是下一项的偏移
+    pos: u64,
+    /// 已经从文件系统读出、但还没有交给调用者的一项
+    pending: Option<(String, FileType)>,
+}
+
+impl DirCursor {
+    /// 打开目录并跳过前`pos`项
+    fn open(path: &str, pos: u64) -> AxResult<Self> {
+        let mut opts = OpenOptions::new();
+        opts.read(true);
+        let mut cursor = Self {
+            dir: Directory::open_dir(path, &opts)?,
+            pos: 0,
+            pending: None,
+        };
+        while cursor.pos < pos && cursor.peek()?.is_some() {
+            cursor.advance();
+        }
+        Ok(cursor)
+    }
+
+    /// 下一项，不移动读取位置
+    fn peek(&mut self) -> AxResult<Option<(String, FileType)>> {
+        if self.pending.is_none() {
+            self.pending = match self.pos {
+                0 => Some((String::from("."), FileType::Dir)),
+                1 => Some((String::from(".."), FileType::Dir)),
+                _ => self.read_from_fs()?,
+            };
+        }
+        Ok(self.pending.clone())
+    }
+
+    fn advance(&mut self) {
+        self.pending = None;
+        self.pos += 1;
+    }
+
+    fn read_from_fs(&mut self) -> AxResult<Option<(String, FileType)>> {
+        let mut entries = [DirEntry::default()];
+        loop {
+            if self.dir.read_dir(&mut entries)? == 0 {
+                return Ok(None);
+            }
+            let name = entries[0].name_as_bytes();
+            if name == b"." || name == b".." {
+                continue;
+            }
+            let name = String::from_utf8_lossy(name).into_owned();
+            return Ok(Some((name, entries[0].entry_type())));
+        }
+    }
 }
 
 /// 目录描述符的实现
 impl DirDesc {
     /// 创建一个新的目录描述符
     pub fn new(path: String) -> Self {
-        Self { dir_path: path }
+        Self {
+            dir_path: path,
+            cursor: Mutex::new(None),
+        }
+    }
+
+    /// 从当前读取位置起依次把目录项的名字、类型和下一项的偏移交给`f`。
+    /// `f`返回`false`时停止，这一项留到下次读取。
+    pub fn read_entries(&self, mut f: impl FnMut(&str, FileType, u64) -> bool) -> AxResult {
+        let mut cursor = self.cursor.lock();
+        if cursor.is_none() {
+            *cursor = Some(DirCursor::open(&self.dir_path, 0)?);
+        }
+        let cursor = cursor.as_mut().unwrap();
+        while let Some((name, ty)) = cursor.peek()? {
+            if !f(&name, ty, cursor.pos + 1) {
+                break;
+            }
+            cursor.advance();
+        }
+        Ok(())
     }
 }
 
@@ -32,8 +123,21 @@ impl FileIO for DirDesc {
     fn flush(&self) -> AxResult {
         Err(AxError::IsADirectory)
     }
-    fn seek(&self, _: SeekFrom) -> AxResult<u64> {
-        Err(AxError::IsADirectory)
+    /// 目录项保存在文件系统的元数据中，写回整个文件系统
+    fn sync(&self) -> AxResult {
+        axfs::api::sync()
+    }
+    /// 只支持`rewinddir`/`seekdir`用到的`SEEK_SET`和查询当前位置
+    fn seek(&self, pos: SeekFrom) -> AxResult<u64> {
+        let mut cursor = self.cursor.lock();
+        match pos {
+            SeekFrom::Start(pos) => {
+                *cursor = Some(DirCursor::open(&self.dir_path, pos)?);
+                Ok(pos)
+            }
+            SeekFrom::Current(0) => Ok(cursor.as_ref().map_or(0, |cursor| cursor.pos)),
+            _ => Err(AxError::InvalidInput),
+        }
     }
     fn get_type(&self) -> FileIOType {
         FileIOType::DirDesc
@@ -51,29 +155,21 @@ impl FileIO for DirDesc {
         self.dir_path.to_string().clone()
     }
 
-    //    fn get_stat(&self) -> AxResult<Kstat> {
-    //        let kstat = Kstat {
-    //            st_dev: 1,
-    //            st_ino: 0,
-    //            st_mode: normal_file_mode(StMode::S_IFDIR).bits(),
-    //            st_nlink: 1,
-    //            st_uid: 0,
-    //            st_gid: 0,
-    //            st_rdev: 0,
-    //            _pad0: 0,
-    //            st_size: 0,
-    //            st_blksize: 0,
-    //            _pad1: 0,
-    //            st_blocks: 0,
-    //            st_atime_sec: 0,
-    //            st_atime_nsec: 0,
-    //            st_mtime_sec: 0,
-    //            st_mtime_nsec: 0,
-    //            st_ctime_sec: 0,
-    //            st_ctime_nsec: 0,
-    //        };
-    //        Ok(kstat)
-    //    }
+    fn get_stat(&self) -> AxResult<Kstat> {
+        let metadata = metadata(&self.dir_path)?;
+        let mut kstat = Kstat {
+            st_dev: 1,
+            st_ino: inode_of(&self.dir_path, true),
+            st_mode: StMode::S_IFDIR.bits() | metadata.permissions().bits() as u32,
+            st_nlink: 2,
+            st_size: metadata.size(),
+            st_blksize: BLOCK_SIZE as _,
+            st_blocks: metadata.blocks(),
+            ..Kstat::default()
+        };
+        fill_access(&mut kstat, &self.dir_path);
+        Ok(kstat)
+    }
 }
 
 pub fn new_dir(dir_path: String, _flags: OpenFlags) -> AxResult<DirDesc> {
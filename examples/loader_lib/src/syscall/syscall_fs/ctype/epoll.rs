@@ -1,18 +1,16 @@
-use alloc::{
-    collections::{BTreeMap, BTreeSet},
-    sync::Arc,
-    vec::Vec,
-};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
 use axerrno::{AxError, AxResult};
-use axhal::time::current_ticks;
+use axhal::time::TimeValue;
 use axsync::Mutex;
-use axtask::yield_now;
 use bitflags::bitflags;
 
 use crate::{
     linux_env::{
-        axfs_ext::api::{FileIO, FileIOType, SeekFrom},
-        linux_fs::fd_manager::FDM,
+        axfs_ext::api::{FileIO, FileIOType, OpenFlags, SeekFrom},
+        linux_fs::{
+            fd_manager::current_fd_manager,
+            poll_queue::{PollQueue, PollWait, wait_on},
+        },
     },
     syscall::SyscallError,
 };
@@ -39,9 +37,12 @@ bitflags! {
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, Copy)]
 /// 定义一个`epoll`事件
+///
+/// x86_64 上沿用 i386 的布局，结构体是紧凑的 12 字节，其他架构上是 16 字节。
+#[cfg_attr(target_arch = "x86_64", repr(C, packed))]
+#[cfg_attr(not(target_arch = "x86_64"), repr(C))]
+#[derive(Debug, Clone, Copy)]
 pub struct EpollEvent {
     /// 事件类型
     pub event_type: EpollEventType,
@@ -66,23 +67,32 @@ pub struct EpollFile {
     /// 定义内部可变变量
     /// 由于存在clone，所以要用arc指针包围
     pub inner: Arc<Mutex<EpollFileInner>>,
+    /// 文件打开的标志位，只用到`CLOEXEC`
+    flags: Arc<Mutex<OpenFlags>>,
+    /// 监控列表改变时唤醒`epoll_wait`，让它登记到新加入的文件上
+    ctl_queue: Arc<PollQueue>,
 }
 
 pub struct EpollFileInner {
     /// 监控的所有事件，通过map来进行映射，根据fd找到对应的event
     monitor_list: BTreeMap<i32, EpollEvent>,
-    /// 响应的事件集
-    _response_list: BTreeSet<i32>,
+    /// 设置了`EPOLLET`的文件上一次等待时已经就绪的事件，只有新就绪的事件才会报告
+    edges: BTreeMap<i32, EpollEventType>,
+    /// 每次改变`monitor_list`加一
+    generation: u64,
 }
 
 impl EpollFile {
     /// 新建一个`epoll`文件
-    pub fn new() -> Self {
+    pub fn new(flags: OpenFlags) -> Self {
         Self {
             inner: Arc::new(Mutex::new(EpollFileInner {
                 monitor_list: BTreeMap::new(),
-                _response_list: BTreeSet::new(),
+                edges: BTreeMap::new(),
+                generation: 0,
             })),
+            flags: Arc::new(Mutex::new(flags)),
+            ctl_queue: Arc::new(PollQueue::new()),
         }
     }
 
@@ -91,6 +101,8 @@ impl EpollFile {
     pub fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            flags: self.flags.clone(),
+            ctl_queue: self.ctl_queue.clone(),
         }
     }
 
@@ -105,22 +117,25 @@ impl EpollFile {
 
     /// 控制指定的事件，改变其对应的事件内容
     ///
-    /// 成功返回0，错误返回对应的编号
+    /// 成功返回0，错误返回对应的编号。添加或修改事件后，已经就绪的事件在下一次等待时
+    /// 会报告一次，`EPOLLET`也不例外。
     pub fn epoll_ctl(
         &self,
         op: EpollCtl,
         fd: i32,
-        event: EpollEvent,
+        mut event: EpollEvent,
     ) -> Result<isize, SyscallError> {
+        if !matches!(op, EpollCtl::DEL) {
+            // `EPOLLHUP`和`EPOLLERR`总是会报告
+            event.event_type =
+                event.event_type | EpollEventType::EPOLLHUP | EpollEventType::EPOLLERR;
+        }
         let mut inner = self.inner.lock();
         match op {
             // 添加事件
             EpollCtl::ADD => {
                 if inner.monitor_list.contains_key(&fd) {
-                    // ```
-                    // return Err(SyscallError::EEXIST);
-                    // TODO : fd close callback ?
-                    inner.monitor_list.insert(fd, event);
+                    return Err(SyscallError::EEXIST);
                 }
                 inner.monitor_list.insert(fd, event);
             }
@@ -141,78 +156,146 @@ impl EpollFile {
                 inner.monitor_list.insert(fd, event);
             }
         }
+        inner.edges.remove(&fd);
+        // 唤醒正在等待的`epoll_wait`，让它按新的列表重新登记
+        inner.generation += 1;
+        drop(inner);
+        self.ctl_queue.notify();
         Ok(0)
     }
 
-    /// 获取list中所有的`epoll`事件
-    pub fn get_events(&self) -> Vec<EpollEvent> {
-        let mut ans = Vec::new();
-        for (fd, event) in self.inner.lock().monitor_list.iter() {
-            let mut nevent = *event;
-            if *fd as u64 != nevent.data {
-                nevent.data = *fd as u64;
-            }
-            ans.push(nevent);
-        }
-        ans
+    /// 监控列表中仍然打开着的文件
+    fn monitored_files(&self) -> Vec<(i32, Arc<dyn FileIO>, EpollEvent)> {
+        let monitor_list: Vec<(i32, EpollEvent)> = self
+            .inner
+            .lock()
+            .monitor_list
+            .iter()
+            .map(|(fd, event)| (*fd, *event))
+            .collect();
+        // 检查时不能持有文件描述符表的锁，被监控的可能也是`epoll`文件
+        let fd_manager = current_fd_manager();
+        let fd_table = fd_manager.fd_table.lock();
+        monitor_list
+            .into_iter()
+            .filter_map(|(fd, event)| {
+                let file = fd_table.get(fd as usize)?.as_ref()?;
+                Some((fd, file.clone(), event))
+            })
+            .collect()
     }
 
-    /// 实现`epoll wait`，在规定超时时间内收集达到触发条件的事件
+    /// 检查一次所有监控的文件，返回至多`max_events`个就绪的事件
     ///
-    /// 实现原理和`ppoll`很像
-    pub fn epoll_wait(&self, expire_time: usize) -> AxResult<Vec<EpollEvent>> {
-        let events = self.get_events();
+    /// 返回的事件带有注册时的`data`。设置了`EPOLLET`的文件只报告上一次等待之后新就绪的
+    /// 事件，变为未就绪的事件下一次就绪时会再报告。`disarm`为真时记录这一次看到的就绪
+    /// 状态，并停用报告过的`EPOLLONESHOT`事件，直到用`EPOLL_CTL_MOD`重新设置。
+    fn poll_events(&self, max_events: usize, disarm: bool) -> Vec<EpollEvent> {
         let mut ret_events = Vec::new();
-        loop {
-            for req_event in events.iter() {
-                let fd_table = FDM.fd_table.lock();
-                if let Some(file) = &fd_table[req_event.data as usize] {
-                    let mut ret_event_type = EpollEventType::empty();
-                    if file.is_hang_up() {
-                        ret_event_type |= EpollEventType::EPOLLHUP;
-                    }
-                    if file.in_exceptional_conditions() {
-                        ret_event_type |= EpollEventType::EPOLLERR;
-                    }
-                    if file.ready_to_read()
-                        && req_event.event_type.contains(EpollEventType::EPOLLIN)
-                    {
-                        ret_event_type |= EpollEventType::EPOLLIN;
-                    }
-                    if file.ready_to_write()
-                        && req_event.event_type.contains(EpollEventType::EPOLLOUT)
-                    {
-                        ret_event_type |= EpollEventType::EPOLLOUT;
-                    }
-                    if !ret_event_type.is_empty() {
-                        let mut ret_event = *req_event;
-                        ret_event.event_type = ret_event_type;
-                        ret_events.push(ret_event);
-                    }
-                    // 若文件存在但未响应，此时不加入到`ret`中，并以此作为是否终止的条件
-                } else {
-                    // 若文件不存在，认为不存在也是一种响应，所以要加入到`ret`中，并以此作为是否终止的条件
-                    ret_events.push(EpollEvent {
-                        event_type: EpollEventType::EPOLLERR,
-                        data: req_event.data,
-                    });
+        let mut fired = Vec::new();
+        let mut edges = Vec::new();
+        let last_edges = self.inner.lock().edges.clone();
+        for (fd, file, req_event) in self.monitored_files() {
+            let req = req_event.event_type;
+            let mut ret_event_type = EpollEventType::empty();
+            if req.contains(EpollEventType::EPOLLHUP) && file.is_hang_up() {
+                ret_event_type |= EpollEventType::EPOLLHUP;
+            }
+            if req.contains(EpollEventType::EPOLLERR) && file.in_exceptional_conditions() {
+                ret_event_type |= EpollEventType::EPOLLERR;
+            }
+            if req.contains(EpollEventType::EPOLLIN) && file.ready_to_read() {
+                ret_event_type |= EpollEventType::EPOLLIN;
+            }
+            if req.contains(EpollEventType::EPOLLOUT) && file.ready_to_write() {
+                ret_event_type |= EpollEventType::EPOLLOUT;
+            }
+            if req.contains(EpollEventType::EPOLLET) {
+                edges.push((fd, ret_event_type));
+                if let Some(&last) = last_edges.get(&fd) {
+                    ret_event_type = ret_event_type.difference(last);
                 }
             }
-            if !ret_events.is_empty() {
-                // 此时收到了响应，直接返回
-                return Ok(ret_events);
+            if !ret_event_type.is_empty() {
+                ret_events.push(EpollEvent {
+                    event_type: ret_event_type,
+                    data: req_event.data,
+                });
+                if req.contains(EpollEventType::EPOLLONESHOT) {
+                    fired.push(fd);
+                }
+                if ret_events.len() == max_events {
+                    break;
+                }
+            }
+        }
+        if disarm && !(fired.is_empty() && edges.is_empty()) {
+            let mut inner = self.inner.lock();
+            // 停用后只保留`EPOLLONESHOT`，不再报告任何事件
+            for fd in fired {
+                if let Some(event) = inner.monitor_list.get_mut(&fd) {
+                    event.event_type = EpollEventType::EPOLLONESHOT;
+                }
             }
-            // 否则直接block
-            if current_ticks() as usize > expire_time {
-                return Ok(ret_events);
+            for (fd, ready) in edges {
+                if inner.monitor_list.contains_key(&fd) {
+                    inner.edges.insert(fd, ready);
+                }
             }
-            yield_now();
+        }
+        ret_events
+    }
 
-            // ```
-            // #[cfg(feature = "signal")]
-            // if current_process.have_signals().is_some() {
-            //     return Err(AxError::Timeout);
-            // }
+    /// `fd`被关闭时不再监控它
+    pub fn forget(&self, fd: i32) {
+        let mut inner = self.inner.lock();
+        if inner.monitor_list.remove(&fd).is_some() {
+            inner.edges.remove(&fd);
+            inner.generation += 1;
+            drop(inner);
+            self.ctl_queue.notify();
+        }
+    }
+
+    /// 实现`epoll wait`，在截止时间`deadline`前收集达到触发条件的事件
+    ///
+    /// `deadline`为`None`时一直等待。等待期间有信号需要处理时返回`EINTR`。
+    pub fn epoll_wait(
+        &self,
+        max_events: usize,
+        deadline: Option<TimeValue>,
+    ) -> Result<Vec<EpollEvent>, SyscallError> {
+        let mut ret_events = Vec::new();
+        loop {
+            // 在检查之前登记到所有被监控文件的队列上，列表改变时重新登记
+            let generation = self.inner.lock().generation;
+            let files = self.monitored_files();
+            let mut queues: Vec<&PollQueue> =
+                files.iter().filter_map(|(_, file, _)| file.poll_queue()).collect();
+            let timed = queues.len() < files.len();
+            queues.push(&self.ctl_queue);
+            let mut changed = false;
+            let wait = wait_on(&queues, timed, deadline, || {
+                ret_events = self.poll_events(max_events, true);
+                changed = self.inner.lock().generation != generation;
+                !ret_events.is_empty() || changed
+            });
+            if ret_events.is_empty() && changed && wait == PollWait::Ready {
+                continue;
+            }
+            break match wait {
+                PollWait::Interrupted => Err(SyscallError::EINTR),
+                PollWait::Ready | PollWait::Timeout => Ok(ret_events),
+            };
+        }
+    }
+}
+
+/// 文件描述符`fd`被关闭或被`dup`覆盖时，从表中所有`epoll`文件的监控列表里删除它
+pub fn forget_fd(fd_table: &[Option<Arc<dyn FileIO>>], fd: i32) {
+    for file in fd_table.iter().flatten() {
+        if let Some(epoll_file) = file.as_any().downcast_ref::<EpollFile>() {
+            epoll_file.forget(fd);
         }
     }
 }
@@ -244,31 +327,23 @@ impl FileIO for EpollFile {
     fn get_type(&self) -> FileIOType {
         FileIOType::FileDesc
     }
+    fn get_status(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+    fn set_status(&self, flags: OpenFlags) -> bool {
+        *self.flags.lock() = flags;
+        true
+    }
+    fn set_close_on_exec(&self, is_set: bool) -> bool {
+        if is_set {
+            *self.flags.lock() |= OpenFlags::CLOEXEC;
+        } else {
+            *self.flags.lock() &= !OpenFlags::CLOEXEC;
+        }
+        true
+    }
     fn ready_to_read(&self) -> bool {
         // 如果当前`epoll`事件确实正在等待事件响应，那么可以认为事件准备好read，尽管无法读到实际内容
-        let events = self.get_events();
-        let fd_table = FDM.fd_table.lock();
-        for req_event in events.iter() {
-            if let Some(file) = fd_table[req_event.data as usize].as_ref() {
-                let mut ret_event_type = EpollEventType::empty();
-                if file.is_hang_up() {
-                    ret_event_type |= EpollEventType::EPOLLHUP;
-                }
-                if file.in_exceptional_conditions() {
-                    ret_event_type |= EpollEventType::EPOLLERR;
-                }
-                if file.ready_to_read() && req_event.event_type.contains(EpollEventType::EPOLLIN) {
-                    ret_event_type |= EpollEventType::EPOLLIN;
-                }
-                if file.ready_to_write() && req_event.event_type.contains(EpollEventType::EPOLLOUT)
-                {
-                    ret_event_type |= EpollEventType::EPOLLOUT;
-                }
-                if !ret_event_type.is_empty() {
-                    return true;
-                }
-            }
-        }
-        false
+        !self.poll_events(1, false).is_empty()
     }
 }
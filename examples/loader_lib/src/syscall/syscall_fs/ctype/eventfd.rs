@@ -0,0 +1,136 @@
+//! `eventfd`创建的事件计数器
+use axerrno::{AxError, AxResult};
+use axsync::Mutex;
+
+use crate::linux_env::{
+    axfs_ext::api::{FileIO, FileIOType, OpenFlags},
+    linux_fs::poll_queue::{PollQueue, PollWait, wait_ready},
+};
+
+/// 计数器能达到的最大值
+const MAX_COUNT: u64 = u64::MAX - 1;
+
+pub struct EventFd {
+    count: Mutex<u64>,
+    /// `EFD_SEMAPHORE`：每次读出 1 而不是整个计数
+    semaphore: bool,
+    flags: Mutex<OpenFlags>,
+    queue: PollQueue,
+}
+
+impl EventFd {
+    pub fn new(count: u64, semaphore: bool, flags: OpenFlags) -> Self {
+        Self {
+            count: Mutex::new(count),
+            semaphore,
+            flags: Mutex::new(flags | OpenFlags::RDWR),
+            queue: PollQueue::new(),
+        }
+    }
+
+    /// 阻塞直到`ready`，非阻塞或者被信号打断时返回`WouldBlock`
+    fn wait(&self, ready: impl Fn() -> bool) -> AxResult {
+        if self.flags.lock().contains(OpenFlags::NON_BLOCK)
+            || wait_ready(&[self], None, ready) != PollWait::Ready
+        {
+            return Err(AxError::WouldBlock);
+        }
+        Ok(())
+    }
+}
+
+impl FileIO for EventFd {
+    /// 读出 8 字节的计数，计数为 0 时阻塞
+    fn read(&self, buf: &mut [u8]) -> AxResult<usize> {
+        if buf.len() < 8 {
+            return Err(AxError::InvalidInput);
+        }
+        loop {
+            {
+                let mut count = self.count.lock();
+                if *count > 0 {
+                    let value = if self.semaphore { 1 } else { *count };
+                    *count -= value;
+                    drop(count);
+                    buf[..8].copy_from_slice(&value.to_ne_bytes());
+                    self.queue.notify();
+                    return Ok(8);
+                }
+            }
+            self.wait(|| self.ready_to_read())?;
+        }
+    }
+
+    /// 把 8 字节的值加到计数上，计数会超过`MAX_COUNT`时阻塞
+    fn write(&self, buf: &[u8]) -> AxResult<usize> {
+        if buf.len() < 8 {
+            return Err(AxError::InvalidInput);
+        }
+        let value = u64::from_ne_bytes(buf[..8].try_into().unwrap());
+        if value == u64::MAX {
+            return Err(AxError::InvalidInput);
+        }
+        loop {
+            {
+                let mut count = self.count.lock();
+                if MAX_COUNT - *count >= value {
+                    *count += value;
+                    drop(count);
+                    self.queue.notify();
+                    return Ok(8);
+                }
+            }
+            self.wait(|| MAX_COUNT - *self.count.lock() >= value)?;
+        }
+    }
+
+    fn flush(&self) -> AxResult {
+        Ok(())
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn executable(&self) -> bool {
+        false
+    }
+
+    fn get_type(&self) -> FileIOType {
+        FileIOType::Other
+    }
+
+    fn set_status(&self, flags: OpenFlags) -> bool {
+        *self.flags.lock() = flags;
+        true
+    }
+
+    fn get_status(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_close_on_exec(&self, is_set: bool) -> bool {
+        if is_set {
+            *self.flags.lock() |= OpenFlags::CLOEXEC;
+        } else {
+            *self.flags.lock() &= !OpenFlags::CLOEXEC;
+        }
+        true
+    }
+
+    fn ready_to_read(&self) -> bool {
+        *self.count.lock() > 0
+    }
+
+    fn ready_to_write(&self) -> bool {
+        *self.count.lock() < MAX_COUNT
+    }
+
+    fn poll_queue(&self) -> Option<&PollQueue> {
+        Some(&self.queue)
+    }
+}
@@ -1,24 +1,21 @@
 use crate::{
-    linux_env::{
-        axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags, Read, Seek, SeekFrom, Write},
-        linux_fs::link::get_link_count,
-    },
-    syscall::{StMode, TimeSecs, ctypes::normal_file_mode, new_file},
+    linux_env::axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags, Read, Seek, SeekFrom, Write},
+    syscall::{StMode, TimeSecs, ctypes::normal_file_mode, new_file, new_user_file},
 };
 use alloc::{
-    collections::BTreeMap,
     string::{String, ToString},
     sync::Arc,
     vec,
     vec::Vec,
 };
 use axerrno::AxResult;
-use axfs::{BLOCK_SIZE, api::File};
+use axfs::{
+    BLOCK_SIZE,
+    api::{File, file_access, inode_number, link_count},
+};
 use axlog::debug;
 use axsync::Mutex;
 
-pub static INODE_NAME_MAP: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
-
 /// 文件描述符
 pub struct FileDesc {
     /// 文件路径
@@ -54,6 +51,9 @@ impl FileIO for FileDesc {
     fn write(&self, buf: &[u8]) -> AxResult<usize> {
         // 如果seek时超出了文件原有大小，则在write的时候进行补零操作
         let mut file = self.file.lock();
+        if self.flags.lock().contains(OpenFlags::APPEND) {
+            file.seek(SeekFrom::End(0))?;
+        }
         let old_offset = file.seek(SeekFrom::Current(0)).unwrap();
         let size = file.metadata().unwrap().size();
         if old_offset > size {
@@ -72,6 +72,24 @@ impl FileIO for FileDesc {
         self.file.lock().seek(pos)
     }
 
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> AxResult<usize> {
+        self.file.lock().read_at(offset, buf)
+    }
+
+    fn write_at(&self, offset: u64, buf: &[u8]) -> AxResult<usize> {
+        // 与`write`一样，写入位置超出文件末尾时先补零
+        let file = self.file.lock();
+        let size = file.metadata()?.size();
+        if offset > size {
+            file.write_at(size, &vec![0u8; (offset - size) as usize])?;
+        }
+        file.write_at(offset, buf)
+    }
+
+    fn sync(&self) -> AxResult<()> {
+        self.file.lock().sync_all()
+    }
+
     fn readable(&self) -> bool {
         self.flags.lock().readable()
     }
@@ -97,33 +115,16 @@ impl FileIO for FileDesc {
         let file = self.file.lock();
         let attr = file.get_attr()?;
         let stat = self.stat.lock();
-        let inode_map = INODE_NAME_MAP.lock();
-        let inode_number = if let Some(inode_number) = inode_map.get(&self.path) {
-            *inode_number
-        } else {
-            // ```
-            // return Err(axerrno::AxError::NotFound);
-            // Now the file exists but it wasn't opened
-            drop(inode_map);
-            new_inode(self.path.clone())?;
-            let inode_map = INODE_NAME_MAP.lock();
-            assert!(inode_map.contains_key(&self.path));
-            let number = *(inode_map.get(&self.path).unwrap());
-            drop(inode_map);
-            number
-        };
-        let kstat = Kstat {
+        let mut kstat = Kstat {
             st_dev: 1,
-            st_ino: inode_number,
+            st_ino: inode_of(&self.path, true),
             st_mode: normal_file_mode(StMode::S_IFREG).bits() | 0o644,
-            st_nlink: get_link_count(&(self.path.as_str().to_string())) as _,
+            st_nlink: link_count(&self.path).unwrap_or(1) as _,
             st_uid: 0,
             st_gid: 0,
             st_rdev: 0,
-            _pad0: 0,
             st_size: attr.size(),
-            st_blksize: BLOCK_SIZE as u32,
-            _pad1: 0,
+            st_blksize: BLOCK_SIZE as _,
             st_blocks: attr.blocks(),
             st_atime_sec: stat.atime.tv_sec as isize,
             st_atime_nsec: stat.atime.tv_nsec as isize,
@@ -131,7 +132,9 @@ impl FileIO for FileDesc {
             st_mtime_nsec: stat.mtime.tv_nsec as isize,
             st_ctime_sec: stat.ctime.tv_sec as isize,
             st_ctime_nsec: stat.ctime.tv_nsec as isize,
+            ..Kstat::default()
         };
+        fill_access(&mut kstat, &self.path);
         Ok(kstat)
     }
 
@@ -211,14 +214,27 @@ pub fn new_fd(path: String, flags: OpenFlags) -> AxResult<FileDesc> {
     Ok(fd)
 }
 
-/// 当新建一个文件或者目录节点时，需要为其分配一个新的`inode`号
-/// 由于我们不涉及删除文件，因此我们可以简单地使用一个全局增的计数器来分配`inode`号
-pub fn new_inode(path: String) -> AxResult<()> {
-    let mut inode_name_map = INODE_NAME_MAP.lock();
-    if inode_name_map.contains_key(&path) {
-        return Ok(());
+/// `openat`使用的文件描述符，检查当前进程的权限，见[`new_user_file`]
+pub fn new_user_fd(path: String, flags: OpenFlags, mode: u32) -> AxResult<FileDesc> {
+    let file = new_user_file(path.as_str(), &flags, mode)?;
+    Ok(FileDesc::new(
+        path.as_str(),
+        Arc::new(Mutex::new(file)),
+        flags,
+    ))
+}
+
+/// 文件系统给出的`inode`编号，同一文件的硬链接编号相同。`follow_last`为真时跟随最后一项
+/// 的符号链接。文件已经不存在时为0。
+pub fn inode_of(path: &str, follow_last: bool) -> u64 {
+    inode_number(path, follow_last).unwrap_or(0)
+}
+
+/// 用文件系统记录的属主和权限填充`kstat`，文件类型不变
+pub fn fill_access(kstat: &mut Kstat, path: &str) {
+    if let Ok(access) = file_access(path) {
+        kstat.st_uid = access.uid;
+        kstat.st_gid = access.gid;
+        kstat.st_mode = (kstat.st_mode & !0o7777) | access.mode as u32;
     }
-    let inode_number = inode_name_map.len() as u64 + 1;
-    inode_name_map.insert(path, inode_number);
-    Ok(())
 }
@@ -1,4 +1,6 @@
 pub mod dir;
 pub mod epoll;
+pub mod eventfd;
 pub mod file;
 pub mod mount;
+pub mod pipe;
@@ -1,24 +1,24 @@
 use crate::{
     linux_env::{
-        axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags, SeekFrom},
+        axfs_ext::api::{FileIO, FileIOType, Kstat, SeekFrom},
         linux_fs::{
-            fd_manager::{FDM, alloc_fd},
-            link::{FilePath, create_link},
+            fd_manager::current_fd_manager,
+            link::FilePath,
             utils::{UtilsError, deal_path, has_permission},
         },
     },
     syscall::{
         IoVec, O_CLOEXEC, StMode, SyscallError, SyscallResult, normal_file_mode,
         syscall_fs::ctype::{
-            dir::{get_dir_desc, new_dir},
+            dir::get_dir_desc,
             epoll::{EpollCtl, EpollEvent, EpollEventType, EpollFile},
-            file::{new_fd, new_inode},
+            file::{fill_access, inode_of, new_fd},
         },
     },
 };
 use alloc::{string::ToString, sync::Arc, vec};
 use axerrno::AxError;
-use axfs::api::{Permissions, lookup};
+use axfs::api::{Permissions, link_count, lookup, read_link};
 use axlog::{debug, error, info, warn};
 use core::slice::{from_raw_parts, from_raw_parts_mut};
 
@@ -30,6 +30,17 @@ pub fn get_stat_in_fs(path: &FilePath) -> Result<Kstat, SyscallError> {
     let real_path = path.path();
     let mut ans = Kstat::default();
     info!("get_stat_in_fs: {}", real_path);
+    // 符号链接本身，只有不跟随最后一项时才会到这里
+    if let Ok(target) = read_link(real_path) {
+        return Ok(Kstat {
+            st_dev: 1,
+            st_ino: inode_of(real_path, false),
+            st_mode: StMode::S_IFLNK.bits() | 0o777,
+            st_nlink: 1,
+            st_size: target.len() as u64,
+            ..Kstat::default()
+        });
+    }
     if real_path.starts_with("/var")
         || real_path.starts_with("/dev")
         || real_path.starts_with("/tmp")
@@ -38,12 +49,15 @@ pub fn get_stat_in_fs(path: &FilePath) -> Result<Kstat, SyscallError> {
     {
         if path.is_dir() {
             ans.st_dev = 2;
+            ans.st_ino = inode_of(real_path, true);
             ans.st_mode = normal_file_mode(StMode::S_IFDIR).bits();
+            fill_access(&mut ans, real_path);
             return Ok(ans);
         }
         if let Ok(node) = lookup(path.path()) {
             let mut stat = Kstat {
-                st_nlink: 1,
+                st_ino: inode_of(real_path, true),
+                st_nlink: link_count(real_path).unwrap_or(1) as _,
                 ..Kstat::default()
             };
             // 先检查是否在vfs中存在对应文件
@@ -59,6 +73,7 @@ pub fn get_stat_in_fs(path: &FilePath) -> Result<Kstat, SyscallError> {
             {
                 stat.st_dev = 2;
                 stat.st_mode = normal_file_mode(StMode::S_IFDIR).bits();
+                fill_access(&mut stat, real_path);
                 return Ok(stat);
             }
             if node
@@ -85,12 +100,13 @@ pub fn get_stat_in_fs(path: &FilePath) -> Result<Kstat, SyscallError> {
             {
                 stat.st_mode = normal_file_mode(StMode::S_IFREG).bits();
                 stat.st_size = node.get_attr().unwrap().size();
+                fill_access(&mut stat, real_path);
                 return Ok(stat);
             }
         }
     }
     // 是文件
-    let metadata = axfs::api::metadata(path.path()).unwrap();
+    let metadata = axfs::api::metadata(path.path()).map_err(|_| SyscallError::ENOENT)?;
     if metadata.is_file() {
         if let Ok(file) = new_fd(real_path.to_string(), 0.into()) {
             match file.get_stat() {
@@ -105,16 +121,12 @@ pub fn get_stat_in_fs(path: &FilePath) -> Result<Kstat, SyscallError> {
         }
     } else if metadata.is_dir() {
         // 是目录
-        if let Ok(dir) = new_dir(real_path.to_string(), OpenFlags::DIR) {
-            match dir.get_stat() {
-                Ok(stat) => Ok(stat),
-                Err(e) => {
-                    debug!("get stat error: {:?}", e);
-                    Err(SyscallError::EINVAL)
-                }
+        match get_dir_desc(real_path.to_string()).get_stat() {
+            Ok(stat) => Ok(stat),
+            Err(e) => {
+                debug!("get stat error: {:?}", e);
+                Err(SyscallError::EINVAL)
             }
-        } else {
-            Err(SyscallError::ENOENT)
         }
     } else {
         // 是字符设备
@@ -0,0 +1,218 @@
+//! `pipe2`创建的管道
+use alloc::{collections::VecDeque, sync::Arc};
+
+use axerrno::{AxError, AxResult};
+use axsync::Mutex;
+
+use crate::{
+    linux_env::{
+        axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags},
+        linux_fs::poll_queue::{PollQueue, PollWait, wait_ready},
+    },
+    syscall::StMode,
+};
+
+/// 管道缓冲区的大小
+const PIPE_BUF_SIZE: usize = 0x10000;
+
+/// 两端共享的缓冲区
+struct PipeBuffer {
+    data: VecDeque<u8>,
+    /// 还打开着的读端数目
+    readers: usize,
+    /// 还打开着的写端数目
+    writers: usize,
+}
+
+/// 管道的一端，同一端被`dup`或`fork`时共享同一个`Pipe`，最后一个引用关闭时这一端才关闭
+pub struct Pipe {
+    readable: bool,
+    buffer: Arc<Mutex<PipeBuffer>>,
+    /// 两端共享，任何一端的读写和关闭都会改变两端的状态
+    queue: Arc<PollQueue>,
+    flags: Mutex<OpenFlags>,
+}
+
+/// 创建一个管道，返回读端和写端
+pub fn make_pipe(flags: OpenFlags) -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(Mutex::new(PipeBuffer {
+        data: VecDeque::new(),
+        readers: 1,
+        writers: 1,
+    }));
+    let queue = Arc::new(PollQueue::new());
+    let read_end = Arc::new(Pipe {
+        readable: true,
+        buffer: buffer.clone(),
+        queue: queue.clone(),
+        flags: Mutex::new(flags | OpenFlags::RDONLY),
+    });
+    let write_end = Arc::new(Pipe {
+        readable: false,
+        buffer,
+        queue,
+        flags: Mutex::new(flags | OpenFlags::WRONLY),
+    });
+    (read_end, write_end)
+}
+
+impl Pipe {
+    fn is_nonblocking(&self) -> bool {
+        self.flags.lock().contains(OpenFlags::NON_BLOCK)
+    }
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        {
+            let mut buffer = self.buffer.lock();
+            if self.readable {
+                buffer.readers -= 1;
+            } else {
+                buffer.writers -= 1;
+            }
+        }
+        self.queue.notify();
+    }
+}
+
+impl FileIO for Pipe {
+    /// 没有数据时阻塞，所有写端都关闭后返回 0；阻塞期间有信号时返回`WouldBlock`，
+    /// 由`read`系统调用转为`EINTR`
+    fn read(&self, buf: &mut [u8]) -> AxResult<usize> {
+        if !self.readable {
+            return Err(AxError::PermissionDenied);
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            {
+                let mut buffer = self.buffer.lock();
+                if !buffer.data.is_empty() {
+                    let len = buf.len().min(buffer.data.len());
+                    for (dst, src) in buf.iter_mut().zip(buffer.data.drain(..len)) {
+                        *dst = src;
+                    }
+                    drop(buffer);
+                    self.queue.notify();
+                    return Ok(len);
+                }
+                if buffer.writers == 0 {
+                    return Ok(0);
+                }
+            }
+            if self.is_nonblocking()
+                || wait_ready(&[self], None, || self.ready_to_read()) != PollWait::Ready
+            {
+                return Err(AxError::WouldBlock);
+            }
+        }
+    }
+
+    /// 写完所有数据才返回。所有读端都关闭时返回`ConnectionReset`，由`write`系统调用
+    /// 转为`SIGPIPE`和`EPIPE`。非阻塞或者被信号打断时返回已经写入的长度。
+    fn write(&self, buf: &[u8]) -> AxResult<usize> {
+        if self.readable {
+            return Err(AxError::PermissionDenied);
+        }
+        let mut written = 0;
+        while written < buf.len() {
+            {
+                let mut buffer = self.buffer.lock();
+                if buffer.readers == 0 {
+                    return Err(AxError::ConnectionReset);
+                }
+                let len = (PIPE_BUF_SIZE - buffer.data.len()).min(buf.len() - written);
+                if len > 0 {
+                    buffer.data.extend(&buf[written..written + len]);
+                    written += len;
+                    drop(buffer);
+                    self.queue.notify();
+                    continue;
+                }
+            }
+            if self.is_nonblocking()
+                || wait_ready(&[self], None, || self.ready_to_write()) != PollWait::Ready
+            {
+                break;
+            }
+        }
+        if written == 0 && !buf.is_empty() {
+            return Err(AxError::WouldBlock);
+        }
+        Ok(written)
+    }
+
+    fn flush(&self) -> AxResult {
+        Ok(())
+    }
+
+    fn readable(&self) -> bool {
+        self.readable
+    }
+
+    fn writable(&self) -> bool {
+        !self.readable
+    }
+
+    fn executable(&self) -> bool {
+        false
+    }
+
+    fn get_type(&self) -> FileIOType {
+        FileIOType::Pipe
+    }
+
+    fn get_stat(&self) -> AxResult<Kstat> {
+        Ok(Kstat {
+            st_ino: 1,
+            st_mode: StMode::S_IFIFO.bits() | 0o600,
+            st_nlink: 1,
+            st_blksize: PIPE_BUF_SIZE as _,
+            ..Default::default()
+        })
+    }
+
+    fn set_status(&self, flags: OpenFlags) -> bool {
+        *self.flags.lock() = flags;
+        true
+    }
+
+    fn get_status(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_close_on_exec(&self, is_set: bool) -> bool {
+        if is_set {
+            *self.flags.lock() |= OpenFlags::CLOEXEC;
+        } else {
+            *self.flags.lock() &= !OpenFlags::CLOEXEC;
+        }
+        true
+    }
+
+    /// 写端关闭时读端挂起
+    fn is_hang_up(&self) -> bool {
+        self.readable && self.buffer.lock().writers == 0
+    }
+
+    /// 读端关闭时写端出错
+    fn in_exceptional_conditions(&self) -> bool {
+        !self.readable && self.buffer.lock().readers == 0
+    }
+
+    fn ready_to_read(&self) -> bool {
+        let buffer = self.buffer.lock();
+        self.readable && (!buffer.data.is_empty() || buffer.writers == 0)
+    }
+
+    fn ready_to_write(&self) -> bool {
+        let buffer = self.buffer.lock();
+        !self.readable && (buffer.data.len() < PIPE_BUF_SIZE || buffer.readers == 0)
+    }
+
+    fn poll_queue(&self) -> Option<&PollQueue> {
+        Some(&self.queue)
+    }
+}
@@ -1,22 +1,34 @@
 use crate::{
     linux_env::{
-        axfs_ext::api::{FileIO, OpenFlags},
+        axfs_ext::api::{FileIO, FileIOType, OpenFlags},
         linux_fs::{
-            fd_manager::{FDM, alloc_fd},
-            link::{AT_FDCWD, FilePath, deal_with_path},
-            utils::{UtilsError, deal_path},
+            fd_manager::{current_fd_manager, get_cwd},
+            link::{AT_FDCWD, FilePath, deal_with_path, raw_ptr_to_ref_str},
+            utils::{UtilsError, check_parent_writable, deal_path},
         },
+        process_ext::api::current_process,
     },
     syscall::{
         SyscallError, SyscallResult, TimeSecs,
         ctypes::{Fcntl64Cmd, RenameFlags},
-        syscall_fs::ctype::file::{FileDesc, new_fd},
+        syscall_fs::ctype::{
+            dir::DirDesc,
+            file::{FileDesc, inode_of, new_fd},
+        },
     },
 };
-use alloc::{string::ToString, sync::Arc, vec};
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+};
 use axerrno::AxError;
-use axfs::api::{
-    Permissions, create_dir, metadata, remove_dir, remove_file, rename, set_current_dir,
+use axfs::{
+    api::{
+        check_access, create_dir, file_access, metadata, read_link, remove_dir, remove_file,
+        rename, set_mode, set_owner,
+    },
+    fops::FileType,
 };
 use axlog::{debug, error, info, warn};
 
@@ -27,27 +39,28 @@ use axlog::{debug, error, info, warn};
 /// # Return
 /// 成功执行,则返回当前工作目录的字符串的指针。失败,则返回`NULL`。
 /// 暂时:成功执行,则返回当前工作目录的字符串的指针 `as isize`。失败,返回0。
-///
-/// TODO: 当前写法存在问题,`cwd`应当是各个进程独立的,而这里修改的是整个`fs`的目录
 pub fn syscall_getcwd(args: [usize; 6]) -> SyscallResult {
     let buf = args[0] as *mut u8;
     let len = args[1];
     debug!("Into syscall_getcwd. buf: {}, len: {}", buf as usize, len);
-    let cwd = axfs::api::current_dir().unwrap();
+    let mut cwd = get_cwd();
+    if cwd.len() > 1 {
+        // 内部保存的工作目录以`/`结尾，返回给用户时去掉
+        cwd.pop();
+    }
 
     // TODO: 如果buf为NULL,则系统分配缓存区
-    // let process = current_process();
-    // let process_inner = process.inner.lock();
     // if buf.is_null() {
     //     buf = allocate_buffer(cwd.len());   // 分配缓存区 allocate_buffer
     // }
 
     let cwd = cwd.as_bytes();
 
-    if len >= cwd.len() {
+    if len > cwd.len() {
         // FIX: 这里需要判断能不能访问
         unsafe {
             core::ptr::copy_nonoverlapping(cwd.as_ptr(), buf, cwd.len());
+            *buf.add(cwd.len()) = 0;
         }
         Ok(buf as isize)
     } else {
@@ -59,8 +72,8 @@ pub fn syscall_getcwd(args: [usize; 6]) -> SyscallResult {
 /// # Arguments
 /// * `dirfd: usize`, 要创建的目录所在的目录的文件描述符。
 /// * `path: *const u8`, 要创建的目录的名称。如果`path`是相对路径,则它是相对于`dirfd`目录而言的。如果`path`是相对路径,且`dirfd`的值为`AT_FDCWD`,则它是相对于当前路径而言的。如果`path`是绝对路径,则`dirfd`被忽略。
-/// * `mode: u32`, 文件的所有权描述。详见`man 7 inode`。
-/// 返回值:成功执行,返回0。失败,返回-1。
+/// * `mode: u32`, 目录的权限，去掉`umask`后生效。详见`man 7 inode`。
+/// 返回值:成功执行,返回0。失败,返回-1。新目录属于当前进程的有效用户。
 // FIX: 不建议对一个目录使用判断是否存在的方法。
 // 在对目录进行查找的时候，会报错: `[  1.266962 0:2 fatfs::dir:139] Is a directory`
 // 这个查找之后似乎不对`ArceOS`代码和`fatfs`的代码进行修改是没有办法解决的。
@@ -112,9 +125,22 @@ pub fn syscall_mkdirat(args: [usize; 6]) -> SyscallResult {
         },
     }
 
+    check_parent_writable(path.path(), false)?;
+
     // 只要文件夹存在就返回0
     match create_dir(path.path()) {
-        Ok(_) => Ok(0),
+        Ok(_) => {
+            let process = current_process();
+            let cred = process.cred();
+            let mode = mode & 0o7777 & !(process.fs.get_mask() as u32);
+            // 文件系统不支持时保留默认的属主和权限
+            if let Err(e) = set_owner(path.path(), Some(cred.euid), Some(cred.egid))
+                .and_then(|_| set_mode(path.path(), mode as u16))
+            {
+                warn!("set owner of {} failed: {:?}", path.path(), e);
+            }
+            Ok(0)
+        }
         Err(e) => match e {
             AxError::AlreadyExists => {
                 debug!("Err: EEXIST pathname already exists (not necessarily as a directory).");
@@ -160,14 +186,15 @@ pub fn syscall_chdir(args: [usize; 6]) -> SyscallResult {
     debug!("Into syscall_chdir. path: {:?}", path.path());
     match metadata(path.path()) {
         Ok(metadata) => {
-            let perm = metadata.permissions();
-            if !perm.contains(Permissions::OWNER_READ) {
-                return Err(SyscallError::EACCES);
-            }
             if !metadata.is_dir() {
                 return Err(SyscallError::ENOTDIR);
             }
-            set_current_dir(path.path()).unwrap();
+            // 需要对目录有搜索权限
+            let cred = current_process().cred().fs_credentials();
+            if check_access(path.path(), &cred, 0o1).is_err() {
+                return Err(SyscallError::EACCES);
+            }
+            current_process().fs.set_cwd(path.path());
             Ok(0)
         }
         Err(e) => match e {
@@ -189,8 +216,84 @@ pub fn syscall_chdir(args: [usize; 6]) -> SyscallResult {
 /// # Return
 /// * On success, the number of bytes read is returned. On end of directory, 0 is returned.
 /// * On error, -1 is returned.
-pub fn syscall_getdents64(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+///
+/// 每一项是一个`linux_dirent64`，按 8 字节对齐；`d_off`是下一项的位置，可以传给`lseek`。
+pub fn syscall_getdents64(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let buf = args[1] as *mut u8;
+    let len = args[2];
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+    let Some(dir) = file.as_any().downcast_ref::<DirDesc>() else {
+        return Err(SyscallError::ENOTDIR);
+    };
+    if buf.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let buf = unsafe { core::slice::from_raw_parts_mut(buf, len) };
+    let dir_path = dir.dir_path.trim_end_matches('/');
+    let mut written = 0;
+    dir.read_entries(|name, ty, next_offset| {
+        let reclen = (DIRENT64_NAME_OFFSET + name.len() + 1).next_multiple_of(8);
+        if written + reclen > buf.len() {
+            return false;
+        }
+        let path = alloc::format!("{}/{}", dir_path, name);
+        let d_type = match ty {
+            FileType::File if read_link(&path).is_ok() => DT_LNK,
+            _ => dirent_type(ty),
+        };
+        let record = &mut buf[written..written + reclen];
+        record.fill(0);
+        record[0..8].copy_from_slice(&inode_of(&path, false).to_ne_bytes());
+        record[8..16].copy_from_slice(&next_offset.to_ne_bytes());
+        record[16..18].copy_from_slice(&(reclen as u16).to_ne_bytes());
+        record[18] = d_type;
+        record[DIRENT64_NAME_OFFSET..DIRENT64_NAME_OFFSET + name.len()]
+            .copy_from_slice(name.as_bytes());
+        written += reclen;
+        true
+    })
+    .map_err(|_| SyscallError::ENOENT)?;
+    // 还有目录项但缓冲区连一项也放不下
+    if written == 0 && !dir_exhausted(dir) {
+        return Err(SyscallError::EINVAL);
+    }
+    Ok(written as isize)
+}
+
+/// `linux_dirent64`中`d_name`的偏移，前面是`d_ino`、`d_off`、`d_reclen`和`d_type`
+const DIRENT64_NAME_OFFSET: usize = 19;
+const DT_FIFO: u8 = 1;
+const DT_CHR: u8 = 2;
+const DT_DIR: u8 = 4;
+const DT_BLK: u8 = 6;
+const DT_REG: u8 = 8;
+const DT_LNK: u8 = 10;
+const DT_SOCK: u8 = 12;
+
+fn dirent_type(ty: FileType) -> u8 {
+    match ty {
+        FileType::Fifo => DT_FIFO,
+        FileType::CharDevice => DT_CHR,
+        FileType::Dir => DT_DIR,
+        FileType::BlockDevice => DT_BLK,
+        FileType::File => DT_REG,
+        FileType::SymLink => DT_LNK,
+        FileType::Socket => DT_SOCK,
+    }
+}
+
+/// 目录是否已经读完
+fn dir_exhausted(dir: &DirDesc) -> bool {
+    let mut exhausted = true;
+    let _ = dir.read_entries(|_, _, _| {
+        exhausted = false;
+        false
+    });
+    exhausted
 }
 
 /// 276
@@ -306,6 +409,9 @@ pub fn syscall_renameat2(args: [usize; 6]) -> SyscallResult {
         }
     }
 
+    check_parent_writable(old_path.path(), true)?;
+    check_parent_writable(new_path.path(), false)?;
+
     // 做实际重命名操作
     match metadata(old_path.path()) {
         Ok(_) => {}
@@ -346,13 +452,20 @@ pub fn syscall_renameat2(args: [usize; 6]) -> SyscallResult {
             Err(e) => match e {
                 // 新文件不存在时，直接执行后续操作
                 AxError::NotFound => {}
-                _ => panic!("{}", e),
+                _ => {
+                    error!("error: {:?}", e);
+                    return Err(SyscallError::from(e));
+                }
             },
         };
 
-        if let Err(err) = rename(old_path.path(), new_path.path()) {
-            error!("error: {:?}", err);
-            return Err(SyscallError::EPERM);
+        match rename(old_path.path(), new_path.path()) {
+            Ok(()) => {}
+            Err(AxError::CrossesDevices) => return Err(SyscallError::EXDEV),
+            Err(err) => {
+                error!("error: {:?}", err);
+                return Err(SyscallError::EPERM);
+            }
         }
     } else {
         // 当前不支持交换
@@ -370,7 +483,8 @@ pub fn syscall_fcntl64(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let cmd = args[1];
     let arg = args[2];
-    let mut fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
 
     if fd >= fd_table.len() {
         debug!("fd {} is out of range", fd);
@@ -384,7 +498,7 @@ pub fn syscall_fcntl64(args: [usize; 6]) -> SyscallResult {
     info!("fd: {}, cmd: {}", fd, cmd);
     match Fcntl64Cmd::try_from(cmd) {
         Ok(Fcntl64Cmd::F_DUPFD) => {
-            let new_fd = if let Ok(fd) = alloc_fd(&mut fd_table) {
+            let new_fd = if let Ok(fd) = fd_manager.alloc_fd(&mut fd_table) {
                 fd
             } else {
                 // 文件描述符达到上限了
@@ -417,7 +531,7 @@ pub fn syscall_fcntl64(args: [usize; 6]) -> SyscallResult {
             Err(SyscallError::EINVAL)
         }
         Ok(Fcntl64Cmd::F_DUPFD_CLOEXEC) => {
-            let new_fd = if let Ok(fd) = alloc_fd(&mut fd_table) {
+            let new_fd = if let Ok(fd) = fd_manager.alloc_fd(&mut fd_table) {
                 fd
             } else {
                 // 文件描述符达到上限了
@@ -435,10 +549,66 @@ pub fn syscall_fcntl64(args: [usize; 6]) -> SyscallResult {
     }
 }
 
-// FIXME: fatfs文件系统不支持设置权限，会直接当作0o755返回。
+/// 修改`path`的权限，只有属主和 root 可以修改。
+/// 非 root 用户不属于文件的组时，设置组号位被清除
+fn chmod(path: &str, mode: usize) -> SyscallResult {
+    let cred = current_process().cred();
+    let access = file_access(path).map_err(|_| SyscallError::ENOENT)?;
+    if !cred.is_root() && cred.euid != access.uid {
+        return Err(SyscallError::EPERM);
+    }
+    let mut mode = (mode & 0o7777) as u16;
+    if !cred.is_root() && !cred.in_group(access.gid) {
+        mode &= !0o2000;
+    }
+    set_mode(path, mode).map_err(|e| {
+        warn!("chmod {} failed: {:?}", path, e);
+        SyscallError::EPERM
+    })?;
+    Ok(0)
+}
+
+/// 修改`path`的属主和组，`-1`表示不改变。
+/// root 可以任意修改；属主只能把组改为自己所在的组，不能改变属主。
+/// 普通文件的属主或组改变后清除设置用户号和设置组号位
+fn chown(path: &str, uid: usize, gid: usize) -> SyscallResult {
+    let id_arg = |id: usize| (id as u32 != u32::MAX).then_some(id as u32);
+    let (uid, gid) = (id_arg(uid), id_arg(gid));
+    let cred = current_process().cred();
+    let access = file_access(path).map_err(|_| SyscallError::ENOENT)?;
+    if !cred.is_root()
+        && (cred.euid != access.uid
+            || uid.is_some_and(|uid| uid != access.uid)
+            || gid.is_some_and(|gid| !cred.in_group(gid)))
+    {
+        return Err(SyscallError::EPERM);
+    }
+    set_owner(path, uid, gid).map_err(|e| {
+        warn!("chown {} failed: {:?}", path, e);
+        SyscallError::EPERM
+    })?;
+    let is_dir = metadata(path).is_ok_and(|meta| meta.is_dir());
+    if (uid.is_some() || gid.is_some()) && !is_dir && access.mode & 0o6000 != 0 {
+        set_mode(path, access.mode & !0o6000).map_err(|_| SyscallError::EPERM)?;
+    }
+    Ok(0)
+}
+
+/// 文件描述符对应的路径，只有普通文件和目录有路径
+fn fd_path(fd: usize) -> Result<String, SyscallError> {
+    let file_io = match current_fd_manager().fd_table.lock().get(fd) {
+        Some(Some(f)) => f.clone(),
+        _ => return Err(SyscallError::EBADF), // 文件描述符无效
+    };
+    match file_io.get_type() {
+        FileIOType::FileDesc | FileIOType::DirDesc => Ok(file_io.get_path()),
+        _ => Err(SyscallError::EINVAL),
+    }
+}
+
 /// 53
-/// 修改文件权限
-/// mode: 0o777, 3位八进制数字
+/// 修改文件权限，只有属主和 root 可以修改
+/// mode: 0o7777, 包括设置用户号、设置组号和粘滞位
 /// path为相对路径:
 ///     1. 若`dir_fd`为`AT_FDCWD`,则相对于当前工作目录
 ///     2. 若`dir_fd`为`AT_FDCWD`以外的值,则相对于`dir_fd`所指的目录
@@ -453,12 +623,6 @@ pub fn syscall_fchmodat(args: [usize; 6]) -> SyscallResult {
     let path = args[1] as *const u8;
     let mode = args[2];
 
-    let mode = if let Some(ans) = Permissions::from_bits(mode as u16) {
-        ans
-    } else {
-        return Err(SyscallError::EINVAL);
-    };
-
     let file_path = match deal_path(dir_fd, Some(path), false) {
         Ok(path) => path,
         Err(e) => match e {
@@ -474,82 +638,73 @@ pub fn syscall_fchmodat(args: [usize; 6]) -> SyscallResult {
             }
         },
     };
-
-    //```
-    // FIXME: 这里是有问题的，因为现有的文件系统尚不支持设置权限，在这里实验了一下
-    //    let file_io = match FDM.fd_table.lock().get(dir_fd) {
-    //        Some(Some(f)) => f.clone(),
-    //        _ => return Err(SyscallError::EBADF),
-    //    };
-    //    if let Some(file_desc) = file_io.as_any().downcast_ref::<FileDesc>() {
-    //        warn!("Get perm, before {:?}", file_desc.get_perm());
-    //        file_desc.set_perm(mode);
-    //        warn!("Get perm, after  {:?}", file_desc.get_perm());
-    //    } else {
-    //        warn!("fdsaf");
-    //    }
-
-    match metadata(file_path.path()) {
-        Ok(mut meta) => {
-            meta.set_permissions(mode);
-            Ok(0)
-        }
-        Err(e) => match e {
-            AxError::NotFound => return Err(SyscallError::ENOENT),
-            _ => {
-                panic!("{:?}", e);
-            }
-        },
-    }
+    chmod(file_path.follow_link()?.path(), mode)
 }
 
 /// 修改指定文件描述符的文件权限
-/// mode: 0o777, 3位八进制数字
+/// mode: 0o7777
 /// # Arguments
 /// * `fd: usize`, 文件的文件描述符
 /// * `mode: usize`, 文件的权限
 pub fn syscall_fchmod(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let mode = args[1];
+    chmod(&fd_path(fd)?, mode)
+}
 
-    // 将模式转换为 Permissions 类型
-    let mode = if let Some(ans) = Permissions::from_bits(mode as u16) {
-        ans
-    } else {
-        return Err(SyscallError::EINVAL);
-    };
+/// `fchownat`的`flags`：最后一项是符号链接时修改链接本身
+const AT_SYMLINK_NOFOLLOW: usize = 0x100;
+/// `fchownat`的`flags`：`path`为空时修改`dir_fd`本身
+const AT_EMPTY_PATH: usize = 0x1000;
 
-    // 获取文件描述符对应的文件
-    let file_io = match FDM.fd_table.lock().get(fd) {
-        Some(Some(f)) => f.clone(),
-        _ => return Err(SyscallError::EBADF), // 文件描述符无效
-    };
+/// 54
+/// 修改文件的属主和组，`-1`表示不改变。路径的规则同`fchmodat`
+///
+/// 符号链接本身没有单独记录的属主，`AT_SYMLINK_NOFOLLOW`时不做修改
+/// # Arguments
+/// * `dir_fd: usize`, 目录的文件描述符
+/// * `path: *const u8`, 文件的路径
+/// * `owner: u32`, 新的属主
+/// * `group: u32`, 新的组
+/// * `flags: usize`, `AT_SYMLINK_NOFOLLOW`和`AT_EMPTY_PATH`
+pub fn syscall_fchownat(args: [usize; 6]) -> SyscallResult {
+    let dir_fd = args[0];
+    let path = args[1] as *const u8;
+    let (owner, group) = (args[2], args[3]);
+    let flags = args[4];
 
-    if let Some(file_desc) = file_io.as_any().downcast_ref::<FileDesc>() {
-        // 修改文件权限
-        match file_desc.file.lock().metadata() {
-            Ok(mut metadata) => {
-                metadata.set_permissions(mode);
-                Ok(0)
-            }
-            Err(e) => {
-                warn!("Unexpected err catched. {:?}", e);
-                Err(SyscallError::EINVAL)
+    if flags & AT_EMPTY_PATH != 0 && unsafe { raw_ptr_to_ref_str(path) }.is_empty() {
+        return chown(&fd_path(dir_fd)?, owner, group);
+    }
+    let file_path = match deal_path(dir_fd, Some(path), false) {
+        Ok(path) => path,
+        Err(e) => match e {
+            UtilsError::CannotAcce | UtilsError::NULL => return Err(SyscallError::EFAULT),
+            UtilsError::StrTooLong => return Err(SyscallError::ENAMETOOLONG),
+            UtilsError::OutOfTable => return Err(SyscallError::EBADF),
+            UtilsError::StrEmpty | UtilsError::NoEntryInTable => return Err(SyscallError::ENOENT),
+            _ => {
+                error!("{:?}", e);
+                return Err(SyscallError::EPERM);
             }
-        }
-    } else {
-        Err(SyscallError::EINVAL) // 文件描述福类型错误
+        },
+    };
+    if flags & AT_SYMLINK_NOFOLLOW != 0 && read_link(file_path.path()).is_ok() {
+        debug!("lchown on symlink {} is ignored", file_path.path());
+        return Ok(0);
     }
+    chown(file_path.follow_link()?.path(), owner, group)
 }
 
-// TODO: 添加支持与文档
-pub fn syscall_fchownat(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-}
-
-// TODO: 添加支持与文档
-pub fn syscall_fchown(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+/// 修改指定文件描述符的属主和组，`-1`表示不改变
+/// # Arguments
+/// * `fd: usize`, 文件的文件描述符
+/// * `owner: u32`, 新的属主
+/// * `group: u32`, 新的组
+pub fn syscall_fchown(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let (owner, group) = (args[1], args[2]);
+    chown(&fd_path(fd)?, owner, group)
 }
 
 /// 48
@@ -562,6 +717,8 @@ pub fn syscall_fchown(_args: [usize; 6]) -> SyscallResult {
 ///        file exists and grants read, write, and execute permissions,
 ///        respectively.
 /// 0: F_OK, 1: X_OK, 2: W_OK, 4: R_OK
+///
+/// 与 Linux 相同，使用当前进程的实际用户号和组号检查
 /// # Arguments
 /// * `dir_fd: usize`, 目录的文件描述符
 /// * `path: *const u8`, 文件的路径
@@ -570,8 +727,6 @@ pub fn syscall_faccessat(args: [usize; 6]) -> SyscallResult {
     let dir_fd = args[0];
     let path = args[1] as *const u8;
     let mode = args[2];
-    // `todo`: 有问题,实际上需要考虑当前进程对应的用户`UID`和文件拥有者之间的关系
-    // 现在一律当作root用户处理
     let file_path = match deal_path(dir_fd, Some(path), false) {
         Ok(path) => path,
         Err(e) => match e {
@@ -587,39 +742,22 @@ pub fn syscall_faccessat(args: [usize; 6]) -> SyscallResult {
         },
     };
 
-    let mode = if let Some(ans) = Permissions::from_bits(mode as u16) {
-        ans
-    } else {
+    let file_path = file_path.follow_link()?;
+
+    if mode & !0o7 != 0 {
         debug!("Err: An invalid flag was specified in mode");
         return Err(SyscallError::EINVAL);
-    };
+    }
 
-    match metadata(file_path.path()) {
-        Ok(metadata) => {
-            if mode.is_empty() {
-                // F_OK
-                Ok(0)
-            } else {
-                let perm = metadata.permissions();
-                // 逐位对比
-                if (mode.contains(Permissions::OWNER_EXEC)
-                    && !perm.contains(Permissions::OWNER_EXEC))
-                    || (mode.contains(Permissions::OWNER_WRITE)
-                        && !perm.contains(Permissions::OWNER_WRITE))
-                    || (mode.contains(Permissions::OWNER_READ)
-                        && !perm.contains(Permissions::OWNER_READ))
-                {
-                    return Err(SyscallError::EACCES);
-                }
-                Ok(0)
-            }
+    let cred = current_process().cred().real_credentials();
+    match check_access(file_path.path(), &cred, mode as u16) {
+        Ok(()) => Ok(0),
+        Err(AxError::PermissionDenied) => Err(SyscallError::EACCES),
+        Err(AxError::NotFound) => Err(SyscallError::ENOENT),
+        Err(e) => {
+            warn!("Unexpected err catched. {:?}", e);
+            Err(SyscallError::EINVAL)
         }
-        Err(e) => match e {
-            AxError::NotFound => return Err(SyscallError::ENOENT),
-            _ => {
-                panic!("{:?}", e);
-            }
-        },
     }
 }
 
@@ -634,7 +772,8 @@ pub fn syscall_ioctl(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let request = args[1];
     let argp = args[2];
-    let fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let fd_table = fd_manager.fd_table.lock();
     warn!("fd: {}, request: {}, argp: {}", fd, request, argp);
     if fd >= fd_table.len() {
         debug!("fd {} is out of range", fd);
@@ -651,6 +790,8 @@ pub fn syscall_ioctl(args: [usize; 6]) -> SyscallResult {
     let file = fd_table[fd].clone().unwrap();
     match file.ioctl(request, argp) {
         Ok(ret) => Ok(ret),
+        Err(AxError::BadAddress) => Err(SyscallError::EFAULT),
+        Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
         Err(_) => Ok(0),
     }
 }
@@ -700,7 +841,8 @@ pub fn syscall_utimensat(args: [usize; 6]) -> SyscallResult {
         //     return ErrorNo::EPERM as isize;
         // }
         //        let fd_table = process.fd_manager.fd_table.lock();
-        let fd_table = FDM.fd_table.lock();
+        let fd_manager = current_fd_manager();
+        let fd_table = fd_manager.fd_table.lock();
         if dir_fd > fd_table.len() || fd_table[dir_fd].is_none() {
             return Err(SyscallError::EBADF);
         }
@@ -1,4 +1,25 @@
-use crate::syscall::SyscallResult;
+use alloc::sync::Arc;
+use core::time::Duration;
+
+use axhal::time::monotonic_time;
+use axlog::{debug, info};
+
+use crate::{
+    linux_env::{
+        axfs_ext::api::OpenFlags, linux_fs::fd_manager::current_fd_manager,
+        signal_ext::api::set_temporary_mask,
+    },
+    syscall::{
+        SyscallError, SyscallResult,
+        syscall_fs::ctype::epoll::{EpollCtl, EpollEvent, EpollEventType, EpollFile},
+    },
+};
+
+/// 与`O_CLOEXEC`相同
+const EPOLL_CLOEXEC: usize = 0o2000000;
+
+/// 内核中信号集的大小
+const SIGSET_SIZE_IN_BYTE: usize = 8;
 
 /// For `epoll_create`, Since Linux 2.6.8, the size argument is ignored, but must be greater than zero;
 ///
@@ -9,8 +30,21 @@ use crate::syscall::SyscallResult;
 /// If flag equals to `EPOLL_CLOEXEC`, than set the `cloexec` flag for the `fd`
 /// # Arguments
 /// * `flag - usize`
-pub fn syscall_epoll_create1(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_epoll_create1(args: [usize; 6]) -> SyscallResult {
+    let flag = args[0];
+    info!("[epoll_create1()] flag: {flag:#x}");
+    let flags = match flag {
+        0 => OpenFlags::empty(),
+        EPOLL_CLOEXEC => OpenFlags::CLOEXEC,
+        _ => return Err(SyscallError::EINVAL),
+    };
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
+    let fd = fd_manager
+        .alloc_fd(&mut fd_table)
+        .map_err(|_| SyscallError::EMFILE)?;
+    fd_table[fd] = Some(Arc::new(EpollFile::new(flags)));
+    Ok(fd as isize)
 }
 
 /// 执行syscall_epoll_ctl，修改文件对应的响应事件
@@ -22,8 +56,45 @@ pub fn syscall_epoll_create1(_args: [usize; 6]) -> SyscallResult {
 /// * `op: i32`, 修改操作的类型
 /// * `fd: i32`, 接受事件的文件的`fd`
 /// * `event: *const EpollEvent`, 接受的事件
-pub fn syscall_epoll_ctl(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_epoll_ctl(args: [usize; 6]) -> SyscallResult {
+    let epfd = args[0] as i32;
+    let op = args[1] as i32;
+    let fd = args[2] as i32;
+    let event = args[3] as *const EpollEvent;
+    info!("[epoll_ctl()] epfd: {epfd}, op: {op}, fd: {fd}");
+    let op = EpollCtl::try_from(op).map_err(|_| SyscallError::EINVAL)?;
+
+    let (epoll_file, file) = {
+        let fd_manager = current_fd_manager();
+        let fd_table = fd_manager.fd_table.lock();
+        let get = |fd: i32| match fd_table.get(fd as usize) {
+            Some(Some(file)) => Ok(file.clone()),
+            _ => Err(SyscallError::EBADF),
+        };
+        (get(epfd)?, get(fd)?)
+    };
+    let Some(epoll_file) = epoll_file.as_any().downcast_ref::<EpollFile>() else {
+        return Err(SyscallError::EINVAL);
+    };
+    // 不能监控自己
+    let watches_itself = file
+        .as_any()
+        .downcast_ref::<EpollFile>()
+        .is_some_and(|file| Arc::ptr_eq(&file.inner, &epoll_file.inner));
+    if watches_itself {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let event = match op {
+        EpollCtl::DEL => EpollEvent {
+            event_type: EpollEventType::empty(),
+            data: 0,
+        },
+        _ if event.is_null() => return Err(SyscallError::EFAULT),
+        _ => unsafe { *event },
+    };
+    debug!("[epoll_ctl()] event: {:?}", event);
+    epoll_file.epoll_ctl(op, fd, event)
 }
 
 /// 执行syscall_epoll_wait系统调用
@@ -33,8 +104,45 @@ pub fn syscall_epoll_ctl(_args: [usize; 6]) -> SyscallResult {
 /// * `event: *mut EpollEvent`, 接受事件的数组
 /// * `max_event: i32`, 最大的响应事件数量,必须大于0
 /// * `timeout: i32`, 超时时间，是一段相对时间，需要手动转化为绝对时间
+/// * `sigmask: *const u64`, 等待期间使用的信号掩码，为空时不改变
+/// * `sigsetsize: usize`
 ///
 /// `ret`: 实际写入的响应事件数目
-pub fn syscall_epoll_wait(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_epoll_wait(args: [usize; 6]) -> SyscallResult {
+    let epfd = args[0];
+    let events = args[1] as *mut EpollEvent;
+    let max_event = args[2] as i32;
+    let timeout = args[3] as i32;
+    let sigmask = args[4] as *const u64;
+    let sigsetsize = args[5];
+    info!("[epoll_pwait()] epfd: {epfd}, max_event: {max_event}, timeout: {timeout}");
+    if max_event <= 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if events.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let file = match current_fd_manager().fd_table.lock().get(epfd) {
+        Some(Some(file)) => file.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+    let Some(epoll_file) = file.as_any().downcast_ref::<EpollFile>() else {
+        return Err(SyscallError::EINVAL);
+    };
+    if !sigmask.is_null() {
+        if sigsetsize != SIGSET_SIZE_IN_BYTE {
+            return Err(SyscallError::EINVAL);
+        }
+        set_temporary_mask(unsafe { *sigmask });
+    }
+
+    // 负数表示一直等待
+    let deadline = (timeout >= 0).then(|| monotonic_time() + Duration::from_millis(timeout as u64));
+    let ret_events = epoll_file.epoll_wait(max_event as usize, deadline)?;
+    for (i, event) in ret_events.iter().enumerate() {
+        unsafe {
+            *events.add(i) = *event;
+        }
+    }
+    Ok(ret_events.len() as isize)
 }
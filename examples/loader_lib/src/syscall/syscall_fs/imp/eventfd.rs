@@ -1,5 +1,44 @@
-use crate::syscall::SyscallResult;
+use alloc::sync::Arc;
 
-pub fn syscall_eventfd(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+use axlog::info;
+
+use crate::{
+    linux_env::{axfs_ext::api::OpenFlags, linux_fs::fd_manager::current_fd_manager},
+    syscall::{SyscallError, SyscallResult, syscall_fs::ctype::eventfd::EventFd},
+};
+
+/// 每次读出 1
+const EFD_SEMAPHORE: u32 = 1;
+/// 与`O_NONBLOCK`相同
+const EFD_NONBLOCK: u32 = 0o4000;
+/// 与`O_CLOEXEC`相同
+const EFD_CLOEXEC: u32 = 0o2000000;
+
+/// 创建一个`eventfd`
+/// # Arguments
+/// * `initval: u32`, 计数器的初值
+/// * `flags: u32`, `EFD_SEMAPHORE`、`EFD_NONBLOCK`和`EFD_CLOEXEC`
+pub fn syscall_eventfd(args: [usize; 6]) -> SyscallResult {
+    let initval = args[0] as u32;
+    let flags = args[1] as u32;
+    info!("[eventfd2()] initval: {initval}, flags: {flags:#x}");
+    if flags & !(EFD_SEMAPHORE | EFD_NONBLOCK | EFD_CLOEXEC) != 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let mut open_flags = OpenFlags::empty();
+    if flags & EFD_NONBLOCK != 0 {
+        open_flags |= OpenFlags::NON_BLOCK;
+    }
+    if flags & EFD_CLOEXEC != 0 {
+        open_flags |= OpenFlags::CLOEXEC;
+    }
+    let eventfd = EventFd::new(initval as u64, flags & EFD_SEMAPHORE != 0, open_flags);
+
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
+    let fd = fd_manager
+        .alloc_fd(&mut fd_table)
+        .map_err(|_| SyscallError::EMFILE)?;
+    fd_table[fd] = Some(Arc::new(eventfd));
+    Ok(fd as isize)
 }
@@ -1,24 +1,30 @@
 use crate::{
     linux_env::{
-        axfs_ext::api::{FileIOType, OpenFlags, SeekFrom},
+        axfs_ext::api::{FileIO, FileIOType, OpenFlags, SeekFrom},
         linux_fs::{
-            fd_manager::{FDM, alloc_fd},
-            link::{create_link, deal_with_path},
-            utils::{UtilsError, deal_path, has_permission},
+            fd_manager::current_fd_manager,
+            link::deal_with_path,
+            utils::{UtilsError, deal_path},
+        },
+        process_ext::api::current_process,
+        signal_ext::{
+            api::{has_pending_signal, send_signal_to_current},
+            signal_no::SignalNo,
         },
     },
     syscall::{
         IoVec, O_CLOEXEC, SyscallError, SyscallResult,
         syscall_fs::ctype::{
             dir::{get_dir_desc, new_dir},
-            epoll::{EpollCtl, EpollEvent, EpollEventType, EpollFile},
-            file::{new_fd, new_inode},
+            epoll::forget_fd,
+            file::new_user_fd,
+            pipe::make_pipe,
         },
     },
 };
 use alloc::{string::ToString, sync::Arc, vec};
 use axerrno::AxError;
-use axfs::api::{Permissions, metadata};
+use axfs::api::{check_access, metadata, read_link};
 use axlog::{debug, error, info, warn};
 use core::slice::{from_raw_parts, from_raw_parts_mut};
 
@@ -27,16 +33,17 @@ use core::slice::{from_raw_parts, from_raw_parts_mut};
 /// * `fd: usize`, 文件所在目录的文件描述符。
 /// * `path: *const u8`, 要打开或创建的文件名。如为绝对路径,则忽略`fd`。如为相对路径,且`fd`是`AT_FDCWD`,则`filename`是相对于当前工作目录来说的。如为相对路径,且`fd`是一个文件描述符,则`filename`是相对于`fd`所指向的目录来说的。
 /// * `flags: usize`, 必须包含如下访问模式的其中一种:`O_RDONLY`,`O_WRONLY`,`O_RDWR`。还可以包含文件创建标志和文件状态标志。
-/// * `mode: u8`, 文件的所有权描述。详见`man 7 inode `。
+/// * `mode: u32`, 新建文件的权限，去掉`umask`后生效。详见`man 7 inode `。
 /// 返回值:成功执行,返回新的文件描述符。失败,返回-1。
 ///
 /// 说明:如果打开的是一个目录,那么返回的文件描述符指向的是该目录的描述符。(后面会用到针对目录的文件描述符)
+/// 以当前进程的有效用户身份检查权限，没有权限时返回`EACCES`。
 /// `flags: O_RDONLY: 0, O_WRONLY: 1, O_RDWR: 2, O_CREAT: 64, O_DIRECTORY: 65536`
 pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let path = args[1] as *const u8;
     let flags = args[2];
-    let mode = args[3] as u8;
+    let mode = (args[3] & 0o7777) as u32;
 
     // 这些东西是常见的操作
     let flags = if let Some(ans) = OpenFlags::from_bits(flags as u32) {
@@ -55,13 +62,6 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EINVAL);
     }
 
-    let mode = if let Some(ans) = Permissions::from_bits(mode as u16) {
-        ans
-    } else {
-        debug!("Err: An invalid flag was specified in mode");
-        return Err(SyscallError::EINVAL);
-    };
-
     let path = match deal_path(fd, Some(path), flags.is_dir()) {
         Ok(path) => path,
         Err(e) => match e {
@@ -79,9 +79,19 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
             }
         },
     };
+    // `O_NOFOLLOW`时最后一项不能是符号链接
+    let path = if flags.contains(OpenFlags::NOFOLLOW) {
+        if read_link(path.path()).is_ok() {
+            return Err(SyscallError::ELOOP);
+        }
+        path
+    } else {
+        path.follow_link()?
+    };
 
-    let mut fd_table = FDM.fd_table.lock();
-    let fd_num: usize = if let Ok(fd) = alloc_fd(&mut fd_table) {
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
+    let fd_num: usize = if let Ok(fd) = fd_manager.alloc_fd(&mut fd_table) {
         debug!("allocated fd_num: {}", fd);
         fd
     } else {
@@ -91,42 +101,23 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EMFILE);
     };
 
-    let _metadata = match metadata(path.path()) {
-        Ok(metadata) => {
+    match metadata(path.path()) {
+        Ok(_) => {
             if flags.contains(OpenFlags::CREATE) && flags.contains(OpenFlags::EXCLUSIVE) {
                 debug!("Err: EEXIST pathname already exists and O_CREAT and O_EXCL were used.");
                 return Err(SyscallError::EEXIST);
             }
-            let perm = metadata.permissions();
-            if !has_permission(mode, perm) {
-                return Err(SyscallError::EACCES);
-            }
-            Some(metadata)
         }
-        Err(e) => match e {
-            AxError::NotFound => {
-                if !flags.contains(OpenFlags::CREATE) {
-                    debug!("ENOENT O_CREAT is not set and the named file does not exist.");
-                    return Err(SyscallError::ENOENT);
-                }
-                if !mode.contains(Permissions::OWNER_WRITE)
-                    && !mode.contains(Permissions::GROUP_WRITE)
-                    && !mode.contains(Permissions::OTHER_WRITE)
-                {
-                    return Err(SyscallError::EACCES);
-                }
-                debug!("not found");
-                None
+        Err(AxError::NotFound) => {
+            if !flags.contains(OpenFlags::CREATE) {
+                debug!("ENOENT O_CREAT is not set and the named file does not exist.");
+                return Err(SyscallError::ENOENT);
             }
-            _ => {
-                error!("{:?}", e);
-                None
-            }
-        },
-    };
+            debug!("not found");
+        }
+        Err(e) => error!("{:?}", e),
+    }
 
-    // 分配`inode`
-    new_inode(path.path().to_string()).unwrap();
     // 如果是`DIR`
     info!("path: {:?}", path.path());
     if path.is_dir() {
@@ -137,6 +128,11 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
             );
             return Err(SyscallError::EISDIR);
         }
+        let cred = current_process().cred().fs_credentials();
+        if let Err(AxError::PermissionDenied) = check_access(path.path(), &cred, 0o4) {
+            debug!("Err: EACCES read permission is denied for the directory.");
+            return Err(SyscallError::EACCES);
+        }
         match new_dir(path.path().to_string(), flags.into()) {
             Ok(dir) => {
                 if flags.contains(OpenFlags::TMP_FILE)
@@ -176,15 +172,18 @@ pub fn syscall_openat(args: [usize; 6]) -> SyscallResult {
     // 如果是FILE,注意若创建了新文件,需要添加链接
     else {
         debug!("open file");
-        match new_fd(path.path().to_string(), flags.into()) {
+        match new_user_fd(path.path().to_string(), flags.into(), mode) {
             Ok(file) => {
                 debug!("new file_desc successfully allocated");
                 fd_table[fd_num] = Some(Arc::new(file));
-                let _ = create_link(&path, &path);
                 Ok(fd_num as isize)
             }
             Err(e) => match e {
                 //                AxError::InvalidInput => Err(SyscallError::EEXIST),
+                AxError::PermissionDenied => {
+                    debug!("Err: EACCES the requested access to the file is not allowed.");
+                    Err(SyscallError::EACCES)
+                }
                 _ => {
                     warn!("open file failed, {}", e);
                     Err(SyscallError::ENOENT)
@@ -202,7 +201,8 @@ pub fn syscall_close(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     info!("Into syscall_close. fd: {}", fd);
 
-    let mut fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
     if fd >= fd_table.len() {
         debug!("fd {} is out of range", fd);
         return Err(SyscallError::EPERM);
@@ -218,19 +218,7 @@ pub fn syscall_close(args: [usize; 6]) -> SyscallResult {
     }
     // ```
     // let file = process_inner.fd_manager.fd_table[fd].unwrap();
-    for i in 0..fd_table.len() {
-        if let Some(file) = fd_table[i].as_ref() {
-            if let Some(epoll_file) = file.as_any().downcast_ref::<EpollFile>() {
-                if epoll_file.contains(fd as i32) {
-                    let ev = EpollEvent {
-                        event_type: EpollEventType::EPOLLMSG,
-                        data: 0,
-                    };
-                    epoll_file.epoll_ctl(EpollCtl::DEL, fd as i32, ev)?;
-                }
-            }
-        }
-    }
+    forget_fd(&fd_table, fd as i32);
 
     fd_table[fd] = None;
     // ```
@@ -262,7 +250,7 @@ pub fn syscall_read(args: [usize; 6]) -> SyscallResult {
     // FIX: 进行检查，这里是不安全
     let buf = unsafe { from_raw_parts_mut(buf, count) };
 
-    let file = match FDM.fd_table.lock().get(fd) {
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
         Some(Some(f)) => f.clone(),
         _ => return Err(SyscallError::EBADF),
     };
@@ -276,7 +264,7 @@ pub fn syscall_read(args: [usize; 6]) -> SyscallResult {
 
     match file.read(buf) {
         Ok(len) => Ok(len as isize),
-        Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(AxError::WouldBlock) => Err(would_block_error(file.as_ref())),
         Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
         Err(_) => Err(SyscallError::EPERM),
     }
@@ -301,7 +289,7 @@ pub fn syscall_write(args: [usize; 6]) -> SyscallResult {
     // FIX: 进行地址检查，当超出可访问地址空间的时候，返回错误EFAULT
     let buf = unsafe { from_raw_parts(buf, count) };
 
-    let file = match FDM.fd_table.lock().get(fd) {
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
         Some(Some(f)) => f.clone(),
         _ => return Err(SyscallError::EBADF),
     };
@@ -315,14 +303,25 @@ pub fn syscall_write(args: [usize; 6]) -> SyscallResult {
 
     match file.write(buf) {
         Ok(len) => Ok(len as isize),
-        // TODO: Send a `SIGPIPE` signal to the process
-        Err(AxError::ConnectionReset) => Err(SyscallError::EPIPE),
-        Err(AxError::WouldBlock) => Err(SyscallError::EAGAIN),
+        Err(AxError::ConnectionReset) => {
+            send_signal_to_current(SignalNo::SIGPIPE as usize);
+            Err(SyscallError::EPIPE)
+        }
+        Err(AxError::WouldBlock) => Err(would_block_error(file.as_ref())),
         Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
         Err(_) => Err(SyscallError::EPERM),
     }
 }
 
+/// 读写返回`WouldBlock`时的错误码：阻塞的文件只会因为信号而放弃等待
+fn would_block_error(file: &dyn FileIO) -> SyscallError {
+    if !file.get_status().contains(OpenFlags::NON_BLOCK) && has_pending_signal() {
+        SyscallError::EINTR
+    } else {
+        SyscallError::EAGAIN
+    }
+}
+
 /// 功能:创建管道；
 /// # Arguments
 /// * `fd[2]: *mut u32`, 用于保存2个文件描述符。其中,`fd[0]`为管道的读出端,`fd[1]`为管道的写入端。
@@ -330,8 +329,40 @@ pub fn syscall_write(args: [usize; 6]) -> SyscallResult {
 /// 返回值:成功执行,返回0。失败,返回-1。
 ///
 /// 注意:`fd[2]`是32位数组,所以这里的`fd`是 u32 类型的指针,而不是`usize`类型的指针。
-pub fn syscall_pipe2(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_pipe2(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0] as *mut u32;
+    let flags = args[1] as u32;
+    info!("[pipe2()] fd: {fd:?}, flags: {flags:#x}");
+    if fd.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    // 只支持`O_CLOEXEC`和`O_NONBLOCK`
+    let flags = match OpenFlags::from_bits(flags) {
+        Some(flags) if (flags - (OpenFlags::CLOEXEC | OpenFlags::NON_BLOCK)).is_empty() => flags,
+        _ => return Err(SyscallError::EINVAL),
+    };
+    let (read_end, write_end) = make_pipe(flags);
+
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
+    let read_fd = fd_manager
+        .alloc_fd(&mut fd_table)
+        .map_err(|_| SyscallError::EMFILE)?;
+    fd_table[read_fd] = Some(read_end);
+    let write_fd = match fd_manager.alloc_fd(&mut fd_table) {
+        Ok(fd) => fd,
+        Err(_) => {
+            fd_table[read_fd] = None;
+            return Err(SyscallError::EMFILE);
+        }
+    };
+    fd_table[write_fd] = Some(write_end);
+    debug!("[pipe2()] read fd: {read_fd}, write fd: {write_fd}");
+    unsafe {
+        *fd = read_fd as u32;
+        *fd.add(1) = write_fd as u32;
+    }
+    Ok(0)
 }
 
 /// 功能:复制文件描述符；
@@ -340,7 +371,8 @@ pub fn syscall_pipe2(_args: [usize; 6]) -> SyscallResult {
 /// 返回值:成功执行,返回新的文件描述符。失败,返回-1。
 pub fn syscall_dup(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
-    let mut fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
     if fd >= fd_table.len() {
         debug!("fd {} is out of range", fd);
         return Err(SyscallError::EBADF);
@@ -350,7 +382,7 @@ pub fn syscall_dup(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EBADF);
     }
 
-    let new_fd = if let Ok(fd) = alloc_fd(&mut fd_table) {
+    let new_fd = if let Ok(fd) = fd_manager.alloc_fd(&mut fd_table) {
         fd
     } else {
         // 文件描述符达到上限了
@@ -371,7 +403,8 @@ pub fn syscall_dup3(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let new_fd = args[1];
     let flags = args[2];
-    let mut fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
     if fd >= fd_table.len() {
         debug!("fd {} is out of range", fd);
         return Err(SyscallError::EBADF);
@@ -385,7 +418,7 @@ pub fn syscall_dup3(args: [usize; 6]) -> SyscallResult {
         return Err(SyscallError::EINVAL);
     }
     if new_fd >= fd_table.len() {
-        if new_fd >= (FDM.get_limit() as usize) {
+        if new_fd >= (current_fd_manager().get_limit() as usize) {
             // 超出了资源限制
             return Err(SyscallError::EBADF);
         }
@@ -394,6 +427,10 @@ pub fn syscall_dup3(args: [usize; 6]) -> SyscallResult {
         }
     }
     info!("dup3 fd {} to new fd {} with flags {}", fd, new_fd, flags);
+    // 原来的`new_fd`被隐式关闭
+    if fd_table[new_fd].is_some() {
+        forget_fd(&fd_table, new_fd as i32);
+    }
     fd_table[new_fd] = fd_table[fd].clone();
     if flags as u32 & O_CLOEXEC != 0 {
         fd_table[new_fd].as_mut().unwrap().set_close_on_exec(true);
@@ -464,19 +501,16 @@ pub fn syscall_lseek(args: [usize; 6]) -> SyscallResult {
     let offset = args[1] as isize;
     let whence = args[2];
     info!("fd: {} offset: {} whence: {}", fd, offset, whence);
-    if fd >= FDM.fd_table.lock().len() || fd < 3 {
+    if fd >= current_fd_manager().fd_table.lock().len() || fd < 3 {
         debug!("fd {} is out of range", fd);
         return Err(SyscallError::EBADF);
     }
     if offset < 0 {
         return Err(SyscallError::EINVAL);
     }
-    let fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let fd_table = fd_manager.fd_table.lock();
     if let Some(file) = fd_table[fd].as_ref() {
-        if file.get_type() == FileIOType::DirDesc {
-            debug!("fd is a dir");
-            return Err(SyscallError::EISDIR);
-        }
         let ans = if whence == 0 {
             // 即SEEK_SET
             file.seek(SeekFrom::Start(offset as u64))
@@ -514,7 +548,7 @@ pub fn syscall_pread64(args: [usize; 6]) -> SyscallResult {
     let count = args[2];
     let offset = args[3];
     // todo: 把check fd整合到fd_manager中
-    let file = match FDM.fd_table.lock().get(fd) {
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
         Some(Some(f)) => f.clone(),
         _ => return Err(SyscallError::EBADF),
     };
@@ -528,73 +562,47 @@ pub fn syscall_pread64(args: [usize; 6]) -> SyscallResult {
         .unwrap_or_else(|_| Err(SyscallError::EINVAL))
 }
 
-// FIX: 有待测试
 /// 78
 /// `readlinkat`
 /// 读取符号链接文件的内容
-/// * 如果`buf`为`NULL`,则返回符号链接文件的长度
-/// * 如果`buf`不为`NULL`,则将符号链接文件的内容写入`buf`中
-/// 如果写入的内容超出了`buf_size`则直接截断
+/// 如果写入的内容超出了`buf_size`则直接截断，末尾不加`\0`
 /// # Arguments
 /// * `dir_fd: usize`
 /// * `path: *const u8`
 /// * `buf: *mut u8`
 /// * `bufsiz: usize`
-pub fn syscall_readlinkat(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    // let dir_fd = args[0];
-    //    let path = args[1] as *const u8;
-    //    let buf = args[2] as *mut u8;
-    //    let bufsiz = args[3];
-    //    let process = current_process();
-    //    if process
-    //        .manual_alloc_for_lazy((path as usize).into())
-    //        .is_err()
-    //    {
-    //        return Err(SyscallError::EFAULT);
-    //    }
-    //    if !buf.is_null()
-    //        && process
-    //            .manual_alloc_for_lazy((buf as usize).into())
-    //            .is_err()
-    //    {
-    //        return Err(SyscallError::EFAULT);
-    //    }
-
-    //    let path = deal_with_path(dir_fd, Some(path), false);
-    //
-    //    if path.is_none() {
-    //        return Err(SyscallError::ENOENT);
-    //    }
-    //    let path = path.unwrap();
-    //    if path.path() == "proc/self/exe" {
-    //        // 针对lmbench_all特判
-    //        let name = "/lmbench_all";
-    //        let len = bufsiz.min(name.len());
-    //        let slice = unsafe { core::slice::from_raw_parts_mut(buf, bufsiz) };
-    //        slice.copy_from_slice(&name.as_bytes()[..len]);
-    //        return Ok(len as isize);
-    //    }
-    //    // 获取进程自身的符号链接信息
-    //    if path.path() == "/proc/self/exe" {
-    //        // 获取该进程符号链接对应的真正地址
-    //        let file_real_path = process.get_file_path();
-    //        let len = bufsiz.min(file_real_path.len());
-    //        let slice = unsafe { core::slice::from_raw_parts_mut(buf, len) };
-    //        slice.copy_from_slice(&file_real_path.as_bytes()[..len]);
-    //
-    //        return Ok(file_real_path.len() as isize);
-    //    }
-    //
-    //    if *path.path() != real_path(&(path.path().to_string())) {
-    //        // 说明链接存在
-    //        let path = path.path();
-    //        let len = bufsiz.min(path.len());
-    //        let slice = unsafe { core::slice::from_raw_parts_mut(buf, len) };
-    //        slice.copy_from_slice(&path.as_bytes()[..len]);
-    //        return Ok(path.len() as isize);
-    //    }
-    //    Err(SyscallError::EINVAL)
+/// # Return
+/// 成功时返回写入`buf`的字节数；`path`不是符号链接时返回`EINVAL`
+pub fn syscall_readlinkat(args: [usize; 6]) -> SyscallResult {
+    let dir_fd = args[0];
+    let path = args[1] as *const u8;
+    let buf = args[2] as *mut u8;
+    let bufsiz = args[3] as isize;
+    if bufsiz <= 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if buf.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let path = match deal_path(dir_fd, Some(path), false) {
+        Ok(path) => path,
+        Err(e) => match e {
+            UtilsError::CannotAcce | UtilsError::NULL => return Err(SyscallError::EFAULT),
+            UtilsError::StrTooLong => return Err(SyscallError::ENAMETOOLONG),
+            UtilsError::OutOfTable | UtilsError::NoEntryInTable => return Err(SyscallError::EBADF),
+            UtilsError::InvalidArg => return Err(SyscallError::ENOTDIR),
+            _ => return Err(SyscallError::ENOENT),
+        },
+    };
+    let target = match read_link(path.path()) {
+        Ok(target) => target,
+        Err(AxError::InvalidInput) => return Err(SyscallError::EINVAL),
+        Err(_) => return Err(SyscallError::ENOENT),
+    };
+    let len = target.len().min(bufsiz as usize);
+    let slice = unsafe { from_raw_parts_mut(buf, len) };
+    slice.copy_from_slice(&target.as_bytes()[..len]);
+    Ok(len as isize)
 }
 
 /// 68
@@ -611,7 +619,7 @@ pub fn syscall_pwrite64(args: [usize; 6]) -> SyscallResult {
     let count = args[2];
     let offset = args[3];
 
-    let file = match FDM.fd_table.lock().get(fd) {
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
         Some(Some(f)) => f.clone(),
         _ => return Err(SyscallError::EBADF),
     };
@@ -649,8 +657,10 @@ pub fn syscall_sendfile64(args: [usize; 6]) -> SyscallResult {
     if (out_fd as isize) < 0 || (in_fd as isize) < 0 {
         return Err(SyscallError::EBADF);
     }
-    let out_file = FDM.fd_table.lock()[out_fd].clone().unwrap();
-    let in_file = FDM.fd_table.lock()[in_fd].clone().unwrap();
+    let out_file = current_fd_manager().fd_table.lock()[out_fd]
+        .clone()
+        .unwrap();
+    let in_file = current_fd_manager().fd_table.lock()[in_fd].clone().unwrap();
     let old_in_offset = in_file.seek(SeekFrom::Current(0)).unwrap();
 
     let mut buf = vec![0u8; count];
@@ -679,11 +689,27 @@ pub fn syscall_sendfile64(args: [usize; 6]) -> SyscallResult {
     }
 }
 
+/// 46
+/// 把文件截断或扩展到`len`字节，扩展的部分读出为 0。不改变读写指针
 /// # Arguments
 /// * `fd: usize`
 /// * `len: usize`
-pub fn syscall_ftruncate64(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_ftruncate64(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let len = args[1];
+    if (len as isize) < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
+        Some(Some(f)) => f.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+    // 不是普通文件或没有以写方式打开
+    if file.get_type() != FileIOType::FileDesc || !file.writable() {
+        return Err(SyscallError::EINVAL);
+    }
+    file.truncate(len)?;
+    Ok(0)
 }
 
 /**
@@ -695,11 +721,120 @@ pub fn syscall_ftruncate64(_args: [usize; 6]) -> SyscallResult {
  */
 /// # Arguments
 /// * `fd_in: usize`
-/// * `off_in: *mut usize`
+/// * `off_in: *mut u64`
 /// * `fd_out: usize`
-/// * `off_out: *mut usize`
+/// * `off_out: *mut u64`
 /// * `len: usize`
 /// * `flags: usize`
-pub fn syscall_copyfilerange(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_copyfilerange(args: [usize; 6]) -> SyscallResult {
+    let fd_in = args[0];
+    let off_in = args[1] as *mut u64;
+    let fd_out = args[2];
+    let off_out = args[3] as *mut u64;
+    let len = args[4];
+    let flags = args[5];
+    if flags != 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let (file_in, file_out) = {
+        let fd_manager = current_fd_manager();
+        let fd_table = fd_manager.fd_table.lock();
+        match (fd_table.get(fd_in), fd_table.get(fd_out)) {
+            (Some(Some(file_in)), Some(Some(file_out))) => (file_in.clone(), file_out.clone()),
+            _ => return Err(SyscallError::EBADF),
+        }
+    };
+    if !file_in.readable()
+        || !file_out.writable()
+        || file_out.get_status().contains(OpenFlags::APPEND)
+    {
+        return Err(SyscallError::EBADF);
+    }
+    if file_in.get_type() != FileIOType::FileDesc || file_out.get_type() != FileIOType::FileDesc {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let in_pos = match off_in.is_null() {
+        true => file_in.seek(SeekFrom::Current(0))?,
+        false => unsafe { *off_in },
+    };
+    let out_pos = match off_out.is_null() {
+        true => file_out.seek(SeekFrom::Current(0))?,
+        false => unsafe { *off_out },
+    };
+    // 同一个文件中源和目标的范围不能重叠
+    if file_in.get_path() == file_out.get_path()
+        && in_pos < out_pos.saturating_add(len as u64)
+        && out_pos < in_pos.saturating_add(len as u64)
+    {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let mut buf = vec![0u8; len.min(COPY_CHUNK_SIZE)];
+    let mut copied = 0;
+    while copied < len {
+        let count = (len - copied).min(buf.len());
+        let read = file_in.read_at(in_pos + copied as u64, &mut buf[..count])?;
+        if read == 0 {
+            break;
+        }
+        let written = file_out.write_at(out_pos + copied as u64, &buf[..read])?;
+        copied += written;
+        if written < read {
+            break;
+        }
+    }
+
+    if off_in.is_null() {
+        file_in.seek(SeekFrom::Start(in_pos + copied as u64))?;
+    } else {
+        unsafe { *off_in = in_pos + copied as u64 };
+    }
+    if off_out.is_null() {
+        file_out.seek(SeekFrom::Start(out_pos + copied as u64))?;
+    } else {
+        unsafe { *off_out = out_pos + copied as u64 };
+    }
+    Ok(copied as isize)
+}
+
+/// `copy_file_range`每次读写的字节数
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// 82
+/// 把文件的数据和元数据写回存储设备
+/// # Arguments
+/// * `fd: usize`
+pub fn syscall_fsync(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
+        Some(Some(f)) => f.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+    match file.sync() {
+        Ok(()) => Ok(0),
+        // 管道、终端等不支持同步
+        Err(AxError::InvalidInput) => Err(SyscallError::EINVAL),
+        Err(e) => {
+            warn!("fsync error: {:?}", e);
+            Err(SyscallError::EIO)
+        }
+    }
+}
+
+/// 83
+/// 只要求写回数据和读取数据所需的元数据。元数据不单独缓存，同`fsync`
+/// # Arguments
+/// * `fd: usize`
+pub fn syscall_fdatasync(args: [usize; 6]) -> SyscallResult {
+    syscall_fsync(args)
+}
+
+/// 81
+/// 把所有文件系统缓存的数据写回存储设备，总是成功
+pub fn syscall_sync() -> SyscallResult {
+    if let Err(e) = axfs::api::sync() {
+        warn!("sync error: {:?}", e);
+    }
+    Ok(0)
 }
@@ -1,14 +1,13 @@
-use std::string::ToString;
-
 use crate::{
     linux_env::linux_fs::{
-        link::{FilePath, new_link, remove_link},
-        utils::{UtilsError, deal_path},
+        fd_manager::get_cwd,
+        link::{FilePath, raw_ptr_to_ref_str},
+        utils::{UtilsError, check_parent_writable, deal_path},
     },
     syscall::{SyscallError, SyscallResult, UnlinkatFlags},
 };
 use axerrno::AxError;
-use axfs::api::{current_dir, metadata, remove_dir};
+use axfs::api::{hard_link, metadata, read_link, remove_dir, remove_file, symlink};
 use axlog::{debug, warn};
 
 /// 功能:创建文件的链接；
@@ -20,7 +19,8 @@ use axlog::{debug, warn};
 /// * `flags: usize`, 在2.6.18内核之前,应置为0。其它的值详见`man 2 linkat`。
 /// # Return
 /// 成功执行,返回0。失败,返回-1。
-#[allow(dead_code)]
+///
+/// 链接由文件系统保存，不支持硬链接的文件系统(FAT)返回`EPERM`。
 pub fn sys_linkat(args: [usize; 6]) -> SyscallResult {
     let old_dir_fd = args[0];
     let old_path = args[1] as *const u8;
@@ -59,37 +59,57 @@ pub fn sys_linkat(args: [usize; 6]) -> SyscallResult {
         },
     };
 
-    match metadata(old_path.path()) {
-        Ok(_) => {}
-        Err(e) => match e {
-            AxError::NotFound => {
-                debug!(
-                    "ENOENT A directory component in oldpath or newpath does not exist or is a dangling symbolic link."
-                );
-                return Err(SyscallError::ENOENT);
-            }
-            _ => {
-                warn!("Unexpect errno catched {:?}", e);
-                return Err(SyscallError::EPERM);
-            }
-        },
+    check_parent_writable(new_path.path(), false)?;
+    match hard_link(old_path.path(), new_path.path()) {
+        Ok(()) => Ok(0),
+        Err(AxError::AlreadyExists) => Err(SyscallError::EEXIST),
+        Err(AxError::NotFound) => Err(SyscallError::ENOENT),
+        Err(AxError::CrossesDevices) => Err(SyscallError::EXDEV),
+        Err(e) => {
+            debug!("link error: {:?}", e);
+            Err(SyscallError::EPERM)
+        }
     }
+}
 
-    match metadata(new_path.path()) {
-        Ok(_) => return Err(SyscallError::EEXIST),
+/// 功能:创建符号链接；
+/// # Arguments
+/// * `target: *const u8`, 链接指向的路径，原样保存，不要求存在。
+/// * `new_dir_fd: usize`, 链接所在的目录。
+/// * `link_path: *const u8`, 链接的名字。规则同`linkat`的`new_path`。
+/// # Return
+/// 成功执行,返回0。失败,返回-1。
+pub fn syscall_symlinkat(args: [usize; 6]) -> SyscallResult {
+    let target = args[0] as *const u8;
+    let new_dir_fd = args[1];
+    let link_path = args[2] as *const u8;
+    if target.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let target = unsafe { raw_ptr_to_ref_str(target) };
+    if target.is_empty() {
+        return Err(SyscallError::ENOENT);
+    }
+    let link_path = match deal_path(new_dir_fd, Some(link_path), false) {
+        Ok(path) => path,
         Err(e) => match e {
-            AxError::NotFound => {
-                if new_link(&new_path, &old_path) {
-                    Ok(0)
-                } else {
-                    Err(SyscallError::EINVAL)
-                }
-            }
-            _ => {
-                warn!("Unexpect errno catched {:?}", e);
-                return Err(SyscallError::EPERM);
-            }
+            UtilsError::NULL | UtilsError::CannotAcce => return Err(SyscallError::EFAULT),
+            UtilsError::StrTooLong => return Err(SyscallError::ENAMETOOLONG),
+            UtilsError::InvalidArg => return Err(SyscallError::ENOTDIR),
+            UtilsError::OutOfTable | UtilsError::NoEntryInTable => return Err(SyscallError::EBADF),
+            _ => return Err(SyscallError::ENOENT),
         },
+    };
+    check_parent_writable(link_path.path(), false)?;
+    match symlink(target, link_path.path()) {
+        Ok(()) => Ok(0),
+        Err(AxError::AlreadyExists) => Err(SyscallError::EEXIST),
+        Err(AxError::InvalidInput) => Err(SyscallError::ENAMETOOLONG),
+        Err(AxError::Unsupported) => Err(SyscallError::EPERM),
+        Err(e) => {
+            warn!("symlink error: {:?}", e);
+            Err(SyscallError::ENOENT)
+        }
     }
 }
 
@@ -131,6 +151,18 @@ pub fn syscall_unlinkat(args: [usize; 6]) -> SyscallResult {
     if path.start_with(&FilePath::new("/proc").unwrap()) {
         return Ok(-1);
     }
+    check_parent_writable(path.path(), true)?;
+
+    // 删除的是符号链接本身，不跟随它
+    if flags.is_empty() && read_link(path.path()).is_ok() {
+        return match remove_file(path.path()) {
+            Ok(()) => Ok(0),
+            Err(e) => {
+                warn!("unlink symlink error: {:?}", e);
+                Err(SyscallError::EPERM)
+            }
+        };
+    }
 
     let metadata = match metadata(path.path()) {
         Ok(t) => t,
@@ -146,8 +178,8 @@ pub fn syscall_unlinkat(args: [usize; 6]) -> SyscallResult {
         },
     };
 
-    let cwd = current_dir().unwrap();
-    if cwd == path.path().to_string() {
+    let cwd = get_cwd();
+    if cwd.trim_end_matches('/') == path.path().trim_end_matches('/') {
         return Err(SyscallError::EPERM);
     }
 
@@ -159,9 +191,9 @@ pub fn syscall_unlinkat(args: [usize; 6]) -> SyscallResult {
             );
             return Err(SyscallError::EISDIR);
         }
-        if remove_link(&path).is_none() {
-            warn!("unlink file error");
-            return Err(SyscallError::EINVAL);
+        if let Err(e) = remove_file(path.path()) {
+            warn!("unlink file error: {:?}", e);
+            return Err(SyscallError::EPERM);
         }
     }
     // Remove `dir`
@@ -1,23 +1,141 @@
-use crate::syscall::SyscallResult;
+//! 挂载和卸载文件系统。挂载表由 axfs 维护，可以从`/proc/mounts`读取
+//!
+//! 支持的文件系统类型：`tmpfs`、`devtmpfs`、`proc`、`sysfs`，以及块设备（如
+//! `/dev/vdb`）上的`vfat`；axfs 以`ext4_rs`构建时还可以挂载块设备上的`ext4`。
+
+use alloc::string::String;
+use axerrno::AxError;
+use axfs::api::mount_point;
+use axlog::{debug, warn};
+
+use crate::{
+    linux_env::{
+        linux_fs::{
+            link::{AT_FDCWD, raw_ptr_to_ref_str},
+            utils::{UtilsError, deal_path},
+        },
+        process_ext::{api::current_process, process::PID2PC},
+    },
+    syscall::{SyscallError, SyscallResult},
+};
+
+/// 重新挂载，只改变挂载参数
+const MS_REMOUNT: usize = 0x20;
+const MS_BIND: usize = 0x1000;
+const MS_MOVE: usize = 0x2000;
+
+const MNT_FORCE: usize = 1;
+const MNT_DETACH: usize = 2;
+const MNT_EXPIRE: usize = 4;
+const UMOUNT_NOFOLLOW: usize = 8;
+
+/// 挂载点的绝对路径，不以`/`结尾
+fn mount_path(path: *const u8) -> Result<String, SyscallError> {
+    match deal_path(AT_FDCWD, Some(path), false) {
+        Ok(path) => {
+            let path = path.path().trim_end_matches('/');
+            Ok(if path.is_empty() { "/" } else { path }.into())
+        }
+        Err(UtilsError::StrEmpty) => Err(SyscallError::ENOENT),
+        Err(UtilsError::StrTooLong) => Err(SyscallError::ENAMETOOLONG),
+        Err(_) => Err(SyscallError::EFAULT),
+    }
+}
+
+/// `path`是否在挂载于`mount_point`的文件系统中
+fn in_mount(path: &str, mount_point: &str) -> bool {
+    path.strip_prefix(mount_point)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// 是否有进程的工作目录或打开的文件在`mount_point`中
+fn mount_busy(mount_point: &str) -> bool {
+    PID2PC.lock().values().any(|process| {
+        in_mount(&process.fs.cwd(), mount_point)
+            || process
+                .fd_manager()
+                .fd_table
+                .lock()
+                .iter()
+                .flatten()
+                .any(|file| in_mount(&file.get_path(), mount_point))
+    })
+}
 
 /// 功能:挂载文件系统；
 /// # Arguments
-/// * `special: *const u8`, 挂载设备
-/// * `dir: *const u8`, 挂载点
+/// * `special: *const u8`, 挂载设备，虚拟文件系统可为NULL
+/// * `dir: *const u8`, 挂载点，必须是已经存在的目录
 /// * `fs_type: *const u8`, 挂载的文件系统类型
-/// * `flags: usize`, 挂载参数
-/// * `data: *const u8`, 传递给文件系统的字符串参数,可为NULL
+/// * `flags: usize`, 挂载参数。不支持`MS_BIND`和`MS_MOVE`，`MS_REMOUNT`不做处理，其余忽略
+/// * `data: *const u8`, 传递给文件系统的字符串参数,可为NULL，忽略
 /// 返回值:成功返回0,失败返回-1
-pub fn syscall_mount(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_mount(args: [usize; 6]) -> SyscallResult {
+    let special = args[0] as *const u8;
+    let dir = args[1] as *const u8;
+    let fs_type = args[2] as *const u8;
+    let flags = args[3];
+    if !current_process().cred().is_root() {
+        return Err(SyscallError::EPERM);
+    }
+    let target = mount_path(dir)?;
+    if flags & MS_REMOUNT != 0 {
+        return match mount_point(&target) {
+            Some(_) => Ok(0),
+            None => Err(SyscallError::EINVAL),
+        };
+    }
+    if flags & (MS_BIND | MS_MOVE) != 0 {
+        warn!("mount: unsupported flags {:#x}", flags);
+        return Err(SyscallError::EINVAL);
+    }
+    if fs_type.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let fs_type = unsafe { raw_ptr_to_ref_str(fs_type) };
+    let source = match special.is_null() {
+        true => fs_type,
+        false => unsafe { raw_ptr_to_ref_str(special) },
+    };
+    debug!("mount {} on {} type {}", source, target, fs_type);
+    axfs::api::mount(source, &target, fs_type).map_err(|e| match e {
+        AxError::Unsupported => SyscallError::ENODEV,
+        AxError::ResourceBusy => SyscallError::EBUSY,
+        AxError::InvalidData => SyscallError::EINVAL,
+        e => e.into(),
+    })?;
+    Ok(0)
 }
 
 /// 功能:卸载文件系统；
 /// 输入:指定卸载目录,卸载参数；
 /// 返回值:成功返回0,失败返回-1
+///
+/// 有进程的工作目录或打开的文件在其中，或者其中还挂载了其他文件系统时返回`EBUSY`。
+/// 不支持延迟卸载，`MNT_FORCE`和`MNT_DETACH`也会检查。
 /// # Arguments
 /// * `dir: *const u8`, 指定卸载目录
 /// * `flags: usize`, 卸载参数
-pub fn syscall_umount(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_umount(args: [usize; 6]) -> SyscallResult {
+    let dir = args[0] as *const u8;
+    let flags = args[1];
+    if flags & !(MNT_FORCE | MNT_DETACH | MNT_EXPIRE | UMOUNT_NOFOLLOW) != 0
+        || (flags & MNT_EXPIRE != 0 && flags & (MNT_FORCE | MNT_DETACH) != 0)
+    {
+        return Err(SyscallError::EINVAL);
+    }
+    if !current_process().cred().is_root() {
+        return Err(SyscallError::EPERM);
+    }
+    let target = mount_path(dir)?;
+    if mount_point(&target).is_none() || target == "/" {
+        return Err(SyscallError::EINVAL);
+    }
+    debug!("umount {}", target);
+    axfs::api::umount(&target, mount_busy).map_err(|e| match e {
+        AxError::ResourceBusy => SyscallError::EBUSY,
+        AxError::InvalidInput => SyscallError::EINVAL,
+        e => e.into(),
+    })?;
+    Ok(0)
 }
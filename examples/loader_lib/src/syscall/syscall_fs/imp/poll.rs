@@ -1,18 +1,67 @@
-use axhal::{mem::VirtAddr, time::current_ticks};
+use core::{slice::from_raw_parts_mut, time::Duration};
+
+use axhal::time::{TimeValue, monotonic_time};
 use axtask::yield_now;
 use bitflags::bitflags;
 
 use crate::{
-    linux_env::{axfs_ext::api::FileIO, linux_fs::fd_manager::FDM},
+    linux_env::{
+        axfs_ext::api::FileIO,
+        linux_fs::{
+            fd_manager::current_fd_manager,
+            poll_queue::{PollWait, wait_ready},
+        },
+        signal_ext::api::set_temporary_mask,
+    },
     syscall::{SyscallError, SyscallResult, TimeSecs},
 };
 
-use alloc::{
-    string::{String, ToString},
-    sync::Arc,
-    vec,
-    vec::Vec,
-};
+use alloc::{sync::Arc, vec::Vec};
+
+/// 内核中信号集的大小
+const SIGSET_SIZE_IN_BYTE: usize = 8;
+
+/// 把相对的超时时间转为`monotonic_time`下的截止时间，空指针表示一直等待
+fn deadline_of(timeout: *const TimeSecs) -> Option<TimeValue> {
+    if timeout.is_null() {
+        return None;
+    }
+    let timeout = unsafe { &*timeout };
+    Some(monotonic_time() + Duration::new(timeout.tv_sec as u64, timeout.tv_nsec as u32))
+}
+
+/// 检查一次所有的`PollFd`，填写`revents`，返回有事件的个数
+///
+/// `fd`为负数的项被忽略，已经关闭的`fd`报告`NVAL`。
+fn poll_once(fds: &mut [PollFd], files: &[Option<Arc<dyn FileIO>>]) -> usize {
+    let mut ready = 0;
+    for (poll_fd, file) in fds.iter_mut().zip(files) {
+        let mut revents = PollEvents::empty();
+        match file {
+            _ if poll_fd.fd < 0 => {}
+            None => revents |= PollEvents::NVAL,
+            Some(file) => {
+                if file.is_hang_up() {
+                    revents |= PollEvents::HUP;
+                }
+                if file.in_exceptional_conditions() {
+                    revents |= PollEvents::ERR;
+                }
+                if poll_fd.events.contains(PollEvents::IN) && file.ready_to_read() {
+                    revents |= PollEvents::IN;
+                }
+                if poll_fd.events.contains(PollEvents::OUT) && file.ready_to_write() {
+                    revents |= PollEvents::OUT;
+                }
+            }
+        }
+        poll_fd.revents = revents;
+        if !revents.is_empty() {
+            ready += 1;
+        }
+    }
+    ready
+}
 
 /// 实现`ppoll`系统调用
 ///
@@ -22,9 +71,50 @@ use alloc::{
 /// * `ufds - *mut PollFd`
 /// * `nfds - usize`
 /// * `timeout - *const TimeSecs`
-/// * `mask - usize`
-pub fn syscall_ppoll(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+/// * `mask - *const u64`
+/// * `sigsetsize - usize`
+pub fn syscall_ppoll(args: [usize; 6]) -> SyscallResult {
+    let ufds = args[0] as *mut PollFd;
+    let nfds = args[1];
+    let timeout = args[2] as *const TimeSecs;
+    let mask = args[3] as *const u64;
+    let sigsetsize = args[4];
+    if nfds > current_fd_manager().get_limit() as usize {
+        return Err(SyscallError::EINVAL);
+    }
+    if ufds.is_null() && nfds > 0 {
+        return Err(SyscallError::EFAULT);
+    }
+    let fds: &mut [PollFd] = if nfds == 0 {
+        &mut []
+    } else {
+        unsafe { from_raw_parts_mut(ufds, nfds) }
+    };
+    // 等待期间不能持有文件描述符表的锁
+    let files: Vec<Option<Arc<dyn FileIO>>> = {
+        let fd_manager = current_fd_manager();
+        let fd_table = fd_manager.fd_table.lock();
+        fds.iter()
+            .map(|poll_fd| fd_table.get(poll_fd.fd as usize).cloned().flatten())
+            .collect()
+    };
+    axlog::debug!("[ppoll()] fds: {:?}", fds);
+    if !mask.is_null() {
+        if sigsetsize != SIGSET_SIZE_IN_BYTE {
+            return Err(SyscallError::EINVAL);
+        }
+        set_temporary_mask(unsafe { *mask });
+    }
+
+    let sources: Vec<&dyn FileIO> = files.iter().flatten().map(|file| file.as_ref()).collect();
+    let mut ready = 0;
+    match wait_ready(&sources, deadline_of(timeout), || {
+        ready = poll_once(fds, &files);
+        ready > 0
+    }) {
+        PollWait::Interrupted => Err(SyscallError::EINTR),
+        PollWait::Ready | PollWait::Timeout => Ok(ready as isize),
+    }
 }
 
 /// 实现`pselect6`系统调用
@@ -34,14 +124,14 @@ pub fn syscall_ppoll(_args: [usize; 6]) -> SyscallResult {
 /// * `writefds - *mut usize`
 /// * `exceptfds - *mut usize`
 /// * `timeout - *const TimeSecs`
-/// * `mask - usize`
+/// * `mask - *const [usize; 2]`, 信号集的地址和大小
 pub fn syscall_pselect6(args: [usize; 6]) -> SyscallResult {
     let nfds = args[0];
     let readfds = args[1] as *mut usize;
     let writefds = args[2] as *mut usize;
     let exceptfds = args[3] as *mut usize;
     let timeout = args[4] as *const TimeSecs;
-    let _mask = args[5];
+    let mask = args[5] as *const [usize; 2];
     let (rfiles, rfds, mut rset) = match init_fd_set(readfds, nfds) {
         Ok(ans) => (ans.files, ans.fds, ans.shadow_bitset),
         Err(e) => return Err(e),
@@ -54,42 +144,39 @@ pub fn syscall_pselect6(args: [usize; 6]) -> SyscallResult {
         Ok(ans) => (ans.files, ans.fds, ans.shadow_bitset),
         Err(e) => return Err(e),
     };
-    //    let process = current_process();
-
-    let expire_time = if !timeout.is_null() {
-        // FIX:
-        //        if process
-        //            .memory_set
-        //            .lock()
-        //            .lock()
-        //            .manual_alloc_type_for_lazy(timeout)
-        //            .is_err()
-        //        {
-        //            axlog::error!("[pselect6()] timeout addr {timeout:?} invalid");
-        //            return Err(SyscallError::EFAULT);
-        //        }
-        current_ticks() as usize + unsafe { (*timeout).get_ticks() }
-    } else {
-        usize::MAX
-    };
+    if !mask.is_null() {
+        let [sigset, sigsetsize] = unsafe { *mask };
+        if sigset != 0 {
+            if sigsetsize != SIGSET_SIZE_IN_BYTE {
+                return Err(SyscallError::EINVAL);
+            }
+            set_temporary_mask(unsafe { *(sigset as *const u64) });
+        }
+    }
 
     axlog::debug!("[pselect6()]: r: {rfds:?}, w: {wfds:?}, e: {efds:?}");
 
-    loop {
-        // Why yield first?
-        //
-        // 当用户程序中出现如下结构：
-        // while (true) { select(); }
-        // 如果存在 ready 的 fd,select() 立即返回,
-        // 但并不完全满足用户程序的要求,可能出现死循环。
-        //
-        // 因此先 yield 避免其他进程 starvation。
-        //
-        // 可见 iperf 测例。
-        yield_now();
-        //        yield_now_task();
+    // Why yield first?
+    //
+    // 当用户程序中出现如下结构：
+    // while (true) { select(); }
+    // 如果存在 ready 的 fd,select() 立即返回,
+    // 但并不完全满足用户程序的要求,可能出现死循环。
+    //
+    // 因此先 yield 避免其他进程 starvation。
+    //
+    // 可见 iperf 测例。
+    yield_now();
 
-        let mut set = 0;
+    let sources: Vec<&dyn FileIO> = rfiles
+        .iter()
+        .chain(&wfiles)
+        .chain(&efiles)
+        .map(|file| file.as_ref())
+        .collect();
+    let mut set = 0;
+    match wait_ready(&sources, deadline_of(timeout), || {
+        set = 0;
         if rset.valid() {
             for i in 0..rfds.len() {
                 if rfiles[i].ready_to_read() {
@@ -114,28 +201,18 @@ pub fn syscall_pselect6(args: [usize; 6]) -> SyscallResult {
                 }
             }
         }
-        if set > 0 {
-            return Ok(set as isize);
-        }
-        if current_ticks() as usize > expire_time {
-            return Ok(0);
-        }
-        // TODO: fix this and use mask to ignore specific signal
-        #[cfg(feature = "signal")]
-        if let Some(signalno) = process.have_signals() {
-            if signalno == SignalNo::SIGKILL as usize {
-                return Err(SyscallError::EINTR);
-            }
-        }
+        set > 0
+    }) {
+        PollWait::Interrupted => Err(SyscallError::EINTR),
+        PollWait::Ready | PollWait::Timeout => Ok(set as isize),
     }
 }
 
 /// 根据给定的地址和长度新建一个fd set,包括文件描述符指针数组,文件描述符数值数组,以及一个bitset
 fn init_fd_set(addr: *mut usize, len: usize) -> Result<PpollFdSet, SyscallError> {
-    //    let process = current_process();
-    //   if len >= process.fd_manager.get_limit() as usize {
-    if len >= FDM.get_limit() as usize {
-        axlog::error!("[pselect6()] len {len} >= limit {}", FDM.get_limit());
+    let limit = current_fd_manager().get_limit() as usize;
+    if len >= limit {
+        axlog::error!("[pselect6()] len {len} >= limit {limit}");
         return Err(SyscallError::EINVAL);
     }
 
@@ -159,7 +236,8 @@ fn init_fd_set(addr: *mut usize, len: usize) -> Result<PpollFdSet, SyscallError>
     let mut files = Vec::new();
     for fd in 0..len {
         if shadow_bitset.check(fd) {
-            let fd_table = FDM.fd_table.lock();
+            let fd_manager = current_fd_manager();
+            let fd_table = fd_manager.fd_table.lock();
             if let Some(file) = fd_table[fd].as_ref() {
                 files.push(Arc::clone(file));
                 fds.push(fd);
@@ -4,11 +4,12 @@ use crate::{
     linux_env::{
         axfs_ext::api::{FileIOType, Kstat},
         linux_fs::{
-            fd_manager::{self, FDM},
-            link::{deal_with_path, raw_ptr_to_ref_str},
+            fd_manager::{self, current_fd_manager},
+            link::{AT_FDCWD, deal_with_path, raw_ptr_to_ref_str},
+            utils::deal_path,
         },
     },
-    syscall::{SyscallError, SyscallResult, syscall_fs::ctype::mount::get_stat_in_fs},
+    syscall::{FsStat, SyscallError, SyscallResult, syscall_fs::ctype::mount::get_stat_in_fs},
 };
 
 // FIX: 未测试
@@ -19,9 +20,8 @@ use crate::{
 pub fn syscall_fstat(args: [usize; 6]) -> SyscallResult {
     let fd = args[0];
     let kst = args[1] as *mut Kstat;
-    //    let process = current_process();
-    //    let fd_table = process.fd_manager.fd_table.lock();
-    let fd_table = FDM.fd_table.lock();
+    let fd_manager = current_fd_manager();
+    let fd_table = fd_manager.fd_table.lock();
 
     if fd >= fd_table.len() || fd < 3 {
         debug!("fd {} is out of range", fd);
@@ -51,16 +51,21 @@ pub fn syscall_fstat(args: [usize; 6]) -> SyscallResult {
     }
 }
 
+/// `fstatat`的`flags`：最后一项是符号链接时返回链接本身的信息
+const AT_SYMLINK_NOFOLLOW: usize = 0x100;
+
 // FIX: 未测试
 /// 获取文件状态信息，但是给出的是目录`fd`和相对路径。
 /// # Arguments
 /// * `dir_fd - usize`
 /// * `path - *const u8`
 /// * `kst - *mut Kstat`
+/// * `flags - usize`
 pub fn syscall_fstatat(args: [usize; 6]) -> SyscallResult {
     let dir_fd = args[0];
     let path = args[1] as *const u8;
     let kst = args[2] as *mut Kstat;
+    let flags = args[3];
     let file_path = if let Some(file_path) = deal_with_path(dir_fd, Some(path), false) {
         // error!("test {:?}", file_path);
         file_path
@@ -78,6 +83,11 @@ pub fn syscall_fstatat(args: [usize; 6]) -> SyscallResult {
         }
         panic!("Wrong path at syscall_fstatat: {}(dir_fd={})", path, dir_fd);
     };
+    let file_path = if flags & AT_SYMLINK_NOFOLLOW != 0 {
+        file_path
+    } else {
+        file_path.follow_link()?
+    };
     info!("path : {}", file_path.path());
 
     // TODO: 快速开发，跳过了这个检查，请马上添加
@@ -100,6 +110,29 @@ pub fn syscall_fstatat(args: [usize; 6]) -> SyscallResult {
 /// # Arguments
 /// * `path - *const u8`
 /// * `stat - *mut FsStat`
-pub fn syscall_statfs(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_statfs(args: [usize; 6]) -> SyscallResult {
+    let path = args[0] as *const u8;
+    let stat = args[1] as *mut FsStat;
+    if stat.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let file_path = deal_path(AT_FDCWD, Some(path), false)
+        .map_err(|_| SyscallError::ENOENT)?
+        .follow_link()?;
+    let fs_stat = axfs::api::statfs(file_path.path()).map_err(|_| SyscallError::ENOENT)?;
+    unsafe {
+        *stat = FsStat {
+            f_type: fs_stat.fs_type as i64,
+            f_bsize: fs_stat.block_size as i64,
+            f_blocks: fs_stat.blocks,
+            f_bfree: fs_stat.blocks_free,
+            f_bavail: fs_stat.blocks_free,
+            f_files: fs_stat.files,
+            f_ffree: fs_stat.files_free,
+            f_namelen: fs_stat.name_max as i64,
+            f_frsize: fs_stat.block_size as i64,
+            ..FsStat::default()
+        };
+    }
+    Ok(0)
 }
@@ -40,19 +40,15 @@ pub fn fs_syscall(syscall_id: fs_syscall_id::FsSyscallId, args: [usize; 6]) -> S
         PREADLINKAT => syscall_readlinkat(args),
         PWRITE64 => syscall_pwrite64(args),
         SENDFILE64 => syscall_sendfile64(args),
-        FSYNC => Ok(0),
-        FTRUNCATE64 => {
-            syscall_ftruncate64(args)
-            // 0
-        }
+        FSYNC => syscall_fsync(args),
+        FTRUNCATE64 => syscall_ftruncate64(args),
         IOCTL => syscall_ioctl(args),
-        // 不做处理即可
-        SYNC => Ok(0),
-        FDATASYNC => unimplemented!(),
+        SYNC => syscall_sync(),
+        FDATASYNC => syscall_fdatasync(args),
         COPYFILERANGE => syscall_copyfilerange(args),
         LINKAT => sys_linkat(args),
         UNLINKAT => syscall_unlinkat(args),
-        SYMLINKAT => Ok(0),
+        SYMLINKAT => syscall_symlinkat(args),
         UTIMENSAT => syscall_utimensat(args),
         EPOLL_CREATE => syscall_epoll_create1(args),
         EPOLL_CTL => syscall_epoll_ctl(args),
@@ -1,15 +1,35 @@
-// use crate::{MMAPFlags, MMAPPROT, MREMAPFlags, SyscallError, SyscallResult, syscall_fs::FileDesc};
-// extern crate alloc;
-//
-// use axhal::{arch::flush_tlb, mem::VirtAddr, paging::MappingFlags};
-// use axmem::MemorySet;
-//
-// use axprocess::current_process;
-
-use crate::syscall::{SyscallError, SyscallResult};
+use crate::{
+    config::{HEAP_START, MAX_HEAP_SIZE, MMAP_END, MMAP_START},
+    linux_env::{
+        linux_fs::fd_manager::current_fd_manager, linux_mm::memory_set::FileBackend,
+        process_ext::api::current_process,
+    },
+    syscall::{
+        MMAPFlags, MMAPPROT, MREMAPFlags, SyscallError, SyscallResult, unimplemented_syscall,
+    },
+};
+use axhal::{
+    mem::{MemoryAddr, VirtAddr},
+    paging::MappingFlags,
+};
+use axlog::debug;
 use bitflags::bitflags;
 
-const MAX_HEAP_SIZE: usize = 0x20000;
+/// `mmap`窗口
+const MMAP_LIMIT: (VirtAddr, VirtAddr) = (
+    VirtAddr::from_usize(MMAP_START),
+    VirtAddr::from_usize(MMAP_END),
+);
+
+/// 检查地址是否按页对齐，并把长度向上取整到页
+fn page_range(start: usize, len: usize) -> Result<(VirtAddr, usize), SyscallError> {
+    let start = VirtAddr::from(start);
+    if !start.is_aligned_4k() || len == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    Ok((start, VirtAddr::from(len).align_up_4k().as_usize()))
+}
+
 /// 修改用户堆大小，
 ///
 /// - 如输入`brk`为`0`，则返回堆顶地址
@@ -17,17 +37,13 @@ const MAX_HEAP_SIZE: usize = 0x20000;
 ///
 /// # Arguments
 /// * `brk - usize`
-pub fn syscall_brk(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let brk = args[0];
-    //    let curr_process = current_process();
-    //    let mut return_val: isize = curr_process.get_heap_top() as isize;
-    //    let heap_bottom = curr_process.get_heap_bottom() as usize;
-    //    if brk != 0 && brk >= heap_bottom && brk <= heap_bottom + MAX_HEAP_SIZE {
-    //        curr_process.set_heap_top(brk as u64);
-    //        return_val = brk as isize;
-    //    }
-    //    Ok(return_val)
+pub fn syscall_brk(args: [usize; 6]) -> SyscallResult {
+    let brk = args[0];
+    let heap_top = current_process()
+        .memory_set
+        .lock()
+        .set_brk(brk, HEAP_START, MAX_HEAP_SIZE);
+    Ok(heap_top as isize)
 }
 
 /// 将文件内容映射到内存中
@@ -42,114 +58,97 @@ pub fn syscall_brk(_args: [usize; 6]) -> SyscallResult {
 /// * `flags - MMAPFlags`
 /// * `fd - i32`
 /// * `offset - usize`
-pub fn syscall_mmap(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let start = args[0];
-    //    let len = args[1];
-    //    let prot = MMAPPROT::from_bits_truncate(args[2] as u32);
-    //    let flags = MMAPFlags::from_bits_truncate(args[3] as u32);
-    //    let fd = args[4] as i32;
-    //    let offset = args[5];
-    //    use axlog::debug;
-    //    use axmem::MemBackend;
-    //
-    //    let fixed = flags.contains(MMAPFlags::MAP_FIXED);
-    //    // try to map to NULL
-    //    if fixed && start == 0 {
-    //        return Err(SyscallError::EINVAL);
-    //    }
-    //
-    //    let process = current_process();
-    //
-    //    let addr = if flags.contains(MMAPFlags::MAP_ANONYMOUS) {
-    //        // no file
-    //        if !(fd == -1 && offset == 0) {
-    //            return Err(SyscallError::EINVAL);
-    //        }
-    //        process
-    //            .memory_set
-    //            .lock()
-    //            .lock()
-    //            .mmap(start.into(), len, prot.into(), fixed, None)
-    //    } else {
-    //        // file backend
-    //        debug!("[mmap] fd: {}, offset: 0x{:x}", fd, offset);
-    //        if fd >= process.fd_manager.fd_table.lock().len() as i32 || fd < 0 {
-    //            return Err(SyscallError::EINVAL);
-    //        }
-    //        let file = match &process.fd_manager.fd_table.lock()[fd as usize] {
-    //            // 文件描述符表里面存的是文件描述符，这很合理罢
-    //            Some(file) => alloc::boxed::Box::new(
-    //                file.as_any()
-    //                    .downcast_ref::<FileDesc>()
-    //                    .expect("Try to mmap with a non-file backend")
-    //                    .file
-    //                    .lock()
-    //                    .clone(),
-    //            ),
-    //            // fd not found
-    //            None => return Err(SyscallError::EINVAL),
-    //        };
-    //
-    //        let backend = MemBackend::new(file, offset as u64);
-    //        process
-    //            .memory_set
-    //            .lock()
-    //            .lock()
-    //            .mmap(start.into(), len, prot.into(), fixed, Some(backend))
-    //    };
-    //
-    //    flush_tlb(None);
-    //    debug!("mmap: 0x{:x}", addr);
-    //    // info!("val: {}", unsafe { *(addr as *const usize) });
-    //    Ok(addr)
+pub fn syscall_mmap(args: [usize; 6]) -> SyscallResult {
+    let start = args[0];
+    let len = args[1];
+    let prot = MMAPPROT::from_bits_truncate(args[2] as u32);
+    let flags = MMAPFlags::from_bits_truncate(args[3] as u32);
+    let fd = args[4] as i32;
+    let offset = args[5];
+    debug!(
+        "[mmap] start: 0x{:x}, len: 0x{:x}, prot: {:?}, flags: {:?}, fd: {}, offset: 0x{:x}",
+        start, len, prot, flags, fd, offset
+    );
+
+    let shared = flags.contains(MMAPFlags::MAP_SHARED);
+    if shared == flags.contains(MMAPFlags::MAP_PRIVATE) || len == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if offset % axhal::mem::PAGE_SIZE_4K != 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let size = VirtAddr::from(len).align_up_4k().as_usize();
+    let fixed = flags.intersects(MMAPFlags::MAP_FIXED | MMAPFlags::MAP_FIXED_NOREPLACE);
+    // try to map to NULL
+    if fixed && start == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+
+    let backend = if flags.contains(MMAPFlags::MAP_ANONYMOUS) {
+        None
+    } else {
+        let file = match current_fd_manager().fd_table.lock().get(fd as usize) {
+            Some(Some(file)) if fd >= 0 => file.clone(),
+            _ => return Err(SyscallError::EBADF),
+        };
+        if !file.readable() || (shared && prot.contains(MMAPPROT::PROT_WRITE) && !file.writable()) {
+            return Err(SyscallError::EACCES);
+        }
+        Some(FileBackend { file, offset })
+    };
+
+    let process = current_process();
+    let mut memory_set = process.memory_set.lock();
+    let addr = if fixed {
+        let (addr, _) = page_range(start, len)?;
+        if flags.contains(MMAPFlags::MAP_FIXED_NOREPLACE) {
+            if memory_set.find_free_area(addr, size, (addr, addr + size)) != Some(addr) {
+                return Err(SyscallError::EEXIST);
+            }
+        } else {
+            memory_set.unmap(addr, size)?;
+        }
+        addr
+    } else {
+        memory_set
+            .find_free_area(VirtAddr::from(start).align_down_4k(), size, MMAP_LIMIT)
+            .ok_or(SyscallError::ENOMEM)?
+    };
+    memory_set.map(addr, size, prot.into(), shared, backend)?;
+    debug!("mmap: 0x{:x}", addr.as_usize());
+    Ok(addr.as_usize() as isize)
 }
 
 /// # Arguments
 /// * `start - usize`
 /// * `len - usize`
-pub fn syscall_munmap(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let start = args[0];
-    //    let len = args[1];
-    //    let process = current_process();
-    //    process.memory_set.lock().lock().munmap(start.into(), len);
-    //    flush_tlb(None);
-    //    Ok(0)
+pub fn syscall_munmap(args: [usize; 6]) -> SyscallResult {
+    let (start, size) = page_range(args[0], args[1])?;
+    current_process().memory_set.lock().unmap(start, size)?;
+    Ok(0)
 }
 
 /// # Arguments
 /// * `start - usize`
 /// * `len - usize`
-pub fn syscall_msync(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let start = args[0];
-    //    let len = args[1];
-    //    let process = current_process();
-    //    process.memory_set.lock().lock().msync(start.into(), len);
-    //
-    //    Ok(0)
+pub fn syscall_msync(args: [usize; 6]) -> SyscallResult {
+    let (start, size) = page_range(args[0], args[1])?;
+    current_process().memory_set.lock().sync(start, size)?;
+    Ok(0)
 }
 
 /// # Arguments
 /// * `start - usize`
 /// * `len - usize`
 /// * `prot - MMAPPROT`
-pub fn syscall_mprotect(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let start = args[0];
-    //    let len = args[1];
-    //    let prot = MMAPPROT::from_bits_truncate(args[2] as u32);
-    //    let process = current_process();
-    //
-    //    process
-    //        .memory_set
-    //        .lock()
-    //        .lock()
-    //        .mprotect(VirtAddr::from(start), len, prot.into());
-    //
-    //    flush_tlb(None);
-    //    Ok(0)
+pub fn syscall_mprotect(args: [usize; 6]) -> SyscallResult {
+    let (start, size) = page_range(args[0], args[1])?;
+    let prot = MMAPPROT::from_bits_truncate(args[2] as u32);
+    current_process()
+        .memory_set
+        .lock()
+        .protect(start, size, MappingFlags::from(prot))?;
+    Ok(0)
 }
 
 /// # Arguments
@@ -158,84 +157,25 @@ pub fn syscall_mprotect(_args: [usize; 6]) -> SyscallResult {
 /// * `new_size - usize`
 /// * `flags - usize`
 /// * `new_addr - usize`
-pub fn syscall_mremap(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    use axlog::info;
-    //
-    //    let old_addr = args[0];
-    //    let old_size = args[1];
-    //    let new_size = args[2];
-    //    let flags = args[3];
-    //    let new_addr = args[4];
-    //
-    //    info!(
-    //        "[mremap] old_addr: 0x{:x}, old_size: 0x{:x}, new_size: 0x{:x}, flags: {}, new_addr: {}",
-    //        old_addr, old_size, new_size, flags, new_addr,
-    //    );
-    //
-    //    // old_addr must be aligned
-    //    // new_size must be greater than 0
-    //    if !(VirtAddr::from(old_addr).is_aligned_4k()) || new_size == 0 {
-    //        return Err(SyscallError::EINVAL);
-    //    };
-    //
-    //    // (new_addr, new_addr + size) must not overlap with (old_addr, old_addr + old_size)
-    //    if !(new_addr + new_size <= old_addr || new_addr >= old_addr + old_size) {
-    //        return Err(SyscallError::EINVAL);
-    //    };
-    //
-    //    let flags = MREMAPFlags::from_bits_truncate(args[3] as u32);
-    //    let maymove = flags.contains(MREMAPFlags::MREMAP_MAYMOVE);
-    //    let fixed = flags.contains(MREMAPFlags::MREMAP_FIXED);
-    //    let dontunmap = flags.contains(MREMAPFlags::MREMAP_DONTUNMAP);
-    //
-    //    // MREMAP_FIXED was specified without MREMAP_MAYMOVE
-    //    if fixed && !maymove {
-    //        return Err(SyscallError::EINVAL);
-    //    };
-    //
-    //    // MREMAP_DONTUNMAP was specified without MREMAP_MAYMOVE
-    //    if dontunmap && !maymove {
-    //        return Err(SyscallError::EINVAL);
-    //    };
-    //
-    //    // MREMAP_DONTUNMAP was specified with a size change
-    //    if dontunmap && old_size != new_size {
-    //        return Err(SyscallError::EINVAL);
-    //    };
-    //
-    //    // old_size was 0 and MREMAP_MAYMOVE was not specified
-    //    if old_size == 0 && !maymove {
-    //        return Err(SyscallError::EINVAL);
-    //    };
-    //
-    //    // MREMAP_FIXED is not implemented
-    //    if fixed {
-    //        unimplemented!();
-    //    }
-    //
-    //    let process = current_process();
-    //    let old_start: VirtAddr = old_addr.into();
-    //    if old_size > new_size {
-    //        let old_end = old_start + new_size;
-    //        process
-    //            .memory_set
-    //            .lock()
-    //            .lock()
-    //            .munmap(old_end, old_size - new_size);
-    //        flush_tlb(None);
-    //
-    //        return Ok(old_start.as_usize() as isize);
-    //    }
-    //
-    //    // Only deal with MREMAP_MAYMOVE now
-    //    let new_addr = process
-    //        .memory_set
-    //        .lock()
-    //        .lock()
-    //        .mremap(old_start, old_size, new_size);
-    //    flush_tlb(None);
-    //    Ok(new_addr)
+pub fn syscall_mremap(args: [usize; 6]) -> SyscallResult {
+    let (old_addr, old_size) = page_range(args[0], args[1])?;
+    let new_size = VirtAddr::from(args[2]).align_up_4k().as_usize();
+    let flags = MREMAPFlags::from_bits_truncate(args[3] as u32);
+    if new_size == 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if flags.contains(MREMAPFlags::MREMAP_FIXED) {
+        debug!("[mremap] MREMAP_FIXED is not supported");
+        return Err(SyscallError::EINVAL);
+    }
+    let addr = current_process().memory_set.lock().remap(
+        old_addr,
+        old_size,
+        new_size,
+        flags.contains(MREMAPFlags::MREMAP_MAYMOVE),
+        MMAP_LIMIT,
+    )?;
+    Ok(addr.as_usize() as isize)
 }
 const IPC_PRIVATE: i32 = 0;
 
@@ -256,7 +196,7 @@ bitflags! {
 /// * `size - usize`
 /// * `flags - i32`
 pub fn syscall_shmget(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+    unimplemented_syscall("shmget")
     //    let key = args[0] as i32;
     //    let size = args[1];
     //    let flags = args[2] as i32;
@@ -327,7 +267,7 @@ bitflags! {
 /// * `addr - usize`
 /// * `flags - i32`
 pub fn syscall_shmat(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+    unimplemented_syscall("shmat")
     //    let shmid = args[0] as i32;
     //    let addr = args[1];
     //    let flags = args[2] as i32;
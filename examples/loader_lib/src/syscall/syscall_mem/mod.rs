@@ -7,7 +7,7 @@ pub use mem_syscall_id::MemSyscallId::{self, *};
 
 use imp::*;
 
-use super::SyscallResult;
+use super::{SyscallResult, unimplemented_syscall};
 /// 与内存相关的系统调用
 pub fn mem_syscall(syscall_id: mem_syscall_id::MemSyscallId, args: [usize; 6]) -> SyscallResult {
     match syscall_id {
@@ -23,8 +23,6 @@ pub fn mem_syscall(syscall_id: mem_syscall_id::MemSyscallId, args: [usize; 6]) -
         SHMCTL => Ok(0),
         SHMAT => syscall_shmat(args),
         #[allow(unused)]
-        _ => {
-            panic!("Invalid Syscall Id: {:?}!", syscall_id);
-        }
+        _ => unimplemented_syscall(&alloc::format!("{:?}", syscall_id).to_lowercase()),
     }
 }
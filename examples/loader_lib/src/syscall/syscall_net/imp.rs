@@ -0,0 +1,293 @@
+use alloc::sync::Arc;
+use core::slice::{from_raw_parts, from_raw_parts_mut};
+
+use axlog::{debug, info};
+use axnet::{TcpSocket, UdpSocket};
+
+use super::socket::*;
+use crate::{
+    linux_env::{
+        axfs_ext::api::{FileIO, OpenFlags},
+        linux_fs::fd_manager::current_fd_manager,
+    },
+    syscall::{SyscallError, SyscallResult},
+};
+
+/// 取出`fd`对应的 socket
+fn get_socket(fd: usize) -> Result<Arc<dyn FileIO>, SyscallError> {
+    let file = match current_fd_manager().fd_table.lock().get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return Err(SyscallError::EBADF),
+    };
+    if file.as_any().downcast_ref::<Socket>().is_none() {
+        return Err(SyscallError::ENOTSOCK);
+    }
+    Ok(file)
+}
+
+/// 在`get_socket`取出的文件上取得`Socket`
+fn as_socket(file: &Arc<dyn FileIO>) -> &Socket {
+    file.as_any().downcast_ref::<Socket>().unwrap()
+}
+
+/// 把 socket 放入文件描述符表
+fn install_socket(socket: Socket) -> SyscallResult {
+    let fd_manager = current_fd_manager();
+    let mut fd_table = fd_manager.fd_table.lock();
+    let fd = fd_manager
+        .alloc_fd(&mut fd_table)
+        .map_err(|_| SyscallError::EMFILE)?;
+    fd_table[fd] = Some(Arc::new(socket));
+    Ok(fd as isize)
+}
+
+/// `type`中的`SOCK_NONBLOCK`和`SOCK_CLOEXEC`对应的打开选项
+fn socket_flags(ty: usize) -> Result<OpenFlags, SyscallError> {
+    if ty & !(SOCK_NONBLOCK | SOCK_CLOEXEC) != 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    let mut flags = OpenFlags::empty();
+    if ty & SOCK_NONBLOCK != 0 {
+        flags |= OpenFlags::NON_BLOCK;
+    }
+    if ty & SOCK_CLOEXEC != 0 {
+        flags |= OpenFlags::CLOEXEC;
+    }
+    Ok(flags)
+}
+
+/// 创建一个 socket，目前只支持 IPv4 的 TCP 和 UDP
+/// # Arguments
+/// * `domain` - usize
+/// * `ty` - usize, 可以带有`SOCK_NONBLOCK`和`SOCK_CLOEXEC`
+/// * `protocol` - usize
+pub fn syscall_socket(args: [usize; 6]) -> SyscallResult {
+    let domain = args[0];
+    let ty = args[1];
+    let protocol = args[2];
+    info!("[socket()] domain: {domain}, type: {ty:#x}, protocol: {protocol}");
+    if domain != AF_INET as usize {
+        return Err(SyscallError::EAFNOSUPPORT);
+    }
+    let flags = socket_flags(ty & (SOCK_NONBLOCK | SOCK_CLOEXEC))?;
+    let inner = match (ty & !(SOCK_NONBLOCK | SOCK_CLOEXEC), protocol) {
+        (SOCK_STREAM, 0 | IPPROTO_TCP) => SocketInner::Tcp(TcpSocket::new()),
+        (SOCK_DGRAM, 0 | IPPROTO_UDP) => SocketInner::Udp(UdpSocket::new()),
+        (SOCK_STREAM | SOCK_DGRAM, _) => return Err(SyscallError::EPROTONOSUPPORT),
+        _ => return Err(SyscallError::EINVAL),
+    };
+    install_socket(Socket::new(inner, flags))
+}
+
+/// # Arguments
+/// * `fd` - usize
+/// * `addr` - *const u8, `struct sockaddr`
+/// * `addrlen` - usize
+pub fn syscall_bind(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let addr = read_sockaddr(args[1] as *const u8, args[2])?;
+    info!("[bind()] fd: {fd}, addr: {addr}");
+    let file = get_socket(fd)?;
+    as_socket(&file).bind(addr)?;
+    Ok(0)
+}
+
+/// `backlog`由`axnet`决定，这里忽略
+/// # Arguments
+/// * `fd` - usize
+/// * `backlog` - usize
+pub fn syscall_listen(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    info!("[listen()] fd: {fd}");
+    let file = get_socket(fd)?;
+    as_socket(&file).listen()?;
+    Ok(0)
+}
+
+/// 接受一个连接，`accept`即`flags`为 0 的`accept4`
+/// # Arguments
+/// * `fd` - usize
+/// * `addr` - *mut u8, 为空时不返回对端地址
+/// * `addrlen` - *mut u32
+/// * `flags` - usize, `SOCK_NONBLOCK`和`SOCK_CLOEXEC`
+pub fn syscall_accept4(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let addr = args[1] as *mut u8;
+    let addrlen = args[2] as *mut u32;
+    let flags = socket_flags(args[3])?;
+    info!("[accept4()] fd: {fd}, flags: {:#x}", args[3]);
+    let file = get_socket(fd)?;
+    let socket = as_socket(&file).accept()?;
+    let peer = socket.peer_addr()?;
+    // 写地址失败时连接已经被取走，仍然需要关闭它
+    if !addr.is_null() {
+        if let Err(err) = write_sockaddr(peer, addr, addrlen) {
+            let _ = socket.shutdown();
+            return Err(err);
+        }
+    }
+    debug!("[accept4()] fd: {fd}, accept connection from {peer}");
+    install_socket(Socket::new(SocketInner::Tcp(socket), flags))
+}
+
+/// # Arguments
+/// * `fd` - usize
+/// * `addr` - *const u8, `struct sockaddr`
+/// * `addrlen` - usize
+pub fn syscall_connect(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let addr = read_sockaddr(args[1] as *const u8, args[2])?;
+    info!("[connect()] fd: {fd}, addr: {addr}");
+    let file = get_socket(fd)?;
+    as_socket(&file).connect(addr)?;
+    Ok(0)
+}
+
+/// # Arguments
+/// * `fd` - usize
+/// * `addr` - *mut u8
+/// * `addrlen` - *mut u32
+pub fn syscall_getsockname(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    info!("[getsockname()] fd: {fd}");
+    let file = get_socket(fd)?;
+    let local = as_socket(&file).local_addr()?;
+    write_sockaddr(local, args[1] as *mut u8, args[2] as *mut u32)?;
+    Ok(0)
+}
+
+/// 未连接时返回`ENOTCONN`
+/// # Arguments
+/// * `fd` - usize
+/// * `addr` - *mut u8
+/// * `addrlen` - *mut u32
+pub fn syscall_getpeername(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    info!("[getpeername()] fd: {fd}");
+    let file = get_socket(fd)?;
+    let peer = as_socket(&file).peer_addr()?;
+    write_sockaddr(peer, args[1] as *mut u8, args[2] as *mut u32)?;
+    Ok(0)
+}
+
+/// 目前不支持任何`flags`，它们被忽略
+/// # Arguments
+/// * `fd` - usize
+/// * `buf` - *const u8
+/// * `len` - usize
+/// * `flags` - usize
+/// * `addr` - *const u8, 为空时发给已连接的对端
+/// * `addrlen` - usize
+pub fn syscall_sendto(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let buf = args[1] as *const u8;
+    let len = args[2];
+    info!("[sendto()] fd: {fd}, len: {len}, flags: {:#x}", args[3]);
+    if buf.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let addr = if args[4] == 0 {
+        None
+    } else {
+        Some(read_sockaddr(args[4] as *const u8, args[5])?)
+    };
+    let file = get_socket(fd)?;
+    let buf = unsafe { from_raw_parts(buf, len) };
+    Ok(as_socket(&file).send_to(buf, addr)? as isize)
+}
+
+/// 目前不支持任何`flags`，它们被忽略
+/// # Arguments
+/// * `fd` - usize
+/// * `buf` - *mut u8
+/// * `len` - usize
+/// * `flags` - usize
+/// * `addr` - *mut u8, 不为空时写入对端地址
+/// * `addrlen` - *mut u32
+pub fn syscall_recvfrom(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let buf = args[1] as *mut u8;
+    let len = args[2];
+    let addr = args[4] as *mut u8;
+    let addrlen = args[5] as *mut u32;
+    info!("[recvfrom()] fd: {fd}, len: {len}, flags: {:#x}", args[3]);
+    if buf.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let file = get_socket(fd)?;
+    let buf = unsafe { from_raw_parts_mut(buf, len) };
+    let (len, peer) = as_socket(&file).recv_from(buf)?;
+    if let (false, Some(peer)) = (addr.is_null(), peer) {
+        write_sockaddr(peer, addr, addrlen)?;
+    }
+    Ok(len as isize)
+}
+
+/// 只支持`int`类型的选项
+/// # Arguments
+/// * `fd` - usize
+/// * `level` - usize
+/// * `optname` - usize
+/// * `optval` - *const u32
+/// * `optlen` - usize
+pub fn syscall_setsockopt(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let level = args[1];
+    let optname = args[2];
+    let optval = args[3] as *const u32;
+    let optlen = args[4];
+    info!("[setsockopt()] fd: {fd}, level: {level}, optname: {optname}");
+    let file = get_socket(fd)?;
+    if optval.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    if optlen < core::mem::size_of::<u32>() {
+        return Err(SyscallError::EINVAL);
+    }
+    as_socket(&file).set_option(level, optname, unsafe { *optval })?;
+    Ok(0)
+}
+
+/// # Arguments
+/// * `fd` - usize
+/// * `level` - usize
+/// * `optname` - usize
+/// * `optval` - *mut u32
+/// * `optlen` - *mut u32
+pub fn syscall_getsockopt(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let level = args[1];
+    let optname = args[2];
+    let optval = args[3] as *mut u32;
+    let optlen = args[4] as *mut u32;
+    info!("[getsockopt()] fd: {fd}, level: {level}, optname: {optname}");
+    let file = get_socket(fd)?;
+    if optval.is_null() || optlen.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    if (unsafe { *optlen } as usize) < core::mem::size_of::<u32>() {
+        return Err(SyscallError::EINVAL);
+    }
+    let value = as_socket(&file).get_option(level, optname)?;
+    unsafe {
+        *optval = value;
+        *optlen = core::mem::size_of::<u32>() as u32;
+    }
+    Ok(0)
+}
+
+/// 关闭连接的一个或两个方向
+/// # Arguments
+/// * `fd` - usize
+/// * `how` - usize
+pub fn syscall_shutdown(args: [usize; 6]) -> SyscallResult {
+    let fd = args[0];
+    let how = args[1];
+    info!("[shutdown()] fd: {fd}, how: {how}");
+    if !(SHUT_RD..=SHUT_RDWR).contains(&how) {
+        return Err(SyscallError::EINVAL);
+    }
+    let file = get_socket(fd)?;
+    as_socket(&file).shutdown(how)?;
+    Ok(0)
+}
@@ -0,0 +1,29 @@
+//! 与网络相关的系统调用，socket 由`axnet`的`TcpSocket`/`UdpSocket`实现
+
+mod imp;
+pub mod socket;
+
+mod net_syscall_id;
+pub use net_syscall_id::NetSyscallId::{self, *};
+
+use imp::*;
+
+use super::SyscallResult;
+/// 与网络相关的系统调用
+pub fn net_syscall(syscall_id: net_syscall_id::NetSyscallId, args: [usize; 6]) -> SyscallResult {
+    match syscall_id {
+        SOCKET => syscall_socket(args),
+        BIND => syscall_bind(args),
+        LISTEN => syscall_listen(args),
+        ACCEPT => syscall_accept4([args[0], args[1], args[2], 0, 0, 0]),
+        ACCEPT4 => syscall_accept4(args),
+        CONNECT => syscall_connect(args),
+        GETSOCKNAME => syscall_getsockname(args),
+        GETPEERNAME => syscall_getpeername(args),
+        SENDTO => syscall_sendto(args),
+        RECVFROM => syscall_recvfrom(args),
+        SETSOCKOPT => syscall_setsockopt(args),
+        GETSOCKOPT => syscall_getsockopt(args),
+        SHUTDOWN => syscall_shutdown(args),
+    }
+}
@@ -0,0 +1,24 @@
+//! 记录该模块使用到的系统调用 id
+//!
+//!
+numeric_enum_macro::numeric_enum! {
+#[repr(usize)]
+#[allow(non_camel_case_types)]
+#[allow(missing_docs)]
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum NetSyscallId {
+    SOCKET = 198,
+    BIND = 200,
+    LISTEN = 201,
+    ACCEPT = 202,
+    CONNECT = 203,
+    GETSOCKNAME = 204,
+    GETPEERNAME = 205,
+    SENDTO = 206,
+    RECVFROM = 207,
+    SETSOCKOPT = 208,
+    GETSOCKOPT = 209,
+    SHUTDOWN = 210,
+    ACCEPT4 = 242,
+}
+}
@@ -0,0 +1,350 @@
+//! 放在文件描述符表中的 socket
+
+use core::{
+    mem::size_of,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+};
+
+use axerrno::AxResult;
+use axnet::{TcpSocket, UdpSocket};
+use axsync::Mutex;
+
+use crate::{
+    linux_env::axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags},
+    syscall::{StMode, SyscallError},
+};
+
+pub const AF_INET: u16 = 2;
+
+pub const SOCK_STREAM: usize = 1;
+pub const SOCK_DGRAM: usize = 2;
+/// `socket`的`type`参数中与`O_NONBLOCK`相同的选项
+pub const SOCK_NONBLOCK: usize = 0o4000;
+/// `socket`的`type`参数中与`O_CLOEXEC`相同的选项
+pub const SOCK_CLOEXEC: usize = 0o2000000;
+
+/// `shutdown`的`how`参数
+pub const SHUT_RD: usize = 0;
+pub const SHUT_WR: usize = 1;
+pub const SHUT_RDWR: usize = 2;
+
+pub const IPPROTO_TCP: usize = 6;
+pub const IPPROTO_UDP: usize = 17;
+
+pub const SOL_SOCKET: usize = 1;
+pub const SO_REUSEADDR: usize = 2;
+pub const SO_TYPE: usize = 3;
+pub const SO_ERROR: usize = 4;
+pub const SO_SNDBUF: usize = 7;
+pub const SO_RCVBUF: usize = 8;
+pub const SO_KEEPALIVE: usize = 9;
+pub const TCP_NODELAY: usize = 1;
+
+/// 默认的收发缓冲区大小，只用于`getsockopt`报告
+const DEFAULT_BUF_SIZE: u32 = 64 * 1024;
+
+/// `struct sockaddr_in`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SockAddrIn {
+    pub sin_family: u16,
+    /// 网络字节序的端口号
+    pub sin_port: u16,
+    /// 网络字节序的地址
+    pub sin_addr: u32,
+    pub sin_zero: [u8; 8],
+}
+
+impl From<SocketAddrV4> for SockAddrIn {
+    fn from(addr: SocketAddrV4) -> Self {
+        Self {
+            sin_family: AF_INET,
+            sin_port: addr.port().to_be(),
+            sin_addr: u32::from_ne_bytes(addr.ip().octets()),
+            sin_zero: [0; 8],
+        }
+    }
+}
+
+impl From<SockAddrIn> for SocketAddrV4 {
+    fn from(addr: SockAddrIn) -> Self {
+        SocketAddrV4::new(
+            Ipv4Addr::from(addr.sin_addr.to_ne_bytes()),
+            u16::from_be(addr.sin_port),
+        )
+    }
+}
+
+/// 从用户传入的`sockaddr`读出地址，目前只支持 IPv4
+pub fn read_sockaddr(addr: *const u8, addrlen: usize) -> Result<SocketAddr, SyscallError> {
+    if addr.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    if addrlen < size_of::<u16>() {
+        return Err(SyscallError::EINVAL);
+    }
+    match unsafe { *(addr as *const u16) } {
+        AF_INET => {
+            if addrlen < size_of::<SockAddrIn>() {
+                return Err(SyscallError::EINVAL);
+            }
+            let addr = unsafe { *(addr as *const SockAddrIn) };
+            Ok(SocketAddr::V4(addr.into()))
+        }
+        _ => Err(SyscallError::EAFNOSUPPORT),
+    }
+}
+
+/// 把地址写到用户的`sockaddr`中。`*addrlen`是缓冲区大小，超出的部分被截断，返回时
+/// 改为地址的实际长度。
+pub fn write_sockaddr(
+    sockaddr: SocketAddr,
+    addr: *mut u8,
+    addrlen: *mut u32,
+) -> Result<(), SyscallError> {
+    if addr.is_null() || addrlen.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let raw = match sockaddr {
+        SocketAddr::V4(sockaddr) => SockAddrIn::from(sockaddr),
+        SocketAddr::V6(_) => return Err(SyscallError::EAFNOSUPPORT),
+    };
+    let len = unsafe { *addrlen } as usize;
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            &raw as *const SockAddrIn as *const u8,
+            addr,
+            len.min(size_of::<SockAddrIn>()),
+        );
+        *addrlen = size_of::<SockAddrIn>() as u32;
+    }
+    Ok(())
+}
+
+pub enum SocketInner {
+    Tcp(TcpSocket),
+    Udp(UdpSocket),
+}
+
+/// 用`setsockopt`设置过的选项，只记录下来供`getsockopt`读取
+#[derive(Clone, Copy)]
+struct SocketOptions {
+    reuse_addr: bool,
+    keep_alive: bool,
+    no_delay: bool,
+    send_buf: u32,
+    recv_buf: u32,
+}
+
+/// 一个 TCP 或 UDP socket。`TcpSocket`/`UdpSocket`自身是线程安全的，收发可以同时进行。
+pub struct Socket {
+    inner: SocketInner,
+    flags: Mutex<OpenFlags>,
+    options: Mutex<SocketOptions>,
+}
+
+impl Socket {
+    pub fn new(inner: SocketInner, flags: OpenFlags) -> Self {
+        let socket = Self {
+            inner,
+            flags: Mutex::new(OpenFlags::RDWR),
+            options: Mutex::new(SocketOptions {
+                reuse_addr: false,
+                keep_alive: false,
+                no_delay: false,
+                send_buf: DEFAULT_BUF_SIZE,
+                recv_buf: DEFAULT_BUF_SIZE,
+            }),
+        };
+        socket.set_status(flags | OpenFlags::RDWR);
+        socket
+    }
+
+    /// `SO_TYPE`
+    pub fn socket_type(&self) -> usize {
+        match self.inner {
+            SocketInner::Tcp(_) => SOCK_STREAM,
+            SocketInner::Udp(_) => SOCK_DGRAM,
+        }
+    }
+
+    pub fn bind(&self, addr: SocketAddr) -> AxResult {
+        match &self.inner {
+            SocketInner::Tcp(socket) => socket.bind(addr),
+            SocketInner::Udp(socket) => socket.bind(addr),
+        }
+    }
+
+    pub fn listen(&self) -> Result<(), SyscallError> {
+        match &self.inner {
+            SocketInner::Tcp(socket) => Ok(socket.listen()?),
+            SocketInner::Udp(_) => Err(SyscallError::EOPNOTSUPP),
+        }
+    }
+
+    /// 接受一个连接，新的 socket 不继承`O_NONBLOCK`
+    pub fn accept(&self) -> Result<TcpSocket, SyscallError> {
+        match &self.inner {
+            SocketInner::Tcp(socket) => Ok(socket.accept()?),
+            SocketInner::Udp(_) => Err(SyscallError::EOPNOTSUPP),
+        }
+    }
+
+    pub fn connect(&self, addr: SocketAddr) -> AxResult {
+        match &self.inner {
+            SocketInner::Tcp(socket) => socket.connect(addr),
+            SocketInner::Udp(socket) => socket.connect(addr),
+        }
+    }
+
+    pub fn local_addr(&self) -> AxResult<SocketAddr> {
+        match &self.inner {
+            SocketInner::Tcp(socket) => socket.local_addr(),
+            SocketInner::Udp(socket) => socket.local_addr(),
+        }
+    }
+
+    pub fn peer_addr(&self) -> AxResult<SocketAddr> {
+        match &self.inner {
+            SocketInner::Tcp(socket) => socket.peer_addr(),
+            SocketInner::Udp(socket) => socket.peer_addr(),
+        }
+    }
+
+    /// 发送数据，`addr`只对 UDP 有意义，已连接的 TCP socket 忽略它
+    pub fn send_to(&self, buf: &[u8], addr: Option<SocketAddr>) -> AxResult<usize> {
+        match (&self.inner, addr) {
+            (SocketInner::Tcp(socket), _) => socket.send(buf),
+            (SocketInner::Udp(socket), Some(addr)) => socket.send_to(buf, addr),
+            (SocketInner::Udp(socket), None) => socket.send(buf),
+        }
+    }
+
+    /// 接收数据，同时返回对端地址
+    pub fn recv_from(&self, buf: &mut [u8]) -> AxResult<(usize, Option<SocketAddr>)> {
+        match &self.inner {
+            SocketInner::Tcp(socket) => Ok((socket.recv(buf)?, socket.peer_addr().ok())),
+            SocketInner::Udp(socket) => socket.recv_from(buf).map(|(len, addr)| (len, Some(addr))),
+        }
+    }
+
+    /// 按`how`关闭连接的方向
+    ///
+    /// `SHUT_WR`只向对方发送 FIN，仍然可以接收数据；`SHUT_RD`不会通知对方，
+    /// 这里不做任何事。
+    pub fn shutdown(&self, how: usize) -> AxResult {
+        match (&self.inner, how) {
+            (_, SHUT_RD) | (SocketInner::Udp(_), SHUT_WR) => Ok(()),
+            (SocketInner::Tcp(socket), SHUT_WR) => socket.shutdown_write(),
+            (SocketInner::Tcp(socket), _) => socket.shutdown(),
+            (SocketInner::Udp(socket), _) => socket.shutdown(),
+        }
+    }
+
+    /// 设置`level`为`SOL_SOCKET`或`IPPROTO_TCP`的选项，不认识的选项返回`ENOPROTOOPT`
+    pub fn set_option(&self, level: usize, name: usize, value: u32) -> Result<(), SyscallError> {
+        let mut options = self.options.lock();
+        match (level, name) {
+            (SOL_SOCKET, SO_REUSEADDR) => options.reuse_addr = value != 0,
+            (SOL_SOCKET, SO_KEEPALIVE) => options.keep_alive = value != 0,
+            (SOL_SOCKET, SO_SNDBUF) => options.send_buf = value,
+            (SOL_SOCKET, SO_RCVBUF) => options.recv_buf = value,
+            (IPPROTO_TCP, TCP_NODELAY) => options.no_delay = value != 0,
+            _ => return Err(SyscallError::ENOPROTOOPT),
+        }
+        Ok(())
+    }
+
+    pub fn get_option(&self, level: usize, name: usize) -> Result<u32, SyscallError> {
+        let options = self.options.lock();
+        Ok(match (level, name) {
+            (SOL_SOCKET, SO_REUSEADDR) => options.reuse_addr as u32,
+            (SOL_SOCKET, SO_KEEPALIVE) => options.keep_alive as u32,
+            (SOL_SOCKET, SO_SNDBUF) => options.send_buf,
+            (SOL_SOCKET, SO_RCVBUF) => options.recv_buf,
+            (SOL_SOCKET, SO_TYPE) => self.socket_type() as u32,
+            (SOL_SOCKET, SO_ERROR) => 0,
+            (IPPROTO_TCP, TCP_NODELAY) => options.no_delay as u32,
+            _ => return Err(SyscallError::ENOPROTOOPT),
+        })
+    }
+
+    fn poll(&self) -> AxResult<axio::PollState> {
+        match &self.inner {
+            SocketInner::Tcp(socket) => socket.poll(),
+            SocketInner::Udp(socket) => socket.poll(),
+        }
+    }
+}
+
+impl FileIO for Socket {
+    fn read(&self, buf: &mut [u8]) -> AxResult<usize> {
+        self.recv_from(buf).map(|(len, _)| len)
+    }
+
+    fn write(&self, buf: &[u8]) -> AxResult<usize> {
+        self.send_to(buf, None)
+    }
+
+    fn flush(&self) -> AxResult<()> {
+        Ok(())
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn executable(&self) -> bool {
+        false
+    }
+
+    fn get_type(&self) -> FileIOType {
+        FileIOType::Socket
+    }
+
+    fn get_stat(&self) -> AxResult<Kstat> {
+        Ok(Kstat {
+            st_ino: 1,
+            st_mode: StMode::S_IFSOCK.bits() | 0o777,
+            st_nlink: 1,
+            st_blksize: 4096,
+            ..Default::default()
+        })
+    }
+
+    /// `O_NONBLOCK`同时设置到`axnet`的 socket 上
+    fn set_status(&self, flags: OpenFlags) -> bool {
+        let nonblocking = flags.contains(OpenFlags::NON_BLOCK);
+        match &self.inner {
+            SocketInner::Tcp(socket) => socket.set_nonblocking(nonblocking),
+            SocketInner::Udp(socket) => socket.set_nonblocking(nonblocking),
+        }
+        *self.flags.lock() = flags;
+        true
+    }
+
+    fn get_status(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_close_on_exec(&self, is_set: bool) -> bool {
+        if is_set {
+            *self.flags.lock() |= OpenFlags::CLOEXEC;
+        } else {
+            *self.flags.lock() &= !OpenFlags::CLOEXEC;
+        }
+        true
+    }
+
+    fn ready_to_read(&self) -> bool {
+        self.poll().is_ok_and(|state| state.readable)
+    }
+
+    fn ready_to_write(&self) -> bool {
+        self.poll().is_ok_and(|state| state.writable)
+    }
+}
@@ -0,0 +1,212 @@
+//! 用户号、组号和附加组相关的 syscall，见[`Cred`](crate::linux_env::process_ext::cred::Cred)
+
+use alloc::vec::Vec;
+use core::slice::from_raw_parts;
+
+use crate::{
+    linux_env::process_ext::{api::current_process, cred::NGROUPS_MAX},
+    syscall::{SyscallError, SyscallResult},
+};
+
+/// 参数中的`-1`表示不改变
+fn id_arg(arg: usize) -> Option<u32> {
+    match arg as u32 {
+        u32::MAX => None,
+        id => Some(id),
+    }
+}
+
+/// 获取实际用户号
+pub fn syscall_getuid() -> SyscallResult {
+    Ok(current_process().cred().ruid as isize)
+}
+
+/// 获取有效用户号，即相当于哪个用户的权限
+pub fn syscall_geteuid() -> SyscallResult {
+    Ok(current_process().cred().euid as isize)
+}
+
+/// 获取实际组号
+pub fn syscall_getgid() -> SyscallResult {
+    Ok(current_process().cred().rgid as isize)
+}
+
+/// 获取有效组号
+pub fn syscall_getegid() -> SyscallResult {
+    Ok(current_process().cred().egid as isize)
+}
+
+/// 设置用户号。root 同时设置实际、有效和保存的用户号，其他用户只能设置有效用户号
+/// # Arguments
+/// * `uid: u32`
+pub fn syscall_setuid(args: [usize; 6]) -> SyscallResult {
+    let uid = args[0] as u32;
+    current_process().update_cred(|cred| {
+        if cred.is_root() {
+            cred.set_uids(Some(uid), Some(uid), Some(uid))
+        } else {
+            cred.set_uids(None, Some(uid), None)
+        }
+    })?;
+    Ok(0)
+}
+
+/// 设置组号，规则同`setuid`
+/// # Arguments
+/// * `gid: u32`
+pub fn syscall_setgid(args: [usize; 6]) -> SyscallResult {
+    let gid = args[0] as u32;
+    current_process().update_cred(|cred| {
+        if cred.is_root() {
+            cred.set_gids(Some(gid), Some(gid), Some(gid))
+        } else {
+            cred.set_gids(None, Some(gid), None)
+        }
+    })?;
+    Ok(0)
+}
+
+/// 设置实际和有效用户号，`-1`表示不改变。
+/// 设置了实际用户号，或有效用户号不等于原来的实际用户号时，保存的用户号也设为新的有效用户号
+/// # Arguments
+/// * `ruid: u32`
+/// * `euid: u32`
+pub fn syscall_setreuid(args: [usize; 6]) -> SyscallResult {
+    let (ruid, euid) = (id_arg(args[0]), id_arg(args[1]));
+    current_process().update_cred(|cred| {
+        // 非 root 只能把实际用户号设为原来的实际或有效用户号
+        if !cred.is_root() && ruid.is_some_and(|id| id != cred.ruid && id != cred.euid) {
+            return Err(SyscallError::EPERM);
+        }
+        let suid = match euid {
+            Some(euid) if ruid.is_some() || euid != cred.ruid => Some(euid),
+            _ => ruid.map(|_| cred.euid),
+        };
+        cred.set_uids(ruid, euid, suid)
+    })?;
+    Ok(0)
+}
+
+/// 设置实际和有效组号，规则同`setreuid`
+/// # Arguments
+/// * `rgid: u32`
+/// * `egid: u32`
+pub fn syscall_setregid(args: [usize; 6]) -> SyscallResult {
+    let (rgid, egid) = (id_arg(args[0]), id_arg(args[1]));
+    current_process().update_cred(|cred| {
+        if !cred.is_root() && rgid.is_some_and(|id| id != cred.rgid && id != cred.egid) {
+            return Err(SyscallError::EPERM);
+        }
+        let sgid = match egid {
+            Some(egid) if rgid.is_some() || egid != cred.rgid => Some(egid),
+            _ => rgid.map(|_| cred.egid),
+        };
+        cred.set_gids(rgid, egid, sgid)
+    })?;
+    Ok(0)
+}
+
+/// 设置实际、有效和保存的用户号，`-1`表示不改变
+/// # Arguments
+/// * `ruid: u32`
+/// * `euid: u32`
+/// * `suid: u32`
+pub fn syscall_setresuid(args: [usize; 6]) -> SyscallResult {
+    let (ruid, euid, suid) = (id_arg(args[0]), id_arg(args[1]), id_arg(args[2]));
+    current_process().update_cred(|cred| cred.set_uids(ruid, euid, suid))?;
+    Ok(0)
+}
+
+/// 设置实际、有效和保存的组号，`-1`表示不改变
+/// # Arguments
+/// * `rgid: u32`
+/// * `egid: u32`
+/// * `sgid: u32`
+pub fn syscall_setresgid(args: [usize; 6]) -> SyscallResult {
+    let (rgid, egid, sgid) = (id_arg(args[0]), id_arg(args[1]), id_arg(args[2]));
+    current_process().update_cred(|cred| cred.set_gids(rgid, egid, sgid))?;
+    Ok(0)
+}
+
+/// 获取实际、有效和保存的用户号
+/// # Arguments
+/// * `ruid: *mut u32`
+/// * `euid: *mut u32`
+/// * `suid: *mut u32`
+pub fn syscall_getresuid(args: [usize; 6]) -> SyscallResult {
+    let cred = current_process().cred();
+    write_ids(args, [cred.ruid, cred.euid, cred.suid])
+}
+
+/// 获取实际、有效和保存的组号
+/// # Arguments
+/// * `rgid: *mut u32`
+/// * `egid: *mut u32`
+/// * `sgid: *mut u32`
+pub fn syscall_getresgid(args: [usize; 6]) -> SyscallResult {
+    let cred = current_process().cred();
+    write_ids(args, [cred.rgid, cred.egid, cred.sgid])
+}
+
+fn write_ids(args: [usize; 6], ids: [u32; 3]) -> SyscallResult {
+    let ptrs = [args[0], args[1], args[2]].map(|ptr| ptr as *mut u32);
+    if ptrs.iter().any(|ptr| ptr.is_null()) {
+        return Err(SyscallError::EFAULT);
+    }
+    for (ptr, id) in ptrs.into_iter().zip(ids) {
+        unsafe { *ptr = id };
+    }
+    Ok(0)
+}
+
+/// 获取附加组。`size`为 0 时只返回附加组的数目
+/// # Arguments
+/// * `size: i32`, `list`的长度
+/// * `list: *mut u32`
+pub fn syscall_getgroups(args: [usize; 6]) -> SyscallResult {
+    let size = args[0] as i32;
+    let list = args[1] as *mut u32;
+    let groups = current_process().cred().groups;
+    if size < 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    if size == 0 {
+        return Ok(groups.len() as isize);
+    }
+    if (size as usize) < groups.len() {
+        return Err(SyscallError::EINVAL);
+    }
+    if list.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    for (i, gid) in groups.iter().enumerate() {
+        unsafe { *list.add(i) = *gid };
+    }
+    Ok(groups.len() as isize)
+}
+
+/// 设置附加组，只有 root 可以调用
+/// # Arguments
+/// * `size: usize`, `list`的长度
+/// * `list: *const u32`
+pub fn syscall_setgroups(args: [usize; 6]) -> SyscallResult {
+    let size = args[0];
+    let list = args[1] as *const u32;
+    if size > NGROUPS_MAX {
+        return Err(SyscallError::EINVAL);
+    }
+    let groups: Vec<u32> = if size == 0 {
+        Vec::new()
+    } else if list.is_null() {
+        return Err(SyscallError::EFAULT);
+    } else {
+        unsafe { from_raw_parts(list, size) }.to_vec()
+    };
+    current_process().update_cred(|cred| {
+        if !cred.is_root() {
+            return Err(SyscallError::EPERM);
+        }
+        cred.groups = groups;
+        Ok(0)
+    })
+}
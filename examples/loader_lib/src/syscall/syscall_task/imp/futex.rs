@@ -1,143 +1,119 @@
 //! 支持 futex 相关的 syscall
 
 use crate::{
-    linux_env::linux_fs::futex::{FUTEX_WAIT_TASK, FutexKey, WAIT_FOR_FUTEX, get_futex_key},
-    syscall::{FutexFlags, SyscallError, SyscallResult, TimeSecs},
+    linux_env::{
+        linux_fs::futex::{
+            FutexRobustList, ROBUST_LISTS, RobustListHead, futex_lock_pi, futex_requeue,
+            futex_unlock_pi, futex_wait, futex_wake, futex_wake_op, get_futex_key,
+        },
+        process_ext::process::TID2PC,
+    },
+    syscall::{
+        FUTEX_BITSET_MATCH_ANY, FUTEX_CLOCK_REALTIME, FutexFlags, NSEC_PER_SEC, SyscallError,
+        SyscallResult, TimeSecs,
+    },
 };
-use alloc::collections::VecDeque;
-use axhal::{mem::VirtAddr, time::current_ticks};
-use axlog::info;
-use axtask::{CurrentTask, current, yield_now};
+use axhal::{
+    mem::VirtAddr,
+    time::{TimeValue, monotonic_time, wall_time},
+};
+use axtask::current;
 use core::time::Duration;
 
-/// `Futex requeue`操作
-///
-/// 首先唤醒src_addr对应的futex变量的等待队列中，至多wake_num个任务
-///
-/// 若原队列中的任务数大于wake_num，则将多余的任务移动到dst_addr对应的futex变量的等待队列中
+/// 把`timeout`指向的时间转为`monotonic_time`下的截止时间，空指针表示一直等待
 ///
-/// 移动的任务数目至多为move_num
-///
-/// 不考虑检查操作
-pub fn futex_requeue(wake_num: u32, move_num: usize, src_addr: VirtAddr, dst_addr: VirtAddr) {
-    let key = get_futex_key(src_addr, 0);
-    let mut futex_wait_task = FUTEX_WAIT_TASK.lock();
-    if !futex_wait_task.contains_key(&key) {
-        return;
-    }
-    let src_wait_task = futex_wait_task.get_mut(&key).unwrap();
-    for _ in 0..wake_num {
-        if let Some((task, _)) = src_wait_task.pop_front() {
-            WAIT_FOR_FUTEX.lock().notify_task(false, &task);
-        } else {
-            break;
-        }
+/// `FUTEX_WAIT`的超时是相对时间，`FUTEX_WAIT_BITSET`的是绝对时间（默认按
+/// `CLOCK_MONOTONIC`，带`FUTEX_CLOCK_REALTIME`时按`CLOCK_REALTIME`），
+/// `FUTEX_LOCK_PI`的是`CLOCK_REALTIME`下的绝对时间。负数返回`EINVAL`，
+/// 远到无法表示的截止时间当作一直等待。
+fn futex_deadline(
+    flag: &FutexFlags,
+    futex_op: i32,
+    timeout: *const TimeSecs,
+) -> Result<Option<TimeValue>, SyscallError> {
+    if timeout.is_null() {
+        return Ok(None);
     }
-    if !src_wait_task.is_empty() {
-        let key_new = get_futex_key(dst_addr, 0);
-        let move_num = move_num.min(src_wait_task.len());
-
-        let mut temp_move_task = src_wait_task.drain(..move_num).collect::<VecDeque<_>>();
-        let dst_wait_task = futex_wait_task.entry(key_new).or_default();
-        dst_wait_task.append(&mut temp_move_task);
+    let timeout = unsafe { *timeout };
+    // 字段是`usize`，用户传入的负数在这里变成很大的值
+    if (timeout.tv_sec as isize) < 0 || timeout.tv_nsec >= NSEC_PER_SEC {
+        return Err(SyscallError::EINVAL);
     }
-}
-
-fn futex_quque(key: FutexKey, curr: &CurrentTask, val: u32) {
-    let mut futex_wait_task = FUTEX_WAIT_TASK.lock();
-    let wait_list = futex_wait_task.entry(key).or_default();
-    wait_list.push_back((curr.as_task_ref().clone(), val));
-}
-
-fn futex_unqueue(key: FutexKey, curr: &CurrentTask) -> bool {
-    let mut futex_wait_task = FUTEX_WAIT_TASK.lock();
-    if futex_wait_task.contains_key(&key) {
-        let wait_list = futex_wait_task.get_mut(&key).unwrap();
-        if let Some(index) = wait_list
-            .iter()
-            .position(|(task, _)| task.id() == curr.id())
-        {
-            wait_list.remove(index);
-            return true;
-        }
-    }
-    false
+    let time = Duration::new(timeout.tv_sec as u64, timeout.tv_nsec as u32);
+    let now = monotonic_time();
+    let realtime = futex_op & FUTEX_CLOCK_REALTIME != 0;
+    Ok(match flag {
+        FutexFlags::Wait => now.checked_add(time),
+        FutexFlags::WaitBitset if !realtime => Some(time),
+        _ => now.checked_add(time.saturating_sub(wall_time())),
+    })
 }
 
 /// To do the futex operation
 ///
-/// It may create, remove the futex wait task or `requeue` the futex wait task
+/// `timeout` is the deadline of the blocking operations; the other operations use
+/// `val2` (the same argument of the syscall) as a number.
 pub fn futex(
     vaddr: VirtAddr,
     futex_op: i32,
     val: u32,
-    timeout: usize,
+    timeout: Option<TimeValue>,
     vaddr2: VirtAddr,
     val2: usize,
-    _val3: u32,
+    val3: u32,
 ) -> Result<usize, SyscallError> {
     let flag = FutexFlags::new(futex_op);
-    let current_task = current();
+    if matches!(flag, FutexFlags::Unsupported)
+        || (futex_op & FUTEX_CLOCK_REALTIME != 0
+            && !matches!(flag, FutexFlags::Wait | FutexFlags::WaitBitset))
+    {
+        return Err(SyscallError::ENOSYS);
+    }
+    let key = get_futex_key(vaddr, futex_op)?;
     match flag {
         FutexFlags::Wait => {
-            let mut to = false;
-            let deadline = if timeout != 0 {
-                Some(Duration::from_nanos(timeout as u64 + current_ticks()))
-            } else {
-                None
-            };
-            loop {
-                let key = get_futex_key(vaddr, futex_op);
-                let real_futex_val = unsafe { (vaddr.as_usize() as *const u32).read_volatile() };
-                info!("real val: {:#x}, expected val: {:#x}", real_futex_val, val);
-                if real_futex_val != val {
-                    return Err(SyscallError::EAGAIN);
-                }
-                futex_quque(key, &current_task, val);
-
-                if let Some(deadline) = deadline {
-                    let now = Duration::from_nanos(current_ticks());
-                    to = deadline < now;
-                }
-                if timeout == 0 || !to {
-                    yield_now();
-                }
-                // If we were woken (and `unqueued`), we succeeded, whatever.
-                // TODO: `plist_del`, not just iterate all the list
-                if !futex_unqueue(key, &current_task) {
-                    return Ok(0);
-                }
-                if to {
-                    return Err(SyscallError::ETIMEDOUT);
-                }
+            futex_wait(vaddr, key, val, FUTEX_BITSET_MATCH_ANY, timeout)?;
+            Ok(0)
+        }
+        FutexFlags::WaitBitset => {
+            if val3 == 0 {
+                return Err(SyscallError::EINVAL);
             }
+            futex_wait(vaddr, key, val, val3, timeout)?;
+            Ok(0)
         }
-        FutexFlags::Wake => {
-            let mut ret = 0;
-            let key = get_futex_key(vaddr, futex_op);
-            // // 当前任务释放了锁，所以不需要再次释放
-            let mut futex_wait_task = FUTEX_WAIT_TASK.lock();
-            if futex_wait_task.contains_key(&key) {
-                let wait_list = futex_wait_task.get_mut(&key).unwrap();
-                info!("now task: {}", wait_list.len());
-                while let Some((task, _)) = wait_list.pop_front() {
-                    // 唤醒一个正在等待的任务
-                    // WAIT_FOR_FUTEX.notify_task(false, &task);
-                    ret += 1;
-                    if ret == val {
-                        break;
-                    }
-                }
+        FutexFlags::Wake => Ok(futex_wake(key, val as usize, FUTEX_BITSET_MATCH_ANY)),
+        FutexFlags::WakeBitset => {
+            if val3 == 0 {
+                return Err(SyscallError::EINVAL);
             }
-            drop(futex_wait_task);
-            yield_now();
-            Ok(ret as usize)
+            Ok(futex_wake(key, val as usize, val3))
         }
         FutexFlags::Requeue => {
-            futex_requeue(val, val2, vaddr, vaddr2);
+            let key2 = get_futex_key(vaddr2, futex_op)?;
+            futex_requeue(key, key2, val as usize, val2, None)
+        }
+        FutexFlags::CmpRequeue => {
+            let key2 = get_futex_key(vaddr2, futex_op)?;
+            futex_requeue(key, key2, val as usize, val2, Some((vaddr, val3)))
+        }
+        FutexFlags::WakeOp => {
+            let key2 = get_futex_key(vaddr2, futex_op)?;
+            futex_wake_op(key, key2, vaddr2, val as usize, val2, val3)
+        }
+        FutexFlags::LockPi => {
+            futex_lock_pi(vaddr, key, timeout, false)?;
+            Ok(0)
+        }
+        FutexFlags::TrylockPi => {
+            futex_lock_pi(vaddr, key, None, true)?;
             Ok(0)
         }
-        _ => Err(SyscallError::EINVAL),
+        FutexFlags::UnlockPi => {
+            futex_unlock_pi(vaddr, key)?;
+            Ok(0)
+        }
+        FutexFlags::Unsupported => Err(SyscallError::ENOSYS),
     }
 }
 
@@ -145,7 +121,7 @@ pub fn futex(
 /// * `vaddr: usize`
 /// * `futex_op: i32`
 /// * `futex_val: u32`
-/// * `time_out_val: usize`
+/// * `time_out_val: usize`，等待类的操作是指向超时时间的指针，其他操作是数值`val2`
 /// * `vaddr2: usize`
 /// * `val3: u32`
 pub fn syscall_futex(args: [usize; 6]) -> SyscallResult {
@@ -155,14 +131,12 @@ pub fn syscall_futex(args: [usize; 6]) -> SyscallResult {
     let time_out_val = args[3];
     let vaddr2 = args[4];
     let val3 = args[5] as u32;
-    let timeout = if time_out_val != 0 {
-        let time_sepc: TimeSecs = unsafe { *(time_out_val as *const TimeSecs) };
-        time_sepc.turn_to_nanos()
+    let flag = FutexFlags::new(futex_op);
+    let timeout = if flag.has_timeout() {
+        futex_deadline(&flag, futex_op, time_out_val as *const TimeSecs)?
     } else {
-        // usize::MAX
-        0
+        None
     };
-    // 释放锁，防止任务无法被调度
     match futex(
         vaddr.into(),
         futex_op,
@@ -177,58 +151,47 @@ pub fn syscall_futex(args: [usize; 6]) -> SyscallResult {
     }
 }
 
-// /// 内核只发挥存储的作用
-// /// 但要保证head对应的地址已经被分配
-// /// # Arguments
-// /// * head: usize
-// /// * len: usize
-// pub fn syscall_set_robust_list(args: [usize; 6]) -> SyscallResult {
-//     let head = args[0];
-//     let len = args[1];
-//     let process = current_process();
-//     if len != core::mem::size_of::<RobustList>() {
-//         return Err(SyscallError::EINVAL);
-//     }
-//     let curr_id = current_task().id().as_u64();
-//     if process.manual_alloc_for_lazy(head.into()).is_ok() {
-//         let mut robust_list = process.robust_list.lock();
-//         robust_list.insert(curr_id, FutexRobustList::new(head, len));
-//         Ok(0)
-//     } else {
-//         Err(SyscallError::EINVAL)
-//     }
-// }
-//
-// /// 取出对应线程的robust list
-// /// # Arguments
-// /// * pid: i32
-// /// * head: *mut usize
-// /// * len: *mut usize
-// pub fn syscall_get_robust_list(args: [usize; 6]) -> SyscallResult {
-//     let pid = args[0] as i32;
-//     let head = args[1] as *mut usize;
-//     let len = args[2] as *mut usize;
-//
-//     if pid == 0 {
-//         let process = current_process();
-//         let curr_id = current_task().id().as_u64();
-//         if process
-//             .manual_alloc_for_lazy((head as usize).into())
-//             .is_ok()
-//         {
-//             let robust_list = process.robust_list.lock();
-//             if robust_list.contains_key(&curr_id) {
-//                 let list = robust_list.get(&curr_id).unwrap();
-//                 unsafe {
-//                     *head = list.head;
-//                     *len = list.len;
-//                 }
-//             } else {
-//                 return Err(SyscallError::EPERM);
-//             }
-//             return Ok(0);
-//         }
-//         return Err(SyscallError::EPERM);
-//     }
-//     Err(SyscallError::EPERM)
-// }
+/// 内核只发挥存储的作用，线程退出时才访问链表
+/// # Arguments
+/// * head: usize
+/// * len: usize
+pub fn syscall_set_robust_list(args: [usize; 6]) -> SyscallResult {
+    let head = args[0];
+    let len = args[1];
+    if len != core::mem::size_of::<RobustListHead>() {
+        return Err(SyscallError::EINVAL);
+    }
+    let curr_id = current().id().as_u64();
+    ROBUST_LISTS
+        .lock()
+        .insert(curr_id, FutexRobustList::new(head, len));
+    Ok(0)
+}
+
+/// 取出对应线程的robust list，`pid`为 0 时是当前线程
+///
+/// 没有登记过的线程报告空的链表。
+/// # Arguments
+/// * pid: i32
+/// * head: *mut usize
+/// * len: *mut usize
+pub fn syscall_get_robust_list(args: [usize; 6]) -> SyscallResult {
+    let pid = args[0] as i32;
+    let head = args[1] as *mut usize;
+    let len = args[2] as *mut usize;
+    if head.is_null() || len.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let tid = match pid {
+        0 => current().id().as_u64(),
+        pid if pid > 0 && TID2PC.lock().contains_key(&(pid as u64)) => pid as u64,
+        _ => return Err(SyscallError::ESRCH),
+    };
+    let robust_list = ROBUST_LISTS.lock();
+    let list = robust_list.get(&tid);
+    unsafe {
+        *head = list.map_or(0, |list| list.head);
+        *len = core::mem::size_of::<RobustListHead>();
+    }
+    Ok(0)
+}
@@ -1,8 +1,10 @@
+mod cred;
 mod futex;
 mod schedule;
 mod signal;
 mod task;
 mod utils;
+pub use cred::*;
 pub use futex::*;
 pub use schedule::*;
 pub use signal::*;
@@ -2,8 +2,8 @@ use crate::{
     linux_env::{
         axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags, SeekFrom},
         linux_fs::{
-            fd_manager::{FDM, alloc_fd},
-            link::{FilePath, create_link},
+            fd_manager::current_fd_manager,
+            link::FilePath,
             utils::{UtilsError, deal_path, has_permission},
         },
     },
@@ -12,8 +12,9 @@ use crate::{
         syscall_fs::ctype::{
             dir::{get_dir_desc, new_dir},
             epoll::{EpollCtl, EpollEvent, EpollEventType, EpollFile},
-            file::{new_fd, new_inode},
+            file::new_fd,
         },
+        unimplemented_syscall,
     },
 };
 use alloc::{string::ToString, sync::Arc, vec};
@@ -35,8 +36,8 @@ use core::slice::{from_raw_parts, from_raw_parts_mut};
 /// * `pid` - usize
 /// * `cpu_set_size` - usize
 /// * `mask` - *mut usize
-pub fn syscall_sched_getaffinity(args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+pub fn syscall_sched_getaffinity(_args: [usize; 6]) -> SyscallResult {
+    unimplemented_syscall("sched_getaffinity")
     //    let pid = args[0];
     //    let cpu_set_size = args[1];
     //    let mask = args[2] as *mut usize;
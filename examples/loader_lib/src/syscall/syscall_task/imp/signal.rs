@@ -1,83 +1,59 @@
+use alloc::vec::Vec;
+
+use axlog::{debug, info};
+use axtask::current;
+
 use crate::{
     linux_env::{
-        axfs_ext::api::{FileIO, FileIOType, Kstat, OpenFlags, SeekFrom},
-        linux_fs::{
-            fd_manager::{FDM, alloc_fd},
-            link::{FilePath, create_link},
-            utils::{UtilsError, deal_path, has_permission},
-        },
-    },
-    syscall::{
-        IoVec, O_CLOEXEC, StMode, SyscallError, SyscallResult, normal_file_mode,
-        syscall_fs::ctype::{
-            dir::{get_dir_desc, new_dir},
-            epoll::{EpollCtl, EpollEvent, EpollEventType, EpollFile},
-            file::{new_fd, new_inode},
+        process_ext::{api::current_process, process::PID2PC},
+        signal_ext::{
+            action::SigAction,
+            api::{check_signo, restore_frame, send_signal_to_process, send_signal_to_thread},
+            signal_no::{MAX_SIG, SignalNo},
         },
     },
+    syscall::{SyscallError, SyscallResult},
 };
-use alloc::{string::ToString, sync::Arc, vec};
-use axerrno::AxError;
-use axfs::api::{Permissions, lookup};
-use axlog::{debug, error, info, warn};
-use core::slice::{from_raw_parts, from_raw_parts_mut};
+
+/// 内核中信号集的大小
+const SIGSET_SIZE_IN_BYTE: usize = 8;
+
+/// `rt_sigprocmask`中`how`的取值
+const SIG_BLOCK: usize = 0;
+const SIG_UNBLOCK: usize = 1;
+const SIG_SETMASK: usize = 2;
 
 /// # Arguments
 /// * `signum` - usize
 /// * `action` - *const SigAction
 /// * `old_action` - *mut SigAction
-pub fn syscall_sigaction(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let signum = args[0];
-    //    let action = args[1] as *const SigAction;
-    //    let old_action = args[2] as *mut SigAction;
-    //    info!(
-    //        "signum: {}, action: {:X}, old_action: {:X}",
-    //        signum, action as usize, old_action as usize
-    //    );
-    //    if signum == SignalNo::SIGKILL as usize || signum == SignalNo::SIGSTOP as usize {
-    //        // 特殊参数不能被覆盖
-    //        return Err(SyscallError::EPERM);
-    //    }
-    //
-    //    let current_process = current_process();
-    //    let mut signal_modules = current_process.signal_modules.lock();
-    //    let signal_module = signal_modules
-    //        .get_mut(&current_task().id().as_u64())
-    //        .unwrap();
-    //    let mut signal_handler = signal_module.signal_handler.lock();
-    //    let old_address = old_action as usize;
-    //
-    //    if old_address != 0 {
-    //        // old_address非零说明要求写入到这个地址
-    //        // 此时要检查old_address是否在某一个段中
-    //        if current_process
-    //            .manual_alloc_for_lazy(old_address.into())
-    //            .is_err()
-    //        {
-    //            // 无法分配
-    //            return Err(SyscallError::EPERM);
-    //        }
-    //        if let Some(action) = signal_handler.get_action(signum) {
-    //            // 将原有的action存储到old_address
-    //            unsafe {
-    //                *old_action = *action;
-    //            }
-    //        }
-    //    }
-    //
-    //    let new_address = action as usize;
-    //    if new_address != 0 {
-    //        if current_process
-    //            .manual_alloc_for_lazy(new_address.into())
-    //            .is_err()
-    //        {
-    //            // 无法分配
-    //            return Err(SyscallError::EPERM);
-    //        }
-    //        unsafe { signal_handler.set_action(signum, action) };
-    //    }
-    //    Ok(0)
+pub fn syscall_sigaction(args: [usize; 6]) -> SyscallResult {
+    let signum = args[0];
+    let action = args[1] as *const SigAction;
+    let old_action = args[2] as *mut SigAction;
+    info!(
+        "signum: {}, action: {:X}, old_action: {:X}",
+        signum, action as usize, old_action as usize
+    );
+    if signum == 0 || signum > MAX_SIG {
+        return Err(SyscallError::EINVAL);
+    }
+    if !action.is_null()
+        && (signum == SignalNo::SIGKILL as usize || signum == SignalNo::SIGSTOP as usize)
+    {
+        // 特殊参数不能被覆盖
+        return Err(SyscallError::EINVAL);
+    }
+
+    let process = current_process();
+    let mut signals = process.signals.lock();
+    if !old_action.is_null() {
+        unsafe { *old_action = signals.action(signum) };
+    }
+    if !action.is_null() {
+        signals.set_action(signum, unsafe { *action });
+    }
+    Ok(0)
 }
 
 /// # Arguments
@@ -85,78 +61,133 @@ pub fn syscall_sigaction(_args: [usize; 6]) -> SyscallResult {
 /// * `new_mask` - *const usize
 /// * `old_mask` - *mut usize
 /// * `sigsetsize` - usize, specifies the size in bytes of the signal sets in set and oldset, which is equal to sizeof(kernel_sigset_t)
-pub fn syscall_sigprocmask(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let flag = SigMaskFlag::from(args[0]);
-    //    let new_mask = args[1] as *const usize;
-    //    let old_mask = args[2] as *mut usize;
-    //    let sigsetsize = args[3];
-    //    if sigsetsize != SIGSET_SIZE_IN_BYTE {
-    //        // 若sigsetsize不是正确的大小，则返回错误
-    //        return Err(SyscallError::EINVAL);
-    //    }
-    //
-    //    let current_process = current_process();
-    //    if old_mask as usize != 0
-    //        && current_process
-    //            .manual_alloc_for_lazy((old_mask as usize).into())
-    //            .is_err()
-    //    {
-    //        return Err(SyscallError::EFAULT);
-    //    }
-    //    if new_mask as usize != 0
-    //        && current_process
-    //            .manual_alloc_for_lazy((new_mask as usize).into())
-    //            .is_err()
-    //    {
-    //        return Err(SyscallError::EPERM);
-    //    }
-    //
-    //    let mut signal_modules = current_process.signal_modules.lock();
-    //    let signal_module = signal_modules
-    //        .get_mut(&current_task().id().as_u64())
-    //        .unwrap();
-    //    if old_mask as usize != 0 {
-    //        unsafe {
-    //            *old_mask = signal_module.signal_set.mask;
-    //        }
-    //    }
-    //
-    //    if new_mask as usize != 0 {
-    //        let now_mask = unsafe { *new_mask };
-    //        match flag {
-    //            SigMaskFlag::Block => {
-    //                signal_module.signal_set.mask |= now_mask;
-    //            }
-    //            SigMaskFlag::Unblock => {
-    //                signal_module.signal_set.mask &= !now_mask;
-    //            }
-    //            SigMaskFlag::Setmask => {
-    //                signal_module.signal_set.mask = now_mask;
-    //            }
-    //        }
-    //    }
-    //    Ok(0)
+pub fn syscall_sigprocmask(args: [usize; 6]) -> SyscallResult {
+    let how = args[0];
+    let new_mask = args[1] as *const u64;
+    let old_mask = args[2] as *mut u64;
+    let sigsetsize = args[3];
+    if sigsetsize != SIGSET_SIZE_IN_BYTE {
+        // 若sigsetsize不是正确的大小，则返回错误
+        return Err(SyscallError::EINVAL);
+    }
+
+    let tid = current().id().as_u64();
+    let process = current_process();
+    let mut signals = process.signals.lock();
+    let mask = signals.blocked(tid);
+    if !old_mask.is_null() {
+        unsafe { *old_mask = mask };
+    }
+    if !new_mask.is_null() {
+        let new_mask = unsafe { *new_mask };
+        let mask = match how {
+            SIG_BLOCK => mask | new_mask,
+            SIG_UNBLOCK => mask & !new_mask,
+            SIG_SETMASK => new_mask,
+            _ => return Err(SyscallError::EINVAL),
+        };
+        signals.set_blocked(tid, mask);
+    }
+    Ok(0)
+}
+
+/// 向进程发送信号
+///
+/// 进程组尚未实现：`pid`为 0 时发给当前进程，小于 -1 时发给进程号为`-pid`的进程，
+/// 为 -1 时发给除 init 以外的所有进程。
+/// # Arguments
+/// * `pid` - isize
+/// * `signum` - usize
+pub fn syscall_kill(args: [usize; 6]) -> SyscallResult {
+    let pid = args[0] as isize;
+    let signum = args[1];
+    debug!("kill: pid = {}, signum = {}", pid, signum);
+    match pid {
+        -1 => {
+            check_signo(signum)?;
+            let targets: Vec<u64> = PID2PC
+                .lock()
+                .values()
+                .filter(|process| !process.is_init() && !process.is_zombie())
+                .map(|process| process.pid())
+                .collect();
+            if targets.is_empty() {
+                return Err(SyscallError::ESRCH);
+            }
+            for pid in targets {
+                // 期间退出并被回收的进程不算错误
+                let _ = send_signal_to_process(pid, signum);
+            }
+        }
+        0 => send_signal_to_process(current_process().pid(), signum)?,
+        pid => send_signal_to_process(pid.unsigned_abs() as u64, signum)?,
+    }
+    Ok(0)
 }
 
 /// 向tid指定的线程发送信号
 /// # Arguments
 /// * `tid` - isize
 /// * `signum` - isize
-pub fn syscall_tkill(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let tid = args[0] as isize;
-    //    let signum = args[1] as isize;
-    //    debug!(
-    //        "cpu: {}, send singal: {} to: {}",
-    //        this_cpu_id(),
-    //        signum,
-    //        tid
-    //    );
-    //    if tid > 0 && signum > 0 {
-    //        let _ = axprocess::signal::send_signal_to_thread(tid, signum);
-    //        Ok(0)
-    //    } else {
-    //        Err(SyscallError::EINVAL)
-    //    }
+pub fn syscall_tkill(args: [usize; 6]) -> SyscallResult {
+    let tid = args[0] as isize;
+    let signum = args[1];
+    debug!("tkill: tid = {}, signum = {}", tid, signum);
+    if tid <= 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    send_signal_to_thread(None, tid as u64, signum)?;
+    Ok(0)
+}
+
+/// 向进程`tgid`中的线程`tid`发送信号
+/// # Arguments
+/// * `tgid` - isize
+/// * `tid` - isize
+/// * `signum` - isize
+pub fn syscall_tgkill(args: [usize; 6]) -> SyscallResult {
+    let tgid = args[0] as isize;
+    let tid = args[1] as isize;
+    let signum = args[2];
+    debug!(
+        "tgkill: tgid = {}, tid = {}, signum = {}",
+        tgid, tid, signum
+    );
+    if tgid <= 0 || tid <= 0 {
+        return Err(SyscallError::EINVAL);
+    }
+    send_signal_to_thread(Some(tgid as u64), tid as u64, signum)?;
+    Ok(0)
+}
+
+/// 临时换用`mask`并等待信号，信号处理后返回`EINTR`，处理函数返回后恢复原掩码
+/// # Arguments
+/// * `mask` - *const u64
+/// * `sigsetsize` - usize
+pub fn syscall_sigsuspend(args: [usize; 6]) -> SyscallResult {
+    let mask = args[0] as *const u64;
+    let sigsetsize = args[1];
+    if sigsetsize != SIGSET_SIZE_IN_BYTE {
+        return Err(SyscallError::EINVAL);
+    }
+    if mask.is_null() {
+        return Err(SyscallError::EFAULT);
+    }
+    let tid = current().id().as_u64();
+    let process = current_process();
+    process.signals.lock().suspend(tid, unsafe { *mask });
+    process.wait_signal(tid);
+    Err(SyscallError::EINTR)
+}
+
+/// 从信号处理函数返回，恢复进入处理函数前的掩码
+///
+/// 处理函数直接返回到递送它的地方，那里已经会恢复掩码；这里只处理显式调用
+/// `rt_sigreturn`的情况，随后返回到调用者。
+pub fn syscall_sigreturn() -> SyscallResult {
+    if restore_frame(None) {
+        Ok(0)
+    } else {
+        Err(SyscallError::EINVAL)
+    }
 }
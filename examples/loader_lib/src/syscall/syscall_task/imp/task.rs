@@ -1,21 +1,159 @@
-use crate::syscall::SyscallResult;
+use alloc::{string::String, vec::Vec};
 
+use axerrno::AxError;
+use axlog::{debug, info};
+use axtask::current;
+
+#[cfg(target_arch = "riscv64")]
+use crate::linux_env::{axhal_ext::user_trap_frame, process_ext::process::fork_user};
+use crate::{
+    config::{USER_SPACE_BASE, USER_SPACE_END, USER_STACK_SIZE},
+    elf::LoadError,
+    linux_env::{
+        linux_fs::{
+            link::{AT_FDCWD, raw_ptr_to_ref_str},
+            utils::{UtilsError, deal_path},
+        },
+        process_ext::{
+            api::{current_process, exec_current, exit_current},
+            flags::{CloneFlags, WaitFlags},
+            process::{PID2PC, fork_current},
+        },
+    },
+    load::{Command, check_program},
+    syscall::{RLIMIT_AS, RLIMIT_NOFILE, RLIMIT_STACK, RLimit, SyscallError, SyscallResult},
+};
+
+/// 结束当前线程
+/// # Arguments
+/// * `exit_code` - i32
+pub fn syscall_exit(args: [usize; 6]) -> ! {
+    let exit_code = args[0] as i32;
+    info!("exit: exit_code = {}", exit_code);
+    exit_current(exit_code, false)
+}
+
+/// 结束当前进程
 /// # Arguments
 /// * `exit_code` - i32
-pub fn syscall_exit(_args: [usize; 6]) -> ! {
-    unimplemented!();
-    //    let exit_code = args[0] as i32;
-    //    info!("exit: exit_code = {}", exit_code);
-    // let cases = ["fcanf", "fgetwc_buffering", "lat_pipe"];
-    // let mut test_filter = TEST_FILTER.lock();
-    // for case in cases {
-    //     let case = case.to_string();
-    //     if test_filter.contains_key(&case) {
-    //         test_filter.remove(&case);
-    //     }
-    // }
-    // drop(test_filter);
-    //    exit_current_task(exit_code)
+pub fn syscall_exit_group(args: [usize; 6]) -> ! {
+    let exit_code = args[0] as i32;
+    info!("exit_group: exit_code = {}", exit_code);
+    exit_current(exit_code, true)
+}
+
+/// 只支持不共享地址空间的`clone`，即`fork`。线程由`pthread`接口创建。
+///
+/// # Arguments
+/// * `flags` - usize，低 8 位是子进程退出时发给父进程的信号
+/// * `stack` - usize，S 模式的程序必须为 0，用户态程序的子进程换用这个栈
+/// * `ptid` - *mut i32
+/// * `tls` - usize，用户态程序指定`CLONE_SETTLS`时作为子进程的`tp`
+/// * `ctid` - *mut i32
+pub fn syscall_clone(args: [usize; 6]) -> SyscallResult {
+    let flags = CloneFlags::from_bits_truncate((args[0] & !0xff) as u32);
+    let stack = args[1];
+    let ptid = args[2];
+    let ctid = args[4];
+    let user = current_process().memory_set.lock().is_user();
+    if (stack != 0 && !user)
+        || flags.contains(CloneFlags::CLONE_THREAD)
+        || (flags.contains(CloneFlags::CLONE_VM) && !flags.contains(CloneFlags::CLONE_VFORK))
+    {
+        debug!("[clone] unsupported flags {:?} stack {:#x}", flags, stack);
+        return Err(SyscallError::EINVAL);
+    }
+    let pid = match user {
+        #[cfg(target_arch = "riscv64")]
+        true => {
+            let tls = flags.contains(CloneFlags::CLONE_SETTLS).then_some(args[3]);
+            fork_user(user_trap_frame(), flags, ctid, stack, tls)?
+        }
+        // 此后直到返回都不能持有堆上的对象，子进程也从这里返回
+        _ => fork_current(flags, ctid)?,
+    };
+    if pid != 0 && flags.contains(CloneFlags::CLONE_PARENT_SETTID) && ptid != 0 {
+        unsafe { *(ptid as *mut i32) = pid as i32 };
+    }
+    Ok(pid as isize)
+}
+
+/// 把以空指针结尾的字符串指针数组复制出来
+fn copy_str_array(array: *const *const u8) -> Vec<String> {
+    let mut strings = Vec::new();
+    if array.is_null() {
+        return strings;
+    }
+    for i in 0.. {
+        let ptr = unsafe { *array.add(i) };
+        if ptr.is_null() {
+            break;
+        }
+        strings.push(String::from(unsafe { raw_ptr_to_ref_str(ptr) }));
+    }
+    strings
+}
+
+/// 用新程序替换当前进程，成功时不返回
+///
+/// 清空地址空间之前完成装载所需的全部检查（见[`check_program`]），之后的装载错误无法
+/// 再返回给调用者。缺少依赖的共享对象时返回`ENOENT`，其他问题返回`ENOEXEC`。
+/// # Arguments
+/// * `path` - *const u8
+/// * `argv` - *const *const u8
+/// * `envp` - *const *const u8
+pub fn syscall_execve(args: [usize; 6]) -> SyscallResult {
+    let path = deal_path(AT_FDCWD, Some(args[0] as *const u8), false).map_err(|e| match e {
+        UtilsError::CannotAcce | UtilsError::NULL => SyscallError::EFAULT,
+        UtilsError::StrTooLong => SyscallError::ENAMETOOLONG,
+        _ => SyscallError::ENOENT,
+    })?;
+    let path = String::from(path.follow_link()?.path());
+    let cred = current_process().cred().fs_credentials();
+    match axfs::api::check_access(&path, &cred, 0o1) {
+        Ok(()) => {}
+        Err(AxError::PermissionDenied) => return Err(SyscallError::EACCES),
+        Err(_) => return Err(SyscallError::ENOENT),
+    }
+    let data = axfs::api::read(&path).map_err(|_| SyscallError::ENOENT)?;
+    check_program(&data).map_err(|e| {
+        debug!("execve: cannot load {}: {}", path, e);
+        match e {
+            LoadError::MissingDependency => SyscallError::ENOENT,
+            _ => SyscallError::ENOEXEC,
+        }
+    })?;
+    drop(data);
+    let cmd = Command {
+        path: Some(path),
+        argv: copy_str_array(args[1] as *const *const u8),
+        envp: copy_str_array(args[2] as *const *const u8),
+    };
+    info!("execve: {:?} {:?}", cmd.path, cmd.argv);
+    exec_current(cmd)
+}
+
+/// 等待子进程退出并回收它
+///
+/// `pid`为 -1 时等待任意子进程；进程组尚未实现，0 和小于 -1 同样等待任意子进程。
+/// # Arguments
+/// * `pid` - isize
+/// * `wstatus` - *mut i32
+/// * `options` - WaitFlags
+pub fn syscall_wait4(args: [usize; 6]) -> SyscallResult {
+    let pid = args[0] as isize as i64;
+    let wstatus = args[1] as *mut i32;
+    let options = WaitFlags::from_bits_truncate(args[2] as u32);
+    let nohang = options.contains(WaitFlags::WNOHANG);
+    match current_process().wait_child(pid, nohang)? {
+        Some((pid, status)) => {
+            if !wstatus.is_null() {
+                unsafe { *wstatus = status };
+            }
+            Ok(pid as isize)
+        }
+        None => Ok(0),
+    }
 }
 
 /// 设置任务资源限制
@@ -28,65 +166,90 @@ pub fn syscall_exit(_args: [usize; 6]) -> ! {
 /// * `new_limit - *const RLimit`
 /// * `old_limit - *mut RLimit`
 pub fn syscall_prlimit64(args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
-    //    let pid = args[0];
-    //    let resource = args[1] as i32;
-    //    let new_limit = args[2] as *const RLimit;
-    //    let old_limit = args[3] as *mut RLimit;
-    //    // 当pid不为0，其实没有权利去修改其他的进程的资源限制
-    //    let curr_process = current_process();
-    //    if pid == 0 || pid == curr_process.pid() as usize {
-    //        match resource {
-    //            RLIMIT_STACK => {
-    //                if old_limit as usize != 0 {
-    //                    unsafe {
-    //                        *old_limit = RLimit {
-    //                            rlim_cur: TASK_STACK_SIZE as u64,
-    //                            rlim_max: TASK_STACK_SIZE as u64,
-    //                        };
-    //                    }
-    //                }
-    //            }
-    //            RLIMIT_NOFILE => {
-    //                // 仅支持修改最大文件数
-    //                if old_limit as usize != 0 {
-    //                    let limit = curr_process.fd_manager.get_limit();
-    //                    unsafe {
-    //                        *old_limit = RLimit {
-    //                            rlim_cur: limit as u64,
-    //                            rlim_max: limit as u64,
-    //                        };
-    //                    }
-    //                }
-    //                if new_limit as usize != 0 {
-    //                    let new_limit = unsafe { (*new_limit).rlim_cur };
-    //                    curr_process.fd_manager.set_limit(new_limit);
-    //                }
-    //            }
-    //            RLIMIT_AS => {
-    //                const USER_MEMORY_LIMIT: usize = 0xffff_ffff;
-    //                if old_limit as usize != 0 {
-    //                    unsafe {
-    //                        *old_limit = RLimit {
-    //                            rlim_cur: USER_MEMORY_LIMIT as u64,
-    //                            rlim_max: USER_MEMORY_LIMIT as u64,
-    //                        };
-    //                    }
-    //                }
-    //            }
-    //            _ => {}
-    //        }
-    //    }
-    //    Ok(0)
+    let pid = args[0] as u64;
+    let resource = args[1] as i32;
+    let new_limit = args[2] as *const RLimit;
+    let old_limit = args[3] as *mut RLimit;
+    let process = if pid == 0 {
+        current_process()
+    } else {
+        PID2PC
+            .lock()
+            .get(&pid)
+            .cloned()
+            .ok_or(SyscallError::ESRCH)?
+    };
+    let fixed = |limit: usize| -> SyscallResult {
+        if !old_limit.is_null() {
+            unsafe {
+                *old_limit = RLimit {
+                    rlim_cur: limit as u64,
+                    rlim_max: limit as u64,
+                };
+            }
+        }
+        Ok(0)
+    };
+    match resource {
+        RLIMIT_STACK => fixed(USER_STACK_SIZE),
+        RLIMIT_AS => fixed(USER_SPACE_END - USER_SPACE_BASE),
+        RLIMIT_NOFILE => {
+            // 文件描述符表的长度上限，软硬上限相同
+            let fd_manager = process.fd_manager();
+            if !old_limit.is_null() {
+                let limit = fd_manager.get_limit();
+                unsafe {
+                    *old_limit = RLimit {
+                        rlim_cur: limit,
+                        rlim_max: limit,
+                    };
+                }
+            }
+            if !new_limit.is_null() {
+                let new_limit = unsafe { *new_limit };
+                if new_limit.rlim_cur > new_limit.rlim_max {
+                    return Err(SyscallError::EINVAL);
+                }
+                fd_manager.set_limit(new_limit.rlim_cur);
+            }
+            Ok(0)
+        }
+        _ => {
+            debug!("prlimit64: unsupported resource {}", resource);
+            Ok(0)
+        }
+    }
+}
+
+/// 设置当前进程创建文件时的权限掩码，返回旧的掩码
+///
+/// # Arguments
+/// * `mask - u32`
+pub fn syscall_umask(args: [usize; 6]) -> SyscallResult {
+    let mask = (args[0] & 0o777) as i32;
+    Ok(current_process().fs.set_mask(mask) as isize)
 }
 
 /// 当前不涉及多核情况
 pub fn syscall_getpid() -> SyscallResult {
-    unimplemented!();
-    //    Ok(current_process().pid() as isize)
+    Ok(current_process().pid() as isize)
+}
+
+/// 获取父进程号，init 进程返回 0
+pub fn syscall_getppid() -> SyscallResult {
+    Ok(current_process().parent() as isize)
 }
 
-/// 获取有效用户 id，即相当于哪个用户的权限。在实现多用户权限前默认为最高权限
-pub fn syscall_geteuid() -> SyscallResult {
-    Ok(0)
+/// 获取当前线程号
+pub fn syscall_gettid() -> SyscallResult {
+    Ok(current().id().as_u64() as isize)
+}
+
+/// 返回当前线程号
+///
+/// 不记录`tidptr`：`clone`不支持`CLONE_THREAD`，进程退出时没有等待它的线程。
+/// # Arguments
+/// * `tidptr` - *mut i32
+pub fn syscall_set_tid_address(_args: [usize; 6]) -> SyscallResult {
+    syscall_gettid()
 }
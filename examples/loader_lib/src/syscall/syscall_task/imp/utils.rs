@@ -1,6 +1,5 @@
-use crate::{
-    linux_env::linux_fs::futex::{FUTEX_WAIT_TASK, FutexKey, WAIT_FOR_FUTEX, get_futex_key},
-    syscall::{FutexFlags, SyscallError, SyscallResult, TimeSecs, TimeVal},
+use crate::syscall::{
+    FutexFlags, SyscallError, SyscallResult, TimeSecs, TimeVal, unimplemented_syscall,
 };
 use alloc::collections::VecDeque;
 use axhal::{
@@ -62,7 +61,7 @@ pub fn syscall_clock_get_time(args: [usize; 6]) -> SyscallResult {
 /// * `req` - *const TimeSecs
 /// * `rem` - *mut TimeSecs
 pub fn syscall_sleep(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+    unimplemented_syscall("nanosleep")
     //    let req = args[0] as *const TimeSecs;
     //    let rem = args[1] as *mut TimeSecs;
     //    let req_time = unsafe { *req };
@@ -110,7 +109,7 @@ pub fn syscall_sleep(_args: [usize; 6]) -> SyscallResult {
 ///
 /// 若睡眠被信号处理打断或者遇到未知错误，则返回对应错误码
 pub fn syscall_clock_nanosleep(_args: [usize; 6]) -> SyscallResult {
-    unimplemented!();
+    unimplemented_syscall("clock_nanosleep")
     //     let id = args[0];
     //     let flags = args[1];
     //     let request = args[2] as *const TimeSecs;
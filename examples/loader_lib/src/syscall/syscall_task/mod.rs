@@ -2,7 +2,7 @@
 
 mod task_syscall_id;
 
-use super::SyscallResult;
+use super::{SyscallResult, unimplemented_syscall};
 pub use task_syscall_id::TaskSyscallId::{self, *};
 
 mod imp;
@@ -13,8 +13,8 @@ pub use imp::*;
 pub fn task_syscall(syscall_id: task_syscall_id::TaskSyscallId, args: [usize; 6]) -> SyscallResult {
     match syscall_id {
         EXIT => syscall_exit(args),
-        //        EXECVE => syscall_exec(args),
-        //        CLONE => syscall_clone(args),
+        EXECVE => syscall_execve(args),
+        CLONE => syscall_clone(args),
         //        CLONE3 => syscall_clone3(args),
         NANO_SLEEP => syscall_sleep(args),
         //        SCHED_YIELD => syscall_yield(),
@@ -24,43 +24,44 @@ pub fn task_syscall(syscall_id: task_syscall_id::TaskSyscallId, args: [usize; 6]
         //        GETPGID => syscall_getpgid(),
         //        SETPGID => syscall_setpgid(args),
         GETPID => syscall_getpid(),
-        //        GETPPID => syscall_getppid(),
-        //        WAIT4 => syscall_wait4(args),
+        GETPPID => syscall_getppid(),
+        WAIT4 => syscall_wait4(args),
         //        GETRANDOM => syscall_getrandom(args),
-        //        #[cfg(feature = "signal")]
-        //        SIGSUSPEND => syscall_sigsuspend(args),
-        //        #[cfg(feature = "signal")]
+        SIGSUSPEND => syscall_sigsuspend(args),
         SIGACTION => syscall_sigaction(args),
-        //        #[cfg(feature = "signal")]
-        //        KILL => syscall_kill(args),
-        // #[cfg(feature = "signal")]
+        KILL => syscall_kill(args),
         TKILL => syscall_tkill(args),
-        //        #[cfg(feature = "signal")]
-        //        TGKILL => syscall_tkill(args),
-        //        #[cfg(feature = "signal")]
+        TGKILL => syscall_tgkill(args),
         SIGPROCMASK => syscall_sigprocmask(args),
-        //        #[cfg(feature = "signal")]
-        //        SIGRETURN => syscall_sigreturn(),
-        EXIT_GROUP => syscall_exit(args),
-        //        SET_TID_ADDRESS => syscall_set_tid_address(args),
-        //        PRLIMIT64 => syscall_prlimit64(args),
+        SIGRETURN => syscall_sigreturn(),
+        EXIT_GROUP => syscall_exit_group(args),
+        SET_TID_ADDRESS => syscall_set_tid_address(args),
+        PRLIMIT64 => syscall_prlimit64(args),
         CLOCK_GET_TIME => syscall_clock_get_time(args),
-        //        GETUID => syscall_getuid(),
+        GETUID => syscall_getuid(),
         GETEUID => syscall_geteuid(),
-        //        GETGID => syscall_getgid(),
-        //        GETEGID => syscall_getegid(),
-        //        GETTID => syscall_gettid(),
+        GETGID => syscall_getgid(),
+        GETEGID => syscall_getegid(),
+        SETUID => syscall_setuid(args),
+        SETGID => syscall_setgid(args),
+        SETREUID => syscall_setreuid(args),
+        SETREGID => syscall_setregid(args),
+        SETRESUID => syscall_setresuid(args),
+        SETRESGID => syscall_setresgid(args),
+        GETRESUID => syscall_getresuid(args),
+        GETRESGID => syscall_getresgid(args),
+        GETGROUPS => syscall_getgroups(args),
+        SETGROUPS => syscall_setgroups(args),
+        GETTID => syscall_gettid(),
         FUTEX => syscall_futex(args),
-        //        #[cfg(feature = "futex")]
-        //        SET_ROBUST_LIST => syscall_set_robust_list(args),
-        //        #[cfg(feature = "futex")]
-        //        GET_ROBUST_LIST => syscall_get_robust_list(args),
+        SET_ROBUST_LIST => syscall_set_robust_list(args),
+        GET_ROBUST_LIST => syscall_get_robust_list(args),
         //        SYSINFO => syscall_sysinfo(args),
         //        SETITIMER => syscall_settimer(args),
         //        GETTIMER => syscall_gettimer(args),
         //        SETSID => syscall_setsid(),
         //        GETRUSAGE => syscall_getrusage(args),
-        //        UMASK => syscall_umask(args),
+        UMASK => syscall_umask(args),
         //        // 不做处理即可
         //        SIGTIMEDWAIT => Ok(0),
         //        SYSLOG => Ok(0),
@@ -88,10 +89,6 @@ pub fn task_syscall(syscall_id: task_syscall_id::TaskSyscallId, args: [usize; 6]
         //        #[cfg(target_arch = "x86_64")]
         //        TIME => Ok(0),
         #[allow(unused)]
-        _ => {
-            panic!("Invalid Syscall Id: {:?}!", syscall_id);
-            // return -1;
-            // exit(-1)
-        }
+        _ => unimplemented_syscall(&alloc::format!("{:?}", syscall_id).to_lowercase()),
     }
 }
@@ -24,9 +24,19 @@ pub enum TaskSyscallId {
     SCHED_SETAFFINITY = 122,
     SCHED_GETAFFINITY = 123,
     GET_MEMPOLICY = 236,
+    SETREGID = 143,
+    SETGID = 144,
+    SETREUID = 145,
+    SETUID = 146,
+    SETRESUID = 147,
+    GETRESUID = 148,
+    SETRESGID = 149,
+    GETRESGID = 150,
     SETPGID = 154,
     GETPGID = 155,
     SETSID = 157,
+    GETGROUPS = 158,
+    SETGROUPS = 159,
     GETRUSAGE = 165,
     UMASK = 166,
     PRCTL = 167,
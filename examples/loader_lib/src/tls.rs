@@ -0,0 +1,155 @@
+//! 客户程序的静态线程局部存储（TLS）
+//!
+//! 装载器把主程序和各共享库的 `PT_TLS` 初始化镜像拼成一个模板，每个线程（包括主线程）
+//! 都从模板复制出自己的 TLS 块。riscv64 和 aarch64 使用 TLS variant I，各模块依次放在
+//! `tp` 之上；x86_64 使用 variant II，各模块依次放在 `tp` 之下，`tp` 处是指向自身的
+//! 线程控制块。
+//!
+//! 模板属于进程，在装载程序时替换。TLS 块映射在进程的 `mmap` 窗口中，`fork` 后子进程
+//! 得到写时复制的副本。
+
+use alloc::vec::Vec;
+
+use axhal::{
+    mem::{PAGE_SIZE_4K, VirtAddr},
+    paging::MappingFlags,
+};
+use axlog::debug;
+
+use crate::{
+    arch::{TLS_ABOVE_TP, TLS_GAP_ABOVE_TP, TLS_TCB_SIZE},
+    config::{MMAP_END, MMAP_START},
+    linux_env::process_ext::api::current_process,
+};
+
+/// 静态 TLS 的布局：按装载顺序为各模块分配相对 `tp` 的偏移
+pub struct TlsLayout {
+    /// 已分配部分的大小（variant I 含 `tp` 之上的空隙）
+    end: usize,
+    /// 各模块中最大的对齐要求
+    align: usize,
+}
+
+impl TlsLayout {
+    pub fn new() -> Self {
+        Self {
+            end: if TLS_ABOVE_TP { TLS_GAP_ABOVE_TP } else { 0 },
+            align: 1,
+        }
+    }
+
+    /// 为大小为 `size`、对齐要求为 `align` 的模块分配位置，返回它相对 `tp` 的偏移
+    ///
+    /// variant II 的偏移为负数，以补码表示。
+    pub fn add(&mut self, size: usize, align: usize) -> usize {
+        let align = align.max(1);
+        self.align = self.align.max(align);
+        if TLS_ABOVE_TP {
+            let offset = self.end.next_multiple_of(align);
+            self.end = offset + size;
+            offset
+        } else {
+            self.end = (self.end + size).next_multiple_of(align);
+            self.end.wrapping_neg()
+        }
+    }
+
+    /// 按布局生成全为 0 的模板
+    pub fn template(&self) -> TlsTemplate {
+        let align = self.align.max(core::mem::size_of::<usize>());
+        // variant II 中 `tp` 在所有模块之后，要满足各模块的对齐要求
+        let size = match TLS_ABOVE_TP {
+            true => self.end,
+            false => self.end.next_multiple_of(align),
+        };
+        TlsTemplate {
+            image: alloc::vec![0; size],
+            align,
+            tp_offset: if TLS_ABOVE_TP { 0 } else { size },
+        }
+    }
+}
+
+impl Default for TlsLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 静态 TLS 模板
+pub struct TlsTemplate {
+    /// 初始化镜像：`.tdata` 为文件内容，`.tbss` 已清零
+    image: Vec<u8>,
+    /// 各模块中最大的对齐要求
+    align: usize,
+    /// `tp` 相对镜像起始处的位置
+    tp_offset: usize,
+}
+
+impl TlsTemplate {
+    /// 没有 TLS 模块的模板
+    pub fn empty() -> Self {
+        TlsLayout::new().template()
+    }
+
+    /// 把某个模块已装载的 `.tdata` 内容复制到模板中相对 `tp` 为 `offset` 处
+    ///
+    /// `.tbss` 部分模板中本来就是 0，无需处理。
+    pub fn copy_module(&mut self, offset: usize, init: &[u8]) {
+        let start = self.tp_offset.wrapping_add(offset);
+        self.image[start..start + init.len()].copy_from_slice(init);
+    }
+
+    /// 一个 TLS 块（含线程控制块）占用的大小，按页对齐。对齐要求不超过一页。
+    fn block_size(&self) -> usize {
+        assert!(self.align <= PAGE_SIZE_4K, "TLS alignment exceeds a page");
+        (self.image.len() + TLS_TCB_SIZE)
+            .max(1)
+            .div_ceil(PAGE_SIZE_4K)
+            * PAGE_SIZE_4K
+    }
+}
+
+/// 在当前进程中为一个新线程映射并初始化 TLS 块，返回该线程应使用的 `tp`
+pub fn alloc_tls_block() -> usize {
+    let process = current_process();
+    let template = process.tls_template();
+    let block = process
+        .memory_set
+        .lock()
+        .map_anywhere(
+            template.block_size(),
+            MappingFlags::READ | MappingFlags::WRITE,
+            (VirtAddr::from(MMAP_START), VirtAddr::from(MMAP_END)),
+        )
+        .expect("Failed to map TLS block");
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            template.image.as_ptr(),
+            block.as_mut_ptr(),
+            template.image.len(),
+        );
+    }
+    let tp = block.as_usize() + template.tp_offset;
+    if TLS_TCB_SIZE != 0 {
+        unsafe { *(tp as *mut usize) = tp };
+    }
+    debug!(
+        "[TLS] alloc block @{:#x} size 0x{:x} tp {:#x}",
+        block.as_usize(),
+        template.image.len(),
+        tp
+    );
+    tp
+}
+
+/// 解除 [`alloc_tls_block`] 映射的 TLS 块，`tp` 是它返回的值
+pub fn dealloc_tls_block(tp: usize) {
+    let process = current_process();
+    let template = process.tls_template();
+    let block = tp - template.tp_offset;
+    let _ = process
+        .memory_set
+        .lock()
+        .unmap(VirtAddr::from(block), template.block_size());
+}
@@ -22,13 +22,7 @@ mod ramfs;
 
 use std::io::prelude::*;
 
-const LF: u8 = b'\n';
-const CR: u8 = b'\r';
-const DL: u8 = b'\x7f';
-const BS: u8 = b'\x08';
-const SPACE: u8 = b' ';
-
-const MAX_CMD_LEN: usize = 256;
+use axtty::{LineDiscipline, TtySignal};
 
 fn print_prompt() {
     print!(
@@ -43,40 +37,41 @@ fn main() {
     let mut stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
 
-    let mut buf = [0; MAX_CMD_LEN];
-    let mut cursor = 0;
+    let mut tty = LineDiscipline::new();
+    #[cfg(not(feature = "axstd"))]
+    {
+        // the terminal of the host echoes the input itself
+        let mut termios = tty.termios();
+        termios.lflag &= !axtty::ECHO;
+        tty.set_termios(termios);
+    }
+
+    let mut buf = [0; axtty::MAX_LINE + 1];
     cmd::run_cmd("help".as_bytes());
     print_prompt();
 
     loop {
-        if stdin.read(&mut buf[cursor..cursor + 1]).ok() != Some(1) {
+        let mut c = [0];
+        if stdin.read(&mut c).ok() != Some(1) {
             continue;
         }
-        if buf[cursor] == b'\x1b' {
-            buf[cursor] = b'^';
+        let signal = tty.input(c[0], |echo| stdout.write_all(echo).unwrap());
+        if signal == Some(TtySignal::Interrupt) {
+            // discard the line
+            println!();
+            print_prompt();
+            continue;
         }
-        match buf[cursor] {
-            CR | LF => {
+        stdout.flush().unwrap();
+        if tty.can_read(buf.len()) {
+            let len = tty.read(&mut buf);
+            if len == 0 {
+                // `^D` on an empty line
                 println!();
-                if cursor > 0 {
-                    cmd::run_cmd(&buf[..cursor]);
-                    cursor = 0;
-                }
-                print_prompt();
-            }
-            BS | DL => {
-                if cursor > 0 {
-                    stdout.write_all(&[BS, SPACE, BS]).unwrap();
-                    cursor -= 1;
-                }
-            }
-            0..=31 => {}
-            c => {
-                if cursor < MAX_CMD_LEN - 1 {
-                    stdout.write_all(&[c]).unwrap();
-                    cursor += 1;
-                }
+                cmd::run_cmd("exit".as_bytes());
             }
+            cmd::run_cmd(&buf[..len]);
+            print_prompt();
         }
     }
 }
@@ -5,6 +5,7 @@ echo "Loader my lib version"
 log="warn"
 qemu_log="y"
 type="default"
+arch="riscv64"
 
 # 定义枚举类型
 declare -A valid_types
@@ -16,7 +17,7 @@ valid_types=(
 )
 
 # 解析命令行参数
-options=$(getopt -o l:q:t --long log:,qemu_log:,type:, -- "$@")
+options=$(getopt -o l:q:t:a: --long log:,qemu_log:,type:,arch:, -- "$@")
 eval set -- "$options"
 
 # 提取选项和参数
@@ -37,6 +38,11 @@ while true; do
 		type=$1
 		shift
 		;;
+	-a | --arch)
+		shift
+		arch=$1
+		shift
+		;;
 	--)
 		shift
 		break
@@ -53,4 +59,4 @@ if [[ ! -v "valid_types[$type]" ]]; then
 	exit 1
 fi
 
-make run ARCH=riscv64 A=examples/loader_lib LOG="$log" QEMU_LOG="$qemu_log"
+make run ARCH="$arch" A=examples/loader_lib LOG="$log" QEMU_LOG="$qemu_log"
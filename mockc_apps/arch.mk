@@ -0,0 +1,15 @@
+# 各个程序共用的体系结构设置，`ARCH`与 ArceOS 的`make ARCH=...`一致
+ARCH ?= riscv64
+PLATFORM=$(ARCH)-linux-musl
+
+# 静态程序的链接地址，与加载器的`APP_START`一致
+ifeq ($(ARCH), x86_64)
+	APP_START := 0xffff800000600000
+	ARCH_CFLAGS :=
+else ifeq ($(ARCH), aarch64)
+	APP_START := 0xffff000040480000
+	ARCH_CFLAGS :=
+else
+	APP_START := 0xffffffc080600000
+	ARCH_CFLAGS := -mcmodel=medany
+endif
@@ -70,6 +70,18 @@ impl OpenOptions {
         self
     }
 
+    /// Sets the permission bits of a newly created file.
+    pub fn mode(&mut self, mode: u32) -> &mut Self {
+        self.0.mode(mode);
+        self
+    }
+
+    /// Opens the file as `credentials`, see [`Credentials`](crate::api::Credentials).
+    pub fn credentials(&mut self, credentials: crate::api::Credentials) -> &mut Self {
+        self.0.credentials(credentials);
+        self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     pub fn open(&self, path: &str) -> Result<File> {
         fops::File::open(path, &self.0).map(|inner| File { inner })
@@ -169,6 +181,12 @@ impl File {
         self.inner.truncate(size)
     }
 
+    /// Writes the data and metadata of the file through to the storage
+    /// device, even if the file is opened read-only.
+    pub fn sync_all(&self) -> Result<()> {
+        self.inner.sync_all()
+    }
+
     /// Queries metadata about the underlying file.
     pub fn metadata(&self) -> Result<Metadata> {
         self.inner.get_attr().map(Metadata)
@@ -188,6 +206,16 @@ impl File {
     pub fn truncate(&mut self, len: usize) -> Result<()> {
         self.inner.truncate(len as u64)
     }
+
+    /// Reads the file at the given position, without moving the cursor.
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize> {
+        self.inner.read_at(offset, buf)
+    }
+
+    /// Writes the file at the given position, without moving the cursor.
+    pub fn write_at(&self, offset: u64, buf: &[u8]) -> Result<usize> {
+        self.inner.write_at(offset, buf)
+    }
 }
 
 impl Read for File {
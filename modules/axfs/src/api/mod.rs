@@ -5,9 +5,10 @@ mod file;
 
 pub use self::dir::{DirBuilder, DirEntry, ReadDir};
 pub use self::file::{File, FileType, Metadata, OpenOptions, Permissions};
+pub use crate::fops::{Credentials, FileAccess, FileSystemStat, MountInfo};
 
 use alloc::{string::String, vec::Vec};
-use axerrno::AxResult;
+use axerrno::{AxResult, LinuxError};
 use axfs_vfs::VfsNodeRef;
 use axio::{self as io, prelude::*};
 
@@ -85,7 +86,8 @@ pub fn remove_file(path: &str) -> io::Result<()> {
 /// Rename a file or directory to a new name.
 /// Delete the original file if `old` already exists.
 ///
-/// This only works then the new path is in the same mounted fs.
+/// This only works then the new path is in the same mounted fs, it fails
+/// with `CrossesDevices` otherwise.
 pub fn rename(old: &str, new: &str) -> io::Result<()> {
     crate::root::rename(old, new)
 }
@@ -95,6 +97,107 @@ pub fn path_exists(path: &str) -> bool {
     crate::root::lookup(None, path).is_ok()
 }
 
+/// Creates a new hard link `new` to the file `old`.
+///
+/// Fails with `Unsupported` if the filesystem has no hard links, and with
+/// `CrossesDevices` if `old` and `new` are on different filesystems.
+pub fn hard_link(old: &str, new: &str) -> io::Result<()> {
+    crate::root::link(old, new)
+}
+
+/// Creates a symbolic link `path` that points to `target`.
+pub fn symlink(target: &str, path: &str) -> io::Result<()> {
+    crate::root::symlink(target, path)
+}
+
+/// Reads the target of the symbolic link `path`.
+///
+/// Fails with `InvalidInput` if `path` is not a symbolic link.
+pub fn read_link(path: &str) -> io::Result<String> {
+    crate::root::read_link(path)
+}
+
+/// Returns the number of hard links of the file `path`.
+pub fn link_count(path: &str) -> io::Result<u64> {
+    crate::root::link_count(path)
+}
+
+/// Returns the inode number of `path`, which its hard links share. A symbolic
+/// link in the last component is only followed if `follow_last` is set.
+pub fn inode_number(path: &str, follow_last: bool) -> io::Result<u64> {
+    crate::root::inode(path, follow_last)
+}
+
+/// Returns the owner and mode of `path`.
+pub fn file_access(path: &str) -> io::Result<FileAccess> {
+    crate::root::access_at(None, path)
+}
+
+/// Checks that `cred` may access `path` as requested by `mask`, an OR of `4`
+/// (read), `2` (write) and `1` (execute or search) as in `access(2)`.
+pub fn check_access(path: &str, cred: &Credentials, mask: u16) -> io::Result<()> {
+    crate::root::check_access(None, path, cred, mask)
+}
+
+/// Sets the permission bits of `path`, including set-user-ID, set-group-ID and
+/// sticky.
+pub fn set_mode(path: &str, mode: u16) -> io::Result<()> {
+    crate::root::set_mode(path, mode)
+}
+
+/// Sets the owner of `path`. `None` keeps the current user or group.
+pub fn set_owner(path: &str, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    crate::root::set_owner(path, uid, gid)
+}
+
+/// Returns the absolute form of `path` with the symbolic links in it
+/// resolved. The last component is only followed if `follow_last` is set.
+///
+/// Fails with `ELOOP` if too many symbolic links are encountered.
+pub fn resolve_path(path: &str, follow_last: bool) -> Result<String, LinuxError> {
+    crate::root::resolve_path(path, follow_last)
+}
+
+/// Writes all cached data of the mounted filesystems to their storage
+/// devices, as `sync(2)`.
+pub fn sync() -> io::Result<()> {
+    crate::root::sync()
+}
+
+/// Mounts a new filesystem of `fs_type` at the existing directory `target`.
+///
+/// Disk filesystems (`vfat`) are opened on the block device `source`, such as
+/// `/dev/vdb`; for `tmpfs`, `devtmpfs`, `proc` and `sysfs` it is only a name
+/// shown in the mount table. Fails with `Unsupported` for an unknown type.
+pub fn mount(source: &str, target: &str, fs_type: &str) -> io::Result<()> {
+    crate::root::mount(source, target, fs_type)
+}
+
+/// Unmounts the filesystem mounted at `target`.
+///
+/// Fails with `ResourceBusy` if another filesystem is mounted under it, or if
+/// `in_use` returns true when called with the mount point: the caller checks
+/// the working directories and open files it knows of.
+pub fn umount(target: &str, in_use: impl FnOnce(&str) -> bool) -> io::Result<()> {
+    crate::root::umount(target, in_use)
+}
+
+/// Returns the mount table in the format of `/proc/mounts`.
+pub fn mount_table() -> String {
+    crate::root::mount_table()
+}
+
+/// Returns the entry of the mount table whose mount point is the absolute
+/// path `path`, `None` if nothing is mounted there.
+pub fn mount_point(path: &str) -> Option<MountInfo> {
+    crate::root::mount_point(path)
+}
+
+/// Returns statistics of the mounted filesystem that contains `path`.
+pub fn statfs(path: &str) -> io::Result<FileSystemStat> {
+    crate::root::statfs(path)
+}
+
 /// Look up a file by a given path.
 pub fn lookup(path: &str) -> AxResult<VfsNodeRef> {
     crate::root::lookup(None, path)
@@ -0,0 +1,120 @@
+//! Owners and modes of files.
+//!
+//! [`axfs_vfs`] only reports the permission bits of a node and cannot change
+//! them, so every mounted filesystem is paired with an [`AttrOps`] as with
+//! [`LinkOps`](crate::links::LinkOps). ext4 (both `lwext4` and `ext4_rs`)
+//! keeps them in the inode, the other filesystems in memory, see [`MemAttrs`].
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use axfs_vfs::{VfsNodeRef, VfsResult};
+use axsync::Mutex;
+
+use crate::fops::FileAccess;
+
+/// Owner and mode operations of a mounted filesystem. Paths are relative to
+/// the root of that filesystem.
+pub trait AttrOps: Send + Sync {
+    /// Returns the owner and mode of `node`, found at `path`.
+    fn get(&self, path: &str, node: &VfsNodeRef) -> VfsResult<FileAccess>;
+
+    /// Sets the permission bits, including set-user-ID, set-group-ID and sticky.
+    fn set_mode(&self, path: &str, node: &VfsNodeRef, mode: u16) -> VfsResult;
+
+    /// Sets the owner, `None` keeps the current one.
+    fn set_owner(
+        &self,
+        path: &str,
+        node: &VfsNodeRef,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> VfsResult;
+
+    /// Called after `path` has been removed from the filesystem.
+    fn removed(&self, _path: &str) {}
+
+    /// Called after `src_path` has been renamed to `dst_path`.
+    fn renamed(&self, _src_path: &str, _dst_path: &str) {}
+}
+
+/// Owners and modes kept in memory, lost on reboot.
+///
+/// Files that were never changed are owned by root and have the permission
+/// bits reported by the filesystem.
+pub struct MemAttrs {
+    table: Mutex<BTreeMap<String, FileAccess>>,
+}
+
+impl MemAttrs {
+    pub const fn new() -> Self {
+        Self {
+            table: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn update(&self, path: &str, node: &VfsNodeRef, f: impl FnOnce(&mut FileAccess)) -> VfsResult {
+        let access = self.get(path, node)?;
+        let mut table = self.table.lock();
+        f(table.entry(name_of(path)).or_insert(access));
+        Ok(())
+    }
+}
+
+impl AttrOps for MemAttrs {
+    fn get(&self, path: &str, node: &VfsNodeRef) -> VfsResult<FileAccess> {
+        if let Some(access) = self.table.lock().get(&name_of(path)) {
+            return Ok(*access);
+        }
+        Ok(FileAccess {
+            uid: 0,
+            gid: 0,
+            mode: node.get_attr()?.perm().bits(),
+        })
+    }
+
+    fn set_mode(&self, path: &str, node: &VfsNodeRef, mode: u16) -> VfsResult {
+        self.update(path, node, |access| access.mode = mode)
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        node: &VfsNodeRef,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> VfsResult {
+        self.update(path, node, |access| {
+            access.uid = uid.unwrap_or(access.uid);
+            access.gid = gid.unwrap_or(access.gid);
+        })
+    }
+
+    fn removed(&self, path: &str) {
+        self.table.lock().remove(&name_of(path));
+    }
+
+    fn renamed(&self, src_path: &str, dst_path: &str) {
+        let (src, dst) = (name_of(src_path), name_of(dst_path));
+        let mut table = self.table.lock();
+        // the entries of a renamed directory move with it
+        let moved: Vec<String> = table
+            .keys()
+            .filter(|name| {
+                name.strip_prefix(src.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .cloned()
+            .collect();
+        for name in moved {
+            let access = table.remove(&name).unwrap();
+            table.insert(dst.clone() + &name[src.len()..], access);
+        }
+    }
+}
+
+fn name_of(path: &str) -> String {
+    path.trim_matches('/').to_string()
+}
@@ -1,12 +1,25 @@
+use alloc::{format, string::String, vec::Vec};
 use axdriver::prelude::*;
+use axerrno::{AxResult, ax_err};
+use axsync::Mutex;
+use core::mem::ManuallyDrop;
 
 const BLOCK_SIZE: usize = 512;
 
+/// Block devices that can be mounted at runtime, named `vdb`, `vdc`, ... in
+/// probe order. The first device (`vda`) holds the root filesystem.
+///
+/// A device is taken out while it is mounted and put back when the
+/// filesystem on it is dropped.
+static BLOCK_DEVICES: Mutex<Vec<(String, Option<AxBlockDevice>)>> = Mutex::new(Vec::new());
+
 /// A disk device with a cursor.
 pub struct Disk {
     block_id: u64,
     offset: usize,
-    dev: AxBlockDevice,
+    dev: ManuallyDrop<AxBlockDevice>,
+    /// Name in [`BLOCK_DEVICES`] to return the device to on drop.
+    name: Option<String>,
 }
 
 impl Disk {
@@ -16,7 +29,27 @@ impl Disk {
         Self {
             block_id: 0,
             offset: 0,
-            dev,
+            dev: ManuallyDrop::new(dev),
+            name: None,
+        }
+    }
+
+    /// Takes the registered block device `name` (with or without the `/dev/`
+    /// prefix) out of the registry.
+    #[cfg_attr(not(any(feature = "fatfs", feature = "ext4_rs")), allow(dead_code))]
+    pub fn take(name: &str) -> AxResult<Self> {
+        let name = name.strip_prefix("/dev/").unwrap_or(name);
+        let mut devices = BLOCK_DEVICES.lock();
+        match devices.iter_mut().find(|(dev_name, _)| dev_name == name) {
+            Some((dev_name, dev)) => match dev.take() {
+                Some(dev) => {
+                    let mut disk = Self::new(dev);
+                    disk.name = Some(dev_name.clone());
+                    Ok(disk)
+                }
+                None => ax_err!(ResourceBusy, "block device is already mounted"),
+            },
+            None => ax_err!(NotFound, "no such block device"),
         }
     }
 
@@ -36,6 +69,11 @@ impl Disk {
         self.offset = pos as usize % BLOCK_SIZE;
     }
 
+    /// Flushes the write cache of the device, if any.
+    pub fn flush(&mut self) -> DevResult {
+        self.dev.flush()
+    }
+
     /// Read within one block, returns the number of bytes read.
     pub fn read_one(&mut self, buf: &mut [u8]) -> DevResult<usize> {
         let read_size = if self.offset == 0 && buf.len() >= BLOCK_SIZE {
@@ -112,3 +150,31 @@ impl Disk {
         Ok(buf.len())
     }
 }
+
+impl Drop for Disk {
+    fn drop(&mut self) {
+        // SAFETY: `dev` is not used after this.
+        let dev = unsafe { ManuallyDrop::take(&mut self.dev) };
+        if let Some(name) = self.name.take() {
+            let mut devices = BLOCK_DEVICES.lock();
+            if let Some((_, slot)) = devices.iter_mut().find(|(dev_name, _)| *dev_name == name) {
+                *slot = Some(dev);
+            }
+        }
+    }
+}
+
+/// Name of the `index`-th block device in probe order, `vda` for the root one.
+pub(crate) fn device_name(index: usize) -> String {
+    format!("vd{}", (b'a' + index as u8) as char)
+}
+
+/// Registers the block devices other than the root one for runtime mounts.
+pub(crate) fn register_devices(devs: impl Iterator<Item = AxBlockDevice>) {
+    let mut devices = BLOCK_DEVICES.lock();
+    for dev in devs {
+        let name = device_name(devices.len() + 1);
+        info!("  register block device {}: {:?}", name, dev.device_name());
+        devices.push((name, Some(dev)));
+    }
+}
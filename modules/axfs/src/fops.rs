@@ -1,5 +1,6 @@
 //! Low-level filesystem operations.
 
+use alloc::{string::String, vec::Vec};
 use axerrno::{AxError, AxResult, ax_err, ax_err_type};
 use axfs_vfs::{VfsError, VfsNodeRef};
 use axio::SeekFrom;
@@ -20,6 +21,105 @@ pub type FileAttr = axfs_vfs::VfsNodeAttr;
 /// Alias of [`axfs_vfs::VfsNodePerm`].
 pub type FilePerm = axfs_vfs::VfsNodePerm;
 
+/// An entry of the mount table.
+#[derive(Debug, Clone)]
+pub struct MountInfo {
+    /// The device or name it was mounted from.
+    pub source: String,
+    /// Absolute path of the mount point, without the trailing '/' but for
+    /// the root.
+    pub path: String,
+    /// Type of the filesystem, e.g. `tmpfs`.
+    pub fs_type: &'static str,
+}
+
+/// Statistics of a mounted filesystem, as reported by `statfs(2)`.
+///
+/// Counts the filesystem does not know are left as zero.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileSystemStat {
+    /// Magic number of the filesystem type, e.g. `0x4d44` for FAT.
+    pub fs_type: u64,
+    /// Size of a block in bytes.
+    pub block_size: u64,
+    /// Total number of blocks.
+    pub blocks: u64,
+    /// Number of free blocks.
+    pub blocks_free: u64,
+    /// Total number of inodes.
+    pub files: u64,
+    /// Number of free inodes.
+    pub files_free: u64,
+    /// Maximum length of a file name.
+    pub name_max: u64,
+}
+
+impl FileSystemStat {
+    /// Statistics of a filesystem that only has a type and a block size, such
+    /// as the in-memory filesystems.
+    pub const fn new(fs_type: u64, block_size: u64) -> Self {
+        Self {
+            fs_type,
+            block_size,
+            blocks: 0,
+            blocks_free: 0,
+            files: 0,
+            files_free: 0,
+            name_max: 255,
+        }
+    }
+}
+
+/// Owner and permission bits of a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileAccess {
+    /// User ID of the owner.
+    pub uid: u32,
+    /// Group ID of the owner.
+    pub gid: u32,
+    /// Permission bits, including set-user-ID (`0o4000`), set-group-ID
+    /// (`0o2000`) and sticky (`0o1000`).
+    pub mode: u16,
+}
+
+/// The user a file is accessed as, see [`OpenOptions::credentials`].
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    /// User ID used for file access, `0` is the superuser.
+    pub uid: u32,
+    /// Group ID used for file access.
+    pub gid: u32,
+    /// Supplementary group IDs.
+    pub groups: Vec<u32>,
+}
+
+impl Credentials {
+    /// Whether the user is a member of the group `gid`.
+    pub fn in_group(&self, gid: u32) -> bool {
+        self.gid == gid || self.groups.contains(&gid)
+    }
+
+    /// Whether the user may access a file with `access` as requested by `mask`,
+    /// an OR of `4` (read), `2` (write) and `1` (execute or search) as in
+    /// `access(2)`.
+    ///
+    /// The superuser may read and write anything, and execute anything that
+    /// is executable by someone.
+    pub fn can_access(&self, access: &FileAccess, mask: u16) -> bool {
+        if self.uid == 0 {
+            return mask & 0o1 == 0 || access.mode & 0o111 != 0;
+        }
+        let shift = if self.uid == access.uid {
+            6
+        } else if self.in_group(access.gid) {
+            3
+        } else {
+            0
+        };
+        (access.mode >> shift) & mask == mask
+    }
+}
+
 /// An opened file object, with open permissions and a cursor.
 pub struct File {
     node: WithCap<VfsNodeRef>,
@@ -32,6 +132,9 @@ pub struct File {
 pub struct Directory {
     node: WithCap<VfsNodeRef>,
     entry_idx: usize,
+    /// Absolute path the directory was opened at, used to check the access of
+    /// the paths relative to it.
+    path: String,
 }
 
 /// Options and flags which can be used to configure how a file is opened.
@@ -46,7 +149,8 @@ pub struct OpenOptions {
     create_new: bool,
     // system-specific
     _custom_flags: i32,
-    _mode: u32,
+    mode: u32,
+    credentials: Option<Credentials>,
 }
 
 impl Default for OpenOptions {
@@ -68,7 +172,8 @@ impl OpenOptions {
             create_new: false,
             // system-specific
             _custom_flags: 0,
-            _mode: 0o666,
+            mode: 0o666,
+            credentials: None,
         }
     }
     /// Sets the option for read access.
@@ -95,6 +200,18 @@ impl OpenOptions {
     pub fn create_new(&mut self, create_new: bool) {
         self.create_new = create_new;
     }
+    /// Sets the permission bits of a newly created file. The caller applies
+    /// its `umask` beforehand.
+    pub fn mode(&mut self, mode: u32) {
+        self.mode = mode;
+    }
+    /// Opens the file as `credentials`: the access is checked against the
+    /// owner and mode of the file, and a new file is owned by this user.
+    ///
+    /// Without credentials only the owner permission bits are checked.
+    pub fn credentials(&mut self, credentials: Credentials) {
+        self.credentials = Some(credentials);
+    }
 
     const fn is_valid(&self) -> bool {
         if !self.read && !self.write && !self.append {
@@ -122,13 +239,16 @@ impl File {
         self.node.access_or_err(cap, AxError::PermissionDenied)
     }
 
-    fn _open_at(dir: Option<&VfsNodeRef>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
+    /// Opens `path` relative to the directory whose absolute path is
+    /// `dir_path`.
+    fn _open_at(dir_path: Option<&str>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
         debug!("open file: {} {:?}", path, opts);
         if !opts.is_valid() {
             return ax_err!(InvalidInput);
         }
 
-        let node_option = crate::root::lookup(dir, path);
+        let node_option = crate::root::lookup(dir_path, path);
+        let mut created = false;
         let node = if opts.create || opts.create_new {
             match node_option {
                 Ok(node) => {
@@ -139,7 +259,13 @@ impl File {
                     node
                 }
                 // not exists, create new
-                Err(VfsError::NotFound) => crate::root::create_file(dir, path)?,
+                Err(VfsError::NotFound) => {
+                    if let Some(cred) = &opts.credentials {
+                        crate::root::check_parent_access(dir_path, path, cred)?;
+                    }
+                    created = true;
+                    crate::root::create_file(dir_path, path)?
+                }
                 Err(e) => return Err(e),
             }
         } else {
@@ -154,8 +280,21 @@ impl File {
             return ax_err!(IsADirectory);
         }
         let access_cap = opts.into();
-        if !perm_to_cap(attr.perm()).contains(access_cap) {
-            return ax_err!(PermissionDenied);
+        match &opts.credentials {
+            Some(cred) if created => {
+                let access = FileAccess {
+                    uid: cred.uid,
+                    gid: cred.gid,
+                    mode: (opts.mode & 0o7777) as u16,
+                };
+                crate::root::init_access(dir_path, path, access)?;
+            }
+            Some(cred) => crate::root::check_access(dir_path, path, cred, cap_to_mask(access_cap))?,
+            None => {
+                if !perm_to_cap(attr.perm()).contains(access_cap) {
+                    return ax_err!(PermissionDenied);
+                }
+            }
         }
 
         node.open()?;
@@ -234,6 +373,17 @@ impl File {
         Ok(())
     }
 
+    /// Writes the data and metadata of the file through to the storage
+    /// device, as `fsync(2)`. Unlike [`File::flush`], the file need not be
+    /// opened for writing.
+    pub fn sync_all(&self) -> AxResult {
+        match self.access_node(Cap::empty())?.fsync() {
+            // the default of `VfsNodeOps`, nodes in memory have nothing to write
+            Err(AxError::InvalidInput) => Ok(()),
+            res => res,
+        }
+    }
+
     /// Sets the cursor of the file to the specified offset. Returns the new
     /// position after the seek.
     pub fn seek(&mut self, pos: SeekFrom) -> AxResult<u64> {
@@ -274,7 +424,7 @@ impl Directory {
         self.node.access_or_err(cap, AxError::PermissionDenied)
     }
 
-    fn _open_dir_at(dir: Option<&VfsNodeRef>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
+    fn _open_dir_at(dir_path: Option<&str>, path: &str, opts: &OpenOptions) -> AxResult<Self> {
         debug!("open dir: {}", path);
         if !opts.read {
             return ax_err!(InvalidInput);
@@ -283,13 +433,15 @@ impl Directory {
             return ax_err!(InvalidInput);
         }
 
-        let node = crate::root::lookup(dir, path)?;
+        let node = crate::root::lookup(dir_path, path)?;
         let attr = node.get_attr()?;
         if !attr.is_dir() {
             return ax_err!(NotADirectory);
         }
         let access_cap = opts.into();
-        if !perm_to_cap(attr.perm()).contains(access_cap) {
+        if let Some(cred) = &opts.credentials {
+            crate::root::check_access(dir_path, path, cred, cap_to_mask(access_cap))?;
+        } else if !perm_to_cap(attr.perm()).contains(access_cap) {
             return ax_err!(PermissionDenied);
         }
 
@@ -297,14 +449,18 @@ impl Directory {
         Ok(Self {
             node: WithCap::new(node, access_cap),
             entry_idx: 0,
+            path: crate::root::absolute_path(&crate::root::path_at(dir_path, path))?,
         })
     }
 
-    fn access_at(&self, path: &str) -> AxResult<Option<&VfsNodeRef>> {
+    /// Returns the path of this directory if `path` is relative to it, which
+    /// requires searching the directory.
+    fn access_at(&self, path: &str) -> AxResult<Option<&str>> {
         if path.starts_with('/') {
             Ok(None)
         } else {
-            Ok(Some(self.access_node(Cap::EXECUTE)?))
+            self.access_node(Cap::EXECUTE)?;
+            Ok(Some(self.path.as_str()))
         }
     }
 
@@ -418,6 +574,21 @@ impl From<&OpenOptions> for Cap {
     }
 }
 
+/// `access(2)` mask of `cap`.
+fn cap_to_mask(cap: Cap) -> u16 {
+    let mut mask = 0;
+    if cap.contains(Cap::READ) {
+        mask |= 0o4;
+    }
+    if cap.contains(Cap::WRITE) {
+        mask |= 0o2;
+    }
+    if cap.contains(Cap::EXECUTE) {
+        mask |= 0o1;
+    }
+    mask
+}
+
 fn perm_to_cap(perm: FilePerm) -> Cap {
     let mut cap = Cap::empty();
     if perm.owner_readable() {
@@ -1,5 +1,8 @@
 use crate::alloc::string::String;
+use crate::attrs::AttrOps;
 use crate::dev::Disk;
+use crate::fops::{FileAccess, FileSystemStat};
+use crate::links::{LinkOps, PATH_MAX};
 use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::*;
@@ -20,6 +23,11 @@ unsafe impl Sync for DiskAdapter {}
 
 const DISK_BLOCK_SIZE: usize = 512;
 pub const BLOCK_SIZE: usize = 4096;
+const EXT4_SUPER_MAGIC: u64 = 0xef53;
+/// Inode number of the root directory.
+const ROOT_INO: u32 = 2;
+/// Longest target kept in the block map of the inode, a fast symbolic link.
+const FAST_SYMLINK_MAX: usize = 60;
 
 impl BlockDevice for DiskAdapter {
     fn read_offset(&self, offset: usize) -> Vec<u8> {
@@ -92,6 +100,104 @@ impl Ext4FileSystem {
             root_dir: root,
         }
     }
+
+    /// Only the type and block size are reported, `ext4_rs` does not expose the
+    /// free counts of the superblock.
+    pub fn stat(&self) -> FileSystemStat {
+        FileSystemStat::new(EXT4_SUPER_MAGIC, BLOCK_SIZE as u64)
+    }
+
+    /// Number of the inode at `path`, relative to the root. A symbolic link
+    /// is not followed.
+    fn inode_num(&self, path: &str) -> VfsResult<u32> {
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return Ok(ROOT_INO);
+        }
+        let mut file = Ext4File::new();
+        self.inner
+            .ext4_open(&mut file, path, "r+", false)
+            .map_err(|e| as_vfs_err(e.error()))?;
+        Ok(file.inode)
+    }
+
+    fn inode_ref(&self, path: &str) -> VfsResult<Ext4InodeRef> {
+        let inode_num = self.inode_num(path)?;
+        Ok(Ext4InodeRef::get_inode_ref(
+            Arc::downgrade(&self.inner),
+            inode_num,
+        ))
+    }
+}
+
+/// Hard links are directory entries counted in the inode. A new symbolic
+/// link keeps its target in a data block, the fast ones made by other tools
+/// keep it in the inode and can be read as well.
+impl LinkOps for Ext4FileSystem {
+    fn link(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        info!("link on ext4_rs: {} -> {}", dst_path, src_path);
+        let mut child = self.inode_ref(src_path)?;
+        let dst_path = dst_path.trim_matches('/');
+        let (dir, name) = dst_path.rsplit_once('/').unwrap_or(("", dst_path));
+        let mut parent = self.inode_ref(dir)?;
+        let links_count = child.inner.inode.links_count;
+        self.inner
+            .ext4_link(&mut parent, &mut child, name, name.len() as u32)
+            .map_err(|e| as_vfs_err(e.error()))?;
+        // count the new name
+        child.inner.inode.links_count = links_count + 1;
+        child.write_back_inode();
+        Ok(())
+    }
+
+    fn symlink(&self, target: &str, path: &str) -> VfsResult {
+        info!("symlink on ext4_rs: {} -> {}", path, target);
+        let mut file = Ext4File::new();
+        self.inner
+            .ext4_open(&mut file, path, "w+", true)
+            .map_err(|e| as_vfs_err(e.error()))?;
+        self.inner
+            .ext4_file_write(&mut file, target.as_bytes(), target.len());
+        let mut inode = Ext4InodeRef::get_inode_ref(Arc::downgrade(&self.inner), file.inode);
+        inode.inner.inode.mode = (EXT4_INODE_MODE_SOFTLINK | 0o777) as u16;
+        inode.write_back_inode();
+        Ok(())
+    }
+
+    fn readlink(&self, path: &str) -> Option<String> {
+        let mut file = Ext4File::new();
+        self.inner.ext4_open(&mut file, path, "r+", false).ok()?;
+        let inode = Ext4InodeRef::get_inode_ref(Arc::downgrade(&self.inner), file.inode);
+        let inode = &inode.inner.inode;
+        if (inode.mode & 0xf000) as usize != EXT4_INODE_MODE_SOFTLINK {
+            return None;
+        }
+        let size = file.fsize as usize;
+        if size == 0 || size > PATH_MAX {
+            return None;
+        }
+        let mut buf = vec![0u8; size];
+        if inode.blocks == 0 && size <= FAST_SYMLINK_MAX {
+            for (chunk, word) in buf.chunks_mut(4).zip(inode.block.iter()) {
+                chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+            }
+        } else {
+            let mut read_cnt = 0;
+            file.fpos = 0;
+            self.inner
+                .ext4_file_read(&mut file, &mut buf, size, &mut read_cnt)
+                .ok()?;
+        }
+        String::from_utf8(buf).ok()
+    }
+
+    fn link_count(&self, path: &str) -> VfsResult<u64> {
+        Ok(self.inode_ref(path)?.inner.inode.links_count as u64)
+    }
+
+    fn inode(&self, path: &str) -> Option<u64> {
+        self.inode_num(path).ok().map(u64::from)
+    }
 }
 
 impl VfsOps for Ext4FileSystem {
@@ -99,9 +205,9 @@ impl VfsOps for Ext4FileSystem {
         Arc::clone(&self.root_dir)
     }
 
+    /// Blocks are written through to the disk, nothing is left to flush.
     fn umount(&self) -> VfsResult {
-        log::info!("umount:");
-        todo!()
+        Ok(())
     }
 }
 
@@ -304,6 +410,59 @@ impl VfsNodeOps for Ext4FileWrapper {
     }
 }
 
+/// Owners and modes are kept in the inode. Only the low 16 bits of the user
+/// and group IDs are used, larger ones are rejected.
+impl AttrOps for Ext4FileSystem {
+    fn get(&self, path: &str, _node: &VfsNodeRef) -> VfsResult<FileAccess> {
+        let inode = self.inode_ref(path)?;
+        let inode = &inode.inner.inode;
+        Ok(FileAccess {
+            uid: inode.uid as u32,
+            gid: inode.gid as u32,
+            mode: inode.mode & 0o7777,
+        })
+    }
+
+    fn set_mode(&self, path: &str, _node: &VfsNodeRef, mode: u16) -> VfsResult {
+        info!("chmod on ext4_rs: {} {:o}", path, mode);
+        let mut inode = self.inode_ref(path)?;
+        // only the low 12 bits are replaced, the file type is kept
+        let old = inode.inner.inode.mode;
+        inode.inner.inode.mode = (old & !0o7777) | (mode & 0o7777);
+        inode.write_back_inode();
+        Ok(())
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        _node: &VfsNodeRef,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> VfsResult {
+        info!("chown on ext4_rs: {} {:?}:{:?}", path, uid, gid);
+        let low_16 = |id: u32| u16::try_from(id).map_err(|_| VfsError::InvalidInput);
+        let (uid, gid) = (uid.map(low_16).transpose()?, gid.map(low_16).transpose()?);
+        let mut inode = self.inode_ref(path)?;
+        if let Some(uid) = uid {
+            inode.inner.inode.uid = uid;
+        }
+        if let Some(gid) = gid {
+            inode.inner.inode.gid = gid;
+        }
+        inode.write_back_inode();
+        Ok(())
+    }
+}
+
+fn as_vfs_err(errno: Errnum) -> VfsError {
+    match errno {
+        Errnum::ENOENT => VfsError::NotFound,
+        Errnum::EEXIST => VfsError::AlreadyExists,
+        _ => VfsError::InvalidInput,
+    }
+}
+
 fn map_dir_imode(imode: u16) -> (VfsNodeType, VfsNodePerm) {
     let diren_type = imode;
     let type_code = ext4_rs::DirEntryType::from_bits(diren_type as u8).unwrap();
@@ -1,15 +1,23 @@
-use alloc::sync::Arc;
+use alloc::{ffi::CString, string::String, sync::Arc, vec};
 use axerrno::AxError;
 use axfs_vfs::{VfsDirEntry, VfsError, VfsNodePerm, VfsResult};
 use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps};
 use axsync::Mutex;
 use lwext4_rust::bindings::{
-    O_CREAT, O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, SEEK_CUR, SEEK_END, SEEK_SET,
+    O_CREAT, O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, SEEK_CUR, SEEK_END, SEEK_SET, ext4_cache_flush,
+    ext4_flink, ext4_fsymlink, ext4_inode, ext4_mode_get, ext4_mode_set, ext4_mount_point_stats,
+    ext4_mount_stats, ext4_owner_get, ext4_owner_set, ext4_raw_inode_fill, ext4_readlink,
 };
 use lwext4_rust::{Ext4BlockWrapper, Ext4File, InodeTypes, KernelDevOp};
 
-use crate::dev::Disk;
+use crate::{
+    attrs::AttrOps,
+    dev::Disk,
+    fops::{FileAccess, FileSystemStat},
+    links::{LinkOps, PATH_MAX},
+};
 const BLOCK_SIZE: usize = 512;
+const EXT4_SUPER_MAGIC: u64 = 0xef53;
 
 #[allow(dead_code)]
 pub struct Ext4FileSystem {
@@ -38,6 +46,119 @@ impl Ext4FileSystem {
         let root = Arc::new(FileWrapper::new("/", InodeTypes::EXT4_DE_DIR));
         Self { inner, root }
     }
+
+    /// Block and inode usage from the superblock of the volume mounted at `/`.
+    pub fn stat(&self) -> FileSystemStat {
+        let mut stat = FileSystemStat::new(EXT4_SUPER_MAGIC, BLOCK_SIZE as u64);
+        let mut stats: ext4_mount_stats = unsafe { core::mem::zeroed() };
+        if unsafe { ext4_mount_point_stats(c"/".as_ptr(), &mut stats) } == 0 {
+            stat.block_size = stats.block_size as u64;
+            stat.blocks = stats.blocks_count;
+            stat.blocks_free = stats.free_blocks_count;
+            stat.files = stats.inodes_count as u64;
+            stat.files_free = stats.free_inodes_count as u64;
+        }
+        stat
+    }
+
+    /// Writes the dirty blocks in the block cache of `lwext4` to the disk.
+    pub fn sync(&self) -> VfsResult {
+        ext4_result(unsafe { ext4_cache_flush(c"/".as_ptr()) })
+    }
+}
+
+/// Absolute path in the volume for `path` relative to its root.
+fn ext4_path(path: &str) -> VfsResult<CString> {
+    CString::new(String::from("/") + path.trim_matches('/')).map_err(|_| VfsError::InvalidInput)
+}
+
+fn ext4_result(ret: i32) -> VfsResult {
+    match ret {
+        0 => Ok(()),
+        e => Err(e.try_into().unwrap()),
+    }
+}
+
+/// Hard and symbolic links are native in ext4.
+impl LinkOps for Ext4FileSystem {
+    fn link(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        info!("link on Ext4fs: {} -> {}", dst_path, src_path);
+        let src = ext4_path(src_path)?;
+        let dst = ext4_path(dst_path)?;
+        ext4_result(unsafe { ext4_flink(src.as_ptr(), dst.as_ptr()) })
+    }
+
+    fn symlink(&self, target: &str, path: &str) -> VfsResult {
+        info!("symlink on Ext4fs: {} -> {}", path, target);
+        let target = CString::new(target).map_err(|_| VfsError::InvalidInput)?;
+        let path = ext4_path(path)?;
+        ext4_result(unsafe { ext4_fsymlink(target.as_ptr(), path.as_ptr()) })
+    }
+
+    fn readlink(&self, path: &str) -> Option<String> {
+        let path = ext4_path(path).ok()?;
+        let mut buf = vec![0u8; PATH_MAX];
+        let mut len = 0;
+        let ret =
+            unsafe { ext4_readlink(path.as_ptr(), buf.as_mut_ptr() as _, buf.len(), &mut len) };
+        if ret != 0 {
+            return None;
+        }
+        buf.truncate(len);
+        String::from_utf8(buf).ok()
+    }
+
+    fn link_count(&self, path: &str) -> VfsResult<u64> {
+        let path = ext4_path(path)?;
+        let mut ino = 0;
+        let mut inode: ext4_inode = unsafe { core::mem::zeroed() };
+        ext4_result(unsafe { ext4_raw_inode_fill(path.as_ptr(), &mut ino, &mut inode) })?;
+        Ok(u16::from_le(inode.links_count) as u64)
+    }
+
+    fn inode(&self, path: &str) -> Option<u64> {
+        let path = ext4_path(path).ok()?;
+        let mut ino = 0;
+        let mut inode: ext4_inode = unsafe { core::mem::zeroed() };
+        ext4_result(unsafe { ext4_raw_inode_fill(path.as_ptr(), &mut ino, &mut inode) }).ok()?;
+        Some(ino as u64)
+    }
+}
+
+/// Owners and modes are kept in the inode.
+impl AttrOps for Ext4FileSystem {
+    fn get(&self, path: &str, _node: &VfsNodeRef) -> VfsResult<FileAccess> {
+        let path = ext4_path(path)?;
+        let (mut mode, mut uid, mut gid) = (0, 0, 0);
+        ext4_result(unsafe { ext4_mode_get(path.as_ptr(), &mut mode) })?;
+        ext4_result(unsafe { ext4_owner_get(path.as_ptr(), &mut uid, &mut gid) })?;
+        Ok(FileAccess {
+            uid,
+            gid,
+            mode: (mode & 0o7777) as u16,
+        })
+    }
+
+    fn set_mode(&self, path: &str, _node: &VfsNodeRef, mode: u16) -> VfsResult {
+        info!("chmod on Ext4fs: {} {:o}", path, mode);
+        let path = ext4_path(path)?;
+        // only the low 12 bits are replaced, the file type is kept
+        ext4_result(unsafe { ext4_mode_set(path.as_ptr(), mode as u32) })
+    }
+
+    fn set_owner(
+        &self,
+        path: &str,
+        node: &VfsNodeRef,
+        uid: Option<u32>,
+        gid: Option<u32>,
+    ) -> VfsResult {
+        info!("chown on Ext4fs: {} {:?}:{:?}", path, uid, gid);
+        let old = self.get(path, node)?;
+        let path = ext4_path(path)?;
+        let (uid, gid) = (uid.unwrap_or(old.uid), gid.unwrap_or(old.gid));
+        ext4_result(unsafe { ext4_owner_set(path.as_ptr(), uid, gid) })
+    }
 }
 
 /// The [`VfsOps`] trait provides operations on a filesystem.
@@ -265,6 +386,10 @@ impl VfsNodeOps for FileWrapper {
         } else if file.check_inode_exist(fpath, InodeTypes::EXT4_DE_REG_FILE) {
             debug!("lookup new FILE FileWrapper");
             Ok(Arc::new(Self::new(fpath, InodeTypes::EXT4_DE_REG_FILE)))
+        } else if file.check_inode_exist(fpath, InodeTypes::EXT4_DE_SYMLINK) {
+            // not followed here, `root::lookup` resolves symbolic links
+            debug!("lookup new SYMLINK FileWrapper");
+            Ok(Arc::new(Self::new(fpath, InodeTypes::EXT4_DE_SYMLINK)))
         } else {
             Err(VfsError::NotFound)
         }
@@ -307,7 +432,8 @@ impl VfsNodeOps for FileWrapper {
         let mut file = self.0.lock();
         let path = file.get_path();
         let path = path.to_str().unwrap();
-        file.file_open(path, O_RDWR | O_CREAT | O_TRUNC)
+        // `O_TRUNC` would drop the data before the new size
+        file.file_open(path, O_RDWR)
             .map_err(|e| <i32 as TryInto<AxError>>::try_into(e).unwrap())?;
 
         let t = file.file_truncate(size);
@@ -316,6 +442,12 @@ impl VfsNodeOps for FileWrapper {
         t.map(|_v| ()).map_err(|e| e.try_into().unwrap())
     }
 
+    /// `lwext4` caches blocks for the whole volume, not per file.
+    fn fsync(&self) -> VfsResult {
+        info!("fsync file {:?}", self.0.lock().get_path());
+        ext4_result(unsafe { ext4_cache_flush(c"/".as_ptr()) })
+    }
+
     fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
         info!("rename from {} to {}", src_path, dst_path);
         let mut file = self.0.lock();
@@ -371,8 +503,8 @@ impl KernelDevOp for Disk {
         debug!("WRITE rt len={}", write_len);
         Ok(write_len)
     }
-    fn flush(_dev: &mut Self::DevType) -> Result<usize, i32> {
-        Ok(0)
+    fn flush(dev: &mut Self::DevType) -> Result<usize, i32> {
+        dev.flush().map(|_| 0).map_err(|_| -1)
     }
     fn seek(dev: &mut Disk, off: i64, whence: i32) -> Result<i64, i32> {
         let size = dev.size();
@@ -6,13 +6,15 @@ use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps};
 use axsync::Mutex;
 use fatfs::{Dir, File, LossyOemCpConverter, NullTimeProvider, Read, Seek, SeekFrom, Write};
 
-use crate::dev::Disk;
+use crate::{dev::Disk, fops::FileSystemStat};
 
 pub const BLOCK_SIZE: usize = 512;
+const MSDOS_SUPER_MAGIC: u64 = 0x4d44;
 
 pub struct FatFileSystem {
-    inner: fatfs::FileSystem<Disk, NullTimeProvider, LossyOemCpConverter>,
+    // dropped before `inner` that it refers to
     root_dir: UnsafeCell<Option<VfsNodeRef>>,
+    inner: fatfs::FileSystem<Disk, NullTimeProvider, LossyOemCpConverter>,
 }
 
 pub struct FileWrapper<'a>(Mutex<File<'a, Disk, NullTimeProvider, LossyOemCpConverter>>);
@@ -40,12 +42,17 @@ impl FatFileSystem {
 
     #[cfg(not(feature = "use-ramdisk"))]
     pub fn new(disk: Disk) -> Self {
+        Self::try_new(disk).expect("failed to initialize FAT filesystem")
+    }
+
+    /// Opens the FAT volume on `disk`, `InvalidData` if there is none.
+    pub fn try_new(disk: Disk) -> VfsResult<Self> {
         let inner = fatfs::FileSystem::new(disk, fatfs::FsOptions::new())
-            .expect("failed to initialize FAT filesystem");
-        Self {
+            .map_err(|_| VfsError::InvalidData)?;
+        Ok(Self {
             inner,
             root_dir: UnsafeCell::new(None),
-        }
+        })
     }
 
     pub fn init(&'static self) {
@@ -53,6 +60,17 @@ impl FatFileSystem {
         unsafe { *self.root_dir.get() = Some(Self::new_dir(self.inner.root_dir())) }
     }
 
+    /// Cluster usage of the volume.
+    pub fn stat(&self) -> FileSystemStat {
+        let mut stat = FileSystemStat::new(MSDOS_SUPER_MAGIC, BLOCK_SIZE as u64);
+        if let Ok(stats) = self.inner.stats() {
+            stat.block_size = stats.cluster_size() as u64;
+            stat.blocks = stats.total_clusters() as u64;
+            stat.blocks_free = stats.free_clusters() as u64;
+        }
+        stat
+    }
+
     fn new_file(file: File<'_, Disk, NullTimeProvider, LossyOemCpConverter>) -> Arc<FileWrapper> {
         Arc::new(FileWrapper(Mutex::new(file)))
     }
@@ -91,6 +109,12 @@ impl VfsNodeOps for FileWrapper<'static> {
         file.seek(SeekFrom::Start(size)).map_err(as_vfs_err)?; // TODO: more efficient
         file.truncate().map_err(as_vfs_err)
     }
+
+    /// Writes the directory entry (size, first cluster) and flushes the disk.
+    /// The data itself is written through by `write_at`.
+    fn fsync(&self) -> VfsResult {
+        self.0.lock().flush().map_err(as_vfs_err)
+    }
 }
 
 impl VfsNodeOps for DirWrapper<'static> {
@@ -244,7 +268,7 @@ impl Write for Disk {
         Ok(write_len)
     }
     fn flush(&mut self) -> Result<(), Self::Error> {
-        Ok(())
+        Disk::flush(self).map_err(|_| ())
     }
 }
 
@@ -26,8 +26,10 @@
 extern crate log;
 extern crate alloc;
 
+mod attrs;
 mod dev;
 mod fs;
+mod links;
 mod mounts;
 mod root;
 
@@ -46,5 +48,6 @@ pub fn init_filesystems(mut blk_devs: AxDeviceContainer<AxBlockDevice>) {
 
     let dev = blk_devs.take_one().expect("No block device found!");
     info!("  use block device 0: {:?}", dev.device_name());
+    self::dev::register_devices(core::iter::from_fn(|| blk_devs.take_one()));
     self::root::init_rootfs(self::dev::Disk::new(dev));
 }
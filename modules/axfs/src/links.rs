@@ -0,0 +1,161 @@
+//! Hard links and symbolic links.
+//!
+//! [`axfs_vfs`] has no link operations, so every mounted filesystem is paired
+//! with a [`LinkOps`] that implements them. All paths given to [`LinkOps`] are
+//! relative to the root of that filesystem.
+//!
+//! - ext4 links are native, with `lwext4` (see [`crate::fs::ext4fs`]) as well
+//!   as `ext4_rs` (see `crate::fs::ext4`).
+//! - ramfs links are native, see [`RamLinks`].
+//! - FAT has no links at all. Symbolic links are stored as regular files that
+//!   start with [`SYMLINK_MAGIC`], the same way Cygwin does, see [`MarkerLinks`].
+
+use alloc::{string::String, sync::Arc, vec};
+use axfs_vfs::{VfsError, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
+
+#[cfg(feature = "ramfs")]
+use crate::fs::ramfs::{FileNode, RamFileSystem, SymlinkNode};
+
+/// The beginning of a file that stores a symbolic link.
+pub const SYMLINK_MAGIC: &[u8] = b"!<symlink>";
+/// Maximum length of the target of a symbolic link, i.e. `PATH_MAX`.
+pub const PATH_MAX: usize = 4096;
+
+/// Link operations of a mounted filesystem.
+pub trait LinkOps: Send + Sync {
+    /// Creates a new name `dst_path` for the existing file `src_path`.
+    fn link(&self, src_path: &str, dst_path: &str) -> VfsResult;
+
+    /// Creates a symbolic link `path` that points to `target`.
+    fn symlink(&self, target: &str, path: &str) -> VfsResult;
+
+    /// Returns the target of `path` if it is a symbolic link.
+    fn readlink(&self, path: &str) -> Option<String>;
+
+    /// Returns the number of names of the file `path`.
+    fn link_count(&self, _path: &str) -> VfsResult<u64> {
+        Ok(1)
+    }
+
+    /// Returns the inode number of the existing file `path`, which all its
+    /// names share, or `None` if the filesystem has no inode numbers.
+    fn inode(&self, _path: &str) -> Option<u64> {
+        None
+    }
+}
+
+/// Filesystems without any links, such as devfs.
+pub struct NoLinks;
+
+impl LinkOps for NoLinks {
+    fn link(&self, _src_path: &str, _dst_path: &str) -> VfsResult {
+        Err(VfsError::Unsupported)
+    }
+
+    fn symlink(&self, _target: &str, _path: &str) -> VfsResult {
+        Err(VfsError::Unsupported)
+    }
+
+    fn readlink(&self, _path: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Symbolic links stored as marker files, for filesystems without links.
+///
+/// Hard links are not supported, as on Linux `vfat`.
+pub struct MarkerLinks {
+    fs: Arc<dyn VfsOps>,
+}
+
+impl MarkerLinks {
+    pub fn new(fs: Arc<dyn VfsOps>) -> Self {
+        Self { fs }
+    }
+}
+
+impl LinkOps for MarkerLinks {
+    fn link(&self, _src_path: &str, _dst_path: &str) -> VfsResult {
+        Err(VfsError::Unsupported)
+    }
+
+    fn symlink(&self, target: &str, path: &str) -> VfsResult {
+        write_marker(&self.fs.root_dir(), target, path)
+    }
+
+    fn readlink(&self, path: &str) -> Option<String> {
+        read_marker(&self.fs.root_dir().lookup(path).ok()?)
+    }
+}
+
+/// Links of a ramfs, which keeps both kinds natively: the names of a hard
+/// link share one node and a symbolic link is a [`SymlinkNode`].
+#[cfg(feature = "ramfs")]
+pub struct RamLinks {
+    fs: Arc<RamFileSystem>,
+}
+
+#[cfg(feature = "ramfs")]
+impl RamLinks {
+    pub fn new(fs: Arc<RamFileSystem>) -> Self {
+        Self { fs }
+    }
+}
+
+#[cfg(feature = "ramfs")]
+impl LinkOps for RamLinks {
+    fn link(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        self.fs.root_dir_node().link(src_path, dst_path)
+    }
+
+    fn symlink(&self, target: &str, path: &str) -> VfsResult {
+        self.fs.root_dir_node().symlink(target, path)
+    }
+
+    fn readlink(&self, path: &str) -> Option<String> {
+        let node = self.fs.root_dir().lookup(path).ok()?;
+        let link = node.as_any().downcast_ref::<SymlinkNode>()?;
+        Some(link.target().into())
+    }
+
+    fn link_count(&self, path: &str) -> VfsResult<u64> {
+        let node = self.fs.root_dir().lookup(path)?;
+        let any = node.as_any();
+        if let Some(file) = any.downcast_ref::<FileNode>() {
+            Ok(file.link_count())
+        } else if let Some(link) = any.downcast_ref::<SymlinkNode>() {
+            Ok(link.link_count())
+        } else {
+            Ok(1)
+        }
+    }
+
+    fn inode(&self, path: &str) -> Option<u64> {
+        // a node keeps its address as long as it has a name
+        let node = self.fs.root_dir().lookup(path).ok()?;
+        Some(Arc::as_ptr(&node) as *const () as u64)
+    }
+}
+
+/// Creates the marker file `path` under `root` that points to `target`.
+fn write_marker(root: &VfsNodeRef, target: &str, path: &str) -> VfsResult {
+    root.create(path, VfsNodeType::File)?;
+    let node = root.clone().lookup(path)?;
+    node.write_at(0, SYMLINK_MAGIC)?;
+    node.write_at(SYMLINK_MAGIC.len() as u64, target.as_bytes())?;
+    Ok(())
+}
+
+/// Returns the target if `node` is a marker file written by [`write_marker`].
+fn read_marker(node: &VfsNodeRef) -> Option<String> {
+    let attr = node.get_attr().ok()?;
+    let size = attr.size() as usize;
+    if !attr.is_file() || size <= SYMLINK_MAGIC.len() || size > SYMLINK_MAGIC.len() + PATH_MAX {
+        return None;
+    }
+    let mut buf = vec![0u8; size];
+    if node.read_at(0, &mut buf).ok()? != size || !buf.starts_with(SYMLINK_MAGIC) {
+        return None;
+    }
+    String::from_utf8(buf.split_off(SYMLINK_MAGIC.len())).ok()
+}
@@ -3,6 +3,31 @@ use axfs_vfs::{VfsNodeType, VfsOps, VfsResult};
 
 use crate::fs;
 
+/// Opens the FAT volume on `disk` for a runtime mount.
+#[cfg(all(feature = "fatfs", not(feature = "myfs")))]
+pub(crate) fn fatfs(disk: crate::dev::Disk) -> VfsResult<Arc<fs::fatfs::FatFileSystem>> {
+    let fat = Arc::new(fs::fatfs::FatFileSystem::try_new(disk)?);
+    // SAFETY: the root directory is owned by the filesystem and dropped
+    // before it.
+    unsafe { &*Arc::as_ptr(&fat) }.init();
+    Ok(fat)
+}
+
+/// Opens the ext4 volume on `disk` for a runtime mount.
+#[cfg(all(
+    feature = "ext4_rs",
+    not(any(feature = "myfs", feature = "fatfs", feature = "ext4fs"))
+))]
+pub(crate) fn ext4(mut disk: crate::dev::Disk) -> VfsResult<Arc<fs::ext4::Ext4FileSystem>> {
+    // `ext4_rs` panics on a volume without an ext4 superblock, whose magic is
+    // at 0x38 in the block at 1024.
+    let block = disk.read_offset(1024);
+    if u16::from_le_bytes([block[0x38], block[0x39]]) != 0xef53 {
+        return Err(axfs_vfs::VfsError::InvalidInput);
+    }
+    Ok(Arc::new(fs::ext4::Ext4FileSystem::new(disk)))
+}
+
 #[cfg(feature = "devfs")]
 pub(crate) fn devfs() -> Arc<fs::devfs::DeviceFileSystem> {
     let null = fs::devfs::NullDev;
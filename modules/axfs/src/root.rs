@@ -2,32 +2,178 @@
 //!
 //! TODO: it doesn't work very well if the mount points have containment relationships.
 
-use alloc::{string::String, sync::Arc, vec::Vec};
-use axerrno::{AxError, AxResult, ax_err};
+use alloc::{
+    boxed::Box,
+    collections::VecDeque,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use axerrno::{AxError, AxResult, LinuxError, ax_err, ax_err_type};
 use axfs_vfs::{VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType, VfsOps, VfsResult};
 use axsync::Mutex;
 use lazyinit::LazyInit;
 
-use crate::{api::FileType, fs, mounts};
+#[cfg(feature = "ramfs")]
+use crate::links::RamLinks;
+use crate::{
+    api::FileType,
+    attrs::{AttrOps, MemAttrs},
+    fops::{Credentials, FileAccess, FileSystemStat, MountInfo},
+    fs,
+    links::{LinkOps, MarkerLinks, NoLinks, PATH_MAX},
+    mounts,
+};
 
 static CURRENT_DIR_PATH: Mutex<String> = Mutex::new(String::new());
 static CURRENT_DIR: LazyInit<Mutex<VfsNodeRef>> = LazyInit::new();
 
+/// Magic number of tmpfs, reported for the ramfs-backed mounts.
+const TMPFS_MAGIC: u64 = 0x0102_1994;
+const PROC_SUPER_MAGIC: u64 = 0x9fa0;
+const SYSFS_MAGIC: u64 = 0x6265_6572;
+/// Block size reported for the in-memory filesystems.
+const MEM_BLOCK_SIZE: u64 = 4096;
+/// Maximum number of symbolic links followed while resolving a path.
+const MAX_SYMLINK_FOLLOWS: usize = 40;
+
+/// Statistics of a mounted filesystem, see [`statfs`].
+type StatFn = Box<dyn Fn() -> FileSystemStat + Send + Sync>;
+
 struct MountPoint {
-    path: &'static str,
+    /// Absolute path of the mount point, without the trailing '/'.
+    path: String,
+    /// The device or name it was mounted from, shown in `/proc/mounts`.
+    source: String,
+    fs_type: &'static str,
     fs: Arc<dyn VfsOps>,
+    stat: StatFn,
+    links: Arc<dyn LinkOps>,
+    attrs: Arc<dyn AttrOps>,
 }
 
 struct RootDirectory {
+    /// The device the main filesystem is on.
+    main_source: String,
     main_fs: Arc<dyn VfsOps>,
-    mounts: Vec<MountPoint>,
+    main_type: &'static str,
+    main_stat: fn() -> FileSystemStat,
+    /// Writes the cached data of the main filesystem to the disk. The other
+    /// mounts live in memory.
+    main_sync: fn() -> VfsResult,
+    main_links: Arc<dyn LinkOps>,
+    main_attrs: Arc<dyn AttrOps>,
+    mounts: Mutex<Vec<Arc<MountPoint>>>,
 }
 
 static ROOT_DIR: LazyInit<Arc<RootDirectory>> = LazyInit::new();
 
 impl MountPoint {
-    pub fn new(path: &'static str, fs: Arc<dyn VfsOps>) -> Self {
-        Self { path, fs }
+    pub fn new(
+        source: &str,
+        fs_type: &'static str,
+        fs: Arc<dyn VfsOps>,
+        stat: StatFn,
+        links: Arc<dyn LinkOps>,
+        attrs: Arc<dyn AttrOps>,
+    ) -> Self {
+        Self {
+            path: String::new(),
+            source: source.into(),
+            fs_type,
+            fs,
+            stat,
+            links,
+            attrs,
+        }
+    }
+
+    /// Creates a new filesystem of `fs_type` on `source` to be mounted.
+    ///
+    /// Block devices can only hold the disk filesystem the kernel is built
+    /// with. FAT and `ext4_rs` volumes can be mounted besides the root one,
+    /// while `lwext4` keeps a single volume at its own `/`.
+    fn create(source: &str, fs_type: &str) -> AxResult<Self> {
+        match fs_type {
+            #[cfg(feature = "ramfs")]
+            "tmpfs" | "ramfs" => {
+                let ramfs = mounts::ramfs();
+                Ok(Self::new(
+                    source,
+                    "tmpfs",
+                    ramfs.clone(),
+                    Box::new(|| FileSystemStat::new(TMPFS_MAGIC, MEM_BLOCK_SIZE)),
+                    Arc::new(RamLinks::new(ramfs)),
+                    Arc::new(MemAttrs::new()),
+                ))
+            }
+            #[cfg(feature = "devfs")]
+            "devtmpfs" | "devfs" => Ok(Self::new(
+                source,
+                "devtmpfs",
+                mounts::devfs(),
+                Box::new(|| FileSystemStat::new(TMPFS_MAGIC, MEM_BLOCK_SIZE)),
+                Arc::new(NoLinks),
+                Arc::new(MemAttrs::new()),
+            )),
+            #[cfg(feature = "procfs")]
+            "proc" => {
+                let procfs = mounts::procfs()?;
+                Ok(Self::new(
+                    source,
+                    "proc",
+                    procfs.clone(),
+                    Box::new(|| FileSystemStat::new(PROC_SUPER_MAGIC, MEM_BLOCK_SIZE)),
+                    Arc::new(RamLinks::new(procfs)),
+                    Arc::new(MemAttrs::new()),
+                ))
+            }
+            #[cfg(feature = "sysfs")]
+            "sysfs" => {
+                let sysfs = mounts::sysfs()?;
+                Ok(Self::new(
+                    source,
+                    "sysfs",
+                    sysfs.clone(),
+                    Box::new(|| FileSystemStat::new(SYSFS_MAGIC, MEM_BLOCK_SIZE)),
+                    Arc::new(RamLinks::new(sysfs)),
+                    Arc::new(MemAttrs::new()),
+                ))
+            }
+            #[cfg(all(feature = "fatfs", not(feature = "myfs")))]
+            "vfat" | "fat" | "msdos" => {
+                let fat = mounts::fatfs(crate::dev::Disk::take(source)?)?;
+                let stat_fs = fat.clone();
+                Ok(Self::new(
+                    source,
+                    "vfat",
+                    fat.clone(),
+                    Box::new(move || stat_fs.stat()),
+                    Arc::new(MarkerLinks::new(fat)),
+                    Arc::new(MemAttrs::new()),
+                ))
+            }
+            #[cfg(all(
+                feature = "ext4_rs",
+                not(any(feature = "myfs", feature = "fatfs", feature = "ext4fs"))
+            ))]
+            "ext4" => {
+                let ext4 = mounts::ext4(crate::dev::Disk::take(source)?)?;
+                let stat_fs = ext4.clone();
+                Ok(Self::new(
+                    source,
+                    "ext4",
+                    ext4.clone(),
+                    Box::new(move || stat_fs.stat()),
+                    ext4.clone(),
+                    ext4,
+                ))
+            }
+            #[cfg(all(feature = "ext4fs", not(any(feature = "myfs", feature = "fatfs"))))]
+            "ext4" => ax_err!(Unsupported, "lwext4 only holds the root volume"),
+            _ => ax_err!(Unsupported, "unsupported filesystem type"),
+        }
     }
 }
 
@@ -38,36 +184,154 @@ impl Drop for MountPoint {
 }
 
 impl RootDirectory {
-    pub const fn new(main_fs: Arc<dyn VfsOps>) -> Self {
+    pub const fn new(
+        main_source: String,
+        main_fs: Arc<dyn VfsOps>,
+        main_type: &'static str,
+        main_stat: fn() -> FileSystemStat,
+        main_sync: fn() -> VfsResult,
+        main_links: Arc<dyn LinkOps>,
+        main_attrs: Arc<dyn AttrOps>,
+    ) -> Self {
         Self {
+            main_source,
             main_fs,
-            mounts: Vec::new(),
+            main_type,
+            main_stat,
+            main_sync,
+            main_links,
+            main_attrs,
+            mounts: Mutex::new(Vec::new()),
         }
     }
 
-    pub fn mount(&mut self, path: &'static str, fs: Arc<dyn VfsOps>) -> AxResult {
-        if path == "/" {
+    /// Mounts `mp` on the existing directory `path` (absolute, already
+    /// resolved).
+    fn mount(self: &Arc<Self>, path: &str, mut mp: MountPoint) -> AxResult {
+        let path = path.trim_end_matches('/');
+        if path.is_empty() {
             return ax_err!(InvalidInput, "cannot mount root filesystem");
         }
         if !path.starts_with('/') {
             return ax_err!(InvalidInput, "mount path must start with '/'");
         }
-        if self.mounts.iter().any(|mp| mp.path == path) {
-            return ax_err!(InvalidInput, "mount point already exists");
+        let node = self.clone().lookup(path)?;
+        if !node.get_attr()?.is_dir() {
+            return ax_err!(NotADirectory);
+        }
+        let mut mounts = self.mounts.lock();
+        if mounts.iter().any(|mp| mp.path == path) {
+            return ax_err!(ResourceBusy, "mount point already exists");
         }
-        // create the mount point in the main filesystem if it does not exist
-        self.main_fs.root_dir().create(path, FileType::Dir)?;
-        fs.mount(path, self.main_fs.root_dir().lookup(path)?)?;
-        self.mounts.push(MountPoint::new(path, fs));
+        mp.fs.mount(path, node)?;
+        mp.path = path.into();
+        mounts.push(Arc::new(mp));
+        drop(mounts);
+        self.update_proc_mounts();
         Ok(())
     }
 
-    pub fn _umount(&mut self, path: &str) {
-        self.mounts.retain(|mp| mp.path != path);
+    /// Unmounts the filesystem mounted on `path` (absolute, already
+    /// resolved). It is busy if another filesystem is mounted under it or
+    /// `in_use` returns true for `path`.
+    ///
+    /// Working directories and open files are not tracked here, `in_use`
+    /// checks them. It is called without the mount table locked, as it may
+    /// look up paths itself.
+    fn umount(&self, path: &str, in_use: impl FnOnce(&str) -> bool) -> AxResult {
+        let path = path.trim_end_matches('/');
+        if self.contains(path) && in_use(path) {
+            return ax_err!(ResourceBusy);
+        }
+        let mut mounts = self.mounts.lock();
+        let Some(idx) = mounts.iter().position(|mp| mp.path == path) else {
+            return ax_err!(InvalidInput, "not a mount point");
+        };
+        let in_mount = |p: &str| {
+            p.strip_prefix(path)
+                .is_some_and(|rest| rest.starts_with('/'))
+        };
+        if mounts.iter().any(|mp| in_mount(&mp.path)) {
+            return ax_err!(ResourceBusy);
+        }
+        // the filesystem is unmounted when the last reference is dropped
+        mounts.remove(idx);
+        drop(mounts);
+        self.update_proc_mounts();
+        Ok(())
     }
 
     pub fn contains(&self, path: &str) -> bool {
-        self.mounts.iter().any(|mp| mp.path == path)
+        self.mounts.lock().iter().any(|mp| mp.path == path)
+    }
+
+    /// The main filesystem and the mounted ones, in mount order.
+    fn mount_points(&self) -> Vec<MountInfo> {
+        let main = MountInfo {
+            source: self.main_source.clone(),
+            path: "/".into(),
+            fs_type: self.main_type,
+        };
+        let mounts = self.mounts.lock();
+        let mounted = mounts.iter().map(|mp| MountInfo {
+            source: mp.source.clone(),
+            path: mp.path.clone(),
+            fs_type: mp.fs_type,
+        });
+        core::iter::once(main).chain(mounted).collect()
+    }
+
+    /// The mount table in the format of `/proc/mounts`.
+    fn mount_table(&self) -> String {
+        self.mount_points()
+            .iter()
+            .map(|mp| format!("{} {} {} rw 0 0\n", mp.source, mp.path, mp.fs_type))
+            .collect()
+    }
+
+    /// Rewrites `mounts` in every mounted procfs.
+    fn update_proc_mounts(&self) {
+        let table = self.mount_table();
+        let procs: Vec<_> = self
+            .mounts
+            .lock()
+            .iter()
+            .filter(|mp| mp.fs_type == "proc")
+            .cloned()
+            .collect();
+        for mp in procs {
+            let root = mp.fs.root_dir();
+            let ret = match root.create("mounts", VfsNodeType::File) {
+                Ok(()) | Err(AxError::AlreadyExists) => root.lookup("mounts").and_then(|file| {
+                    file.truncate(0)?;
+                    file.write_at(0, table.as_bytes()).map(|_| ())
+                }),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = ret {
+                warn!("failed to update {}/mounts: {:?}", mp.path, e);
+            }
+        }
+    }
+
+    /// Finds the mount point that has the longest mounted path match with
+    /// `path` (already trimmed of '/'). Returns it and the length of the
+    /// matched prefix.
+    fn find_mount(&self, path: &str) -> Option<(Arc<MountPoint>, usize)> {
+        let mut found = None;
+        let mut max_len = 0;
+
+        // TODO: more efficient, e.g. trie
+        for mp in self.mounts.lock().iter() {
+            // skip the first '/'
+            let mp_path = &mp.path[1..];
+            let on_boundary = matches!(path.as_bytes().get(mp_path.len()), None | Some(b'/'));
+            if path.starts_with(mp_path) && on_boundary && mp_path.len() > max_len {
+                max_len = mp_path.len();
+                found = Some(mp.clone());
+            }
+        }
+        found.map(|mp| (mp, max_len))
     }
 
     fn lookup_mounted_fs<F, T>(&self, path: &str, f: F) -> AxResult<T>
@@ -80,25 +344,117 @@ impl RootDirectory {
             return self.lookup_mounted_fs(rest, f);
         }
 
-        let mut idx = 0;
-        let mut max_len = 0;
+        match self.find_mount(path) {
+            Some((mp, max_len)) => f(mp.fs.clone(), &path[max_len..]),
+            None => f(self.main_fs.clone(), path), // not matched any mount point
+        }
+    }
 
-        // Find the filesystem that has the longest mounted path match
-        // TODO: more efficient, e.g. trie
-        for (i, mp) in self.mounts.iter().enumerate() {
-            // skip the first '/'
-            if path.starts_with(&mp.path[1..]) && mp.path.len() - 1 > max_len {
-                max_len = mp.path.len() - 1;
-                idx = i;
-            }
+    /// Calls `f` with the link operations of the filesystem that `path` lives
+    /// on and the path relative to its root.
+    fn with_links<F, T>(&self, path: &str, f: F) -> T
+    where
+        F: FnOnce(&dyn LinkOps, &str) -> T,
+    {
+        let path = path.trim_matches('/');
+        let path = path.strip_prefix("./").unwrap_or(path);
+        match self.find_mount(path) {
+            Some((mp, max_len)) => f(mp.links.as_ref(), &path[max_len..]),
+            None => f(self.main_links.as_ref(), path),
         }
+    }
+
+    /// Calls `f` with the owner and mode operations of the filesystem that
+    /// `path` lives on and the path relative to its root.
+    fn with_attrs<F, T>(&self, path: &str, f: F) -> T
+    where
+        F: FnOnce(&dyn AttrOps, &str) -> T,
+    {
+        let path = path.trim_matches('/');
+        let path = path.strip_prefix("./").unwrap_or(path);
+        match self.find_mount(path) {
+            Some((mp, max_len)) => f(mp.attrs.as_ref(), &path[max_len..]),
+            None => f(self.main_attrs.as_ref(), path),
+        }
+    }
+
+    /// Whether the absolute paths `a` and `b` live on the same mounted
+    /// filesystem.
+    fn same_mount(&self, a: &str, b: &str) -> bool {
+        let mount_of = |path: &str| self.find_mount(path.trim_matches('/')).map(|(mp, _)| mp);
+        match (mount_of(a), mount_of(b)) {
+            (Some(a), Some(b)) => Arc::ptr_eq(&a, &b),
+            (a, b) => a.is_none() && b.is_none(),
+        }
+    }
 
-        if max_len == 0 {
-            f(self.main_fs.clone(), path) // not matched any mount point
-        } else {
-            f(self.mounts[idx].fs.clone(), &path[max_len..]) // matched at `idx`
+    /// Statistics of the filesystem that `path` (absolute) lives on.
+    fn stat_of(&self, path: &str) -> FileSystemStat {
+        match self.find_mount(path.trim_matches('/')) {
+            Some((mp, _)) => (mp.stat)(),
+            None => (self.main_stat)(),
         }
     }
+
+    /// Resolves the symbolic links in the absolute `path`.
+    ///
+    /// Intermediate components are always followed, the last one only if
+    /// `follow_last` is set or `path` ends with '/', as on Linux. The trailing
+    /// '/' of `path` is kept.
+    fn resolve(&self, path: &str, follow_last: bool) -> Result<String, LinuxError> {
+        let trailing_slash = path.ends_with('/') && path != "/";
+        let follow_last = follow_last || trailing_slash;
+        let mut rest: VecDeque<String> = components(path).collect();
+        let mut resolved = String::from("/");
+        let mut follows = 0;
+        while let Some(name) = rest.pop_front() {
+            match name.as_str() {
+                "." => continue,
+                ".." => {
+                    pop_component(&mut resolved);
+                    continue;
+                }
+                _ => {}
+            }
+            let candidate = resolved.clone() + &name;
+            if !rest.is_empty() || follow_last {
+                if let Some(target) = self.with_links(&candidate, |links, p| links.readlink(p)) {
+                    follows += 1;
+                    if follows > MAX_SYMLINK_FOLLOWS {
+                        return Err(LinuxError::ELOOP);
+                    }
+                    if target.starts_with('/') {
+                        resolved = String::from("/");
+                    }
+                    for name in components(&target).rev() {
+                        rest.push_front(name);
+                    }
+                    continue;
+                }
+            }
+            resolved = candidate + "/";
+        }
+        if resolved != "/" && !trailing_slash {
+            resolved.pop();
+        }
+        Ok(resolved)
+    }
+}
+
+fn components(path: &str) -> impl DoubleEndedIterator<Item = String> + '_ {
+    path.split('/')
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+}
+
+/// Removes the last component of `path` that ends with '/'. The root is kept.
+fn pop_component(path: &mut String) {
+    if path == "/" {
+        return;
+    }
+    path.pop();
+    let len = path.rfind('/').map_or(0, |pos| pos + 1);
+    path.truncate(len);
 }
 
 impl VfsNodeOps for RootDirectory {
@@ -129,15 +485,23 @@ impl VfsNodeOps for RootDirectory {
             } else {
                 fs.root_dir().remove(rest_path)
             }
-        })
+        })?;
+        self.with_attrs(path, |attrs, rest_path| attrs.removed(rest_path));
+        Ok(())
     }
 
     fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        if !self.same_mount(src_path, dst_path) {
+            return ax_err!(CrossesDevices, "rename across filesystems");
+        }
+        // both paths are given to the filesystem relative to its root
+        let dst_path =
+            self.lookup_mounted_fs(dst_path, |_, rest_path| Ok(rest_path.to_string()))?;
         self.lookup_mounted_fs(src_path, |fs, rest_path| {
-            if rest_path.is_empty() {
+            if rest_path.is_empty() || dst_path.is_empty() {
                 ax_err!(PermissionDenied) // cannot rename mount points
             } else {
-                fs.root_dir().rename(rest_path, dst_path)
+                fs.root_dir().rename(rest_path, &dst_path)
             }
         })
     }
@@ -147,17 +511,34 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
     cfg_if::cfg_if! {
         if #[cfg(feature = "myfs")] { // override the default filesystem
             let main_fs = fs::myfs::new_myfs(disk);
+            let main_type = "myfs";
+            let main_stat: fn() -> FileSystemStat = || FileSystemStat::new(0, fs::BLOCK_SIZE as u64);
+            let main_sync: fn() -> VfsResult = || Ok(());
+            let main_links: Arc<dyn LinkOps> = Arc::new(MarkerLinks::new(main_fs.clone()));
+            let main_attrs: Arc<dyn AttrOps> = Arc::new(MemAttrs::new());
         } else if #[cfg(feature = "fatfs")] {
             warn!("FAT FS");
             static FAT_FS: LazyInit<Arc<fs::fatfs::FatFileSystem>> = LazyInit::new();
             FAT_FS.init_once(Arc::new(fs::fatfs::FatFileSystem::new(disk)));
             FAT_FS.init();
             let main_fs = FAT_FS.clone();
+            let main_type = "vfat";
+            let main_stat: fn() -> FileSystemStat = || FAT_FS.stat();
+            // file data is written through, directory entries on `fsync` of each file
+            let main_sync: fn() -> VfsResult = || Ok(());
+            let main_links: Arc<dyn LinkOps> = Arc::new(MarkerLinks::new(main_fs.clone()));
+            // FAT has no owners or modes
+            let main_attrs: Arc<dyn AttrOps> = Arc::new(MemAttrs::new());
         } else if #[cfg(feature = "ext4fs")] {
             warn!("EXT4 FS");
             static EXT4_FS: LazyInit<Arc<fs::ext4fs::Ext4FileSystem>> = LazyInit::new();
             EXT4_FS.init_once(Arc::new(fs::ext4fs::Ext4FileSystem::new(disk)));
             let main_fs = EXT4_FS.clone();
+            let main_type = "ext4";
+            let main_stat: fn() -> FileSystemStat = || EXT4_FS.stat();
+            let main_sync: fn() -> VfsResult = || EXT4_FS.sync();
+            let main_links: Arc<dyn LinkOps> = EXT4_FS.clone();
+            let main_attrs: Arc<dyn AttrOps> = EXT4_FS.clone();
         } else if #[cfg(feature = "ext4_rs")] {
             warn!("EXT4 FS RS");
             static EXT4_FS: LazyInit<Arc<fs::ext4::Ext4FileSystem>> = LazyInit::new();
@@ -165,43 +546,58 @@ pub(crate) fn init_rootfs(disk: crate::dev::Disk) {
             EXT4_FS.init_once(Arc::new(fs::ext4::Ext4FileSystem::new(disk)));
             warn!("CHECKPOINT 0.2");
             let main_fs = EXT4_FS.clone();
+            let main_type = "ext4";
+            let main_stat: fn() -> FileSystemStat = || EXT4_FS.stat();
+            let main_sync: fn() -> VfsResult = || Ok(());
+            let main_links: Arc<dyn LinkOps> = EXT4_FS.clone();
+            let main_attrs: Arc<dyn AttrOps> = EXT4_FS.clone();
         }
     }
 
-    let mut root_dir = RootDirectory::new(main_fs);
+    let main_source = format!("/dev/{}", crate::dev::device_name(0));
+    let root_dir = RootDirectory::new(
+        main_source,
+        main_fs,
+        main_type,
+        main_stat,
+        main_sync,
+        main_links,
+        main_attrs,
+    );
+    ROOT_DIR.init_once(Arc::new(root_dir));
+    CURRENT_DIR.init_once(Mutex::new(ROOT_DIR.clone()));
+    *CURRENT_DIR_PATH.lock() = "/".into();
 
     #[cfg(feature = "devfs")]
-    root_dir
-        .mount("/dev", mounts::devfs())
-        .expect("failed to mount devfs at /dev");
-
+    init_mount("/dev", "devtmpfs");
     #[cfg(feature = "ramfs")]
-    root_dir
-        .mount("/tmp", mounts::ramfs())
-        .expect("failed to mount ramfs at /tmp");
-
+    init_mount("/tmp", "tmpfs");
     // Mount another ramfs as procfs
     #[cfg(feature = "procfs")]
-    root_dir // should not fail
-        .mount("/proc", mounts::procfs().unwrap())
-        .expect("fail to mount procfs at /proc");
-
+    init_mount("/proc", "proc");
     // Mount another ramfs as sysfs
     #[cfg(feature = "sysfs")]
-    root_dir // should not fail
-        .mount("/sys", mounts::sysfs().unwrap())
-        .expect("fail to mount sysfs at /sys");
+    init_mount("/sys", "sysfs");
+}
 
-    ROOT_DIR.init_once(Arc::new(root_dir));
-    CURRENT_DIR.init_once(Mutex::new(ROOT_DIR.clone()));
-    *CURRENT_DIR_PATH.lock() = "/".into();
+/// Mounts a builtin filesystem at `path`, creating the directory in the main
+/// filesystem if it does not exist.
+#[allow(dead_code)]
+fn init_mount(path: &str, fs_type: &str) {
+    let mount = || {
+        ROOT_DIR.main_fs.root_dir().create(path, FileType::Dir)?;
+        ROOT_DIR.mount(path, MountPoint::create(fs_type, fs_type)?)
+    };
+    if let Err(e) = mount() {
+        panic!("failed to mount {} at {}: {:?}", fs_type, path, e);
+    }
 }
 
-fn parent_node_of(dir: Option<&VfsNodeRef>, path: &str) -> VfsNodeRef {
+fn parent_node_of(path: &str) -> VfsNodeRef {
     if path.starts_with('/') {
         ROOT_DIR.clone()
     } else {
-        dir.cloned().unwrap_or_else(|| CURRENT_DIR.lock().clone())
+        CURRENT_DIR.lock().clone()
     }
 }
 
@@ -214,11 +610,29 @@ pub(crate) fn absolute_path(path: &str) -> AxResult<String> {
     }
 }
 
-pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+pub(crate) fn resolve_path(path: &str, follow_last: bool) -> Result<String, LinuxError> {
+    ROOT_DIR.resolve(&absolute_path(path)?, follow_last)
+}
+
+/// Resolves the symbolic links in `path` relative to the directory at `dir`
+/// (the current directory if `None`) and returns the absolute path.
+///
+/// [`AxError`] has no `ELOOP`, `InvalidData` is returned when too many
+/// symbolic links are followed. Other errors are passed through.
+fn resolve_at(dir: Option<&str>, path: &str, follow_last: bool) -> AxResult<String> {
+    let path = absolute_path(&path_at(dir, path))?;
+    // only exceeding `MAX_SYMLINK_FOLLOWS` makes the resolution fail
+    ROOT_DIR
+        .resolve(&path, follow_last)
+        .map_err(|_| ax_err_type!(InvalidData, "too many levels of symbolic links"))
+}
+
+fn lookup_at(dir: Option<&str>, path: &str, follow_last: bool) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     }
-    let node = parent_node_of(dir, path).lookup(path)?;
+    let path = resolve_at(dir, path, follow_last)?;
+    let node = parent_node_of(&path).lookup(&path)?;
     if path.ends_with('/') && !node.get_attr()?.is_dir() {
         ax_err!(NotADirectory)
     } else {
@@ -226,38 +640,50 @@ pub(crate) fn lookup(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRe
     }
 }
 
-pub(crate) fn create_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult<VfsNodeRef> {
+/// Looks up `path`, following symbolic links.
+pub(crate) fn lookup(dir: Option<&str>, path: &str) -> AxResult<VfsNodeRef> {
+    lookup_at(dir, path, true)
+}
+
+pub(crate) fn create_file(dir: Option<&str>, path: &str) -> AxResult<VfsNodeRef> {
     if path.is_empty() {
         return ax_err!(NotFound);
     } else if path.ends_with('/') {
         return ax_err!(NotADirectory);
     }
-    let parent = parent_node_of(dir, path);
-    parent.create(path, VfsNodeType::File)?;
-    parent.lookup(path)
+    // a dangling symbolic link creates its target
+    let path = resolve_at(dir, path, true)?;
+    let parent = parent_node_of(&path);
+    parent.create(&path, VfsNodeType::File)?;
+    parent.lookup(&path)
 }
 
-pub(crate) fn create_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    match lookup(dir, path) {
+pub(crate) fn create_dir(dir: Option<&str>, path: &str) -> AxResult {
+    match lookup_at(dir, path, false) {
         Ok(_) => ax_err!(AlreadyExists),
-        Err(AxError::NotFound) => parent_node_of(dir, path).create(path, VfsNodeType::Dir),
+        Err(AxError::NotFound) => {
+            let path = resolve_at(dir, path, false)?;
+            parent_node_of(&path).create(&path, VfsNodeType::Dir)
+        }
         Err(e) => Err(e),
     }
 }
 
-pub(crate) fn remove_file(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-    let node = lookup(dir, path)?;
+/// Removes the file `path`. A symbolic link itself is removed, not its target.
+pub(crate) fn remove_file(dir: Option<&str>, path: &str) -> AxResult {
+    let path = &resolve_at(dir, path, false)?;
+    let node = lookup_at(dir, path, false)?;
     let attr = node.get_attr()?;
     if attr.is_dir() {
         ax_err!(IsADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        parent_node_of(path).remove(path)
     }
 }
 
-pub(crate) fn remove_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
+pub(crate) fn remove_dir(dir: Option<&str>, path: &str) -> AxResult {
     if path.is_empty() {
         return ax_err!(NotFound);
     }
@@ -271,18 +697,19 @@ pub(crate) fn remove_dir(dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
     {
         return ax_err!(InvalidInput);
     }
+    let path = &resolve_at(dir, path, false)?;
     if ROOT_DIR.contains(&absolute_path(path)?) {
         return ax_err!(PermissionDenied);
     }
 
-    let node = lookup(dir, path)?;
+    let node = lookup_at(dir, path, false)?;
     let attr = node.get_attr()?;
     if !attr.is_dir() {
         ax_err!(NotADirectory)
     } else if !attr.perm().owner_writable() {
         ax_err!(PermissionDenied)
     } else {
-        parent_node_of(dir, path).remove(path)
+        parent_node_of(path).remove(path)
     }
 }
 
@@ -291,7 +718,7 @@ pub(crate) fn current_dir() -> AxResult<String> {
 }
 
 pub(crate) fn set_current_dir(path: &str) -> AxResult {
-    let mut abs_path = absolute_path(path)?;
+    let mut abs_path = resolve_at(None, path, true)?;
     if !abs_path.ends_with('/') {
         abs_path += "/";
     }
@@ -314,10 +741,198 @@ pub(crate) fn set_current_dir(path: &str) -> AxResult {
     }
 }
 
+pub(crate) fn statfs(path: &str) -> AxResult<FileSystemStat> {
+    lookup(None, path)?;
+    Ok(ROOT_DIR.stat_of(&resolve_at(None, path, true)?))
+}
+
+/// Mounts a new filesystem of `fs_type` on `source` at the directory
+/// `target`. `source` names a block device for disk filesystems and is only
+/// shown in the mount table otherwise.
+pub(crate) fn mount(source: &str, target: &str, fs_type: &str) -> AxResult {
+    let target = resolve_at(None, target, true)?;
+    ROOT_DIR.mount(&target, MountPoint::create(source, fs_type)?)
+}
+
+/// Unmounts the filesystem mounted at `target`, see [`RootDirectory::umount`].
+pub(crate) fn umount(target: &str, in_use: impl FnOnce(&str) -> bool) -> AxResult {
+    ROOT_DIR.umount(&resolve_at(None, target, true)?, in_use)
+}
+
+/// The mount table in the format of `/proc/mounts`.
+pub(crate) fn mount_table() -> String {
+    ROOT_DIR.mount_table()
+}
+
+/// The entry of the mount table whose mount point is `path`, which is
+/// absolute and already resolved.
+pub(crate) fn mount_point(path: &str) -> Option<MountInfo> {
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    };
+    ROOT_DIR
+        .mount_points()
+        .into_iter()
+        .find(|mp| mp.path == path)
+}
+
+pub(crate) fn sync() -> AxResult {
+    (ROOT_DIR.main_sync)()
+}
+
 pub(crate) fn rename(old: &str, new: &str) -> AxResult {
-    if parent_node_of(None, new).lookup(new).is_ok() {
+    let old = &resolve_at(None, old, false)?;
+    let new = &resolve_at(None, new, false)?;
+    if parent_node_of(new).lookup(new).is_ok() {
         warn!("dst file already exist, now remove it");
         remove_file(None, new)?;
     }
-    parent_node_of(None, old).rename(old, new)
+    parent_node_of(old).rename(old, new)?;
+    ROOT_DIR.with_attrs(new, |_, dst_path| {
+        ROOT_DIR.with_attrs(old, |attrs, src_path| attrs.renamed(src_path, dst_path))
+    });
+    Ok(())
+}
+
+/// Creates the hard link `new` to the file `old`, which is not followed if it
+/// is a symbolic link. Both must be on the same mounted filesystem.
+pub(crate) fn link(old: &str, new: &str) -> AxResult {
+    let old = &resolve_at(None, old, false)?;
+    let new = &resolve_at(None, new, false)?;
+    if lookup_at(None, old, false)?.get_attr()?.is_dir() {
+        return ax_err!(PermissionDenied, "hard link to a directory");
+    }
+    if lookup_at(None, new, false).is_ok() {
+        return ax_err!(AlreadyExists);
+    }
+    if !ROOT_DIR.same_mount(old, new) {
+        return ax_err!(CrossesDevices, "hard link across filesystems");
+    }
+    ROOT_DIR.with_links(new, |_, dst_path| {
+        ROOT_DIR.with_links(old, |links, src_path| links.link(src_path, dst_path))
+    })
+}
+
+/// Creates the symbolic link `path` that points to `target`. The target is
+/// stored as is and need not exist.
+pub(crate) fn symlink(target: &str, path: &str) -> AxResult {
+    if target.is_empty() || target.len() >= PATH_MAX {
+        return ax_err!(InvalidInput);
+    }
+    let path = &resolve_at(None, path, false)?;
+    if lookup_at(None, path, false).is_ok() {
+        return ax_err!(AlreadyExists);
+    }
+    ROOT_DIR.with_links(path, |links, rest_path| links.symlink(target, rest_path))
+}
+
+/// Returns the target of the symbolic link `path`, `InvalidInput` if `path`
+/// exists but is not a symbolic link.
+pub(crate) fn read_link(path: &str) -> AxResult<String> {
+    let path = &resolve_at(None, path, false)?;
+    lookup_at(None, path, false)?;
+    ROOT_DIR
+        .with_links(path, |links, rest_path| links.readlink(rest_path))
+        .ok_or(AxError::InvalidInput)
+}
+
+pub(crate) fn link_count(path: &str) -> AxResult<u64> {
+    let path = &resolve_at(None, path, true)?;
+    lookup_at(None, path, false)?;
+    ROOT_DIR.with_links(path, |links, rest_path| links.link_count(rest_path))
+}
+
+/// Inode number of `path`, the same for all its hard links. The last
+/// component is only followed if `follow_last` is set. Filesystems without
+/// inode numbers get one made up from the absolute path.
+pub(crate) fn inode(path: &str, follow_last: bool) -> AxResult<u64> {
+    let path = &resolve_at(None, path, follow_last)?;
+    lookup_at(None, path, false)?;
+    let ino = ROOT_DIR.with_links(path, |links, rest_path| links.inode(rest_path));
+    Ok(ino.unwrap_or_else(|| path_inode(path)))
+}
+
+/// FNV-1a hash of the absolute `path` without the trailing '/', never 0.
+fn path_inode(path: &str) -> u64 {
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    };
+    let hash = path.bytes().fold(0xcbf2_9ce4_8422_2325, |hash: u64, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    hash.max(1)
+}
+
+/// Joins `path` to `dir`, the absolute path of the directory it is relative
+/// to. Without `dir` the path is kept relative to the current directory.
+pub(crate) fn path_at(dir: Option<&str>, path: &str) -> String {
+    match dir {
+        Some(dir) if !path.starts_with('/') => format!("{}/{}", dir.trim_end_matches('/'), path),
+        _ => path.into(),
+    }
+}
+
+/// Owner and mode of `path` relative to the directory at `dir`, following
+/// symbolic links.
+pub(crate) fn access_at(dir: Option<&str>, path: &str) -> AxResult<FileAccess> {
+    let path = &resolve_at(None, &path_at(dir, path), true)?;
+    let node = lookup(None, path)?;
+    ROOT_DIR.with_attrs(path, |attrs, rest_path| attrs.get(rest_path, &node))
+}
+
+/// Checks that `cred` may access `path` relative to the directory at `dir` as
+/// requested by `mask`, see [`Credentials::can_access`].
+pub(crate) fn check_access(
+    dir: Option<&str>,
+    path: &str,
+    cred: &Credentials,
+    mask: u16,
+) -> AxResult {
+    if cred.can_access(&access_at(dir, path)?, mask) {
+        Ok(())
+    } else {
+        ax_err!(PermissionDenied)
+    }
+}
+
+/// Checks that `cred` may create or remove entries in the directory that
+/// contains `path` relative to the directory at `dir`.
+pub(crate) fn check_parent_access(dir: Option<&str>, path: &str, cred: &Credentials) -> AxResult {
+    let path = path_at(dir, path);
+    let path = path.trim_end_matches('/');
+    let parent = match path.rfind('/') {
+        Some(0) => "/",
+        Some(pos) => &path[..pos],
+        None => ".",
+    };
+    check_access(None, parent, cred, 0o3)
+}
+
+/// Sets the owner and mode of a file that was just created at `path` relative
+/// to the directory at `dir`.
+pub(crate) fn init_access(dir: Option<&str>, path: &str, access: FileAccess) -> AxResult {
+    let path = &path_at(dir, path);
+    set_owner(path, Some(access.uid), Some(access.gid))?;
+    set_mode(path, access.mode)
+}
+
+/// Sets the permission bits of `path`, following symbolic links.
+pub(crate) fn set_mode(path: &str, mode: u16) -> AxResult {
+    let node = lookup(None, path)?;
+    let path = &resolve_at(None, path, true)?;
+    ROOT_DIR.with_attrs(path, |attrs, rest_path| {
+        attrs.set_mode(rest_path, &node, mode & 0o7777)
+    })
+}
+
+/// Sets the owner of `path`, following symbolic links. `None` keeps the
+/// current one.
+pub(crate) fn set_owner(path: &str, uid: Option<u32>, gid: Option<u32>) -> AxResult {
+    let node = lookup(None, path)?;
+    let path = &resolve_at(None, path, true)?;
+    ROOT_DIR.with_attrs(path, |attrs, rest_path| {
+        attrs.set_owner(rest_path, &node, uid, gid)
+    })
 }
@@ -252,6 +252,145 @@ fn test_devfs_ramfs() -> Result<()> {
     Ok(())
 }
 
+fn test_links() -> Result<()> {
+    // symbolic links work on every filesystem
+    let target = "/very/long/path/test.txt";
+    println!("test symbolic links to {:?}:", target);
+    let contents = fs::read_to_string(target)?;
+    fs::symlink(target, "//very/./link.txt")?;
+    assert_eq!(fs::read_link("very/link.txt")?, target);
+    assert_eq!(fs::read_to_string("very/link.txt")?, contents);
+    fs::symlink("long/path", "very/dir-link")?;
+    assert_eq!(fs::read_to_string("very/dir-link/test.txt")?, contents);
+    fs::symlink("/not/exist", "dangling")?;
+    assert_eq!(fs::read_link("dangling")?, "/not/exist");
+    assert_err!(fs::metadata("dangling"), NotFound);
+
+    // unlink removes the link itself, not its target
+    assert_eq!(fs::remove_file("very/link.txt"), Ok(()));
+    assert_eq!(fs::remove_file("very/dir-link"), Ok(()));
+    assert_eq!(fs::remove_file("dangling"), Ok(()));
+    assert_err!(fs::read_link("very/link.txt"), NotFound);
+    assert_eq!(fs::read_to_string(target)?, contents);
+
+    // hard links, only ramfs has them
+    println!("test hard links in /tmp:");
+    fs::write("/tmp/a.txt", "hard link\n")?;
+    fs::hard_link("/tmp/a.txt", "tmp//./b.txt")?;
+    assert_eq!(fs::link_count("/tmp/a.txt")?, 2);
+    assert_eq!(fs::link_count("/tmp/b.txt")?, 2);
+    let ino = fs::inode_number("/tmp/a.txt", false)?;
+    assert_eq!(fs::inode_number("/tmp/b.txt", false)?, ino);
+    assert_ne!(fs::inode_number("/tmp", false)?, ino);
+    assert_eq!(fs::read_to_string("/tmp/b.txt")?, "hard link\n");
+    fs::write("/tmp/b.txt", "changed\n")?;
+    assert_eq!(fs::read_to_string("/tmp/a.txt")?, "changed\n");
+
+    // unlink one name, the other keeps the contents
+    assert_eq!(fs::remove_file("/tmp/a.txt"), Ok(()));
+    assert_err!(fs::metadata("/tmp/a.txt"), NotFound);
+    assert_eq!(fs::link_count("/tmp/b.txt")?, 1);
+    assert_eq!(fs::read_to_string("/tmp/b.txt")?, "changed\n");
+
+    // error cases
+    assert_err!(fs::read_link(target), InvalidInput);
+    assert_err!(fs::symlink(target, "short.txt"), AlreadyExists);
+    assert_err!(fs::hard_link("/tmp/b.txt", "/tmp/b.txt"), AlreadyExists);
+    assert_err!(fs::hard_link("/tmp/not-exist.txt", "/tmp/c.txt"), NotFound);
+    assert_err!(fs::hard_link("/very", "/tmp/c.txt"), PermissionDenied);
+    assert_err!(fs::hard_link("/tmp/b.txt", "/b.txt"), Unsupported);
+    assert_err!(fs::hard_link("short.txt", "short-link.txt"), Unsupported);
+    assert_eq!(fs::remove_file("/tmp/b.txt"), Ok(()));
+    assert_eq!(fs::read_dir("tmp").unwrap().count(), 0);
+
+    println!("test_links() OK!");
+    Ok(())
+}
+
+fn test_rename() -> Result<()> {
+    println!("test rename in /tmp:");
+    fs::write("/tmp/old.txt", "rename me\n")?;
+    fs::rename("/tmp/old.txt", "tmp//./new.txt")?;
+    assert_err!(fs::metadata("/tmp/old.txt"), NotFound);
+    assert_eq!(fs::read_to_string("/tmp/new.txt")?, "rename me\n");
+    assert_eq!(fs::link_count("/tmp/new.txt")?, 1);
+
+    // rename a hard-linked file, both names still share the contents
+    fs::hard_link("/tmp/new.txt", "/tmp/link.txt")?;
+    fs::rename("/tmp/new.txt", "/tmp/moved.txt")?;
+    assert_err!(fs::metadata("/tmp/new.txt"), NotFound);
+    assert_eq!(fs::link_count("/tmp/moved.txt")?, 2);
+    assert_eq!(fs::link_count("/tmp/link.txt")?, 2);
+    fs::write("/tmp/moved.txt", "moved\n")?;
+    assert_eq!(fs::read_to_string("/tmp/link.txt")?, "moved\n");
+
+    // rename over an existing name, which drops that link
+    fs::write("/tmp/other.txt", "other\n")?;
+    fs::rename("/tmp/other.txt", "/tmp/link.txt")?;
+    assert_eq!(fs::read_to_string("/tmp/link.txt")?, "other\n");
+    assert_eq!(fs::link_count("/tmp/moved.txt")?, 1);
+    assert_eq!(fs::read_to_string("/tmp/moved.txt")?, "moved\n");
+
+    // error cases
+    assert_err!(fs::rename("/tmp/not-exist.txt", "/tmp/new.txt"), NotFound);
+
+    assert_eq!(fs::remove_file("/tmp/link.txt"), Ok(()));
+    assert_eq!(fs::remove_file("/tmp/moved.txt"), Ok(()));
+    assert_eq!(fs::read_dir("tmp").unwrap().count(), 0);
+
+    println!("test_rename() OK!");
+    Ok(())
+}
+
+fn test_mount() -> Result<()> {
+    let target = "/mnt";
+    println!("test mount and umount at {:?}:", target);
+    fs::create_dir(target)?;
+    fs::mount("tmpfs", target, "tmpfs")?;
+    assert!(fs::mount_table().contains("tmpfs /mnt tmpfs rw 0 0\n"));
+    assert!(fs::read_to_string("/proc/mounts")?.contains("tmpfs /mnt tmpfs rw 0 0\n"));
+    assert_eq!(fs::mount_point("/mnt/").map(|mp| mp.fs_type), Some("tmpfs"));
+    assert_eq!(
+        fs::mount_point("/").map(|mp| mp.source),
+        Some("/dev/vda".into())
+    );
+    assert!(fs::mount_point("/mnt/test.txt").is_none());
+    fs::write("/mnt/test.txt", "mounted\n")?;
+    assert_eq!(fs::read_to_string(".//mnt/./test.txt")?, "mounted\n");
+    assert_err!(fs::mount("tmpfs", "mnt/", "tmpfs"), ResourceBusy);
+
+    // busy while another filesystem is mounted under it
+    fs::create_dir("/mnt/sub")?;
+    fs::mount("none", "/mnt/sub", "tmpfs")?;
+    assert!(fs::mount_table().contains("none /mnt/sub tmpfs rw 0 0\n"));
+    assert_err!(fs::umount(target, |_| false), ResourceBusy);
+    assert_err!(fs::remove_dir("/mnt/sub"), PermissionDenied);
+    // busy while the caller still uses it
+    assert_err!(
+        fs::umount("/mnt/sub", |path| path == "/mnt/sub"),
+        ResourceBusy
+    );
+    assert_eq!(fs::umount("/mnt//sub/", |_| false), Ok(()));
+    assert_eq!(fs::umount(target, |_| false), Ok(()));
+    assert!(!fs::mount_table().contains(" /mnt"));
+    assert!(!fs::read_to_string("/proc/mounts")?.contains(" /mnt"));
+
+    // the directory under the mount point is back
+    assert_err!(fs::metadata("/mnt/test.txt"), NotFound);
+    assert_eq!(fs::read_dir(target)?.count(), 0);
+
+    // error cases
+    assert_err!(fs::umount(target, |_| false), InvalidInput);
+    assert_err!(fs::mount("tmpfs", "/not-exist", "tmpfs"), NotFound);
+    assert_err!(fs::mount("tmpfs", "/short.txt", "tmpfs"), NotADirectory);
+    assert_err!(fs::mount("tmpfs", target, "nofs"), Unsupported);
+    assert_err!(fs::mount("tmpfs", "/", "tmpfs"), InvalidInput);
+    assert_eq!(fs::remove_dir(target), Ok(()));
+
+    println!("test_mount() OK!");
+    Ok(())
+}
+
 pub fn test_all() {
     test_read_write_file().expect("test_read_write_file() failed");
     test_read_dir().expect("test_read_dir() failed");
@@ -259,4 +398,7 @@ pub fn test_all() {
     test_create_file_dir().expect("test_create_file_dir() failed");
     test_remove_file_dir().expect("test_remove_file_dir() failed");
     test_devfs_ramfs().expect("test_devfs_ramfs() failed");
+    test_links().expect("test_links() failed");
+    test_rename().expect("test_rename() failed");
+    test_mount().expect("test_mount() failed");
 }
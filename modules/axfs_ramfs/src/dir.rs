@@ -0,0 +1,298 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::{string::String, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use axfs_vfs::{VfsDirEntry, VfsNodeAttr, VfsNodeOps, VfsNodeRef, VfsNodeType};
+use axfs_vfs::{VfsError, VfsResult};
+use spin::RwLock;
+
+use crate::file::FileNode;
+use crate::symlink::SymlinkNode;
+
+/// The directory node in the RAM filesystem.
+///
+/// It implements [`axfs_vfs::VfsNodeOps`].
+pub struct DirNode {
+    this: Weak<DirNode>,
+    parent: RwLock<Weak<dyn VfsNodeOps>>,
+    children: RwLock<BTreeMap<String, VfsNodeRef>>,
+}
+
+impl DirNode {
+    pub(super) fn new(parent: Option<Weak<dyn VfsNodeOps>>) -> Arc<Self> {
+        Arc::new_cyclic(|this| Self {
+            this: this.clone(),
+            parent: RwLock::new(parent.unwrap_or_else(|| Weak::<Self>::new())),
+            children: RwLock::new(BTreeMap::new()),
+        })
+    }
+
+    pub(super) fn set_parent(&self, parent: Option<&VfsNodeRef>) {
+        *self.parent.write() = parent.map_or(Weak::<Self>::new() as _, Arc::downgrade);
+    }
+
+    /// Returns a string list of all entries in this directory.
+    pub fn get_entries(&self) -> Vec<String> {
+        self.children.read().keys().cloned().collect()
+    }
+
+    /// Checks whether a node with the given name exists in this directory.
+    pub fn exist(&self, name: &str) -> bool {
+        self.children.read().contains_key(name)
+    }
+
+    /// Creates a new node with the given name and type in this directory.
+    pub fn create_node(&self, name: &str, ty: VfsNodeType) -> VfsResult {
+        if self.exist(name) {
+            log::error!("AlreadyExists {}", name);
+            return Err(VfsError::AlreadyExists);
+        }
+        let node: VfsNodeRef = match ty {
+            VfsNodeType::File => Arc::new(FileNode::new()),
+            VfsNodeType::Dir => Self::new(Some(self.this.clone())),
+            _ => return Err(VfsError::Unsupported),
+        };
+        self.children.write().insert(name.into(), node);
+        Ok(())
+    }
+
+    /// Removes a node by the given name in this directory.
+    pub fn remove_node(&self, name: &str) -> VfsResult {
+        let mut children = self.children.write();
+        let node = children.get(name).ok_or(VfsError::NotFound)?;
+        if let Some(dir) = node.as_any().downcast_ref::<DirNode>()
+            && !dir.children.read().is_empty()
+        {
+            return Err(VfsError::DirectoryNotEmpty);
+        }
+        if let Some(node) = children.remove(name) {
+            unlinked(&node);
+        }
+        Ok(())
+    }
+
+    /// Creates the new name `dst_path` for the file `src_path`, both relative
+    /// to this directory. The two names share the same node.
+    pub fn link(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        let node = self.this().lookup(src_path)?;
+        let Some(nlink) = nlink_of(&node) else {
+            return Err(VfsError::PermissionDenied); // hard link to a directory
+        };
+        let (dir, name) = self.lookup_parent(dst_path)?;
+        let mut children = dir.children.write();
+        if children.contains_key(name) {
+            return Err(VfsError::AlreadyExists);
+        }
+        nlink.fetch_add(1, Ordering::AcqRel);
+        children.insert(name.into(), node.clone());
+        Ok(())
+    }
+
+    /// Creates the symbolic link `path`, relative to this directory, that
+    /// points to `target`.
+    pub fn symlink(&self, target: &str, path: &str) -> VfsResult {
+        let (dir, name) = self.lookup_parent(path)?;
+        let mut children = dir.children.write();
+        if children.contains_key(name) {
+            return Err(VfsError::AlreadyExists);
+        }
+        children.insert(name.into(), Arc::new(SymlinkNode::new(target)));
+        Ok(())
+    }
+
+    fn this(&self) -> Arc<Self> {
+        // `self` is only reachable through an `Arc`
+        self.this.upgrade().unwrap()
+    }
+
+    /// Looks up the directory that holds `path` (relative to this directory)
+    /// and returns it with the last component of `path`, which must be a
+    /// valid new name.
+    fn lookup_parent<'a>(&self, path: &'a str) -> VfsResult<(Arc<DirNode>, &'a str)> {
+        let path = path.trim_end_matches('/');
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        if matches!(name, "" | "." | "..") {
+            return Err(VfsError::InvalidInput);
+        }
+        let dir = self.this().lookup(dir)?;
+        let dir = dir
+            .as_any()
+            .downcast_ref::<DirNode>()
+            .ok_or(VfsError::NotADirectory)?;
+        Ok((dir.this(), name))
+    }
+}
+
+impl VfsNodeOps for DirNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new_dir(4096, 0))
+    }
+
+    fn parent(&self) -> Option<VfsNodeRef> {
+        self.parent.read().upgrade()
+    }
+
+    fn lookup(self: Arc<Self>, path: &str) -> VfsResult<VfsNodeRef> {
+        let (name, rest) = split_path(path);
+        let node = match name {
+            "" | "." => Ok(self.clone() as VfsNodeRef),
+            ".." => self.parent().ok_or(VfsError::NotFound),
+            _ => self
+                .children
+                .read()
+                .get(name)
+                .cloned()
+                .ok_or(VfsError::NotFound),
+        }?;
+
+        if let Some(rest) = rest {
+            node.lookup(rest)
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn read_dir(&self, start_idx: usize, dirents: &mut [VfsDirEntry]) -> VfsResult<usize> {
+        let children = self.children.read();
+        let mut children = children.iter().skip(start_idx.max(2) - 2);
+        for (i, ent) in dirents.iter_mut().enumerate() {
+            match i + start_idx {
+                0 => *ent = VfsDirEntry::new(".", VfsNodeType::Dir),
+                1 => *ent = VfsDirEntry::new("..", VfsNodeType::Dir),
+                _ => {
+                    if let Some((name, node)) = children.next() {
+                        *ent = VfsDirEntry::new(name, node.get_attr().unwrap().file_type());
+                    } else {
+                        return Ok(i);
+                    }
+                }
+            }
+        }
+        Ok(dirents.len())
+    }
+
+    fn create(&self, path: &str, ty: VfsNodeType) -> VfsResult {
+        log::debug!("create {:?} at ramfs: {}", ty, path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.create(rest, ty),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.create(rest, ty),
+                _ => {
+                    let subdir = self
+                        .children
+                        .read()
+                        .get(name)
+                        .ok_or(VfsError::NotFound)?
+                        .clone();
+                    subdir.create(rest, ty)
+                }
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Ok(()) // already exists
+        } else {
+            self.create_node(name, ty)
+        }
+    }
+
+    fn remove(&self, path: &str) -> VfsResult {
+        log::debug!("remove at ramfs: {}", path);
+        let (name, rest) = split_path(path);
+        if let Some(rest) = rest {
+            match name {
+                "" | "." => self.remove(rest),
+                ".." => self.parent().ok_or(VfsError::NotFound)?.remove(rest),
+                _ => {
+                    let subdir = self
+                        .children
+                        .read()
+                        .get(name)
+                        .ok_or(VfsError::NotFound)?
+                        .clone();
+                    subdir.remove(rest)
+                }
+            }
+        } else if name.is_empty() || name == "." || name == ".." {
+            Err(VfsError::InvalidInput) // remove '.' or '..
+        } else {
+            self.remove_node(name)
+        }
+    }
+
+    /// Moves the entry `src_path` to `dst_path`, both relative to this
+    /// directory. An existing file at `dst_path` is replaced, an existing
+    /// directory only if it is empty.
+    fn rename(&self, src_path: &str, dst_path: &str) -> VfsResult {
+        log::debug!("rename at ramfs: {} -> {}", src_path, dst_path);
+        let (src_dir, src_name) = self.lookup_parent(src_path)?;
+        let (dst_dir, dst_name) = self.lookup_parent(dst_path)?;
+        let node = src_dir
+            .children
+            .read()
+            .get(src_name)
+            .cloned()
+            .ok_or(VfsError::NotFound)?;
+        let moved_dir = node.as_any().downcast_ref::<DirNode>();
+        if moved_dir.is_some() && is_descendant(src_path, dst_path) {
+            return Err(VfsError::InvalidInput); // into itself
+        }
+        if let Some(old) = dst_dir.children.read().get(dst_name) {
+            if Arc::ptr_eq(old, &node) {
+                return Ok(()); // names of the same file
+            }
+            match (moved_dir, old.as_any().downcast_ref::<DirNode>()) {
+                (Some(_), None) => return Err(VfsError::NotADirectory),
+                (None, Some(_)) => return Err(VfsError::IsADirectory),
+                (Some(_), Some(old)) if !old.children.read().is_empty() => {
+                    return Err(VfsError::DirectoryNotEmpty);
+                }
+                _ => {}
+            }
+        }
+        src_dir.children.write().remove(src_name);
+        let old = dst_dir
+            .children
+            .write()
+            .insert(dst_name.into(), node.clone());
+        if let Some(old) = old {
+            unlinked(&old);
+        }
+        if let Some(dir) = moved_dir {
+            dir.set_parent(Some(&(dst_dir as VfsNodeRef)));
+        }
+        Ok(())
+    }
+
+    axfs_vfs::impl_vfs_dir_default! {}
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    let trimmed_path = path.trim_start_matches('/');
+    trimmed_path.find('/').map_or((trimmed_path, None), |n| {
+        (&trimmed_path[..n], Some(&trimmed_path[n + 1..]))
+    })
+}
+
+/// Returns the link count of `node` if it can have several names, i.e. it is
+/// not a directory.
+fn nlink_of(node: &VfsNodeRef) -> Option<&AtomicU64> {
+    let any = node.as_any();
+    any.downcast_ref::<FileNode>()
+        .map(|file| &file.nlink)
+        .or_else(|| any.downcast_ref::<SymlinkNode>().map(|link| &link.nlink))
+}
+
+/// Drops one name of `node`, which has been taken out of its directory.
+fn unlinked(node: &VfsNodeRef) {
+    if let Some(nlink) = nlink_of(node) {
+        nlink.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Whether `path` is strictly inside the directory `dir`.
+fn is_descendant(dir: &str, path: &str) -> bool {
+    path.trim_matches('/')
+        .strip_prefix(dir.trim_matches('/'))
+        .is_some_and(|rest| rest.starts_with('/'))
+}
@@ -0,0 +1,66 @@
+//! RAM filesystem used by [ArceOS](https://github.com/arceos-org/arceos).
+//!
+//! The implementation is based on [`axfs_vfs`]. Besides the [`VfsOps`]
+//! interface, a directory can hold several names of the same file (hard
+//! links, see [`DirNode::link`]) and symbolic links ([`SymlinkNode`]).
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+mod dir;
+mod file;
+mod symlink;
+
+#[cfg(test)]
+mod tests;
+
+pub use self::dir::DirNode;
+pub use self::file::FileNode;
+pub use self::symlink::SymlinkNode;
+
+use alloc::sync::Arc;
+use axfs_vfs::{VfsNodeRef, VfsOps, VfsResult};
+use spin::once::Once;
+
+/// A RAM filesystem that implements [`axfs_vfs::VfsOps`].
+pub struct RamFileSystem {
+    parent: Once<VfsNodeRef>,
+    root: Arc<DirNode>,
+}
+
+impl RamFileSystem {
+    /// Create a new instance.
+    pub fn new() -> Self {
+        Self {
+            parent: Once::new(),
+            root: DirNode::new(None),
+        }
+    }
+
+    /// Returns the root directory node in [`Arc<DirNode>`](DirNode).
+    pub fn root_dir_node(&self) -> Arc<DirNode> {
+        self.root.clone()
+    }
+}
+
+impl VfsOps for RamFileSystem {
+    fn mount(&self, _path: &str, mount_point: VfsNodeRef) -> VfsResult {
+        if let Some(parent) = mount_point.parent() {
+            self.root.set_parent(Some(self.parent.call_once(|| parent)));
+        } else {
+            self.root.set_parent(None);
+        }
+        Ok(())
+    }
+
+    fn root_dir(&self) -> VfsNodeRef {
+        self.root.clone()
+    }
+}
+
+impl Default for RamFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
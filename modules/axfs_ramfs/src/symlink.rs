@@ -0,0 +1,47 @@
+use alloc::string::String;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use axfs_vfs::{
+    VfsNodeAttr, VfsNodeOps, VfsNodePerm, VfsNodeType, VfsResult, impl_vfs_non_dir_default,
+};
+
+/// The symbolic link node in the RAM filesystem.
+///
+/// It only stores the target path, which is resolved by the caller.
+pub struct SymlinkNode {
+    target: String,
+    /// Number of directory entries that refer to this node.
+    pub(crate) nlink: AtomicU64,
+}
+
+impl SymlinkNode {
+    pub(super) fn new(target: &str) -> Self {
+        Self {
+            target: target.into(),
+            nlink: AtomicU64::new(1),
+        }
+    }
+
+    /// Returns the path this link points to.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the number of names of this link.
+    pub fn link_count(&self) -> u64 {
+        self.nlink.load(Ordering::Acquire)
+    }
+}
+
+impl VfsNodeOps for SymlinkNode {
+    fn get_attr(&self) -> VfsResult<VfsNodeAttr> {
+        Ok(VfsNodeAttr::new(
+            VfsNodePerm::from_bits_truncate(0o777),
+            VfsNodeType::SymLink,
+            self.target.len() as _,
+            0,
+        ))
+    }
+
+    impl_vfs_non_dir_default! {}
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use axfs_vfs::{VfsError, VfsNodeOps, VfsNodeType, VfsOps, VfsResult};
+
+use crate::{FileNode, RamFileSystem, SymlinkNode};
+
+fn read_all(fs: &RamFileSystem, path: &str) -> VfsResult<Vec<u8>> {
+    let node = fs.root_dir().lookup(path)?;
+    let mut buf = vec![0; node.get_attr()?.size() as usize];
+    node.read_at(0, &mut buf)?;
+    Ok(buf)
+}
+
+fn link_count(fs: &RamFileSystem, path: &str) -> u64 {
+    let node = fs.root_dir().lookup(path).unwrap();
+    node.as_any()
+        .downcast_ref::<FileNode>()
+        .unwrap()
+        .link_count()
+}
+
+#[test]
+fn test_ramfs() {
+    let ramfs = RamFileSystem::new();
+    let root = ramfs.root_dir();
+    root.create("f1", VfsNodeType::File).unwrap();
+    root.create("foo", VfsNodeType::Dir).unwrap();
+    root.create("foo/f2", VfsNodeType::File).unwrap();
+    assert_eq!(
+        root.create("foo/f2", VfsNodeType::File),
+        Err(VfsError::AlreadyExists)
+    );
+
+    let f1 = root.clone().lookup("f1").unwrap();
+    assert_eq!(f1.write_at(0, b"hello").unwrap(), 5);
+    assert_eq!(read_all(&ramfs, "/f1").unwrap(), b"hello");
+    assert_eq!(
+        root.clone().lookup("f1/x").err(),
+        Some(VfsError::NotADirectory)
+    );
+    assert_eq!(root.remove("foo"), Err(VfsError::DirectoryNotEmpty));
+    assert!(root.remove("foo/f2").is_ok());
+    assert!(root.remove("foo").is_ok());
+    assert_eq!(ramfs.root_dir_node().get_entries(), vec!["f1"]);
+}
+
+#[test]
+fn test_links() {
+    let ramfs = RamFileSystem::new();
+    let root = ramfs.root_dir_node();
+    root.create("dir", VfsNodeType::Dir).unwrap();
+    root.create("a", VfsNodeType::File).unwrap();
+    root.clone()
+        .lookup("a")
+        .unwrap()
+        .write_at(0, b"data")
+        .unwrap();
+
+    // both names refer to the same node
+    root.link("a", "dir/b").unwrap();
+    assert!(Arc::ptr_eq(
+        &root.clone().lookup("a").unwrap(),
+        &root.clone().lookup("dir/b").unwrap()
+    ));
+    assert_eq!(link_count(&ramfs, "a"), 2);
+    assert_eq!(root.link("a", "dir/b"), Err(VfsError::AlreadyExists));
+    assert_eq!(root.link("dir", "d2"), Err(VfsError::PermissionDenied));
+
+    // the data outlives the first name
+    root.remove("a").unwrap();
+    assert_eq!(read_all(&ramfs, "dir/b").unwrap(), b"data");
+    assert_eq!(link_count(&ramfs, "dir/b"), 1);
+
+    root.symlink("dir/b", "s").unwrap();
+    let link = root.clone().lookup("s").unwrap();
+    assert_eq!(link.get_attr().unwrap().file_type(), VfsNodeType::SymLink);
+    let link = link.as_any().downcast_ref::<SymlinkNode>().unwrap();
+    assert_eq!(link.target(), "dir/b");
+    assert_eq!(root.symlink("x", "s"), Err(VfsError::AlreadyExists));
+}
+
+#[test]
+fn test_rename() {
+    let ramfs = RamFileSystem::new();
+    let root = ramfs.root_dir_node();
+    root.create("d1", VfsNodeType::Dir).unwrap();
+    root.create("d1/f", VfsNodeType::File).unwrap();
+    root.create("d2", VfsNodeType::Dir).unwrap();
+    root.create("g", VfsNodeType::File).unwrap();
+
+    // a directory moves with its entries and gets the new parent
+    root.rename("d1", "d2/d3").unwrap();
+    assert!(root.clone().lookup("d2/d3/f").is_ok());
+    assert!(root.clone().lookup("d2/d3/../../g").is_ok());
+    assert_eq!(root.clone().lookup("d1").err(), Some(VfsError::NotFound));
+    assert_eq!(root.rename("d2", "d2/d3/d4"), Err(VfsError::InvalidInput));
+
+    // an existing file is replaced, a directory is not
+    root.rename("d2/d3/f", "g").unwrap();
+    assert_eq!(root.get_entries(), vec!["d2", "g"]);
+    assert_eq!(root.rename("g", "d2"), Err(VfsError::IsADirectory));
+    assert_eq!(root.rename("d2", "g"), Err(VfsError::NotADirectory));
+}
@@ -1,4 +1,6 @@
 use core::arch::naked_asm;
+#[cfg(feature = "uspace")]
+use memory_addr::PhysAddr;
 use memory_addr::VirtAddr;
 
 /// Saved registers when a trap (exception) occurs.
@@ -65,6 +67,10 @@ pub struct TaskContext {
     pub lr: u64, // r30
     #[cfg(feature = "fp_simd")]
     pub fp_state: FpState,
+    /// Page table root (`TTBR1_EL1`) of the task, or 0 to keep the current one
+    /// on switch.
+    #[cfg(feature = "uspace")]
+    pub ttbr1: PhysAddr,
 }
 
 impl TaskContext {
@@ -81,6 +87,15 @@ impl TaskContext {
         self.tpidr_el0 = tls_area.as_usize() as u64;
     }
 
+    /// Changes the page table root that is switched to with this context.
+    ///
+    /// Tasks whose root is 0 (the default) run in whatever address space is
+    /// active, which must then contain the kernel mappings.
+    #[cfg(feature = "uspace")]
+    pub fn set_page_table_root(&mut self, ttbr1: PhysAddr) {
+        self.ttbr1 = ttbr1;
+    }
+
     /// Switches to another task.
     ///
     /// It first saves the current task's context from CPU to this place, and then
@@ -88,6 +103,10 @@ impl TaskContext {
     pub fn switch_to(&mut self, next_ctx: &Self) {
         #[cfg(feature = "fp_simd")]
         self.fp_state.switch_to(&next_ctx.fp_state);
+        #[cfg(feature = "uspace")]
+        if next_ctx.ttbr1.as_usize() != 0 {
+            unsafe { super::write_page_table_root(next_ctx.ttbr1) };
+        }
         unsafe { context_switch(self, next_ctx) }
     }
 }
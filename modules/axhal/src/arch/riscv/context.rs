@@ -1,4 +1,6 @@
 use core::arch::naked_asm;
+#[cfg(feature = "uspace")]
+use memory_addr::PhysAddr;
 use memory_addr::VirtAddr;
 
 /// General registers of RISC-V.
@@ -51,6 +53,140 @@ pub struct TrapFrame {
     pub sstatus: usize,
 }
 
+impl TrapFrame {
+    /// Gets the system call number.
+    pub const fn sysno(&self) -> usize {
+        self.regs.a7
+    }
+
+    /// Gets the system call arguments.
+    pub const fn args(&self) -> [usize; 6] {
+        let r = &self.regs;
+        [r.a0, r.a1, r.a2, r.a3, r.a4, r.a5]
+    }
+
+    /// Sets the return value of a system call.
+    pub fn set_retval(&mut self, ret: usize) {
+        self.regs.a0 = ret;
+    }
+}
+
+/// Context to enter user space.
+#[cfg(feature = "uspace")]
+#[derive(Debug, Clone)]
+pub struct UspaceContext(TrapFrame);
+
+#[cfg(feature = "uspace")]
+impl UspaceContext {
+    /// Creates a new context with the given entry point, user stack pointer,
+    /// and the argument.
+    pub fn new(entry: usize, ustack_top: VirtAddr, arg0: usize) -> Self {
+        const SPIE: usize = 1 << 5;
+        const FS_INITIAL: usize = 1 << 13;
+        const SUM: usize = 1 << 18;
+        Self(TrapFrame {
+            regs: GeneralRegisters {
+                a0: arg0,
+                sp: ustack_top.as_usize(),
+                ..Default::default()
+            },
+            sepc: entry,
+            sstatus: SPIE | FS_INITIAL | SUM,
+        })
+    }
+
+    /// Creates a new context from the given [`TrapFrame`], e.g. to return to
+    /// user space in the child of `fork`.
+    pub fn from(tf: &TrapFrame) -> Self {
+        Self(tf.clone())
+    }
+
+    /// Enters user space.
+    ///
+    /// It restores the user registers and jumps to the user entry point
+    /// (saved in `sepc`). Traps from user space are then handled on the kernel
+    /// stack whose top is `kstack_top`.
+    ///
+    /// # Safety
+    ///
+    /// This function is unsafe because it changes processor mode and the stack.
+    pub unsafe fn enter_uspace(&self, kstack_top: VirtAddr) -> ! {
+        use riscv::register::{sepc, sscratch};
+
+        super::disable_irqs();
+        // the trap entry takes the kernel stack from `sscratch`
+        sscratch::write(kstack_top.as_usize());
+        sepc::write(self.0.sepc);
+        // where the trap entry saves the frame, and loads the kernel `gp` and `tp` from
+        let kernel_trap_addr = kstack_top.as_usize() - core::mem::size_of::<TrapFrame>();
+        unsafe {
+            core::arch::asm!(
+                include_asm_marcos!(),
+                "
+                mv      sp, {tf}
+
+                STR     gp, {kernel_trap_addr}, 2
+                LDR     gp, sp, 2
+                STR     tp, {kernel_trap_addr}, 3
+                LDR     tp, sp, 3
+
+                LDR     t0, sp, 32
+                csrw    sstatus, t0
+
+                LDR     ra, sp, 0
+                LDR     t0, sp, 4
+                LDR     t1, sp, 5
+                LDR     t2, sp, 6
+                LDR     s0, sp, 7
+                LDR     s1, sp, 8
+                LDR     a0, sp, 9
+                LDR     a1, sp, 10
+                LDR     a2, sp, 11
+                LDR     a3, sp, 12
+                LDR     a4, sp, 13
+                LDR     a5, sp, 14
+                LDR     a6, sp, 15
+                LDR     a7, sp, 16
+                LDR     s2, sp, 17
+                LDR     s3, sp, 18
+                LDR     s4, sp, 19
+                LDR     s5, sp, 20
+                LDR     s6, sp, 21
+                LDR     s7, sp, 22
+                LDR     s8, sp, 23
+                LDR     s9, sp, 24
+                LDR     s10, sp, 25
+                LDR     s11, sp, 26
+                LDR     t3, sp, 27
+                LDR     t4, sp, 28
+                LDR     t5, sp, 29
+                LDR     t6, sp, 30
+                LDR     sp, sp, 1
+                sret",
+                tf = in(reg) &self.0,
+                kernel_trap_addr = in(reg) kernel_trap_addr,
+                options(noreturn),
+            )
+        }
+    }
+}
+
+#[cfg(feature = "uspace")]
+impl core::ops::Deref for UspaceContext {
+    type Target = TrapFrame;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "uspace")]
+impl core::ops::DerefMut for UspaceContext {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 /// Saved hardware states of a task.
 ///
 /// The context usually includes:
@@ -84,6 +220,9 @@ pub struct TaskContext {
     pub s11: usize,
 
     pub tp: usize,
+    /// Page table root of the task, or 0 to keep the current one on switch.
+    #[cfg(feature = "uspace")]
+    pub satp: PhysAddr,
     // TODO: FP states
 }
 
@@ -101,6 +240,15 @@ impl TaskContext {
         self.tp = tls_area.as_usize();
     }
 
+    /// Changes the page table root that is switched to with this context.
+    ///
+    /// Tasks whose root is 0 (the default) run in whatever address space is
+    /// active, which must then contain the kernel mappings.
+    #[cfg(feature = "uspace")]
+    pub fn set_page_table_root(&mut self, satp: PhysAddr) {
+        self.satp = satp;
+    }
+
     /// Switches to another task.
     ///
     /// It first saves the current task's context from CPU to this place, and then
@@ -111,6 +259,10 @@ impl TaskContext {
             self.tp = super::read_thread_pointer();
             unsafe { super::write_thread_pointer(next_ctx.tp) };
         }
+        #[cfg(feature = "uspace")]
+        if next_ctx.satp.as_usize() != 0 {
+            unsafe { super::write_page_table_root(next_ctx.satp) };
+        }
         unsafe {
             // TODO: switch FP states
             context_switch(self, next_ctx)
@@ -32,6 +32,18 @@ fn handle_page_fault(tf: &TrapFrame, mut access_flags: MappingFlags, is_user: bo
     }
 }
 
+#[cfg(feature = "uspace")]
+fn handle_user_ecall(tf: &mut TrapFrame) {
+    let Some(handler) = crate::trap::SYSCALL.iter().next() else {
+        panic!("No registered handler for system calls:\n{:#x?}", tf);
+    };
+    // return to the instruction after `ecall`, unless the handler changes it
+    tf.sepc += 4;
+    super::enable_irqs();
+    handler(tf);
+    super::disable_irqs();
+}
+
 #[unsafe(no_mangle)]
 fn riscv_trap_handler(tf: &mut TrapFrame, from_user: bool) {
     let scause = scause::read();
@@ -42,6 +54,8 @@ fn riscv_trap_handler(tf: &mut TrapFrame, from_user: bool) {
             handle_page_fault(tf, MappingFlags::EXECUTE, from_user)
         }
         Trap::Exception(E::Breakpoint) => handle_breakpoint(&mut tf.sepc),
+        #[cfg(feature = "uspace")]
+        Trap::Exception(E::UserEnvCall) => handle_user_ecall(tf),
         Trap::Interrupt(_) => {
             handle_trap!(IRQ, scause.bits());
         }
@@ -1,4 +1,6 @@
 use core::{arch::naked_asm, fmt};
+#[cfg(feature = "uspace")]
+use memory_addr::PhysAddr;
 use memory_addr::VirtAddr;
 
 /// Saved registers when a trap (interrupt or exception) occurs.
@@ -141,6 +143,9 @@ pub struct TaskContext {
     /// Extended states, i.e., FP/SIMD states.
     #[cfg(feature = "fp_simd")]
     pub ext_state: ExtendedState,
+    /// Page table root of the task, or 0 to keep the current one on switch.
+    #[cfg(feature = "uspace")]
+    pub cr3: PhysAddr,
 }
 
 impl TaskContext {
@@ -152,6 +157,8 @@ impl TaskContext {
             fs_base: 0,
             #[cfg(feature = "fp_simd")]
             ext_state: ExtendedState::default(),
+            #[cfg(feature = "uspace")]
+            cr3: pa!(0),
         }
     }
 
@@ -174,6 +181,15 @@ impl TaskContext {
         self.fs_base = tls_area.as_usize();
     }
 
+    /// Changes the page table root that is switched to with this context.
+    ///
+    /// Tasks whose root is 0 (the default) run in whatever address space is
+    /// active, which must then contain the kernel mappings.
+    #[cfg(feature = "uspace")]
+    pub fn set_page_table_root(&mut self, cr3: PhysAddr) {
+        self.cr3 = cr3;
+    }
+
     /// Switches to another task.
     ///
     /// It first saves the current task's context from CPU to this place, and then
@@ -189,6 +205,10 @@ impl TaskContext {
             self.fs_base = super::read_thread_pointer();
             unsafe { super::write_thread_pointer(next_ctx.fs_base) };
         }
+        #[cfg(feature = "uspace")]
+        if next_ctx.cr3.as_usize() != 0 {
+            unsafe { super::write_page_table_root(next_ctx.cr3) };
+        }
         unsafe { context_switch(&mut self.rsp, &next_ctx.rsp) }
     }
 }
@@ -22,6 +22,11 @@ unsafe extern "C" fn rust_entry(cpu_id: usize, dtb: usize) {
     crate::mem::clear_bss();
     crate::cpu::init_primary(cpu_id);
     crate::arch::set_trap_vector_base(trap_vector_base as usize);
+    // the kernel accesses user memory directly, e.g. system call arguments
+    #[cfg(feature = "uspace")]
+    unsafe {
+        riscv::register::sstatus::set_sum()
+    };
     self::time::init_early();
     rust_main(cpu_id, dtb);
 }
@@ -29,6 +34,10 @@ unsafe extern "C" fn rust_entry(cpu_id: usize, dtb: usize) {
 #[cfg(feature = "smp")]
 unsafe extern "C" fn rust_entry_secondary(cpu_id: usize) {
     crate::arch::set_trap_vector_base(trap_vector_base as usize);
+    #[cfg(feature = "uspace")]
+    unsafe {
+        riscv::register::sstatus::set_sum()
+    };
     crate::cpu::init_secondary(cpu_id);
     rust_main_secondary(cpu_id);
 }
@@ -4,6 +4,9 @@ use linkme::distributed_slice as def_trap_handler;
 use memory_addr::VirtAddr;
 use page_table_entry::MappingFlags;
 
+#[cfg(feature = "uspace")]
+use crate::arch::TrapFrame;
+
 pub use linkme::distributed_slice as register_trap_handler;
 
 /// A slice of IRQ handler functions.
@@ -14,6 +17,15 @@ pub static IRQ: [fn(usize) -> bool];
 #[def_trap_handler]
 pub static PAGE_FAULT: [fn(VirtAddr, MappingFlags, bool) -> bool];
 
+/// A slice of system call handler functions.
+///
+/// They are called for system call instructions executed in user mode, with
+/// interrupts enabled. The handler reads the system call number and arguments
+/// from the trap frame and writes the return value back.
+#[cfg(feature = "uspace")]
+#[def_trap_handler]
+pub static SYSCALL: [fn(&mut TrapFrame)];
+
 #[allow(unused_macros)]
 macro_rules! handle_trap {
     ($trap:ident, $($args:tt)*) => {{
@@ -1,20 +1,40 @@
+use alloc::{collections::BTreeMap, vec::Vec};
 use core::fmt;
 
+use axalloc::global_allocator;
 use axerrno::{AxError, AxResult, ax_err};
 use axhal::{
-    mem::phys_to_virt,
-    paging::{MappingFlags, PageTable},
+    mem::{phys_to_virt, virt_to_phys},
+    paging::{MappingFlags, PageSize, PageTable},
 };
+use kspin::SpinNoIrq;
 use memory_addr::{
-    MemoryAddr, PAGE_SIZE_4K, PageIter4K, PhysAddr, VirtAddr, VirtAddrRange, is_aligned_4k, pa,
+    MemoryAddr, PAGE_SIZE_4K, PageIter4K, PhysAddr, VirtAddr, VirtAddrRange, is_aligned_4k, pa, va,
 };
 
 use crate::paging_err_to_ax_err;
 
+/// Frames of allocation mappings shared by more than one address space after
+/// [`AddrSpace::share_cow`], with their number of owners. Frames not listed
+/// here have a single owner.
+static SHARED_FRAMES: SpinNoIrq<BTreeMap<PhysAddr, usize>> = SpinNoIrq::new(BTreeMap::new());
+
+/// A region added by [`AddrSpace::map_alloc`], whose pages are backed by
+/// frames from the global allocator.
+struct AllocArea {
+    end: VirtAddr,
+    flags: MappingFlags,
+}
+
 /// The virtual memory address space.
 pub struct AddrSpace {
     va_range: VirtAddrRange,
+    /// An additional range below `va_range` that only this address space
+    /// maps, see [`AddrSpace::set_user_range`]. Empty by default.
+    user_range: VirtAddrRange,
     pt: PageTable,
+    /// Allocation mappings, keyed by their start address. They never overlap.
+    alloc_areas: BTreeMap<VirtAddr, AllocArea>,
 }
 
 impl AddrSpace {
@@ -45,18 +65,63 @@ impl AddrSpace {
 
     /// Checks if the address space contains the given address range.
     pub fn contains_range(&self, start: VirtAddr, size: usize) -> bool {
-        self.va_range
-            .contains_range(VirtAddrRange::from_start_size(start, size))
+        let range = VirtAddrRange::from_start_size(start, size);
+        self.va_range.contains_range(range) || self.user_range.contains_range(range)
+    }
+
+    /// Lets the address space also cover `[start, start + size)`, which must
+    /// lie below the address space base.
+    ///
+    /// The kernel address space only covers the upper half, while programs
+    /// running in user mode are usually linked at low addresses. Nothing in
+    /// this range is shared with other address spaces.
+    pub fn set_user_range(&mut self, start: VirtAddr, size: usize) -> AxResult {
+        let range = VirtAddrRange::from_start_size(start, size);
+        if range.end > self.base() {
+            return ax_err!(InvalidInput, "address out of range");
+        }
+        self.user_range = range;
+        Ok(())
     }
 
     /// Creates a new empty address space.
     pub(crate) fn new_empty(base: VirtAddr, size: usize) -> AxResult<Self> {
         Ok(Self {
             va_range: VirtAddrRange::from_start_size(base, size),
+            user_range: VirtAddrRange::from_start_size(va!(0), 0),
             pt: PageTable::try_new().map_err(|_| AxError::NoMemory)?,
+            alloc_areas: BTreeMap::new(),
         })
     }
 
+    /// Copies the page table entries of `other` that map outside `private`.
+    ///
+    /// Only the top-level entries are copied, so both address spaces share the
+    /// lower-level page tables and later mappings under those entries become
+    /// visible in both. `private` should be aligned to the range covered by a
+    /// top-level entry.
+    pub fn copy_mappings_from(&mut self, other: &AddrSpace, private: VirtAddrRange) -> AxResult {
+        if self.va_range != other.va_range || !self.va_range.contains_range(private) {
+            return ax_err!(InvalidInput, "address out of range");
+        }
+        let (start, end) = (self.base(), self.end());
+        if private.start > start {
+            self.pt.copy_from(
+                &other.pt,
+                start,
+                private.start.as_usize() - start.as_usize(),
+            );
+        }
+        if private.end < end {
+            self.pt.copy_from(
+                &other.pt,
+                private.end,
+                end.as_usize() - private.end.as_usize(),
+            );
+        }
+        Ok(())
+    }
+
     /// Add a new linear mapping.
     ///
     /// The mapping is linear, i.e., `start_vaddr` is mapped to `start_paddr`,
@@ -64,8 +129,8 @@ impl AddrSpace {
     ///
     /// The `flags` parameter indicates the mapping permissions and attributes.
     ///
-    /// Returns an error if the address range is out of the address space or not
-    /// aligned.
+    /// Returns an error if the address range is out of the address space, not
+    /// aligned, or overlaps an allocation mapping.
     pub fn map_linear(
         &mut self,
         start_vaddr: VirtAddr,
@@ -79,6 +144,10 @@ impl AddrSpace {
         if !start_vaddr.is_aligned_4k() || !start_paddr.is_aligned_4k() || !is_aligned_4k(size) {
             return ax_err!(InvalidInput, "address not aligned");
         }
+        // the frames of an allocation mapping are freed on unmap
+        if self.overlaps_alloc_area(start_vaddr, start_vaddr + size) {
+            return ax_err!(AlreadyExists, "address already mapped");
+        }
 
         let offset = start_vaddr.as_usize() - start_paddr.as_usize();
         self.pt
@@ -95,8 +164,118 @@ impl AddrSpace {
         Ok(())
     }
 
+    /// Add a new allocation mapping.
+    ///
+    /// Each page is backed by a zero-filled frame from the global allocator.
+    /// If `populate` is `true`, all frames are allocated and mapped now;
+    /// otherwise nothing is mapped until the page is first accessed, see
+    /// [`AddrSpace::handle_page_fault`].
+    ///
+    /// Returns an error if the address range is out of the address space, not
+    /// aligned, or overlaps another mapping.
+    pub fn map_alloc(
+        &mut self,
+        start: VirtAddr,
+        size: usize,
+        flags: MappingFlags,
+        populate: bool,
+    ) -> AxResult {
+        if !self.contains_range(start, size) {
+            return ax_err!(InvalidInput, "address out of range");
+        }
+        if !start.is_aligned_4k() || !is_aligned_4k(size) {
+            return ax_err!(InvalidInput, "address not aligned");
+        }
+        let end = start + size;
+        // frames found in the range on unmap are released to the allocator, so
+        // it must not contain pages mapped by someone else
+        if self.overlaps_alloc_area(start, end)
+            || PageIter4K::new(start, end)
+                .unwrap()
+                .any(|vaddr| self.pt.query(vaddr).is_ok())
+        {
+            return ax_err!(AlreadyExists, "address already mapped");
+        }
+
+        if populate {
+            for vaddr in PageIter4K::new(start, end).expect("Failed to create page iterator") {
+                if let Err(e) = self.populate_page(vaddr, flags) {
+                    self.unmap_alloc_pages(start, vaddr);
+                    return Err(e);
+                }
+            }
+        }
+        self.alloc_areas.insert(start, AllocArea { end, flags });
+        Ok(())
+    }
+
+    /// Handles a page fault at the given address.
+    ///
+    /// `access_flags` indicates the access type that caused the page fault.
+    ///
+    /// Returns `true` if the fault is in an allocation mapping that permits
+    /// the access and the page has been populated, `false` otherwise.
+    pub fn handle_page_fault(&mut self, vaddr: VirtAddr, access_flags: MappingFlags) -> bool {
+        let Some((_, area)) = self.alloc_areas.range(..=vaddr).next_back() else {
+            return false;
+        };
+        let flags = area.flags;
+        if vaddr >= area.end || !flags.contains(access_flags) {
+            return false;
+        }
+        let page = vaddr.align_down_4k();
+        match self.pt.query(page) {
+            Err(_) => self.populate_page(page, flags).is_ok(),
+            // Only a write to a copy-on-write page can be resolved here.
+            Ok((frame, page_flags, _)) => {
+                access_flags.contains(MappingFlags::WRITE)
+                    && !page_flags.contains(MappingFlags::WRITE)
+                    && self.copy_on_write(page, frame, flags).is_ok()
+            }
+        }
+    }
+
+    /// Copies all allocation mappings to `child`, sharing the populated
+    /// frames copy-on-write.
+    ///
+    /// Populated pages become read-only in both address spaces. The first
+    /// write to such a page gives the writer its own copy, see
+    /// [`AddrSpace::handle_page_fault`].
+    pub fn share_cow(&mut self, child: &mut AddrSpace) -> AxResult {
+        let mut shared = SHARED_FRAMES.lock();
+        for (&start, area) in &self.alloc_areas {
+            for vaddr in PageIter4K::new(start, area.end).unwrap() {
+                let Ok((frame, flags, _)) = self.pt.query(vaddr) else {
+                    continue;
+                };
+                let cow_flags = flags - MappingFlags::WRITE;
+                if flags.contains(MappingFlags::WRITE) {
+                    let (_, tlb) = self
+                        .pt
+                        .protect(vaddr, cow_flags)
+                        .map_err(paging_err_to_ax_err)?;
+                    tlb.flush();
+                }
+                child
+                    .pt
+                    .map(vaddr, frame, PageSize::Size4K, cow_flags)
+                    .map_err(paging_err_to_ax_err)?
+                    .ignore();
+                *shared.entry(frame).or_insert(1) += 1;
+            }
+            child.alloc_areas.insert(start, AllocArea {
+                end: area.end,
+                flags: area.flags,
+            });
+        }
+        Ok(())
+    }
+
     /// Removes mappings within the specified virtual address range.
     ///
+    /// Frames of allocation mappings in the range are returned to the global
+    /// allocator.
+    ///
     /// Returns an error if the address range is out of the address space or not
     /// aligned.
     pub fn unmap(&mut self, start: VirtAddr, size: usize) -> AxResult {
@@ -107,10 +286,17 @@ impl AddrSpace {
             return ax_err!(InvalidInput, "address not aligned");
         }
 
-        self.pt
-            .unmap_region(start, size, true)
-            .map_err(paging_err_to_ax_err)?
-            .ignore();
+        for (seg_start, seg_end, alloc) in self.split_by_alloc_areas(start, start + size) {
+            if alloc {
+                self.unmap_alloc_pages(seg_start, seg_end);
+                self.alloc_areas.remove(&seg_start);
+            } else {
+                self.pt
+                    .unmap_region(seg_start, seg_end.as_usize() - seg_start.as_usize(), true)
+                    .map_err(paging_err_to_ax_err)?
+                    .ignore();
+            }
+        }
         Ok(())
     }
 
@@ -184,19 +370,181 @@ impl AddrSpace {
             return ax_err!(InvalidInput, "address not aligned");
         }
 
+        for (seg_start, seg_end, alloc) in self.split_by_alloc_areas(start, start + size) {
+            if alloc {
+                self.alloc_areas.get_mut(&seg_start).unwrap().flags = flags;
+                // Pages not populated yet will be mapped with the new flags.
+                for vaddr in PageIter4K::new(seg_start, seg_end).unwrap() {
+                    let Ok((frame, _, _)) = self.pt.query(vaddr) else {
+                        continue;
+                    };
+                    // Shared frames stay read-only until copied on write.
+                    let page_flags = if SHARED_FRAMES.lock().contains_key(&frame) {
+                        flags - MappingFlags::WRITE
+                    } else {
+                        flags
+                    };
+                    if let Ok((_, tlb)) = self.pt.protect(vaddr, page_flags) {
+                        tlb.flush();
+                    }
+                }
+            } else {
+                self.pt
+                    .protect_region(
+                        seg_start,
+                        seg_end.as_usize() - seg_start.as_usize(),
+                        flags,
+                        true,
+                    )
+                    .map_err(paging_err_to_ax_err)?
+                    .ignore();
+            }
+        }
+        Ok(())
+    }
+
+    /// Splits `[start, end)` at the boundaries of allocation mappings.
+    ///
+    /// Allocation mappings crossing `start` or `end` are split as well, so
+    /// each returned `(start, end, is_alloc)` segment is either a whole
+    /// allocation mapping or a gap between them.
+    fn split_by_alloc_areas(
+        &mut self,
+        start: VirtAddr,
+        end: VirtAddr,
+    ) -> Vec<(VirtAddr, VirtAddr, bool)> {
+        for addr in [start, end] {
+            if let Some((_, area)) = self.alloc_areas.range_mut(..addr).next_back() {
+                if area.end > addr {
+                    let tail = AllocArea {
+                        end: area.end,
+                        flags: area.flags,
+                    };
+                    area.end = addr;
+                    self.alloc_areas.insert(addr, tail);
+                }
+            }
+        }
+
+        let mut segments = Vec::new();
+        let mut cursor = start;
+        for (&area_start, area) in self.alloc_areas.range(start..end) {
+            if cursor < area_start {
+                segments.push((cursor, area_start, false));
+            }
+            segments.push((area_start, area.end, true));
+            cursor = area.end;
+        }
+        if cursor < end {
+            segments.push((cursor, end, false));
+        }
+        segments
+    }
+
+    /// Allocates a zero-filled frame and maps the page at `vaddr` to it.
+    fn populate_page(&mut self, vaddr: VirtAddr, flags: MappingFlags) -> AxResult {
+        let frame = alloc_frame().ok_or(AxError::NoMemory)?;
+        match self.pt.map(vaddr, frame, PageSize::Size4K, flags) {
+            Ok(tlb) => {
+                tlb.flush();
+                Ok(())
+            }
+            Err(e) => {
+                dealloc_frame(frame);
+                Err(paging_err_to_ax_err(e))
+            }
+        }
+    }
+
+    /// Gives the page at `vaddr` its own writable frame.
+    ///
+    /// If `frame` is still shared with another address space, its content is
+    /// copied to a new frame. Otherwise the page is simply made writable.
+    fn copy_on_write(&mut self, vaddr: VirtAddr, frame: PhysAddr, flags: MappingFlags) -> AxResult {
+        if !SHARED_FRAMES.lock().contains_key(&frame) {
+            let (_, tlb) = self
+                .pt
+                .protect(vaddr, flags)
+                .map_err(paging_err_to_ax_err)?;
+            tlb.flush();
+            return Ok(());
+        }
+
+        let new_frame = alloc_frame().ok_or(AxError::NoMemory)?;
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                phys_to_virt(frame).as_ptr(),
+                phys_to_virt(new_frame).as_mut_ptr(),
+                PAGE_SIZE_4K,
+            )
+        };
+        let (_, _, tlb) = self.pt.unmap(vaddr).map_err(paging_err_to_ax_err)?;
+        tlb.ignore();
         self.pt
-            .protect_region(start, size, flags, true)
+            .map(vaddr, new_frame, PageSize::Size4K, flags)
             .map_err(paging_err_to_ax_err)?
-            .ignore();
+            .flush();
+        release_frame(frame);
         Ok(())
     }
+
+    /// Whether `[start, end)` overlaps an allocation mapping.
+    fn overlaps_alloc_area(&self, start: VirtAddr, end: VirtAddr) -> bool {
+        self.alloc_areas
+            .range(..end)
+            .next_back()
+            .is_some_and(|(_, area)| area.end > start)
+    }
+
+    /// Unmaps the populated pages in `[start, end)` and releases their frames.
+    fn unmap_alloc_pages(&mut self, start: VirtAddr, end: VirtAddr) {
+        for vaddr in PageIter4K::new(start, end).unwrap() {
+            if let Ok((frame, _, tlb)) = self.pt.unmap(vaddr) {
+                tlb.flush();
+                release_frame(frame);
+            }
+        }
+    }
+}
+
+impl Drop for AddrSpace {
+    fn drop(&mut self) {
+        let areas = core::mem::take(&mut self.alloc_areas);
+        for (start, area) in areas {
+            self.unmap_alloc_pages(start, area.end);
+        }
+    }
 }
 
 impl fmt::Debug for AddrSpace {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("AddrSpace")
             .field("va_range", &self.va_range)
+            .field("user_range", &self.user_range)
             .field("page_table_root", &self.pt.root_paddr())
+            .field("alloc_areas", &self.alloc_areas.len())
             .finish()
     }
 }
+
+fn alloc_frame() -> Option<PhysAddr> {
+    let vaddr = va!(global_allocator().alloc_pages(1, PAGE_SIZE_4K).ok()?);
+    unsafe { core::ptr::write_bytes(vaddr.as_mut_ptr(), 0, PAGE_SIZE_4K) };
+    Some(virt_to_phys(vaddr))
+}
+
+fn dealloc_frame(frame: PhysAddr) {
+    global_allocator().dealloc_pages(phys_to_virt(frame).as_usize(), 1);
+}
+
+/// Drops one owner of `frame`, freeing it when no owner is left.
+fn release_frame(frame: PhysAddr) {
+    let mut shared = SHARED_FRAMES.lock();
+    match shared.get_mut(&frame) {
+        Some(owners) if *owners > 2 => *owners -= 1,
+        Some(_) => {
+            shared.remove(&frame);
+        }
+        None => dealloc_frame(frame),
+    }
+}
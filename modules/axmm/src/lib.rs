@@ -1,6 +1,6 @@
 //! [ArceOS](https://github.com/arceos-org/arceos) memory management module.
 
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 #[macro_use]
 extern crate log;
@@ -8,6 +8,9 @@ extern crate alloc;
 
 mod aspace;
 
+#[cfg(test)]
+mod tests;
+
 pub use self::aspace::AddrSpace;
 
 use axerrno::{AxError, AxResult};
@@ -15,7 +18,7 @@ use axhal::mem::phys_to_virt;
 use axhal::paging::PagingError;
 use kspin::SpinNoIrq;
 use lazyinit::LazyInit;
-use memory_addr::{PhysAddr, va};
+use memory_addr::{PhysAddr, VirtAddr, VirtAddrRange, va};
 
 static KERNEL_ASPACE: LazyInit<SpinNoIrq<AddrSpace>> = LazyInit::new();
 
@@ -42,6 +45,23 @@ pub fn new_kernel_aspace() -> AxResult<AddrSpace> {
     Ok(aspace)
 }
 
+/// Creates a new address space that shares the kernel mappings.
+///
+/// Only `[base, base + size)` is private to the new address space: the
+/// top-level page table entries of everything else are copied from the kernel
+/// address space, see [`AddrSpace::copy_mappings_from`].
+pub fn new_user_aspace(base: VirtAddr, size: usize) -> AxResult<AddrSpace> {
+    let mut aspace = AddrSpace::new_empty(
+        va!(axconfig::plat::KERNEL_ASPACE_BASE),
+        axconfig::plat::KERNEL_ASPACE_SIZE,
+    )?;
+    aspace.copy_mappings_from(
+        &KERNEL_ASPACE.lock(),
+        VirtAddrRange::from_start_size(base, size),
+    )?;
+    Ok(aspace)
+}
+
 /// Returns the globally unique kernel address space.
 pub fn kernel_aspace() -> &'static SpinNoIrq<AddrSpace> {
     &KERNEL_ASPACE
@@ -0,0 +1,98 @@
+use std::sync::{Mutex, Once};
+
+use axhal::paging::MappingFlags;
+use memory_addr::{PAGE_SIZE_4K, VirtAddr, va};
+
+use crate::AddrSpace;
+
+const POOL_SIZE: usize = 0x40_0000;
+
+#[repr(align(4096))]
+struct Pool([u8; POOL_SIZE]);
+
+static mut POOL: Pool = Pool([0; POOL_SIZE]);
+static INIT: Once = Once::new();
+static SERIAL: Mutex<()> = Mutex::new(());
+
+const BASE: VirtAddr = va!(0x1000_0000);
+const RW: MappingFlags = MappingFlags::READ.union(MappingFlags::WRITE);
+
+fn new_aspace() -> AddrSpace {
+    INIT.call_once(|| {
+        let start = &raw mut POOL as usize;
+        axalloc::global_init(start, POOL_SIZE);
+    });
+    AddrSpace::new_empty(BASE, 0x10_0000).unwrap()
+}
+
+fn read_byte(aspace: &AddrSpace, vaddr: VirtAddr) -> u8 {
+    let mut buf = [0];
+    aspace.read(vaddr, &mut buf).unwrap();
+    buf[0]
+}
+
+#[test]
+fn test_lazy_fault() {
+    let _lock = SERIAL.lock();
+    let mut aspace = new_aspace();
+    aspace.map_alloc(BASE, 2 * PAGE_SIZE_4K, RW, false).unwrap();
+
+    // nothing is mapped until the first access
+    assert!(aspace.page_table().query(BASE).is_err());
+    assert!(aspace.read(BASE, &mut [0]).is_err());
+
+    assert!(aspace.handle_page_fault(BASE + 0x10, MappingFlags::READ));
+    assert!(aspace.page_table().query(BASE).is_ok());
+    assert!(aspace.page_table().query(BASE + PAGE_SIZE_4K).is_err());
+    assert_eq!(read_byte(&aspace, BASE + 0x10), 0);
+
+    // outside of the area, or an access the area does not permit
+    assert!(!aspace.handle_page_fault(BASE + 2 * PAGE_SIZE_4K, MappingFlags::READ));
+    assert!(!aspace.handle_page_fault(BASE + PAGE_SIZE_4K, MappingFlags::EXECUTE));
+    assert!(aspace.page_table().query(BASE + PAGE_SIZE_4K).is_err());
+
+    // a fault on a populated writable page is not resolved again
+    assert!(!aspace.handle_page_fault(BASE, MappingFlags::WRITE));
+}
+
+#[test]
+fn test_cow_fault() {
+    let _lock = SERIAL.lock();
+    let mut parent = new_aspace();
+    parent.map_alloc(BASE, 2 * PAGE_SIZE_4K, RW, true).unwrap();
+    parent.write(BASE, &[1]).unwrap();
+    parent.write(BASE + PAGE_SIZE_4K, &[2]).unwrap();
+
+    let mut child = new_aspace();
+    parent.share_cow(&mut child).unwrap();
+    let (frame, flags, _) = parent.page_table().query(BASE).unwrap();
+    assert!(!flags.contains(MappingFlags::WRITE));
+    assert_eq!(child.page_table().query(BASE).unwrap().0, frame);
+    assert_eq!(read_byte(&child, BASE), 1);
+
+    // the writer gets its own copy, the other side keeps the old frame
+    assert!(child.handle_page_fault(BASE, MappingFlags::WRITE));
+    let (child_frame, child_flags, _) = child.page_table().query(BASE).unwrap();
+    assert_ne!(child_frame, frame);
+    assert!(child_flags.contains(MappingFlags::WRITE));
+    child.write(BASE, &[3]).unwrap();
+    assert_eq!(read_byte(&child, BASE), 3);
+    assert_eq!(read_byte(&parent, BASE), 1);
+
+    // the last owner of a frame reuses it instead of copying
+    assert!(parent.handle_page_fault(BASE, MappingFlags::WRITE));
+    let (parent_frame, parent_flags, _) = parent.page_table().query(BASE).unwrap();
+    assert_eq!(parent_frame, frame);
+    assert!(parent_flags.contains(MappingFlags::WRITE));
+
+    // pages nobody wrote to stay shared
+    assert_eq!(
+        parent.page_table().query(BASE + PAGE_SIZE_4K).unwrap().0,
+        child.page_table().query(BASE + PAGE_SIZE_4K).unwrap().0,
+    );
+    assert_eq!(read_byte(&child, BASE + PAGE_SIZE_4K), 2);
+
+    drop(child);
+    assert!(parent.handle_page_fault(BASE + PAGE_SIZE_4K, MappingFlags::WRITE));
+    assert_eq!(read_byte(&parent, BASE + PAGE_SIZE_4K), 2);
+}
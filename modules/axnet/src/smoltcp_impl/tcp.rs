@@ -269,6 +269,23 @@ impl TcpSocket {
         Ok(())
     }
 
+    /// Close the sending half of the connection.
+    ///
+    /// A FIN is sent to the peer, while the data it sends can still be received.
+    pub fn shutdown_write(&self) -> AxResult {
+        if !self.is_connected() {
+            return ax_err!(NotConnected, "socket shutdown() failed");
+        }
+        // SAFETY: `self.handle` should be initialized in a connected socket.
+        let handle = unsafe { self.handle.get().read().unwrap() };
+        SOCKET_SET.with_socket_mut::<tcp::Socket, _, _>(handle, |socket| {
+            debug!("TCP socket {}: shutting down the sending half", handle);
+            socket.close();
+        });
+        SOCKET_SET.poll_interfaces();
+        Ok(())
+    }
+
     /// Receives data from the socket, stores it in the given buffer.
     pub fn recv(&self, buf: &mut [u8]) -> AxResult<usize> {
         if self.is_connecting() {
@@ -393,8 +393,13 @@ impl TaskInner {
         self.wait_for_exit.notify_all(false);
     }
 
+    /// Returns a raw pointer to the task context.
+    ///
+    /// # Safety
+    ///
+    /// The context must not be modified while the task is being switched.
     #[inline]
-    pub(crate) const unsafe fn ctx_mut_ptr(&self) -> *mut TaskContext {
+    pub const unsafe fn ctx_mut_ptr(&self) -> *mut TaskContext {
         self.ctx.get()
     }
 
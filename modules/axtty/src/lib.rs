@@ -0,0 +1,548 @@
+//! TTY line discipline for the [ArceOS](https://github.com/arceos-org/arceos)
+//! console.
+//!
+//! The console driver only moves raw bytes. [`LineDiscipline`] sits between it
+//! and the readers of the terminal, as `N_TTY` of Linux does:
+//!
+//! - In canonical mode the input is collected into lines that can be edited
+//!   with ERASE (DEL or backspace), WERASE (`^W`) and KILL (`^U`), and is made
+//!   available a line at a time. EOF (`^D`) ends the read with what has been
+//!   typed so far, or with 0 bytes on an empty line.
+//! - In raw (non-canonical) mode every byte is available at once, a read
+//!   returns after `VMIN` bytes. `VTIME` is not implemented.
+//! - INTR (`^C`), QUIT (`^\`) and SUSP (`^Z`) are turned into a [`TtySignal`]
+//!   for the caller to deliver.
+//! - Input is echoed back, control characters as `^X`.
+//!
+//! It does no I/O itself: the caller feeds the received bytes to
+//! [`LineDiscipline::input`] and passes a function to write the echo.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+mod termios;
+
+pub use self::termios::*;
+
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+/// Maximum length of a line in canonical mode, the rest is dropped until the
+/// end of the line.
+pub const MAX_LINE: usize = 4095;
+
+const BS: u8 = 0x08;
+const DEL: u8 = 0x7f;
+
+/// A signal generated by a special input character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TtySignal {
+    /// INTR, `SIGINT`.
+    Interrupt,
+    /// QUIT, `SIGQUIT`.
+    Quit,
+    /// SUSP, `SIGTSTP`.
+    Suspend,
+}
+
+impl TtySignal {
+    /// The Linux signal number.
+    pub const fn signo(self) -> usize {
+        match self {
+            Self::Interrupt => 2,
+            Self::Quit => 3,
+            Self::Suspend => 20,
+        }
+    }
+}
+
+/// The input and echo processing of a terminal.
+pub struct LineDiscipline {
+    termios: Termios,
+    winsize: WinSize,
+    /// The line being edited in canonical mode.
+    line: Vec<u8>,
+    /// Input that can be read. In canonical mode each entry is one line, an
+    /// empty entry marks an end of file.
+    ready: VecDeque<Vec<u8>>,
+    /// The next character is taken literally (after LNEXT).
+    literal_next: bool,
+}
+
+impl LineDiscipline {
+    /// Creates a line discipline with the default attributes, see
+    /// [`Termios::new`].
+    pub const fn new() -> Self {
+        Self {
+            termios: Termios::new(),
+            winsize: WinSize::new(),
+            line: Vec::new(),
+            ready: VecDeque::new(),
+            literal_next: false,
+        }
+    }
+
+    /// The current terminal attributes.
+    pub fn termios(&self) -> Termios {
+        self.termios
+    }
+
+    /// Changes the terminal attributes. A partially edited line becomes
+    /// readable when leaving canonical mode.
+    pub fn set_termios(&mut self, termios: Termios) {
+        if self.termios.is_canonical() && !termios.is_canonical() && !self.line.is_empty() {
+            let line = core::mem::take(&mut self.line);
+            self.ready.push_back(line);
+        }
+        self.termios = termios;
+    }
+
+    /// The window size.
+    pub fn winsize(&self) -> WinSize {
+        self.winsize
+    }
+
+    /// Sets the window size. Nothing is resized, it is only reported to
+    /// programs that ask.
+    pub fn set_winsize(&mut self, winsize: WinSize) {
+        self.winsize = winsize;
+    }
+
+    /// Discards all input that has not been read.
+    pub fn flush_input(&mut self) {
+        self.line.clear();
+        self.ready.clear();
+        self.literal_next = false;
+    }
+
+    /// Number of bytes that can be read right away.
+    pub fn available(&self) -> usize {
+        self.ready.iter().map(Vec::len).sum()
+    }
+
+    /// Whether a read of `count` bytes would return now: a complete line (or
+    /// end of file) in canonical mode, `VMIN` bytes otherwise.
+    pub fn can_read(&self, count: usize) -> bool {
+        if self.termios.is_canonical() {
+            !self.ready.is_empty()
+        } else {
+            let min = self.termios.cc[VMIN] as usize;
+            self.available() >= min.min(count)
+        }
+    }
+
+    /// Reads the available input into `buf`, at most one line in canonical
+    /// mode. Returns 0 at the end of file or if nothing is available.
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+        if self.termios.is_canonical() {
+            return match self.ready.pop_front() {
+                Some(line) => self.take_front(line, buf),
+                None => 0,
+            };
+        }
+        let mut read = 0;
+        while read < buf.len() {
+            match self.ready.pop_front() {
+                Some(chunk) => read += self.take_front(chunk, &mut buf[read..]),
+                None => break,
+            }
+        }
+        read
+    }
+
+    /// Copies the beginning of `chunk` into `buf` and puts the rest back.
+    fn take_front(&mut self, mut chunk: Vec<u8>, buf: &mut [u8]) -> usize {
+        let len = chunk.len().min(buf.len());
+        buf[..len].copy_from_slice(&chunk[..len]);
+        if len < chunk.len() {
+            chunk.drain(..len);
+            self.ready.push_front(chunk);
+        }
+        len
+    }
+
+    /// Processes a byte received from the terminal. The echo is written with
+    /// `echo`.
+    pub fn input(&mut self, mut c: u8, mut echo: impl FnMut(&[u8])) -> Option<TtySignal> {
+        let t = self.termios;
+        if self.literal_next {
+            self.literal_next = false;
+            self.push(c, &mut echo);
+            return None;
+        }
+        if t.iflag & ISTRIP != 0 {
+            c &= 0x7f;
+        }
+        match c {
+            b'\r' if t.iflag & IGNCR != 0 => return None,
+            b'\r' if t.iflag & ICRNL != 0 => c = b'\n',
+            b'\n' if t.iflag & INLCR != 0 => c = b'\r',
+            _ => {}
+        }
+        // a control character of 0 is disabled
+        let is = |idx: usize| t.cc[idx] != 0 && c == t.cc[idx];
+
+        if t.lflag(ISIG) {
+            let signal = if is(VINTR) {
+                Some(TtySignal::Interrupt)
+            } else if is(VQUIT) {
+                Some(TtySignal::Quit)
+            } else if is(VSUSP) {
+                Some(TtySignal::Suspend)
+            } else {
+                None
+            };
+            if signal.is_some() {
+                if !t.lflag(NOFLSH) {
+                    self.flush_input();
+                }
+                self.echo(c, &mut echo);
+                return signal;
+            }
+        }
+        if t.lflag(IEXTEN) && is(VLNEXT) {
+            self.literal_next = true;
+            if t.lflag(ECHO) && t.lflag(ECHOCTL) {
+                echo(&[b'^', BS]);
+            }
+            return None;
+        }
+        if !t.is_canonical() {
+            self.push(c, &mut echo);
+            return None;
+        }
+
+        if is(VERASE) || c == BS {
+            self.erase(&mut echo);
+        } else if t.lflag(IEXTEN) && is(VWERASE) {
+            while self.line.last().is_some_and(u8::is_ascii_whitespace) {
+                self.erase(&mut echo);
+            }
+            while self.line.last().is_some_and(|c| !c.is_ascii_whitespace()) {
+                self.erase(&mut echo);
+            }
+        } else if is(VKILL) {
+            if t.lflag(ECHO) && t.lflag(ECHOKE) {
+                while !self.line.is_empty() {
+                    self.erase(&mut echo);
+                }
+            } else {
+                self.echo(c, &mut echo);
+                if t.lflag(ECHO) && t.lflag(ECHOK) {
+                    echo(b"\n");
+                }
+            }
+            self.line.clear();
+        } else if t.lflag(IEXTEN) && is(VREPRINT) {
+            if t.lflag(ECHO) {
+                self.echo(c, &mut echo);
+                echo(b"\n");
+                echo(&self.line);
+            }
+        } else if is(VEOF) {
+            let line = core::mem::take(&mut self.line);
+            self.ready.push_back(line);
+        } else if c == b'\n' || is(VEOL) {
+            self.line.push(c);
+            if t.lflag(ECHO) || (c == b'\n' && t.lflag(ECHONL)) {
+                echo(&[c]);
+            }
+            let line = core::mem::take(&mut self.line);
+            self.ready.push_back(line);
+        } else {
+            self.push(c, &mut echo);
+        }
+        None
+    }
+
+    /// Appends a normal character to the input and echoes it.
+    fn push(&mut self, c: u8, echo: &mut impl FnMut(&[u8])) {
+        if self.termios.is_canonical() {
+            if self.line.len() >= MAX_LINE {
+                return;
+            }
+            self.line.push(c);
+        } else {
+            match self.ready.back_mut() {
+                Some(chunk) if !chunk.is_empty() => chunk.push(c),
+                _ => self.ready.push_back(vec![c]),
+            }
+        }
+        self.echo(c, echo);
+    }
+
+    /// Removes the last character, which may take several bytes in UTF-8, of
+    /// the line being edited.
+    fn erase(&mut self, echo: &mut impl FnMut(&[u8])) {
+        let Some(mut c) = self.line.pop() else {
+            return;
+        };
+        while c & 0xc0 == 0x80 {
+            match self.line.pop() {
+                Some(prev) => c = prev,
+                None => break,
+            }
+        }
+        let t = self.termios;
+        if !t.lflag(ECHO) {
+            return;
+        }
+        if t.lflag(ECHOE) {
+            let width = if is_echoed_as_ctl(&t, c) { 2 } else { 1 };
+            for _ in 0..width {
+                echo(&[BS, b' ', BS]);
+            }
+        } else {
+            self.echo(t.cc[VERASE], echo);
+        }
+    }
+
+    /// Echoes an input character, control characters as `^X` if `ECHOCTL` is
+    /// set.
+    fn echo(&self, c: u8, echo: &mut impl FnMut(&[u8])) {
+        if !self.termios.lflag(ECHO) {
+            return;
+        }
+        if is_echoed_as_ctl(&self.termios, c) {
+            echo(&[b'^', c ^ 0x40]);
+        } else {
+            echo(&[c]);
+        }
+    }
+
+    /// Writes `buf` to the terminal with `write`, with the output processing
+    /// of `oflag`.
+    pub fn output(&self, buf: &[u8], mut write: impl FnMut(&[u8])) {
+        let oflag = self.termios.oflag;
+        if oflag & OPOST == 0 || oflag & (ONLCR | OCRNL) == 0 {
+            write(buf);
+            return;
+        }
+        let mut start = 0;
+        for (i, &c) in buf.iter().enumerate() {
+            let replace: &[u8] = match c {
+                b'\n' if oflag & ONLCR != 0 => b"\r\n",
+                b'\r' if oflag & OCRNL != 0 => b"\n",
+                _ => continue,
+            };
+            write(&buf[start..i]);
+            write(replace);
+            start = i + 1;
+        }
+        write(&buf[start..]);
+    }
+}
+
+impl Default for LineDiscipline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_echoed_as_ctl(termios: &Termios, c: u8) -> bool {
+    termios.lflag(ECHOCTL) && (c < b' ' && c != b'\n' && c != b'\t' || c == DEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(tty: &mut LineDiscipline, input: &[u8]) -> (Vec<u8>, Option<TtySignal>) {
+        let mut echoed = Vec::new();
+        let mut signal = None;
+        for &c in input {
+            signal = signal.or(tty.input(c, |b| echoed.extend_from_slice(b)));
+        }
+        (echoed, signal)
+    }
+
+    fn read_all(tty: &mut LineDiscipline) -> Vec<u8> {
+        let mut buf = [0; 64];
+        let len = tty.read(&mut buf);
+        buf[..len].to_vec()
+    }
+
+    #[test]
+    fn canonical_editing() {
+        let mut tty = LineDiscipline::new();
+        let (echoed, _) = feed(&mut tty, b"lss\x7f -l");
+        assert!(!tty.can_read(64));
+        feed(&mut tty, b"\r");
+        assert!(tty.can_read(64));
+        assert_eq!(read_all(&mut tty), b"ls -l\n");
+        assert_eq!(echoed, b"lss\x08 \x08 -l");
+
+        feed(&mut tty, b"rm -rf foo\x17bar\n");
+        assert_eq!(read_all(&mut tty), b"rm -rf bar\n");
+        feed(&mut tty, b"oops\x15ok\n");
+        assert_eq!(read_all(&mut tty), b"ok\n");
+    }
+
+    #[test]
+    fn eof_and_partial_reads() {
+        let mut tty = LineDiscipline::new();
+        feed(&mut tty, b"abc\x04\x04");
+        let mut buf = [0; 2];
+        assert_eq!(tty.read(&mut buf), 2);
+        assert_eq!(tty.read(&mut buf), 1);
+        assert_eq!(buf[0], b'c');
+        // the empty line is the end of file
+        assert!(tty.can_read(2));
+        assert_eq!(tty.read(&mut buf), 0);
+        assert!(!tty.can_read(2));
+    }
+
+    #[test]
+    fn interrupt() {
+        let mut tty = LineDiscipline::new();
+        let (echoed, signal) = feed(&mut tty, b"sleep 10\x03");
+        assert_eq!(signal, Some(TtySignal::Interrupt));
+        assert!(echoed.ends_with(b"^C"));
+        assert_eq!(tty.available(), 0);
+    }
+
+    #[test]
+    fn raw_mode() {
+        let mut tty = LineDiscipline::new();
+        feed(&mut tty, b"ab");
+        let mut termios = tty.termios();
+        termios.make_raw();
+        tty.set_termios(termios);
+        let (echoed, signal) = feed(&mut tty, b"\x03\r");
+        assert_eq!(signal, None);
+        assert!(echoed.is_empty());
+        assert!(tty.can_read(1));
+        assert_eq!(read_all(&mut tty), b"ab\x03\r");
+    }
+
+    #[test]
+    fn echo_flags() {
+        let mut tty = LineDiscipline::new();
+        let mut termios = tty.termios();
+        // control characters are echoed as `^X` with `ECHOCTL`
+        let (echoed, _) = feed(&mut tty, b"a\x01\t\n");
+        assert_eq!(echoed, b"a^A\t\n");
+
+        termios.lflag &= !ECHOCTL;
+        tty.set_termios(termios);
+        let (echoed, _) = feed(&mut tty, b"a\x01\n");
+        assert_eq!(echoed, b"a\x01\n");
+
+        // only the newline with `ECHONL`
+        termios.lflag &= !ECHO;
+        termios.lflag |= ECHONL;
+        tty.set_termios(termios);
+        let (echoed, _) = feed(&mut tty, b"secret\x7f\n");
+        assert_eq!(echoed, b"\n");
+        termios.lflag &= !ECHONL;
+        tty.set_termios(termios);
+        let (echoed, _) = feed(&mut tty, b"secret\n");
+        assert!(echoed.is_empty());
+
+        tty.flush_input();
+        assert_eq!(tty.available(), 0);
+        feed(&mut tty, b"x\n");
+        assert_eq!(read_all(&mut tty), b"x\n");
+    }
+
+    #[test]
+    fn erase() {
+        let mut tty = LineDiscipline::new();
+        // nothing to erase
+        let (echoed, _) = feed(&mut tty, b"\x7f\x08");
+        assert!(echoed.is_empty());
+
+        // a multi-byte character and a control character shown as `^A`
+        let (echoed, _) = feed(&mut tty, "é\x01\x7f\x7f".as_bytes());
+        assert_eq!(echoed, "é^A\x08 \x08\x08 \x08\x08 \x08".as_bytes());
+        feed(&mut tty, b"e\n");
+        assert_eq!(read_all(&mut tty), b"e\n");
+
+        // the ERASE character itself is echoed without `ECHOE`
+        let mut termios = tty.termios();
+        termios.lflag &= !ECHOE;
+        tty.set_termios(termios);
+        let (echoed, _) = feed(&mut tty, b"ab\x7f\n");
+        assert_eq!(echoed, b"ab^?\n");
+        assert_eq!(read_all(&mut tty), b"a\n");
+
+        // KILL without `ECHOKE` echoes `^U` and a newline (`ECHOK`)
+        termios.lflag &= !ECHOKE;
+        tty.set_termios(termios);
+        let (echoed, _) = feed(&mut tty, b"abc\x15x\n");
+        assert_eq!(echoed, b"abc^U\nx\n");
+        assert_eq!(read_all(&mut tty), b"x\n");
+
+        // WERASE removes the trailing blanks and the word before them
+        let (echoed, _) = feed(&mut tty, b"one two  \x17\n");
+        assert_eq!(echoed, b"one two  ^?^?^?^?^?\n");
+        assert_eq!(read_all(&mut tty), b"one \n");
+    }
+
+    #[test]
+    fn literal_next() {
+        let mut tty = LineDiscipline::new();
+        let (echoed, signal) = feed(&mut tty, b"\x16\x03\x16\x7f\n");
+        assert_eq!(signal, None);
+        assert_eq!(echoed, b"^\x08^C^\x08^?\n");
+        assert_eq!(read_all(&mut tty), b"\x03\x7f\n");
+    }
+
+    #[test]
+    fn line_too_long() {
+        let mut tty = LineDiscipline::new();
+        let long = vec![b'x'; MAX_LINE + 10];
+        feed(&mut tty, &long);
+        feed(&mut tty, b"\n");
+        let mut buf = vec![0; MAX_LINE + 10];
+        assert_eq!(tty.read(&mut buf), MAX_LINE + 1);
+        assert_eq!(buf[MAX_LINE], b'\n');
+    }
+
+    #[test]
+    fn vmin_vtime() {
+        let mut tty = LineDiscipline::new();
+        let mut termios = tty.termios();
+        termios.make_raw();
+        termios.cc[VMIN] = 3;
+        tty.set_termios(termios);
+        feed(&mut tty, b"ab");
+        // waits for `VMIN` bytes, or for as many as asked for if fewer
+        assert!(!tty.can_read(64));
+        assert!(tty.can_read(2));
+        feed(&mut tty, b"c");
+        assert!(tty.can_read(64));
+        let mut buf = [0; 2];
+        assert_eq!(tty.read(&mut buf), 2);
+        assert_eq!(&buf, b"ab");
+        assert_eq!(tty.available(), 1);
+
+        // `VMIN` of 0 polls: a read returns at once, possibly with nothing
+        termios.cc[VMIN] = 0;
+        tty.set_termios(termios);
+        assert_eq!(read_all(&mut tty), b"c");
+        assert!(tty.can_read(64));
+        assert_eq!(read_all(&mut tty), b"");
+
+        // `VTIME` is not implemented, it does not delay or end a read
+        termios.cc[VTIME] = 10;
+        tty.set_termios(termios);
+        assert!(tty.can_read(64));
+        termios.cc[VMIN] = 2;
+        tty.set_termios(termios);
+        feed(&mut tty, b"x");
+        assert!(!tty.can_read(64));
+        assert_eq!(tty.available(), 1);
+    }
+
+    #[test]
+    fn output_processing() {
+        let tty = LineDiscipline::new();
+        let mut out = Vec::new();
+        tty.output(b"a\nb\n", |b| out.extend_from_slice(b));
+        assert_eq!(out, b"a\r\nb\r\n");
+    }
+}
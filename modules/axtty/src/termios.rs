@@ -0,0 +1,185 @@
+//! Terminal attributes in the layout of the Linux `struct termios` and
+//! `struct winsize`, as used by the `TCGETS`/`TCSETS` and
+//! `TIOCGWINSZ`/`TIOCSWINSZ` ioctls.
+
+/// Number of control characters in [`Termios::cc`].
+pub const NCCS: usize = 19;
+
+// `iflag`
+/// Strip off the eighth bit.
+pub const ISTRIP: u32 = 0o40;
+/// Translate NL to CR on input.
+pub const INLCR: u32 = 0o100;
+/// Ignore CR on input.
+pub const IGNCR: u32 = 0o200;
+/// Translate CR to NL on input.
+pub const ICRNL: u32 = 0o400;
+/// Enable XON/XOFF flow control on output, not implemented.
+pub const IXON: u32 = 0o2000;
+
+// `oflag`
+/// Enable output processing.
+pub const OPOST: u32 = 0o1;
+/// Map NL to CR-NL on output.
+pub const ONLCR: u32 = 0o4;
+/// Map CR to NL on output.
+pub const OCRNL: u32 = 0o10;
+
+// `cflag`, reported only
+const B38400: u32 = 0o17;
+const CS8: u32 = 0o60;
+const CREAD: u32 = 0o200;
+const HUPCL: u32 = 0o2000;
+
+// `lflag`
+/// Generate signals for the INTR, QUIT and SUSP characters.
+pub const ISIG: u32 = 0o1;
+/// Canonical mode: input is made available line by line and can be edited.
+pub const ICANON: u32 = 0o2;
+/// Echo input characters.
+pub const ECHO: u32 = 0o10;
+/// ERASE erases the preceding character on the screen.
+pub const ECHOE: u32 = 0o20;
+/// KILL erases the current line on the screen.
+pub const ECHOK: u32 = 0o40;
+/// Echo NL even if `ECHO` is not set.
+pub const ECHONL: u32 = 0o100;
+/// Do not flush the input when generating a signal.
+pub const NOFLSH: u32 = 0o200;
+/// Echo control characters as `^X`.
+pub const ECHOCTL: u32 = 0o1000;
+/// KILL erases each character of the line on the screen.
+pub const ECHOKE: u32 = 0o4000;
+/// Enable the WERASE and LNEXT characters.
+pub const IEXTEN: u32 = 0o100000;
+
+// indices in `cc`
+/// Interrupt character (`^C`), sends `SIGINT`.
+pub const VINTR: usize = 0;
+/// Quit character (`^\`), sends `SIGQUIT`.
+pub const VQUIT: usize = 1;
+/// Erase character (DEL).
+pub const VERASE: usize = 2;
+/// Kill line character (`^U`).
+pub const VKILL: usize = 3;
+/// End of file character (`^D`).
+pub const VEOF: usize = 4;
+/// Timeout of a non-canonical read in deciseconds, not implemented.
+pub const VTIME: usize = 5;
+/// Minimum number of bytes of a non-canonical read.
+pub const VMIN: usize = 6;
+/// Start character (`^Q`).
+pub const VSTART: usize = 8;
+/// Stop character (`^S`).
+pub const VSTOP: usize = 9;
+/// Suspend character (`^Z`), sends `SIGTSTP`.
+pub const VSUSP: usize = 10;
+/// Additional end of line character.
+pub const VEOL: usize = 11;
+/// Reprint character (`^R`).
+pub const VREPRINT: usize = 12;
+/// Discard character (`^O`).
+pub const VDISCARD: usize = 13;
+/// Word erase character (`^W`).
+pub const VWERASE: usize = 14;
+/// Literal next character (`^V`).
+pub const VLNEXT: usize = 15;
+
+/// Terminal attributes, the kernel `struct termios` of Linux.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Termios {
+    /// Input modes.
+    pub iflag: u32,
+    /// Output modes.
+    pub oflag: u32,
+    /// Control modes.
+    pub cflag: u32,
+    /// Local modes.
+    pub lflag: u32,
+    /// Line discipline.
+    pub line: u8,
+    /// Control characters.
+    pub cc: [u8; NCCS],
+}
+
+impl Termios {
+    /// The attributes a Linux console starts with: canonical mode with echo
+    /// and signals.
+    pub const fn new() -> Self {
+        let mut cc = [0; NCCS];
+        cc[VINTR] = 0x03;
+        cc[VQUIT] = 0x1c;
+        cc[VERASE] = 0x7f;
+        cc[VKILL] = 0x15;
+        cc[VEOF] = 0x04;
+        cc[VMIN] = 1;
+        cc[VSTART] = 0x11;
+        cc[VSTOP] = 0x13;
+        cc[VSUSP] = 0x1a;
+        cc[VREPRINT] = 0x12;
+        cc[VDISCARD] = 0x0f;
+        cc[VWERASE] = 0x17;
+        cc[VLNEXT] = 0x16;
+        Self {
+            iflag: ICRNL | IXON,
+            oflag: OPOST | ONLCR,
+            cflag: B38400 | CS8 | CREAD | HUPCL,
+            lflag: ISIG | ICANON | ECHO | ECHOE | ECHOK | ECHOCTL | ECHOKE | IEXTEN,
+            line: 0,
+            cc,
+        }
+    }
+
+    /// Whether the local mode `flag` is set.
+    pub const fn lflag(&self, flag: u32) -> bool {
+        self.lflag & flag != 0
+    }
+
+    /// Whether the input is read line by line.
+    pub const fn is_canonical(&self) -> bool {
+        self.lflag(ICANON)
+    }
+
+    /// Switches to raw mode like `cfmakeraw(3)`: no input or output
+    /// processing, no echo and no signals.
+    pub fn make_raw(&mut self) {
+        self.iflag &= !(ISTRIP | INLCR | IGNCR | ICRNL | IXON);
+        self.oflag &= !OPOST;
+        self.lflag &= !(ECHO | ECHONL | ICANON | ISIG | IEXTEN);
+        self.cc[VMIN] = 1;
+        self.cc[VTIME] = 0;
+    }
+}
+
+impl Default for Termios {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Size of the terminal window, the `struct winsize` of Linux.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WinSize {
+    /// Rows, in characters.
+    pub ws_row: u16,
+    /// Columns, in characters.
+    pub ws_col: u16,
+    /// Width, in pixels.
+    pub ws_xpixel: u16,
+    /// Height, in pixels.
+    pub ws_ypixel: u16,
+}
+
+impl WinSize {
+    /// The size of a VT100 terminal.
+    pub const fn new() -> Self {
+        Self {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        }
+    }
+}
@@ -12,6 +12,8 @@ endif
 
 ifeq ($(ARCH), x86_64)
   machine := q35
+  # `apps.bin` is loaded right after the 128M managed by the kernel
+  override MEM := 160M
 else ifeq ($(ARCH), riscv64)
   machine := virt
 else ifeq ($(ARCH), aarch64)
@@ -25,6 +27,7 @@ endif
 
 qemu_args-x86_64 := \
   -machine $(machine) \
+  -device loader,file=$(CURDIR)/payload/apps.bin,addr=0x8000000,force-raw=on \
   -kernel $(OUT_ELF)
 
 qemu_args-riscv64 := \
@@ -38,6 +41,10 @@ qemu_args-aarch64 := \
   -machine $(machine) \
   -kernel $(OUT_BIN)
 
+ifeq ($(machine), virt)
+  qemu_args-aarch64 += -drive if=pflash,file=$(CURDIR)/payload/apps.bin,format=raw,unit=1
+endif
+
 qemu_args-y := -m $(MEM) -smp $(SMP) $(qemu_args-$(ARCH))
 
 qemu_args-$(BLK) += \
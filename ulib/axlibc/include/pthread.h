@@ -11,6 +11,11 @@
 #define PTHREAD_CANCEL_DEFERRED     0
 #define PTHREAD_CANCEL_ASYNCHRONOUS 1
 
+#define PTHREAD_CREATE_JOINABLE 0
+#define PTHREAD_CREATE_DETACHED 1
+
+#define PTHREAD_BARRIER_SERIAL_THREAD (-1)
+
 typedef struct {
     unsigned __attr;
 } pthread_condattr_t;
@@ -31,6 +36,7 @@ typedef struct {
 #define _a_stacksize __u.__s[0]
 #define _a_guardsize __u.__s[1]
 #define _a_stackaddr __u.__s[2]
+#define _a_detach    __u.__i[3 * sizeof(long) / sizeof(int)]
 
 typedef struct {
     union {
@@ -42,6 +48,38 @@ typedef struct {
 #define _c_clock  __u.__i[4]
 #define _c_shared __u.__p[0]
 
+typedef struct {
+    union {
+        int __i[sizeof(long) == 8 ? 14 : 8];
+        volatile int __vi[sizeof(long) == 8 ? 14 : 8];
+        void *__p[sizeof(long) == 8 ? 7 : 8];
+    } __u;
+} pthread_rwlock_t;
+
+typedef struct {
+    unsigned __attr[2];
+} pthread_rwlockattr_t;
+
+typedef struct {
+    union {
+        int __i[sizeof(long) == 8 ? 8 : 5];
+        volatile int __vi[sizeof(long) == 8 ? 8 : 5];
+        void *__p[sizeof(long) == 8 ? 4 : 5];
+    } __u;
+} pthread_barrier_t;
+
+typedef struct {
+    unsigned __attr;
+} pthread_barrierattr_t;
+
+typedef unsigned pthread_key_t;
+typedef int pthread_once_t;
+
+#define PTHREAD_MUTEXATTR_INITIALIZER {0}
+#define PTHREAD_COND_INITIALIZER      {{{0}}}
+#define PTHREAD_RWLOCK_INITIALIZER    {{{0}}}
+#define PTHREAD_ONCE_INIT             0
+
 typedef void *pthread_t;
 
 #define PTHREAD_CANCELED ((void *)-1)
@@ -55,6 +93,7 @@ pthread_t pthread_self(void);
 int pthread_create(pthread_t *__restrict, const pthread_attr_t *__restrict, void *(*)(void *),
                    void *__restrict);
 int pthread_join(pthread_t t, void **res);
+int pthread_detach(pthread_t t);
 
 int pthread_setcancelstate(int, int *);
 int pthread_setcanceltype(int, int *);
@@ -65,6 +104,8 @@ int pthread_mutex_init(pthread_mutex_t *__restrict, const pthread_mutexattr_t *_
 int pthread_mutex_lock(pthread_mutex_t *);
 int pthread_mutex_unlock(pthread_mutex_t *);
 int pthread_mutex_trylock(pthread_mutex_t *);
+int pthread_mutex_timedlock(pthread_mutex_t *__restrict, const struct timespec *__restrict);
+int pthread_mutex_destroy(pthread_mutex_t *);
 
 int pthread_setname_np(pthread_t, const char *);
 
@@ -72,12 +113,40 @@ int pthread_cond_init(pthread_cond_t *__restrict__ __cond,
                       const pthread_condattr_t *__restrict__ __cond_attr);
 int pthread_cond_signal(pthread_cond_t *__cond);
 int pthread_cond_wait(pthread_cond_t *__restrict__ __cond, pthread_mutex_t *__restrict__ __mutex);
+int pthread_cond_timedwait(pthread_cond_t *__restrict, pthread_mutex_t *__restrict,
+                           const struct timespec *__restrict);
 int pthread_cond_broadcast(pthread_cond_t *);
+int pthread_cond_destroy(pthread_cond_t *);
+
+int pthread_rwlock_init(pthread_rwlock_t *__restrict, const pthread_rwlockattr_t *__restrict);
+int pthread_rwlock_destroy(pthread_rwlock_t *);
+int pthread_rwlock_rdlock(pthread_rwlock_t *);
+int pthread_rwlock_tryrdlock(pthread_rwlock_t *);
+int pthread_rwlock_timedrdlock(pthread_rwlock_t *__restrict, const struct timespec *__restrict);
+int pthread_rwlock_wrlock(pthread_rwlock_t *);
+int pthread_rwlock_trywrlock(pthread_rwlock_t *);
+int pthread_rwlock_timedwrlock(pthread_rwlock_t *__restrict, const struct timespec *__restrict);
+int pthread_rwlock_unlock(pthread_rwlock_t *);
+
+int pthread_barrier_init(pthread_barrier_t *__restrict, const pthread_barrierattr_t *__restrict,
+                         unsigned);
+int pthread_barrier_destroy(pthread_barrier_t *);
+int pthread_barrier_wait(pthread_barrier_t *);
+
+int pthread_key_create(pthread_key_t *, void (*)(void *));
+int pthread_key_delete(pthread_key_t);
+void *pthread_getspecific(pthread_key_t);
+int pthread_setspecific(pthread_key_t, const void *);
+
+int pthread_once(pthread_once_t *, void (*)(void));
 
 int pthread_attr_init(pthread_attr_t *__attr);
 int pthread_attr_getstacksize(const pthread_attr_t *__restrict__ __attr,
                               size_t *__restrict__ __stacksize);
 int pthread_attr_setstacksize(pthread_attr_t *__attr, size_t __stacksize);
+int pthread_attr_destroy(pthread_attr_t *__attr);
+int pthread_attr_getdetachstate(const pthread_attr_t *__attr, int *__detachstate);
+int pthread_attr_setdetachstate(pthread_attr_t *__attr, int __detachstate);
 
 #endif // AX_CONFIG_MULTITASK
 
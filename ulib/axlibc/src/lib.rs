@@ -109,9 +109,35 @@ pub use self::net::{
 };
 
 #[cfg(feature = "multitask")]
-pub use self::pthread::{pthread_create, pthread_exit, pthread_join, pthread_self};
+pub use self::pthread::{
+    pthread_attr_destroy, pthread_attr_getdetachstate, pthread_attr_getstacksize,
+    pthread_attr_init, pthread_attr_setdetachstate, pthread_attr_setstacksize,
+};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_barrier_destroy, pthread_barrier_init, pthread_barrier_wait, pthread_cond_broadcast,
+    pthread_cond_destroy, pthread_cond_init, pthread_cond_signal, pthread_cond_timedwait,
+    pthread_cond_wait,
+};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_create, pthread_detach, pthread_exit, pthread_join, pthread_once, pthread_self,
+};
 #[cfg(feature = "multitask")]
-pub use self::pthread::{pthread_mutex_init, pthread_mutex_lock, pthread_mutex_unlock};
+pub use self::pthread::{
+    pthread_getspecific, pthread_key_create, pthread_key_delete, pthread_setspecific,
+};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_mutex_destroy, pthread_mutex_init, pthread_mutex_lock, pthread_mutex_timedlock,
+    pthread_mutex_trylock, pthread_mutex_unlock,
+};
+#[cfg(feature = "multitask")]
+pub use self::pthread::{
+    pthread_rwlock_destroy, pthread_rwlock_init, pthread_rwlock_rdlock, pthread_rwlock_timedrdlock,
+    pthread_rwlock_timedwrlock, pthread_rwlock_tryrdlock, pthread_rwlock_trywrlock,
+    pthread_rwlock_unlock, pthread_rwlock_wrlock,
+};
 
 #[cfg(feature = "pipe")]
 pub use self::pipe::pipe;
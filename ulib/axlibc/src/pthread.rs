@@ -1,6 +1,6 @@
 use crate::{ctypes, utils::e};
 use arceos_posix_api as api;
-use core::ffi::{c_int, c_void};
+use core::ffi::{c_int, c_uint, c_void};
 
 /// Returns the `pthread` struct of current thread.
 #[unsafe(no_mangle)]
@@ -57,3 +57,251 @@ pub unsafe extern "C" fn pthread_mutex_lock(mutex: *mut ctypes::pthread_mutex_t)
 pub unsafe extern "C" fn pthread_mutex_unlock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
     e(api::sys_pthread_mutex_unlock(mutex))
 }
+
+/// Detaches the given thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_detach(thread: ctypes::pthread_t) -> c_int {
+    e(api::sys_pthread_detach(thread))
+}
+
+/// Calls `init_routine` exactly once for the given `once_control`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_once(
+    once_control: *mut ctypes::pthread_once_t,
+    init_routine: extern "C" fn(),
+) -> c_int {
+    e(api::sys_pthread_once(once_control, init_routine))
+}
+
+/// Initializes a thread attributes object.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_attr_init(attr: *mut ctypes::pthread_attr_t) -> c_int {
+    e(api::sys_pthread_attr_init(attr))
+}
+
+/// Destroys a thread attributes object.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_attr_destroy(attr: *mut ctypes::pthread_attr_t) -> c_int {
+    e(api::sys_pthread_attr_destroy(attr))
+}
+
+/// Gets the stack size attribute.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_attr_getstacksize(
+    attr: *const ctypes::pthread_attr_t,
+    stacksize: *mut usize,
+) -> c_int {
+    e(api::sys_pthread_attr_getstacksize(attr, stacksize))
+}
+
+/// Sets the stack size attribute.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_attr_setstacksize(
+    attr: *mut ctypes::pthread_attr_t,
+    stacksize: usize,
+) -> c_int {
+    e(api::sys_pthread_attr_setstacksize(attr, stacksize))
+}
+
+/// Gets the detach state attribute.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_attr_getdetachstate(
+    attr: *const ctypes::pthread_attr_t,
+    detachstate: *mut c_int,
+) -> c_int {
+    e(api::sys_pthread_attr_getdetachstate(attr, detachstate))
+}
+
+/// Sets the detach state attribute.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_attr_setdetachstate(
+    attr: *mut ctypes::pthread_attr_t,
+    detachstate: c_int,
+) -> c_int {
+    e(api::sys_pthread_attr_setdetachstate(attr, detachstate))
+}
+
+/// Lock the given mutex if it is not held by another thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_mutex_trylock(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    e(api::sys_pthread_mutex_trylock(mutex))
+}
+
+/// Lock the given mutex, giving up at the absolute time `abstime`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_mutex_timedlock(
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    e(api::sys_pthread_mutex_timedlock(mutex, abstime))
+}
+
+/// Destroy the given mutex.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_mutex_destroy(mutex: *mut ctypes::pthread_mutex_t) -> c_int {
+    e(api::sys_pthread_mutex_destroy(mutex))
+}
+
+/// Initialize a condition variable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_cond_init(
+    cond: *mut ctypes::pthread_cond_t,
+    attr: *const ctypes::pthread_condattr_t,
+) -> c_int {
+    e(api::sys_pthread_cond_init(cond, attr))
+}
+
+/// Destroy a condition variable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_cond_destroy(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    e(api::sys_pthread_cond_destroy(cond))
+}
+
+/// Wait on a condition variable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_cond_wait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+) -> c_int {
+    e(api::sys_pthread_cond_wait(cond, mutex))
+}
+
+/// Wait on a condition variable, giving up at the absolute time `abstime`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_cond_timedwait(
+    cond: *mut ctypes::pthread_cond_t,
+    mutex: *mut ctypes::pthread_mutex_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    e(api::sys_pthread_cond_timedwait(cond, mutex, abstime))
+}
+
+/// Wake up one thread waiting on a condition variable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_cond_signal(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    e(api::sys_pthread_cond_signal(cond))
+}
+
+/// Wake up all threads waiting on a condition variable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_cond_broadcast(cond: *mut ctypes::pthread_cond_t) -> c_int {
+    e(api::sys_pthread_cond_broadcast(cond))
+}
+
+/// Initialize a read-write lock.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_init(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    attr: *const ctypes::pthread_rwlockattr_t,
+) -> c_int {
+    e(api::sys_pthread_rwlock_init(rwlock, attr))
+}
+
+/// Destroy a read-write lock.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_destroy(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    e(api::sys_pthread_rwlock_destroy(rwlock))
+}
+
+/// Lock a read-write lock for reading.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_rdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    e(api::sys_pthread_rwlock_rdlock(rwlock))
+}
+
+/// Try to lock a read-write lock for reading.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_tryrdlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    e(api::sys_pthread_rwlock_tryrdlock(rwlock))
+}
+
+/// Lock a read-write lock for reading, giving up at the absolute time `abstime`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_timedrdlock(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    e(api::sys_pthread_rwlock_timedrdlock(rwlock, abstime))
+}
+
+/// Lock a read-write lock for writing.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_wrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    e(api::sys_pthread_rwlock_wrlock(rwlock))
+}
+
+/// Try to lock a read-write lock for writing.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_trywrlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    e(api::sys_pthread_rwlock_trywrlock(rwlock))
+}
+
+/// Lock a read-write lock for writing, giving up at the absolute time `abstime`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_timedwrlock(
+    rwlock: *mut ctypes::pthread_rwlock_t,
+    abstime: *const ctypes::timespec,
+) -> c_int {
+    e(api::sys_pthread_rwlock_timedwrlock(rwlock, abstime))
+}
+
+/// Unlock a read-write lock.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_rwlock_unlock(rwlock: *mut ctypes::pthread_rwlock_t) -> c_int {
+    e(api::sys_pthread_rwlock_unlock(rwlock))
+}
+
+/// Initialize a barrier for `count` threads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_barrier_init(
+    barrier: *mut ctypes::pthread_barrier_t,
+    attr: *const ctypes::pthread_barrierattr_t,
+    count: c_uint,
+) -> c_int {
+    e(api::sys_pthread_barrier_init(barrier, attr, count))
+}
+
+/// Destroy a barrier.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_barrier_destroy(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    e(api::sys_pthread_barrier_destroy(barrier))
+}
+
+/// Wait on a barrier.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_barrier_wait(barrier: *mut ctypes::pthread_barrier_t) -> c_int {
+    match api::sys_pthread_barrier_wait(barrier) {
+        ctypes::PTHREAD_BARRIER_SERIAL_THREAD => ctypes::PTHREAD_BARRIER_SERIAL_THREAD,
+        ret => e(ret),
+    }
+}
+
+/// Create a thread-specific data key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_key_create(
+    key: *mut ctypes::pthread_key_t,
+    destructor: Option<extern "C" fn(*mut c_void)>,
+) -> c_int {
+    e(api::sys_pthread_key_create(key, destructor))
+}
+
+/// Delete a thread-specific data key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_key_delete(key: ctypes::pthread_key_t) -> c_int {
+    e(api::sys_pthread_key_delete(key))
+}
+
+/// Returns the value bound to `key` in the current thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_getspecific(key: ctypes::pthread_key_t) -> *mut c_void {
+    api::sys_pthread_getspecific(key)
+}
+
+/// Bind `value` to `key` in the current thread.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pthread_setspecific(
+    key: ctypes::pthread_key_t,
+    value: *const c_void,
+) -> c_int {
+    e(api::sys_pthread_setspecific(key, value))
+}
@@ -0,0 +1,53 @@
+#define a_ll a_ll
+static inline int a_ll(volatile int *p)
+{
+    int v;
+    __asm__ __volatile__("ldaxr %w0,%1" : "=r"(v) : "Q"(*p));
+    return v;
+}
+
+#define a_sc a_sc
+static inline int a_sc(volatile int *p, int v)
+{
+    int r;
+    __asm__ __volatile__("stlxr %w0,%w2,%1" : "=&r"(r), "=Q"(*p) : "r"(v) : "memory");
+    return !r;
+}
+
+#define a_barrier a_barrier
+static inline void a_barrier()
+{
+    __asm__ __volatile__("dmb ish" : : : "memory");
+}
+
+#define a_cas a_cas
+static inline int a_cas(volatile int *p, int t, int s)
+{
+    int old;
+    do {
+        old = a_ll(p);
+        if (old != t) {
+            a_barrier();
+            break;
+        }
+    } while (!a_sc(p, s));
+    return old;
+}
+
+#define a_cas_p a_cas_p
+static inline void *a_cas_p(volatile void *p, void *t, void *s)
+{
+    void *old;
+    __asm__ __volatile__("	dmb ish\n"
+                         "1:	ldxr %0,%3\n"
+                         "	cmp %0,%1\n"
+                         "	b.ne 1f\n"
+                         "	stxr %w0,%2,%3\n"
+                         "	cbnz %w0,1b\n"
+                         "	mov %0,%1\n"
+                         "1:	dmb ish\n"
+                         : "=&r"(old)
+                         : "r"(t), "r"(s), "Q"(*(void *volatile *)p)
+                         : "memory", "cc");
+    return old;
+}
@@ -0,0 +1,25 @@
+#define a_cas a_cas
+static inline int a_cas(volatile int *p, int t, int s)
+{
+    __asm__ __volatile__("lock ; cmpxchg %3, %1"
+                         : "=a"(t), "=m"(*p)
+                         : "a"(t), "r"(s)
+                         : "memory");
+    return t;
+}
+
+#define a_cas_p a_cas_p
+static inline void *a_cas_p(volatile void *p, void *t, void *s)
+{
+    __asm__("lock ; cmpxchg %3, %1"
+            : "=a"(t), "=m"(*(void *volatile *)p)
+            : "a"(t), "r"(s)
+            : "memory");
+    return t;
+}
+
+#define a_barrier a_barrier
+static inline void a_barrier()
+{
+    __asm__ __volatile__("" : : : "memory");
+}
@@ -0,0 +1,55 @@
+// x86_64 的系统调用号，只收录 include/bits/syscall.h 里启用了的调用，
+// 加载器会把它们换算回通用调用号再分发
+#define __NR_gettimeofday 96
+
+#define SYS_read     0
+#define SYS_write    1
+#define SYS_close    3
+#define SYS_fstat    5
+#define SYS_lseek    8
+#define SYS_mmap     9
+#define SYS_munmap   11
+#define SYS_brk      12
+#define SYS_rt_sigaction   13
+#define SYS_rt_sigprocmask 14
+#define SYS_ioctl    16
+#define SYS_pread64  17
+#define SYS_pwrite64 18
+#define SYS_readv    19
+#define SYS_writev   20
+#define SYS_mremap   25
+#define SYS_dup      32
+#define SYS_nanosleep 35
+#define SYS_getpid   39
+#define SYS_sendfile 40
+#define SYS_exit     60
+#define SYS_fcntl    72
+#define SYS_fsync     74
+#define SYS_fdatasync 75
+#define SYS_ftruncate 77
+#define SYS_getcwd   79
+#define SYS_chdir    80
+#define SYS_fchmod   91
+#define SYS_fchown   93
+#define SYS_sysinfo  99
+#define SYS_geteuid  107
+#define SYS_tkill    200
+#define SYS_futex    202
+#define SYS_sched_getaffinity 204
+#define SYS_clock_gettime   228
+#define SYS_clock_nanosleep 230
+#define SYS_exit_group 231
+#define SYS_openat     257
+#define SYS_mkdirat    258
+#define SYS_fchownat   260
+#define SYS_newfstatat 262
+#define SYS_unlinkat   263
+#define SYS_linkat     265
+#define SYS_readlinkat 267
+#define SYS_fchmodat   268
+#define SYS_faccessat  269
+#define SYS_pselect6   270
+#define SYS_utimensat  280
+#define SYS_dup3       292
+#define SYS_prlimit64  302
+#define SYS_renameat2  316
@@ -3,9 +3,31 @@
 
 unsigned long volatile abi_entry = 0;
 
+// 加载器把 ABI 表的地址放在这个寄存器中：riscv64 为`a7`，x86_64 和 aarch64
+// 为第二个参数寄存器
+#if defined(__x86_64__)
+#define LOAD_ABI_TABLE(t) __asm__ volatile("mov %%rsi, %0" : "=r"(t))
+#elif defined(__aarch64__)
+#define LOAD_ABI_TABLE(t) __asm__ volatile("mov %0, x1" : "=r"(t))
+#else
+#define LOAD_ABI_TABLE(t) __asm__ volatile("mv %0, a7" : "=r"(t))
+#endif
+
+// 检查表头后让`abi_entry`指向表项；装载器的表与编译时的头文件不一致时
+// 直接停下，而不是调用到错误的函数
+#define LOAD_ABI_ENTRY()                                              \
+    do {                                                              \
+        struct abi_table *t;                                          \
+        LOAD_ABI_TABLE(t);                                            \
+        if (t->magic != ABI_MAGIC || t->version != ABI_VERSION ||     \
+            t->count < ABI_COUNT)                                     \
+            __builtin_trap();                                         \
+        abi_entry = (unsigned long)t->entries;                        \
+    } while (0)
+
 __attribute__((visibility("hidden"))) void _start(long *p)
 {
-    __asm__ volatile("mv %0, a7" : "=r"(abi_entry));
+    LOAD_ABI_ENTRY();
     int argc = p[0];
     char **argv = (void *)(p + 1);
 
@@ -17,7 +39,7 @@ __attribute__((visibility("hidden"))) void _start(long *p)
 /// void mock_start_main(long *p)
 void __libc_start_main(long *p)
 {
-    __asm__ volatile("mv %0, a7" : "=r"(abi_entry));
+    LOAD_ABI_ENTRY();
     int argc = p[0];
     char **argv = (void *)(p + 1);
 
@@ -33,3 +55,11 @@ void terminate()
     Fn func = (Fn)(*abi_ptr);
     return func();
 }
+
+void *abi_lookup(const char *name)
+{
+    typedef void *(*Fn)(const char *);
+    long *abi_ptr = (long *)(abi_entry + 8 * ABI_LOOKUP);
+    Fn func = (Fn)(*abi_ptr);
+    return func(name);
+}
@@ -0,0 +1,34 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_attr_init(pthread_attr_t *a)
+{
+    return ABI_FN(ABI_PTHREAD_ATTR_INIT, int (*)(pthread_attr_t *))(a);
+}
+
+int pthread_attr_destroy(pthread_attr_t *a)
+{
+    return ABI_FN(ABI_PTHREAD_ATTR_DESTROY, int (*)(pthread_attr_t *))(a);
+}
+
+int pthread_attr_getstacksize(const pthread_attr_t *restrict a, size_t *restrict size)
+{
+    typedef int (*FnABI)(const pthread_attr_t *, size_t *);
+    return ABI_FN(ABI_PTHREAD_ATTR_GETSTACKSIZE, FnABI)(a, size);
+}
+
+int pthread_attr_setstacksize(pthread_attr_t *a, size_t size)
+{
+    return ABI_FN(ABI_PTHREAD_ATTR_SETSTACKSIZE, int (*)(pthread_attr_t *, size_t))(a, size);
+}
+
+int pthread_attr_getdetachstate(const pthread_attr_t *a, int *state)
+{
+    typedef int (*FnABI)(const pthread_attr_t *, int *);
+    return ABI_FN(ABI_PTHREAD_ATTR_GETDETACHSTATE, FnABI)(a, state);
+}
+
+int pthread_attr_setdetachstate(pthread_attr_t *a, int state)
+{
+    return ABI_FN(ABI_PTHREAD_ATTR_SETDETACHSTATE, int (*)(pthread_attr_t *, int))(a, state);
+}
@@ -0,0 +1,19 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_barrier_init(pthread_barrier_t *restrict b, const pthread_barrierattr_t *restrict a,
+                         unsigned count)
+{
+    typedef int (*FnABI)(pthread_barrier_t *, const pthread_barrierattr_t *, unsigned);
+    return ABI_FN(ABI_PTHREAD_BARRIER_INIT, FnABI)(b, a, count);
+}
+
+int pthread_barrier_destroy(pthread_barrier_t *b)
+{
+    return ABI_FN(ABI_PTHREAD_BARRIER_DESTROY, int (*)(pthread_barrier_t *))(b);
+}
+
+int pthread_barrier_wait(pthread_barrier_t *b)
+{
+    return ABI_FN(ABI_PTHREAD_BARRIER_WAIT, int (*)(pthread_barrier_t *))(b);
+}
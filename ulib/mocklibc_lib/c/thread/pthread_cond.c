@@ -0,0 +1,36 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_cond_init(pthread_cond_t *restrict c, const pthread_condattr_t *restrict a)
+{
+    typedef int (*FnABI)(pthread_cond_t *, const pthread_condattr_t *);
+    return ABI_FN(ABI_PTHREAD_COND_INIT, FnABI)(c, a);
+}
+
+int pthread_cond_destroy(pthread_cond_t *c)
+{
+    return ABI_FN(ABI_PTHREAD_COND_DESTROY, int (*)(pthread_cond_t *))(c);
+}
+
+int pthread_cond_wait(pthread_cond_t *restrict c, pthread_mutex_t *restrict m)
+{
+    typedef int (*FnABI)(pthread_cond_t *, pthread_mutex_t *);
+    return ABI_FN(ABI_PTHREAD_COND_WAIT, FnABI)(c, m);
+}
+
+int pthread_cond_timedwait(pthread_cond_t *restrict c, pthread_mutex_t *restrict m,
+                           const struct timespec *restrict at)
+{
+    typedef int (*FnABI)(pthread_cond_t *, pthread_mutex_t *, const struct timespec *);
+    return ABI_FN(ABI_PTHREAD_COND_TIMEDWAIT, FnABI)(c, m, at);
+}
+
+int pthread_cond_signal(pthread_cond_t *c)
+{
+    return ABI_FN(ABI_PTHREAD_COND_SIGNAL, int (*)(pthread_cond_t *))(c);
+}
+
+int pthread_cond_broadcast(pthread_cond_t *c)
+{
+    return ABI_FN(ABI_PTHREAD_COND_BROADCAST, int (*)(pthread_cond_t *))(c);
+}
@@ -0,0 +1,7 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_detach(pthread_t t)
+{
+    return ABI_FN(ABI_PTHREAD_DETACH, int (*)(pthread_t))(t);
+}
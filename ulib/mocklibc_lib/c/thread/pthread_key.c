@@ -0,0 +1,23 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_key_create(pthread_key_t *k, void (*dtor)(void *))
+{
+    typedef int (*FnABI)(pthread_key_t *, void (*)(void *));
+    return ABI_FN(ABI_PTHREAD_KEY_CREATE, FnABI)(k, dtor);
+}
+
+int pthread_key_delete(pthread_key_t k)
+{
+    return ABI_FN(ABI_PTHREAD_KEY_DELETE, int (*)(pthread_key_t))(k);
+}
+
+void *pthread_getspecific(pthread_key_t k)
+{
+    return ABI_FN(ABI_PTHREAD_GETSPECIFIC, void *(*)(pthread_key_t))(k);
+}
+
+int pthread_setspecific(pthread_key_t k, const void *x)
+{
+    return ABI_FN(ABI_PTHREAD_SETSPECIFIC, int (*)(pthread_key_t, const void *))(k, x);
+}
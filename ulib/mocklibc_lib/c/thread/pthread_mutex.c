@@ -0,0 +1,34 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_mutex_init(pthread_mutex_t *restrict m, const pthread_mutexattr_t *restrict a)
+{
+    typedef int (*FnABI)(pthread_mutex_t *, const pthread_mutexattr_t *);
+    return ABI_FN(ABI_PTHREAD_MUTEX_INIT, FnABI)(m, a);
+}
+
+int pthread_mutex_lock(pthread_mutex_t *m)
+{
+    return ABI_FN(ABI_PTHREAD_MUTEX_LOCK, int (*)(pthread_mutex_t *))(m);
+}
+
+int pthread_mutex_trylock(pthread_mutex_t *m)
+{
+    return ABI_FN(ABI_PTHREAD_MUTEX_TRYLOCK, int (*)(pthread_mutex_t *))(m);
+}
+
+int pthread_mutex_timedlock(pthread_mutex_t *restrict m, const struct timespec *restrict at)
+{
+    typedef int (*FnABI)(pthread_mutex_t *, const struct timespec *);
+    return ABI_FN(ABI_PTHREAD_MUTEX_TIMEDLOCK, FnABI)(m, at);
+}
+
+int pthread_mutex_unlock(pthread_mutex_t *m)
+{
+    return ABI_FN(ABI_PTHREAD_MUTEX_UNLOCK, int (*)(pthread_mutex_t *))(m);
+}
+
+int pthread_mutex_destroy(pthread_mutex_t *m)
+{
+    return ABI_FN(ABI_PTHREAD_MUTEX_DESTROY, int (*)(pthread_mutex_t *))(m);
+}
@@ -0,0 +1,7 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_once(pthread_once_t *control, void (*init)(void))
+{
+    return ABI_FN(ABI_PTHREAD_ONCE, int (*)(pthread_once_t *, void (*)(void)))(control, init);
+}
@@ -0,0 +1,50 @@
+#include <mocklibc.h>
+#include <pthread.h>
+
+int pthread_rwlock_init(pthread_rwlock_t *restrict rw, const pthread_rwlockattr_t *restrict a)
+{
+    typedef int (*FnABI)(pthread_rwlock_t *, const pthread_rwlockattr_t *);
+    return ABI_FN(ABI_PTHREAD_RWLOCK_INIT, FnABI)(rw, a);
+}
+
+int pthread_rwlock_destroy(pthread_rwlock_t *rw)
+{
+    return ABI_FN(ABI_PTHREAD_RWLOCK_DESTROY, int (*)(pthread_rwlock_t *))(rw);
+}
+
+int pthread_rwlock_rdlock(pthread_rwlock_t *rw)
+{
+    return ABI_FN(ABI_PTHREAD_RWLOCK_RDLOCK, int (*)(pthread_rwlock_t *))(rw);
+}
+
+int pthread_rwlock_tryrdlock(pthread_rwlock_t *rw)
+{
+    return ABI_FN(ABI_PTHREAD_RWLOCK_TRYRDLOCK, int (*)(pthread_rwlock_t *))(rw);
+}
+
+int pthread_rwlock_timedrdlock(pthread_rwlock_t *restrict rw, const struct timespec *restrict at)
+{
+    typedef int (*FnABI)(pthread_rwlock_t *, const struct timespec *);
+    return ABI_FN(ABI_PTHREAD_RWLOCK_TIMEDRDLOCK, FnABI)(rw, at);
+}
+
+int pthread_rwlock_wrlock(pthread_rwlock_t *rw)
+{
+    return ABI_FN(ABI_PTHREAD_RWLOCK_WRLOCK, int (*)(pthread_rwlock_t *))(rw);
+}
+
+int pthread_rwlock_trywrlock(pthread_rwlock_t *rw)
+{
+    return ABI_FN(ABI_PTHREAD_RWLOCK_TRYWRLOCK, int (*)(pthread_rwlock_t *))(rw);
+}
+
+int pthread_rwlock_timedwrlock(pthread_rwlock_t *restrict rw, const struct timespec *restrict at)
+{
+    typedef int (*FnABI)(pthread_rwlock_t *, const struct timespec *);
+    return ABI_FN(ABI_PTHREAD_RWLOCK_TIMEDWRLOCK, FnABI)(rw, at);
+}
+
+int pthread_rwlock_unlock(pthread_rwlock_t *rw)
+{
+    return ABI_FN(ABI_PTHREAD_RWLOCK_UNLOCK, int (*)(pthread_rwlock_t *))(rw);
+}
@@ -0,0 +1,258 @@
+// 由 examples/loader_lib/build.rs 根据 examples/loader_lib/src/abi/table.def 生成，不要手动修改
+#ifndef _ABI_TABLE_H
+#define _ABI_TABLE_H
+
+#define ABI_MAGIC   0x54494241
+#define ABI_VERSION 1
+#define ABI_COUNT   289
+
+// 装载器传给程序入口的 ABI 表，表头之后是各项实现函数的地址
+struct abi_table {
+    unsigned int magic;
+    unsigned int version;
+    unsigned long count;
+    unsigned long entries[];
+};
+
+#define ABI_NOIMPL                 0
+#define ABI_INIT_SCHEDULER         1
+#define ABI_TERMINATE              2
+#define ABI_LOOKUP                 3
+#define ABI_TIMESPEC               11
+#define ABI_PTHREAD_CREATE         20
+#define ABI_PTHREAD_JOIN           21
+#define ABI_PTHREAD_EXIT           22
+#define ABI_PTHREAD_SELF           23
+#define ABI_PTHREAD_MUTEX_INIT     24
+#define ABI_PTHREAD_MUTEX_LOCK     25
+#define ABI_PTHREAD_MUTEX_UNLOCK   26
+#define ABI_PTHREAD_MUTEX_DESTROY  27
+#define ABI_PTHREAD_DETACH         28
+#define ABI_PTHREAD_ONCE           29
+#define ABI_PTHREAD_MUTEX_TRYLOCK  30
+#define ABI_PTHREAD_MUTEX_TIMEDLOCK 31
+#define ABI_PTHREAD_ATTR_INIT      32
+#define ABI_PTHREAD_ATTR_DESTROY   33
+#define ABI_PTHREAD_ATTR_GETSTACKSIZE 34
+#define ABI_PTHREAD_ATTR_SETSTACKSIZE 35
+#define ABI_PTHREAD_ATTR_GETDETACHSTATE 36
+#define ABI_PTHREAD_ATTR_SETDETACHSTATE 37
+#define ABI_MALLOC                 40
+#define ABI_CALLOC                 41
+#define ABI_REALLOC                42
+#define ABI_FREE                   43
+#define ABI_SLEEP                  50
+#define ABI_SYSCALL0               60
+#define ABI_SYSCALL1               61
+#define ABI_SYSCALL2               62
+#define ABI_SYSCALL3               63
+#define ABI_SYSCALL4               64
+#define ABI_SYSCALL5               65
+#define ABI_SYSCALL6               66
+#define ABI_PTHREAD_COND_INIT      70
+#define ABI_PTHREAD_COND_DESTROY   71
+#define ABI_PTHREAD_COND_WAIT      72
+#define ABI_PTHREAD_COND_TIMEDWAIT 73
+#define ABI_PTHREAD_COND_SIGNAL    74
+#define ABI_PTHREAD_COND_BROADCAST 75
+#define ABI_PTHREAD_RWLOCK_INIT    76
+#define ABI_PTHREAD_RWLOCK_DESTROY 77
+#define ABI_PTHREAD_RWLOCK_RDLOCK  78
+#define ABI_PTHREAD_RWLOCK_TRYRDLOCK 79
+#define ABI_PTHREAD_RWLOCK_TIMEDRDLOCK 80
+#define ABI_PTHREAD_RWLOCK_WRLOCK  81
+#define ABI_PTHREAD_RWLOCK_TRYWRLOCK 82
+#define ABI_PTHREAD_RWLOCK_TIMEDWRLOCK 83
+#define ABI_PTHREAD_RWLOCK_UNLOCK  84
+#define ABI_PTHREAD_BARRIER_INIT   85
+#define ABI_PTHREAD_BARRIER_DESTROY 86
+#define ABI_PTHREAD_BARRIER_WAIT   87
+#define ABI_PTHREAD_KEY_CREATE     88
+#define ABI_PTHREAD_KEY_DELETE     89
+#define ABI_PTHREAD_GETSPECIFIC    90
+#define ABI_PTHREAD_SETSPECIFIC    91
+#define ABI_RT_ADDSF3              100
+#define ABI_RT_ADDDF3              101
+#define ABI_RT_ADDTF3              102
+#define ABI_RT_ADDXF3              103
+#define ABI_RT_SUBSF3              104
+#define ABI_RT_SUBDF3              105
+#define ABI_RT_SUBTF3              106
+#define ABI_RT_SUBXF3              107
+#define ABI_RT_MULSF3              108
+#define ABI_RT_MULDF3              109
+#define ABI_RT_MULTF3              110
+#define ABI_RT_MULXF3              111
+#define ABI_RT_DIVSF3              112
+#define ABI_RT_DIVDF3              113
+#define ABI_RT_DIVTF3              114
+#define ABI_RT_DIVXF3              115
+#define ABI_RT_NEGSF2              116
+#define ABI_RT_NEGDF2              117
+#define ABI_RT_NEGTF2              118
+#define ABI_RT_NEGXF2              119
+#define ABI_RT_EXTENDSFDF2         120
+#define ABI_RT_EXTENDSFTF2         121
+#define ABI_RT_EXTENDSFXF2         122
+#define ABI_RT_EXTENDDFTF2         123
+#define ABI_RT_EXTENDDFXF2         124
+#define ABI_RT_TRUNCXFDF2          125
+#define ABI_RT_TRUNCTFDF2          126
+#define ABI_RT_TRUNCXFSF2          127
+#define ABI_RT_TRUNCTFSF2          128
+#define ABI_RT_TRUNCDFSF2          129
+#define ABI_RT_FIXSFSI             130
+#define ABI_RT_FIXDFSI             131
+#define ABI_RT_FIXTFSI             132
+#define ABI_RT_FIXXFSI             133
+#define ABI_RT_FIXSFDI             134
+#define ABI_RT_FIXDFDI             135
+#define ABI_RT_FIXTFDI             136
+#define ABI_RT_FIXXFDI             137
+#define ABI_RT_FIXSFTI             138
+#define ABI_RT_FIXDFTI             139
+#define ABI_RT_FIXTFTI             140
+#define ABI_RT_FIXXFTI             141
+#define ABI_RT_FIXUNSSFSI          142
+#define ABI_RT_FIXUNSDFSI          143
+#define ABI_RT_FIXUNSTFSI          144
+#define ABI_RT_FIXUNSXFSI          145
+#define ABI_RT_FIXUNSSFDI          146
+#define ABI_RT_FIXUNSDFDI          147
+#define ABI_RT_FIXUNSTFDI          148
+#define ABI_RT_FIXUNSXFDI          149
+#define ABI_RT_FIXUNSSFTI          150
+#define ABI_RT_FIXUNSDFTI          151
+#define ABI_RT_FIXUNSTFTI          152
+#define ABI_RT_FIXUNSXFTI          153
+#define ABI_RT_FLOATSISF           154
+#define ABI_RT_FLOATSIDF           155
+#define ABI_RT_FLOATSITF           156
+#define ABI_RT_FLOATSIXF           157
+#define ABI_RT_FLOATDISF           158
+#define ABI_RT_FLOATDIDF           159
+#define ABI_RT_FLOATDITF           160
+#define ABI_RT_FLOATDIXF           161
+#define ABI_RT_FLOATTISF           162
+#define ABI_RT_FLOATTIDF           163
+#define ABI_RT_FLOATTITF           164
+#define ABI_RT_FLOATTIXF           165
+#define ABI_RT_FLOATUNSISF         166
+#define ABI_RT_FLOATUNSIDF         167
+#define ABI_RT_FLOATUNSITF         168
+#define ABI_RT_FLOATUNSIXF         169
+#define ABI_RT_FLOATUNDISF         170
+#define ABI_RT_FLOATUNDIDF         171
+#define ABI_RT_FLOATUNDITF         172
+#define ABI_RT_FLOATUNDIXF         173
+#define ABI_RT_FLOATUNTISF         174
+#define ABI_RT_FLOATUNTIDF         175
+#define ABI_RT_FLOATUNTITF         176
+#define ABI_RT_FLOATUNTIXF         177
+#define ABI_RT_FIXSFBITINT         178
+#define ABI_RT_FIXDFBITINT         179
+#define ABI_RT_FIXXFBITINT         180
+#define ABI_RT_FIXTFBITINT         181
+#define ABI_RT_FLOATBITINTSF       182
+#define ABI_RT_FLOATBITINTDF       183
+#define ABI_RT_FLOATBITINTXF       184
+#define ABI_RT_FLOATBITINTTF       185
+#define ABI_RT_FLOATBITINTHF       186
+#define ABI_RT_FLOATBITINTBF       187
+#define ABI_RT_CMPSF2              188
+#define ABI_RT_CMPDF2              189
+#define ABI_RT_CMPTF2              190
+#define ABI_RT_UNORDSF2            191
+#define ABI_RT_UNORDDF2            192
+#define ABI_RT_UNORDTF2            193
+#define ABI_RT_EQSF2               194
+#define ABI_RT_EQDF2               195
+#define ABI_RT_EQTF2               196
+#define ABI_RT_NESF2               197
+#define ABI_RT_NEDF2               198
+#define ABI_RT_NETF2               199
+#define ABI_RT_GESF2               200
+#define ABI_RT_GEDF2               201
+#define ABI_RT_GETF2               202
+#define ABI_RT_LTSF2               203
+#define ABI_RT_LTDF2               204
+#define ABI_RT_LTTF2               205
+#define ABI_RT_LESF2               206
+#define ABI_RT_LEDF2               207
+#define ABI_RT_LETF2               208
+#define ABI_RT_GTSF2               209
+#define ABI_RT_GTDF2               210
+#define ABI_RT_GTTF2               211
+#define ABI_RT_POWISF2             212
+#define ABI_RT_POWIDF2             213
+#define ABI_RT_POWITF2             214
+#define ABI_RT_POWIXF2             215
+#define ABI_RT_MULSC3              216
+#define ABI_RT_MULDC3              217
+#define ABI_RT_MULTC3              218
+#define ABI_RT_MULXC3              219
+#define ABI_RT_DIVSC3              220
+#define ABI_RT_DIVDC3              221
+#define ABI_RT_DIVTC3              222
+#define ABI_RT_DIVXC3              223
+#define ABI_RT_ASHLSI3             230
+#define ABI_RT_ASHLDI3             231
+#define ABI_RT_ASHRSI3             232
+#define ABI_RT_ASHRDI3             233
+#define ABI_RT_ASHRTI3             234
+#define ABI_RT_DIVSI3              235
+#define ABI_RT_DIVDI3              236
+#define ABI_RT_DIVTI3              237
+#define ABI_RT_LSHRSI3             238
+#define ABI_RT_LSHRDI3             239
+#define ABI_RT_LSHRTI3             240
+#define ABI_RT_MODSI3              241
+#define ABI_RT_MODDI3              242
+#define ABI_RT_MODTI3              243
+#define ABI_RT_MULSI3              244
+#define ABI_RT_MULDI3              245
+#define ABI_RT_MULTI3              246
+#define ABI_RT_NEGDI2              247
+#define ABI_RT_NEGTI2              248
+#define ABI_RT_UDIVSI3             249
+#define ABI_RT_UDIVDI3             250
+#define ABI_RT_UDIVTI3             251
+#define ABI_RT_UDIVMODDI4          252
+#define ABI_RT_UDIVMODTI4          253
+#define ABI_RT_UMODSI3             254
+#define ABI_RT_UMODDI3             255
+#define ABI_RT_UMODTI3             256
+#define ABI_RT_CMPDI2              257
+#define ABI_RT_CMPTI2              258
+#define ABI_RT_UCMPDI2             259
+#define ABI_RT_UCMPTI2             260
+#define ABI_RT_ABSVSI2             261
+#define ABI_RT_ABSVDI2             262
+#define ABI_RT_ADDVSI3             263
+#define ABI_RT_ADDVDI3             264
+#define ABI_RT_MULVSI3             265
+#define ABI_RT_MULVDI3             266
+#define ABI_RT_NEGVSI2             267
+#define ABI_RT_NEGVDI2             268
+#define ABI_RT_SUBVSI3             269
+#define ABI_RT_SUBVDI3             270
+#define ABI_RT_CLZSI2              271
+#define ABI_RT_CLZDI2              272
+#define ABI_RT_CLZTI2              273
+#define ABI_RT_CTZSI2              274
+#define ABI_RT_CTZDI2              275
+#define ABI_RT_CTZTI2              276
+#define ABI_RT_FFSDI2              277
+#define ABI_RT_FFSTI2              278
+#define ABI_RT_PARITYSI2           279
+#define ABI_RT_PARITYDI2           280
+#define ABI_RT_PARITYTI2           281
+#define ABI_RT_POPCOUNTSI2         282
+#define ABI_RT_POPCOUNTDI2         283
+#define ABI_RT_POPCOUNTTI2         284
+#define ABI_RT_BSWAPSI2            285
+#define ABI_RT_BSWAPDI2            286
+#define ABI_RT_MULBITINT3          287
+#define ABI_RT_DIVMODBITINT4       288
+
+#endif
@@ -13,6 +13,18 @@ typedef int wchar_t;
 
 #endif
 
+/* x86_64 的 struct stat 里这两个字段是 64 位的 */
+#ifdef __x86_64__
+#if defined(__NEED_blksize_t) && !defined(__DEFINED_blksize_t)
+typedef long blksize_t;
+#define __DEFINED_blksize_t
+#endif
+
+#if defined(__NEED_nlink_t) && !defined(__DEFINED_nlink_t)
+typedef unsigned long nlink_t;
+#define __DEFINED_nlink_t
+#endif
+#else
 #if defined(__NEED_blksize_t) && !defined(__DEFINED_blksize_t)
 typedef int blksize_t;
 #define __DEFINED_blksize_t
@@ -22,6 +34,7 @@ typedef int blksize_t;
 typedef unsigned int nlink_t;
 #define __DEFINED_nlink_t
 #endif
+#endif
 
 #if defined(__NEED_float_t) && !defined(__DEFINED_float_t)
 typedef float float_t;
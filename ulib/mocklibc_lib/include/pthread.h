@@ -83,7 +83,7 @@ extern "C" {
 
 int pthread_create(pthread_t *__restrict, const pthread_attr_t *__restrict, void *(*)(void *),
                    void *__restrict);
-// int pthread_detach(pthread_t);
+int pthread_detach(pthread_t);
 _Noreturn void pthread_exit(void *);
 int pthread_join(pthread_t, void **);
 
@@ -106,63 +106,63 @@ pthread_self(void);
 // int pthread_getschedparam(pthread_t, int *__restrict, struct sched_param *__restrict);
 // int pthread_setschedparam(pthread_t, int, const struct sched_param *);
 // int pthread_setschedprio(pthread_t, int);
-//
-// int pthread_once(pthread_once_t *, void (*)(void));
-//
-// int pthread_mutex_init(pthread_mutex_t *__restrict, const pthread_mutexattr_t *__restrict);
-// int pthread_mutex_lock(pthread_mutex_t *);
-// int pthread_mutex_unlock(pthread_mutex_t *);
-// int pthread_mutex_trylock(pthread_mutex_t *);
-// int pthread_mutex_timedlock(pthread_mutex_t *__restrict, const struct timespec *__restrict);
-// int pthread_mutex_destroy(pthread_mutex_t *);
+
+int pthread_once(pthread_once_t *, void (*)(void));
+
+int pthread_mutex_init(pthread_mutex_t *__restrict, const pthread_mutexattr_t *__restrict);
+int pthread_mutex_lock(pthread_mutex_t *);
+int pthread_mutex_unlock(pthread_mutex_t *);
+int pthread_mutex_trylock(pthread_mutex_t *);
+int pthread_mutex_timedlock(pthread_mutex_t *__restrict, const struct timespec *__restrict);
+int pthread_mutex_destroy(pthread_mutex_t *);
 // int pthread_mutex_consistent(pthread_mutex_t *);
 //
 // int pthread_mutex_getprioceiling(const pthread_mutex_t *__restrict, int *__restrict);
 // int pthread_mutex_setprioceiling(pthread_mutex_t *__restrict, int, int *__restrict);
-//
-// int pthread_cond_init(pthread_cond_t *__restrict, const pthread_condattr_t *__restrict);
-// int pthread_cond_destroy(pthread_cond_t *);
-// int pthread_cond_wait(pthread_cond_t *__restrict, pthread_mutex_t *__restrict);
-// int pthread_cond_timedwait(pthread_cond_t *__restrict, pthread_mutex_t *__restrict,
-//                            const struct timespec *__restrict);
-// int pthread_cond_broadcast(pthread_cond_t *);
-// int pthread_cond_signal(pthread_cond_t *);
-//
-// int pthread_rwlock_init(pthread_rwlock_t *__restrict, const pthread_rwlockattr_t *__restrict);
-// int pthread_rwlock_destroy(pthread_rwlock_t *);
-// int pthread_rwlock_rdlock(pthread_rwlock_t *);
-// int pthread_rwlock_tryrdlock(pthread_rwlock_t *);
-// int pthread_rwlock_timedrdlock(pthread_rwlock_t *__restrict, const struct timespec *__restrict);
-// int pthread_rwlock_wrlock(pthread_rwlock_t *);
-// int pthread_rwlock_trywrlock(pthread_rwlock_t *);
-// int pthread_rwlock_timedwrlock(pthread_rwlock_t *__restrict, const struct timespec *__restrict);
-// int pthread_rwlock_unlock(pthread_rwlock_t *);
-//
+
+int pthread_cond_init(pthread_cond_t *__restrict, const pthread_condattr_t *__restrict);
+int pthread_cond_destroy(pthread_cond_t *);
+int pthread_cond_wait(pthread_cond_t *__restrict, pthread_mutex_t *__restrict);
+int pthread_cond_timedwait(pthread_cond_t *__restrict, pthread_mutex_t *__restrict,
+                           const struct timespec *__restrict);
+int pthread_cond_broadcast(pthread_cond_t *);
+int pthread_cond_signal(pthread_cond_t *);
+
+int pthread_rwlock_init(pthread_rwlock_t *__restrict, const pthread_rwlockattr_t *__restrict);
+int pthread_rwlock_destroy(pthread_rwlock_t *);
+int pthread_rwlock_rdlock(pthread_rwlock_t *);
+int pthread_rwlock_tryrdlock(pthread_rwlock_t *);
+int pthread_rwlock_timedrdlock(pthread_rwlock_t *__restrict, const struct timespec *__restrict);
+int pthread_rwlock_wrlock(pthread_rwlock_t *);
+int pthread_rwlock_trywrlock(pthread_rwlock_t *);
+int pthread_rwlock_timedwrlock(pthread_rwlock_t *__restrict, const struct timespec *__restrict);
+int pthread_rwlock_unlock(pthread_rwlock_t *);
+
 // int pthread_spin_init(pthread_spinlock_t *, int);
 // int pthread_spin_destroy(pthread_spinlock_t *);
 // int pthread_spin_lock(pthread_spinlock_t *);
 // int pthread_spin_trylock(pthread_spinlock_t *);
 // int pthread_spin_unlock(pthread_spinlock_t *);
-//
-// int pthread_barrier_init(pthread_barrier_t *__restrict, const pthread_barrierattr_t *__restrict,
-//                          unsigned);
-// int pthread_barrier_destroy(pthread_barrier_t *);
-// int pthread_barrier_wait(pthread_barrier_t *);
-//
-// int pthread_key_create(pthread_key_t *, void (*)(void *));
-// int pthread_key_delete(pthread_key_t);
-// void *pthread_getspecific(pthread_key_t);
-// int pthread_setspecific(pthread_key_t, const void *);
-//
-// int pthread_attr_init(pthread_attr_t *);
-// int pthread_attr_destroy(pthread_attr_t *);
-//
+
+int pthread_barrier_init(pthread_barrier_t *__restrict, const pthread_barrierattr_t *__restrict,
+                         unsigned);
+int pthread_barrier_destroy(pthread_barrier_t *);
+int pthread_barrier_wait(pthread_barrier_t *);
+
+int pthread_key_create(pthread_key_t *, void (*)(void *));
+int pthread_key_delete(pthread_key_t);
+void *pthread_getspecific(pthread_key_t);
+int pthread_setspecific(pthread_key_t, const void *);
+
+int pthread_attr_init(pthread_attr_t *);
+int pthread_attr_destroy(pthread_attr_t *);
+
 // int pthread_attr_getguardsize(const pthread_attr_t *__restrict, size_t *__restrict);
 // int pthread_attr_setguardsize(pthread_attr_t *, size_t);
-// int pthread_attr_getstacksize(const pthread_attr_t *__restrict, size_t *__restrict);
-// int pthread_attr_setstacksize(pthread_attr_t *, size_t);
-// int pthread_attr_getdetachstate(const pthread_attr_t *, int *);
-// int pthread_attr_setdetachstate(pthread_attr_t *, int);
+int pthread_attr_getstacksize(const pthread_attr_t *__restrict, size_t *__restrict);
+int pthread_attr_setstacksize(pthread_attr_t *, size_t);
+int pthread_attr_getdetachstate(const pthread_attr_t *, int *);
+int pthread_attr_setdetachstate(pthread_attr_t *, int);
 // int pthread_attr_getstack(const pthread_attr_t *__restrict, void **__restrict, size_t
 // *__restrict); int pthread_attr_setstack(pthread_attr_t *, void *, size_t); int
 // pthread_attr_getscope(const pthread_attr_t *__restrict, int *__restrict); int